@@ -5,11 +5,25 @@
 //! Detects system accessibility preferences including:
 //! - Reduced motion / animation preferences
 //! - High contrast mode (for Story 4.5)
+//!
+//! ## Animation Timings
+//! [`EffectiveAnimationTimings::compute`] is the one place the theme's
+//! animation durations meet these settings: reduced motion (user override,
+//! else the desktop's) turns animations off, otherwise durations are
+//! multiplied by `accessibility.animation_scale`. The daemon sends the
+//! result in `MenuReady` and from `GetAnimationTimings`, so overlays never
+//! combine the two sources themselves.
 
 use std::env;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use crate::config::AccessibilityConfig;
+use crate::theme::AnimationSettings;
 
 /// Accessibility settings for the application
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct AccessibilitySettings {
     /// User override for reduced motion (None = follow system)
     pub reduced_motion_override: Option<bool>,
@@ -22,9 +36,37 @@ pub struct AccessibilitySettings {
 
     /// Detected system preference for high contrast
     system_prefers_high_contrast: bool,
+
+    /// Multiplier for animation durations (1.0 = as the theme declares)
+    pub animation_scale: f32,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            reduced_motion_override: None,
+            system_prefers_reduced_motion: false,
+            high_contrast_override: None,
+            system_prefers_high_contrast: false,
+            animation_scale: 1.0,
+        }
+    }
 }
 
+/// System preferences, detected once
+static SYSTEM: OnceLock<AccessibilitySettings> = OnceLock::new();
+
 impl AccessibilitySettings {
+    /// System preferences with the user's configured overrides applied
+    pub fn from_config(config: &AccessibilityConfig) -> Self {
+        Self {
+            reduced_motion_override: config.reduced_motion,
+            high_contrast_override: config.high_contrast,
+            animation_scale: config.animation_scale,
+            ..SYSTEM.get_or_init(Self::new).clone()
+        }
+    }
+
     /// Create new accessibility settings with system detection
     pub fn new() -> Self {
         let mut settings = Self::default();
@@ -130,7 +172,7 @@ impl AccessibilitySettings {
 }
 
 /// Animation timings with reduced motion support (Task 3.2)
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub struct EffectiveAnimationTimings {
     /// Menu appear duration (ms)
     pub appear_ms: u16,
@@ -147,6 +189,23 @@ pub struct EffectiveAnimationTimings {
 }
 
 impl EffectiveAnimationTimings {
+    /// Merge a theme's animation settings with accessibility settings
+    pub fn compute(animation: &AnimationSettings, settings: &AccessibilitySettings) -> Self {
+        if settings.should_reduce_motion() {
+            return Self::reduced_motion();
+        }
+        let scale = if settings.animation_scale.is_finite() { settings.animation_scale.max(0.0) } else { 1.0 };
+        let scaled = |ms: u16| (ms as f32 * scale).round().min(u16::MAX as f32) as u16;
+        Self {
+            appear_ms: scaled(animation.appear_ms),
+            dismiss_ms: scaled(animation.dismiss_ms),
+            highlight_in_ms: scaled(animation.highlight_in_ms),
+            highlight_out_ms: scaled(animation.highlight_out_ms),
+            icon_scale_enabled: true,
+            idle_effects_enabled: animation.idle_effect != "none" || animation.enable_particles,
+        }
+    }
+
     /// Create timings for reduced motion mode (all 0ms, effects disabled)
     pub fn reduced_motion() -> Self {
         Self {
@@ -260,6 +319,24 @@ mod tests {
         assert!(settings.should_use_high_contrast());
     }
 
+    #[test]
+    fn test_compute_merges_theme_and_settings() {
+        let mut animation = crate::theme::Theme::catppuccin_mocha().animation;
+        animation.appear_ms = 100;
+        let mut settings = AccessibilitySettings {
+            animation_scale: 1.5,
+            ..AccessibilitySettings::default()
+        };
+
+        let timings = EffectiveAnimationTimings::compute(&animation, &settings);
+        assert_eq!(timings.appear_ms, 150);
+        assert_eq!(timings.dismiss_ms, 75);
+        assert!(!timings.idle_effects_enabled);
+
+        settings.set_system_reduced_motion(true);
+        assert_eq!(EffectiveAnimationTimings::compute(&animation, &settings), EffectiveAnimationTimings::reduced_motion());
+    }
+
     #[test]
     fn test_default_settings() {
         let settings = AccessibilitySettings::default();
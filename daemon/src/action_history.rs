@@ -0,0 +1,221 @@
+//! Recent action executions for the widget's activity feed
+//!
+//! The last [`MAX_ENTRIES`] executed actions are kept with their time and
+//! result and persisted as JSON under
+//! `$XDG_STATE_HOME/juhradial/action-history.json`, so the feed survives a
+//! restart. `GetRecentActions` returns them newest first and every new
+//! entry emits `ActionHistoryChanged`.
+//!
+//! Entries are recorded by [`ActionExecutor::execute`], i.e. only for
+//! actions the daemon ran itself and whose result it knows. Until [`init`]
+//! is called (i.e. outside the daemon) the history is kept in memory only.
+//!
+//! [`ActionExecutor::execute`]: crate::actions::ActionExecutor::execute
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::dbus::{DBUS_INTERFACE, DBUS_PATH};
+
+/// Number of executions kept
+pub const MAX_ENTRIES: usize = 20;
+
+/// State subdirectory
+const STATE_DIR: &str = "juhradial";
+
+/// History file name
+const HISTORY_FILE: &str = "action-history.json";
+
+/// One executed action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Slice label, or the action ID / kind when it has none
+    pub name: String,
+    /// Action type (e.g. "shortcut"), empty if unknown
+    #[serde(default)]
+    pub kind: String,
+    /// Execution time (Unix seconds)
+    pub timestamp: u64,
+    /// Whether the action succeeded
+    pub success: bool,
+    /// Error message (empty on success)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// Bounded, persisted list of executions (oldest first)
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ActionHistory {
+    #[serde(default)]
+    entries: VecDeque<HistoryEntry>,
+
+    /// Backing file (not serialized, None = in-memory only)
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl ActionHistory {
+    /// Get the default history file path
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::state_dir().map(|p| p.join(STATE_DIR).join(HISTORY_FILE))
+    }
+
+    /// Load the history from a file (missing or corrupt files start empty)
+    pub fn load(path: &Path) -> Self {
+        let mut history: Self = fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| match serde_json::from_str(&contents) {
+                Ok(history) => Some(history),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), "Ignoring corrupt action history: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        history.truncate();
+        history.path = Some(path.to_path_buf());
+        history
+    }
+
+    /// Load the history from the default location
+    pub fn load_default() -> Self {
+        match Self::default_path() {
+            Some(path) => Self::load(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Append an entry, dropping the oldest beyond [`MAX_ENTRIES`], and persist
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.entries.push_back(entry);
+        self.truncate();
+
+        if let Err(e) = self.save() {
+            tracing::warn!("Failed to save action history: {}", e);
+        }
+    }
+
+    /// Entries, newest first
+    pub fn recent(&self) -> Vec<HistoryEntry> {
+        self.entries.iter().rev().cloned().collect()
+    }
+
+    fn truncate(&mut self) {
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Write the history to the backing file (see [`crate::state_file`])
+    fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        crate::state_file::write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+}
+
+/// The daemon's history
+static HISTORY: Mutex<Option<ActionHistory>> = Mutex::new(None);
+
+/// Wakes the signal task when an entry is added
+fn changed() -> &'static Notify {
+    static CHANGED: OnceLock<Notify> = OnceLock::new();
+    CHANGED.get_or_init(Notify::new)
+}
+
+/// Use (and persist to) `history` from now on
+pub fn init(history: ActionHistory) {
+    if let Ok(mut current) = HISTORY.lock() {
+        *current = Some(history);
+    }
+}
+
+/// Record an execution
+pub fn record(name: &str, kind: &str, result: Result<(), String>) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let entry = HistoryEntry {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        timestamp,
+        success: result.is_ok(),
+        error: result.err().unwrap_or_default(),
+    };
+    if let Ok(mut history) = HISTORY.lock() {
+        history.get_or_insert_with(ActionHistory::default).push(entry);
+    }
+    changed().notify_one();
+}
+
+/// Recorded executions, newest first
+pub fn recent() -> Vec<HistoryEntry> {
+    HISTORY
+        .lock()
+        .ok()
+        .and_then(|history| history.as_ref().map(ActionHistory::recent))
+        .unwrap_or_default()
+}
+
+/// Emit `ActionHistoryChanged` whenever an execution is recorded
+pub async fn start_action_history_signals(connection: zbus::Connection) {
+    loop {
+        changed().notified().await;
+        if let Err(e) = connection
+            .emit_signal(None::<&str>, DBUS_PATH, DBUS_INTERFACE, "ActionHistoryChanged", &())
+            .await
+        {
+            tracing::debug!("Failed to emit ActionHistoryChanged: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, success: bool) -> HistoryEntry {
+        HistoryEntry {
+            name: name.to_string(),
+            kind: "shortcut".to_string(),
+            timestamp: 100,
+            success,
+            error: if success { String::new() } else { "failed".to_string() },
+        }
+    }
+
+    #[test]
+    fn test_history_is_bounded_and_newest_first() {
+        let mut history = ActionHistory::default();
+        for i in 0..MAX_ENTRIES + 5 {
+            history.push(entry(&i.to_string(), true));
+        }
+        let recent = history.recent();
+        assert_eq!(recent.len(), MAX_ENTRIES);
+        assert_eq!(recent[0].name, (MAX_ENTRIES + 4).to_string());
+        assert_eq!(recent[MAX_ENTRIES - 1].name, "5");
+    }
+
+    #[test]
+    fn test_persistence_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join(HISTORY_FILE);
+
+        let mut history = ActionHistory::load(&path);
+        history.push(entry("Copy", true));
+        history.push(entry("Screenshot", false));
+
+        let reloaded = ActionHistory::load(&path).recent();
+        assert_eq!(reloaded, vec![entry("Screenshot", false), entry("Copy", true)]);
+    }
+}
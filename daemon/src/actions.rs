@@ -4,16 +4,139 @@
 //!
 //! ## Key Synthesis (Story 2.6)
 //! Uses xdotool for X11 and ydotool for Wayland to synthesize key events.
+//! In portal mode keys are injected through the RemoteDesktop portal instead.
 //!
 //! ## Shell Commands (Story 2.8)
 //! Executes commands via sh -c for shell interpretation, non-blocking.
+//! Each command runs in its own process group, so a hung script can be
+//! killed when its `timeout_secs` expires or, if it has one, by
+//! `CancelRunningAction`, see
+//! [`crate::command_runner`].
+//!
+//! ## Feedback Notifications
+//! An action with a `notify` text shows it as a transient desktop
+//! notification once the action succeeded. Consecutive action notifications
+//! replace each other.
+//!
+//! ## Long-Hover Alternates
+//! A slice may carry an `alternate` action. Hovering it for longer than
+//! `long_hover_ms` arms the alternate (timed by the daemon, see
+//! [`crate::long_hover`]); releasing then runs the alternate instead.
+//!
+//! ## Undo
+//! An action may declare an `inverse` (e.g. switch back to the previous
+//! workspace). Once the action succeeded its inverse goes on a small
+//! history stack; `UndoLastAction` or an `undo` slice
+//! (`{"type": "undo"}`) pops and runs the most recent one.
+//!
+//! ## Text Entry
+//! A `text_entry` slice asks for a query and runs its templated action
+//! with it, see [`crate::text_entry`].
+//!
+//! ## Insert Text
+//! An `insert_text` action copies its text to the clipboard and pastes it;
+//! the emoji submenu is made of these, see [`crate::emoji_picker`].
+//!
+//! ## Color Picker
+//! A `pick_color` action asks the Screenshot portal for a color on screen,
+//! copies it as `#rrggbb` and confirms with the Completed haptic.
+//!
+//! ## OCR
+//! An `ocr` action captures a screen region and copies the text the
+//! configured OCR command recognizes in it, see [`crate::ocr`].
+//!
+//! ## Timer
+//! A `start_timer` action starts a countdown that ends with a haptic alert
+//! and a notification, see [`crate::timer`].
+//!
+//! ## Zoom
+//! A `zoom` action (`{"type": "zoom", "value": "in"}`, `"out"` or
+//! `"toggle"`) drives the compositor's magnifier, see
+//! [`crate::compositor::CompositorBackend::zoom`].
+//!
+//! ## Night Light
+//! `{"type": "night_light", "value": "toggle"}` (`"warmer"`, `"cooler"`)
+//! controls KDE night light or gammastep/wlsunset, see [`crate::night_light`].
+//!
+//! ## Power Profiles
+//! `{"type": "set_power_profile", "value": "power-saver"}` (`"balanced"`,
+//! `"performance"`) switches power-profiles-daemon; `GetMenuLayout` marks
+//! these slices `"active"` when they match the current profile (see
+//! [`crate::slice_state`]).
+//!
+//! ## Audio Output
+//! `{"type": "cycle_audio_output"}` switches the default PipeWire sink to
+//! the next one and announces it, see [`crate::audio_output`].
+//!
+//! ## Bluetooth
+//! `{"type": "bluetooth", "value": {"device": "headphones", "op": "toggle"}}`
+//! connects or disconnects a paired device named in `bluetooth.devices`
+//! (or given by MAC), see [`crate::bluetooth`].
+//!
+//! ## Network
+//! `{"type": "network", "value": {"connection": "Work VPN"}}` toggles a
+//! NetworkManager connection and `{"type": "wifi", "value": "toggle"}` the
+//! Wi-Fi radio, see [`crate::network`].
+//!
+//! ## OBS Studio
+//! `{"type": "obs", "value": "toggle_record"}` or
+//! `{"type": "obs", "value": {"switch_scene": "BRB"}}` drives OBS over
+//! obs-websocket (`obs.host`, `obs.port`, `obs.password`) when built with the
+//! `obs` feature, see [`crate::obs`].
+//!
+//! ## HTTP Requests
+//! `{"type": "http_request", "value": {"method": "POST", "url": "...",
+//! "headers": {...}, "body": "..."}}` calls a webhook or REST API, with
+//! `{secret:name}` taken from `secrets.json`; see [`crate::http_request`].
+//! `timeout_secs` on the action bounds the request.
+//!
+//! ## Activities
+//! `{"type": "switch_activity", "value": "next"}` (`"previous"`,
+//! `{"activity": "Work"}`) switches KDE activity; profiles can also be
+//! bound to an activity, see [`crate::activities`].
+//!
+//! ## KRunner
+//! `{"type": "krunner", "value": {"query": "="}}` opens KRunner pre-filled
+//! (here: the calculator); `"runner": "baloosearch"` limits it to one
+//! runner, e.g. for a "search files" slice.
+//!
+//! ## GNOME Shell
+//! `{"type": "gnome_shell", "value": "overview"}` (`"app_grid"`) toggles
+//! the GNOME overview or app grid, falling back to the Super / Super+A
+//! keybindings where the shell refuses D-Bus callers, see
+//! [`crate::gnome_shell`].
+//!
+//! ## Menu Pages
+//! A profile may have more than one page of 8 slices. A `page` slice
+//! (`{"type": "page", "value": 1}`) switches pages while the menu is open;
+//! the overlay asks the daemon with `ChangePage`, see [`crate::menu_pages`].
+//!
+//! ## Dynamic Slices
+//! A `dynamic` slice names a [`SliceProvider`] that computes the concrete
+//! action when the menu opens (e.g. "switch to previous window").
+//! Providers are registered in [`SLICE_PROVIDERS`].
 
 use serde::{Deserialize, Serialize};
 use std::process::Command;
-use std::time::Instant;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::activities::ActivityTarget;
+use crate::bluetooth::BluetoothAction;
+use crate::compositor::ZoomStep;
+use crate::http_request::HttpRequest;
+use crate::config::SharedConfig;
+use crate::gnome_shell::GnomeShellView;
+use crate::network::{NetworkAction, NetworkOp};
+use crate::night_light::NightLightStep;
+use crate::obs::ObsCommand;
+use crate::power_profiles::PowerProfile;
+use crate::hidpp::SharedHapticManager;
+use crate::window_tracker::OpenWindow;
 
 /// Action types supported by radial menu
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", content = "value")]
 pub enum ActionType {
     /// Keyboard shortcut (e.g., "Ctrl+C")
@@ -32,13 +155,136 @@ pub enum ActionType {
     #[serde(rename = "kwin")]
     KWin(String),
 
+    /// Activate an open window (window switcher ring; KWin runner match ID)
+    #[serde(rename = "focus_window")]
+    FocusWindow(String),
+
+    /// Provider-computed action, resolved at menu-open (provider ID)
+    #[serde(rename = "dynamic")]
+    Dynamic(String),
+
+    /// Switch the open menu's page by a relative step (1 = next, -1 = previous)
+    #[serde(rename = "page")]
+    Page(i32),
+
+    /// Undo the most recent action that declared an inverse
+    #[serde(rename = "undo")]
+    Undo,
+
+    /// Ask for text, then run a templated action with it
+    #[serde(rename = "text_entry")]
+    TextEntry(TextEntry),
+
+    /// Copy text to the clipboard and optionally paste it (emoji picker)
+    #[serde(rename = "insert_text")]
+    InsertText(InsertText),
+
+    /// Pick a color on screen (Screenshot portal) and copy it as hex
+    #[serde(rename = "pick_color")]
+    PickColor,
+
+    /// Capture a screen region and copy its text (`ocr.command`)
+    #[serde(rename = "ocr")]
+    Ocr,
+
+    /// Start the countdown timer (seconds)
+    #[serde(rename = "start_timer")]
+    StartTimer(u64),
+
+    /// Step or toggle the desktop magnifier ("in", "out", "toggle")
+    #[serde(rename = "zoom")]
+    Zoom(ZoomStep),
+
+    /// Toggle night light or step its temperature ("toggle", "warmer", "cooler")
+    #[serde(rename = "night_light")]
+    NightLight(NightLightStep),
+
+    /// Switch the power-profiles-daemon profile
+    #[serde(rename = "set_power_profile")]
+    SetPowerProfile(PowerProfile),
+
+    /// Make the next PipeWire audio sink the default
+    #[serde(rename = "cycle_audio_output")]
+    CycleAudioOutput,
+
+    /// Connect, disconnect or toggle a paired Bluetooth device
+    #[serde(rename = "bluetooth")]
+    Bluetooth(BluetoothAction),
+
+    /// Bring a NetworkManager connection (e.g. a VPN) up or down
+    #[serde(rename = "network")]
+    Network(NetworkAction),
+
+    /// Switch the Wi-Fi radio ("up", "down", "toggle")
+    #[serde(rename = "wifi")]
+    Wifi(NetworkOp),
+
+    /// Control OBS Studio over obs-websocket
+    #[serde(rename = "obs")]
+    Obs(ObsCommand),
+
+    /// Send an HTTP request (webhooks, Home Assistant REST API)
+    #[serde(rename = "http_request")]
+    HttpRequest(HttpRequest),
+
+    /// Switch KDE activity ("next", "previous" or `{"activity": name}`)
+    #[serde(rename = "switch_activity")]
+    SwitchActivity(ActivityTarget),
+
+    /// Open KRunner with a query (e.g. a runner prefix)
+    #[serde(rename = "krunner")]
+    KRunner(KRunnerQuery),
+
+    /// Toggle the GNOME Shell overview or app grid
+    #[serde(rename = "gnome_shell")]
+    GnomeShell(GnomeShellView),
+
+    /// Run a Hyprland dispatcher over its IPC socket (e.g. "workspace e+1")
+    #[serde(rename = "hyprland_dispatch")]
+    HyprlandDispatch(String),
+
     /// No action (empty slice)
     #[serde(rename = "none")]
     None,
 }
 
+impl ActionType {
+    /// Short type name (matches the serialized `type` tag)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ActionType::Shortcut(_) => "shortcut",
+            ActionType::Command(_) => "command",
+            ActionType::DBus(_) => "dbus",
+            ActionType::KWin(_) => "kwin",
+            ActionType::FocusWindow(_) => "focus_window",
+            ActionType::Dynamic(_) => "dynamic",
+            ActionType::Page(_) => "page",
+            ActionType::Undo => "undo",
+            ActionType::TextEntry(_) => "text_entry",
+            ActionType::InsertText(_) => "insert_text",
+            ActionType::PickColor => "pick_color",
+            ActionType::Ocr => "ocr",
+            ActionType::StartTimer(_) => "start_timer",
+            ActionType::Zoom(_) => "zoom",
+            ActionType::NightLight(_) => "night_light",
+            ActionType::SetPowerProfile(_) => "set_power_profile",
+            ActionType::CycleAudioOutput => "cycle_audio_output",
+            ActionType::Bluetooth(_) => "bluetooth",
+            ActionType::Network(_) => "network",
+            ActionType::Wifi(_) => "wifi",
+            ActionType::Obs(_) => "obs",
+            ActionType::HttpRequest(_) => "http_request",
+            ActionType::SwitchActivity(_) => "switch_activity",
+            ActionType::KRunner(_) => "krunner",
+            ActionType::GnomeShell(_) => "gnome_shell",
+            ActionType::HyprlandDispatch(_) => "hyprland_dispatch",
+            ActionType::None => "none",
+        }
+    }
+}
+
 /// D-Bus method call specification
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DBusCall {
     /// D-Bus service name
     pub service: String,
@@ -53,8 +299,41 @@ pub struct DBusCall {
     pub args: Vec<serde_json::Value>,
 }
 
+/// Text entry slice specification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextEntry {
+    /// Prompt shown above the input field
+    pub prompt: String,
+    /// Action run with `{query}` / `{query_url}` filled in
+    pub action: Box<Action>,
+}
+
+/// Text to put into the focused window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InsertText {
+    /// Text (e.g. an emoji)
+    pub text: String,
+    /// Paste with Ctrl+V after copying
+    #[serde(default = "default_paste")]
+    pub paste: bool,
+}
+
+fn default_paste() -> bool { true }
+
+/// What KRunner opens with
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KRunnerQuery {
+    /// Text pre-filled in the search field, e.g. `"="` (calculator) or
+    /// `"gg:"` (web search); empty opens KRunner as usual
+    #[serde(default)]
+    pub query: String,
+    /// Restrict the search to one runner (e.g. `"baloosearch"` for files)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runner: Option<String>,
+}
+
 /// A complete action with icon and label
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Action {
     /// Action type and parameters
     #[serde(flatten)]
@@ -67,8 +346,43 @@ pub struct Action {
     /// Icon (emoji, path, or system icon name)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub icon: Option<String>,
+
+    /// Transient notification shown after the action succeeded (e.g. "Screenshot saved")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<String>,
+
+    /// Alternate action run when the slice was hovered past the long-hover
+    /// threshold before release
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternate: Option<Box<Action>>,
+
+    /// Kill a `command` action still running after this many seconds
+    /// (for `http_request`, abort the request)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+
+    /// Action that reverses this one, run by `UndoLastAction`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inverse: Option<Box<Action>>,
 }
 
+/// Id of the last action feedback notification (0 = none yet)
+static LAST_FEEDBACK_NOTIFICATION: AtomicU32 = AtomicU32::new(0);
+
+/// KRunner's D-Bus service, object and interface
+const KRUNNER_NAME: &str = "org.kde.krunner";
+const KRUNNER_PATH: &str = "/App";
+const KRUNNER_INTERFACE: &str = "org.kde.krunner.App";
+
+/// How long the clipboard tool may take to take over the selection
+const CLIPBOARD_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Number of inverses kept for undo
+pub const UNDO_HISTORY_LEN: usize = 10;
+
+/// Inverses of recently executed actions, most recent last
+static UNDO_HISTORY: Mutex<Vec<Action>> = Mutex::new(Vec::new());
+
 /// Action executor
 pub struct ActionExecutor;
 
@@ -77,12 +391,63 @@ impl ActionExecutor {
     ///
     /// Returns within 10ms for keyboard shortcuts (NFR-001)
     pub async fn execute(action: &Action) -> Result<(), ActionError> {
+        let start = std::time::Instant::now();
+        let result = Self::dispatch(action).await;
+        crate::metrics::record_action(action.action_type.kind(), result.is_ok(), start.elapsed());
+        let kind = action.action_type.kind();
+        let name = action.label.as_deref().unwrap_or(kind);
+        crate::action_history::record(name, kind, result.as_ref().map(|_| ()).map_err(|e| e.to_string()));
+        crate::usage_stats::record_execution(name);
+        if let (Ok(()), Some(text)) = (&result, &action.notify) {
+            Self::notify_feedback(text).await;
+        }
+        if let (Ok(()), Some(inverse)) = (&result, &action.inverse) {
+            push_undo(inverse);
+        }
+        result
+    }
+
+    /// Run the inverse of the most recent reversible action
+    ///
+    /// Returns false if there is nothing to undo. The inverse is not
+    /// recorded itself, so repeated undos walk back through the history.
+    pub async fn undo_last() -> Result<bool, ActionError> {
+        let Some(inverse) = pop_undo() else {
+            tracing::debug!("Nothing to undo");
+            return Ok(false);
+        };
+        tracing::info!(label = ?inverse.label, kind = inverse.action_type.kind(), "Undoing last action");
+        let start = std::time::Instant::now();
+        let result = Box::pin(Self::dispatch(&inverse)).await;
+        crate::metrics::record_action(inverse.action_type.kind(), result.is_ok(), start.elapsed());
+        result.map(|()| true)
+    }
+
+    /// Show an action's feedback notification, replacing the previous one
+    async fn notify_feedback(text: &str) {
+        let connection = match zbus::Connection::session().await {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::debug!("Session bus unavailable for action notification: {}", e);
+                return;
+            }
+        };
+        let notification = crate::notifications::Notification::transient(text, "");
+        let replaces_id = LAST_FEEDBACK_NOTIFICATION.load(Ordering::Relaxed);
+        match crate::notifications::notify(&connection, &notification, replaces_id).await {
+            Ok(id) => LAST_FEEDBACK_NOTIFICATION.store(id, Ordering::Relaxed),
+            Err(e) => tracing::debug!("Action notification failed: {}", e),
+        }
+    }
+
+    /// Run the action for its type
+    async fn dispatch(action: &Action) -> Result<(), ActionError> {
         match &action.action_type {
             ActionType::Shortcut(keys) => {
                 Self::execute_shortcut(keys).await
             }
             ActionType::Command(cmd) => {
-                Self::execute_command(cmd).await
+                Self::execute_command(cmd, action.timeout_secs).await
             }
             ActionType::DBus(call) => {
                 Self::execute_dbus(call).await
@@ -90,7 +455,88 @@ impl ActionExecutor {
             ActionType::KWin(script) => {
                 Self::execute_kwin(script).await
             }
-            ActionType::None => Ok(()),
+            ActionType::FocusWindow(id) => {
+                Self::execute_focus_window(id).await
+            }
+            ActionType::Dynamic(id) => {
+                // Normally resolved at menu-open; resolve without context as a fallback
+                let resolved = find_provider(id)
+                    .and_then(|p| p.resolve(&ProviderContext::default()))
+                    .ok_or(ActionError::InvalidAction)?;
+                if matches!(resolved.action_type, ActionType::Dynamic(_)) {
+                    return Err(ActionError::InvalidAction);
+                }
+                Box::pin(Self::dispatch(&resolved)).await
+            }
+            ActionType::Undo => Self::undo_last().await.map(|_| ()),
+            ActionType::InsertText(insert) => {
+                Self::execute_insert_text(insert).await
+            }
+            ActionType::PickColor => {
+                Self::execute_pick_color().await
+            }
+            ActionType::Ocr => {
+                Self::execute_ocr().await
+            }
+            ActionType::Zoom(step) => {
+                Self::execute_zoom(*step).await
+            }
+            ActionType::NightLight(step) => crate::night_light::apply(*step)
+                .await
+                .map_err(|e| ActionError::ExecutionFailed(e.to_string())),
+            ActionType::SetPowerProfile(profile) => crate::power_profiles::set(*profile)
+                .await
+                .map_err(|e| ActionError::ExecutionFailed(format!("Could not set power profile: {}", e))),
+            ActionType::CycleAudioOutput => crate::audio_output::cycle()
+                .await
+                .map(|_| ())
+                .map_err(|e| ActionError::ExecutionFailed(e.to_string())),
+            ActionType::Bluetooth(bluetooth) => {
+                Self::execute_bluetooth(bluetooth).await
+            }
+            ActionType::Network(network) => crate::network::apply(network)
+                .await
+                .map(|_| ())
+                .map_err(|e| ActionError::ExecutionFailed(e.to_string())),
+            ActionType::Wifi(op) => crate::network::apply_wifi(*op)
+                .await
+                .map(|_| ())
+                .map_err(|e| ActionError::ExecutionFailed(e.to_string())),
+            ActionType::Obs(command) => {
+                let config = context()
+                    .and_then(|c| c.config.read().ok().map(|config| config.obs.clone()))
+                    .unwrap_or_default();
+                crate::obs::send(&config, command)
+                    .await
+                    .map_err(|e| ActionError::ExecutionFailed(e.to_string()))
+            }
+            ActionType::HttpRequest(request) => {
+                Self::execute_http_request(request, action.timeout_secs).await
+            }
+            ActionType::SwitchActivity(target) => crate::activities::switch(target)
+                .await
+                .map(|_| ())
+                .map_err(|e| ActionError::ExecutionFailed(e.to_string())),
+            ActionType::KRunner(query) => {
+                Self::execute_krunner(query).await
+            }
+            ActionType::GnomeShell(view) => {
+                Self::execute_gnome_shell(*view).await
+            }
+            ActionType::HyprlandDispatch(command) => crate::hyprland::dispatch(command)
+                .await
+                .map_err(|e| ActionError::ExecutionFailed(e.to_string())),
+            ActionType::StartTimer(secs) => {
+                crate::timer::start(Duration::from_secs(*secs), action.label.as_deref());
+                Ok(())
+            }
+            // The action runs once the overlay submits the text
+            ActionType::TextEntry(entry) => {
+                crate::text_entry::begin(entry);
+                Ok(())
+            }
+            // Handled by the open menu (ChangePage); nothing to run outside it
+            ActionType::Page(_) | ActionType::None => Ok(()),
         }
     }
 
@@ -105,6 +551,13 @@ impl ActionExecutor {
 
         tracing::info!(keys, "Executing keyboard shortcut");
 
+        // Portal mode: no xdotool/ydotool inside the sandbox
+        if let Some(session) = crate::portal::remote_desktop() {
+            return session.send_shortcut(keys).await.map_err(|e| {
+                ActionError::ExecutionFailed(format!("Portal key injection failed: {}", e))
+            });
+        }
+
         // Convert our format to xdotool format
         // e.g., "ctrl+c" -> "ctrl+c", "ctrl+shift+z" -> "ctrl+shift+z"
         let xdotool_keys = keys.to_lowercase();
@@ -169,20 +622,19 @@ impl ActionExecutor {
     /// Non-blocking: spawns subprocess and returns immediately.
     ///
     /// AC1: Execution begins within 10ms
-    async fn execute_command(cmd: &str) -> Result<(), ActionError> {
+    async fn execute_command(cmd: &str, timeout_secs: Option<u64>) -> Result<(), ActionError> {
         let start = Instant::now();
 
         tracing::info!(cmd, "Executing shell command");
 
         // Use sh -c for shell interpretation (handles pipes, redirects, etc.)
-        let result = Command::new("sh")
-            .args(["-c", cmd])
-            .spawn();
+        let timeout = timeout_secs.map(Duration::from_secs);
+        let result = crate::command_runner::spawn(cmd, timeout);
 
         match result {
-            Ok(_child) => {
+            Ok(pgid) => {
                 // Don't wait for command to complete (AC2: non-blocking)
-                tracing::debug!("Shell command spawned successfully");
+                tracing::debug!(pgid, "Shell command spawned successfully");
             }
             Err(e) => {
                 tracing::error!(cmd, error = %e, "Failed to execute shell command");
@@ -210,6 +662,122 @@ impl ActionExecutor {
         Ok(())
     }
 
+    /// Copy text to the clipboard, then paste it
+    async fn execute_insert_text(insert: &InsertText) -> Result<(), ActionError> {
+        copy_to_clipboard(&insert.text).await?;
+        if insert.paste {
+            Self::execute_shortcut("ctrl+v").await?;
+        }
+        Ok(())
+    }
+
+    /// Pick a color through the Screenshot portal and copy its hex code
+    ///
+    /// Confirmed with the Completed haptic; cancelling the picker is not an error.
+    async fn execute_pick_color() -> Result<(), ActionError> {
+        let connection = zbus::Connection::session().await.map_err(|e| {
+            ActionError::ExecutionFailed(format!("Session bus unavailable: {}", e))
+        })?;
+        let color = match crate::portal::pick_color(&connection).await {
+            Ok(color) => color,
+            Err(crate::portal::PortalError::Cancelled) => {
+                tracing::debug!("Color picker cancelled");
+                return Ok(());
+            }
+            Err(e) => return Err(ActionError::ExecutionFailed(format!("Color picker failed: {}", e))),
+        };
+        let hex = crate::portal::color_to_hex(color);
+        tracing::info!(color = %hex, "Color picked");
+        copy_to_clipboard(&hex).await?;
+        haptic_cue(crate::hidpp::Mx4HapticPattern::Completed);
+        Ok(())
+    }
+
+    /// Recognize the text in a screen region and copy it
+    ///
+    /// Confirmed with the Completed haptic, failures get the AngryAlert one;
+    /// cancelling the capture is not an error.
+    async fn execute_ocr() -> Result<(), ActionError> {
+        let result = Self::ocr_to_clipboard().await;
+        match &result {
+            Ok(true) => haptic_cue(crate::hidpp::Mx4HapticPattern::Completed),
+            Ok(false) => {}
+            Err(_) => haptic_cue(crate::hidpp::Mx4HapticPattern::AngryAlert),
+        }
+        result.map(|_| ())
+    }
+
+    /// Capture, recognize and copy; false if the capture was cancelled
+    async fn ocr_to_clipboard() -> Result<bool, ActionError> {
+        let connection = zbus::Connection::session().await.map_err(|e| {
+            ActionError::ExecutionFailed(format!("Session bus unavailable: {}", e))
+        })?;
+        let image = match crate::portal::screenshot(&connection).await {
+            Ok(image) => image,
+            Err(crate::portal::PortalError::Cancelled) => {
+                tracing::debug!("OCR capture cancelled");
+                return Ok(false);
+            }
+            Err(e) => return Err(ActionError::ExecutionFailed(format!("Screen capture failed: {}", e))),
+        };
+        let ocr_config = context()
+            .and_then(|c| c.config.read().ok().map(|config| config.ocr.clone()))
+            .unwrap_or_default();
+        let text = crate::ocr::recognize(&ocr_config, &image)
+            .await
+            .map_err(|e| ActionError::ExecutionFailed(e.to_string()))?;
+        tracing::info!(chars = text.chars().count(), "Text recognized");
+        copy_to_clipboard(&text).await?;
+        Ok(true)
+    }
+
+    /// Send an HTTP request; the cue tells success from failure
+    async fn execute_http_request(request: &HttpRequest, timeout_secs: Option<u64>) -> Result<(), ActionError> {
+        match crate::http_request::send(request, timeout_secs).await {
+            Ok(status) => {
+                tracing::debug!(status, "HTTP request succeeded");
+                haptic_cue(crate::hidpp::Mx4HapticPattern::Completed);
+                Ok(())
+            }
+            Err(e) => {
+                haptic_cue(crate::hidpp::Mx4HapticPattern::AngryAlert);
+                Err(ActionError::ExecutionFailed(e.to_string()))
+            }
+        }
+    }
+
+    /// Quick-connect a Bluetooth device; the cue tells connect from disconnect
+    async fn execute_bluetooth(action: &BluetoothAction) -> Result<(), ActionError> {
+        let config = context()
+            .and_then(|c| c.config.read().ok().map(|config| config.bluetooth.clone()))
+            .unwrap_or_default();
+        let connected = crate::bluetooth::apply(action, &config)
+            .await
+            .map_err(|e| ActionError::ExecutionFailed(e.to_string()))?;
+        haptic_cue(if connected {
+            crate::hidpp::Mx4HapticPattern::Completed
+        } else {
+            crate::hidpp::Mx4HapticPattern::DampStateChange
+        });
+        Ok(())
+    }
+
+    /// Change the desktop magnifier through the compositor backend
+    async fn execute_zoom(step: ZoomStep) -> Result<(), ActionError> {
+        tracing::info!(?step, "Changing desktop zoom");
+        let backend = crate::compositor::backend();
+        let zoomed = tokio::task::spawn_blocking(move || backend.zoom(step))
+            .await
+            .unwrap_or(false);
+        if !zoomed {
+            return Err(ActionError::ExecutionFailed(format!(
+                "Zoom not available on {}",
+                backend.name()
+            )));
+        }
+        Ok(())
+    }
+
     async fn execute_dbus(call: &DBusCall) -> Result<(), ActionError> {
         // TODO: Make D-Bus method call via zbus
         tracing::info!(
@@ -220,6 +788,52 @@ impl ActionExecutor {
         Ok(())
     }
 
+    /// Open KRunner through its D-Bus interface
+    async fn execute_krunner(query: &KRunnerQuery) -> Result<(), ActionError> {
+        tracing::info!(query = %query.query, runner = ?query.runner, "Opening KRunner");
+        let connection = zbus::Connection::session().await.map_err(|e| {
+            ActionError::ExecutionFailed(format!("Session bus unavailable: {}", e))
+        })?;
+        let (name, interface) = (Some(KRUNNER_NAME), Some(KRUNNER_INTERFACE));
+        let result = match &query.runner {
+            Some(runner) => {
+                let body = (runner.as_str(), query.query.as_str());
+                connection.call_method(name, KRUNNER_PATH, interface, "querySingleRunner", &body).await
+            }
+            None if query.query.is_empty() => {
+                connection.call_method(name, KRUNNER_PATH, interface, "display", &()).await
+            }
+            None => {
+                connection.call_method(name, KRUNNER_PATH, interface, "query", &(query.query.as_str(),)).await
+            }
+        };
+        result
+            .map(|_| ())
+            .map_err(|e| ActionError::ExecutionFailed(format!("KRunner unavailable: {}", e)))
+    }
+
+    /// Toggle a GNOME Shell view, using its keybinding if D-Bus is refused
+    async fn execute_gnome_shell(view: GnomeShellView) -> Result<(), ActionError> {
+        match crate::gnome_shell::toggle(view).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::debug!("GNOME Shell D-Bus unavailable ({}), using keybinding", e);
+                Self::execute_shortcut(view.fallback_shortcut()).await
+            }
+        }
+    }
+
+    /// Activate a window from the window switcher ring via KWin
+    async fn execute_focus_window(window_id: &str) -> Result<(), ActionError> {
+        tracing::info!(window_id, "Focusing window");
+        let connection = zbus::Connection::session().await.map_err(|e| {
+            ActionError::ExecutionFailed(format!("Session bus unavailable: {}", e))
+        })?;
+        crate::window_tracker::focus_window(&connection, window_id)
+            .await
+            .map_err(|e| ActionError::ExecutionFailed(format!("Window activation failed: {}", e)))
+    }
+
     async fn execute_kwin(script: &str) -> Result<(), ActionError> {
         // TODO: Invoke KWin script via D-Bus
         tracing::info!(script, "Executing KWin script");
@@ -253,6 +867,214 @@ impl std::fmt::Display for ActionError {
 
 impl std::error::Error for ActionError {}
 
+/// Copy text to the clipboard (wl-copy, or xclip on X11)
+async fn copy_to_clipboard(text: &str) -> Result<(), ActionError> {
+    use tokio::io::AsyncWriteExt;
+
+    let (program, args): (&str, &[&str]) = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        ("wl-copy", &[])
+    } else {
+        ("xclip", &["-selection", "clipboard"])
+    };
+    tracing::info!(program, "Copying text to clipboard");
+
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| ActionError::ExecutionFailed(format!("{} failed: {}", program, e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .await
+            .map_err(|e| ActionError::ExecutionFailed(format!("{} failed: {}", program, e)))?;
+    }
+    // Both fork into the background once they own the selection
+    match tokio::time::timeout(CLIPBOARD_TIMEOUT, child.wait()).await {
+        Ok(Ok(status)) if status.success() => {}
+        Ok(Ok(status)) => {
+            return Err(ActionError::ExecutionFailed(format!("{} exited with {}", program, status)));
+        }
+        Ok(Err(e)) => return Err(ActionError::ExecutionFailed(format!("{} failed: {}", program, e))),
+        Err(_) => return Err(ActionError::Timeout),
+    }
+    Ok(())
+}
+
+/// Daemon state some actions need
+struct ActionContext {
+    /// Haptics for feedback cues
+    haptics: SharedHapticManager,
+    /// Live configuration (e.g. the OCR command)
+    config: SharedConfig,
+}
+
+/// Set once by the daemon at startup
+static CONTEXT: OnceLock<ActionContext> = OnceLock::new();
+
+/// Give actions the haptic manager and the configuration
+pub fn set_context(haptics: SharedHapticManager, config: SharedConfig) {
+    let _ = CONTEXT.set(ActionContext { haptics, config });
+}
+
+fn context() -> Option<&'static ActionContext> {
+    CONTEXT.get()
+}
+
+/// Play a feedback cue, if haptics were set up
+pub fn haptic_cue(pattern: crate::hidpp::Mx4HapticPattern) {
+    if let Some(Ok(mut manager)) = context().map(|c| c.haptics.lock()) {
+        manager.emit_cue(pattern);
+    }
+}
+
+/// Record an inverse, dropping the oldest beyond [`UNDO_HISTORY_LEN`]
+fn push_undo(inverse: &Action) {
+    if matches!(inverse.action_type, ActionType::Undo) {
+        return;
+    }
+    if let Ok(mut history) = UNDO_HISTORY.lock() {
+        if history.len() == UNDO_HISTORY_LEN {
+            history.remove(0);
+        }
+        history.push(inverse.clone());
+    }
+}
+
+/// Take the most recent inverse
+fn pop_undo() -> Option<Action> {
+    UNDO_HISTORY.lock().ok()?.pop()
+}
+
+// ============================================================================
+// Dynamic Slice Providers
+// ============================================================================
+
+/// Desktop state available to providers when the menu opens
+#[derive(Debug, Clone, Default)]
+pub struct ProviderContext {
+    /// Resource class of the focused window
+    pub active_window: Option<String>,
+    /// Recently focused window classes, most recent first
+    pub recent_windows: Vec<String>,
+    /// Open windows (only collected for window switcher menus)
+    pub open_windows: Vec<OpenWindow>,
+}
+
+impl ProviderContext {
+    /// Most recently focused window class other than the active one
+    pub fn previous_window(&self) -> Option<&str> {
+        self.recent_windows
+            .iter()
+            .map(String::as_str)
+            .find(|class| Some(*class) != self.active_window.as_deref())
+    }
+}
+
+/// Computes a slice's action at menu-open
+pub trait SliceProvider: Send + Sync {
+    /// Provider ID referenced by `{"type": "dynamic", "value": "<id>"}`
+    fn id(&self) -> &'static str;
+
+    /// Compute the action, or None if nothing applies right now
+    fn resolve(&self, ctx: &ProviderContext) -> Option<Action>;
+}
+
+/// Switch to the previously focused window
+pub struct PreviousWindowProvider;
+
+impl SliceProvider for PreviousWindowProvider {
+    fn id(&self) -> &'static str {
+        "previous-window"
+    }
+
+    fn resolve(&self, ctx: &ProviderContext) -> Option<Action> {
+        let label = match ctx.previous_window() {
+            Some(class) => crate::i18n::tr_fmt("Switch to {}", &[class]),
+            None => crate::i18n::tr("Previous Window"),
+        };
+        Some(Action {
+            action_type: ActionType::Shortcut("alt+tab".to_string()),
+            label: Some(label),
+            icon: Some("go-previous".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
+        })
+    }
+}
+
+/// Launch (or reopen) the most recently used other application
+pub struct LastAppProvider;
+
+impl SliceProvider for LastAppProvider {
+    fn id(&self) -> &'static str {
+        "last-app"
+    }
+
+    fn resolve(&self, ctx: &ProviderContext) -> Option<Action> {
+        let class = ctx.previous_window()?;
+        // Window classes come from the compositor; keep them out of the shell
+        if !class.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')) {
+            return None;
+        }
+        Some(Action {
+            action_type: ActionType::Command(format!("gtk-launch {}", class)),
+            label: Some(crate::i18n::tr_fmt("Open {}", &[class])),
+            icon: Some(class.to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
+        })
+    }
+}
+
+/// Registered dynamic slice providers
+pub static SLICE_PROVIDERS: &[&dyn SliceProvider] = &[&PreviousWindowProvider, &LastAppProvider];
+
+/// Look up a provider by ID
+pub fn find_provider(id: &str) -> Option<&'static dyn SliceProvider> {
+    SLICE_PROVIDERS.iter().copied().find(|p| p.id() == id)
+}
+
+/// Resolve a dynamic action; other actions are returned unchanged
+///
+/// A configured icon takes precedence over the provider's. Unknown providers
+/// and providers with nothing to offer yield an empty (`none`) slice. The
+/// long-hover alternate is resolved the same way.
+pub fn resolve_action(action: &Action, ctx: &ProviderContext) -> Action {
+    let mut resolved = resolve_primary(action, ctx);
+    resolved.alternate = action
+        .alternate
+        .as_ref()
+        .map(|alternate| Box::new(resolve_action(alternate, ctx)));
+    resolved
+}
+
+/// Resolve the action itself (not its alternate)
+fn resolve_primary(action: &Action, ctx: &ProviderContext) -> Action {
+    let ActionType::Dynamic(id) = &action.action_type else {
+        return action.clone();
+    };
+
+    let Some(provider) = find_provider(id) else {
+        tracing::warn!(provider = %id, "Unknown dynamic slice provider");
+        return Action { action_type: ActionType::None, ..action.clone() };
+    };
+
+    match provider.resolve(ctx) {
+        Some(resolved) => Action {
+            icon: action.icon.clone().or(resolved.icon),
+            notify: action.notify.clone().or(resolved.notify),
+            inverse: action.inverse.clone().or(resolved.inverse),
+            ..resolved
+        },
+        None => Action { action_type: ActionType::None, ..action.clone() },
+    }
+}
+
 /// Default actions for the 8 slices (Story 2.6)
 /// N=0, NE=1, E=2, SE=3, S=4, SW=5, W=6, NW=7
 pub fn get_default_actions() -> [Action; 8] {
@@ -262,48 +1084,80 @@ pub fn get_default_actions() -> [Action; 8] {
             action_type: ActionType::Shortcut("ctrl+c".to_string()),
             label: Some("Copy".to_string()),
             icon: Some("📋".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         },
         // NE (1): Paste
         Action {
             action_type: ActionType::Shortcut("ctrl+v".to_string()),
             label: Some("Paste".to_string()),
             icon: Some("📄".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         },
         // E (2): Undo
         Action {
             action_type: ActionType::Shortcut("ctrl+z".to_string()),
             label: Some("Undo".to_string()),
             icon: Some("↩️".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         },
         // SE (3): Redo
         Action {
             action_type: ActionType::Shortcut("ctrl+shift+z".to_string()),
             label: Some("Redo".to_string()),
             icon: Some("↪️".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         },
         // S (4): Select All
         Action {
             action_type: ActionType::Shortcut("ctrl+a".to_string()),
             label: Some("Select All".to_string()),
             icon: Some("🔲".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         },
         // SW (5): Cut
         Action {
             action_type: ActionType::Shortcut("ctrl+x".to_string()),
             label: Some("Cut".to_string()),
             icon: Some("✂️".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         },
         // W (6): Save
         Action {
             action_type: ActionType::Shortcut("ctrl+s".to_string()),
             label: Some("Save".to_string()),
             icon: Some("💾".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         },
         // NW (7): Close Tab
         Action {
             action_type: ActionType::Shortcut("ctrl+w".to_string()),
             label: Some("Close".to_string()),
             icon: Some("❌".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         },
     ]
 }
@@ -318,6 +1172,10 @@ mod tests {
             action_type: ActionType::Shortcut("Ctrl+C".to_string()),
             label: Some("Copy".to_string()),
             icon: Some("📋".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         };
 
         let json = serde_json::to_string(&action).unwrap();
@@ -335,6 +1193,23 @@ mod tests {
             _ => panic!("Expected Shortcut action"),
         }
         assert_eq!(action.label, Some("Copy".to_string()));
+        assert_eq!(action.notify, None);
+    }
+
+    #[test]
+    fn test_action_notify_survives_resolution() {
+        let json = r#"{"type":"dynamic","value":"previous-window","notify":"Switched window"}"#;
+        let action: Action = serde_json::from_str(json).unwrap();
+        assert_eq!(action.notify.as_deref(), Some("Switched window"));
+
+        let ctx = ProviderContext {
+            active_window: Some("konsole".to_string()),
+            recent_windows: vec!["konsole".to_string(), "firefox".to_string()],
+            ..ProviderContext::default()
+        };
+        let resolved = resolve_action(&action, &ctx);
+        assert_eq!(resolved.action_type, ActionType::Shortcut("alt+tab".to_string()));
+        assert_eq!(resolved.notify.as_deref(), Some("Switched window"));
     }
 
     #[test]
@@ -343,6 +1218,10 @@ mod tests {
             action_type: ActionType::Command("konsole".to_string()),
             label: Some("Terminal".to_string()),
             icon: None,
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         };
 
         let json = serde_json::to_string(&action).unwrap();
@@ -356,6 +1235,10 @@ mod tests {
             action_type: ActionType::None,
             label: None,
             icon: None,
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         };
 
         let json = serde_json::to_string(&action).unwrap();
@@ -393,15 +1276,103 @@ mod tests {
         assert!(format!("{}", err).contains("Shell execution"));
     }
 
+    #[test]
+    fn test_dynamic_action_deserialization() {
+        let json = r#"{"type":"dynamic","value":"previous-window","label":"Back"}"#;
+        let action: Action = serde_json::from_str(json).unwrap();
+        assert!(matches!(action.action_type, ActionType::Dynamic(ref id) if id == "previous-window"));
+        assert_eq!(action.action_type.kind(), "dynamic");
+    }
+
+    #[test]
+    fn test_krunner_action_deserialization() {
+        let json = r#"{"type":"krunner","value":{"query":"=","runner":"calculator"},"label":"Calculate"}"#;
+        let action: Action = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            action.action_type,
+            ActionType::KRunner(KRunnerQuery { query: "=".to_string(), runner: Some("calculator".to_string()) })
+        );
+        let plain: Action = serde_json::from_str(r#"{"type":"krunner","value":{}}"#).unwrap();
+        assert_eq!(plain.action_type, ActionType::KRunner(KRunnerQuery::default()));
+    }
+
+    #[test]
+    fn test_resolve_dynamic_actions() {
+        let ctx = ProviderContext {
+            active_window: Some("konsole".to_string()),
+            recent_windows: vec!["konsole".to_string(), "firefox".to_string()],
+            ..ProviderContext::default()
+        };
+        let dynamic = |id: &str| Action {
+            action_type: ActionType::Dynamic(id.to_string()),
+            label: None,
+            icon: None,
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
+        };
+
+        let previous = resolve_action(&dynamic("previous-window"), &ctx);
+        assert!(matches!(previous.action_type, ActionType::Shortcut(ref k) if k == "alt+tab"));
+        assert_eq!(previous.label.as_deref(), Some("Switch to firefox"));
+
+        let last_app = resolve_action(&dynamic("last-app"), &ctx);
+        assert!(matches!(last_app.action_type, ActionType::Command(ref c) if c == "gtk-launch firefox"));
+
+        // Nothing to offer / unknown provider -> empty slice
+        let empty = ProviderContext::default();
+        assert!(matches!(resolve_action(&dynamic("last-app"), &empty).action_type, ActionType::None));
+        assert!(matches!(resolve_action(&dynamic("nope"), &ctx).action_type, ActionType::None));
+
+        // Static actions pass through
+        let copy = &get_default_actions()[0];
+        assert!(matches!(resolve_action(copy, &ctx).action_type, ActionType::Shortcut(_)));
+    }
+
+    #[test]
+    fn test_last_app_rejects_unsafe_class() {
+        let ctx = ProviderContext {
+            active_window: None,
+            recent_windows: vec!["evil; rm -rf ~".to_string()],
+            ..ProviderContext::default()
+        };
+        assert!(LastAppProvider.resolve(&ctx).is_none());
+    }
+
     #[tokio::test]
     async fn test_execute_none_action() {
         let action = Action {
             action_type: ActionType::None,
             label: None,
             icon: None,
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
         };
 
         let result = ActionExecutor::execute(&action).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_undo_runs_inverses_newest_first() {
+        let json = r#"{"type":"none","label":"Forward","inverse":{"type":"none","label":"Back"}}"#;
+        let action: Action = serde_json::from_str(json).unwrap();
+        let undo: Action = serde_json::from_str(r#"{"type":"undo"}"#).unwrap();
+        assert_eq!(undo.action_type.kind(), "undo");
+
+        for _ in 0..UNDO_HISTORY_LEN + 2 {
+            ActionExecutor::execute(&action).await.unwrap();
+        }
+        assert_eq!(UNDO_HISTORY.lock().unwrap().len(), UNDO_HISTORY_LEN);
+        assert_eq!(pop_undo().and_then(|a| a.label).as_deref(), Some("Back"));
+
+        // The undo slice empties the stack without recording anything
+        for _ in 0..UNDO_HISTORY_LEN + 1 {
+            ActionExecutor::execute(&undo).await.unwrap();
+        }
+        assert!(!ActionExecutor::undo_last().await.unwrap());
+    }
 }
@@ -0,0 +1,191 @@
+//! KDE Activities: `switch_activity` actions and per-activity profiles
+//!
+//! The current activity is tracked from `org.kde.ActivityManager`
+//! (`CurrentActivityChanged`) so profile lookup can take it into account:
+//! a profile with `"activity": "Work"` (activity name or id) applies while
+//! that activity is current. With a `window_class` as well it only applies
+//! to that app in that activity, and such profiles win over app-only ones,
+//! which win over activity-only ones.
+//!
+//! ```json
+//! {"type": "switch_activity", "value": "next"}
+//! {"type": "switch_activity", "value": {"activity": "Work"}}
+//! ```
+//!
+//! Outside Plasma there is no activity manager; tracking stops quietly and
+//! profiles fall back to app matching only.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use tokio_stream::StreamExt;
+
+/// Activity manager bus name, object and interface
+const ACTIVITY_MANAGER_NAME: &str = "org.kde.ActivityManager";
+const ACTIVITIES_PATH: &str = "/ActivityManager/Activities";
+const ACTIVITIES_INTERFACE: &str = "org.kde.ActivityManager.Activities";
+
+/// A KDE activity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activity {
+    /// Activity UUID
+    pub id: String,
+    /// User-visible name
+    pub name: String,
+}
+
+impl Activity {
+    /// Whether a profile's `activity` refers to this activity (id or name)
+    pub fn matches(&self, reference: &str) -> bool {
+        reference == self.id || reference == self.name
+    }
+}
+
+/// Activity a `switch_activity` action goes to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityTarget {
+    Next,
+    Previous,
+    /// Activity by name or id
+    Activity(String),
+}
+
+/// Activity switching failure
+#[derive(Debug)]
+pub enum ActivityError {
+    /// No activity with this name or id
+    NotFound(String),
+    /// D-Bus error talking to the activity manager
+    DBus(zbus::Error),
+}
+
+impl std::fmt::Display for ActivityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActivityError::NotFound(name) => write!(f, "No activity named {}", name),
+            ActivityError::DBus(e) => write!(f, "Activity manager error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ActivityError {}
+
+impl From<zbus::Error> for ActivityError {
+    fn from(e: zbus::Error) -> Self {
+        ActivityError::DBus(e)
+    }
+}
+
+/// The current activity (None outside Plasma or before tracking starts)
+static CURRENT: RwLock<Option<Activity>> = RwLock::new(None);
+
+/// The current activity
+pub fn current() -> Option<Activity> {
+    CURRENT.read().ok()?.clone()
+}
+
+fn set_current(activity: Option<Activity>) {
+    if let Ok(mut current) = CURRENT.write() {
+        *current = activity;
+    }
+}
+
+async fn proxy(connection: &zbus::Connection) -> zbus::Result<zbus::Proxy<'static>> {
+    zbus::Proxy::new(connection, ACTIVITY_MANAGER_NAME, ACTIVITIES_PATH, ACTIVITIES_INTERFACE).await
+}
+
+async fn activity(proxy: &zbus::Proxy<'_>, id: String) -> Activity {
+    let name = proxy.call("ActivityName", &(id.as_str(),)).await.unwrap_or_default();
+    Activity { id, name }
+}
+
+/// Keep [`current`] up to date until the activity manager goes away
+pub async fn start_activity_tracking(connection: zbus::Connection) {
+    let Ok(proxy) = proxy(&connection).await else {
+        return;
+    };
+    let Ok(mut changes) = proxy.receive_signal("CurrentActivityChanged").await else {
+        return;
+    };
+    match proxy.call::<_, _, String>("CurrentActivity", &()).await {
+        Ok(id) => {
+            let activity = activity(&proxy, id).await;
+            tracing::info!(activity = %activity.name, "Tracking KDE activities");
+            set_current(Some(activity));
+        }
+        Err(e) => {
+            tracing::debug!("KDE activity manager not available: {}", e);
+            return;
+        }
+    }
+
+    while let Some(msg) = changes.next().await {
+        let Ok((id,)) = msg.body().deserialize::<(String,)>() else {
+            continue;
+        };
+        let activity = activity(&proxy, id).await;
+        tracing::debug!(activity = %activity.name, "Current activity changed");
+        set_current(Some(activity));
+    }
+    set_current(None);
+}
+
+/// Index of the activity to switch to in `ids`
+pub fn target_index(ids: &[String], current: Option<&str>, target: &ActivityTarget, names: &[String]) -> Option<usize> {
+    let position = current.and_then(|c| ids.iter().position(|id| id == c));
+    match target {
+        ActivityTarget::Next if !ids.is_empty() => Some(position.map_or(0, |i| (i + 1) % ids.len())),
+        ActivityTarget::Previous if !ids.is_empty() => {
+            Some(position.map_or(0, |i| (i + ids.len() - 1) % ids.len()))
+        }
+        ActivityTarget::Activity(reference) => ids
+            .iter()
+            .zip(names)
+            .position(|(id, name)| id == reference || name == reference),
+        _ => None,
+    }
+}
+
+/// Switch activity; returns the one switched to
+pub async fn switch(target: &ActivityTarget) -> Result<Activity, ActivityError> {
+    let connection = zbus::Connection::session().await?;
+    let proxy = proxy(&connection).await?;
+    let ids: Vec<String> = proxy.call("ListActivities", &()).await?;
+    let current: String = proxy.call("CurrentActivity", &()).await?;
+    let mut names = Vec::with_capacity(ids.len());
+    for id in &ids {
+        names.push(proxy.call::<_, _, String>("ActivityName", &(id.as_str(),)).await.unwrap_or_default());
+    }
+
+    let not_found = || match target {
+        ActivityTarget::Activity(reference) => ActivityError::NotFound(reference.clone()),
+        _ => ActivityError::NotFound("(none)".to_string()),
+    };
+    let index = target_index(&ids, Some(&current), target, &names).ok_or_else(not_found)?;
+    let next = Activity { id: ids[index].clone(), name: names[index].clone() };
+    let _switched: bool = proxy.call("SetCurrentActivity", &(next.id.as_str(),)).await?;
+    tracing::info!(activity = %next.name, "Switched activity");
+    Ok(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_index() {
+        let ids = vec!["a1".to_string(), "b2".to_string(), "c3".to_string()];
+        let names = vec!["Default".to_string(), "Work".to_string(), "Games".to_string()];
+
+        assert_eq!(target_index(&ids, Some("c3"), &ActivityTarget::Next, &names), Some(0));
+        assert_eq!(target_index(&ids, Some("a1"), &ActivityTarget::Previous, &names), Some(2));
+        assert_eq!(target_index(&ids, None, &ActivityTarget::Next, &names), Some(0));
+        assert_eq!(target_index(&ids, None, &ActivityTarget::Activity("Work".to_string()), &names), Some(1));
+        assert_eq!(target_index(&ids, None, &ActivityTarget::Activity("c3".to_string()), &names), Some(2));
+        assert_eq!(target_index(&ids, None, &ActivityTarget::Activity("Video".to_string()), &names), None);
+        assert_eq!(target_index(&[], None, &ActivityTarget::Next, &[]), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Per-application DPI switching
+//!
+//! Profiles may declare a preferred `dpi`. While a window mapped to such a
+//! profile is focused that DPI is applied; leaving it restores the DPI the
+//! mouse had before. A focus change only takes effect once it has been
+//! stable for [`DPI_SWITCH_DEBOUNCE_MS`], so alt-tabbing through windows
+//! does not thrash the sensor.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::hidpp::SharedHapticManager;
+use crate::profiles::SharedProfileManager;
+use crate::window_tracker::WindowTracker;
+
+/// How often the focused window is checked (milliseconds)
+const FOCUS_POLL_INTERVAL_MS: u64 = 500;
+
+/// How long a focus change must be stable before the DPI switches (milliseconds)
+pub const DPI_SWITCH_DEBOUNCE_MS: u64 = 750;
+
+/// DPI change decided by the policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DpiChange {
+    /// Switch to a profile's DPI
+    Apply(u16),
+    /// Restore the DPI from before the first switch
+    Restore,
+}
+
+/// Debounced per-app DPI state machine
+#[derive(Debug)]
+pub struct AppDpiPolicy {
+    /// Required stable time before switching
+    debounce: Duration,
+    /// Most recently observed target and when it was first seen
+    candidate: Option<(Option<u16>, Instant)>,
+    /// DPI currently applied by this policy (None = user's own DPI)
+    applied: Option<u16>,
+}
+
+impl AppDpiPolicy {
+    /// Create a policy with the given debounce
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            candidate: None,
+            applied: None,
+        }
+    }
+
+    /// Feed the focused window's preferred DPI; returns a change once it is stable
+    pub fn update(&mut self, target: Option<u16>, now: Instant) -> Option<DpiChange> {
+        match self.candidate {
+            Some((candidate, since)) if candidate == target => {
+                if now.duration_since(since) < self.debounce || target == self.applied {
+                    return None;
+                }
+            }
+            _ => {
+                self.candidate = Some((target, now));
+                return None;
+            }
+        }
+
+        self.applied = target;
+        Some(match target {
+            Some(dpi) => DpiChange::Apply(dpi),
+            None => DpiChange::Restore,
+        })
+    }
+}
+
+/// Watch window focus and apply per-profile DPI
+pub async fn start_app_dpi_switcher(
+    window_tracker: Arc<WindowTracker>,
+    profiles: SharedProfileManager,
+    haptics: SharedHapticManager,
+) {
+    let mut policy = AppDpiPolicy::new(Duration::from_millis(DPI_SWITCH_DEBOUNCE_MS));
+    let mut baseline: Option<u16> = None;
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(FOCUS_POLL_INTERVAL_MS));
+
+    loop {
+        interval.tick().await;
+
+        let has_dpi_profiles = profiles.read().map(|p| p.has_dpi_profiles()).unwrap_or(false);
+        if !has_dpi_profiles && baseline.is_none() {
+            continue;
+        }
+
+        let class = window_tracker.refresh_active_window().await;
+        let target = match (&class, profiles.read()) {
+            (Some(class), Ok(p)) => p.get_profile_for_window(class).dpi,
+            _ => None,
+        };
+
+        let Some(change) = policy.update(target, Instant::now()) else {
+            continue;
+        };
+
+        let Ok(mut manager) = haptics.lock() else {
+            continue;
+        };
+        match change {
+            DpiChange::Apply(dpi) => {
+                if baseline.is_none() {
+                    baseline = manager.get_dpi();
+                }
+                tracing::info!(dpi, window = ?class, "Applying per-app DPI");
+                if let Err(e) = manager.set_dpi(dpi) {
+                    tracing::warn!(dpi, "Per-app DPI switch failed: {}", e);
+                }
+            }
+            DpiChange::Restore => {
+                if let Some(dpi) = baseline.take() {
+                    tracing::info!(dpi, "Restoring DPI after leaving per-app profile");
+                    if let Err(e) = manager.set_dpi(dpi) {
+                        tracing::warn!(dpi, "DPI restore failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_apply_after_debounce_and_restore() {
+        let mut policy = AppDpiPolicy::new(ms(750));
+        let t0 = Instant::now();
+
+        assert_eq!(policy.update(Some(800), t0), None);
+        assert_eq!(policy.update(Some(800), t0 + ms(500)), None);
+        assert_eq!(policy.update(Some(800), t0 + ms(800)), Some(DpiChange::Apply(800)));
+        // Already applied
+        assert_eq!(policy.update(Some(800), t0 + ms(2000)), None);
+
+        assert_eq!(policy.update(None, t0 + ms(3000)), None);
+        assert_eq!(policy.update(None, t0 + ms(4000)), Some(DpiChange::Restore));
+        assert_eq!(policy.update(None, t0 + ms(5000)), None);
+    }
+
+    #[test]
+    fn test_alt_tab_does_not_thrash() {
+        let mut policy = AppDpiPolicy::new(ms(750));
+        let t0 = Instant::now();
+
+        // Quickly cycling through windows never settles
+        for (i, target) in [Some(800), None, Some(1600), None, Some(800)].into_iter().enumerate() {
+            assert_eq!(policy.update(target, t0 + ms(i as u64 * 200)), None);
+        }
+    }
+
+    #[test]
+    fn test_no_restore_without_apply() {
+        let mut policy = AppDpiPolicy::new(ms(750));
+        let t0 = Instant::now();
+
+        assert_eq!(policy.update(None, t0), None);
+        assert_eq!(policy.update(None, t0 + ms(1000)), None);
+    }
+}
@@ -0,0 +1,179 @@
+//! Default audio output cycling for `cycle_audio_output` actions
+//!
+//! Lists PipeWire's audio sinks with `pw-dump`, makes the one after the
+//! current default the new default with `wpctl set-default`, names it in a
+//! notification and plays a cue picked by its position in the list, so a
+//! headset and the speakers can be told apart without looking.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::hidpp::Mx4HapticPattern;
+use crate::i18n;
+use crate::notifications::{self, Notification};
+
+/// Cue for the n-th sink (wrapping)
+pub const SINK_CUES: &[Mx4HapticPattern] = &[
+    Mx4HapticPattern::Knock,
+    Mx4HapticPattern::Wave,
+    Mx4HapticPattern::Square,
+    Mx4HapticPattern::Firework,
+];
+
+/// Notification icon for the switch
+const ICON: &str = "audio-card";
+
+/// id of the last switch notification, replaced on the next switch
+static NOTIFICATION_ID: AtomicU32 = AtomicU32::new(0);
+
+/// An audio sink from `pw-dump`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sink {
+    /// PipeWire object id
+    pub id: u32,
+    /// `node.name`, as stored in the default metadata
+    pub name: String,
+    /// `node.description`, shown to the user
+    pub description: String,
+}
+
+/// Audio output failure
+#[derive(Debug)]
+pub enum AudioOutputError {
+    /// pw-dump or wpctl could not be run or failed
+    Command(String),
+    /// Fewer than two sinks, nothing to cycle to
+    NoOtherSink,
+}
+
+impl std::fmt::Display for AudioOutputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AudioOutputError::Command(msg) => write!(f, "Audio output command failed: {}", msg),
+            AudioOutputError::NoOtherSink => write!(f, "No other audio output to switch to"),
+        }
+    }
+}
+
+impl std::error::Error for AudioOutputError {}
+
+/// Sinks (in PipeWire object order) and the default sink's `node.name`
+pub fn parse_dump(dump: &serde_json::Value) -> (Vec<Sink>, Option<String>) {
+    let objects = dump.as_array().map(Vec::as_slice).unwrap_or_default();
+    let sinks = objects
+        .iter()
+        .filter(|o| o["type"] == "PipeWire:Interface:Node")
+        .filter(|o| o["info"]["props"]["media.class"] == "Audio/Sink")
+        .filter_map(|o| {
+            let props = &o["info"]["props"];
+            let name = props["node.name"].as_str()?.to_string();
+            Some(Sink {
+                id: u32::try_from(o["id"].as_u64()?).ok()?,
+                description: props["node.description"].as_str().unwrap_or(&name).to_string(),
+                name,
+            })
+        })
+        .collect();
+
+    // {"type": "PipeWire:Interface:Metadata", "props": {"metadata.name": "default"},
+    //  "metadata": [{"key": "default.audio.sink", "value": {"name": "..."}}]}
+    let default = objects
+        .iter()
+        .filter(|o| o["type"] == "PipeWire:Interface:Metadata" && o["props"]["metadata.name"] == "default")
+        .filter_map(|o| o["metadata"].as_array())
+        .flatten()
+        .find(|entry| entry["key"] == "default.audio.sink")
+        .and_then(|entry| entry["value"]["name"].as_str())
+        .map(str::to_string);
+    (sinks, default)
+}
+
+/// Index of the sink after the default one (the first if none is default)
+pub fn next_index(sinks: &[Sink], default: Option<&str>) -> Option<usize> {
+    if sinks.len() < 2 {
+        return None;
+    }
+    let current = sinks.iter().position(|s| Some(s.name.as_str()) == default);
+    Some(current.map_or(0, |i| (i + 1) % sinks.len()))
+}
+
+/// Switch to the next sink; returns it
+pub async fn cycle() -> Result<Sink, AudioOutputError> {
+    let dump = run("pw-dump", &[]).await?;
+    let dump: serde_json::Value =
+        serde_json::from_slice(&dump).map_err(|e| AudioOutputError::Command(format!("pw-dump: {}", e)))?;
+    let (sinks, default) = parse_dump(&dump);
+    let index = next_index(&sinks, default.as_deref()).ok_or(AudioOutputError::NoOtherSink)?;
+    let sink = sinks[index].clone();
+
+    run("wpctl", &["set-default", &sink.id.to_string()]).await?;
+    tracing::info!(sink = %sink.name, "Default audio output switched");
+    crate::actions::haptic_cue(SINK_CUES[index % SINK_CUES.len()]);
+    announce(&sink).await;
+    Ok(sink)
+}
+
+/// Name the new output in a notification
+async fn announce(sink: &Sink) {
+    let connection = match zbus::Connection::session().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::debug!("Session bus unavailable for audio output notification: {}", e);
+            return;
+        }
+    };
+    let notification = Notification {
+        icon: ICON.to_string(),
+        ..Notification::transient(i18n::tr("Audio output"), sink.description.clone())
+    };
+    match notifications::notify(&connection, &notification, NOTIFICATION_ID.load(Ordering::Relaxed)).await {
+        Ok(id) => NOTIFICATION_ID.store(id, Ordering::Relaxed),
+        Err(e) => tracing::debug!("Audio output notification failed: {}", e),
+    }
+}
+
+/// Run a helper; its stdout if it exited successfully
+async fn run(program: &str, args: &[&str]) -> Result<Vec<u8>, AudioOutputError> {
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .await
+        .map_err(|e| AudioOutputError::Command(format!("{}: {}", program, e)))?;
+    if !output.status.success() {
+        return Err(AudioOutputError::Command(format!("{} exited with {}", program, output.status)));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dump_and_cycle() {
+        let dump = serde_json::json!([
+            {"id": 31, "type": "PipeWire:Interface:Metadata", "props": {"metadata.name": "default"},
+             "metadata": [{"subject": 0, "key": "default.audio.sink", "type": "Spa:String:JSON",
+                           "value": {"name": "bluez_output.headset"}}]},
+            {"id": 48, "type": "PipeWire:Interface:Node",
+             "info": {"props": {"media.class": "Audio/Sink", "node.name": "alsa_output.speakers",
+                                "node.description": "Speakers"}}},
+            {"id": 52, "type": "PipeWire:Interface:Node",
+             "info": {"props": {"media.class": "Audio/Source", "node.name": "alsa_input.mic"}}},
+            {"id": 60, "type": "PipeWire:Interface:Node",
+             "info": {"props": {"media.class": "Audio/Sink", "node.name": "bluez_output.headset"}}},
+        ]);
+        let (sinks, default) = parse_dump(&dump);
+        assert_eq!(sinks.len(), 2);
+        assert_eq!(sinks[1].description, "bluez_output.headset");
+        assert_eq!(default.as_deref(), Some("bluez_output.headset"));
+
+        assert_eq!(next_index(&sinks, default.as_deref()), Some(0));
+        assert_eq!(next_index(&sinks, Some("alsa_output.speakers")), Some(1));
+        assert_eq!(next_index(&sinks, None), Some(0));
+        assert_eq!(next_index(&sinks[..1], None), None);
+    }
+}
@@ -22,6 +22,9 @@ const HIDPP_LONG: u8 = 0x11;
 /// Software ID for our requests
 const SOFTWARE_ID: u8 = 0x01;
 
+/// Default battery poll interval (fast, for instant charging status detection)
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 2;
+
 /// Battery state shared across threads
 #[derive(Debug, Clone, Default)]
 pub struct BatteryState {
@@ -35,6 +38,16 @@ pub struct BatteryState {
     pub error: Option<String>,
     /// Whether logid is controlling HID++ (battery unavailable)
     pub logid_active: bool,
+    /// Poll interval override set by the battery saver (None = default)
+    pub poll_interval_override_secs: Option<u64>,
+}
+
+impl BatteryState {
+    /// Effective poll interval in seconds
+    pub fn poll_interval_secs(&self) -> u64 {
+        self.poll_interval_override_secs
+            .unwrap_or(DEFAULT_POLL_INTERVAL_SECS)
+    }
 }
 
 /// Shared battery state type
@@ -199,8 +212,13 @@ impl BatteryHandler {
         Err(BatteryError::DeviceNotFound)
     }
 
-    /// Send a HID++ request and read the response
+    /// Send a HID++ request and read the response, retrying lost requests
     fn hidpp_request(&mut self, feature_index: u8, function: u8, params: &[u8]) -> Result<Vec<u8>, BatteryError> {
+        crate::hidpp_retry::run(|| self.hidpp_request_once(feature_index, function, params), BatteryError::failure)
+    }
+
+    /// One attempt at a HID++ request
+    fn hidpp_request_once(&mut self, feature_index: u8, function: u8, params: &[u8]) -> Result<Vec<u8>, BatteryError> {
         let device = self.device.as_mut().ok_or(BatteryError::DeviceNotFound)?;
 
         // Drain any pending data first to avoid stale responses
@@ -232,11 +250,21 @@ impl BatteryHandler {
         );
 
         // Send request
+        let feature_id = match feature_index {
+            0x00 => Some(0x0000), // IRoot
+            idx if Some(idx) == self.battery_feature_index => Some(if self.is_unified_battery {
+                FEATURE_UNIFIED_BATTERY
+            } else {
+                FEATURE_BATTERY_STATUS
+            }),
+            _ => None,
+        };
+        crate::hidpp_audit::record(&request, feature_id);
         device.write_all(&request).map_err(BatteryError::IoError)?;
 
         // Read response with timeout (non-blocking, so we poll)
         let mut response = [0u8; 20];
-        let mut attempts = 0;
+        let deadline = std::time::Instant::now() + crate::hidpp_retry::attempt_timeout();
 
         loop {
             match device.read(&mut response) {
@@ -274,19 +302,19 @@ impl BatteryHandler {
                     // Short read, continue
                 }
                 Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                    // No data yet
+                    // No data yet: sleep until a report arrives or the deadline passes
+                    if crate::hidraw::wait_readable(device, deadline).map_err(BatteryError::IoError)? {
+                        continue;
+                    }
                 }
                 Err(e) => {
                     return Err(BatteryError::IoError(e));
                 }
             }
 
-            attempts += 1;
-            if attempts > 100 {
+            if std::time::Instant::now() >= deadline {
                 return Err(BatteryError::Timeout);
             }
-
-            std::thread::sleep(std::time::Duration::from_millis(10));
         }
     }
 
@@ -366,7 +394,7 @@ impl BatteryHandler {
             let charging_status = response[7]; // Charging status is at byte 7 for UNIFIED_BATTERY
 
             // UNIFIED_BATTERY charging_status: 0=discharging, 1=charging, 2=charging_slow, 3=charging_complete, 5=invalid
-            let charging = charging_status >= 1 && charging_status <= 3;
+            let charging = (1..=3).contains(&charging_status);
 
             tracing::debug!(
                 percentage,
@@ -381,7 +409,7 @@ impl BatteryHandler {
             let charging_status = response[6];
 
             // BATTERY_STATUS status: 0=discharging, 1-4=various charging states
-            let charging = charging_status >= 1 && charging_status <= 4;
+            let charging = (1..=4).contains(&charging_status);
 
             tracing::debug!(
                 percentage,
@@ -443,6 +471,16 @@ impl std::fmt::Display for BatteryError {
 
 impl std::error::Error for BatteryError {}
 
+impl BatteryError {
+    /// Retry classification (only lost requests are retried)
+    fn failure(&self) -> crate::hidpp_retry::Failure {
+        match self {
+            BatteryError::Timeout => crate::hidpp_retry::Failure::Timeout,
+            _ => crate::hidpp_retry::Failure::Fatal,
+        }
+    }
+}
+
 /// Check if logid (LogiOps) is running
 fn is_logid_running() -> bool {
     std::process::Command::new("pgrep")
@@ -480,13 +518,15 @@ pub async fn start_battery_updater(state: SharedBatteryState) {
         interval.tick().await;
 
         // Re-check logid periodically (every 30 seconds worth of ticks)
-        if consecutive_errors > 0 && consecutive_errors % 15 == 0 {
-            if is_logid_running() && !logid_warned {
-                tracing::info!("LogiOps (logid) detected - battery queries will fail");
-                let mut s = state.write().await;
-                s.logid_active = true;
-                logid_warned = true;
-            }
+        if consecutive_errors > 0
+            && consecutive_errors.is_multiple_of(15)
+            && is_logid_running()
+            && !logid_warned
+        {
+            tracing::info!("LogiOps (logid) detected - battery queries will fail");
+            let mut s = state.write().await;
+            s.logid_active = true;
+            logid_warned = true;
         }
 
         match handler.query_battery() {
@@ -562,20 +602,43 @@ pub async fn start_battery_updater_shared(
         }
     }
 
-    // Update every 2 seconds for instant charging status detection
-    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+    let mut presence = crate::receiver_notifications::subscribe();
 
     loop {
-        interval.tick().await;
+        // Every 2 seconds by default; the battery saver may lengthen this.
+        // The mouse coming back (or going away) is handled right away.
+        let poll_secs = state.read().await.poll_interval_secs();
+        tokio::select! {
+            _ = tokio::time::sleep(tokio::time::Duration::from_secs(poll_secs)) => {}
+            Ok(()) = presence.changed() => {}
+        }
 
-        // Re-check logid periodically (every 30 seconds worth of ticks)
-        if consecutive_errors > 0 && consecutive_errors % 15 == 0 {
-            if is_logid_running() && !logid_warned {
-                tracing::info!("LogiOps (logid) detected - battery queries will fail");
-                let mut s = state.write().await;
-                s.logid_active = true;
-                logid_warned = true;
-            }
+        // Re-check logid periodically (every 15 failed polls)
+        if consecutive_errors > 0
+            && consecutive_errors.is_multiple_of(15)
+            && is_logid_running()
+            && !logid_warned
+        {
+            tracing::info!("LogiOps (logid) detected - battery queries will fail");
+            let mut s = state.write().await;
+            s.logid_active = true;
+            logid_warned = true;
+        }
+
+        // The mouse is on another host; don't fail (and log) every poll
+        if crate::host_switch::is_paused() {
+            let mut s = state.write().await;
+            s.available = false;
+            s.error = Some("Mouse connected to another host".to_string());
+            continue;
+        }
+
+        // Solaar shares the hidraw device; polling would interleave with its requests
+        if crate::solaar::is_running() {
+            let mut s = state.write().await;
+            s.available = false;
+            s.error = Some("Solaar controls HID++".to_string());
+            continue;
         }
 
         // Lock the haptic manager briefly to query battery
@@ -623,5 +686,6 @@ mod tests {
         assert_eq!(state.percentage, 0);
         assert!(!state.charging);
         assert!(!state.available);
+        assert_eq!(state.poll_interval_secs(), DEFAULT_POLL_INTERVAL_SECS);
     }
 }
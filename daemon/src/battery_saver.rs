@@ -0,0 +1,221 @@
+//! Battery saver policy for JuhRadial MX
+//!
+//! Observes [`SharedBatteryState`] and, while the mouse battery is low and
+//! not charging, applies the configured power-saving policy:
+//!
+//! - drop slice-change haptics (menu appear / confirm still pulse)
+//! - lengthen the battery poll interval
+//! - optionally switch to a lower DPI
+//!
+//! Everything is restored once the mouse charges or the level recovers
+//! above `restore_threshold` (hysteresis avoids flapping around the threshold).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use crate::battery::{BatteryState, SharedBatteryState};
+use crate::config::{BatterySaverConfig, SharedConfig};
+use crate::hidpp::SharedHapticManager;
+
+/// How often the policy re-evaluates battery state (seconds)
+const POLICY_CHECK_INTERVAL_SECS: u64 = 5;
+
+/// Policy state change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaverTransition {
+    /// Start power saving
+    Enter,
+    /// Restore normal settings
+    Exit,
+}
+
+/// Battery saver state machine
+#[derive(Debug, Default)]
+pub struct BatterySaverPolicy {
+    /// Whether power saving is currently applied
+    active: bool,
+    /// DPI before power saving lowered it (restored on exit)
+    saved_dpi: Option<u16>,
+}
+
+impl BatterySaverPolicy {
+    /// Create a new inactive policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if power saving is currently applied
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Decide whether to enter or leave power saving
+    ///
+    /// Unavailable battery readings never change the state.
+    pub fn evaluate(&mut self, battery: &BatteryState, config: &BatterySaverConfig) -> Option<SaverTransition> {
+        if self.active {
+            let recovered = battery.available
+                && (battery.charging || battery.percentage > config.restore_threshold);
+            if !config.enabled || recovered {
+                self.active = false;
+                return Some(SaverTransition::Exit);
+            }
+        } else if config.enabled
+            && battery.available
+            && !battery.charging
+            && battery.percentage <= config.low_threshold
+        {
+            self.active = true;
+            return Some(SaverTransition::Enter);
+        }
+        None
+    }
+
+    /// Apply power-saving settings
+    async fn enter(&mut self, config: &BatterySaverConfig, battery: &SharedBatteryState, haptics: &SharedHapticManager) {
+        battery.write().await.poll_interval_override_secs = Some(config.poll_interval_secs);
+
+        if let Ok(mut manager) = haptics.lock() {
+            if config.reduce_haptics {
+                manager.set_power_saving(true);
+            }
+
+            if config.dpi > 0 {
+                self.saved_dpi = manager.get_dpi();
+                if let Err(e) = manager.set_dpi(config.dpi) {
+                    tracing::warn!(dpi = config.dpi, "Battery saver could not lower DPI: {}", e);
+                    self.saved_dpi = None;
+                }
+            }
+        }
+    }
+
+    /// Restore normal settings
+    async fn exit(&mut self, battery: &SharedBatteryState, haptics: &SharedHapticManager) {
+        battery.write().await.poll_interval_override_secs = None;
+
+        if let Ok(mut manager) = haptics.lock() {
+            manager.set_power_saving(false);
+
+            if let Some(dpi) = self.saved_dpi.take() {
+                if let Err(e) = manager.set_dpi(dpi) {
+                    tracing::warn!(dpi, "Battery saver could not restore DPI: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Run the battery saver policy loop
+pub async fn start_battery_saver(
+    battery: SharedBatteryState,
+    config: SharedConfig,
+    haptics: SharedHapticManager,
+) {
+    let mut policy = BatterySaverPolicy::new();
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(POLICY_CHECK_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        let saver_config = match config.read() {
+            Ok(c) => c.battery_saver.clone(),
+            Err(_) => continue,
+        };
+        let state = battery.read().await.clone();
+
+        match policy.evaluate(&state, &saver_config) {
+            Some(SaverTransition::Enter) => {
+                tracing::info!(
+                    percentage = state.percentage,
+                    threshold = saver_config.low_threshold,
+                    "Battery low - entering power saving"
+                );
+                policy.enter(&saver_config, &battery, &haptics).await;
+            }
+            Some(SaverTransition::Exit) => {
+                tracing::info!(
+                    percentage = state.percentage,
+                    charging = state.charging,
+                    "Leaving power saving - restoring settings"
+                );
+                policy.exit(&battery, &haptics).await;
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battery::new_shared_state;
+    use crate::hidpp::new_shared_haptic_manager;
+
+    fn battery(percentage: u8, charging: bool) -> BatteryState {
+        BatteryState {
+            percentage,
+            charging,
+            available: true,
+            ..BatteryState::default()
+        }
+    }
+
+    #[test]
+    fn test_enter_and_exit_with_hysteresis() {
+        let config = BatterySaverConfig::default();
+        let mut policy = BatterySaverPolicy::new();
+
+        assert_eq!(policy.evaluate(&battery(50, false), &config), None);
+        assert_eq!(policy.evaluate(&battery(15, false), &config), Some(SaverTransition::Enter));
+        assert!(policy.is_active());
+
+        // Still within hysteresis band
+        assert_eq!(policy.evaluate(&battery(18, false), &config), None);
+        assert_eq!(policy.evaluate(&battery(21, false), &config), Some(SaverTransition::Exit));
+        assert!(!policy.is_active());
+    }
+
+    #[test]
+    fn test_charging_restores() {
+        let config = BatterySaverConfig::default();
+        let mut policy = BatterySaverPolicy::new();
+
+        // Low but charging: never enter
+        assert_eq!(policy.evaluate(&battery(5, true), &config), None);
+
+        policy.evaluate(&battery(5, false), &config);
+        assert_eq!(policy.evaluate(&battery(5, true), &config), Some(SaverTransition::Exit));
+    }
+
+    #[test]
+    fn test_unavailable_and_disabled() {
+        let mut config = BatterySaverConfig::default();
+        let mut policy = BatterySaverPolicy::new();
+
+        assert_eq!(policy.evaluate(&BatteryState::default(), &config), None);
+
+        policy.evaluate(&battery(10, false), &config);
+        // Lost battery reading keeps the current state
+        assert_eq!(policy.evaluate(&BatteryState::default(), &config), None);
+        assert!(policy.is_active());
+
+        config.enabled = false;
+        assert_eq!(policy.evaluate(&battery(10, false), &config), Some(SaverTransition::Exit));
+    }
+
+    #[tokio::test]
+    async fn test_enter_exit_applies_settings() {
+        let config = BatterySaverConfig::default();
+        let state = new_shared_state();
+        let haptics = new_shared_haptic_manager(&Default::default());
+        let mut policy = BatterySaverPolicy::new();
+
+        policy.enter(&config, &state, &haptics).await;
+        assert_eq!(state.read().await.poll_interval_secs(), config.poll_interval_secs);
+        assert!(haptics.lock().unwrap().is_power_saving());
+
+        policy.exit(&state, &haptics).await;
+        assert_eq!(state.read().await.poll_interval_override_secs, None);
+        assert!(!haptics.lock().unwrap().is_power_saving());
+    }
+}
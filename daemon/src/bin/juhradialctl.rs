@@ -0,0 +1,418 @@
+//! juhradialctl - command-line control tool for JuhRadial MX
+//!
+//! Talks to the running daemon over D-Bus and hosts the privileged helper
+//! used by the permissions setup flow.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+
+use juhradiald::config::{Config, IssueSeverity};
+use juhradiald::dbus::{DBUS_INTERFACE, DBUS_NAME, DBUS_PATH};
+use juhradiald::feature_explorer::{self, capabilities, feature_name, feature_use, FeatureStatus};
+use juhradiald::hidpp::Mx4HapticPattern;
+use juhradiald::i18n::{self, tr, tr_fmt};
+use juhradiald::theme::ThemeManager;
+use juhradiald::theme_preview;
+use juhradiald::setup::{
+    check_permissions, current_username, request_install, run_install_helper, PermissionStatus,
+    INPUT_GROUP,
+};
+
+/// JuhRadial MX control tool
+#[derive(Parser, Debug)]
+#[command(name = "juhradialctl")]
+#[command(version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check device permissions and install udev rules / input group membership
+    Setup {
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// List the connected device's HID++ features (read-only) and what juhradiald uses
+    Features,
+
+    /// Play haptic waveforms through the running daemon to audition them
+    #[command(name = "haptic-test")]
+    HapticTest {
+        /// Waveform name (e.g. "happy_alert"); omit to list all names
+        pattern: Option<String>,
+        /// Play every waveform in turn
+        #[arg(long, conflicts_with = "pattern")]
+        all: bool,
+        /// Pause between waveforms with --all (milliseconds)
+        #[arg(long, default_value_t = 1000)]
+        delay_ms: u64,
+    },
+
+    /// Check the running daemon end to end: overlay signals, haptics and battery
+    #[command(name = "selftest")]
+    SelfTest,
+
+    /// List installed themes, optionally with palette swatches and preview images
+    Themes {
+        /// Show color swatches and render SVG previews into the cache
+        #[arg(long)]
+        preview: bool,
+        /// Preview edge length in pixels
+        #[arg(long, default_value_t = theme_preview::DEFAULT_PREVIEW_SIZE)]
+        size: u32,
+    },
+
+    /// Work with config.json
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+
+    /// Privileged install step (run by pkexec, not by users)
+    #[command(name = "install-rules-helper", hide = true)]
+    InstallRulesHelper {
+        /// User to add to the input group
+        #[arg(long)]
+        user: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Check a config file without applying it (exits non-zero on errors)
+    Validate {
+        /// File to check (default: the daemon's config.json)
+        file: Option<PathBuf>,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Setup { yes } => setup(yes),
+        Command::Features => list_features(),
+        Command::HapticTest { pattern, all, delay_ms } => haptic_test(pattern.as_deref(), all, delay_ms),
+        Command::SelfTest => self_test(),
+        Command::Themes { preview, size } => list_themes(preview, size),
+        Command::Config { command: ConfigCommand::Validate { file } } => validate_config(file),
+        Command::InstallRulesHelper { user } => {
+            run_install_helper(&user).map_err(|e| e.to_string())
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// ============================================================================
+// setup
+// ============================================================================
+
+/// Interactive permissions setup
+fn setup(assume_yes: bool) -> Result<(), String> {
+    let user = current_username().ok_or("Cannot determine current user")?;
+    i18n::set_language(&Config::load_default().map(|c| c.language).unwrap_or_default());
+    let status = check_permissions(&user);
+    print_status(&user, &status);
+
+    if !status.needs_install() {
+        if status.needs_relogin() {
+            println!("\n{}", tr_fmt("Log out and back in to activate the '{}' group.", &[INPUT_GROUP]));
+        } else {
+            println!("\n{}", tr("Permissions are already set up."));
+        }
+        return Ok(());
+    }
+
+    if !assume_yes
+        && !confirm(&format!(
+            "\n{} [Y/n] ",
+            tr_fmt("Install udev rules and add '{}' to the '{}' group?", &[&user, INPUT_GROUP])
+        ))
+    {
+        println!("{}", tr("Aborted."));
+        return Ok(());
+    }
+
+    // Prefer the daemon so the request is tied to the session's polkit agent;
+    // fall back to running pkexec ourselves when the daemon is not running.
+    match install_via_daemon() {
+        Ok(()) => {}
+        Err(DaemonInstallError::Unavailable(reason)) => {
+            println!("Daemon not reachable ({}), running pkexec directly...", reason);
+            request_install(&user).map_err(|e| e.to_string())?;
+        }
+        Err(DaemonInstallError::Failed(e)) => return Err(e),
+    }
+
+    let status = check_permissions(&user);
+    println!();
+    print_status(&user, &status);
+
+    if status.needs_relogin() {
+        println!("\n{}", tr_fmt("Done. Log out and back in for the '{}' group to take effect.", &[INPUT_GROUP]));
+    } else {
+        println!("\n{}", tr("Done."));
+    }
+    Ok(())
+}
+
+/// Print current permission state
+fn print_status(user: &str, status: &PermissionStatus) {
+    let mark = |ok: bool| if ok { "ok" } else { "missing" };
+
+    println!("{}", tr_fmt("Permissions for user '{}':", &[user]));
+    println!("  {:<26} {}", "udev rules installed:", mark(status.rules_installed));
+    println!(
+        "  {:<26} {}",
+        "udev rules up to date:",
+        if status.rules_current { "ok" } else { "outdated" }
+    );
+    println!("  {:<26} {}", format!("member of '{}' group:", INPUT_GROUP), mark(status.in_input_group));
+    println!(
+        "  {:<26} {}",
+        "group active in session:",
+        if status.group_active { "ok" } else { "no (re-login required)" }
+    );
+}
+
+/// Ask a yes/no question (default yes)
+fn confirm(prompt: &str) -> bool {
+    print!("{}", prompt);
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "" | "y" | "yes")
+}
+
+/// Why the daemon-side install did not happen
+enum DaemonInstallError {
+    /// Daemon not running or too old to have InstallUdevRules
+    Unavailable(String),
+    /// Daemon ran the helper and it failed
+    Failed(String),
+}
+
+/// Ask the running daemon to perform the install
+fn install_via_daemon() -> Result<(), DaemonInstallError> {
+    let connection = zbus::blocking::Connection::session()
+        .map_err(|e| DaemonInstallError::Unavailable(e.to_string()))?;
+    let proxy = zbus::blocking::Proxy::new(&connection, DBUS_NAME, DBUS_PATH, DBUS_INTERFACE)
+        .map_err(|e| DaemonInstallError::Unavailable(e.to_string()))?;
+
+    match proxy.call_method("InstallUdevRules", &()) {
+        Ok(_) => Ok(()),
+        Err(zbus::Error::MethodError(name, msg, _))
+            if name.as_str() == "org.freedesktop.DBus.Error.ServiceUnknown"
+                || name.as_str() == "org.freedesktop.DBus.Error.UnknownMethod" =>
+        {
+            Err(DaemonInstallError::Unavailable(msg.unwrap_or_else(|| name.to_string())))
+        }
+        Err(zbus::Error::MethodError(_, Some(msg), _)) => Err(DaemonInstallError::Failed(msg)),
+        Err(e) => Err(DaemonInstallError::Failed(e.to_string())),
+    }
+}
+
+// ============================================================================
+// features
+// ============================================================================
+
+/// Print the device's feature table with safety classification
+fn list_features() -> Result<(), String> {
+    let device = feature_explorer::explore()
+        .ok_or("No HID++ 2.0 device found (is the mouse connected and are permissions set up?)")?;
+
+    println!("Device connected via {}", device.connection);
+    println!();
+    println!("  {:<5} {:<8} {:<28} {:<12} Used for", "Index", "Feature", "Name", "Status");
+    for feature in &device.features {
+        println!(
+            "  {:<5} 0x{:04X}   {:<28} {:<12} {}",
+            feature.index,
+            feature.feature_id,
+            feature_name(feature.feature_id).unwrap_or("?"),
+            feature.status.label(),
+            feature_use(feature.feature_id).unwrap_or("-"),
+        );
+    }
+
+    let blocklisted: Vec<_> = device
+        .features
+        .iter()
+        .filter_map(|f| match f.status {
+            FeatureStatus::Blocklisted(reason) => Some((f.feature_id, reason)),
+            _ => None,
+        })
+        .collect();
+    if !blocklisted.is_empty() {
+        println!();
+        println!("Blocklisted features (never written by juhradiald):");
+        for (feature_id, reason) in blocklisted {
+            println!("  0x{:04X}  {}", feature_id, reason);
+        }
+    }
+
+    println!();
+    println!("Capabilities:");
+    for capability in capabilities(&device.features) {
+        match capability.feature_id {
+            Some(feature_id) => println!("  {:<20} yes (0x{:04X})", capability.name, feature_id),
+            None => println!("  {:<20} not available", capability.name),
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// haptic-test
+// ============================================================================
+
+/// Play one or all waveforms via the daemon's TestHaptic method
+fn haptic_test(pattern: Option<&str>, all: bool, delay_ms: u64) -> Result<(), String> {
+    if pattern.is_none() && !all {
+        println!("Available waveforms:");
+        for pattern in Mx4HapticPattern::ALL {
+            println!("  {:<20} {}", pattern.config_name(), pattern.name());
+        }
+        println!("\nPlay one with `juhradialctl haptic-test <name>` or all with --all.");
+        return Ok(());
+    }
+
+    let connection = zbus::blocking::Connection::session().map_err(|e| e.to_string())?;
+    let proxy = zbus::blocking::Proxy::new(&connection, DBUS_NAME, DBUS_PATH, DBUS_INTERFACE)
+        .map_err(|e| e.to_string())?;
+    let play = |name: &str| -> Result<(), String> {
+        proxy.call_method("TestHaptic", &(name,)).map(|_| ()).map_err(|e| match e {
+            zbus::Error::MethodError(_, Some(msg), _) => msg,
+            e => format!("Daemon not reachable: {}", e),
+        })
+    };
+
+    match pattern {
+        Some(name) => play(name),
+        None => {
+            for (i, pattern) in Mx4HapticPattern::ALL.into_iter().enumerate() {
+                if i > 0 {
+                    std::thread::sleep(Duration::from_millis(delay_ms));
+                }
+                println!("{:>2}/{}  {:<20} {}", i + 1, Mx4HapticPattern::ALL.len(), pattern.config_name(), pattern.name());
+                play(pattern.config_name())?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// ============================================================================
+// selftest
+// ============================================================================
+
+/// Run the daemon's self-test and print a pass/fail summary
+fn self_test() -> Result<(), String> {
+    let connection = zbus::blocking::Connection::session().map_err(|e| e.to_string())?;
+    let proxy = zbus::blocking::Proxy::new(&connection, DBUS_NAME, DBUS_PATH, DBUS_INTERFACE)
+        .map_err(|e| e.to_string())?;
+
+    println!("Running self-test (an empty menu briefly appears at the screen center)...\n");
+    let checks: Vec<(String, bool, String)> = proxy
+        .call("RunSelfTest", &())
+        .map_err(|e| format!("Daemon not reachable: {}", e))?;
+
+    for (name, passed, detail) in &checks {
+        println!("  {:<4}  {:<14} {}", if *passed { "PASS" } else { "FAIL" }, name, detail);
+    }
+    let passed = checks.iter().filter(|(_, passed, _)| *passed).count();
+    println!("\n{}/{} checks passed", passed, checks.len());
+    if passed < checks.len() {
+        return Err("Self-test failed".to_string());
+    }
+    Ok(())
+}
+
+// ============================================================================
+// themes
+// ============================================================================
+
+/// List bundled, system and user themes
+fn list_themes(preview: bool, size: u32) -> Result<(), String> {
+    let themes = ThemeManager::load_all().map_err(|e| e.to_string())?;
+    let mut names = themes.theme_names();
+    names.sort();
+
+    for name in names {
+        let Some(theme) = themes.get(name) else {
+            continue;
+        };
+        println!("  {:<20} {}", name, theme.display_name);
+        if preview {
+            let colors = &theme.colors;
+            let palette = [
+                &colors.base,
+                &colors.surface,
+                &colors.accent,
+                &colors.accent_secondary,
+                &colors.text,
+                &colors.border,
+            ];
+            let swatches: String = palette.iter().filter_map(|c| swatch(c)).collect();
+            let path = theme_preview::cached_preview(theme, size).map_err(|e| e.to_string())?;
+            println!("  {:<20} {}  {}", "", swatches, path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Two truecolor cells in a #rrggbb color
+fn swatch(hex: &str) -> Option<String> {
+    let hex = hex.strip_prefix('#').filter(|h| h.len() == 6 && h.is_ascii())?;
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(format!("\x1b[48;2;{};{};{}m  \x1b[0m", channel(0)?, channel(2)?, channel(4)?))
+}
+
+// ============================================================================
+// config
+// ============================================================================
+
+/// Dry-run check of a config file
+fn validate_config(file: Option<PathBuf>) -> Result<(), String> {
+    let path = file
+        .or_else(Config::default_config_path)
+        .ok_or("Cannot determine the config directory")?;
+    let json = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let issues = Config::check(&json);
+    for issue in &issues {
+        println!("{}: {}", path.display(), issue);
+    }
+    let errors = issues.iter().filter(|i| i.severity == IssueSeverity::Error).count();
+    match (errors, issues.len()) {
+        (0, 0) => {
+            println!("{}: OK", path.display());
+            Ok(())
+        }
+        (0, warnings) => {
+            println!("{}: loads with {} warning(s)", path.display(), warnings);
+            Ok(())
+        }
+        (errors, _) => Err(format!("{} would be rejected ({} error(s))", path.display(), errors)),
+    }
+}
@@ -0,0 +1,178 @@
+//! Haptic-only ("blind") menus
+//!
+//! Profiles with `"blind": true` never show the overlay. While the gesture
+//! button is held the daemon hit-tests the cursor offset itself (with the
+//! configured [`SliceGeometry`]) and announces every slice it enters with a
+//! train of pulses: one for slice 0, two for slice 1 and so on clockwise
+//! (counter-clockwise when mirrored). Empty slices stay silent. Releasing
+//! the button runs the selected slice's action; releasing in the dead zone
+//! runs nothing.
+//!
+//! Only the profile's first page is used; blind menus do not page.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::actions::{Action, ActionExecutor, ActionType};
+use crate::hidpp::Mx4HapticPattern;
+use crate::profiles::Profile;
+use crate::slice_geometry::SliceGeometry;
+
+/// Pulse that counts out a slice
+pub const PULSE_PATTERN: Mx4HapticPattern = Mx4HapticPattern::DampCollision;
+
+/// Cue played when a selection is run
+pub const CONFIRM_PATTERN: Mx4HapticPattern = Mx4HapticPattern::Completed;
+
+/// Gap between the pulses of one slice
+const PULSE_GAP: Duration = Duration::from_millis(120);
+
+/// A blind menu held open by the gesture button
+#[derive(Debug, Clone)]
+pub struct BlindMenu {
+    slices: [Option<Action>; 8],
+    geometry: SliceGeometry,
+    selected: Option<u8>,
+}
+
+impl BlindMenu {
+    /// Menu over the layout's first page
+    pub fn new(layout: &Profile, geometry: SliceGeometry) -> Self {
+        Self { slices: layout.slices.clone(), geometry, selected: None }
+    }
+
+    /// Follow the cursor offset; returns the slice just entered, if any
+    pub fn on_move(&mut self, dx: i32, dy: i32) -> Option<u8> {
+        let hit = self.geometry.hit_test(dx, dy, self.selected);
+        if hit == self.selected {
+            return None;
+        }
+        self.selected = hit;
+        hit
+    }
+
+    /// Selected slice
+    pub fn selected(&self) -> Option<u8> {
+        self.selected
+    }
+
+    /// Action of the selected slice (empty slices select nothing)
+    pub fn into_selection(self) -> Option<Action> {
+        let index = self.selected? as usize;
+        self.slices
+            .into_iter()
+            .nth(index)
+            .flatten()
+            .filter(|action| !matches!(action.action_type, ActionType::None))
+    }
+
+    /// Pulses announcing `index`, 0 for an empty slice
+    pub fn pulse_count(&self, index: u8) -> u32 {
+        match self.slices.get(index as usize) {
+            Some(Some(action)) if !matches!(action.action_type, ActionType::None) => index as u32 + 1,
+            _ => 0,
+        }
+    }
+}
+
+/// The open blind menu
+static MENU: Mutex<Option<BlindMenu>> = Mutex::new(None);
+
+/// Distinguishes a pulse train from the ones it cut short
+static PULSE_TRAIN: AtomicU64 = AtomicU64::new(0);
+
+/// Open a blind menu (replacing any left open)
+pub fn open(layout: &Profile, geometry: SliceGeometry) {
+    tracing::info!(profile = %layout.name, "Blind menu opened");
+    if let Ok(mut menu) = MENU.lock() {
+        *menu = Some(BlindMenu::new(layout, geometry));
+    }
+}
+
+/// Whether a blind menu is open
+pub fn is_open() -> bool {
+    MENU.lock().is_ok_and(|menu| menu.is_some())
+}
+
+/// Feed a cursor offset from the menu center; pulses out a newly entered slice
+pub fn on_cursor_moved(dx: i32, dy: i32) {
+    let pulses = {
+        let Ok(mut menu) = MENU.lock() else { return };
+        let Some(menu) = menu.as_mut() else { return };
+        match menu.on_move(dx, dy) {
+            Some(index) => menu.pulse_count(index),
+            None if menu.selected().is_none() => 0,
+            None => return,
+        }
+    };
+    // A new slice (or the dead zone) cuts the previous count short
+    let train = PULSE_TRAIN.fetch_add(1, Ordering::Relaxed) + 1;
+    if pulses == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        for i in 0..pulses {
+            if i > 0 {
+                tokio::time::sleep(PULSE_GAP).await;
+            }
+            if PULSE_TRAIN.load(Ordering::Relaxed) != train {
+                return;
+            }
+            crate::actions::haptic_cue(PULSE_PATTERN);
+        }
+    });
+}
+
+/// Close the blind menu, if one is open
+pub fn take() -> Option<BlindMenu> {
+    PULSE_TRAIN.fetch_add(1, Ordering::Relaxed);
+    MENU.lock().ok()?.take()
+}
+
+/// Run a blind selection with a confirmation cue
+pub fn run(action: Action) {
+    tracing::info!(label = ?action.label, kind = action.action_type.kind(), "Blind menu selection");
+    crate::actions::haptic_cue(CONFIRM_PATTERN);
+    crate::training::record_selection();
+    tokio::spawn(async move {
+        if let Err(e) = ActionExecutor::execute(&action).await {
+            tracing::error!("Blind menu action failed: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::create_default_profile;
+
+    #[test]
+    fn test_blind_menu_selection() {
+        let mut menu = BlindMenu::new(&create_default_profile(), SliceGeometry::default());
+        assert_eq!(menu.on_move(0, 0), None);
+        assert_eq!(menu.on_move(0, -100), Some(0)); // N
+        assert_eq!(menu.on_move(5, -100), None); // still N
+        assert_eq!(menu.on_move(100, 0), Some(2)); // E
+        assert_eq!(menu.pulse_count(2), 3);
+        assert_eq!(menu.into_selection().unwrap().label.as_deref(), Some("Undo"));
+    }
+
+    #[test]
+    fn test_blind_menu_dead_zone_and_empty_slices() {
+        let mut layout = create_default_profile();
+        layout.slices[4] = None;
+        let mut menu = BlindMenu::new(&layout, SliceGeometry::default());
+
+        assert_eq!(menu.on_move(0, 100), Some(4)); // S, empty
+        assert_eq!(menu.pulse_count(4), 0);
+        assert!(menu.clone().into_selection().is_none());
+
+        menu.on_move(0, -100);
+        menu.on_move(0, 10); // back to the dead zone
+        assert_eq!(menu.selected(), None);
+        assert!(menu.into_selection().is_none());
+    }
+}
@@ -0,0 +1,152 @@
+//! Bluetooth quick-connect for `bluetooth` actions
+//!
+//! ```json
+//! {"type": "bluetooth", "value": {"device": "headphones", "op": "toggle"}}
+//! ```
+//!
+//! `device` is a name from `bluetooth.devices` or a MAC address; `op` is
+//! `connect`, `disconnect` or `toggle` (the default). The device must
+//! already be paired: the action calls `Connect`/`Disconnect` on its
+//! `org.bluez.Device1` object, found on whichever adapter knows it.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+
+use crate::config::BluetoothConfig;
+
+/// BlueZ bus name
+const BLUEZ_NAME: &str = "org.bluez";
+
+/// BlueZ device interface
+const DEVICE_INTERFACE: &str = "org.bluez.Device1";
+
+/// What a `bluetooth` action does with its device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BluetoothOp {
+    Connect,
+    Disconnect,
+    /// Disconnect if connected, otherwise connect
+    #[default]
+    Toggle,
+}
+
+/// `bluetooth` action payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BluetoothAction {
+    /// Name from `bluetooth.devices`, or a MAC address
+    pub device: String,
+    #[serde(default)]
+    pub op: BluetoothOp,
+}
+
+/// Bluetooth action failure
+#[derive(Debug)]
+pub enum BluetoothError {
+    /// Neither a configured name nor a MAC address
+    UnknownDevice(String),
+    /// BlueZ has no paired device with this address
+    NotPaired(String),
+    /// D-Bus error talking to BlueZ
+    DBus(zbus::Error),
+}
+
+impl std::fmt::Display for BluetoothError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BluetoothError::UnknownDevice(name) => write!(f, "Unknown Bluetooth device: {}", name),
+            BluetoothError::NotPaired(mac) => write!(f, "Bluetooth device {} is not paired", mac),
+            BluetoothError::DBus(e) => write!(f, "BlueZ error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BluetoothError {}
+
+impl From<zbus::Error> for BluetoothError {
+    fn from(e: zbus::Error) -> Self {
+        BluetoothError::DBus(e)
+    }
+}
+
+impl From<zbus::fdo::Error> for BluetoothError {
+    fn from(e: zbus::fdo::Error) -> Self {
+        BluetoothError::DBus(e.into())
+    }
+}
+
+/// Whether `s` is a MAC address (`AA:BB:CC:DD:EE:FF`, either case)
+pub fn is_valid_mac(s: &str) -> bool {
+    let parts: Vec<&str> = s.split(':').collect();
+    parts.len() == 6 && parts.iter().all(|p| p.len() == 2 && p.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// MAC address for an action's `device`
+pub fn resolve_mac(device: &str, config: &BluetoothConfig) -> Option<String> {
+    match config.devices.get(device) {
+        Some(mac) => Some(mac.clone()),
+        None if is_valid_mac(device) => Some(device.to_ascii_uppercase()),
+        None => None,
+    }
+}
+
+/// Last path element BlueZ uses for a device (`dev_AA_BB_CC_DD_EE_FF`)
+pub fn device_node(mac: &str) -> String {
+    format!("dev_{}", mac.to_ascii_uppercase().replace(':', "_"))
+}
+
+/// Run a `bluetooth` action; returns whether the device is connected afterwards
+pub async fn apply(action: &BluetoothAction, config: &BluetoothConfig) -> Result<bool, BluetoothError> {
+    let mac = resolve_mac(&action.device, config)
+        .ok_or_else(|| BluetoothError::UnknownDevice(action.device.clone()))?;
+    let connection = crate::dbus::system_bus().await?;
+    let path = find_device(connection, &mac).await?.ok_or_else(|| BluetoothError::NotPaired(mac.clone()))?;
+
+    let device = zbus::Proxy::new(connection, BLUEZ_NAME, path, DEVICE_INTERFACE).await?;
+    let connect = match action.op {
+        BluetoothOp::Connect => true,
+        BluetoothOp::Disconnect => false,
+        BluetoothOp::Toggle => !device.get_property::<bool>("Connected").await?,
+    };
+    tracing::info!(mac = %mac, connect, "Bluetooth quick-connect");
+    device.call_method(if connect { "Connect" } else { "Disconnect" }, &()).await?;
+    Ok(connect)
+}
+
+/// Object path of the paired device with this address, on any adapter
+async fn find_device(connection: &zbus::Connection, mac: &str) -> Result<Option<OwnedObjectPath>, BluetoothError> {
+    type Objects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
+
+    let manager = zbus::Proxy::new(connection, BLUEZ_NAME, "/", "org.freedesktop.DBus.ObjectManager").await?;
+    let objects: Objects = manager.call("GetManagedObjects", &()).await?;
+    let node = format!("/{}", device_node(mac));
+    Ok(objects
+        .into_iter()
+        .find(|(path, interfaces)| path.as_str().ends_with(&node) && interfaces.contains_key(DEVICE_INTERFACE))
+        .map(|(path, _)| path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_device() {
+        let mut config: BluetoothConfig =
+            serde_json::from_str(r#"{"devices": {"headphones": " aa:bb:cc:dd:ee:0f ", "broken": "aa:bb"}}"#).unwrap();
+        config.validate();
+        assert_eq!(config.devices.len(), 1);
+
+        assert_eq!(resolve_mac("headphones", &config).as_deref(), Some("AA:BB:CC:DD:EE:0F"));
+        assert_eq!(resolve_mac("01:23:45:67:89:ab", &config).as_deref(), Some("01:23:45:67:89:AB"));
+        assert_eq!(resolve_mac("speaker", &config), None);
+        assert_eq!(device_node("aa:bb:cc:dd:ee:0f"), "dev_AA_BB_CC_DD_EE_0F");
+
+        let action: BluetoothAction = serde_json::from_str(r#"{"device": "headphones"}"#).unwrap();
+        assert_eq!(action.op, BluetoothOp::Toggle);
+    }
+}
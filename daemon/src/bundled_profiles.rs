@@ -0,0 +1,133 @@
+//! Bundled starter profiles per desktop environment
+//!
+//! Like the bundled themes, these profile sets are compiled into the
+//! binary. On first run (no `profiles.json` yet) the set matching the
+//! detected compositor is written out; `ResetProfile` installs one later,
+//! moving the current file to `profiles.json.bak`.
+//!
+//! | Set        | Used on                              |
+//! |------------|--------------------------------------|
+//! | `kde`      | KWin                                 |
+//! | `gnome`    | GNOME Shell                          |
+//! | `hyprland` | Hyprland                             |
+//! | `generic`  | everything else (editing shortcuts)  |
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use crate::compositor::BackendKind;
+use crate::profiles::ProfilesConfig;
+
+/// Plasma starter profiles JSON
+const KDE_JSON: &str = include_str!("bundled_profiles/kde.json");
+
+/// GNOME starter profiles JSON
+const GNOME_JSON: &str = include_str!("bundled_profiles/gnome.json");
+
+/// Hyprland starter profiles JSON
+const HYPRLAND_JSON: &str = include_str!("bundled_profiles/hyprland.json");
+
+/// Desktop-neutral starter profiles JSON
+const GENERIC_JSON: &str = include_str!("bundled_profiles/generic.json");
+
+/// Name of the set used when no desktop-specific one fits
+pub const GENERIC_PROFILES_NAME: &str = "generic";
+
+/// Information about a bundled profile set
+#[derive(Debug, Clone)]
+pub struct BundledProfilesInfo {
+    /// Set name, as passed to `ResetProfile`
+    pub name: &'static str,
+    /// Display name (human readable)
+    pub display_name: &'static str,
+    /// Short description
+    pub description: &'static str,
+}
+
+/// List of all bundled profile sets with metadata
+pub const BUNDLED_PROFILES_INFO: &[BundledProfilesInfo] = &[
+    BundledProfilesInfo {
+        name: "kde",
+        display_name: "KDE Plasma",
+        description: "Overview, KRunner, virtual desktops and editing",
+    },
+    BundledProfilesInfo {
+        name: "gnome",
+        display_name: "GNOME",
+        description: "Overview, app grid, workspaces and editing",
+    },
+    BundledProfilesInfo {
+        name: "hyprland",
+        display_name: "Hyprland",
+        description: "Workspaces, fullscreen, floating and editing",
+    },
+    BundledProfilesInfo {
+        name: "generic",
+        display_name: "Generic",
+        description: "Copy, paste, undo and other editing shortcuts",
+    },
+];
+
+/// Get a bundled profile set by name (case-insensitive)
+///
+/// Returns `None` if there is no such set or it fails to parse.
+pub fn get_bundled_profiles(name: &str) -> Option<ProfilesConfig> {
+    let json = match name.to_lowercase().as_str() {
+        "kde" => KDE_JSON,
+        "gnome" => GNOME_JSON,
+        "hyprland" => HYPRLAND_JSON,
+        "generic" => GENERIC_JSON,
+        _ => return None,
+    };
+    serde_json::from_str(json).ok()
+}
+
+/// List all bundled profile set names
+pub fn list_bundled_profiles() -> Vec<&'static str> {
+    BUNDLED_PROFILES_INFO.iter().map(|info| info.name).collect()
+}
+
+/// Name of the set that fits a compositor
+pub fn profiles_name_for(kind: BackendKind) -> &'static str {
+    match kind {
+        BackendKind::KWin => "kde",
+        BackendKind::Gnome => "gnome",
+        BackendKind::Hyprland => "hyprland",
+        _ => GENERIC_PROFILES_NAME,
+    }
+}
+
+/// Profile set for the current session's compositor
+pub fn profiles_for_session() -> (&'static str, ProfilesConfig) {
+    let name = profiles_name_for(BackendKind::detect());
+    let config = get_bundled_profiles(name).unwrap_or_default();
+    (name, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_profiles_parse() {
+        for name in list_bundled_profiles() {
+            let config = get_bundled_profiles(name).unwrap_or_else(|| panic!("{} must parse", name));
+            assert!(config.profiles.iter().any(|p| p.name == "default"), "{} has no default profile", name);
+        }
+        assert!(get_bundled_profiles("KDE").is_some());
+        assert!(get_bundled_profiles("unknown").is_none());
+    }
+
+    #[test]
+    fn test_generic_matches_builtin_default() {
+        let generic = get_bundled_profiles(GENERIC_PROFILES_NAME).unwrap();
+        let builtin = crate::profiles::create_default_profile();
+        assert_eq!(generic.profiles[0].slices, builtin.slices);
+    }
+
+    #[test]
+    fn test_profiles_name_for() {
+        assert_eq!(profiles_name_for(BackendKind::KWin), "kde");
+        assert_eq!(profiles_name_for(BackendKind::Gnome), "gnome");
+        assert_eq!(profiles_name_for(BackendKind::Sway), GENERIC_PROFILES_NAME);
+    }
+}
@@ -69,7 +69,7 @@ pub const BUNDLED_THEME_INFO: &[BundledThemeInfo] = &[
 pub fn get_bundled_theme(name: &str) -> Option<Theme> {
     let name_lower = name.to_lowercase();
     // Normalize separators: convert spaces and underscores to dashes
-    let normalized = name_lower.replace(' ', "-").replace('_', "-");
+    let normalized = name_lower.replace([' ', '_'], "-");
 
     let json = match normalized.as_str() {
         "catppuccin-mocha" => Some(CATPPUCCIN_MOCHA_JSON),
@@ -0,0 +1,151 @@
+//! Supervised shell commands for `command` actions
+//!
+//! Every command runs as `sh -c` in a process group of its own, so the
+//! whole pipeline it starts can be signalled at once. A watcher task reaps
+//! the shell when it exits. Commands with a `timeout_secs` are tracked until
+//! then, and their group is terminated when the timeout expires or
+//! `CancelRunningAction` is called: SIGTERM first, SIGKILL if the group is
+//! still there after [`KILL_GRACE`].
+//!
+//! Commands without a timeout are never signalled, so applications launched
+//! from a slice keep running. Processes a command detaches from its group
+//! (e.g. `setsid`) are not tracked either.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use tokio::process::{Child, Command};
+use tokio::sync::Notify;
+
+/// Time between SIGTERM and SIGKILL
+pub const KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// Running commands with a timeout by process group ID
+static RUNNING: OnceLock<Mutex<HashMap<u32, RunningCommand>>> = OnceLock::new();
+
+/// A command whose shell has not exited yet
+struct RunningCommand {
+    /// Command line (for logs)
+    command: String,
+    /// Wakes the watcher to terminate the group
+    cancel: Arc<Notify>,
+}
+
+fn running() -> &'static Mutex<HashMap<u32, RunningCommand>> {
+    RUNNING.get_or_init(Default::default)
+}
+
+/// Start `command` in its own process group; returns its process group ID
+///
+/// Must be called from within the tokio runtime.
+pub fn spawn(command: &str, timeout: Option<Duration>) -> std::io::Result<u32> {
+    let mut child = Command::new("sh").args(["-c", command]).process_group(0).spawn()?;
+    let pgid = child.id().ok_or_else(|| std::io::Error::other("command exited before it was tracked"))?;
+
+    let cancel = Arc::new(Notify::new());
+    if timeout.is_some() {
+        if let Ok(mut running) = running().lock() {
+            running.insert(pgid, RunningCommand { command: command.to_string(), cancel: cancel.clone() });
+        }
+    }
+
+    tokio::spawn(async move {
+        let expired = async {
+            match timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            status = child.wait() => {
+                tracing::debug!(pgid, ?status, "Command action finished");
+            }
+            _ = expired => {
+                tracing::warn!(pgid, timeout_secs = timeout.map(|t| t.as_secs()), "Command action timed out, terminating");
+                terminate(pgid, &mut child).await;
+            }
+            _ = cancel.notified() => {
+                tracing::info!(pgid, "Command action cancelled, terminating");
+                terminate(pgid, &mut child).await;
+            }
+        }
+        if let Ok(mut running) = running().lock() {
+            running.remove(&pgid);
+        }
+    });
+
+    Ok(pgid)
+}
+
+/// Terminate every running command that has a timeout; returns how many were signalled
+pub fn cancel_all() -> usize {
+    let Ok(running) = running().lock() else {
+        return 0;
+    };
+    for (pgid, command) in running.iter() {
+        tracing::info!(pgid, command = %command.command, "Cancelling command action");
+        command.cancel.notify_one();
+    }
+    running.len()
+}
+
+/// Number of commands with a timeout whose shell is still running
+pub fn running_count() -> usize {
+    running().lock().map(|r| r.len()).unwrap_or(0)
+}
+
+/// SIGTERM the group, then SIGKILL it if the shell outlives the grace period
+async fn terminate(pgid: u32, child: &mut Child) {
+    signal_group(pgid, libc::SIGTERM);
+    if tokio::time::timeout(KILL_GRACE, child.wait()).await.is_err() {
+        tracing::warn!(pgid, "Command action ignored SIGTERM, killing");
+        signal_group(pgid, libc::SIGKILL);
+        let _ = child.wait().await;
+    }
+}
+
+/// Send `signal` to every process in the group
+fn signal_group(pgid: u32, signal: libc::c_int) {
+    // SAFETY: kill(2) with a negative pid only signals the group; no memory is shared
+    if unsafe { libc::kill(-(pgid as libc::pid_t), signal) } != 0 {
+        tracing::debug!(pgid, signal, "kill failed: {}", std::io::Error::last_os_error());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn wait_until_gone(pgid: u32) -> bool {
+        for _ in 0..50 {
+            if !running().lock().unwrap().contains_key(&pgid) {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_cancel_kill_the_group() {
+        // SIGTERM is ignored by the whole group, so this needs the SIGKILL
+        let started = std::time::Instant::now();
+        let timed = spawn("trap '' TERM; sleep 30 & wait", Some(Duration::from_millis(100))).unwrap();
+        assert!(wait_until_gone(timed).await);
+        assert!(started.elapsed() >= KILL_GRACE);
+
+        let cancelled = spawn("sleep 30", Some(Duration::from_secs(60))).unwrap();
+        let app = spawn("sleep 30", None).unwrap();
+        assert_eq!(cancel_all(), 1);
+        assert!(wait_until_gone(cancelled).await);
+        assert_eq!(running_count(), 0);
+
+        // Without a timeout the command is left alone
+        // SAFETY: signal 0 only checks that the group exists
+        assert_eq!(unsafe { libc::kill(-(app as libc::pid_t), 0) }, 0);
+        signal_group(app, libc::SIGKILL);
+    }
+}
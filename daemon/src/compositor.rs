@@ -0,0 +1,962 @@
+//! Compositor backends
+//!
+//! Everything that depends on the desktop environment goes through a
+//! [`CompositorBackend`]: the cursor position, the screen bounds used for
+//! edge clamping, the focused window for per-app profiles, and whether the
+//! compositor can open the menu at the true cursor position itself. The
+//! backend is detected once from the session environment (see
+//! [`BackendKind::detect`]) and shared process-wide via [`backend`].
+//!
+//! | Backend  | Cursor               | Screen bounds         | Active window                |
+//! |----------|----------------------|-----------------------|------------------------------|
+//! | KWin     | KWin script, qdbus   | xrandr                | KWin D-Bus (`WindowTracker`) |
+//! | Hyprland | IPC socket / hyprctl | `hyprctl monitors`    | `hyprctl activewindow`       |
+//! | Sway     | xdotool (XWayland)   | `swaymsg get_outputs` | `swaymsg get_tree`           |
+//! | River    | xdotool (XWayland)   | `wlr-randr --json`    | `lswt -j` (foreign toplevel) |
+//! | Niri     | xdotool (XWayland)   | IPC `Outputs`         | IPC `FocusedWindow`          |
+//! | COSMIC   | xdotool (XWayland)   | `wlr-randr --json`    | `lswt -j` (foreign toplevel) |
+//! | GNOME    | xdotool (XWayland)   | xrandr                | -                            |
+//! | X11      | xdotool              | xrandr, xdotool       | xdotool                      |
+//!
+//! The desktop magnifier (`zoom` actions) is driven on KWin (zoom effect
+//! D-Bus, kglobalaccel before Plasma 6), Hyprland (`cursor:zoom_factor`)
+//! and GNOME (a11y magnifier settings); other backends report it as
+//! unsupported.
+//!
+//! Hyprland (`hyprctl activewindow`), Sway (`swaymsg get_tree`) and X11
+//! (xdotool, xprop) also report the focused window's geometry and whether
+//! it is fullscreen, which the menu placement uses while a fullscreen app may
+//! be confining the pointer (see `crate::pointer_confinement`).
+//!
+//! Hyprland (event socket) and Sway (`swaymsg -t subscribe`) report monitor
+//! hotplug directly; other backends are polled (see `crate::screen_watcher`).
+//!
+//! With the `xdg-output` feature, screen bounds come from the compositor's
+//! `zxdg_output_manager_v1` on any Wayland session instead (see
+//! `crate::xdg_output`).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::process::Command;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cursor::{self, CursorPosition, ScreenBounds};
+
+/// Magnifier zoom factor change per step
+pub const ZOOM_STEP: f64 = 0.5;
+
+/// Largest zoom factor stepped to
+pub const MAX_ZOOM: f64 = 10.0;
+
+/// Zoom factor a toggle switches to from 1x
+pub const TOGGLE_ZOOM: f64 = 2.0;
+
+/// Magnifier change requested by a `zoom` action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ZoomStep {
+    /// Magnify by one step
+    In,
+    /// Step back towards 1x
+    Out,
+    /// Switch between 1x and [`TOGGLE_ZOOM`] (or back to 1x when zoomed)
+    Toggle,
+}
+
+/// Zoom factor after applying `step` to `current`
+pub fn next_zoom_factor(current: f64, step: ZoomStep) -> f64 {
+    match step {
+        ZoomStep::In => (current + ZOOM_STEP).min(MAX_ZOOM),
+        ZoomStep::Out => (current - ZOOM_STEP).max(1.0),
+        ZoomStep::Toggle if current > 1.0 => 1.0,
+        ZoomStep::Toggle => TOGGLE_ZOOM,
+    }
+}
+
+/// Geometry and state of the focused window, in global coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FocusedWindow {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    /// Fullscreen (maximized windows are not)
+    pub fullscreen: bool,
+}
+
+impl FocusedWindow {
+    /// Center of the window
+    pub fn center(&self) -> CursorPosition {
+        CursorPosition::new(self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    /// Whether `pos` lies inside the window
+    pub fn contains(&self, pos: CursorPosition) -> bool {
+        pos.x >= self.x && pos.x < self.x + self.width && pos.y >= self.y && pos.y < self.y + self.height
+    }
+}
+
+/// Desktop environment capabilities the daemon relies on
+pub trait CompositorBackend: Send + Sync {
+    /// Backend name for logs and diagnostics
+    fn name(&self) -> &'static str;
+
+    /// Current cursor position in global coordinates
+    fn cursor_pos(&self) -> Option<CursorPosition>;
+
+    /// Bounding box of all outputs
+    fn screen_bounds(&self) -> Option<ScreenBounds>;
+
+    /// Resource class (lowercase) of the focused window
+    fn active_window(&self) -> Option<String>;
+
+    /// Whether [`CompositorBackend::active_window`] can report the focused window
+    fn tracks_active_window(&self) -> bool {
+        true
+    }
+
+    /// Geometry of the focused window, None if the backend cannot tell
+    ///
+    /// No compositor exposes pointer-constraint state over IPC, so
+    /// [`FocusedWindow::fullscreen`] is what the menu placement goes by (see
+    /// [`crate::pointer_confinement`]).
+    fn focused_window(&self) -> Option<FocusedWindow> {
+        None
+    }
+
+    /// Ask the compositor to open the menu at the true cursor position
+    ///
+    /// Returns true if the compositor took over (it calls `ShowMenuAtCursor`
+    /// itself); otherwise the caller opens the menu at
+    /// [`CompositorBackend::cursor_pos`].
+    fn show_overlay_hint(&self) -> bool {
+        false
+    }
+
+    /// Block until the output layout may have changed (monitor hotplug)
+    ///
+    /// Returns false if the backend cannot subscribe to output changes; the
+    /// caller then polls [`CompositorBackend::screen_bounds`] instead.
+    fn wait_screen_change(&self) -> bool {
+        false
+    }
+
+    /// Change the desktop magnifier; false if unsupported or it failed
+    fn zoom(&self, _step: ZoomStep) -> bool {
+        false
+    }
+}
+
+/// Known compositor backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    KWin,
+    Hyprland,
+    Sway,
+    River,
+    Niri,
+    Cosmic,
+    Gnome,
+    X11,
+}
+
+impl BackendKind {
+    /// Detect the backend from the session environment
+    pub fn detect() -> Self {
+        Self::detect_from(|name| std::env::var(name).ok())
+    }
+
+    /// Detect the backend from environment variables provided by `var`
+    pub fn detect_from(var: impl Fn(&str) -> Option<String>) -> Self {
+        if var("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+            return BackendKind::Hyprland;
+        }
+        if var("SWAYSOCK").is_some() {
+            return BackendKind::Sway;
+        }
+        if var("NIRI_SOCKET").is_some() {
+            return BackendKind::Niri;
+        }
+        let desktop = var("XDG_CURRENT_DESKTOP").unwrap_or_default().to_uppercase();
+        if desktop.split(':').any(|d| d == "RIVER") {
+            return BackendKind::River;
+        }
+        if desktop.split(':').any(|d| d == "COSMIC") {
+            return BackendKind::Cosmic;
+        }
+        if desktop.split(':').any(|d| d == "KDE") || var("KDE_FULL_SESSION").is_some() {
+            return BackendKind::KWin;
+        }
+        if desktop.split(':').any(|d| d == "GNOME") {
+            return BackendKind::Gnome;
+        }
+        if var("WAYLAND_DISPLAY").is_none() && var("DISPLAY").is_some() {
+            return BackendKind::X11;
+        }
+        // Unknown Wayland session: KWin's D-Bus API is the most common, and
+        // every KWin query fails fast elsewhere
+        BackendKind::KWin
+    }
+
+    /// Create the backend
+    pub fn create(self) -> Box<dyn CompositorBackend> {
+        match self {
+            BackendKind::KWin => Box::new(KWinBackend),
+            BackendKind::Hyprland => Box::new(HyprlandBackend),
+            BackendKind::Sway => Box::new(SwayBackend),
+            BackendKind::River => Box::new(RiverBackend),
+            BackendKind::Niri => Box::new(NiriBackend),
+            BackendKind::Cosmic => Box::new(CosmicBackend),
+            BackendKind::Gnome => Box::new(GnomeBackend),
+            BackendKind::X11 => Box::new(X11Backend),
+        }
+    }
+}
+
+/// The process-wide backend, detected on first use
+static BACKEND: OnceLock<Box<dyn CompositorBackend>> = OnceLock::new();
+
+/// Get the compositor backend for this session
+pub fn backend() -> &'static dyn CompositorBackend {
+    BACKEND
+        .get_or_init(|| {
+            let kind = BackendKind::detect();
+            tracing::info!(?kind, "Compositor backend selected");
+            kind.create()
+        })
+        .as_ref()
+}
+
+// ============================================================================
+// KWin (KDE Plasma)
+// ============================================================================
+
+/// KDE Plasma (KWin)
+pub struct KWinBackend;
+
+impl CompositorBackend for KWinBackend {
+    fn name(&self) -> &'static str {
+        "kwin"
+    }
+
+    fn cursor_pos(&self) -> Option<CursorPosition> {
+        cursor::get_cursor_via_kwin_dbus().or_else(cursor::get_cursor_via_xdotool)
+    }
+
+    fn screen_bounds(&self) -> Option<ScreenBounds> {
+        cursor::get_screen_via_xrandr().or_else(cursor::get_screen_via_xdotool)
+    }
+
+    /// KWin is queried asynchronously over D-Bus by `WindowTracker`
+    fn active_window(&self) -> Option<String> {
+        None
+    }
+
+    fn tracks_active_window(&self) -> bool {
+        false
+    }
+
+    /// Have the loaded KWin script call `ShowMenuAtCursor` with `workspace.cursorPos`
+    ///
+    /// Correct on Plasma 6 Wayland with multiple monitors, unlike
+    /// xdotool/XWayland which clamps the cursor to a single screen.
+    /// See [`crate::kwin_scripts`].
+    fn show_overlay_hint(&self) -> bool {
+        crate::kwin_scripts::show_menu_at_cursor()
+    }
+
+    /// Zoom effect D-Bus interface (Plasma 6), else its global shortcuts
+    fn zoom(&self, step: ZoomStep) -> bool {
+        let method = match step {
+            ZoomStep::In => "zoomIn",
+            ZoomStep::Out => "zoomOut",
+            ZoomStep::Toggle if kwin_zoom_factor().is_some_and(|f| f > 1.0) => "actualSize",
+            ZoomStep::Toggle => "zoomIn",
+        };
+        let effect = Command::new("dbus-send")
+            .args([
+                "--session",
+                "--print-reply",
+                "--dest=org.kde.KWin",
+                KWIN_ZOOM_PATH,
+                &format!("{}.{}", KWIN_ZOOM_INTERFACE, method),
+            ])
+            .output();
+        if effect.is_ok_and(|output| output.status.success()) {
+            return true;
+        }
+
+        let shortcut = match method {
+            "zoomIn" => "view_zoom_in",
+            "zoomOut" => "view_zoom_out",
+            _ => "view_actual_size",
+        };
+        Command::new("dbus-send")
+            .args([
+                "--session",
+                "--print-reply",
+                "--dest=org.kde.kglobalaccel",
+                "/component/kwin",
+                "org.kde.kglobalaccel.Component.invokeShortcut",
+                &format!("string:{}", shortcut),
+            ])
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+}
+
+/// KWin zoom effect D-Bus object and interface
+const KWIN_ZOOM_PATH: &str = "/org/kde/KWin/Effect/Zoom1";
+const KWIN_ZOOM_INTERFACE: &str = "org.kde.kwin.Effect.Zoom1";
+
+/// Current zoom of the KWin zoom effect
+fn kwin_zoom_factor() -> Option<f64> {
+    let output = Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply",
+            "--dest=org.kde.KWin",
+            KWIN_ZOOM_PATH,
+            "org.freedesktop.DBus.Properties.Get",
+            &format!("string:{}", KWIN_ZOOM_INTERFACE),
+            "string:zoomFactor",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // "   variant       double 1.5"
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .last()?
+        .parse()
+        .ok()
+}
+
+// ============================================================================
+// Hyprland
+// ============================================================================
+
+/// Hyprland (wlroots-style IPC)
+pub struct HyprlandBackend;
+
+impl CompositorBackend for HyprlandBackend {
+    fn name(&self) -> &'static str {
+        "hyprland"
+    }
+
+    fn cursor_pos(&self) -> Option<CursorPosition> {
+        cursor::get_cursor_via_hyprland()
+    }
+
+    fn screen_bounds(&self) -> Option<ScreenBounds> {
+        cursor::get_screen_via_hyprland()
+    }
+
+    fn active_window(&self) -> Option<String> {
+        let window = command_json("hyprctl", &["activewindow", "-j"])?;
+        non_empty_class(window.get("class")?.as_str()?)
+    }
+
+    fn focused_window(&self) -> Option<FocusedWindow> {
+        hyprland_focused_window(&command_json("hyprctl", &["activewindow", "-j"])?)
+    }
+
+    /// Waits for a monitor event on the event socket (`.socket2.sock`)
+    fn wait_screen_change(&self) -> bool {
+        use std::io::{BufRead, BufReader};
+        use std::os::unix::net::UnixStream;
+
+        let Ok(sig) = std::env::var("HYPRLAND_INSTANCE_SIGNATURE") else {
+            return false;
+        };
+        let xdg_runtime = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/run/user/1000".to_string());
+        let Ok(stream) = UnixStream::connect(format!("{}/hypr/{}/.socket2.sock", xdg_runtime, sig)) else {
+            return false;
+        };
+        BufReader::new(stream)
+            .lines()
+            .map_while(Result::ok)
+            .any(|line| is_hyprland_monitor_event(&line))
+    }
+
+    /// `cursor:zoom_factor` (`misc:cursor_zoom_factor` before Hyprland 0.38)
+    fn zoom(&self, step: ZoomStep) -> bool {
+        for option in ["cursor:zoom_factor", "misc:cursor_zoom_factor"] {
+            let Some(current) = command_json("hyprctl", &["getoption", option, "-j"])
+                .and_then(|value| value.get("float")?.as_f64())
+            else {
+                continue;
+            };
+            let factor = next_zoom_factor(current, step);
+            return Command::new("hyprctl")
+                .args(["keyword", option, &factor.to_string()])
+                .output()
+                .is_ok_and(|output| output.status.success());
+        }
+        false
+    }
+}
+
+/// Whether a Hyprland event socket line announces a monitor change
+pub fn is_hyprland_monitor_event(line: &str) -> bool {
+    let event = line.split(">>").next().unwrap_or_default();
+    matches!(
+        event,
+        "monitoradded" | "monitoraddedv2" | "monitorremoved" | "monitorremovedv2" | "configreloaded"
+    )
+}
+
+/// Geometry of `hyprctl activewindow -j`
+///
+/// `fullscreen` is a bool before Hyprland 0.42 and a mode (0 none,
+/// 1 maximized, 2 fullscreen) since.
+pub fn hyprland_focused_window(window: &serde_json::Value) -> Option<FocusedWindow> {
+    let pair = |name: &str| {
+        let values = window.get(name)?.as_array()?;
+        Some((values.first()?.as_i64()? as i32, values.get(1)?.as_i64()? as i32))
+    };
+    let (x, y) = pair("at")?;
+    let (width, height) = pair("size")?;
+    let fullscreen = match window.get("fullscreen") {
+        Some(serde_json::Value::Bool(b)) => *b,
+        Some(mode) => mode.as_i64().unwrap_or(0) == 2,
+        None => false,
+    };
+    Some(FocusedWindow { x, y, width, height, fullscreen })
+}
+
+// ============================================================================
+// Sway
+// ============================================================================
+
+/// Sway (i3 IPC via swaymsg)
+pub struct SwayBackend;
+
+impl CompositorBackend for SwayBackend {
+    fn name(&self) -> &'static str {
+        "sway"
+    }
+
+    /// Sway has no cursor query; XWayland reports it while over X11 windows
+    fn cursor_pos(&self) -> Option<CursorPosition> {
+        cursor::get_cursor_via_xdotool()
+    }
+
+    fn screen_bounds(&self) -> Option<ScreenBounds> {
+        let outputs = command_json("swaymsg", &["-t", "get_outputs", "-r"])?;
+        let rects = outputs
+            .as_array()?
+            .iter()
+            .filter(|output| output.get("active").and_then(|a| a.as_bool()).unwrap_or(true))
+            .filter_map(|output| json_rect(output.get("rect")?));
+        bounding_box(rects)
+    }
+
+    fn active_window(&self) -> Option<String> {
+        let tree = command_json("swaymsg", &["-t", "get_tree", "-r"])?;
+        sway_focused_class(&tree)
+    }
+
+    fn focused_window(&self) -> Option<FocusedWindow> {
+        sway_focused_window(&command_json("swaymsg", &["-t", "get_tree", "-r"])?)
+    }
+
+    /// `swaymsg -t subscribe` exits after the first output event
+    fn wait_screen_change(&self) -> bool {
+        Command::new("swaymsg")
+            .args(["-t", "subscribe", "[\"output\"]"])
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+}
+
+/// Find the focused node in a `swaymsg -t get_tree` tree and return its class
+///
+/// Wayland-native windows report `app_id`, XWayland windows
+/// `window_properties.class`.
+pub fn sway_focused_class(node: &serde_json::Value) -> Option<String> {
+    if node.get("focused").and_then(|f| f.as_bool()) == Some(true) {
+        let app_id = node.get("app_id").and_then(|a| a.as_str());
+        let class = node.pointer("/window_properties/class").and_then(|c| c.as_str());
+        return app_id.or(class).and_then(non_empty_class);
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node.get(*key)?.as_array())
+        .flatten()
+        .find_map(sway_focused_class)
+}
+
+/// Rect and fullscreen mode of the focused node in a sway tree
+pub fn sway_focused_window(node: &serde_json::Value) -> Option<FocusedWindow> {
+    if node.get("focused").and_then(|f| f.as_bool()) == Some(true) {
+        let (x, y, width, height) = json_rect(node.get("rect")?)?;
+        let fullscreen = node.get("fullscreen_mode").and_then(|m| m.as_i64()).unwrap_or(0) != 0;
+        return Some(FocusedWindow { x, y, width, height, fullscreen });
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node.get(*key)?.as_array())
+        .flatten()
+        .find_map(sway_focused_window)
+}
+
+// ============================================================================
+// River
+// ============================================================================
+
+/// River
+///
+/// riverctl only sends commands, so queries go through the generic wlroots
+/// protocol tools: `wlr-randr` (wlr-output-management) for outputs and
+/// `lswt` (foreign-toplevel-management) for the focused window.
+pub struct RiverBackend;
+
+impl CompositorBackend for RiverBackend {
+    fn name(&self) -> &'static str {
+        "river"
+    }
+
+    /// River has no cursor query; XWayland reports it while over X11 windows
+    fn cursor_pos(&self) -> Option<CursorPosition> {
+        cursor::get_cursor_via_xdotool()
+    }
+
+    fn screen_bounds(&self) -> Option<ScreenBounds> {
+        screen_via_wlr_randr()
+    }
+
+    fn active_window(&self) -> Option<String> {
+        active_window_via_lswt()
+    }
+}
+
+/// Screen bounds from `wlr-randr --json` (wlr-output-management)
+fn screen_via_wlr_randr() -> Option<ScreenBounds> {
+    let outputs = command_json("wlr-randr", &["--json"])?;
+    bounding_box(wlr_randr_rects(&outputs).into_iter())
+}
+
+/// Focused window from `lswt -j` (foreign-toplevel-management)
+fn active_window_via_lswt() -> Option<String> {
+    let toplevels = command_json("lswt", &["-j"])?;
+    lswt_activated_app_id(&toplevels)
+}
+
+/// Logical output rectangles from `wlr-randr --json` (enabled outputs only)
+///
+/// The logical size is the current mode divided by the output scale, with
+/// width and height swapped for 90/270 degree transforms.
+pub fn wlr_randr_rects(outputs: &serde_json::Value) -> Vec<(i32, i32, i32, i32)> {
+    let Some(outputs) = outputs.as_array() else {
+        return Vec::new();
+    };
+    outputs
+        .iter()
+        .filter(|output| output.get("enabled").and_then(|e| e.as_bool()).unwrap_or(false))
+        .filter_map(|output| {
+            let mode = output
+                .get("modes")?
+                .as_array()?
+                .iter()
+                .find(|m| m.get("current").and_then(|c| c.as_bool()) == Some(true))?;
+            let scale = output.get("scale").and_then(|s| s.as_f64()).filter(|s| *s > 0.0).unwrap_or(1.0);
+            let width = (mode.get("width")?.as_f64()? / scale).round() as i32;
+            let height = (mode.get("height")?.as_f64()? / scale).round() as i32;
+            let rotated = matches!(
+                output.get("transform").and_then(|t| t.as_str()),
+                Some("90" | "270" | "flipped-90" | "flipped-270")
+            );
+            let (width, height) = if rotated { (height, width) } else { (width, height) };
+            let position = output.get("position")?;
+            Some((position.get("x")?.as_i64()? as i32, position.get("y")?.as_i64()? as i32, width, height))
+        })
+        .collect()
+}
+
+/// App ID (lowercase) of the activated toplevel in `lswt -j` output
+pub fn lswt_activated_app_id(output: &serde_json::Value) -> Option<String> {
+    // lswt 2.x wraps the list in {"toplevels": [...]}; older versions print the list
+    let toplevels = output.get("toplevels").unwrap_or(output).as_array()?;
+    toplevels
+        .iter()
+        .find(|t| t.get("activated").and_then(|a| a.as_bool()) == Some(true))
+        .and_then(|t| t.get("app-id")?.as_str())
+        .and_then(non_empty_class)
+}
+
+// ============================================================================
+// Niri
+// ============================================================================
+
+/// Niri (JSON IPC socket at `$NIRI_SOCKET`)
+pub struct NiriBackend;
+
+impl NiriBackend {
+    /// Send one request and return its `Ok` payload
+    ///
+    /// Requests and replies are single JSON lines, e.g. `"FocusedWindow"` ->
+    /// `{"Ok":{"FocusedWindow":{...}}}`.
+    fn request(request: &str) -> Option<serde_json::Value> {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixStream;
+        use std::time::Duration;
+
+        let socket_path = std::env::var("NIRI_SOCKET").ok()?;
+        let mut stream = UnixStream::connect(socket_path).ok()?;
+        stream.set_read_timeout(Some(Duration::from_millis(100))).ok()?;
+        stream.set_write_timeout(Some(Duration::from_millis(100))).ok()?;
+        writeln!(stream, "\"{}\"", request).ok()?;
+
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).ok()?;
+        let mut reply: serde_json::Value = serde_json::from_str(&line).ok()?;
+        Some(reply.get_mut("Ok")?.get_mut(request)?.take())
+    }
+}
+
+impl CompositorBackend for NiriBackend {
+    fn name(&self) -> &'static str {
+        "niri"
+    }
+
+    /// Niri has no cursor query; XWayland reports it while over X11 windows
+    fn cursor_pos(&self) -> Option<CursorPosition> {
+        cursor::get_cursor_via_xdotool()
+    }
+
+    fn screen_bounds(&self) -> Option<ScreenBounds> {
+        let outputs = Self::request("Outputs")?;
+        bounding_box(niri_output_rects(&outputs).into_iter())
+    }
+
+    fn active_window(&self) -> Option<String> {
+        let window = Self::request("FocusedWindow")?;
+        non_empty_class(window.get("app_id")?.as_str()?)
+    }
+}
+
+/// Logical output rectangles from niri's `Outputs` reply (enabled outputs only)
+pub fn niri_output_rects(outputs: &serde_json::Value) -> Vec<(i32, i32, i32, i32)> {
+    let Some(outputs) = outputs.as_object() else {
+        return Vec::new();
+    };
+    // Disabled outputs have "logical": null
+    outputs
+        .values()
+        .filter_map(|output| json_rect(output.get("logical")?))
+        .collect()
+}
+
+// ============================================================================
+// COSMIC
+// ============================================================================
+
+/// System76 COSMIC (cosmic-comp)
+///
+/// cosmic-comp offers no D-Bus query for the cursor or the focused window;
+/// it implements the wlroots output-management and foreign-toplevel
+/// protocols (which cosmic-randr and the panel use), so the same tools as
+/// for River apply.
+pub struct CosmicBackend;
+
+impl CompositorBackend for CosmicBackend {
+    fn name(&self) -> &'static str {
+        "cosmic"
+    }
+
+    /// No cursor query; XWayland reports it while over X11 windows
+    fn cursor_pos(&self) -> Option<CursorPosition> {
+        cursor::get_cursor_via_xdotool()
+    }
+
+    fn screen_bounds(&self) -> Option<ScreenBounds> {
+        screen_via_wlr_randr().or_else(cursor::get_screen_via_xrandr)
+    }
+
+    fn active_window(&self) -> Option<String> {
+        active_window_via_lswt()
+    }
+}
+
+// ============================================================================
+// GNOME
+// ============================================================================
+
+/// GNOME Shell (Mutter)
+///
+/// Mutter exposes neither the cursor nor the focused window to other
+/// processes; only the XWayland fallbacks are available.
+pub struct GnomeBackend;
+
+impl CompositorBackend for GnomeBackend {
+    fn name(&self) -> &'static str {
+        "gnome"
+    }
+
+    fn cursor_pos(&self) -> Option<CursorPosition> {
+        cursor::get_cursor_via_xdotool()
+    }
+
+    fn screen_bounds(&self) -> Option<ScreenBounds> {
+        cursor::get_screen_via_xrandr()
+    }
+
+    fn active_window(&self) -> Option<String> {
+        None
+    }
+
+    fn tracks_active_window(&self) -> bool {
+        false
+    }
+
+    /// The accessibility magnifier via gsettings
+    fn zoom(&self, step: ZoomStep) -> bool {
+        let enabled = gsettings(&["get", GNOME_A11Y_APPLICATIONS, "screen-magnifier-enabled"])
+            .is_some_and(|o| String::from_utf8_lossy(&o.stdout).trim() == "true");
+        let current = if enabled {
+            gsettings(&["get", GNOME_MAGNIFIER, "mag-factor"])
+                .and_then(|o| String::from_utf8_lossy(&o.stdout).trim().parse().ok())
+                .unwrap_or(1.0)
+        } else {
+            1.0
+        };
+
+        let factor = next_zoom_factor(current, step);
+        let enable = if factor > 1.0 { "true" } else { "false" };
+        if factor > 1.0 && gsettings(&["set", GNOME_MAGNIFIER, "mag-factor", &factor.to_string()]).is_none() {
+            return false;
+        }
+        gsettings(&["set", GNOME_A11Y_APPLICATIONS, "screen-magnifier-enabled", enable]).is_some()
+    }
+}
+
+/// GNOME magnifier settings schemas
+const GNOME_A11Y_APPLICATIONS: &str = "org.gnome.desktop.a11y.applications";
+const GNOME_MAGNIFIER: &str = "org.gnome.desktop.a11y.magnifier";
+
+/// Run gsettings; the output if it succeeded
+fn gsettings(args: &[&str]) -> Option<std::process::Output> {
+    Command::new("gsettings")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+}
+
+// ============================================================================
+// X11
+// ============================================================================
+
+/// Plain X11 session (any window manager)
+pub struct X11Backend;
+
+impl CompositorBackend for X11Backend {
+    fn name(&self) -> &'static str {
+        "x11"
+    }
+
+    fn cursor_pos(&self) -> Option<CursorPosition> {
+        cursor::get_cursor_via_xdotool()
+    }
+
+    fn screen_bounds(&self) -> Option<ScreenBounds> {
+        cursor::get_screen_via_xrandr().or_else(cursor::get_screen_via_xdotool)
+    }
+
+    fn active_window(&self) -> Option<String> {
+        let output = Command::new("xdotool")
+            .args(["getactivewindow", "getwindowclassname"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        non_empty_class(String::from_utf8_lossy(&output.stdout).trim())
+    }
+
+    /// `xdotool getwindowgeometry` plus `_NET_WM_STATE` from xprop
+    fn focused_window(&self) -> Option<FocusedWindow> {
+        let output = Command::new("xdotool")
+            .args(["getactivewindow", "getwindowgeometry", "--shell"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let (id, mut window) = parse_xdotool_geometry(&String::from_utf8_lossy(&output.stdout))?;
+        window.fullscreen = Command::new("xprop")
+            .args(["-id", &id.to_string(), "_NET_WM_STATE"])
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains("_NET_WM_STATE_FULLSCREEN"));
+        Some(window)
+    }
+}
+
+/// Parse `xdotool getwindowgeometry --shell` into (window ID, geometry)
+pub fn parse_xdotool_geometry(output: &str) -> Option<(u64, FocusedWindow)> {
+    let field = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+            .and_then(|v| v.trim().parse::<i64>().ok())
+    };
+    let window = FocusedWindow {
+        x: field("X")? as i32,
+        y: field("Y")? as i32,
+        width: field("WIDTH")? as i32,
+        height: field("HEIGHT")? as i32,
+        fullscreen: false,
+    };
+    Some((field("WINDOW")? as u64, window))
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Run a command and parse its stdout as JSON
+fn command_json(program: &str, args: &[&str]) -> Option<serde_json::Value> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+/// Lowercase window class, None if empty
+fn non_empty_class(class: &str) -> Option<String> {
+    (!class.is_empty()).then(|| class.to_lowercase())
+}
+
+/// Parse `{"x", "y", "width", "height"}`
+fn json_rect(rect: &serde_json::Value) -> Option<(i32, i32, i32, i32)> {
+    let field = |name: &str| rect.get(name)?.as_i64().map(|v| v as i32);
+    Some((field("x")?, field("y")?, field("width")?, field("height")?))
+}
+
+/// Bounding box of output rectangles (x, y, width, height)
+pub fn bounding_box(rects: impl Iterator<Item = (i32, i32, i32, i32)>) -> Option<ScreenBounds> {
+    let (width, height) = rects.fold((0, 0), |(w, h), (x, y, width, height)| {
+        (w.max(x + width), h.max(y + height))
+    });
+    (width > 0 && height > 0).then_some(ScreenBounds { width, height })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_backend() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        assert_eq!(BackendKind::detect_from(env(&[("HYPRLAND_INSTANCE_SIGNATURE", "abc")])), BackendKind::Hyprland);
+        assert_eq!(BackendKind::detect_from(env(&[("SWAYSOCK", "/run/sway.sock")])), BackendKind::Sway);
+        assert_eq!(BackendKind::detect_from(env(&[("NIRI_SOCKET", "/run/niri.sock")])), BackendKind::Niri);
+        assert_eq!(BackendKind::detect_from(env(&[("XDG_CURRENT_DESKTOP", "river")])), BackendKind::River);
+        assert_eq!(BackendKind::detect_from(env(&[("XDG_CURRENT_DESKTOP", "COSMIC")])), BackendKind::Cosmic);
+        assert_eq!(BackendKind::detect_from(env(&[("XDG_CURRENT_DESKTOP", "KDE")])), BackendKind::KWin);
+        assert_eq!(BackendKind::detect_from(env(&[("XDG_CURRENT_DESKTOP", "ubuntu:GNOME")])), BackendKind::Gnome);
+        assert_eq!(BackendKind::detect_from(env(&[("DISPLAY", ":0")])), BackendKind::X11);
+        assert_eq!(BackendKind::detect_from(env(&[("WAYLAND_DISPLAY", "wayland-0")])), BackendKind::KWin);
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bounds = bounding_box([(0, 0, 2560, 1440), (2560, 200, 1920, 1080)].into_iter()).unwrap();
+        assert_eq!((bounds.width, bounds.height), (4480, 1440));
+        assert!(bounding_box(std::iter::empty()).is_none());
+    }
+
+    #[test]
+    fn test_river_and_niri_outputs() {
+        let wlr_randr = serde_json::json!([
+            {"name": "eDP-1", "enabled": true, "scale": 2.0, "transform": "normal", "position": {"x": 0, "y": 0},
+             "modes": [{"width": 2880, "height": 1800, "current": true}]},
+            {"name": "DP-1", "enabled": true, "scale": 1.0, "transform": "90", "position": {"x": 1440, "y": 0},
+             "modes": [{"width": 1920, "height": 1080, "current": false}, {"width": 2560, "height": 1440, "current": true}]},
+            {"name": "HDMI-A-1", "enabled": false, "position": {"x": 0, "y": 0}, "modes": []}
+        ]);
+        assert_eq!(wlr_randr_rects(&wlr_randr), vec![(0, 0, 1440, 900), (1440, 0, 1440, 2560)]);
+
+        let niri = serde_json::json!({
+            "eDP-1": {"name": "eDP-1", "logical": {"x": 0, "y": 0, "width": 1280, "height": 800, "scale": 1.5}},
+            "DP-2": {"name": "DP-2", "logical": null}
+        });
+        assert_eq!(niri_output_rects(&niri), vec![(0, 0, 1280, 800)]);
+
+        let lswt = serde_json::json!({"toplevels": [
+            {"title": "foot", "app-id": "foot", "activated": false},
+            {"title": "Firefox", "app-id": "Firefox", "activated": true}
+        ]});
+        assert_eq!(lswt_activated_app_id(&lswt), Some("firefox".to_string()));
+    }
+
+    #[test]
+    fn test_focused_window_geometry() {
+        let hypr = serde_json::json!({"class": "cs2", "at": [1920, 0], "size": [2560, 1440], "fullscreen": 2});
+        let window = hyprland_focused_window(&hypr).unwrap();
+        assert_eq!(window, FocusedWindow { x: 1920, y: 0, width: 2560, height: 1440, fullscreen: true });
+        assert_eq!(window.center(), CursorPosition::new(3200, 720));
+        assert!(window.contains(CursorPosition::new(1920, 0)));
+        assert!(!window.contains(CursorPosition::new(4480, 100)));
+        let maximized = serde_json::json!({"at": [0, 30], "size": [1920, 1050], "fullscreen": 1});
+        assert!(!hyprland_focused_window(&maximized).unwrap().fullscreen);
+        let legacy = serde_json::json!({"at": [0, 0], "size": [1920, 1080], "fullscreen": true});
+        assert!(hyprland_focused_window(&legacy).unwrap().fullscreen);
+
+        let tree = serde_json::json!({"focused": false, "nodes": [
+            {"focused": false, "rect": {"x": 0, "y": 0, "width": 960, "height": 1080}, "fullscreen_mode": 0},
+            {"focused": true, "rect": {"x": 960, "y": 0, "width": 960, "height": 1080}, "fullscreen_mode": 1}
+        ]});
+        assert_eq!(
+            sway_focused_window(&tree),
+            Some(FocusedWindow { x: 960, y: 0, width: 960, height: 1080, fullscreen: true })
+        );
+
+        let xdotool = "WINDOW=41943047\nX=0\nY=0\nWIDTH=1920\nHEIGHT=1080\nSCREEN=0\n";
+        let (id, window) = parse_xdotool_geometry(xdotool).unwrap();
+        assert_eq!(id, 41943047);
+        assert_eq!((window.width, window.height, window.fullscreen), (1920, 1080, false));
+    }
+
+    #[test]
+    fn test_sway_focused_class() {
+        let tree = serde_json::json!({
+            "focused": false,
+            "nodes": [{
+                "focused": false,
+                "nodes": [
+                    {"focused": false, "app_id": "foot"},
+                    {"focused": false, "nodes": [], "floating_nodes": [
+                        {"focused": true, "app_id": null, "window_properties": {"class": "GIMP"}}
+                    ]}
+                ]
+            }]
+        });
+        assert_eq!(sway_focused_class(&tree), Some("gimp".to_string()));
+    }
+
+    #[test]
+    fn test_next_zoom_factor() {
+        assert_eq!(next_zoom_factor(1.0, ZoomStep::In), 1.5);
+        assert_eq!(next_zoom_factor(MAX_ZOOM, ZoomStep::In), MAX_ZOOM);
+        assert_eq!(next_zoom_factor(1.2, ZoomStep::Out), 1.0);
+        assert_eq!(next_zoom_factor(1.0, ZoomStep::Toggle), TOGGLE_ZOOM);
+        assert_eq!(next_zoom_factor(3.5, ZoomStep::Toggle), 1.0);
+    }
+
+    #[test]
+    fn test_hyprland_monitor_events() {
+        assert!(is_hyprland_monitor_event("monitoradded>>DP-1"));
+        assert!(is_hyprland_monitor_event("monitorremovedv2>>1,DP-1,Dell U2720Q"));
+        assert!(!is_hyprland_monitor_event("focusedmon>>DP-1,2"));
+        assert!(!is_hyprland_monitor_event("activewindow>>foot,~"));
+    }
+}
@@ -4,125 +4,1397 @@
 //! Configuration is stored at `~/.config/juhradial/config.json`.
 
 use serde::{Deserialize, Serialize};
+use zbus::zvariant::Type;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::actions::Action;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Default config directory name
+const CONFIG_DIR: &str = "juhradial";
+
+/// Default config file name
+const CONFIG_FILE: &str = "config.json";
+
+// ============================================================================
+// Limits
+// ============================================================================
+//
+// Hard limits for numeric settings. Out-of-range values are clamped with a
+// warning when the config is loaded, so a typo cannot stall the event loop
+// (a 0 ms heartbeat timeout) or make the menu unusable (a 10 s debounce).
+
+/// Longest haptic debounce (milliseconds)
+pub const MAX_HAPTIC_DEBOUNCE_MS: u64 = 1000;
+
+/// Overlay heartbeat timeout range (milliseconds)
+pub const MIN_HEARTBEAT_TIMEOUT_MS: u64 = 500;
+pub const MAX_HEARTBEAT_TIMEOUT_MS: u64 = 60_000;
+
+/// Longest battery or metrics polling interval (seconds)
+pub const MAX_POLL_INTERVAL_SECS: u64 = 3600;
+
+/// DPI range for configured DPI values
+pub const MIN_DPI: u16 = 200;
+pub const MAX_DPI: u16 = 8000;
+
+/// Longest gesture button press filters (milliseconds)
+pub const MAX_PRESS_DEBOUNCE_MS: u64 = 2000;
+
+/// Multi-press interval range (milliseconds)
+pub const MIN_MULTI_PRESS_INTERVAL_MS: u64 = 100;
+pub const MAX_MULTI_PRESS_INTERVAL_MS: u64 = 1000;
+
+/// Longest cursor update interval (milliseconds; slower looks choppy)
+pub const MAX_CURSOR_UPDATE_INTERVAL_MS: u64 = 100;
+
+/// Long-hover delay range (milliseconds)
+pub const MIN_LONG_HOVER_MS: u64 = 200;
+pub const MAX_LONG_HOVER_MS: u64 = 10_000;
+
+/// Touchpad hold range (milliseconds)
+pub const MIN_TOUCHPAD_HOLD_MS: u64 = 100;
+pub const MAX_TOUCHPAD_HOLD_MS: u64 = 2000;
+
+/// HID++ retry limits (attempts, per-attempt timeout and backoff in milliseconds)
+pub const MAX_HIDPP_ATTEMPTS: u32 = 10;
+pub const MIN_HIDPP_TIMEOUT_MS: u64 = 50;
+pub const MAX_HIDPP_TIMEOUT_MS: u64 = 5000;
+pub const MAX_HIDPP_BACKOFF_MS: u64 = 5000;
+
+/// Clamp a setting into `min..=max`, warning if it was outside
+pub(crate) fn clamp_setting<T>(name: &str, value: &mut T, min: T, max: T)
+where
+    T: PartialOrd + Copy + std::fmt::Display,
+{
+    let clamped = if *value < min {
+        min
+    } else if *value > max {
+        max
+    } else {
+        return;
+    };
+    tracing::warn!(setting = name, value = %value, clamped = %clamped, "Config value out of range, clamped");
+    *value = clamped;
+}
+
+// ============================================================================
+// Haptic Configuration
+// ============================================================================
+
+/// Per-event haptic pattern overrides
+/// Pattern names match MX Master 4 waveform IDs from the HID++ spec
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct HapticEventConfig {
+    /// Pattern when menu appears (default: damp_state_change)
+    #[serde(default = "default_menu_appear")]
+    pub menu_appear: String,
+
+    /// Pattern when hovering over different slices (default: subtle_collision)
+    #[serde(default = "default_slice_change")]
+    pub slice_change: String,
+
+    /// Pattern when selecting an action (default: sharp_state_change)
+    #[serde(default = "default_confirm")]
+    pub confirm: String,
+
+    /// Pattern for invalid/blocked actions (default: angry_alert)
+    #[serde(default = "default_invalid")]
+    pub invalid: String,
+}
+
+fn default_menu_appear() -> String { "damp_state_change".to_string() }
+fn default_slice_change() -> String { "subtle_collision".to_string() }
+fn default_confirm() -> String { "sharp_state_change".to_string() }
+fn default_invalid() -> String { "angry_alert".to_string() }
+
+impl Default for HapticEventConfig {
+    fn default() -> Self {
+        Self {
+            menu_appear: default_menu_appear(),
+            slice_change: default_slice_change(),
+            confirm: default_confirm(),
+            invalid: default_invalid(),
+        }
+    }
+}
+
+impl HapticEventConfig {
+    /// Validate pattern names (no-op for now, could check against valid patterns)
+    pub fn validate(&mut self) {
+        // Pattern validation could be added here if needed
+    }
+}
+
+/// Daily window in which haptics are muted or reduced (local time)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct QuietHoursConfig {
+    /// Enable the quiet hours schedule
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Start of quiet hours, "HH:MM" (default: 22:00)
+    #[serde(default = "default_quiet_start")]
+    pub start: String,
+
+    /// End of quiet hours, "HH:MM" (default: 07:00); may be before `start`
+    #[serde(default = "default_quiet_end")]
+    pub end: String,
+
+    /// Play whisper_collision instead of muting haptics entirely
+    #[serde(default = "default_true")]
+    pub reduce_to_whisper: bool,
+}
+
+fn default_quiet_start() -> String { "22:00".to_string() }
+fn default_quiet_end() -> String { "07:00".to_string() }
+
+impl Default for QuietHoursConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: default_quiet_start(),
+            end: default_quiet_end(),
+            reduce_to_whisper: true,
+        }
+    }
+}
+
+impl QuietHoursConfig {
+    /// Reset unparsable times to their defaults
+    pub fn validate(&mut self) {
+        if crate::quiet_hours::parse_clock(&self.start).is_none() {
+            tracing::warn!(start = %self.start, "Invalid quiet hours start, using default");
+            self.start = default_quiet_start();
+        }
+        if crate::quiet_hours::parse_clock(&self.end).is_none() {
+            tracing::warn!(end = %self.end, "Invalid quiet hours end, using default");
+            self.end = default_quiet_end();
+        }
+    }
+}
+
+/// Token-bucket budget for haptic sends (see [`crate::haptic_budget`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct HapticRateLimitConfig {
+    /// Enforce the budget
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Sustained sends per second
+    #[serde(default = "default_haptic_rate")]
+    pub events_per_sec: u32,
+
+    /// Sends allowed back-to-back before the sustained rate applies
+    #[serde(default = "default_haptic_burst")]
+    pub burst: u32,
+}
+
+fn default_haptic_rate() -> u32 { 25 }
+fn default_haptic_burst() -> u32 { 8 }
+
+impl Default for HapticRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            events_per_sec: default_haptic_rate(),
+            burst: default_haptic_burst(),
+        }
+    }
+}
+
+impl HapticRateLimitConfig {
+    /// Clamp to a usable range
+    pub fn validate(&mut self) {
+        clamp_setting("haptics.rate_limit.events_per_sec", &mut self.events_per_sec, 1, 200);
+        clamp_setting("haptics.rate_limit.burst", &mut self.burst, 1, 100);
+    }
+}
+
+/// Haptic feedback configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct HapticConfig {
+    /// Enable haptic feedback
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Default haptic pattern (fallback when event-specific not set)
+    #[serde(default = "default_pattern")]
+    pub default_pattern: String,
+
+    /// Per-event pattern overrides
+    #[serde(default)]
+    pub per_event: HapticEventConfig,
+
+    /// Minimum time between pulses in milliseconds (general debounce)
+    #[serde(default = "default_debounce")]
+    pub debounce_ms: u64,
+
+    /// Minimum time between slice change haptics in milliseconds
+    /// Used to prevent rapid-fire feedback during fast cursor movement
+    #[serde(default = "default_slice_debounce")]
+    pub slice_debounce_ms: u64,
+
+    /// Time window for re-entry detection in milliseconds
+    /// Prevents duplicate haptic when cursor re-enters the same slice quickly
+    #[serde(default = "default_reentry_debounce")]
+    pub reentry_debounce_ms: u64,
+
+    /// Quiet hours schedule
+    #[serde(default)]
+    pub quiet_hours: QuietHoursConfig,
+
+    /// Budget for haptic sends over the HID++ link
+    #[serde(default)]
+    pub rate_limit: HapticRateLimitConfig,
+
+    /// Wait for the mouse to acknowledge each waveform and count the
+    /// outcome per pattern in metrics (adds one HID++ round trip per pulse)
+    #[serde(default)]
+    pub verify_ack: bool,
+}
+
+fn default_true() -> bool { true }
+fn default_pattern() -> String { "subtle_collision".to_string() }
+fn default_debounce() -> u64 { 20 }
+fn default_slice_debounce() -> u64 { 20 }
+fn default_reentry_debounce() -> u64 { 50 }
+
+impl Default for HapticConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_pattern: default_pattern(),
+            per_event: HapticEventConfig::default(),
+            debounce_ms: 20,
+            slice_debounce_ms: 20,
+            reentry_debounce_ms: 50,
+            quiet_hours: QuietHoursConfig::default(),
+            rate_limit: HapticRateLimitConfig::default(),
+            verify_ack: false,
+        }
+    }
+}
+
+impl HapticConfig {
+    /// Validate all values
+    pub fn validate(&mut self) {
+        self.per_event.validate();
+        self.quiet_hours.validate();
+        self.rate_limit.validate();
+        clamp_setting("haptics.debounce_ms", &mut self.debounce_ms, 0, MAX_HAPTIC_DEBOUNCE_MS);
+        clamp_setting("haptics.slice_debounce_ms", &mut self.slice_debounce_ms, 0, MAX_HAPTIC_DEBOUNCE_MS);
+        clamp_setting("haptics.reentry_debounce_ms", &mut self.reentry_debounce_ms, 0, MAX_HAPTIC_DEBOUNCE_MS);
+    }
+
+    /// Check if haptics are effectively disabled
+    pub fn is_disabled(&self) -> bool {
+        !self.enabled
+    }
+}
+
+// ============================================================================
+// Overlay Configuration
+// ============================================================================
+
+/// Overlay liveness monitoring configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct OverlayConfig {
+    /// Time without a heartbeat before the overlay is considered dead (milliseconds)
+    #[serde(default = "default_heartbeat_timeout")]
+    pub heartbeat_timeout_ms: u64,
+
+    /// Re-activate the overlay via its D-Bus service name when it dies
+    /// (the shipped overlay registers as `org.kde.juhradialmx.Overlay`)
+    #[serde(default)]
+    pub restart_on_crash: bool,
+}
+
+fn default_heartbeat_timeout() -> u64 { 5000 }
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_timeout_ms: default_heartbeat_timeout(),
+            restart_on_crash: false,
+        }
+    }
+}
+
+impl OverlayConfig {
+    /// Clamp the heartbeat timeout
+    pub fn validate(&mut self) {
+        clamp_setting(
+            "overlay.heartbeat_timeout_ms",
+            &mut self.heartbeat_timeout_ms,
+            MIN_HEARTBEAT_TIMEOUT_MS,
+            MAX_HEARTBEAT_TIMEOUT_MS,
+        );
+    }
+}
+
+// ============================================================================
+// Battery Saver Configuration
+// ============================================================================
+
+/// Power-saving policy applied while the mouse battery is low
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct BatterySaverConfig {
+    /// Enable the battery saver policy
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Enter power saving at or below this battery percentage (when not charging)
+    #[serde(default = "default_low_threshold")]
+    pub low_threshold: u8,
+
+    /// Leave power saving above this percentage (or as soon as charging starts)
+    #[serde(default = "default_restore_threshold")]
+    pub restore_threshold: u8,
+
+    /// Drop slice-change haptics while saving (menu/confirm pulses remain)
+    #[serde(default = "default_true")]
+    pub reduce_haptics: bool,
+
+    /// Battery poll interval while saving, in seconds
+    #[serde(default = "default_saver_poll_interval")]
+    pub poll_interval_secs: u64,
+
+    /// DPI to switch to while saving (0 = leave DPI unchanged)
+    #[serde(default)]
+    pub dpi: u16,
+}
+
+fn default_low_threshold() -> u8 { 15 }
+fn default_restore_threshold() -> u8 { 20 }
+fn default_saver_poll_interval() -> u64 { 60 }
+
+impl Default for BatterySaverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            low_threshold: default_low_threshold(),
+            restore_threshold: default_restore_threshold(),
+            reduce_haptics: true,
+            poll_interval_secs: default_saver_poll_interval(),
+            dpi: 0,
+        }
+    }
+}
+
+impl BatterySaverConfig {
+    /// Validate and clamp values
+    pub fn validate(&mut self) {
+        clamp_setting("battery_saver.low_threshold", &mut self.low_threshold, 0, 100);
+        clamp_setting("battery_saver.restore_threshold", &mut self.restore_threshold, self.low_threshold, 100);
+        clamp_setting("battery_saver.poll_interval_secs", &mut self.poll_interval_secs, 1, MAX_POLL_INTERVAL_SECS);
+        if self.dpi != 0 {
+            clamp_setting("battery_saver.dpi", &mut self.dpi, MIN_DPI, MAX_DPI);
+        }
+    }
+}
+
+// ============================================================================
+// Runtime Mode Configuration
+// ============================================================================
+
+/// How the daemon reaches input devices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuntimeMode {
+    /// Portal mode inside Flatpak, native otherwise
+    #[default]
+    Auto,
+    /// Direct evdev/hidraw access, logid and ydotool/xdotool
+    Native,
+    /// Sandbox-friendly: no /dev scanning, xdg-desktop-portal for trigger and key injection
+    Portal,
+}
+
+/// xdg-desktop-portal settings (used in portal mode)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct PortalConfig {
+    /// Preferred trigger for the GlobalShortcuts portal (XDG shortcut format, e.g. "F19")
+    /// The desktop may let the user pick a different one.
+    #[serde(default = "default_trigger_shortcut")]
+    pub trigger_shortcut: String,
+}
+
+fn default_trigger_shortcut() -> String { "F19".to_string() }
+
+impl Default for PortalConfig {
+    fn default() -> Self {
+        Self {
+            trigger_shortcut: default_trigger_shortcut(),
+        }
+    }
+}
+
+// ============================================================================
+// Metrics Configuration
+// ============================================================================
+
+/// Metrics exporter settings (requires the `metrics` build feature)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// Enable the metrics exporter
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Address for the Prometheus HTTP endpoint (empty = no HTTP server)
+    #[serde(default = "default_metrics_listen")]
+    pub listen: String,
+
+    /// node_exporter textfile-collector output file (empty = disabled)
+    #[serde(default)]
+    pub textfile_path: String,
+
+    /// How often the textfile is rewritten, in seconds
+    #[serde(default = "default_textfile_interval")]
+    pub textfile_interval_secs: u64,
+}
+
+fn default_metrics_listen() -> String { "127.0.0.1:9847".to_string() }
+fn default_textfile_interval() -> u64 { 15 }
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: default_metrics_listen(),
+            textfile_path: String::new(),
+            textfile_interval_secs: default_textfile_interval(),
+        }
+    }
+}
+
+impl MetricsConfig {
+    /// Clamp the textfile interval
+    pub fn validate(&mut self) {
+        clamp_setting("metrics.textfile_interval_secs", &mut self.textfile_interval_secs, 1, MAX_POLL_INTERVAL_SECS);
+    }
+}
+
+// ============================================================================
+// Usage Statistics Configuration
+// ============================================================================
+
+/// Local per-action usage statistics (opt-in)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageStatsConfig {
+    /// Record action counts and last-used times under XDG_STATE_HOME
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+// ============================================================================
+// Fast Path Configuration
+// ============================================================================
+
+/// Unix-socket stream of cursor/slice updates for overlays (opt-in)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FastPathConfig {
+    /// Listen on `$XDG_RUNTIME_DIR/juhradial/cursor.sock`
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+// ============================================================================
+// Press Debounce Configuration
+// ============================================================================
+
+/// Filtering of gesture button chatter (spurious press/release pairs)
+///
+/// Both are off (0) by default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PressDebounceConfig {
+    /// Minimum hold before the menu is shown; shorter press/release pairs are dropped (ms)
+    #[serde(default)]
+    pub min_hold_ms: u64,
+
+    /// Minimum time between closing a menu and the next press (ms)
+    #[serde(default)]
+    pub min_gap_ms: u64,
+}
+
+impl PressDebounceConfig {
+    /// Clamp both filters
+    pub fn validate(&mut self) {
+        clamp_setting("press_debounce.min_hold_ms", &mut self.min_hold_ms, 0, MAX_PRESS_DEBOUNCE_MS);
+        clamp_setting("press_debounce.min_gap_ms", &mut self.min_gap_ms, 0, MAX_PRESS_DEBOUNCE_MS);
+    }
+}
+
+// ============================================================================
+// Multi-Press Configuration
+// ============================================================================
+
+/// What a double or triple press of the gesture button does
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum PressBinding {
+    /// Behave like a single press (normal menu)
+    #[default]
+    None,
+    /// Show the window switcher ring while held
+    WindowSwitcher,
+    /// Run an action without showing the menu
+    Action(Box<Action>),
+}
+
+/// Double/triple press detection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiPressConfig {
+    /// Enable multi-press gestures
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Max tap length and max gap between taps (ms)
+    #[serde(default = "default_multi_press_interval")]
+    pub interval_ms: u64,
+
+    /// Double press binding
+    #[serde(default = "default_double_press")]
+    pub double: PressBinding,
+
+    /// Triple press binding
+    #[serde(default)]
+    pub triple: PressBinding,
+}
+
+fn default_multi_press_interval() -> u64 { 300 }
+fn default_double_press() -> PressBinding { PressBinding::WindowSwitcher }
+
+impl Default for MultiPressConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: default_multi_press_interval(),
+            double: default_double_press(),
+            triple: PressBinding::None,
+        }
+    }
+}
+
+impl MultiPressConfig {
+    /// Clamp the press interval
+    pub fn validate(&mut self) {
+        clamp_setting(
+            "multi_press.interval_ms",
+            &mut self.interval_ms,
+            MIN_MULTI_PRESS_INTERVAL_MS,
+            MAX_MULTI_PRESS_INTERVAL_MS,
+        );
+    }
+}
+
+// ============================================================================
+// Button Menus Configuration
+// ============================================================================
+
+/// An extra diverted button that opens one of the profile's `menus`
+///
+/// The button must be diverted to HID++ notifications (Solaar, or
+/// `native_divert`); see [`crate::hidraw`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ButtonMenuConfig {
+    /// HID++ control ID of the button (e.g. 196 for the mode shift button)
+    pub cid: u16,
+
+    /// Menu ID in the focused window's profile (`menus`)
+    pub menu: String,
+}
+
+/// Drop button menus that can never open: no CID, the gesture button's
+/// own CIDs, no menu ID, or a CID mapped twice (the first mapping wins)
+fn validate_button_menus(button_menus: &mut Vec<ButtonMenuConfig>) {
+    use crate::hidraw::button_cid;
+
+    let mut seen = std::collections::HashSet::new();
+    button_menus.retain(|b| {
+        let reason = if b.cid == 0 || b.cid == button_cid::GESTURE_BUTTON || b.cid == button_cid::HAPTIC {
+            "CID reserved for the main menu"
+        } else if b.menu.trim().is_empty() {
+            "no menu ID"
+        } else if !seen.insert(b.cid) {
+            "CID already mapped"
+        } else {
+            return true;
+        };
+        tracing::warn!(cid = b.cid, menu = %b.menu, "Ignoring button_menus entry: {}", reason);
+        false
+    });
+}
+
+// ============================================================================
+// Game Mode Configuration
+// ============================================================================
+
+/// What happens while a game is focused
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameModeResponse {
+    /// Ignore the gesture button entirely
+    #[default]
+    SuppressTrigger,
+    /// Keep the menu but ask overlays for a minimal, non-blurred theme
+    MinimalTheme,
+}
+
+impl GameModeResponse {
+    /// Name used in config and D-Bus signals
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameModeResponse::SuppressTrigger => "suppress_trigger",
+            GameModeResponse::MinimalTheme => "minimal_theme",
+        }
+    }
+}
+
+/// Game detection settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameModeConfig {
+    /// Enable game-mode detection
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Window classes always treated as games (case-insensitive)
+    #[serde(default)]
+    pub apps: Vec<String>,
+
+    /// Treat Steam games (`steam_app_*` window classes) as games
+    #[serde(default = "default_true")]
+    pub detect_steam: bool,
+
+    /// Treat an active Feral GameMode session as gaming
+    #[serde(default = "default_true")]
+    pub detect_gamemoded: bool,
+
+    /// Response while gaming
+    #[serde(default)]
+    pub response: GameModeResponse,
+}
+
+impl Default for GameModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            apps: Vec::new(),
+            detect_steam: true,
+            detect_gamemoded: true,
+            response: GameModeResponse::default(),
+        }
+    }
+}
+
+// ============================================================================
+// Notification Haptics Configuration
+// ============================================================================
+
+/// Notification urgency (freedesktop `urgency` hint)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationUrgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl NotificationUrgency {
+    /// Map the `urgency` hint byte (missing or unknown = normal)
+    pub fn from_hint(hint: Option<u8>) -> Self {
+        match hint {
+            Some(0) => NotificationUrgency::Low,
+            Some(2) => NotificationUrgency::Critical,
+            _ => NotificationUrgency::Normal,
+        }
+    }
+}
+
+/// Haptic waveform for notifications matching an app name and/or urgency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationHapticRule {
+    /// Sending application name, case-insensitive (empty = any app)
+    #[serde(default)]
+    pub app_name: String,
+
+    /// Required urgency (None = any)
+    #[serde(default)]
+    pub urgency: Option<NotificationUrgency>,
+
+    /// Waveform to play (e.g. "jingle", "angry_alert")
+    pub pattern: String,
+}
+
+impl NotificationHapticRule {
+    fn new(app_name: &str, urgency: Option<NotificationUrgency>, pattern: &str) -> Self {
+        Self {
+            app_name: app_name.to_string(),
+            urgency,
+            pattern: pattern.to_string(),
+        }
+    }
+}
+
+/// Haptics for desktop notifications (see [`crate::notification_haptics`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationHapticsConfig {
+    /// Listen for notifications (opt-in; takes effect on daemon restart)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Rules checked in order; the first match decides the waveform
+    #[serde(default = "default_notification_rules")]
+    pub rules: Vec<NotificationHapticRule>,
+}
+
+fn default_notification_rules() -> Vec<NotificationHapticRule> {
+    vec![
+        NotificationHapticRule::new("", Some(NotificationUrgency::Critical), "angry_alert"),
+        NotificationHapticRule::new("discord", None, "jingle"),
+        NotificationHapticRule::new("slack", None, "jingle"),
+        NotificationHapticRule::new("signal", None, "jingle"),
+        NotificationHapticRule::new("telegram desktop", None, "jingle"),
+        NotificationHapticRule::new("element", None, "jingle"),
+    ]
+}
+
+impl Default for NotificationHapticsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rules: default_notification_rules(),
+        }
+    }
+}
+
+impl NotificationHapticsConfig {
+    /// Drop rules with unknown waveform names
+    pub fn validate(&mut self) {
+        self.rules.retain(|rule| {
+            let known = crate::hidpp::Mx4HapticPattern::parse_name(&rule.pattern).is_some();
+            if !known {
+                tracing::warn!(pattern = %rule.pattern, "Ignoring notification rule with unknown haptic pattern");
+            }
+            known
+        });
+    }
+}
+
+// ============================================================================
+// Slice Geometry Configuration
+// ============================================================================
+
+/// Quarter-turn rotation of the whole menu (e.g. for portrait monitors)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MenuRotation {
+    /// Slice 0 where `start_angle_deg` puts it
+    #[default]
+    None,
+    /// Turned 90 degrees clockwise
+    Clockwise,
+    /// Turned 180 degrees
+    UpsideDown,
+    /// Turned 90 degrees counter-clockwise
+    CounterClockwise,
+}
+
+impl MenuRotation {
+    /// Rotation in degrees, clockwise
+    pub fn degrees(&self) -> f32 {
+        match self {
+            MenuRotation::None => 0.0,
+            MenuRotation::Clockwise => 90.0,
+            MenuRotation::UpsideDown => 180.0,
+            MenuRotation::CounterClockwise => 270.0,
+        }
+    }
+}
+
+/// Radial menu hit-testing (see [`crate::slice_geometry`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SliceGeometryConfig {
+    /// Center dead-zone radius where no slice is selected (pixels)
+    #[serde(default = "default_dead_zone_radius")]
+    pub dead_zone_radius: u32,
+
+    /// Angle of slice 0's center, degrees clockwise from north
+    #[serde(default)]
+    pub start_angle_deg: f32,
+
+    /// How far past its boundary a selected slice stays selected (degrees)
+    #[serde(default = "default_hysteresis")]
+    pub hysteresis_deg: f32,
+
+    /// Rotate the menu in quarter turns (added to `start_angle_deg`)
+    #[serde(default)]
+    pub rotation: MenuRotation,
+
+    /// Mirror slice order (counter-clockwise) for left-handed use
+    #[serde(default)]
+    pub left_handed: bool,
+}
+
+fn default_dead_zone_radius() -> u32 { crate::slice_geometry::DEFAULT_DEAD_ZONE_RADIUS }
+fn default_hysteresis() -> f32 { crate::slice_geometry::DEFAULT_HYSTERESIS_DEG }
+
+impl Default for SliceGeometryConfig {
+    fn default() -> Self {
+        Self {
+            dead_zone_radius: default_dead_zone_radius(),
+            start_angle_deg: 0.0,
+            hysteresis_deg: default_hysteresis(),
+            rotation: MenuRotation::default(),
+            left_handed: false,
+        }
+    }
+}
+
+impl SliceGeometryConfig {
+    /// Validate and clamp values
+    pub fn validate(&mut self) {
+        let max_radius = (crate::cursor::MENU_RADIUS / 2) as u32;
+        clamp_setting("slice_geometry.dead_zone_radius", &mut self.dead_zone_radius, 0, max_radius);
+        self.start_angle_deg = if self.start_angle_deg.is_finite() {
+            self.start_angle_deg.rem_euclid(360.0)
+        } else {
+            0.0
+        };
+        // Never let a slice cover half its neighbour
+        let max_hysteresis = 180.0 / crate::slice_geometry::SLICE_COUNT as f32;
+        if !self.hysteresis_deg.is_finite() {
+            self.hysteresis_deg = default_hysteresis();
+        }
+        clamp_setting("slice_geometry.hysteresis_deg", &mut self.hysteresis_deg, 0.0, max_hysteresis);
+    }
+}
+
+// ============================================================================
+// Menu Grab Configuration
+// ============================================================================
+
+/// Exclusive mouse grab while the menu is open (see [`crate::menu_grab`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MenuGrabConfig {
+    /// Grab the mouse and re-inject its events through a virtual device
+    /// (opt-in; takes effect when the mouse is next opened)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Also hold back the side buttons (back/forward) while the menu is open
+    #[serde(default = "default_true")]
+    pub block_side_buttons: bool,
+}
+
+impl Default for MenuGrabConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            block_side_buttons: true,
+        }
+    }
+}
+
+// ============================================================================
+// Evdev Trigger Configuration
+// ============================================================================
+
+/// Any evdev device and key as the trigger (e.g. a macro pad key or a
+/// trackball button) instead of the MX Master 4 gesture button
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvdevTriggerConfig {
+    /// Event device path (`/dev/input/by-id/...-event-mouse`, `/dev/input/event5`)
+    /// or device name as listed by `--list-devices`; empty = find the MX Master 4
+    #[serde(default)]
+    pub device: String,
+
+    /// Key name (`BTN_EXTRA`, `KEY_F13`) or numeric event code; empty = gesture button
+    #[serde(default)]
+    pub key: String,
+}
+
+impl EvdevTriggerConfig {
+    /// Trigger key codes
+    pub fn key_codes(&self) -> Vec<u16> {
+        match crate::evdev::parse_key_code(&self.key) {
+            Some(code) => vec![code],
+            None => crate::evdev::GESTURE_BUTTON_CODES.to_vec(),
+        }
+    }
+
+    /// Device selector, if one is configured
+    pub fn device(&self) -> Option<&str> {
+        Some(self.device.trim()).filter(|d| !d.is_empty())
+    }
+
+    /// Drop a key that is not a known key name or code
+    pub fn validate(&mut self) {
+        if !self.key.is_empty() && crate::evdev::parse_key_code(&self.key).is_none() {
+            tracing::warn!(key = %self.key, "evdev_trigger.key is not a known key, using the gesture button");
+            self.key.clear();
+        }
+    }
+}
+
+// ============================================================================
+// Touchpad Trigger Configuration
+// ============================================================================
+
+/// Three-finger press-and-hold on the touchpad (see [`crate::touchpad`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TouchpadTriggerConfig {
+    /// Open the menu on a three-finger hold (opt-in)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// How long three fingers have to rest before the menu opens (milliseconds)
+    #[serde(default = "default_touchpad_hold")]
+    pub hold_ms: u64,
+
+    /// Movement that turns the touch into a swipe instead of a hold (millimeters)
+    #[serde(default = "default_touchpad_travel")]
+    pub max_travel_mm: f32,
+
+    /// Cursor pixels per millimeter of finger movement while the menu is open
+    #[serde(default = "default_touchpad_sensitivity")]
+    pub pixels_per_mm: f32,
+}
+
+fn default_touchpad_hold() -> u64 { 300 }
+fn default_touchpad_travel() -> f32 { 3.0 }
+fn default_touchpad_sensitivity() -> f32 { 10.0 }
+
+impl Default for TouchpadTriggerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hold_ms: default_touchpad_hold(),
+            max_travel_mm: default_touchpad_travel(),
+            pixels_per_mm: default_touchpad_sensitivity(),
+        }
+    }
+}
+
+impl TouchpadTriggerConfig {
+    /// Validate and clamp values
+    pub fn validate(&mut self) {
+        clamp_setting("touchpad_trigger.hold_ms", &mut self.hold_ms, MIN_TOUCHPAD_HOLD_MS, MAX_TOUCHPAD_HOLD_MS);
+        if !self.max_travel_mm.is_finite() {
+            self.max_travel_mm = default_touchpad_travel();
+        }
+        clamp_setting("touchpad_trigger.max_travel_mm", &mut self.max_travel_mm, 0.5, 20.0);
+        if !self.pixels_per_mm.is_finite() {
+            self.pixels_per_mm = default_touchpad_sensitivity();
+        }
+        clamp_setting("touchpad_trigger.pixels_per_mm", &mut self.pixels_per_mm, 1.0, 100.0);
+    }
+}
+
+// ============================================================================
+// Stylus Trigger Configuration
+// ============================================================================
+
+/// Pen barrel button as a trigger (see [`crate::stylus`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StylusTriggerConfig {
+    /// Open the menu with the pen's upper barrel button (opt-in)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Keep the menu open after the button is released and select by
+    /// pressing the pen tip instead
+    #[serde(default)]
+    pub pressure_confirm: bool,
+
+    /// Tip pressure that confirms a selection (fraction of the pen's range)
+    #[serde(default = "default_stylus_pressure")]
+    pub pressure_threshold: f32,
+}
+
+fn default_stylus_pressure() -> f32 { 0.3 }
+
+impl Default for StylusTriggerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pressure_confirm: false,
+            pressure_threshold: default_stylus_pressure(),
+        }
+    }
+}
+
+impl StylusTriggerConfig {
+    /// Validate and clamp values
+    pub fn validate(&mut self) {
+        if !self.pressure_threshold.is_finite() {
+            self.pressure_threshold = default_stylus_pressure();
+        }
+        clamp_setting("stylus_trigger.pressure_threshold", &mut self.pressure_threshold, 0.05, 1.0);
+    }
+}
+
+// ============================================================================
+// Tap Passthrough Configuration
+// ============================================================================
+
+/// Re-injecting the button on quick taps (see [`crate::tap_passthrough`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TapPassthroughConfig {
+    /// Send the button to applications when a tap selects nothing
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Longest press still treated as a tap (ms)
+    #[serde(default = "default_max_tap")]
+    pub max_tap_ms: u64,
+
+    /// Linux key code sent on a tap (default BTN_BACK, the button's own code)
+    #[serde(default = "default_tap_key_code")]
+    pub key_code: u16,
+}
+
+fn default_max_tap() -> u64 { 200 }
+fn default_tap_key_code() -> u16 { crate::evdev::GESTURE_BUTTON_CODES[0] }
+
+impl Default for TapPassthroughConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_tap_ms: default_max_tap(),
+            key_code: default_tap_key_code(),
+        }
+    }
+}
+
+impl TapPassthroughConfig {
+    /// Clamp the tap length and reset key codes outside the key/button range
+    pub fn validate(&mut self) {
+        clamp_setting("tap_passthrough.max_tap_ms", &mut self.max_tap_ms, 50, 1000);
+        if self.key_code == 0 || self.key_code > crate::tap_passthrough::MAX_KEY_CODE {
+            tracing::warn!(key_code = self.key_code, "Invalid tap passthrough key code, using default");
+            self.key_code = default_tap_key_code();
+        }
+    }
+}
+
+// ============================================================================
+// Emoji Picker Configuration
+// ============================================================================
+
+/// Characters offered by the emoji submenu (see [`crate::emoji_picker`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmojiPickerConfig {
+    /// Emoji or special characters, one submenu item each
+    #[serde(default = "default_emoji_characters")]
+    pub characters: Vec<String>,
+
+    /// Paste the selection into the focused window (otherwise only copy it)
+    #[serde(default = "default_true")]
+    pub paste: bool,
+}
+
+fn default_emoji_characters() -> Vec<String> {
+    crate::emoji_picker::DEFAULT_CHARACTERS.iter().map(|c| c.to_string()).collect()
+}
+
+impl Default for EmojiPickerConfig {
+    fn default() -> Self {
+        Self {
+            characters: default_emoji_characters(),
+            paste: true,
+        }
+    }
+}
+
+impl EmojiPickerConfig {
+    /// Drop blank and overlong entries and keep what fits the submenu
+    pub fn validate(&mut self) {
+        self.characters.retain(|c| {
+            let len = c.trim().chars().count();
+            len > 0 && len <= crate::emoji_picker::MAX_ENTRY_CHARS
+        });
+        self.characters.truncate(crate::emoji_picker::MAX_ITEMS);
+        if self.characters.is_empty() {
+            self.characters = default_emoji_characters();
+        }
+    }
+}
+
+// ============================================================================
+// OCR Configuration
+// ============================================================================
+
+/// Text recognition for `ocr` actions (see [`crate::ocr`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrConfig {
+    /// Shell command printing the text of `{image}` on stdout
+    #[serde(default = "default_ocr_command")]
+    pub command: String,
+
+    /// Kill the OCR command after this many seconds
+    #[serde(default = "default_ocr_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_ocr_command() -> String {
+    "tesseract {image} -".to_string()
+}
+
+fn default_ocr_timeout() -> u64 { 30 }
+
+impl Default for OcrConfig {
+    fn default() -> Self {
+        Self {
+            command: default_ocr_command(),
+            timeout_secs: default_ocr_timeout(),
+        }
+    }
+}
+
+impl OcrConfig {
+    /// Clamp the timeout and restore the default command if none is set
+    pub fn validate(&mut self) {
+        clamp_setting("ocr.timeout_secs", &mut self.timeout_secs, 1, 300);
+        if !self.command.contains("{image}") {
+            tracing::warn!(command = %self.command, "OCR command has no {{image}} placeholder, using default");
+            self.command = default_ocr_command();
+        }
+    }
+}
+
+// ============================================================================
+// Bluetooth Configuration
+// ============================================================================
+
+/// Devices `bluetooth` actions can refer to by name (see [`crate::bluetooth`])
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BluetoothConfig {
+    /// Name -> MAC address, e.g. `"headphones": "AA:BB:CC:DD:EE:FF"`
+    #[serde(default)]
+    pub devices: BTreeMap<String, String>,
+}
+
+impl BluetoothConfig {
+    /// Normalize addresses to upper case and drop invalid ones
+    pub fn validate(&mut self) {
+        self.devices.retain(|name, mac| {
+            *mac = mac.trim().to_ascii_uppercase();
+            let valid = crate::bluetooth::is_valid_mac(mac);
+            if !valid {
+                tracing::warn!(device = %name, mac = %mac, "Invalid Bluetooth address, ignoring device");
+            }
+            valid
+        });
+    }
+}
+
 // ============================================================================
-// Constants
+// OBS Configuration
 // ============================================================================
 
-/// Default config directory name
-const CONFIG_DIR: &str = "juhradial";
+/// obs-websocket connection for `obs` actions (see [`crate::obs`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObsConfig {
+    /// Host OBS runs on
+    #[serde(default = "default_obs_host")]
+    pub host: String,
 
-/// Default config file name
-const CONFIG_FILE: &str = "config.json";
+    /// obs-websocket server port (Tools > WebSocket Server Settings)
+    #[serde(default = "default_obs_port")]
+    pub port: u16,
 
-// ============================================================================
-// Haptic Configuration
-// ============================================================================
+    /// Server password (empty if authentication is disabled)
+    #[serde(default)]
+    pub password: String,
 
-/// Per-event haptic pattern overrides
-/// Pattern names match MX Master 4 waveform IDs from the HID++ spec
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HapticEventConfig {
-    /// Pattern when menu appears (default: damp_state_change)
-    #[serde(default = "default_menu_appear")]
-    pub menu_appear: String,
+    /// Give up on OBS after this many seconds
+    #[serde(default = "default_obs_timeout")]
+    pub timeout_secs: u64,
+}
 
-    /// Pattern when hovering over different slices (default: subtle_collision)
-    #[serde(default = "default_slice_change")]
-    pub slice_change: String,
+fn default_obs_host() -> String {
+    "127.0.0.1".to_string()
+}
 
-    /// Pattern when selecting an action (default: sharp_state_change)
-    #[serde(default = "default_confirm")]
-    pub confirm: String,
+fn default_obs_port() -> u16 { 4455 }
 
-    /// Pattern for invalid/blocked actions (default: angry_alert)
-    #[serde(default = "default_invalid")]
-    pub invalid: String,
+fn default_obs_timeout() -> u64 { 5 }
+
+impl Default for ObsConfig {
+    fn default() -> Self {
+        Self {
+            host: default_obs_host(),
+            port: default_obs_port(),
+            password: String::new(),
+            timeout_secs: default_obs_timeout(),
+        }
+    }
 }
 
-fn default_menu_appear() -> String { "damp_state_change".to_string() }
-fn default_slice_change() -> String { "subtle_collision".to_string() }
-fn default_confirm() -> String { "sharp_state_change".to_string() }
-fn default_invalid() -> String { "angry_alert".to_string() }
+impl ObsConfig {
+    /// Clamp the timeout and restore a blank host or zero port
+    pub fn validate(&mut self) {
+        clamp_setting("obs.timeout_secs", &mut self.timeout_secs, 1, 30);
+        if self.host.trim().is_empty() {
+            self.host = default_obs_host();
+        }
+        if self.port == 0 {
+            self.port = default_obs_port();
+        }
+    }
+}
 
-impl Default for HapticEventConfig {
+// ============================================================================
+// Accessibility Configuration
+// ============================================================================
+
+/// Motion and contrast preferences, merged with the desktop's and the
+/// theme's (see [`crate::accessibility`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessibilityConfig {
+    /// Force reduced motion on or off (unset: follow the desktop)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reduced_motion: Option<bool>,
+
+    /// Force high contrast on or off (unset: follow the desktop)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub high_contrast: Option<bool>,
+
+    /// Multiplier for the theme's animation durations (2.0 = half speed)
+    #[serde(default = "default_animation_scale")]
+    pub animation_scale: f32,
+}
+
+fn default_animation_scale() -> f32 { 1.0 }
+
+impl Default for AccessibilityConfig {
     fn default() -> Self {
         Self {
-            menu_appear: default_menu_appear(),
-            slice_change: default_slice_change(),
-            confirm: default_confirm(),
-            invalid: default_invalid(),
+            reduced_motion: None,
+            high_contrast: None,
+            animation_scale: default_animation_scale(),
         }
     }
 }
 
-impl HapticEventConfig {
-    /// Validate pattern names (no-op for now, could check against valid patterns)
+impl AccessibilityConfig {
+    /// Keep the animation scale within 0.25-4.0
     pub fn validate(&mut self) {
-        // Pattern validation could be added here if needed
+        if !self.animation_scale.is_finite() {
+            self.animation_scale = default_animation_scale();
+        }
+        clamp_setting("accessibility.animation_scale", &mut self.animation_scale, 0.25, 4.0);
     }
 }
 
-/// Haptic feedback configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HapticConfig {
-    /// Enable haptic feedback
-    #[serde(default = "default_true")]
-    pub enabled: bool,
+// ============================================================================
+// Menu Layout Configuration
+// ============================================================================
 
-    /// Default haptic pattern (fallback when event-specific not set)
-    #[serde(default = "default_pattern")]
-    pub default_pattern: String,
+/// What the menu's slices show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MenuContent {
+    /// Icon and label
+    #[default]
+    Both,
+    /// Icons only (slices without an icon keep their label)
+    IconsOnly,
+    /// Labels only (slices without a label keep their icon)
+    LabelsOnly,
+}
 
-    /// Per-event pattern overrides
+/// Menu density for small screens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MenuLayoutConfig {
+    /// What `GetMenuLayout` includes for each slice
     #[serde(default)]
-    pub per_event: HapticEventConfig,
+    pub content: MenuContent,
 
-    /// Minimum time between pulses in milliseconds (general debounce)
-    #[serde(default = "default_debounce")]
-    pub debounce_ms: u64,
+    /// Draw the smaller menu (see [`crate::cursor::COMPACT_MENU_DIAMETER`])
+    #[serde(default)]
+    pub compact: bool,
+}
 
-    /// Minimum time between slice change haptics in milliseconds
-    /// Used to prevent rapid-fire feedback during fast cursor movement
-    #[serde(default = "default_slice_debounce")]
-    pub slice_debounce_ms: u64,
+impl MenuLayoutConfig {
+    /// Menu diameter in pixels
+    pub fn diameter(&self) -> i32 {
+        if self.compact {
+            crate::cursor::COMPACT_MENU_DIAMETER
+        } else {
+            crate::cursor::MENU_DIAMETER
+        }
+    }
 
-    /// Time window for re-entry detection in milliseconds
-    /// Prevents duplicate haptic when cursor re-enters the same slice quickly
-    #[serde(default = "default_reentry_debounce")]
-    pub reentry_debounce_ms: u64,
+    /// Menu radius in pixels (used for edge clamping)
+    pub fn radius(&self) -> i32 {
+        self.diameter() / 2
+    }
 }
 
-fn default_true() -> bool { true }
-fn default_pattern() -> String { "subtle_collision".to_string() }
-fn default_debounce() -> u64 { 20 }
-fn default_slice_debounce() -> u64 { 20 }
-fn default_reentry_debounce() -> u64 { 50 }
+// ============================================================================
+// HID++ Retry Configuration
+// ============================================================================
 
-impl Default for HapticConfig {
+/// Retry policy for HID++ requests (see [`crate::hidpp_retry`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HidppRetryConfig {
+    /// Attempts per request, including the first (1 = never retry)
+    #[serde(default = "default_retry_attempts")]
+    pub attempts: u32,
+
+    /// How long one attempt waits for its response (milliseconds)
+    #[serde(default = "default_retry_timeout")]
+    pub timeout_ms: u64,
+
+    /// Delay before the first retry, doubled for each further one (milliseconds)
+    #[serde(default = "default_retry_backoff")]
+    pub backoff_ms: u64,
+
+    /// Longest delay between attempts (milliseconds)
+    #[serde(default = "default_retry_max_backoff")]
+    pub max_backoff_ms: u64,
+
+    /// Fraction of each delay that is randomized (0.0-1.0)
+    #[serde(default = "default_retry_jitter")]
+    pub jitter: f32,
+}
+
+fn default_retry_attempts() -> u32 { 3 }
+fn default_retry_timeout() -> u64 { 1000 }
+fn default_retry_backoff() -> u64 { 50 }
+fn default_retry_max_backoff() -> u64 { 500 }
+fn default_retry_jitter() -> f32 { 0.5 }
+
+impl Default for HidppRetryConfig {
     fn default() -> Self {
         Self {
-            enabled: true,
-            default_pattern: default_pattern(),
-            per_event: HapticEventConfig::default(),
-            debounce_ms: 20,
-            slice_debounce_ms: 20,
-            reentry_debounce_ms: 50,
+            attempts: default_retry_attempts(),
+            timeout_ms: default_retry_timeout(),
+            backoff_ms: default_retry_backoff(),
+            max_backoff_ms: default_retry_max_backoff(),
+            jitter: default_retry_jitter(),
         }
     }
 }
 
-impl HapticConfig {
-    /// Validate all values
+impl HidppRetryConfig {
+    /// Validate and clamp values
     pub fn validate(&mut self) {
-        self.per_event.validate();
-    }
-
-    /// Check if haptics are effectively disabled
-    pub fn is_disabled(&self) -> bool {
-        !self.enabled
+        clamp_setting("hidpp_retry.attempts", &mut self.attempts, 1, MAX_HIDPP_ATTEMPTS);
+        clamp_setting("hidpp_retry.timeout_ms", &mut self.timeout_ms, MIN_HIDPP_TIMEOUT_MS, MAX_HIDPP_TIMEOUT_MS);
+        clamp_setting("hidpp_retry.backoff_ms", &mut self.backoff_ms, 0, MAX_HIDPP_BACKOFF_MS);
+        clamp_setting("hidpp_retry.max_backoff_ms", &mut self.max_backoff_ms, self.backoff_ms, MAX_HIDPP_BACKOFF_MS);
+        if !self.jitter.is_finite() {
+            self.jitter = default_retry_jitter();
+        }
+        clamp_setting("hidpp_retry.jitter", &mut self.jitter, 0.0, 1.0);
     }
 }
 
@@ -145,6 +1417,129 @@ pub struct Config {
     #[serde(default = "default_true")]
     pub blur_enabled: bool,
 
+    /// UI language ("system" = from the locale environment, or e.g. "de", "pt_BR")
+    #[serde(default = "default_language")]
+    pub language: String,
+
+    /// Overlay heartbeat and crash recovery settings
+    #[serde(default)]
+    pub overlay: OverlayConfig,
+
+    /// Minimum time between CursorMoved signals (milliseconds, 0 = every move)
+    #[serde(default = "default_cursor_update_interval")]
+    pub cursor_update_interval_ms: u64,
+
+    /// Hover time that arms a slice's alternate action (milliseconds)
+    #[serde(default = "default_long_hover")]
+    pub long_hover_ms: u64,
+
+    /// Native vs portal (Flatpak) operation
+    #[serde(default)]
+    pub mode: RuntimeMode,
+
+    /// Portal mode settings
+    #[serde(default)]
+    pub portal: PortalConfig,
+
+    /// Low-battery power-saving policy
+    #[serde(default)]
+    pub battery_saver: BatterySaverConfig,
+
+    /// Usage metrics exporter
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+
+    /// Per-action usage statistics
+    #[serde(default)]
+    pub usage_stats: UsageStatsConfig,
+
+    /// Gesture button chatter filtering
+    #[serde(default)]
+    pub press_debounce: PressDebounceConfig,
+
+    /// Double/triple press gestures
+    #[serde(default)]
+    pub multi_press: MultiPressConfig,
+
+    /// Extra diverted buttons that open other menus of the profile
+    #[serde(default)]
+    pub button_menus: Vec<ButtonMenuConfig>,
+
+    /// Game detection (suppress menu / minimal theme)
+    #[serde(default)]
+    pub game_mode: GameModeConfig,
+
+    /// Temporarily divert the gesture button over HID++ so Solaar/logid are
+    /// not needed (runtime-only, never persisted to the mouse)
+    #[serde(default)]
+    pub native_divert: bool,
+
+    /// Run without a Logitech mouse: no device handlers, haptics or battery;
+    /// the menu opens from the keyboard shortcut (`portal.trigger_shortcut`
+    /// via the GlobalShortcuts portal) or `ShowMenu` over D-Bus
+    #[serde(default)]
+    pub mouseless: bool,
+
+    /// Unix-socket fast path for cursor updates
+    #[serde(default)]
+    pub fast_path: FastPathConfig,
+
+    /// Dead zone, slice 0 angle and boundary hysteresis
+    #[serde(default)]
+    pub slice_geometry: SliceGeometryConfig,
+
+    /// Haptics for desktop notifications
+    #[serde(default)]
+    pub notification_haptics: NotificationHapticsConfig,
+
+    /// Keep back/forward from reaching applications while the menu is open
+    #[serde(default)]
+    pub menu_grab: MenuGrabConfig,
+
+    /// Custom evdev trigger device and key
+    #[serde(default)]
+    pub evdev_trigger: EvdevTriggerConfig,
+
+    /// Three-finger hold on the touchpad as a trigger
+    #[serde(default)]
+    pub touchpad_trigger: TouchpadTriggerConfig,
+
+    /// Pen barrel button as a trigger
+    #[serde(default)]
+    pub stylus_trigger: StylusTriggerConfig,
+
+    /// Quick taps that select nothing keep the button's normal function
+    #[serde(default)]
+    pub tap_passthrough: TapPassthroughConfig,
+
+    /// Emoji/character submenu
+    #[serde(default)]
+    pub emoji_picker: EmojiPickerConfig,
+
+    /// Screen text recognition
+    #[serde(default)]
+    pub ocr: OcrConfig,
+
+    /// Named Bluetooth devices for quick-connect slices
+    #[serde(default)]
+    pub bluetooth: BluetoothConfig,
+
+    /// OBS Studio remote control
+    #[serde(default)]
+    pub obs: ObsConfig,
+
+    /// Reduced motion, high contrast and animation speed
+    #[serde(default)]
+    pub accessibility: AccessibilityConfig,
+
+    /// Icon/label density and compact menu size
+    #[serde(default)]
+    pub menu_layout: MenuLayoutConfig,
+
+    /// Retries for HID++ requests (battery, DPI, feature lookups)
+    #[serde(default)]
+    pub hidpp_retry: HidppRetryConfig,
+
     /// Configuration file path (not serialized)
     #[serde(skip)]
     pub config_path: Option<PathBuf>,
@@ -154,12 +1549,50 @@ fn default_theme() -> String {
     "catppuccin-mocha".to_string()
 }
 
+fn default_language() -> String {
+    crate::i18n::SYSTEM_LANGUAGE.to_string()
+}
+
+fn default_cursor_update_interval() -> u64 { 16 }
+
+fn default_long_hover() -> u64 { 800 }
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             haptics: HapticConfig::default(),
             theme: default_theme(),
             blur_enabled: true,
+            language: default_language(),
+            overlay: OverlayConfig::default(),
+            cursor_update_interval_ms: default_cursor_update_interval(),
+            long_hover_ms: default_long_hover(),
+            mode: RuntimeMode::default(),
+            portal: PortalConfig::default(),
+            battery_saver: BatterySaverConfig::default(),
+            metrics: MetricsConfig::default(),
+            usage_stats: UsageStatsConfig::default(),
+            press_debounce: PressDebounceConfig::default(),
+            multi_press: MultiPressConfig::default(),
+            button_menus: Vec::new(),
+            game_mode: GameModeConfig::default(),
+            native_divert: false,
+            mouseless: false,
+            fast_path: FastPathConfig::default(),
+            slice_geometry: SliceGeometryConfig::default(),
+            notification_haptics: NotificationHapticsConfig::default(),
+            menu_grab: MenuGrabConfig::default(),
+            evdev_trigger: EvdevTriggerConfig::default(),
+            touchpad_trigger: TouchpadTriggerConfig::default(),
+            stylus_trigger: StylusTriggerConfig::default(),
+            tap_passthrough: TapPassthroughConfig::default(),
+            emoji_picker: EmojiPickerConfig::default(),
+            ocr: OcrConfig::default(),
+            bluetooth: BluetoothConfig::default(),
+            obs: ObsConfig::default(),
+            accessibility: AccessibilityConfig::default(),
+            menu_layout: MenuLayoutConfig::default(),
+            hidpp_retry: HidppRetryConfig::default(),
             config_path: None,
         }
     }
@@ -198,9 +1631,10 @@ impl Config {
         // If file doesn't exist, return defaults
         if !path.exists() {
             tracing::info!(path = %path.display(), "Config file not found, using defaults");
-            let mut config = Self::default();
-            config.config_path = Some(path.to_path_buf());
-            return Ok(config);
+            return Ok(Self {
+                config_path: Some(path.to_path_buf()),
+                ..Self::default()
+            });
         }
 
         // Read and parse the file
@@ -209,7 +1643,7 @@ impl Config {
             serde_json::from_str(&contents).map_err(ConfigError::ParseError)?;
 
         // Validate and clamp values
-        config.haptics.validate();
+        config.validate();
         config.config_path = Some(path.to_path_buf());
 
         tracing::info!(
@@ -223,6 +1657,31 @@ impl Config {
         Ok(config)
     }
 
+    /// Validate and clamp every section
+    pub fn validate(&mut self) {
+        self.haptics.validate();
+        self.overlay.validate();
+        self.battery_saver.validate();
+        self.metrics.validate();
+        self.press_debounce.validate();
+        self.multi_press.validate();
+        validate_button_menus(&mut self.button_menus);
+        self.slice_geometry.validate();
+        self.notification_haptics.validate();
+        self.evdev_trigger.validate();
+        self.touchpad_trigger.validate();
+        self.stylus_trigger.validate();
+        self.tap_passthrough.validate();
+        self.emoji_picker.validate();
+        self.ocr.validate();
+        self.bluetooth.validate();
+        self.obs.validate();
+        self.accessibility.validate();
+        self.hidpp_retry.validate();
+        clamp_setting("cursor_update_interval_ms", &mut self.cursor_update_interval_ms, 0, MAX_CURSOR_UPDATE_INTERVAL_MS);
+        clamp_setting("long_hover_ms", &mut self.long_hover_ms, MIN_LONG_HOVER_MS, MAX_LONG_HOVER_MS);
+    }
+
     /// Save configuration to file
     pub fn save(&self) -> Result<(), ConfigError> {
         let path = match &self.config_path {
@@ -290,6 +1749,149 @@ pub fn load_shared_config() -> Result<SharedConfig, ConfigError> {
     Ok(Arc::new(RwLock::new(config)))
 }
 
+// ============================================================================
+// Dry-run Validation
+// ============================================================================
+
+/// How serious a [`ConfigIssue`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "lowercase")]
+#[zvariant(signature = "s")]
+pub enum IssueSeverity {
+    /// The file would be rejected
+    Error,
+    /// The file loads, but not quite as written
+    Warning,
+}
+
+/// A problem found by [`Config::check`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Type)]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    /// Dotted path of the value ("haptics.debounce_ms"); empty if unknown
+    pub path: String,
+    pub message: String,
+    /// Position of a parse error, 1-based (0 when not applicable)
+    pub line: u32,
+    pub column: u32,
+}
+
+impl ConfigIssue {
+    fn warning(path: &str, message: String) -> Self {
+        Self {
+            severity: IssueSeverity::Warning,
+            path: path.to_string(),
+            message,
+            line: 0,
+            column: 0,
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            IssueSeverity::Error => "error",
+            IssueSeverity::Warning => "warning",
+        };
+        write!(f, "{}", severity)?;
+        if self.line > 0 {
+            write!(f, " at {}:{}", self.line, self.column)?;
+        }
+        if !self.path.is_empty() {
+            write!(f, " in {}", self.path)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl Config {
+    /// Check config.json contents without loading or applying anything
+    ///
+    /// Reports malformed JSON and wrong value types as errors; keys the
+    /// daemon does not know (usually typos, which are otherwise silently
+    /// ignored), values validation would adjust and unknown themes as
+    /// warnings. An empty list means the file loads exactly as written.
+    pub fn check(json: &str) -> Vec<ConfigIssue> {
+        let parse_error = |e: serde_json::Error| ConfigIssue {
+            severity: IssueSeverity::Error,
+            path: String::new(),
+            message: e.to_string(),
+            line: e.line() as u32,
+            column: e.column() as u32,
+        };
+        let raw: serde_json::Value = match serde_json::from_str(json) {
+            Ok(raw) => raw,
+            Err(e) => return vec![parse_error(e)],
+        };
+        let parsed: Config = match serde_json::from_str(json) {
+            Ok(parsed) => parsed,
+            Err(e) => return vec![parse_error(e)],
+        };
+
+        let mut issues = Vec::new();
+        let known = serde_json::to_value(&parsed).unwrap_or_default();
+        unknown_keys(&raw, &known, "", &mut issues);
+
+        let mut validated = parsed.clone();
+        validated.validate();
+        let validated = serde_json::to_value(&validated).unwrap_or_default();
+        adjusted_values(&known, &validated, "", &mut issues);
+
+        if crate::theme::find_theme(&parsed.theme).is_none() {
+            issues.push(ConfigIssue::warning("theme", format!("No theme named {:?} is installed", parsed.theme)));
+        }
+        issues
+    }
+}
+
+/// Dotted path of `key` under `prefix`
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// Warn about keys in `raw` that did not survive parsing
+fn unknown_keys(raw: &serde_json::Value, known: &serde_json::Value, prefix: &str, issues: &mut Vec<ConfigIssue>) {
+    let (Some(raw), Some(known)) = (raw.as_object(), known.as_object()) else {
+        return;
+    };
+    for (key, value) in raw {
+        let path = join_path(prefix, key);
+        match known.get(key) {
+            Some(known) => unknown_keys(value, known, &path, issues),
+            // Unset optional values are not serialized
+            None if value.is_null() => {}
+            None => issues.push(ConfigIssue::warning(&path, "Unknown key, ignored".to_string())),
+        }
+    }
+}
+
+/// Warn about values that validation changed
+fn adjusted_values(
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+    prefix: &str,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    match (before.as_object(), after.as_object()) {
+        (Some(before), Some(after)) => {
+            for (key, value) in before {
+                if let Some(adjusted) = after.get(key) {
+                    adjusted_values(value, adjusted, &join_path(prefix, key), issues);
+                }
+            }
+        }
+        _ if before != after => {
+            issues.push(ConfigIssue::warning(prefix, format!("{} would be adjusted to {}", before, after)));
+        }
+        _ => {}
+    }
+}
+
 // ============================================================================
 // Error Types
 // ============================================================================
@@ -368,6 +1970,49 @@ mod tests {
         assert!(config.is_disabled());
     }
 
+    #[test]
+    fn test_overlay_config_defaults() {
+        let config: Config = serde_json::from_str(r#"{"overlay": {"restart_on_crash": true}}"#).unwrap();
+        assert_eq!(config.overlay.heartbeat_timeout_ms, 5000);
+        assert!(config.overlay.restart_on_crash);
+        assert!(!Config::default().overlay.restart_on_crash);
+    }
+
+    #[test]
+    fn test_battery_saver_validate() {
+        let mut saver: BatterySaverConfig =
+            serde_json::from_str(r#"{"low_threshold": 30, "restore_threshold": 10, "poll_interval_secs": 0}"#).unwrap();
+        saver.validate();
+        assert_eq!(saver.low_threshold, 30);
+        assert_eq!(saver.restore_threshold, 30);
+        assert_eq!(saver.poll_interval_secs, 1);
+        assert!(saver.enabled);
+        assert_eq!(saver.dpi, 0);
+    }
+
+    #[test]
+    fn test_slice_geometry_validate() {
+        let mut geometry: SliceGeometryConfig =
+            serde_json::from_str(r#"{"dead_zone_radius": 500, "start_angle_deg": -90, "hysteresis_deg": 90}"#).unwrap();
+        geometry.validate();
+        assert_eq!(geometry.dead_zone_radius, 70);
+        assert_eq!(geometry.start_angle_deg, 270.0);
+        assert_eq!(geometry.hysteresis_deg, 22.5);
+
+        let rotated: SliceGeometryConfig =
+            serde_json::from_str(r#"{"rotation": "counter_clockwise", "left_handed": true}"#).unwrap();
+        assert_eq!(rotated.rotation.degrees(), 270.0);
+        assert!(rotated.left_handed);
+    }
+
+    #[test]
+    fn test_runtime_mode_parsing() {
+        let config: Config = serde_json::from_str(r#"{"mode": "portal"}"#).unwrap();
+        assert_eq!(config.mode, RuntimeMode::Portal);
+        assert_eq!(Config::default().mode, RuntimeMode::Auto);
+        assert_eq!(config.portal.trigger_shortcut, "F19");
+    }
+
     #[test]
     fn test_config_json_parsing() {
         let json = r#"{
@@ -398,23 +2043,24 @@ mod tests {
         let config: Config = serde_json::from_str(json).unwrap();
 
         assert!(config.haptics.enabled);
-        assert_eq!(config.haptics.intensity, 50);
+        assert_eq!(config.haptics.default_pattern, "subtle_collision");
         assert_eq!(config.theme, "catppuccin-mocha");
+        assert!(!config.mouseless);
     }
 
     #[test]
-    fn test_zero_intensity_disables() {
-        let json = r#"{"haptics": {"intensity": 0}}"#;
+    fn test_enabled_false_disables() {
+        let json = r#"{"haptics": {"enabled": false}}"#;
         let config: Config = serde_json::from_str(json).unwrap();
 
-        assert!(!config.haptics_enabled()); // Effectively disabled
+        assert!(!config.haptics_enabled());
         assert!(config.haptics.is_disabled());
     }
 
     #[test]
     fn test_legacy_getters() {
         let config = Config::default();
-        assert_eq!(config.haptic_intensity(), 50);
+        assert_eq!(config.default_haptic_pattern(), "subtle_collision");
         assert!(config.haptics_enabled());
     }
 
@@ -425,7 +2071,63 @@ mod tests {
 
         // Should contain expected fields
         assert!(json.contains("haptics"));
-        assert!(json.contains("intensity"));
+        assert!(json.contains("default_pattern"));
         assert!(json.contains("catppuccin-mocha"));
     }
+
+    #[test]
+    fn test_button_menus_validated() {
+        let mut config: Config = serde_json::from_str(
+            r#"{"button_menus": [
+                {"cid": 196, "menu": "media"}, {"cid": 195, "menu": "apps"},
+                {"cid": 83, "menu": " "}, {"cid": 196, "menu": "apps"}
+            ]}"#,
+        )
+        .unwrap();
+        config.validate();
+        assert_eq!(config.button_menus, vec![ButtonMenuConfig { cid: 196, menu: "media".to_string() }]);
+    }
+
+    #[test]
+    fn test_pathological_values_clamped() {
+        let mut config: Config = serde_json::from_str(
+            r#"{"haptics": {"debounce_ms": 60000}, "overlay": {"heartbeat_timeout_ms": 0},
+                "battery_saver": {"dpi": 50000}, "multi_press": {"interval_ms": 1},
+                "long_hover_ms": 0, "cursor_update_interval_ms": 0}"#,
+        )
+        .unwrap();
+        config.validate();
+        assert_eq!(config.haptics.debounce_ms, MAX_HAPTIC_DEBOUNCE_MS);
+        assert_eq!(config.overlay.heartbeat_timeout_ms, MIN_HEARTBEAT_TIMEOUT_MS);
+        assert_eq!(config.battery_saver.dpi, MAX_DPI);
+        assert_eq!(config.multi_press.interval_ms, MIN_MULTI_PRESS_INTERVAL_MS);
+        assert_eq!(config.long_hover_ms, MIN_LONG_HOVER_MS);
+        // 0 = every move is a documented setting
+        assert_eq!(config.cursor_update_interval_ms, 0);
+
+        let mut defaults = Config::default();
+        defaults.validate();
+        assert_eq!(serde_json::to_value(&defaults).unwrap(), serde_json::to_value(Config::default()).unwrap());
+    }
+
+    #[test]
+    fn test_check_reports_without_applying() {
+        assert!(Config::check(r#"{"theme": "catppuccin-mocha"}"#).is_empty());
+
+        let broken = Config::check("{\n  \"theme\": \"nord\",\n}");
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].severity, IssueSeverity::Error);
+        assert_eq!(broken[0].line, 3);
+
+        let wrong_type = Config::check(r#"{"haptics": {"debounce_ms": "fast"}}"#);
+        assert!(matches!(&wrong_type[..], [issue] if issue.severity == IssueSeverity::Error));
+
+        let issues = Config::check(
+            r#"{"theme": "no-such-theme", "hapitcs": {}, "accessibility": {"animation_scale": 10.0, "reduced_motion": null}}"#,
+        );
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, ["hapitcs", "accessibility.animation_scale", "theme"]);
+        assert!(issues.iter().all(|i| i.severity == IssueSeverity::Warning));
+        assert_eq!(issues[1].to_string(), "warning in accessibility.animation_scale: 10.0 would be adjusted to 4.0");
+    }
 }
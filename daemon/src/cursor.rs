@@ -1,7 +1,8 @@
 //! Cursor position query module
 //!
 //! Provides cross-protocol cursor position retrieval for Wayland and X11.
-//! Supports Hyprland (wlroots), KDE Plasma (KWin), and X11 environments.
+//! The per-compositor query helpers here are combined by the backends in
+//! [`crate::compositor`].
 
 use std::process::Command;
 
@@ -14,8 +15,11 @@ pub const EDGE_MARGIN: i32 = 20;
 /// Menu radius (half of diameter)
 pub const MENU_RADIUS: i32 = MENU_DIAMETER / 2;
 
+/// Menu diameter with `menu_layout.compact` (small screens)
+pub const COMPACT_MENU_DIAMETER: i32 = 200;
+
 /// Screen dimensions for edge clamping
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ScreenBounds {
     pub width: i32,
     pub height: i32,
@@ -32,7 +36,7 @@ impl Default for ScreenBounds {
 }
 
 /// Cursor position with coordinates
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct CursorPosition {
     pub x: i32,
     pub y: i32,
@@ -55,10 +59,15 @@ impl CursorPosition {
     /// # Returns
     /// New CursorPosition with clamped coordinates
     pub fn clamp_to_screen(&self, bounds: &ScreenBounds) -> Self {
-        let min_x = EDGE_MARGIN + MENU_RADIUS;
-        let max_x = bounds.width - EDGE_MARGIN - MENU_RADIUS;
-        let min_y = EDGE_MARGIN + MENU_RADIUS;
-        let max_y = bounds.height - EDGE_MARGIN - MENU_RADIUS;
+        self.clamp_to_screen_with_radius(bounds, MENU_RADIUS)
+    }
+
+    /// Edge clamping for a menu of the given radius (e.g. the compact menu)
+    pub fn clamp_to_screen_with_radius(&self, bounds: &ScreenBounds, radius: i32) -> Self {
+        let min_x = EDGE_MARGIN + radius;
+        let max_x = bounds.width - EDGE_MARGIN - radius;
+        let min_y = EDGE_MARGIN + radius;
+        let max_y = bounds.height - EDGE_MARGIN - radius;
 
         Self {
             x: self.x.clamp(min_x, max_x),
@@ -69,36 +78,14 @@ impl CursorPosition {
 
 /// Get current cursor position
 ///
-/// Attempts to query cursor position using available methods:
-/// 1. Hyprland (wlroots-based Wayland) - via hyprctl
-/// 2. KWin scripting (Wayland) - most accurate for Plasma 6 Wayland multi-monitor
-/// 3. KWin D-Bus API (older Plasma versions)
-/// 4. xdotool fallback (X11)
-/// 5. Returns (0, 0) if all methods fail
+/// Queries the session's [`crate::compositor::CompositorBackend`].
+/// Returns (0, 0) if the backend cannot report the cursor.
 pub fn get_cursor_position() -> CursorPosition {
-    // Try Hyprland first (wlroots-based Wayland compositor)
-    if let Some(pos) = get_cursor_via_hyprland() {
-        return pos;
-    }
-
-    // Try KWin scripting (works correctly on Plasma 6 Wayland multi-monitor)
-    if let Some(pos) = get_cursor_via_kwin_script() {
-        return pos;
-    }
-
-    // Try KWin D-Bus property (older Plasma versions)
-    if let Some(pos) = get_cursor_via_kwin_dbus() {
-        return pos;
-    }
-
-    // Try xdotool (works on X11)
-    if let Some(pos) = get_cursor_via_xdotool() {
-        return pos;
-    }
-
-    // Fallback: return placeholder
-    tracing::warn!("Could not query cursor position, using default (0, 0)");
-    CursorPosition::default()
+    let backend = crate::compositor::backend();
+    backend.cursor_pos().unwrap_or_else(|| {
+        tracing::warn!(backend = backend.name(), "Could not query cursor position, using default (0, 0)");
+        CursorPosition::default()
+    })
 }
 
 /// Query cursor position via Hyprland (wlroots-based Wayland compositor)
@@ -106,7 +93,7 @@ pub fn get_cursor_position() -> CursorPosition {
 /// Uses Hyprland IPC socket for fast cursor position retrieval.
 /// Falls back to hyprctl subprocess if socket fails.
 /// Only attempts if HYPRLAND_INSTANCE_SIGNATURE env var is set.
-fn get_cursor_via_hyprland() -> Option<CursorPosition> {
+pub(crate) fn get_cursor_via_hyprland() -> Option<CursorPosition> {
     // Only try if we're actually running on Hyprland
     let sig = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
 
@@ -169,77 +156,8 @@ fn get_cursor_via_hyprland_socket(sig: &str) -> Option<CursorPosition> {
     None
 }
 
-/// Query cursor position via KWin scripting (for Plasma 6 Wayland)
-///
-/// This method uses KWin's JavaScript scripting API to get the true cursor position,
-/// which works correctly across multiple monitors on Wayland.
-/// The script calls back to our daemon via D-Bus with the position.
-fn get_cursor_via_kwin_script() -> Option<CursorPosition> {
-    use std::io::Write;
-    use tempfile::Builder;
-
-    // Create KWin script that calls our D-Bus method with cursor position
-    let script = r#"
-var pos = workspace.cursorPos;
-callDBus("org.kde.juhradialmx", "/org/kde/juhradialmx/Daemon",
-         "org.kde.juhradialmx.Daemon", "ReportCursorPosition",
-         pos.x, pos.y);
-"#;
-
-    // Create a temporary file with .js suffix securely
-    let mut temp_file = Builder::new().suffix(".js").tempfile().ok()?;
-
-    // Write script to temp file
-    if write!(temp_file, "{}", script).is_err() {
-        return None;
-    }
-
-    // Get the path as a string
-    let script_path = temp_file.path().to_string_lossy();
-
-    // Load script via D-Bus
-    let load_output = Command::new("dbus-send")
-        .args([
-            "--session",
-            "--print-reply",
-            "--dest=org.kde.KWin",
-            "/Scripting",
-            "org.kde.kwin.Scripting.loadScript",
-            &format!("string:{}", script_path),
-        ])
-        .output()
-        .ok()?;
-
-    if !load_output.status.success() {
-        return None;
-    }
-
-    // Parse script ID from output
-    let stdout = String::from_utf8_lossy(&load_output.stdout);
-    let script_id: i32 = stdout
-        .lines()
-        .find(|line| line.contains("int32"))
-        .and_then(|line| line.split_whitespace().last())
-        .and_then(|s| s.parse().ok())?;
-
-    // Run the script
-    let _ = Command::new("dbus-send")
-        .args([
-            "--session",
-            "--print-reply",
-            "--dest=org.kde.KWin",
-            &format!("/Scripting/Script{}", script_id),
-            "org.kde.kwin.Script.run",
-        ])
-        .output();
-
-    // The script will call ReportCursorPosition which is handled by the daemon
-    // For now, we can't easily get the result synchronously, so fall back to other methods
-    None
-}
-
 /// Query cursor position via KWin D-Bus API (for Wayland)
-fn get_cursor_via_kwin_dbus() -> Option<CursorPosition> {
+pub(crate) fn get_cursor_via_kwin_dbus() -> Option<CursorPosition> {
     // Try various qdbus command names for different distros
     for cmd in &["qdbus-qt6", "qdbus6", "qdbus"] {
         // Try the cursorPos property (may not exist in all KWin versions)
@@ -268,7 +186,7 @@ fn get_cursor_via_kwin_dbus() -> Option<CursorPosition> {
 }
 
 /// Query cursor position via xdotool
-fn get_cursor_via_xdotool() -> Option<CursorPosition> {
+pub(crate) fn get_cursor_via_xdotool() -> Option<CursorPosition> {
     let output = Command::new("xdotool")
         .args(["getmouselocation", "--shell"])
         .output()
@@ -298,33 +216,32 @@ fn get_cursor_via_xdotool() -> Option<CursorPosition> {
 
 /// Get screen bounds
 ///
-/// Queries total screen dimensions across all monitors for edge clamping.
+/// Queries total screen dimensions across all monitors for edge clamping
+/// from the session's [`crate::compositor::CompositorBackend`]. With the
+/// `xdg-output` feature the compositor's own output layout is tried first.
 pub fn get_screen_bounds() -> ScreenBounds {
-    // Try Hyprland first (wlroots-based Wayland compositor)
-    if let Some(bounds) = get_screen_via_hyprland() {
-        return bounds;
-    }
-
-    // Try xrandr (supports multi-monitor, works on X11 and XWayland)
-    if let Some(bounds) = get_screen_via_xrandr() {
-        return bounds;
-    }
-
-    // Fallback to xdotool (single monitor)
-    if let Some(bounds) = get_screen_via_xdotool() {
-        return bounds;
+    #[cfg(feature = "xdg-output")]
+    match crate::xdg_output::query_monitors() {
+        Ok(monitors) => {
+            if let Some(bounds) = crate::xdg_output::screen_bounds(&monitors) {
+                return bounds;
+            }
+        }
+        Err(e) => tracing::debug!("xdg-output unavailable: {}", e),
     }
 
-    // Fallback to default
-    tracing::warn!("Could not query screen bounds, using default 1920x1080");
-    ScreenBounds::default()
+    let backend = crate::compositor::backend();
+    backend.screen_bounds().unwrap_or_else(|| {
+        tracing::warn!(backend = backend.name(), "Could not query screen bounds, using default 1920x1080");
+        ScreenBounds::default()
+    })
 }
 
 /// Query screen bounds via Hyprland (wlroots-based Wayland compositor)
 ///
 /// Uses `hyprctl monitors -j` to get monitor dimensions and calculates
 /// the bounding box of all monitors.
-fn get_screen_via_hyprland() -> Option<ScreenBounds> {
+pub(crate) fn get_screen_via_hyprland() -> Option<ScreenBounds> {
     // Only try hyprctl if we're actually running on Hyprland
     if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_err() {
         return None;
@@ -367,7 +284,7 @@ fn get_screen_via_hyprland() -> Option<ScreenBounds> {
 }
 
 /// Query screen bounds via xrandr (for multi-monitor support)
-fn get_screen_via_xrandr() -> Option<ScreenBounds> {
+pub(crate) fn get_screen_via_xrandr() -> Option<ScreenBounds> {
     let output = Command::new("xrandr")
         .output()
         .ok()?;
@@ -399,7 +316,7 @@ fn get_screen_via_xrandr() -> Option<ScreenBounds> {
 }
 
 /// Query screen bounds via xdotool (fallback, single monitor only)
-fn get_screen_via_xdotool() -> Option<ScreenBounds> {
+pub(crate) fn get_screen_via_xdotool() -> Option<ScreenBounds> {
     let output = Command::new("xdotool")
         .args(["getdisplaygeometry"])
         .output()
@@ -410,7 +327,7 @@ fn get_screen_via_xdotool() -> Option<ScreenBounds> {
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let parts: Vec<&str> = stdout.trim().split_whitespace().collect();
+    let parts: Vec<&str> = stdout.split_whitespace().collect();
 
     if parts.len() >= 2 {
         let width = parts[0].parse().ok()?;
@@ -479,6 +396,16 @@ mod tests {
         assert_eq!(clamped.y, 540); // Y unchanged
     }
 
+    #[test]
+    fn test_edge_clamping_compact_menu() {
+        let bounds = ScreenBounds { width: 1280, height: 720 };
+        let radius = COMPACT_MENU_DIAMETER / 2;
+        let clamped = CursorPosition::new(0, 720).clamp_to_screen_with_radius(&bounds, radius);
+
+        assert_eq!(clamped.x, EDGE_MARGIN + radius); // 120
+        assert_eq!(clamped.y, 720 - EDGE_MARGIN - radius); // 600
+    }
+
     #[test]
     fn test_menu_constants() {
         assert_eq!(MENU_DIAMETER, 280);
@@ -0,0 +1,115 @@
+//! Coalescing of CursorMoved updates
+//!
+//! evdev reports every REL_X/REL_Y delta, which during fast movement is far
+//! more often than the overlay can repaint. Moves are rate-limited to one
+//! signal per interval: the first move after a quiet period goes out
+//! immediately, later ones only update the pending position (latest wins)
+//! and are flushed when the interval has elapsed.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::time::{Duration, Instant};
+
+/// Rate limiter for cursor updates
+#[derive(Debug)]
+pub struct MoveCoalescer {
+    /// Minimum time between emitted updates (zero = no coalescing)
+    interval: Duration,
+    /// When the last update was emitted
+    last_emit: Option<Instant>,
+    /// Latest position not yet emitted
+    pending: Option<(i32, i32)>,
+}
+
+impl MoveCoalescer {
+    /// Create a coalescer emitting at most one update per `interval_ms`
+    pub fn new(interval_ms: u64) -> Self {
+        Self {
+            interval: Duration::from_millis(interval_ms),
+            last_emit: None,
+            pending: None,
+        }
+    }
+
+    /// Update the interval (config hot-reload)
+    pub fn set_interval(&mut self, interval_ms: u64) {
+        self.interval = Duration::from_millis(interval_ms);
+    }
+
+    /// Register a move; returns the position to emit now, if any
+    pub fn push(&mut self, x: i32, y: i32, now: Instant) -> Option<(i32, i32)> {
+        let due = self
+            .last_emit
+            .is_none_or(|last| now.duration_since(last) >= self.interval);
+        if due {
+            self.pending = None;
+            self.last_emit = Some(now);
+            Some((x, y))
+        } else {
+            self.pending = Some((x, y));
+            None
+        }
+    }
+
+    /// When the pending position should be flushed (None if nothing is pending)
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending?;
+        self.last_emit.map(|last| last + self.interval)
+    }
+
+    /// Take the pending position if its deadline has passed
+    pub fn flush(&mut self, now: Instant) -> Option<(i32, i32)> {
+        if self.deadline().is_some_and(|deadline| now >= deadline) {
+            self.last_emit = Some(now);
+            self.pending.take()
+        } else {
+            None
+        }
+    }
+
+    /// Take the pending position regardless of the deadline (e.g. on release)
+    pub fn take_pending(&mut self) -> Option<(i32, i32)> {
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_rate_limit_latest_wins() {
+        let mut coalescer = MoveCoalescer::new(16);
+        let t0 = Instant::now();
+
+        assert_eq!(coalescer.push(1, 1, t0), Some((1, 1)));
+        assert_eq!(coalescer.push(2, 2, t0 + ms(4)), None);
+        assert_eq!(coalescer.push(3, 3, t0 + ms(8)), None);
+        assert_eq!(coalescer.deadline(), Some(t0 + ms(16)));
+
+        assert_eq!(coalescer.flush(t0 + ms(10)), None);
+        assert_eq!(coalescer.flush(t0 + ms(16)), Some((3, 3)));
+        assert_eq!(coalescer.deadline(), None);
+
+        // Quiet period: next move goes out immediately
+        assert_eq!(coalescer.push(4, 4, t0 + ms(40)), Some((4, 4)));
+    }
+
+    #[test]
+    fn test_zero_interval_and_take_pending() {
+        let mut passthrough = MoveCoalescer::new(0);
+        let t0 = Instant::now();
+        assert_eq!(passthrough.push(1, 1, t0), Some((1, 1)));
+        assert_eq!(passthrough.push(2, 2, t0), Some((2, 2)));
+
+        let mut coalescer = MoveCoalescer::new(16);
+        coalescer.push(1, 1, t0);
+        coalescer.push(2, 2, t0 + ms(1));
+        assert_eq!(coalescer.take_pending(), Some((2, 2)));
+        assert_eq!(coalescer.flush(t0 + ms(50)), None);
+    }
+}
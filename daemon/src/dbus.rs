@@ -7,18 +7,78 @@
 //!
 //! ### Methods:
 //! - `ShowMenu(x: i32, y: i32)` - Display radial menu at coordinates
+//! - `ShowMenuWithMode(x: i32, y: i32, mode: String)` - Display an alternate menu (e.g. window switcher)
+//! - `ShowMenuById(x: i32, y: i32, menu_id: String)` - Display one of the profile's `menus` (extra button menus)
 //! - `HideMenu()` - Dismiss the radial menu
-//! - `ExecuteAction(action_id: String)` - Execute an action by ID
+//! - `ExecuteAction(action_id: String)` - Report a slice action the overlay ran itself
+//! - `UndoLastAction() -> b` - Run the inverse of the last reversible action, false if none
+//! - `SubmitTextEntry(text: String) -> b` / `CancelTextEntry() -> b` - Answer a `TextEntryRequested`, false if none pending
+//! - `GetTimer() -> (u, s)` / `CancelTimer() -> b` - Countdown timer of `start_timer` slices (remaining seconds, label)
+//! - `CancelRunningAction() -> u32` - Terminate still-running command actions that have a timeout, returns how many
+//! - `RegisterOverlay(service_name: String) -> u32` - Register overlay for liveness tracking
+//! - `Heartbeat()` - Overlay keep-alive
+//! - `AcknowledgeSignal(signal: String)` - Overlay confirms it handled a menu signal (e.g. "MenuRequested")
+//! - `RunSelfTest() -> a(sbs)` - Synthetic menu sequence, haptic and battery check (step, passed, detail)
+//! - `GetPermissionStatus() -> (b, b, b, b)` - udev rules / input group state
+//! - `InstallUdevRules()` - Install udev rules via pkexec + polkit
+//! - `GetActionStats() -> a(stt)` - Per-action (id, count, last_used), most used first
+//! - `GetRecentActions() -> a(sstbs)` - Last 20 executions (name, kind, time, success, error), newest first
+//! - `GetMenuLayout() -> s` - Profile JSON for the focused window, dynamic slices and alternates resolved, plus `geometry` (power profile and network slices marked `active`)
+//! - `GetSubmenu(provider: String) -> s` - Items of a built-in submenu ("emoji") as a JSON action array
+//! - `RunSubmenuItem(provider: String, index: u32)` - Run one of those items
+//! - `GetSliceGeometry() -> s` - Dead zone, slice 0 angle, mirroring and hysteresis as JSON (also in MenuReady)
+//! - `GetAnimationTimings() -> s` - Theme animation durations after reduced motion and `accessibility.animation_scale`, as JSON (also in MenuReady)
+//! - `GetDiagnostics() -> a(ssss)` - Detected setup problems (source, severity, code, message)
+//! - `GetDeviceInfo() -> s` - Connection type, link quality and HID++ link statistics as JSON
+//! - `GetCapabilities() -> as` - Optional features available right now (e.g. "haptics", "dpi")
+//! - `TestHaptic(pattern_name: String)` - Play any MX4 waveform (e.g. "happy_alert"), ignoring debounce
+//! - `DumpHidppAudit() -> a(tqyyay)` - Recent outgoing HID++ messages (time, feature, index, function, params)
+//! - `ChangePage(delta: i32) -> u32` - Step the open menu's page (wraps), returns the page shown
+//! - `SetPage(page: u32)` - Show a page of the open menu
+//! - `GetOnboardingProgress() -> (u, b)` / `SetOnboardingProgress(step: u32, complete: bool)`
+//! - `SetBlurAutoDisabled(disabled: bool)` - Overlay reports its automatic blur decision (persisted)
+//! - `ResetProfile(set: String) -> s` - Install a bundled starter profile set ("" = detected desktop), old file kept as `.bak`
 //!
 //! ### Signals:
 //! - `MenuRequested(x: i32, y: i32)` - Emitted when menu should appear
+//! - `MenuReady(x: i32, y: i32, payload: String)` - Precomputed layout + theme, sent just before MenuRequested
+//! - `MenuCancelled()` - Close the menu without an action (quick tap passed through to applications)
 //! - `SliceSelected(index: u8)` - Emitted when a slice is highlighted
 //! - `ActionExecuted(action_id: String)` - Emitted after action runs
+//! - `GameModeChanged(active: bool, response: String)` - Game detected / ended
+//! - `AlternateArmed(index: u8)` - Slice hovered past `long_hover_ms`; release runs its alternate
+//! - `PageChanged(page: u32, page_count: u32)` - The open menu switched pages
+//! - `TextEntryRequested(prompt: String)` - A `text_entry` slice needs text (answer with SubmitTextEntry)
+//! - `ScreenConfigurationChanged(width: i32, height: i32)` - Monitors were added, removed or rearranged
+//! - `ConnectionChanged(connection_type: String, quality: String)` - Mouse (dis)connected or link quality changed
+//! - `ActionHistoryChanged()` - An execution was recorded (refresh with GetRecentActions)
+//!
+//! ### Properties:
+//! - `CurrentProfile: s`, `HapticsEnabled: b`, `DaemonVersion: s`, `GameModeActive: b`
+//! - `ApiVersion: u` - Version of this interface (see [`DAEMON_API_VERSION`])
+//! - `FastPathSocket: s` - SOCK_SEQPACKET socket streaming cursor/slice updates (empty if disabled)
+
+use std::path::Path;
+use std::sync::Arc;
 
 use zbus::{interface, object_server::SignalEmitter, fdo};
+use crate::accessibility::{AccessibilitySettings, EffectiveAnimationTimings};
+use crate::actions::{ActionExecutor, ProviderContext};
 use crate::battery::SharedBatteryState;
-use crate::config::{Config, SharedConfig};
-use crate::hidpp::{SharedHapticManager, HapticEvent};
+use crate::config::{AccessibilityConfig, Config, GameModeResponse, MenuLayoutConfig, SharedConfig};
+use crate::fast_path::FastPathUpdate;
+use crate::hidpp::{SharedHapticManager, HapticEvent, Mx4HapticPattern};
+use crate::link_quality::ConnectionInfo;
+use crate::long_hover::{SharedLongHover, ALTERNATE_ARMED_PATTERN};
+use crate::menu_pages::MenuPager;
+use crate::overlay_monitor::{now_ms, SharedOverlayMonitor, HEARTBEAT_INTERVAL_MS};
+use crate::profiles::{MenuMode, Profile, ProfileManager, SharedProfileManager};
+use crate::settings_dbus::{SettingsService, SETTINGS_PATH};
+use crate::widget_dbus::{WidgetService, WIDGET_PATH};
+use crate::setup::{check_permissions, current_username, request_install, SetupError};
+use crate::slice_geometry::SliceGeometry;
+use crate::usage_stats::{new_shared_usage_stats, SharedUsageStats};
+use crate::window_tracker::WindowTracker;
 
 /// D-Bus interface name
 pub const DBUS_INTERFACE: &str = "org.kde.juhradialmx.Daemon";
@@ -29,6 +89,20 @@ pub const DBUS_PATH: &str = "/org/kde/juhradialmx/Daemon";
 /// D-Bus bus name
 pub const DBUS_NAME: &str = "org.kde.juhradialmx";
 
+/// Daemon interface version (bumped when methods or signals are added,
+/// removed or changed; optional features are listed by `GetCapabilities`)
+///
+/// - 2: `CancelRunningAction`
+/// - 3: `UndoLastAction`
+/// - 4: `SubmitTextEntry`, `CancelTextEntry`, `TextEntryRequested`
+/// - 5: `GetSubmenu`, `RunSubmenuItem`
+/// - 6: `GetTimer`, `CancelTimer`
+/// - 7: `ResetProfile`
+/// - 8: `GetAnimationTimings`
+/// - 9: `GetRecentActions`, `ActionHistoryChanged`
+/// - 10: `RunSelfTest`
+pub const DAEMON_API_VERSION: u32 = 10;
+
 /// JuhRadial MX D-Bus service
 ///
 /// Implements the D-Bus interface for IPC between daemon, KWin overlay, and Plasma widget.
@@ -43,14 +117,37 @@ pub struct JuhRadialService {
     config: SharedConfig,
     /// Shared haptic manager for triggering haptic feedback
     haptic_manager: SharedHapticManager,
+    /// Overlay liveness and menu visibility tracking
+    overlay_monitor: SharedOverlayMonitor,
+    /// Per-action usage statistics (recorded when enabled in config)
+    usage_stats: SharedUsageStats,
+    /// Loaded profiles for GetMenuLayout
+    profiles: SharedProfileManager,
+    /// Focused window and focus history for profile selection and dynamic slices
+    window_tracker: Arc<WindowTracker>,
+    /// Menu mode requested by ShowMenuWithMode for the currently open menu
+    menu_mode_override: std::sync::Mutex<Option<MenuMode>>,
+    /// Profile menu requested by ShowMenuById for the currently open menu
+    menu_id: std::sync::Mutex<Option<String>>,
+    /// Layout JSON precomputed when the current menu opened
+    menu_cache: std::sync::Mutex<Option<String>>,
+    /// Long-hover alternate timing for the open menu
+    long_hover: SharedLongHover,
+    /// Page shown by the open menu
+    pager: std::sync::Mutex<MenuPager>,
 }
 
 impl JuhRadialService {
-    /// Create a new D-Bus service instance with battery state, config, and haptic manager
+    /// Create a new D-Bus service instance with battery state, config, haptic manager,
+    /// overlay monitor, usage statistics, profiles and window tracker
     pub fn new(
         battery_state: SharedBatteryState,
         config: SharedConfig,
         haptic_manager: SharedHapticManager,
+        overlay_monitor: SharedOverlayMonitor,
+        usage_stats: SharedUsageStats,
+        profiles: SharedProfileManager,
+        window_tracker: Arc<WindowTracker>,
     ) -> Self {
         Self {
             current_profile: "default".to_string(),
@@ -58,10 +155,280 @@ impl JuhRadialService {
             battery_state,
             config,
             haptic_manager,
+            overlay_monitor,
+            usage_stats,
+            profiles,
+            window_tracker,
+            menu_mode_override: std::sync::Mutex::new(None),
+            menu_id: std::sync::Mutex::new(None),
+            menu_cache: std::sync::Mutex::new(None),
+            long_hover: SharedLongHover::default(),
+            pager: std::sync::Mutex::new(MenuPager::default()),
+        }
+    }
+
+    /// Selections `layout`'s profile has left in training mode
+    fn training_remaining(&self, layout: &Profile) -> Option<u64> {
+        layout.training?;
+        let stats = self.usage_stats.lock().ok()?;
+        crate::training::remaining(layout, &stats)
+    }
+
+    /// Mark the menu open, with an optional mode overriding the profile's
+    /// and an optional profile menu in place of the main slices
+    fn open_menu(&self, mode: Option<MenuMode>, menu: Option<String>) {
+        self.set_menu_open(true);
+        crate::metrics::record_menu_invocation();
+        if let Ok(mut current) = self.menu_mode_override.lock() {
+            *current = mode;
+        }
+        if let Ok(mut current) = self.menu_id.lock() {
+            *current = menu;
+        }
+    }
+
+    /// Open the menu: precompute its content, then emit `MenuReady` and `MenuRequested`
+    ///
+    /// Blind profiles start haptic-only navigation instead and emit neither.
+    async fn present_menu(
+        &self,
+        emitter: &SignalEmitter<'_>,
+        x: i32,
+        y: i32,
+        mode: Option<MenuMode>,
+        menu: Option<String>,
+    ) -> fdo::Result<()> {
+        self.open_menu(mode, menu);
+        crate::training::open(None);
+        let prepared = match self.build_menu_layout().await {
+            Ok(layout) => {
+                crate::training::open(self.training_remaining(&layout).map(|_| layout.name.clone()));
+                if layout.blind {
+                    crate::blind_mode::open(&layout, self.slice_geometry()?);
+                    if let Ok(mut manager) = self.haptic_manager.lock() {
+                        manager.emit_async(HapticEvent::MenuAppear);
+                    }
+                    return Ok(());
+                }
+                self.prepare_menu(layout).await
+            }
+            Err(e) => Err(e),
+        };
+        match prepared {
+            Ok(payload) => Self::menu_ready(emitter, x, y, &payload).await?,
+            // The overlay can still fall back to GetMenuLayout
+            Err(e) => tracing::warn!("Failed to precompute menu: {}", e),
+        }
+        Self::menu_requested(emitter, x, y).await?;
+        Ok(())
+    }
+
+    /// Arm the hovered slice's alternate once it has been hovered for `long_hover_ms`
+    fn start_long_hover_timer(&self, emitter: &SignalEmitter<'_>, index: u8) {
+        let Some(token) = self.long_hover.lock().ok().and_then(|mut t| t.on_hover(index)) else {
+            return;
+        };
+        let delay = self.config.read().map(|c| c.long_hover_ms).unwrap_or(800);
+        let long_hover = self.long_hover.clone();
+        let overlay_monitor = self.overlay_monitor.clone();
+        let haptic_manager = self.haptic_manager.clone();
+        let emitter = emitter.to_owned();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            if !overlay_monitor.read().is_ok_and(|m| m.is_menu_open()) {
+                return;
+            }
+            let Some(index) = long_hover.lock().ok().and_then(|mut t| t.arm(token)) else {
+                return;
+            };
+            tracing::debug!(index, "Long-hover alternate armed");
+            if let Ok(mut manager) = haptic_manager.lock() {
+                manager.emit_cue(ALTERNATE_ARMED_PATTERN);
+            }
+            if let Err(e) = Self::alternate_armed(&emitter, index).await {
+                tracing::debug!("Failed to emit AlternateArmed: {}", e);
+            }
+        });
+    }
+
+    /// Apply a page change to the open menu and announce it with `PageChanged`
+    ///
+    /// Returns the page shown afterwards.
+    async fn change_page_with(
+        &self,
+        emitter: &SignalEmitter<'_>,
+        change: impl FnOnce(&mut MenuPager) -> Option<usize>,
+    ) -> fdo::Result<u32> {
+        let (changed, page, count) = {
+            let mut pager = self.pager.lock()
+                .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+            (change(&mut pager), pager.page(), pager.count())
+        };
+        if changed.is_some() {
+            tracing::debug!(page, count, "Menu page changed");
+            if let Ok(mut long_hover) = self.long_hover.lock() {
+                long_hover.show_page(page);
+            }
+            if let Ok(mut manager) = self.haptic_manager.lock() {
+                manager.emit_async(HapticEvent::SliceChange);
+            }
+            Self::page_changed(emitter, page as u32, count as u32).await?;
+        }
+        Ok(page as u32)
+    }
+
+    /// Record whether the menu is currently shown
+    fn set_menu_open(&self, open: bool) {
+        if let Ok(mut monitor) = self.overlay_monitor.write() {
+            monitor.set_menu_open(open);
+        }
+    }
+
+    /// Resolve the layout for the focused window (profile, requested menu,
+    /// mode override, dynamic slices, window list, absolute icon paths)
+    async fn build_menu_layout(&self) -> fdo::Result<Profile> {
+        let mut ctx = ProviderContext {
+            active_window: self.window_tracker.refresh_active_window().await,
+            recent_windows: self.window_tracker.recent_windows().await,
+            ..ProviderContext::default()
+        };
+
+        let menu_id = self.menu_id.lock().ok().and_then(|m| m.clone());
+        let (mut profile, base_dir) = {
+            let profiles = self.profiles.read()
+                .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+            let profile = match &ctx.active_window {
+                Some(class) => profiles.get_profile_for_window(class),
+                None => profiles.current(),
+            };
+            let profile = match &menu_id {
+                Some(id) => profiles.profile_with_menu(profile, id).unwrap_or_else(|| {
+                    tracing::warn!(menu = %id, profile = %profile.name, "Profile has no such menu, showing the main menu");
+                    profile.clone()
+                }),
+                None => profile.clone(),
+            };
+            (profile, profiles.base_dir().map(Path::to_path_buf))
+        };
+        if let Some(mode) = self.menu_mode_override.lock().ok().and_then(|m| *m) {
+            profile.mode = mode;
+        }
+
+        if profile.mode == MenuMode::WindowSwitcher {
+            ctx.open_windows = self.window_tracker.list_windows().await;
+        }
+
+        let layout = profile.resolved(&ctx).with_content(self.menu_layout()?.content);
+        Ok(match base_dir {
+            Some(dir) => layout.with_icon_paths(&dir),
+            None => layout,
+        })
+    }
+
+    /// Slice geometry from the current configuration
+    fn slice_geometry(&self) -> fdo::Result<SliceGeometry> {
+        let config = self.config.read()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+        Ok(SliceGeometry::from_config(&config.slice_geometry))
+    }
+
+    /// Menu density from the current configuration
+    fn menu_layout(&self) -> fdo::Result<MenuLayoutConfig> {
+        let config = self.config.read()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+        Ok(config.menu_layout)
+    }
+
+    /// Items of a built-in submenu
+    fn submenu_items(&self, provider: &str) -> fdo::Result<Vec<crate::actions::Action>> {
+        let config = self.config.read()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+        match provider {
+            crate::emoji_picker::PROVIDER_ID => Ok(crate::emoji_picker::submenu_items(&config.emoji_picker)),
+            _ => Err(fdo::Error::InvalidArgs(format!("Unknown submenu provider: {}", provider))),
+        }
+    }
+
+    /// Menu layout JSON: the profile plus the slice `geometry` it is drawn
+    /// with, the menu `diameter` and `content` density, `active` on slices
+    /// that reflect a system state and, in training, slice descriptions
+    async fn layout_json(
+        layout: &Profile,
+        geometry: &SliceGeometry,
+        density: &MenuLayoutConfig,
+        training: Option<u64>,
+    ) -> fdo::Result<serde_json::Value> {
+        let mut json = serde_json::to_value(layout)
+            .map_err(|e| fdo::Error::Failed(format!("Serialization error: {}", e)))?;
+        json["geometry"] = serde_json::to_value(geometry)
+            .map_err(|e| fdo::Error::Failed(format!("Serialization error: {}", e)))?;
+        json["diameter"] = density.diameter().into();
+        json["content"] = serde_json::to_value(density.content)
+            .map_err(|e| fdo::Error::Failed(format!("Serialization error: {}", e)))?;
+        crate::slice_state::annotate(layout, &mut json).await;
+        if let Some(remaining) = training {
+            crate::training::annotate(&mut json, remaining);
+        }
+        Ok(json)
+    }
+
+    /// Precompute everything the overlay needs at press time
+    ///
+    /// Caches the layout for `GetMenuLayout` until the menu closes and
+    /// returns the `MenuReady` payload.
+    async fn prepare_menu(&self, layout: Profile) -> fdo::Result<String> {
+        if let Ok(mut long_hover) = self.long_hover.lock() {
+            long_hover.open_menu(&layout);
+        }
+        if let Ok(mut pager) = self.pager.lock() {
+            pager.open(layout.page_count());
         }
+        let geometry = self.slice_geometry()?;
+        let (theme, blur_enabled, minimal_theme, long_hover_ms, accessibility) = {
+            let config = self.config.read()
+                .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+            (
+                config.theme.clone(),
+                config.blur_enabled && crate::runtime_state::snapshot().blur_auto_disabled != Some(true),
+                crate::game_mode::is_active() && config.game_mode.response == GameModeResponse::MinimalTheme,
+                config.long_hover_ms,
+                config.accessibility.clone(),
+            )
+        };
+        let training = self.training_remaining(&layout);
+        let accessibility = match training {
+            Some(_) => crate::training::slower(&accessibility),
+            None => accessibility,
+        };
+        let animation = animation_timings(&theme, &accessibility);
+
+        let density = self.menu_layout()?;
+        let layout = Self::layout_json(&layout, &geometry, &density, training).await?;
+        if let Ok(mut cache) = self.menu_cache.lock() {
+            *cache = Some(layout.to_string());
+        }
+
+        let payload = serde_json::json!({
+            "layout": layout,
+            "theme": theme,
+            "blur_enabled": blur_enabled,
+            "minimal_theme": minimal_theme,
+            "long_hover_ms": long_hover_ms,
+            "geometry": geometry,
+            "diameter": density.diameter(),
+            "animation": animation,
+        });
+        Ok(payload.to_string())
     }
 }
 
+/// Animation timings of a theme under the configured accessibility settings
+fn animation_timings(theme: &str, accessibility: &AccessibilityConfig) -> EffectiveAnimationTimings {
+    let animation = crate::theme::find_theme(theme).unwrap_or_default().animation;
+    EffectiveAnimationTimings::compute(&animation, &AccessibilitySettings::from_config(accessibility))
+}
+
 #[interface(name = "org.kde.juhradialmx.Daemon")]
 impl JuhRadialService {
     // =========================================================================
@@ -83,8 +450,49 @@ impl JuhRadialService {
         y: i32,
     ) -> fdo::Result<()> {
         tracing::info!(x, y, "ShowMenu called - emitting MenuRequested signal");
-        Self::menu_requested(&emitter, x, y).await?;
-        Ok(())
+        self.present_menu(&emitter, x, y, None, None).await
+    }
+
+    /// Show an alternate radial menu at the specified coordinates
+    ///
+    /// Like `ShowMenu`, but the next `GetMenuLayout` uses `mode` instead of
+    /// the profile's own mode. Used for multi-press gestures.
+    ///
+    /// # Arguments
+    /// * `mode` - Menu mode name ("actions", "window_switcher")
+    async fn show_menu_with_mode(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        x: i32,
+        y: i32,
+        mode: &str,
+    ) -> fdo::Result<()> {
+        let mode = MenuMode::parse(mode)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("Unknown menu mode: {}", mode)))?;
+        tracing::info!(x, y, ?mode, "ShowMenuWithMode called - emitting MenuRequested signal");
+        self.present_menu(&emitter, x, y, Some(mode), None).await
+    }
+
+    /// Show one of the profile's `menus` at the specified coordinates
+    ///
+    /// Like `ShowMenu`, but the menu shows the focused window's profile menu
+    /// `menu_id` (or the default profile's) instead of its main slices.
+    /// Used for the extra buttons in config.json's `button_menus`.
+    ///
+    /// # Arguments
+    /// * `menu_id` - Key in the profile's `menus`
+    async fn show_menu_by_id(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        x: i32,
+        y: i32,
+        menu_id: &str,
+    ) -> fdo::Result<()> {
+        if menu_id.is_empty() {
+            return Err(fdo::Error::InvalidArgs("Empty menu ID".to_string()));
+        }
+        tracing::info!(x, y, menu_id, "ShowMenuById called - emitting MenuRequested signal");
+        self.present_menu(&emitter, x, y, None, Some(menu_id.to_string())).await
     }
 
     /// Hide the radial menu
@@ -96,28 +504,102 @@ impl JuhRadialService {
         #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
     ) -> fdo::Result<()> {
         tracing::info!("HideMenu called - emitting HideMenu signal");
+        self.set_menu_open(false);
+        if let Ok(mut long_hover) = self.long_hover.lock() {
+            long_hover.close_menu();
+        }
+        if let Ok(mut pager) = self.pager.lock() {
+            pager.close();
+        }
+        crate::training::cancel();
         Self::hide_menu_signal(&emitter).await?;
         Ok(())
     }
 
-    /// Execute an action by its identifier
+    /// Report a slice action the overlay ran itself
     ///
-    /// Called when user selects a slice and releases gesture button.
-    /// Executes the configured action and emits `ActionExecuted` signal.
+    /// Called by overlays that execute their own slice actions after the
+    /// user selected one. Counts it in the usage statistics (actions the
+    /// daemon runs are counted by [`ActionExecutor::execute`]) and emits
+    /// `ActionExecuted`.
     ///
     /// # Arguments
-    /// * `action_id` - Unique identifier for the action to execute
+    /// * `action_id` - The slice's label (its kind when it has none), the
+    ///   key every execution is counted under
     async fn execute_action(
         &self,
         #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
         action_id: String,
     ) -> fdo::Result<()> {
         tracing::info!(action_id = %action_id, "ExecuteAction called");
-        // TODO: Execute the actual action based on action_id
+        crate::usage_stats::record_execution(&action_id);
+        crate::training::record_selection();
         Self::action_executed(&emitter, action_id).await?;
         Ok(())
     }
 
+    /// Undo the most recent action that declared an `inverse`
+    ///
+    /// # Returns
+    /// `false` if there was nothing to undo
+    async fn undo_last_action(&self) -> fdo::Result<bool> {
+        tracing::info!("UndoLastAction called");
+        ActionExecutor::undo_last()
+            .await
+            .map_err(|e| fdo::Error::Failed(format!("Undo failed: {}", e)))
+    }
+
+    /// Run the pending text entry slice's action with the typed text
+    ///
+    /// # Returns
+    /// `false` if no text entry was pending (cancelled or expired)
+    async fn submit_text_entry(&self, text: String) -> fdo::Result<bool> {
+        let Some(action) = crate::text_entry::submit(&text) else {
+            return Ok(false);
+        };
+        tracing::info!(kind = action.action_type.kind(), "SubmitTextEntry called");
+        ActionExecutor::execute(&action)
+            .await
+            .map(|()| true)
+            .map_err(|e| fdo::Error::Failed(format!("Text entry action failed: {}", e)))
+    }
+
+    /// Leave text capture mode without running anything
+    async fn cancel_text_entry(&self) -> bool {
+        crate::text_entry::cancel()
+    }
+
+    /// Get the countdown timer started by a `start_timer` slice
+    ///
+    /// # Returns
+    /// (remaining seconds, label); 0 seconds when no timer is running
+    async fn get_timer(&self) -> (u32, String) {
+        let label = crate::timer::remaining().map(|(_, label)| label).unwrap_or_default();
+        (crate::timer::remaining_secs(), label)
+    }
+
+    /// Stop the countdown timer without an alert
+    ///
+    /// # Returns
+    /// `false` if no timer was running
+    async fn cancel_timer(&self) -> bool {
+        crate::timer::cancel()
+    }
+
+    /// Terminate the `command` actions with a `timeout_secs` that are still running
+    ///
+    /// Each command's process group gets SIGTERM, then SIGKILL if it is
+    /// still alive after a short grace period. Commands without a timeout
+    /// (e.g. launched applications) are left running.
+    ///
+    /// # Returns
+    /// Number of commands signalled
+    async fn cancel_running_action(&self) -> u32 {
+        let cancelled = crate::command_runner::cancel_all();
+        tracing::info!(cancelled, "CancelRunningAction called");
+        cancelled as u32
+    }
+
     // =========================================================================
     // SIGNALS (as per Story 1.2 AC2)
     // =========================================================================
@@ -132,12 +614,48 @@ impl JuhRadialService {
     #[zbus(signal)]
     async fn menu_requested(emitter: &SignalEmitter<'_>, x: i32, y: i32) -> zbus::Result<()>;
 
+    /// Signal emitted just before `MenuRequested` with the precomputed menu
+    ///
+    /// Payload JSON: `{"layout": <GetMenuLayout profile>, "theme": s,
+    /// "blur_enabled": b, "minimal_theme": b, "long_hover_ms": t}`, so the overlay can render
+    /// without follow-up queries.
+    ///
+    /// # Arguments
+    /// * `x` - Screen X coordinate for menu center
+    /// * `y` - Screen Y coordinate for menu center
+    /// * `payload` - Menu payload JSON
+    #[zbus(signal)]
+    async fn menu_ready(emitter: &SignalEmitter<'_>, x: i32, y: i32, payload: &str) -> zbus::Result<()>;
+
     /// Signal emitted when radial menu should be hidden
     ///
     /// Overlay listens for this signal to dismiss the menu.
     #[zbus(signal, name = "HideMenu")]
     async fn hide_menu_signal(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
 
+    /// Signal emitted instead of `HideMenu` when a tap is passed through
+    ///
+    /// The overlay closes the menu without running an action and without
+    /// entering tap-to-toggle mode.
+    #[zbus(signal)]
+    async fn menu_cancelled(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    /// Signal emitted when a `text_entry` slice needs its text
+    ///
+    /// The overlay asks for the text and answers with `SubmitTextEntry`
+    /// or `CancelTextEntry`.
+    ///
+    /// # Arguments
+    /// * `prompt` - Prompt to show above the input field
+    #[zbus(signal)]
+    async fn text_entry_requested(emitter: &SignalEmitter<'_>, prompt: &str) -> zbus::Result<()>;
+
+    /// Signal emitted when an action execution was recorded
+    ///
+    /// Widgets refresh their activity feed with `GetRecentActions`.
+    #[zbus(signal)]
+    async fn action_history_changed(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
     /// Signal emitted when a slice is selected/highlighted
     ///
     /// Sent when cursor moves over a new slice.
@@ -149,7 +667,7 @@ impl JuhRadialService {
 
     /// Signal emitted after an action has been executed
     ///
-    /// Sent after ExecuteAction completes, for feedback/logging.
+    /// Sent after an overlay reported an action with ExecuteAction, for feedback/logging.
     ///
     /// # Arguments
     /// * `action_id` - The identifier of the action that was executed
@@ -167,6 +685,38 @@ impl JuhRadialService {
     #[zbus(signal)]
     async fn cursor_moved(emitter: &SignalEmitter<'_>, x: i32, y: i32) -> zbus::Result<()>;
 
+    /// Signal emitted when game mode starts or ends
+    ///
+    /// Emitted by the game-mode monitor. With response "minimal_theme"
+    /// overlays should drop blur and animations while active.
+    ///
+    /// # Arguments
+    /// * `active` - Whether a game is focused
+    /// * `response` - Configured response ("suppress_trigger", "minimal_theme")
+    #[zbus(signal)]
+    async fn game_mode_changed(emitter: &SignalEmitter<'_>, active: bool, response: &str) -> zbus::Result<()>;
+
+    /// Signal emitted when the screen bounds change (monitor hotplug)
+    ///
+    /// Emitted by the screen watcher. Overlays should re-read their output
+    /// layout before the next menu opens.
+    ///
+    /// # Arguments
+    /// * `width` - Total width across all monitors
+    /// * `height` - Total height across all monitors
+    #[zbus(signal)]
+    async fn screen_configuration_changed(emitter: &SignalEmitter<'_>, width: i32, height: i32) -> zbus::Result<()>;
+
+    /// Signal emitted when the connection type or link quality changes
+    ///
+    /// Emitted by the connection monitor; call `GetDeviceInfo` for details.
+    ///
+    /// # Arguments
+    /// * `connection_type` - "USB", "Bolt", "Bluetooth", "Unifying" or "" when disconnected
+    /// * `quality` - "good", "degraded" or "unknown" (not enough traffic yet)
+    #[zbus(signal)]
+    async fn connection_changed(emitter: &SignalEmitter<'_>, connection_type: &str, quality: &str) -> zbus::Result<()>;
+
     // =========================================================================
     // ADDITIONAL METHODS (extended functionality)
     // =========================================================================
@@ -180,10 +730,72 @@ impl JuhRadialService {
         index: u8,
     ) -> fdo::Result<()> {
         tracing::debug!(index, "Slice hover notification");
+        crate::fast_path::publish(FastPathUpdate::Slice(index));
+        self.start_long_hover_timer(&emitter, index);
+        if crate::training::is_open() {
+            crate::training::on_hover();
+        }
         Self::slice_selected(&emitter, index).await?;
         Ok(())
     }
 
+    /// Step through the open menu's pages
+    ///
+    /// Called by the daemon when the wheel scrolls while the menu is open,
+    /// and by overlays when a `page` slice is selected. Wraps around at
+    /// either end; a menu with a single page is left unchanged.
+    ///
+    /// # Arguments
+    /// * `delta` - Pages to move (1 = next, -1 = previous)
+    ///
+    /// # Returns
+    /// The page shown afterwards
+    async fn change_page(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        delta: i32,
+    ) -> fdo::Result<u32> {
+        self.change_page_with(&emitter, |pager| pager.step(delta)).await
+    }
+
+    /// Show a page of the open menu
+    ///
+    /// # Arguments
+    /// * `page` - Page index (0 = the profile's `slices`)
+    async fn set_page(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        page: u32,
+    ) -> fdo::Result<()> {
+        let count = self.pager.lock().map(|p| p.count()).unwrap_or(1);
+        if page as usize >= count {
+            return Err(fdo::Error::InvalidArgs(format!("Page {} out of range (menu has {})", page, count)));
+        }
+        self.change_page_with(&emitter, |pager| pager.set(page as usize)).await?;
+        Ok(())
+    }
+
+    /// Signal emitted when the open menu switches pages
+    ///
+    /// The overlay shows `pages[page - 1]` of the layout (page 0 is
+    /// `slices`). Slice indices in hover notifications refer to the shown page.
+    ///
+    /// # Arguments
+    /// * `page` - Page now shown
+    /// * `page_count` - Pages of the open menu
+    #[zbus(signal)]
+    async fn page_changed(emitter: &SignalEmitter<'_>, page: u32, page_count: u32) -> zbus::Result<()>;
+
+    /// Signal emitted when a hovered slice's alternate action is armed
+    ///
+    /// Releasing the gesture button now should run the slice's `alternate`
+    /// instead of its normal action. Moving to another slice disarms it.
+    ///
+    /// # Arguments
+    /// * `index` - Slice index (0-7)
+    #[zbus(signal)]
+    async fn alternate_armed(emitter: &SignalEmitter<'_>, index: u8) -> zbus::Result<()>;
+
     /// Trigger haptic feedback for a specific event
     ///
     /// Called by the overlay when haptic feedback should be triggered:
@@ -224,6 +836,20 @@ impl JuhRadialService {
         Ok(())
     }
 
+    /// Play a haptic waveform on demand so users can audition patterns
+    ///
+    /// # Arguments
+    /// * `pattern_name` - Waveform config name (e.g. "subtle_collision", "happy_alert")
+    async fn test_haptic(&self, pattern_name: &str) -> fdo::Result<()> {
+        let pattern = Mx4HapticPattern::parse_name(pattern_name)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("Unknown haptic pattern: {}", pattern_name)))?;
+        self.haptic_manager
+            .lock()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?
+            .test_pattern(pattern)
+            .map_err(|e| fdo::Error::Failed(format!("Haptic test failed: {}", e)))
+    }
+
     /// Set the active profile
     async fn set_profile(&self, name: &str) -> fdo::Result<()> {
         tracing::info!(name, "SetProfile called");
@@ -242,6 +868,8 @@ impl JuhRadialService {
             Ok(new_config) => {
                 // Clone haptic config for updating the haptic manager
                 let haptic_config = new_config.haptics.clone();
+                crate::i18n::set_language(&new_config.language);
+                crate::hidpp_retry::configure(&new_config.hidpp_retry);
 
                 // Update the shared config
                 match self.config.write() {
@@ -288,6 +916,33 @@ impl JuhRadialService {
         }
     }
 
+    /// Install a bundled starter profile set ("kde", "gnome", "hyprland",
+    /// "generic"; empty for the detected desktop), replacing profiles.json
+    ///
+    /// The previous file is kept as profiles.json.bak. Returns the set installed.
+    async fn reset_profile(&self, set: &str) -> fdo::Result<String> {
+        let name = match set {
+            "" => crate::bundled_profiles::profiles_for_session().0.to_string(),
+            set => set.to_lowercase(),
+        };
+        let config = crate::bundled_profiles::get_bundled_profiles(&name).ok_or_else(|| {
+            fdo::Error::InvalidArgs(format!(
+                "Unknown profile set: {} (available: {})",
+                set,
+                crate::bundled_profiles::list_bundled_profiles().join(", ")
+            ))
+        })?;
+        let manager = ProfileManager::install(&config)
+            .map_err(|e| fdo::Error::Failed(format!("Profile reset failed: {}", e)))?;
+        let profile_haptics = manager.current().haptics.clone();
+        *self.profiles.write().map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))? = manager;
+        if let Ok(mut haptics) = self.haptic_manager.lock() {
+            haptics.set_profile_haptics(profile_haptics.as_ref());
+        }
+        tracing::info!(set = %name, "Profiles reset to bundled set");
+        Ok(name)
+    }
+
     /// Called by KWin script to report cursor position and show menu
     ///
     /// This method is called by the JuhRadial KWin script which has access
@@ -299,8 +954,322 @@ impl JuhRadialService {
         y: i32,
     ) -> fdo::Result<()> {
         tracing::info!(x, y, "ShowMenuAtCursor called from KWin script");
-        Self::menu_requested(&emitter, x, y).await?;
-        Ok(())
+        self.present_menu(&emitter, x, y, None, None).await
+    }
+
+    // =========================================================================
+    // OVERLAY LIVENESS METHODS
+    // =========================================================================
+
+    /// Register the calling overlay for liveness tracking
+    ///
+    /// The overlay should call `Heartbeat` at the returned interval. If it stops,
+    /// the daemon resets menu state and emits `HideMenu`.
+    ///
+    /// # Arguments
+    /// * `service_name` - Well-known D-Bus name the overlay can be re-activated by
+    ///   (empty string if the overlay is not D-Bus activatable)
+    ///
+    /// # Returns
+    /// Heartbeat interval in milliseconds
+    async fn register_overlay(
+        &self,
+        #[zbus(header)] header: zbus::message::Header<'_>,
+        service_name: String,
+    ) -> fdo::Result<u32> {
+        let sender = header.sender().map(|s| s.to_string()).unwrap_or_default();
+        let service_name = (!service_name.is_empty()).then_some(service_name);
+
+        match self.overlay_monitor.write() {
+            Ok(mut monitor) => {
+                monitor.register(&sender, service_name, now_ms());
+                Ok(HEARTBEAT_INTERVAL_MS)
+            }
+            Err(e) => Err(fdo::Error::Failed(format!("Lock error: {}", e))),
+        }
+    }
+
+    /// Overlay keep-alive
+    ///
+    /// Called periodically by the registered overlay.
+    async fn heartbeat(
+        &self,
+        #[zbus(header)] header: zbus::message::Header<'_>,
+    ) -> fdo::Result<()> {
+        let sender = header.sender().map(|s| s.to_string()).unwrap_or_default();
+
+        match self.overlay_monitor.write() {
+            Ok(mut monitor) => {
+                monitor.heartbeat(&sender, now_ms());
+                Ok(())
+            }
+            Err(e) => Err(fdo::Error::Failed(format!("Lock error: {}", e))),
+        }
+    }
+
+    /// Overlay confirms it handled a menu signal
+    ///
+    /// # Arguments
+    /// * `signal` - Signal name ("MenuRequested", "CursorMoved", "HideMenu")
+    async fn acknowledge_signal(
+        &self,
+        #[zbus(header)] header: zbus::message::Header<'_>,
+        signal: String,
+    ) -> fdo::Result<()> {
+        let sender = header.sender().map(|s| s.to_string()).unwrap_or_default();
+
+        match self.overlay_monitor.write() {
+            Ok(mut monitor) => {
+                if !monitor.acknowledge(&sender, &signal, now_ms()) {
+                    tracing::debug!(sender, signal, "Acknowledgement from unregistered overlay ignored");
+                }
+                Ok(())
+            }
+            Err(e) => Err(fdo::Error::Failed(format!("Lock error: {}", e))),
+        }
+    }
+
+    /// Exercise the full signal path and report each step
+    ///
+    /// Shows and hides an empty menu at the screen center (nothing is
+    /// executed), plays a haptic and reads the battery. See [`crate::self_test`].
+    ///
+    /// # Returns
+    /// Array of (step, passed, detail)
+    async fn run_self_test(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> Vec<(String, bool, String)> {
+        crate::self_test::run(emitter.connection(), &self.overlay_monitor, &self.haptic_manager, &self.battery_state)
+            .await
+            .into_iter()
+            .map(|c| (c.name, c.passed, c.detail))
+            .collect()
+    }
+
+    // =========================================================================
+    // PERMISSIONS METHODS
+    // =========================================================================
+
+    /// Get udev/input-group setup state for the daemon's user
+    ///
+    /// # Returns
+    /// Tuple of (rules_installed, rules_current, in_input_group, group_active)
+    async fn get_permission_status(&self) -> fdo::Result<(bool, bool, bool, bool)> {
+        let user = current_username()
+            .ok_or_else(|| fdo::Error::Failed("Cannot determine current user".to_string()))?;
+        let status = check_permissions(&user);
+        Ok((status.rules_installed, status.rules_current, status.in_input_group, status.group_active))
+    }
+
+    /// Install udev rules and add the daemon's user to the input group
+    ///
+    /// Runs the juhradialctl helper through pkexec; the polkit agent of the
+    /// session shows the authentication dialog. Returns once the helper exits.
+    async fn install_udev_rules(&self) -> fdo::Result<()> {
+        let user = current_username()
+            .ok_or_else(|| fdo::Error::Failed("Cannot determine current user".to_string()))?;
+        tracing::info!(user = %user, "InstallUdevRules called");
+
+        let result = tokio::task::spawn_blocking(move || request_install(&user))
+            .await
+            .map_err(|e| fdo::Error::Failed(format!("Install task failed: {}", e)))?;
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(SetupError::NotAuthorized) => Err(fdo::Error::AccessDenied(SetupError::NotAuthorized.to_string())),
+            Err(e) => Err(fdo::Error::Failed(e.to_string())),
+        }
+    }
+
+    // =========================================================================
+    // MENU LAYOUT METHODS
+    // =========================================================================
+
+    /// Get the radial menu layout for the focused window
+    ///
+    /// Picks the profile mapped to the focused window (falls back to the
+    /// current profile) and resolves dynamic slices against the current
+    /// desktop state; window switcher profiles list the open windows.
+    /// Intended to be called when the menu opens.
+    ///
+    /// # Returns
+    /// Profile JSON (same schema as profiles.json entries). Slices with a
+    /// long-hover action carry it, resolved, under `alternate`; `geometry`
+    /// holds the slice layout (start angle, rotation, left-handed mirroring),
+    /// as returned by `GetSliceGeometry`. `diameter` and `content` follow
+    /// `menu_layout` in config.json; icon-only and label-only layouts omit
+    /// the other field. Power profile and network slices carry `active`,
+    /// see [`crate::slice_state`]. Profiles in training add `training` and
+    /// slice `description`s, see [`crate::training`].
+    async fn get_menu_layout(&self) -> fdo::Result<String> {
+        // Computed at press time; only valid while that menu is open
+        let menu_open = self.overlay_monitor.read().is_ok_and(|m| m.is_menu_open());
+        if let Some(layout) = self.menu_cache.lock().ok().and_then(|c| c.clone()).filter(|_| menu_open) {
+            return Ok(layout);
+        }
+        let profile = self.build_menu_layout().await?;
+        let training = self.training_remaining(&profile);
+        Ok(Self::layout_json(&profile, &self.slice_geometry()?, &self.menu_layout()?, training).await?.to_string())
+    }
+
+    /// Get the slice geometry used for hit-testing
+    ///
+    /// Overlays should hit-test with the same parameters so their highlight
+    /// matches the daemon's selection.
+    ///
+    /// # Returns
+    /// JSON object: `slice_count`, `dead_zone_radius` (px), `start_angle_deg`
+    /// (center of slice 0, clockwise from north, rotation included),
+    /// `hysteresis_deg` and `mirrored` (slices run counter-clockwise).
+    async fn get_slice_geometry(&self) -> fdo::Result<String> {
+        serde_json::to_string(&self.slice_geometry()?)
+            .map_err(|e| fdo::Error::Failed(format!("Serialization error: {}", e)))
+    }
+
+    /// Animation timings for the configured theme with reduced motion and
+    /// the user's speed multiplier applied (also sent in MenuReady)
+    async fn get_animation_timings(&self) -> fdo::Result<String> {
+        let (theme, accessibility) = {
+            let config = self.config.read()
+                .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+            (config.theme.clone(), config.accessibility.clone())
+        };
+        serde_json::to_string(&animation_timings(&theme, &accessibility))
+            .map_err(|e| fdo::Error::Failed(format!("Serialization error: {}", e)))
+    }
+
+    /// Get the items of a built-in submenu
+    ///
+    /// # Arguments
+    /// * `provider` - Submenu provider ID (currently only "emoji")
+    ///
+    /// # Returns
+    /// JSON array of actions (same schema as profile slices)
+    async fn get_submenu(&self, provider: String) -> fdo::Result<String> {
+        serde_json::to_string(&self.submenu_items(&provider)?)
+            .map_err(|e| fdo::Error::Failed(format!("Serialization error: {}", e)))
+    }
+
+    /// Run an item of a built-in submenu
+    ///
+    /// # Arguments
+    /// * `provider` - Submenu provider ID, as for `GetSubmenu`
+    /// * `index` - Item index in the `GetSubmenu` array
+    async fn run_submenu_item(&self, provider: String, index: u32) -> fdo::Result<()> {
+        let action = self
+            .submenu_items(&provider)?
+            .into_iter()
+            .nth(index as usize)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("No submenu item {}", index)))?;
+        tracing::info!(provider = %provider, index, "RunSubmenuItem called");
+        ActionExecutor::execute(&action)
+            .await
+            .map_err(|e| fdo::Error::Failed(format!("Submenu action failed: {}", e)))
+    }
+
+    // =========================================================================
+    // DIAGNOSTICS METHODS
+    // =========================================================================
+
+    /// Get setup problems detected at runtime, most severe first
+    ///
+    /// # Returns
+    /// Array of (source, severity, code, message); severity is
+    /// "error", "warning" or "info"
+    async fn get_diagnostics(&self) -> fdo::Result<Vec<(String, String, String, String)>> {
+        Ok(crate::diagnostics::all()
+            .into_iter()
+            .map(|(source, d)| (source.to_string(), d.severity.as_str().to_string(), d.code, d.message))
+            .collect())
+    }
+
+    /// Get how the mouse is connected and how healthy the link is
+    ///
+    /// # Returns
+    /// JSON object: `connected`, `connection_type`, `wireless`,
+    /// `host_switched_away`, `quality` ("good", "degraded", "unknown") and
+    /// `link` (request count, failures, round-trip times; null when
+    /// disconnected)
+    async fn get_device_info(&self) -> fdo::Result<String> {
+        let info = ConnectionInfo::collect(&self.haptic_manager);
+        serde_json::to_string(&info)
+            .map_err(|e| fdo::Error::Failed(format!("Serialization error: {}", e)))
+    }
+
+    /// List the optional features available right now
+    ///
+    /// Overlays and widgets check this instead of calling methods that would
+    /// fail: "haptics", "dpi" and "smart-shift" (connected mouse supports
+    /// them), "battery", "window-tracking", "fast-path" and "game-mode".
+    /// Names are only ever added; watch `ConnectionChanged` to re-check the
+    /// device features after reconnects.
+    async fn get_capabilities(&self) -> Vec<String> {
+        let mut capabilities = self
+            .haptic_manager
+            .lock()
+            .map(|m| m.device_capabilities())
+            .unwrap_or_default();
+        if self.battery_state.read().await.available {
+            capabilities.push("battery");
+        }
+        if self.window_tracker.is_available() {
+            capabilities.push("window-tracking");
+        }
+        if !crate::fast_path::socket_path().is_empty() {
+            capabilities.push("fast-path");
+        }
+        if self.config.read().is_ok_and(|c| c.game_mode.enabled) {
+            capabilities.push("game-mode");
+        }
+        capabilities.into_iter().map(String::from).collect()
+    }
+
+    /// Get the audit log of outgoing HID++ messages, oldest first
+    ///
+    /// Lets users verify that only runtime-only features were written.
+    ///
+    /// # Returns
+    /// Array of (unix_ms, feature_id, feature_index, function, params);
+    /// feature_id is 0xFFFF if it could not be resolved
+    async fn dump_hidpp_audit(&self) -> fdo::Result<Vec<(u64, u16, u8, u8, Vec<u8>)>> {
+        Ok(crate::hidpp_audit::entries()
+            .into_iter()
+            .map(|e| (e.timestamp_ms, e.feature_id, e.feature_index, e.function, e.params))
+            .collect())
+    }
+
+    // =========================================================================
+    // USAGE STATISTICS METHODS
+    // =========================================================================
+
+    /// Get per-action usage statistics, most used first
+    ///
+    /// Empty unless `usage_stats.enabled` is set in config.
+    ///
+    /// # Returns
+    /// Array of (action_id, count, last_used_unix_secs)
+    async fn get_action_stats(&self) -> fdo::Result<Vec<(String, u64, u64)>> {
+        let stats = self.usage_stats.lock()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+        Ok(stats
+            .ranked()
+            .into_iter()
+            .map(|(id, stat)| (id.to_string(), stat.count, stat.last_used))
+            .collect())
+    }
+
+    /// Get the most recent action executions, newest first
+    ///
+    /// At most [`crate::action_history::MAX_ENTRIES`]; kept across restarts.
+    ///
+    /// # Returns
+    /// Array of (name, kind, timestamp_unix_secs, success, error)
+    async fn get_recent_actions(&self) -> Vec<(String, String, u64, bool, String)> {
+        crate::action_history::recent()
+            .into_iter()
+            .map(|e| (e.name, e.kind, e.timestamp, e.success, e.error))
+            .collect()
     }
 
     /// Get battery status from the device
@@ -354,6 +1323,7 @@ impl JuhRadialService {
                 match manager.set_dpi(dpi) {
                     Ok(()) => {
                         tracing::info!(dpi, "DPI set successfully");
+                        crate::runtime_state::update(|state| state.last_dpi = Some(dpi));
                         Ok(())
                     }
                     Err(e) => {
@@ -609,6 +1579,44 @@ impl JuhRadialService {
         }
     }
 
+    // =========================================================================
+    // RUNTIME STATE METHODS
+    // =========================================================================
+
+    /// Get first-run onboarding progress (persisted in state.json)
+    ///
+    /// # Returns
+    /// Tuple of (last completed step, complete)
+    async fn get_onboarding_progress(&self) -> (u32, bool) {
+        let progress = crate::runtime_state::snapshot().onboarding;
+        (progress.step, progress.complete)
+    }
+
+    /// Record first-run onboarding progress
+    ///
+    /// # Arguments
+    /// * `step` - Last completed onboarding step
+    /// * `complete` - Onboarding finished or dismissed
+    async fn set_onboarding_progress(&self, step: u32, complete: bool) {
+        tracing::debug!(step, complete, "SetOnboardingProgress called");
+        crate::runtime_state::update(|state| {
+            state.onboarding = crate::runtime_state::OnboardingProgress { step, complete };
+        });
+    }
+
+    /// Record the overlay's automatic blur decision
+    ///
+    /// Once blur was auto-disabled (slow GPU), `MenuReady` reports
+    /// `blur_enabled: false` until the overlay reports `false` again, also
+    /// across restarts.
+    ///
+    /// # Arguments
+    /// * `disabled` - Whether the overlay turned blur off for performance
+    async fn set_blur_auto_disabled(&self, disabled: bool) {
+        tracing::info!(disabled, "SetBlurAutoDisabled called");
+        crate::runtime_state::update(|state| state.blur_auto_disabled = Some(disabled));
+    }
+
     // =========================================================================
     // PROPERTIES
     // =========================================================================
@@ -619,6 +1627,24 @@ impl JuhRadialService {
         &self.current_profile
     }
 
+    /// Path of the cursor/slice fast-path socket (empty if disabled)
+    #[zbus(property)]
+    async fn fast_path_socket(&self) -> String {
+        crate::fast_path::socket_path()
+    }
+
+    /// Whether game mode is active
+    #[zbus(property)]
+    async fn game_mode_active(&self) -> bool {
+        crate::game_mode::is_active()
+    }
+
+    /// Version of this interface
+    #[zbus(property)]
+    async fn api_version(&self) -> u32 {
+        DAEMON_API_VERSION
+    }
+
     /// Get haptics enabled status
     #[zbus(property)]
     async fn haptics_enabled(&self) -> bool {
@@ -635,46 +1661,172 @@ impl JuhRadialService {
     }
 }
 
-/// Initialize and run the D-Bus service
+/// Shared state behind the daemon's D-Bus objects
 ///
-/// Connects to the session bus, registers the service name, and exports
-/// the interface at the specified object path.
+/// Outlives any one session bus connection: after the bus restarts the
+/// objects are served again from the same state (see [`crate::session_bus`]).
+#[derive(Clone)]
+pub struct DbusServiceState {
+    battery_state: SharedBatteryState,
+    config: SharedConfig,
+    haptic_manager: SharedHapticManager,
+    overlay_monitor: SharedOverlayMonitor,
+    usage_stats: SharedUsageStats,
+    profiles: SharedProfileManager,
+    window_tracker: Arc<WindowTracker>,
+}
+
+impl DbusServiceState {
+    /// Collect the shared state and load the action history
+    ///
+    /// # Arguments
+    /// * `battery_state` - Shared battery state for GetBatteryStatus method
+    /// * `config` - Shared configuration for hot-reload support
+    /// * `haptic_manager` - Shared haptic manager for triggering haptic feedback
+    /// * `overlay_monitor` - Shared overlay monitor for RegisterOverlay/Heartbeat
+    /// * `profiles` - Loaded profiles for GetMenuLayout
+    /// * `window_tracker` - Focused window tracking for per-app layouts
+    pub fn new(
+        battery_state: SharedBatteryState,
+        config: SharedConfig,
+        haptic_manager: SharedHapticManager,
+        overlay_monitor: SharedOverlayMonitor,
+        profiles: SharedProfileManager,
+        window_tracker: Arc<WindowTracker>,
+    ) -> Self {
+        crate::action_history::init(crate::action_history::ActionHistory::load_default());
+        let usage_stats = new_shared_usage_stats();
+        crate::usage_stats::init(usage_stats.clone(), config.clone());
+        Self {
+            battery_state,
+            config,
+            haptic_manager,
+            overlay_monitor,
+            usage_stats,
+            profiles,
+            window_tracker,
+        }
+    }
+}
+
+/// Connect to the session bus and serve the daemon's objects
 ///
-/// # Arguments
-/// * `battery_state` - Shared battery state for GetBatteryStatus method
-/// * `config` - Shared configuration for hot-reload support
-/// * `haptic_manager` - Shared haptic manager for triggering haptic feedback
+/// Registers the service name and exports the Daemon interface at
+/// `DBUS_PATH`, the Settings interface at `SETTINGS_PATH` and the Widget
+/// interface at `WIDGET_PATH`. Can be called again for a new connection.
 ///
 /// # Returns
 /// A `zbus::Connection` that should be kept alive for the service to run.
-pub async fn init_dbus_service(
-    battery_state: SharedBatteryState,
-    config: SharedConfig,
-    haptic_manager: SharedHapticManager,
+pub async fn serve_dbus_service(state: &DbusServiceState) -> zbus::Result<zbus::Connection> {
+    serve_dbus_service_on(state, zbus::connection::Builder::session()?).await
+}
+
+/// Serve the daemon's objects on the bus `builder` connects to
+///
+/// Like [`serve_dbus_service`], for a bus other than the session bus
+/// (e.g. a private test bus).
+pub async fn serve_dbus_service_on(
+    state: &DbusServiceState,
+    builder: zbus::connection::Builder<'_>,
 ) -> zbus::Result<zbus::Connection> {
-    let service = JuhRadialService::new(battery_state, config, haptic_manager);
+    let settings = SettingsService::new(state.config.clone(), state.haptic_manager.clone());
+    let service = JuhRadialService::new(
+        state.battery_state.clone(),
+        state.config.clone(),
+        state.haptic_manager.clone(),
+        state.overlay_monitor.clone(),
+        state.usage_stats.clone(),
+        state.profiles.clone(),
+        state.window_tracker.clone(),
+    );
 
-    let connection = zbus::connection::Builder::session()?
+    let connection = builder
         .name(DBUS_NAME)?
         .serve_at(DBUS_PATH, service)?
+        .serve_at(SETTINGS_PATH, settings)?
+        .serve_at(WIDGET_PATH, WidgetService::new())?
         .build()
         .await?;
 
     tracing::info!(
         name = DBUS_NAME,
         path = DBUS_PATH,
+        settings_path = SETTINGS_PATH,
+        widget_path = WIDGET_PATH,
         "D-Bus service registered"
     );
 
     Ok(connection)
 }
 
+/// Initialize and run the D-Bus service
+///
+/// Shorthand for [`DbusServiceState::new`] followed by [`serve_dbus_service`].
+pub async fn init_dbus_service(
+    battery_state: SharedBatteryState,
+    config: SharedConfig,
+    haptic_manager: SharedHapticManager,
+    overlay_monitor: SharedOverlayMonitor,
+    profiles: SharedProfileManager,
+    window_tracker: Arc<WindowTracker>,
+) -> zbus::Result<zbus::Connection> {
+    let state = DbusServiceState::new(battery_state, config, haptic_manager, overlay_monitor, profiles, window_tracker);
+    serve_dbus_service(&state).await
+}
+
+/// Shared system bus connection for clients of system services
+///
+/// Opened on first use and kept, so actions that talk to the system bus
+/// (power profiles, BlueZ) do not connect on every call.
+pub async fn system_bus() -> zbus::Result<&'static zbus::Connection> {
+    static SYSTEM_BUS: tokio::sync::OnceCell<zbus::Connection> = tokio::sync::OnceCell::const_new();
+    SYSTEM_BUS.get_or_try_init(zbus::Connection::system).await
+}
+
+/// Doctype line of D-Bus introspection documents
+const INTROSPECTION_DOCTYPE: &str = r#"<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">"#;
+
+/// Introspection XML of every interface the daemon serves
+///
+/// Returns (interface name, document) pairs, generated from the zbus
+/// interfaces themselves so the description cannot drift from the code.
+/// Written by `juhradiald --dump-introspection`.
+pub fn introspection_documents() -> Vec<(String, String)> {
+    let config = crate::config::new_shared_config();
+    let haptic_manager = crate::hidpp::new_shared_haptic_manager(&Default::default());
+    let daemon = JuhRadialService::new(
+        crate::battery::new_shared_state(),
+        config.clone(),
+        haptic_manager.clone(),
+        crate::overlay_monitor::new_shared_overlay_monitor(),
+        new_shared_usage_stats(),
+        Default::default(),
+        Default::default(),
+    );
+
+    vec![
+        introspection_document(&daemon),
+        introspection_document(&SettingsService::new(config, haptic_manager)),
+        introspection_document(&WidgetService::new()),
+    ]
+}
+
+/// Standalone introspection document for one interface
+fn introspection_document<I: zbus::object_server::Interface>(iface: &I) -> (String, String) {
+    let mut xml = format!("{}\n<node>\n", INTROSPECTION_DOCTYPE);
+    iface.introspect_to_writer(&mut xml, 2);
+    xml.push_str("</node>\n");
+    (I::name().to_string(), xml)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::battery::new_shared_state;
     use crate::config::new_shared_config;
     use crate::hidpp::new_shared_haptic_manager;
+    use crate::overlay_monitor::new_shared_overlay_monitor;
 
     #[test]
     fn test_dbus_constants() {
@@ -683,17 +1835,130 @@ mod tests {
         assert_eq!(DBUS_NAME, "org.kde.juhradialmx");
     }
 
+    #[tokio::test]
+    async fn test_capabilities_without_device() {
+        let config = new_shared_config();
+        let service = JuhRadialService::new(
+            new_shared_state(),
+            config.clone(),
+            new_shared_haptic_manager(&Default::default()),
+            new_shared_overlay_monitor(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(service.api_version().await, DAEMON_API_VERSION);
+        assert_eq!(service.get_capabilities().await, ["game-mode"]);
+
+        config.write().unwrap().game_mode.enabled = false;
+        assert!(service.get_capabilities().await.is_empty());
+    }
+
+    #[test]
+    fn test_introspection_documents() {
+        let documents = introspection_documents();
+        let names: Vec<&str> = documents.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, [DBUS_INTERFACE, crate::SETTINGS_INTERFACE, "org.kde.juhradialmx.Widget"]);
+
+        let (_, daemon) = &documents[0];
+        assert!(daemon.starts_with("<!DOCTYPE node"));
+        assert!(daemon.contains(r#"<method name="ShowMenu">"#));
+        assert!(daemon.contains(r#"<signal name="MenuCancelled">"#));
+        assert!(daemon.trim_end().ends_with("</node>"));
+    }
+
     #[test]
     fn test_service_creation() {
         let battery_state = new_shared_state();
         let config = new_shared_config();
         let haptic_config = config.read().unwrap().haptics.clone();
         let haptic_manager = new_shared_haptic_manager(&haptic_config);
-        let service = JuhRadialService::new(battery_state, config, haptic_manager);
+        let service = JuhRadialService::new(
+            battery_state,
+            config,
+            haptic_manager,
+            new_shared_overlay_monitor(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
         assert_eq!(service.current_profile, "default");
         // Check haptics from config
         let haptics = service.config.read().unwrap().haptics.enabled;
         assert!(haptics);
         assert!(!service.version.is_empty());
     }
+
+    #[test]
+    fn test_menu_open_tracking() {
+        let config = new_shared_config();
+        let haptic_config = config.read().unwrap().haptics.clone();
+        let overlay_monitor = new_shared_overlay_monitor();
+        let service = JuhRadialService::new(
+            new_shared_state(),
+            config,
+            new_shared_haptic_manager(&haptic_config),
+            overlay_monitor.clone(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+
+        service.set_menu_open(true);
+        assert!(overlay_monitor.read().unwrap().is_menu_open());
+        service.set_menu_open(false);
+        assert!(!overlay_monitor.read().unwrap().is_menu_open());
+    }
+
+    #[test]
+    fn test_menu_mode_override() {
+        let config = new_shared_config();
+        let haptic_config = config.read().unwrap().haptics.clone();
+        let service = JuhRadialService::new(
+            new_shared_state(),
+            config,
+            new_shared_haptic_manager(&haptic_config),
+            new_shared_overlay_monitor(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+
+        service.open_menu(Some(MenuMode::WindowSwitcher), None);
+        assert_eq!(*service.menu_mode_override.lock().unwrap(), Some(MenuMode::WindowSwitcher));
+        // A normal ShowMenu clears the override
+        service.open_menu(None, None);
+        assert_eq!(*service.menu_mode_override.lock().unwrap(), None);
+        assert_eq!(MenuMode::parse("window_switcher"), Some(MenuMode::WindowSwitcher));
+        assert_eq!(MenuMode::parse("bogus"), None);
+    }
+
+    #[tokio::test]
+    async fn test_menu_precomputed_at_press() {
+        let config = new_shared_config();
+        let haptic_config = config.read().unwrap().haptics.clone();
+        let overlay_monitor = new_shared_overlay_monitor();
+        let service = JuhRadialService::new(
+            new_shared_state(),
+            config,
+            new_shared_haptic_manager(&haptic_config),
+            overlay_monitor.clone(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+
+        service.open_menu(None, None);
+        let layout = service.build_menu_layout().await.unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&service.prepare_menu(layout).await.unwrap()).unwrap();
+        assert_eq!(payload["theme"], "catppuccin-mocha");
+        assert_eq!(payload["minimal_theme"], false);
+        assert_eq!(payload["layout"]["name"], "default");
+
+        // GetMenuLayout serves the cached layout while the menu is open
+        *service.menu_cache.lock().unwrap() = Some("cached".to_string());
+        assert_eq!(service.get_menu_layout().await.unwrap(), "cached");
+        service.set_menu_open(false);
+        assert_ne!(service.get_menu_layout().await.unwrap(), "cached");
+    }
 }
@@ -0,0 +1,116 @@
+//! Runtime diagnostics for JuhRadial MX
+//!
+//! Components report setup problems they detect (e.g. a logid config that
+//! never sends F19) so they show up both in the log and via the
+//! `GetDiagnostics` D-Bus method, instead of the daemon silently receiving
+//! nothing. Each source replaces its own previous report.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Diagnostic severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Informational hint
+    Info,
+    /// Something is likely misconfigured
+    Warning,
+    /// A feature cannot work
+    Error,
+}
+
+impl Severity {
+    /// Name used on D-Bus
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// A single diagnostic finding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Severity
+    pub severity: Severity,
+    /// Stable machine-readable code (e.g. "logid-not-diverted")
+    pub code: String,
+    /// Human-readable explanation with a suggested fix
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic
+    pub fn new(severity: Severity, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Current findings keyed by source
+static REPORTS: Mutex<BTreeMap<&'static str, Vec<Diagnostic>>> = Mutex::new(BTreeMap::new());
+
+/// Replace the findings of `source` (an empty list clears them) and log new ones
+pub fn report(source: &'static str, diagnostics: Vec<Diagnostic>) {
+    let Ok(mut reports) = REPORTS.lock() else {
+        return;
+    };
+
+    let previous = reports.get(source);
+    for diagnostic in &diagnostics {
+        if previous.is_some_and(|p| p.contains(diagnostic)) {
+            continue;
+        }
+        match diagnostic.severity {
+            Severity::Info => tracing::info!(source, code = %diagnostic.code, "{}", diagnostic.message),
+            Severity::Warning => tracing::warn!(source, code = %diagnostic.code, "{}", diagnostic.message),
+            Severity::Error => tracing::error!(source, code = %diagnostic.code, "{}", diagnostic.message),
+        }
+    }
+
+    if diagnostics.is_empty() {
+        reports.remove(source);
+    } else {
+        reports.insert(source, diagnostics);
+    }
+}
+
+/// All current findings as (source, diagnostic), most severe first
+pub fn all() -> Vec<(&'static str, Diagnostic)> {
+    let Ok(reports) = REPORTS.lock() else {
+        return Vec::new();
+    };
+    let mut all: Vec<_> = reports
+        .iter()
+        .flat_map(|(source, list)| list.iter().map(move |d| (*source, d.clone())))
+        .collect();
+    all.sort_by_key(|(_, d)| std::cmp::Reverse(d.severity));
+    all
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_replaces_and_clears() {
+        report("test-a", vec![Diagnostic::new(Severity::Info, "a-info", "hint")]);
+        report("test-b", vec![Diagnostic::new(Severity::Error, "b-error", "broken")]);
+
+        let codes: Vec<_> = all().into_iter().map(|(_, d)| d.code).collect();
+        let b = codes.iter().position(|c| c == "b-error").unwrap();
+        let a = codes.iter().position(|c| c == "a-info").unwrap();
+        // Most severe first
+        assert!(b < a);
+
+        report("test-a", Vec::new());
+        assert!(!all().iter().any(|(source, _)| *source == "test-a"));
+    }
+}
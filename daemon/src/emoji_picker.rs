@@ -0,0 +1,68 @@
+//! Emoji / special character submenu
+//!
+//! An `emoji` slice opens a submenu with the characters from
+//! `emoji_picker.characters`. The overlay asks for the items with
+//! `GetSubmenu("emoji")` and runs the chosen one with
+//! `RunSubmenuItem("emoji", index)`; each item is an `insert_text` action
+//! that copies the character to the clipboard and, with
+//! `emoji_picker.paste`, pastes it into the focused window.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use crate::actions::{Action, ActionType, InsertText};
+use crate::config::EmojiPickerConfig;
+
+/// Provider ID used with `GetSubmenu` / `RunSubmenuItem`
+pub const PROVIDER_ID: &str = "emoji";
+
+/// Items the overlay can lay out around one slice
+pub const MAX_ITEMS: usize = 8;
+
+/// Longest entry (characters; sequences like flags or "¯\_(ツ)_/¯" fit)
+pub const MAX_ENTRY_CHARS: usize = 16;
+
+/// Characters offered until the user configures their own
+pub const DEFAULT_CHARACTERS: &[&str] = &["👍", "😂", "❤️", "🎉", "🤔", "👀", "✅", "→"];
+
+/// Submenu items for the configured characters
+pub fn submenu_items(config: &EmojiPickerConfig) -> Vec<Action> {
+    config
+        .characters
+        .iter()
+        .take(MAX_ITEMS)
+        .map(|character| Action {
+            action_type: ActionType::InsertText(InsertText {
+                text: character.clone(),
+                paste: config.paste,
+            }),
+            label: Some(character.clone()),
+            icon: Some(character.clone()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_items_follow_config() {
+        let mut config: EmojiPickerConfig =
+            serde_json::from_str(r#"{"characters": ["", "é", "a-very-long-entry-indeed", "👍"], "paste": false}"#).unwrap();
+        config.validate();
+
+        let items = submenu_items(&config);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].label.as_deref(), Some("é"));
+        assert_eq!(
+            items[1].action_type,
+            ActionType::InsertText(InsertText { text: "👍".to_string(), paste: false })
+        );
+
+        assert_eq!(submenu_items(&EmojiPickerConfig::default()).len(), DEFAULT_CHARACTERS.len());
+    }
+}
@@ -5,16 +5,22 @@
 //!
 //! ## Device Detection
 //! Scans `/dev/input/event*` for Logitech devices (vendor ID 0x046D)
-//! and identifies the MX Master 4 by product ID.
+//! and identifies the MX Master 4 by product ID. With `evdev_trigger`
+//! configured, any device (by path or name) and key can be the trigger
+//! instead.
 //!
 //! ## Event Handling
 //! Listens for EV_KEY events on the gesture button and emits
 //! `GestureEvent::Pressed` and `GestureEvent::Released` accordingly.
+//! While the button is held, pointer motion and wheel scrolling are
+//! reported as `CursorMoved` and `Scrolled`.
 
 use std::path::PathBuf;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
+use crate::config::{EvdevTriggerConfig, MenuGrabConfig};
+
 /// MX Master 4 vendor ID (Logitech)
 pub const LOGITECH_VENDOR_ID: u16 = 0x046D;
 
@@ -36,15 +42,79 @@ pub const GESTURE_BUTTON_CODES: &[u16] = &[
 /// Press event (value=1) triggers menu, release event (value=0) dismisses
 pub const LOGID_GESTURE_KEY: u16 = 189;   // KEY_F19
 
+/// How long a gesture press may stay open before the watchdog
+/// cross-checks it against the kernel key state (10 seconds)
+pub const STALE_PRESS_TIMEOUT_SECS: u64 = 10;
+
+/// Interval between stale-press watchdog checks (milliseconds)
+pub const STALE_PRESS_CHECK_INTERVAL_MS: u64 = 1000;
+
+/// Check whether an open press has outlived the stale-press timeout
+///
+/// A press that stays open this long usually means the release event
+/// (HID++ notification or EV_KEY value 0) was dropped.
+pub fn press_is_stale(press_time: Option<Instant>, timeout: Duration) -> bool {
+    press_time.is_some_and(|t| t.elapsed() >= timeout)
+}
+
+/// What the stale-press watchdog knows about a button
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyState {
+    /// Confirmed down
+    Held,
+    /// Confirmed up: the release was lost
+    Released,
+    /// Could not be determined (device missing or unreadable)
+    Unknown,
+}
+
+impl KeyState {
+    /// From an EVIOCGKEY read of `codes`
+    pub fn from_key_state<E>(keys: Result<evdev::AttributeSet<evdev::KeyCode>, E>, codes: &[u16]) -> Self {
+        match keys {
+            Ok(keys) if any_key_down(&keys, codes) => KeyState::Held,
+            Ok(_) => KeyState::Released,
+            Err(_) => KeyState::Unknown,
+        }
+    }
+}
+
+/// Check whether any gesture button is down in an EVIOCGKEY key state snapshot
+pub fn gesture_key_down(keys: &evdev::AttributeSetRef<evdev::KeyCode>) -> bool {
+    any_key_down(keys, GESTURE_BUTTON_CODES)
+}
+
+/// Check whether any of `codes` is down in an EVIOCGKEY key state snapshot
+pub fn any_key_down(keys: &evdev::AttributeSetRef<evdev::KeyCode>, codes: &[u16]) -> bool {
+    codes.iter().any(|&code| keys.contains(evdev::KeyCode(code)))
+}
+
+/// Parse a key name (`BTN_EXTRA`, `KEY_F13`) or numeric code (`276`, `0x114`)
+pub fn parse_key_code(key: &str) -> Option<u16> {
+    let key = key.trim();
+    if let Some(hex) = key.strip_prefix("0x").or_else(|| key.strip_prefix("0X")) {
+        return u16::from_str_radix(hex, 16).ok();
+    }
+    if let Ok(code) = key.parse::<u16>() {
+        return Some(code);
+    }
+    key.to_ascii_uppercase().parse::<evdev::KeyCode>().ok().map(|k| k.code())
+}
+
 /// Event types for gesture button
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum GestureEvent {
     /// Gesture button pressed, includes cursor position
-    Pressed { x: i32, y: i32 },
+    ///
+    /// `menu` is the profile menu a `button_menus` button opens (None for
+    /// the main menu).
+    Pressed { x: i32, y: i32, menu: Option<String> },
     /// Gesture button released, includes hold duration
     Released { duration_ms: u64 },
     /// Cursor moved while button is held (for hover detection on Wayland)
     CursorMoved { x: i32, y: i32 },
+    /// Wheel scrolled while button is held, in detents (positive = down)
+    Scrolled { delta: i32 },
 }
 
 /// Information about a detected input device
@@ -68,6 +138,8 @@ pub struct EvdevHandler {
     event_tx: mpsc::Sender<GestureEvent>,
     /// Currently connected device path
     device_path: Option<PathBuf>,
+    /// Device to open instead of scanning for the MX Master 4
+    pinned_path: Option<PathBuf>,
     /// Time when gesture button was pressed
     press_time: Option<Instant>,
     /// Whether we're currently polling for device connection
@@ -78,6 +150,14 @@ pub struct EvdevHandler {
     cursor_y: i32,
     /// Whether menu is currently active (button held)
     menu_active: bool,
+    /// Press age after which the watchdog cross-checks the key state
+    stale_press_timeout: Duration,
+    /// Grab the mouse and hold back back/forward while the menu is open
+    menu_grab: MenuGrabConfig,
+    /// Device to find by path or name instead of the MX Master 4
+    trigger_device: Option<String>,
+    /// Key codes that open the menu
+    trigger_keys: Vec<u16>,
 }
 
 impl EvdevHandler {
@@ -86,14 +166,99 @@ impl EvdevHandler {
         Self {
             event_tx,
             device_path: None,
+            pinned_path: None,
             press_time: None,
             polling: false,
             cursor_x: 0,
             cursor_y: 0,
             menu_active: false,
+            stale_press_timeout: Duration::from_secs(STALE_PRESS_TIMEOUT_SECS),
+            menu_grab: MenuGrabConfig::default(),
+            trigger_device: None,
+            trigger_keys: GESTURE_BUTTON_CODES.to_vec(),
         }
     }
 
+    /// Set the press age after which the stale-press watchdog kicks in
+    pub fn set_stale_press_timeout(&mut self, timeout: Duration) {
+        self.stale_press_timeout = timeout;
+    }
+
+    /// Always open `path` instead of scanning (e.g. a test's virtual mouse)
+    pub fn pin_device(&mut self, path: PathBuf) {
+        self.pinned_path = Some(path);
+    }
+
+    /// Configure the exclusive grab (applies when the device is next opened)
+    pub fn set_menu_grab(&mut self, config: MenuGrabConfig) {
+        self.menu_grab = config;
+    }
+
+    /// Use a custom trigger device and key (applies when the device is next opened)
+    pub fn set_trigger(&mut self, config: &EvdevTriggerConfig) {
+        self.trigger_device = config.device().map(str::to_string);
+        self.trigger_keys = config.key_codes();
+    }
+
+    /// Find the device this handler listens to
+    ///
+    /// A pinned device, else the configured trigger device, else the MX Master 4.
+    pub fn locate(&self) -> Result<DeviceInfo, EvdevError> {
+        #[cfg(not(target_os = "linux"))]
+        {
+            Err(EvdevError::DeviceNotFound)
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            match (&self.pinned_path, &self.trigger_device) {
+                (Some(path), _) => Self::check_device(path)?.ok_or(EvdevError::DeviceNotFound),
+                (None, Some(selector)) => Self::find_trigger_device(selector),
+                (None, None) => Self::find_device(),
+            }
+        }
+    }
+
+    /// Find an arbitrary device by path (symlinks such as /dev/input/by-id
+    /// are followed) or by its kernel name
+    #[cfg(target_os = "linux")]
+    pub fn find_trigger_device(selector: &str) -> Result<DeviceInfo, EvdevError> {
+        if selector.starts_with('/') {
+            return Self::describe_device(&PathBuf::from(selector));
+        }
+
+        let entries = std::fs::read_dir("/dev/input").map_err(EvdevError::IoError)?;
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("event")))
+            .collect();
+        paths.sort();
+        paths
+            .iter()
+            .filter_map(|path| Self::describe_device(path).ok())
+            .find(|info| device_name_matches(&info.name, selector))
+            .ok_or(EvdevError::DeviceNotFound)
+    }
+
+    /// Identify any input device
+    #[cfg(target_os = "linux")]
+    fn describe_device(path: &PathBuf) -> Result<DeviceInfo, EvdevError> {
+        let device = evdev::Device::open(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => EvdevError::PermissionDenied,
+            std::io::ErrorKind::NotFound => EvdevError::DeviceNotFound,
+            _ => EvdevError::IoError(e),
+        })?;
+        let input_id = device.input_id();
+        Ok(DeviceInfo {
+            path: path.clone(),
+            name: device.name().unwrap_or("Unknown").to_string(),
+            vendor_id: input_id.vendor(),
+            product_id: input_id.product(),
+            is_mx_master_4: false,
+        })
+    }
+
     /// Scan /dev/input/ for MX Master 4 device
     ///
     /// Returns the first matching device found.
@@ -263,11 +428,11 @@ impl EvdevHandler {
         use evdev::{Device, EventType, RelativeAxisCode};
 
         // Find the device
-        let device_info = Self::find_device()?;
+        let device_info = self.locate()?;
         self.device_path = Some(device_info.path.clone());
 
         // Open the device for reading
-        let device = Device::open(&device_info.path).map_err(|e| {
+        let mut device = Device::open(&device_info.path).map_err(|e| {
             if e.kind() == std::io::ErrorKind::PermissionDenied {
                 tracing::error!(
                     "Permission denied opening {:?}. Make sure udev rules are installed \
@@ -286,43 +451,82 @@ impl EvdevHandler {
             device_info.path
         );
 
+        // Optional exclusive grab; the mouse keeps working without it
+        let mut grab = if self.menu_grab.enabled {
+            crate::menu_grab::MenuGrab::start(&mut device, &self.menu_grab)
+                .map_err(|e| tracing::warn!("Could not grab {:?}, back/forward stay visible: {}", device_info.path, e))
+                .ok()
+        } else {
+            None
+        };
+
         // Create async event stream using into_event_stream()
         let mut events = device.into_event_stream()
             .map_err(EvdevError::IoError)?;
 
+        // Stale-press watchdog: catches presses whose release was dropped
+        // (e.g. SYN_DROPPED) by cross-checking EVIOCGKEY
+        let mut watchdog = tokio::time::interval(Duration::from_millis(STALE_PRESS_CHECK_INTERVAL_MS));
+
         loop {
-            match events.next_event().await {
+            let next = tokio::select! {
+                result = events.next_event() => Some(result),
+                _ = watchdog.tick() => None,
+            };
+
+            let Some(result) = next else {
+                if press_is_stale(self.press_time, self.stale_press_timeout) {
+                    let state = KeyState::from_key_state(events.device().get_key_state(), &self.trigger_keys);
+                    if state == KeyState::Released {
+                        self.reset_stale_press().await;
+                    }
+                }
+                continue;
+            };
+
+            match result {
                 Ok(event) => {
+                    if let Some(active) = grab.as_mut() {
+                        let menu_open = self.menu_active || crate::menu_grab::is_menu_open();
+                        if let Err(e) = active.pass(&event, menu_open) {
+                            tracing::warn!("Passthrough device failed, releasing grab: {}", e);
+                            let _ = events.device_mut().ungrab();
+                            grab = None;
+                        }
+                    }
+
                     match event.event_type() {
                         EventType::KEY => {
                             let key_code = event.code();
-                            if GESTURE_BUTTON_CODES.contains(&key_code) {
+                            if self.trigger_keys.contains(&key_code) {
                                 self.handle_gesture_event(event.value()).await;
                             }
                         }
-                        EventType::RELATIVE => {
+                        EventType::RELATIVE if self.menu_active => {
                             // Track mouse movement while menu is active
-                            if self.menu_active {
-                                let code = RelativeAxisCode(event.code());
-                                let value = event.value();
-
-                                match code {
-                                    RelativeAxisCode::REL_X => {
-                                        self.cursor_x += value;
-                                        let _ = self.event_tx.send(GestureEvent::CursorMoved {
-                                            x: self.cursor_x,
-                                            y: self.cursor_y,
-                                        }).await;
-                                    }
-                                    RelativeAxisCode::REL_Y => {
-                                        self.cursor_y += value;
-                                        let _ = self.event_tx.send(GestureEvent::CursorMoved {
-                                            x: self.cursor_x,
-                                            y: self.cursor_y,
-                                        }).await;
-                                    }
-                                    _ => {}
+                            let code = RelativeAxisCode(event.code());
+                            let value = event.value();
+
+                            match code {
+                                RelativeAxisCode::REL_X => {
+                                    self.cursor_x += value;
+                                    let _ = self.event_tx.send(GestureEvent::CursorMoved {
+                                        x: self.cursor_x,
+                                        y: self.cursor_y,
+                                    }).await;
                                 }
+                                RelativeAxisCode::REL_Y => {
+                                    self.cursor_y += value;
+                                    let _ = self.event_tx.send(GestureEvent::CursorMoved {
+                                        x: self.cursor_x,
+                                        y: self.cursor_y,
+                                    }).await;
+                                }
+                                RelativeAxisCode::REL_WHEEL => {
+                                    // REL_WHEEL is positive when scrolling up
+                                    let _ = self.event_tx.send(GestureEvent::Scrolled { delta: -value }).await;
+                                }
+                                _ => {}
                             }
                         }
                         _ => {}
@@ -351,22 +555,14 @@ impl EvdevHandler {
                 self.cursor_x = 0;
                 self.cursor_y = 0;
 
-                // Check for Hyprland - use direct cursor query (no KWin script)
-                if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
-                    tracing::info!("Gesture button pressed - using Hyprland cursor query");
-                    let pos = crate::cursor::get_cursor_position();
-                    tracing::info!(x = pos.x, y = pos.y, "Cursor position from Hyprland");
-                    let _ = self.event_tx.send(GestureEvent::Pressed { x: pos.x, y: pos.y }).await;
-                } else {
-                    // KDE/other - try KWin script first for multi-monitor accuracy
-                    tracing::info!("Gesture button pressed - triggering KWin cursor query");
-                    if !Self::trigger_kwin_cursor_script() {
-                        // Fallback to get_cursor_position if KWin script fails
-                        let pos = crate::cursor::get_cursor_position();
-                        tracing::warn!(x = pos.x, y = pos.y, "KWin script failed, using fallback");
-                        let _ = self.event_tx.send(GestureEvent::Pressed { x: pos.x, y: pos.y }).await;
-                    }
-                    // If KWin script succeeded, it calls ShowMenuAtCursor via D-Bus directly
+                // Let the compositor open the menu at the true cursor position
+                // (KWin script calls ShowMenuAtCursor), else query the cursor
+                let backend = crate::compositor::backend();
+                tracing::info!(backend = backend.name(), "Gesture button pressed");
+                if !backend.show_overlay_hint() {
+                    let pos = crate::pointer_confinement::menu_position();
+                    tracing::info!(x = pos.x, y = pos.y, "Menu position from compositor backend");
+                    let _ = self.event_tx.send(GestureEvent::Pressed { x: pos.x, y: pos.y, menu: None }).await;
                 }
             }
             0 => {
@@ -389,96 +585,41 @@ impl EvdevHandler {
         }
     }
 
-    /// Trigger KWin script to get cursor position and call ShowMenuAtCursor
+    /// Force-release a press whose release event never arrived
     ///
-    /// This works correctly on Plasma 6 Wayland with multiple monitors.
-    fn trigger_kwin_cursor_script() -> bool {
-        use std::process::Command;
-        use std::io::Write;
-        use tempfile::Builder;
-
-        // Create KWin script that calls ShowMenuAtCursor with true cursor position
-        let script = r#"
-var pos = workspace.cursorPos;
-callDBus("org.kde.juhradialmx", "/org/kde/juhradialmx/Daemon",
-         "org.kde.juhradialmx.Daemon", "ShowMenuAtCursor",
-         pos.x, pos.y);
-"#;
-
-        // Create a temporary file with .js suffix securely
-        let mut temp_file = match Builder::new().suffix(".js").tempfile() {
-            Ok(file) => file,
-            Err(e) => {
-                tracing::warn!("Failed to create temp file for KWin script: {}", e);
-                return false;
-            }
-        };
+    /// Resets menu_active/press_time and emits a Released event so the
+    /// overlay receives HideMenu instead of staying stuck open.
+    async fn reset_stale_press(&mut self) {
+        tracing::warn!(
+            timeout_secs = self.stale_press_timeout.as_secs(),
+            "Gesture button not held according to key state - resetting stale press"
+        );
+        self.handle_gesture_event(0).await;
+    }
 
-        // Write script to temp file
-        if let Err(e) = write!(temp_file, "{}", script) {
-            tracing::warn!("Failed to write KWin script: {}", e);
-            return false;
+    /// Query the live key state (EVIOCGKEY) of the MX Master 4 gesture button
+    ///
+    /// Used by other input paths to cross-check a press whose release was
+    /// never seen. Only meaningful for buttons that reach evdev; a button
+    /// diverted over HID++ always reads as released. Unknown if the device
+    /// cannot be found or read.
+    pub fn is_gesture_key_held() -> KeyState {
+        #[cfg(not(target_os = "linux"))]
+        {
+            KeyState::Unknown
         }
 
-        // Get the path as a string
-        let script_path = temp_file.path().to_string_lossy();
-
-        // Load script via D-Bus
-        let load_result = Command::new("dbus-send")
-            .args([
-                "--session",
-                "--print-reply",
-                "--dest=org.kde.KWin",
-                "/Scripting",
-                "org.kde.kwin.Scripting.loadScript",
-                &format!("string:{}", script_path),
-            ])
-            .output();
-
-        let load_output = match load_result {
-            Ok(output) if output.status.success() => output,
-            _ => {
-                tracing::warn!("Failed to load KWin script");
-                return false;
-            }
-        };
-
-        // Parse script ID from output (looks like "int32 5")
-        let stdout = String::from_utf8_lossy(&load_output.stdout);
-        let script_id: Option<i32> = stdout
-            .lines()
-            .find(|line| line.contains("int32"))
-            .and_then(|line| line.split_whitespace().last())
-            .and_then(|s| s.parse().ok());
-
-        let script_id = match script_id {
-            Some(id) => id,
-            None => {
-                tracing::warn!("Failed to parse KWin script ID");
-                return false;
-            }
-        };
+        #[cfg(target_os = "linux")]
+        {
+            let Ok(info) = Self::find_device() else {
+                return KeyState::Unknown;
+            };
 
-        // Run the script
-        let run_result = Command::new("dbus-send")
-            .args([
-                "--session",
-                "--print-reply",
-                "--dest=org.kde.KWin",
-                &format!("/Scripting/Script{}", script_id),
-                "org.kde.kwin.Script.run",
-            ])
-            .output();
-
-        match run_result {
-            Ok(output) if output.status.success() => {
-                tracing::debug!(script_id, "KWin cursor script triggered successfully");
-                true
-            }
-            _ => {
-                tracing::warn!("Failed to run KWin script");
-                false
+            let keys = evdev::Device::open(&info.path).and_then(|d| d.get_key_state());
+            if let Err(e) = &keys {
+                tracing::debug!("Could not read key state of {:?}: {}", info.path, e);
             }
+            KeyState::from_key_state(keys, GESTURE_BUTTON_CODES)
         }
     }
 
@@ -514,6 +655,11 @@ callDBus("org.kde.juhradialmx", "/org/kde/juhradialmx/Daemon",
     }
 }
 
+/// Whether a device name matches a configured name (case-insensitive)
+pub fn device_name_matches(name: &str, selector: &str) -> bool {
+    name.trim().eq_ignore_ascii_case(selector.trim())
+}
+
 /// LogiOps Virtual Input handler for logid-generated keypresses
 ///
 /// Listens for KEY_F19 (press) and KEY_F20 (release) from logid
@@ -645,115 +791,14 @@ impl LogidHandler {
     async fn handle_press(&mut self) {
         self.press_time = Some(Instant::now());
 
-        // Check for Hyprland - use direct cursor query (no KWin script)
-        if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
-            tracing::info!("Logid: F19 press - using Hyprland cursor query");
-            let pos = crate::cursor::get_cursor_position();
-            tracing::info!(x = pos.x, y = pos.y, "Cursor position from Hyprland");
-            let _ = self.event_tx.send(GestureEvent::Pressed { x: pos.x, y: pos.y }).await;
-        } else {
-            // KDE/other - try KWin script first for multi-monitor accuracy
-            tracing::info!("Logid: F19 press - triggering KWin cursor query");
-            if !Self::trigger_kwin_cursor_script() {
-                // Fallback to get_cursor_position if KWin script fails
-                let pos = crate::cursor::get_cursor_position();
-                tracing::warn!(x = pos.x, y = pos.y, "KWin script failed, using fallback");
-                let _ = self.event_tx.send(GestureEvent::Pressed { x: pos.x, y: pos.y }).await;
-            }
-            // If KWin script succeeded, it calls ShowMenuAtCursor via D-Bus directly
-        }
-    }
-
-    /// Trigger KWin script to get cursor position and call ShowMenuAtCursor
-    ///
-    /// This works correctly on Plasma 6 Wayland with multiple monitors.
-    fn trigger_kwin_cursor_script() -> bool {
-        use std::process::Command;
-        use std::io::Write;
-        use tempfile::Builder;
-
-        // Create KWin script that calls ShowMenuAtCursor with true cursor position
-        let script = r#"
-var pos = workspace.cursorPos;
-callDBus("org.kde.juhradialmx", "/org/kde/juhradialmx/Daemon",
-         "org.kde.juhradialmx.Daemon", "ShowMenuAtCursor",
-         pos.x, pos.y);
-"#;
-
-        // Create a temporary file with .js suffix securely
-        let mut temp_file = match Builder::new().suffix(".js").tempfile() {
-            Ok(file) => file,
-            Err(e) => {
-                tracing::warn!("Failed to create temp file for KWin script: {}", e);
-                return false;
-            }
-        };
-
-        // Write script to temp file
-        if let Err(e) = write!(temp_file, "{}", script) {
-            tracing::warn!("Failed to write KWin script: {}", e);
-            return false;
-        }
-
-        // Get the path as a string
-        let script_path = temp_file.path().to_string_lossy();
-
-        // Load script via D-Bus
-        let load_result = Command::new("dbus-send")
-            .args([
-                "--session",
-                "--print-reply",
-                "--dest=org.kde.KWin",
-                "/Scripting",
-                "org.kde.kwin.Scripting.loadScript",
-                &format!("string:{}", script_path),
-            ])
-            .output();
-
-        let load_output = match load_result {
-            Ok(output) if output.status.success() => output,
-            _ => {
-                tracing::warn!("Failed to load KWin script");
-                return false;
-            }
-        };
-
-        // Parse script ID from output (looks like "int32 5")
-        let stdout = String::from_utf8_lossy(&load_output.stdout);
-        let script_id: Option<i32> = stdout
-            .lines()
-            .find(|line| line.contains("int32"))
-            .and_then(|line| line.split_whitespace().last())
-            .and_then(|s| s.parse().ok());
-
-        let script_id = match script_id {
-            Some(id) => id,
-            None => {
-                tracing::warn!("Failed to parse KWin script ID");
-                return false;
-            }
-        };
-
-        // Run the script
-        let run_result = Command::new("dbus-send")
-            .args([
-                "--session",
-                "--print-reply",
-                "--dest=org.kde.KWin",
-                &format!("/Scripting/Script{}", script_id),
-                "org.kde.kwin.Script.run",
-            ])
-            .output();
-
-        match run_result {
-            Ok(output) if output.status.success() => {
-                tracing::debug!(script_id, "KWin cursor script triggered successfully");
-                true
-            }
-            _ => {
-                tracing::warn!("Failed to run KWin script");
-                false
-            }
+        // Let the compositor open the menu at the true cursor position
+        // (KWin script calls ShowMenuAtCursor), else query the cursor
+        let backend = crate::compositor::backend();
+        tracing::info!(backend = backend.name(), "Logid: F19 press");
+        if !backend.show_overlay_hint() {
+            let pos = crate::pointer_confinement::menu_position();
+            tracing::info!(x = pos.x, y = pos.y, "Menu position from compositor backend");
+            let _ = self.event_tx.send(GestureEvent::Pressed { x: pos.x, y: pos.y, menu: None }).await;
         }
     }
 
@@ -821,8 +866,8 @@ mod tests {
 
     #[test]
     fn test_gesture_event_equality() {
-        let e1 = GestureEvent::Pressed { x: 100, y: 200 };
-        let e2 = GestureEvent::Pressed { x: 100, y: 200 };
+        let e1 = GestureEvent::Pressed { x: 100, y: 200, menu: None };
+        let e2 = GestureEvent::Pressed { x: 100, y: 200, menu: None };
         let e3 = GestureEvent::Released { duration_ms: 500 };
 
         assert_eq!(e1, e2);
@@ -837,4 +882,65 @@ mod tests {
         let err = EvdevError::PermissionDenied;
         assert!(format!("{}", err).contains("Permission denied"));
     }
+
+    #[test]
+    fn test_press_is_stale() {
+        let timeout = Duration::from_secs(STALE_PRESS_TIMEOUT_SECS);
+
+        // No open press is never stale
+        assert!(!press_is_stale(None, timeout));
+
+        // Fresh press is not stale
+        assert!(!press_is_stale(Some(Instant::now()), timeout));
+
+        // Press older than the timeout is stale
+        let old = Instant::now() - Duration::from_secs(STALE_PRESS_TIMEOUT_SECS + 1);
+        assert!(press_is_stale(Some(old), timeout));
+    }
+
+    #[test]
+    fn test_parse_key_code() {
+        assert_eq!(parse_key_code("BTN_EXTRA"), Some(0x114));
+        assert_eq!(parse_key_code("key_f13"), Some(183));
+        assert_eq!(parse_key_code("276"), Some(276));
+        assert_eq!(parse_key_code("0x116"), Some(0x116));
+        assert_eq!(parse_key_code("KEY_NOPE"), None);
+        assert_eq!(parse_key_code(""), None);
+    }
+
+    #[test]
+    fn test_trigger_config() {
+        let mut config = EvdevTriggerConfig { device: "  ".into(), key: "BTN_EXTRA".into() };
+        assert_eq!(config.device(), None);
+        assert_eq!(config.key_codes(), vec![0x114]);
+
+        config.key = "BTN_BOGUS".into();
+        config.validate();
+        assert!(config.key.is_empty());
+        assert_eq!(config.key_codes(), GESTURE_BUTTON_CODES);
+
+        assert!(device_name_matches("Kensington Expert Mouse ", "kensington expert mouse"));
+        assert!(!device_name_matches("Kensington Expert Mouse", "Kensington"));
+    }
+
+    #[test]
+    fn test_key_state_from_eviocgkey() {
+        let mut keys = evdev::AttributeSet::<evdev::KeyCode>::new();
+        assert_eq!(KeyState::from_key_state::<()>(Ok(keys.clone()), GESTURE_BUTTON_CODES), KeyState::Released);
+        keys.insert(evdev::KeyCode(GESTURE_BUTTON_CODES[0]));
+        assert_eq!(KeyState::from_key_state::<()>(Ok(keys), GESTURE_BUTTON_CODES), KeyState::Held);
+        assert_eq!(KeyState::from_key_state(Err(()), GESTURE_BUTTON_CODES), KeyState::Unknown);
+    }
+
+    #[test]
+    fn test_gesture_key_down() {
+        let mut keys = evdev::AttributeSet::<evdev::KeyCode>::new();
+        assert!(!gesture_key_down(&keys));
+
+        keys.insert(evdev::KeyCode::BTN_LEFT);
+        assert!(!gesture_key_down(&keys));
+
+        keys.insert(evdev::KeyCode(GESTURE_BUTTON_CODES[0]));
+        assert!(gesture_key_down(&keys));
+    }
 }
@@ -0,0 +1,387 @@
+//! Unix-socket fast path for cursor and slice updates
+//!
+//! D-Bus round-trips add jitter to hover tracking. When enabled, the daemon
+//! listens on a `SOCK_SEQPACKET` socket in `$XDG_RUNTIME_DIR/juhradial/`
+//! (announced via the `FastPathSocket` D-Bus property) and streams every
+//! cursor and slice update to connected overlays as small binary packets.
+//! D-Bus stays in charge of control messages (show/hide, actions, ...).
+//!
+//! # Packet format
+//!
+//! One update per packet (message boundaries are preserved by SEQPACKET):
+//!
+//! - `0x01 x:i32le y:i32le` - cursor offset from the menu center
+//! - `0x02 index:u8` - hovered slice
+//!
+//! Slow clients never block the daemon: a packet that cannot be sent
+//! immediately is dropped (the next update supersedes it).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::fmt;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Socket file name inside the runtime directory
+pub const FAST_PATH_SOCKET_NAME: &str = "cursor.sock";
+
+/// Packet kind: cursor offset
+const KIND_CURSOR: u8 = 0x01;
+
+/// Packet kind: hovered slice
+const KIND_SLICE: u8 = 0x02;
+
+/// Largest encoded packet
+pub const MAX_PACKET_LEN: usize = 9;
+
+/// Pending connections queued by the kernel
+const LISTEN_BACKLOG: i32 = 4;
+
+// ============================================================================
+// Updates
+// ============================================================================
+
+/// An update streamed to overlays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FastPathUpdate {
+    /// Cursor offset relative to the menu center
+    Cursor { x: i32, y: i32 },
+    /// Hovered slice index
+    Slice(u8),
+}
+
+impl FastPathUpdate {
+    /// Encode into `buf`, returning the packet length
+    pub fn encode(&self, buf: &mut [u8; MAX_PACKET_LEN]) -> usize {
+        match *self {
+            FastPathUpdate::Cursor { x, y } => {
+                buf[0] = KIND_CURSOR;
+                buf[1..5].copy_from_slice(&x.to_le_bytes());
+                buf[5..9].copy_from_slice(&y.to_le_bytes());
+                9
+            }
+            FastPathUpdate::Slice(index) => {
+                buf[0] = KIND_SLICE;
+                buf[1] = index;
+                2
+            }
+        }
+    }
+
+    /// Decode a packet (for clients and tests)
+    pub fn decode(packet: &[u8]) -> Option<Self> {
+        match packet {
+            [KIND_CURSOR, rest @ ..] if rest.len() == 8 => Some(FastPathUpdate::Cursor {
+                x: i32::from_le_bytes(rest[0..4].try_into().ok()?),
+                y: i32::from_le_bytes(rest[4..8].try_into().ok()?),
+            }),
+            [KIND_SLICE, index] => Some(FastPathUpdate::Slice(*index)),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+/// Fast path error type
+#[derive(Debug)]
+pub enum FastPathError {
+    /// No runtime directory to place the socket in
+    NoRuntimeDir,
+    /// Socket path does not fit in sockaddr_un
+    PathTooLong(PathBuf),
+    /// Socket setup failed
+    IoError(io::Error),
+}
+
+impl fmt::Display for FastPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FastPathError::NoRuntimeDir => write!(f, "XDG_RUNTIME_DIR is not set"),
+            FastPathError::PathTooLong(path) => write!(f, "Socket path too long: {}", path.display()),
+            FastPathError::IoError(e) => write!(f, "Socket error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FastPathError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FastPathError::IoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FastPathError {
+    fn from(err: io::Error) -> Self {
+        FastPathError::IoError(err)
+    }
+}
+
+// ============================================================================
+// Server
+// ============================================================================
+
+/// Listening socket and its connected clients
+pub struct FastPathServer {
+    /// Socket file path
+    path: PathBuf,
+    /// Listening socket
+    listener: OwnedFd,
+    /// Connected (non-blocking) clients
+    clients: Mutex<Vec<OwnedFd>>,
+}
+
+/// Running server (set once at startup when enabled)
+static SERVER: OnceLock<FastPathServer> = OnceLock::new();
+
+/// Default socket path: `$XDG_RUNTIME_DIR/juhradial/cursor.sock`
+pub fn default_socket_path() -> Option<PathBuf> {
+    dirs::runtime_dir().map(|dir| dir.join("juhradial").join(FAST_PATH_SOCKET_NAME))
+}
+
+/// Check an io result from libc (negative = error)
+fn check(ret: libc::c_int) -> io::Result<libc::c_int> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+/// Build a sockaddr_un for `path`
+fn socket_addr(path: &Path) -> Result<(libc::sockaddr_un, libc::socklen_t), FastPathError> {
+    // SAFETY: sockaddr_un is plain data; all-zero is a valid (empty) address
+    let mut addr: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+    addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+
+    let bytes = path.as_os_str().as_bytes();
+    // Keep room for the terminating NUL
+    if bytes.len() >= addr.sun_path.len() {
+        return Err(FastPathError::PathTooLong(path.to_path_buf()));
+    }
+    for (dst, src) in addr.sun_path.iter_mut().zip(bytes) {
+        *dst = *src as libc::c_char;
+    }
+
+    let len = std::mem::size_of::<libc::sa_family_t>() + bytes.len() + 1;
+    Ok((addr, len as libc::socklen_t))
+}
+
+/// Create a SEQPACKET unix socket
+fn seqpacket_socket() -> io::Result<OwnedFd> {
+    // SAFETY: plain socket(2) call; the fd is owned immediately
+    let fd = check(unsafe { libc::socket(libc::AF_UNIX, libc::SOCK_SEQPACKET | libc::SOCK_CLOEXEC, 0) })?;
+    // SAFETY: fd was just returned by socket(2) and is not owned elsewhere
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+impl FastPathServer {
+    /// Bind the socket at `path` (replacing a stale socket file)
+    pub fn bind(path: &Path) -> Result<Self, FastPathError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+        }
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+
+        let (addr, len) = socket_addr(path)?;
+        let listener = seqpacket_socket()?;
+        // SAFETY: addr is a valid sockaddr_un of `len` bytes
+        check(unsafe {
+            libc::bind(listener.as_raw_fd(), &addr as *const libc::sockaddr_un as *const libc::sockaddr, len)
+        })?;
+        // SAFETY: listener is a bound socket
+        check(unsafe { libc::listen(listener.as_raw_fd(), LISTEN_BACKLOG) })?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            listener,
+            clients: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Socket file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of connected clients
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().map(|c| c.len()).unwrap_or(0)
+    }
+
+    /// Block until a client connects and register it
+    pub fn accept(&self) -> io::Result<()> {
+        // SAFETY: listener is a listening socket; peer address is not requested
+        let fd = check(unsafe {
+            libc::accept4(
+                self.listener.as_raw_fd(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
+            )
+        })?;
+        // SAFETY: fd was just returned by accept4 and is not owned elsewhere
+        let client = unsafe { OwnedFd::from_raw_fd(fd) };
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.push(client);
+            tracing::debug!(clients = clients.len(), "Fast-path client connected");
+        }
+        Ok(())
+    }
+
+    /// Send an update to every client, dropping clients that went away
+    pub fn publish(&self, update: FastPathUpdate) {
+        let Ok(mut clients) = self.clients.lock() else {
+            return;
+        };
+        if clients.is_empty() {
+            return;
+        }
+
+        let mut buf = [0u8; MAX_PACKET_LEN];
+        let len = update.encode(&mut buf);
+        clients.retain(|client| {
+            // SAFETY: buf holds `len` initialized bytes; the send never blocks
+            let sent = unsafe {
+                libc::send(
+                    client.as_raw_fd(),
+                    buf.as_ptr() as *const libc::c_void,
+                    len,
+                    libc::MSG_DONTWAIT | libc::MSG_NOSIGNAL,
+                )
+            };
+            if sent >= 0 {
+                return true;
+            }
+            // Full queue: drop this packet, keep the client
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::WouldBlock {
+                return true;
+            }
+            tracing::debug!("Fast-path client disconnected: {}", err);
+            false
+        });
+    }
+}
+
+impl Drop for FastPathServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Start the fast path at the default location
+///
+/// Connections are accepted on a dedicated thread; updates are sent from
+/// whichever task calls [`publish`].
+pub fn start_fast_path() -> Result<&'static Path, FastPathError> {
+    if let Some(server) = SERVER.get() {
+        return Ok(&server.path);
+    }
+
+    let path = default_socket_path().ok_or(FastPathError::NoRuntimeDir)?;
+    let server = FastPathServer::bind(&path)?;
+    let server = SERVER.get_or_init(|| server);
+
+    std::thread::Builder::new()
+        .name("fast-path-accept".to_string())
+        .spawn(move || loop {
+            if let Err(e) = server.accept() {
+                tracing::warn!("Fast-path accept failed: {}", e);
+                std::thread::sleep(std::time::Duration::from_secs(1));
+            }
+        })?;
+
+    tracing::info!(path = %server.path.display(), "Fast-path socket listening");
+    Ok(&server.path)
+}
+
+/// Publish an update if the fast path is running
+pub fn publish(update: FastPathUpdate) {
+    if let Some(server) = SERVER.get() {
+        server.publish(update);
+    }
+}
+
+/// Socket path announced on D-Bus (empty if the fast path is not running)
+pub fn socket_path() -> String {
+    SERVER
+        .get()
+        .map(|server| server.path.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Connect a SEQPACKET client to `path`
+    fn connect(path: &Path) -> OwnedFd {
+        let (addr, len) = socket_addr(path).unwrap();
+        let client = seqpacket_socket().unwrap();
+        // SAFETY: addr is a valid sockaddr_un of `len` bytes
+        check(unsafe {
+            libc::connect(client.as_raw_fd(), &addr as *const libc::sockaddr_un as *const libc::sockaddr, len)
+        })
+        .unwrap();
+        client
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let mut buf = [0u8; MAX_PACKET_LEN];
+        for update in [
+            FastPathUpdate::Cursor { x: -120, y: 75 },
+            FastPathUpdate::Slice(5),
+        ] {
+            let len = update.encode(&mut buf);
+            assert_eq!(FastPathUpdate::decode(&buf[..len]), Some(update));
+        }
+        assert_eq!(FastPathUpdate::decode(&[0x01, 0x00]), None);
+        assert_eq!(FastPathUpdate::decode(&[0x7F, 0x00]), None);
+    }
+
+    #[test]
+    fn test_streams_packets_to_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run").join(FAST_PATH_SOCKET_NAME);
+        let server = FastPathServer::bind(&path).unwrap();
+
+        let client = connect(&path);
+        server.accept().unwrap();
+        assert_eq!(server.client_count(), 1);
+
+        server.publish(FastPathUpdate::Cursor { x: 10, y: -4 });
+        server.publish(FastPathUpdate::Slice(3));
+
+        let mut buf = [0u8; 64];
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            // SAFETY: buf is a writable buffer of buf.len() bytes
+            let n = unsafe { libc::recv(client.as_raw_fd(), buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+            assert!(n > 0);
+            received.push(FastPathUpdate::decode(&buf[..n as usize]).unwrap());
+        }
+        assert_eq!(
+            received,
+            vec![FastPathUpdate::Cursor { x: 10, y: -4 }, FastPathUpdate::Slice(3)]
+        );
+
+        // Disconnected clients are dropped on the next publish
+        drop(client);
+        server.publish(FastPathUpdate::Slice(1));
+        assert_eq!(server.client_count(), 0);
+    }
+}
@@ -0,0 +1,134 @@
+//! HID++ feature table cache
+//!
+//! Enumerating a device's feature table takes one IFeatureSet round trip per
+//! feature, which dominates reconnect time after sleep. The table of each
+//! device is cached by serial (unit ID and model ID from DeviceInformation)
+//! in `$XDG_CACHE_HOME/juhradial/features.json`; on reconnect only the
+//! feature count is re-read to check the entry is still current (a firmware
+//! update that adds or removes features invalidates it).
+//!
+//! Blocklisted features are never stored, exactly like the live table.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Cache subdirectory
+const CACHE_DIR: &str = "juhradial";
+
+/// Cache file name
+const CACHE_FILE: &str = "features.json";
+
+/// Cached feature table of one device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedFeatures {
+    /// IFeatureSet feature count when the table was read
+    pub feature_count: u8,
+    /// (feature ID, feature index), blocklisted features excluded
+    pub features: Vec<(u16, u8)>,
+}
+
+/// Feature tables of all known devices
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureCache {
+    /// Tables by device serial
+    #[serde(default)]
+    devices: HashMap<String, CachedFeatures>,
+
+    /// Backing file (not serialized)
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl FeatureCache {
+    /// Get the default cache file path
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|p| p.join(CACHE_DIR).join(CACHE_FILE))
+    }
+
+    /// Load the cache (missing or corrupt files start empty)
+    pub fn load(path: &Path) -> Self {
+        let mut cache: Self = fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        cache.path = Some(path.to_path_buf());
+        cache
+    }
+
+    /// Cached table for a device
+    pub fn get(&self, serial: &str) -> Option<&CachedFeatures> {
+        self.devices.get(serial)
+    }
+
+    /// Remember a device's table
+    pub fn insert(&mut self, serial: &str, features: CachedFeatures) {
+        self.devices.insert(serial.to_string(), features);
+    }
+
+    /// Forget a device's table
+    pub fn remove(&mut self, serial: &str) {
+        self.devices.remove(serial);
+    }
+
+    /// Write the cache to its backing file (see [`crate::state_file`])
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        crate::state_file::write_atomic(path, serde_json::to_string(self)?.as_bytes())
+    }
+}
+
+/// Look up a device in the default cache
+pub fn lookup(serial: &str) -> Option<CachedFeatures> {
+    let path = FeatureCache::default_path()?;
+    FeatureCache::load(&path).get(serial).cloned()
+}
+
+/// Store a device's table in the default cache (None = forget it)
+pub fn store(serial: &str, features: Option<CachedFeatures>) {
+    let Some(path) = FeatureCache::default_path() else {
+        return;
+    };
+    let mut cache = FeatureCache::load(&path);
+    match features {
+        Some(features) => cache.insert(serial, features),
+        None => cache.remove(serial),
+    }
+    if let Err(e) = cache.save() {
+        tracing::debug!("Failed to save HID++ feature cache: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CACHE_DIR).join(CACHE_FILE);
+
+        let mut cache = FeatureCache::load(&path);
+        assert!(cache.get("1A2B3C4D-B042").is_none());
+
+        let table = CachedFeatures {
+            feature_count: 3,
+            features: vec![(0x0001, 1), (0x1004, 2), (0x19B0, 3)],
+        };
+        cache.insert("1A2B3C4D-B042", table.clone());
+        cache.save().unwrap();
+
+        assert_eq!(FeatureCache::load(&path).get("1A2B3C4D-B042"), Some(&table));
+
+        // Corrupt file starts empty
+        fs::write(&path, "{").unwrap();
+        assert!(FeatureCache::load(&path).get("1A2B3C4D-B042").is_none());
+    }
+}
@@ -0,0 +1,217 @@
+//! Read-only HID++ feature explorer
+//!
+//! Backs `juhradialctl features`: lists the connected device's full HID++
+//! feature table, classifies every entry against the safety lists in
+//! [`crate::hidpp`] (blocklisted / allowed / unknown) and derives which
+//! capabilities juhradiald will use. Only IRoot and IFeatureSet queries are
+//! sent, so it is safe to run on unknown firmware and paste into bug reports.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use crate::hidpp::{allowed_features, blocklisted_features, features, ConnectionType, HidppDevice};
+
+/// Safety classification of a feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureStatus {
+    /// Never used: writes to onboard memory (reason)
+    Blocklisted(&'static str),
+    /// On the explicit safelist
+    Allowed,
+    /// Neither listed nor blocklisted
+    Unknown,
+}
+
+impl FeatureStatus {
+    /// Classify a feature ID
+    pub fn of(feature_id: u16) -> Self {
+        if let Some(reason) = blocklisted_features::blocklist_reason(feature_id) {
+            FeatureStatus::Blocklisted(reason)
+        } else if allowed_features::is_allowed(feature_id) {
+            FeatureStatus::Allowed
+        } else {
+            FeatureStatus::Unknown
+        }
+    }
+
+    /// Short label for table output
+    pub fn label(&self) -> &'static str {
+        match self {
+            FeatureStatus::Blocklisted(_) => "blocklisted",
+            FeatureStatus::Allowed => "allowed",
+            FeatureStatus::Unknown => "unknown",
+        }
+    }
+}
+
+/// Human-readable name of well-known HID++ 2.0 features
+pub fn feature_name(feature_id: u16) -> Option<&'static str> {
+    Some(match feature_id {
+        features::I_ROOT => "IRoot",
+        features::I_FEATURE_SET => "IFeatureSet",
+        0x0002 => "IFeatureInfo",
+        0x0003 => "DeviceFwVersion",
+        0x0004 => "DeviceUnitID",
+        features::DEVICE_NAME => "DeviceName",
+        0x0007 => "DeviceFriendlyName",
+        0x0020 => "ConfigChange",
+        0x00C2 => "DFUControl",
+        0x00D0 => "DFU",
+        features::BATTERY_STATUS => "BatteryStatus",
+        features::UNIFIED_BATTERY => "UnifiedBattery",
+        features::LED_CONTROL => "LEDControl",
+        features::CHANGE_HOST => "ChangeHost",
+        features::HOSTS_INFO => "HostsInfo",
+        0x1D4B => "WirelessDeviceStatus",
+        features::MX4_HAPTIC_ALT => "Haptic (alt)",
+        features::MX_MASTER_4_HAPTIC => "Haptic",
+        blocklisted_features::SPECIAL_KEYS => "ReprogControlsV4",
+        blocklisted_features::PERSISTENT_REMAPPABLE_ACTION => "PersistentRemappableAction",
+        features::SMARTSHIFT_LEGACY => "SmartShift",
+        features::HIRES_SCROLL => "HiResScroll (SmartShift)",
+        0x2121 => "HiResWheel",
+        0x2150 => "Thumbwheel",
+        features::ADJUSTABLE_DPI => "AdjustableDPI",
+        0x2250 => "AnalysisMode",
+        0x6501 => "Gestures",
+        blocklisted_features::REPORT_RATE => "ReportRate",
+        blocklisted_features::MODE_STATUS => "ModeStatus",
+        blocklisted_features::ONBOARD_PROFILES => "OnboardProfiles",
+        blocklisted_features::MOUSE_BUTTON_SPY => "MouseButtonSpy",
+        features::FORCE_FEEDBACK => "ForceFeedback",
+        _ => return None,
+    })
+}
+
+/// What juhradiald uses a feature for (None = not used)
+pub fn feature_use(feature_id: u16) -> Option<&'static str> {
+    Some(match feature_id {
+        features::I_ROOT | features::I_FEATURE_SET => "feature discovery",
+        features::MX_MASTER_4_HAPTIC | features::MX4_HAPTIC_ALT => "haptic feedback",
+        features::FORCE_FEEDBACK => "haptic feedback (legacy)",
+        features::ADJUSTABLE_DPI => "DPI control",
+        features::HIRES_SCROLL => "SmartShift",
+        features::SMARTSHIFT_LEGACY => "SmartShift (legacy)",
+        features::UNIFIED_BATTERY => "battery status",
+        features::BATTERY_STATUS => "battery status (legacy)",
+        features::CHANGE_HOST => "Easy-Switch status",
+        blocklisted_features::SPECIAL_KEYS => "temporary gesture divert (native_divert only)",
+        _ => return None,
+    })
+}
+
+/// One entry of a device's feature table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureInfo {
+    /// Feature index on the device
+    pub index: u8,
+    /// HID++ feature ID
+    pub feature_id: u16,
+    /// Safety classification
+    pub status: FeatureStatus,
+}
+
+impl FeatureInfo {
+    /// Classify a raw (index, feature ID) pair
+    pub fn new(index: u8, feature_id: u16) -> Self {
+        Self {
+            index,
+            feature_id,
+            status: FeatureStatus::of(feature_id),
+        }
+    }
+}
+
+/// A capability of juhradiald and whether the device provides it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capability {
+    /// Capability name
+    pub name: &'static str,
+    /// Feature that provides it (None = unavailable)
+    pub feature_id: Option<u16>,
+}
+
+/// Capabilities juhradiald will use, in order of preference per capability
+pub fn capabilities(table: &[FeatureInfo]) -> Vec<Capability> {
+    let first = |ids: &[u16]| ids.iter().copied().find(|id| table.iter().any(|f| f.feature_id == *id));
+    vec![
+        Capability {
+            name: "Haptic feedback",
+            feature_id: first(&[features::MX_MASTER_4_HAPTIC, features::MX4_HAPTIC_ALT, features::FORCE_FEEDBACK]),
+        },
+        Capability {
+            name: "DPI control",
+            feature_id: first(&[features::ADJUSTABLE_DPI]),
+        },
+        Capability {
+            name: "SmartShift",
+            feature_id: first(&[features::HIRES_SCROLL, features::SMARTSHIFT_LEGACY]),
+        },
+        Capability {
+            name: "Battery status",
+            feature_id: first(&[features::UNIFIED_BATTERY, features::BATTERY_STATUS]),
+        },
+        Capability {
+            name: "Easy-Switch status",
+            feature_id: first(&[features::CHANGE_HOST]),
+        },
+    ]
+}
+
+/// Feature table of the connected device
+#[derive(Debug, Clone)]
+pub struct DeviceFeatures {
+    /// How the device is connected
+    pub connection: ConnectionType,
+    /// Complete feature table, by index
+    pub features: Vec<FeatureInfo>,
+}
+
+/// Open the first HID++ 2.0 device and read its feature table
+pub fn explore() -> Option<DeviceFeatures> {
+    let mut device = HidppDevice::open()?;
+    let features = device
+        .list_features()
+        .into_iter()
+        .map(|(index, feature_id)| FeatureInfo::new(index, feature_id))
+        .collect();
+    Some(DeviceFeatures {
+        connection: device.connection_type(),
+        features,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_classification() {
+        assert_eq!(FeatureStatus::of(features::MX_MASTER_4_HAPTIC), FeatureStatus::Allowed);
+        assert_eq!(
+            FeatureStatus::of(blocklisted_features::ONBOARD_PROFILES),
+            FeatureStatus::Blocklisted("Persistent profile storage")
+        );
+        assert_eq!(FeatureStatus::of(0x1D4B), FeatureStatus::Unknown);
+        assert_eq!(feature_name(0x1D4B), Some("WirelessDeviceStatus"));
+        assert_eq!(feature_use(blocklisted_features::ONBOARD_PROFILES), None);
+    }
+
+    #[test]
+    fn test_capabilities_prefer_newer_features() {
+        let table: Vec<_> = [
+            (0, features::I_ROOT),
+            (5, features::BATTERY_STATUS),
+            (6, features::UNIFIED_BATTERY),
+            (9, features::MX_MASTER_4_HAPTIC),
+        ]
+        .into_iter()
+        .map(|(index, id)| FeatureInfo::new(index, id))
+        .collect();
+
+        let caps = capabilities(&table);
+        let feature = |name: &str| caps.iter().find(|c| c.name == name).unwrap().feature_id;
+        assert_eq!(feature("Battery status"), Some(features::UNIFIED_BATTERY));
+        assert_eq!(feature("Haptic feedback"), Some(features::MX_MASTER_4_HAPTIC));
+        assert_eq!(feature("DPI control"), None);
+    }
+}
@@ -0,0 +1,148 @@
+//! Game-mode detection for JuhRadial MX
+//!
+//! Avoids accidental overlays during games. A game is detected when the
+//! focused window's class is in the user's app list, is a Steam game
+//! (`steam_app_*`), or Feral GameMode (`gamemoded`) has registered clients.
+//!
+//! While active, the daemon either ignores the gesture trigger entirely or
+//! tells overlays to switch to a minimal, non-blurred theme
+//! (`GameModeChanged` signal / `GameModeActive` property).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::config::{GameModeConfig, GameModeResponse, SharedConfig};
+use crate::dbus::{DBUS_INTERFACE, DBUS_PATH};
+use crate::window_tracker::WindowTracker;
+
+/// How often game mode is re-evaluated (seconds)
+const GAME_MODE_POLL_INTERVAL_SECS: u64 = 1;
+
+/// Window class prefix Steam assigns to games
+const STEAM_APP_PREFIX: &str = "steam_app_";
+
+/// Feral GameMode D-Bus daemon
+const GAMEMODE_NAME: &str = "com.feralinteractive.GameMode";
+const GAMEMODE_PATH: &str = "/com/feralinteractive/GameMode";
+
+/// Whether game mode is currently active
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Check if game mode is currently active
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Check if the gesture trigger should be ignored right now
+pub fn suppresses_trigger(config: &GameModeConfig) -> bool {
+    is_active() && config.response == GameModeResponse::SuppressTrigger
+}
+
+/// Decide whether the current desktop state counts as gaming
+pub fn is_game(window_class: Option<&str>, gamemode_clients: i32, config: &GameModeConfig) -> bool {
+    if !config.enabled {
+        return false;
+    }
+    if config.detect_gamemoded && gamemode_clients > 0 {
+        return true;
+    }
+    let Some(class) = window_class else {
+        return false;
+    };
+    (config.detect_steam && class.starts_with(STEAM_APP_PREFIX))
+        || config.apps.iter().any(|app| app.eq_ignore_ascii_case(class))
+}
+
+/// Number of clients registered with Feral GameMode (0 if it is not running)
+async fn gamemode_client_count(connection: &zbus::Connection) -> i32 {
+    let proxy = match zbus::Proxy::new(connection, GAMEMODE_NAME, GAMEMODE_PATH, GAMEMODE_NAME).await {
+        Ok(p) => p,
+        Err(_) => return 0,
+    };
+    proxy.get_property::<i32>("ClientCount").await.unwrap_or(0)
+}
+
+/// Track game mode and announce changes on D-Bus
+pub async fn start_game_mode_monitor(
+    connection: zbus::Connection,
+    window_tracker: Arc<WindowTracker>,
+    config: SharedConfig,
+) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(GAME_MODE_POLL_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        let game_config = match config.read() {
+            Ok(c) => c.game_mode.clone(),
+            Err(_) => continue,
+        };
+
+        let active = if game_config.enabled {
+            let class = if window_tracker.is_available() {
+                window_tracker.refresh_active_window().await
+            } else {
+                None
+            };
+            let clients = if game_config.detect_gamemoded {
+                gamemode_client_count(&connection).await
+            } else {
+                0
+            };
+            is_game(class.as_deref(), clients, &game_config)
+        } else {
+            false
+        };
+
+        if ACTIVE.swap(active, Ordering::Relaxed) == active {
+            continue;
+        }
+
+        let response = game_config.response.as_str();
+        tracing::info!(active, response, "Game mode changed");
+        if let Err(e) = connection
+            .emit_signal(None::<&str>, DBUS_PATH, DBUS_INTERFACE, "GameModeChanged", &(active, response))
+            .await
+        {
+            tracing::warn!("Failed to emit GameModeChanged: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_game() {
+        let config = GameModeConfig {
+            apps: vec!["Minecraft".to_string()],
+            ..GameModeConfig::default()
+        };
+
+        assert!(is_game(Some("steam_app_570"), 0, &config));
+        assert!(is_game(Some("minecraft"), 0, &config));
+        assert!(is_game(Some("konsole"), 1, &config));
+        assert!(!is_game(Some("konsole"), 0, &config));
+        assert!(!is_game(None, 0, &config));
+    }
+
+    #[test]
+    fn test_detection_switches() {
+        let config = GameModeConfig {
+            detect_steam: false,
+            detect_gamemoded: false,
+            ..GameModeConfig::default()
+        };
+        assert!(!is_game(Some("steam_app_570"), 3, &config));
+
+        let disabled = GameModeConfig {
+            enabled: false,
+            apps: vec!["minecraft".to_string()],
+            ..GameModeConfig::default()
+        };
+        assert!(!is_game(Some("minecraft"), 1, &disabled));
+    }
+}
@@ -0,0 +1,181 @@
+//! GlobalShortcuts portal trigger backend
+//!
+//! Input backend for compositors where evdev access is restricted (Flatpak,
+//! hardened desktops). Registers a "show radial menu" shortcut through
+//! `org.freedesktop.portal.GlobalShortcuts` and turns the portal's
+//! `Activated` / `Deactivated` signals into `Pressed` / `Released`
+//! [`GestureEvent`]s, so the rest of the daemon works unchanged.
+//!
+//! The preferred trigger is only a hint: the desktop may show a dialog and
+//! let the user choose the actual key.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+
+use crate::evdev::GestureEvent;
+use crate::portal::{create_session, new_token, portal_request, PortalError, PortalOptions, PORTAL_NAME, PORTAL_PATH};
+
+/// GlobalShortcuts portal interface
+const GLOBAL_SHORTCUTS_INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+
+/// Shortcut ID registered with the portal
+pub const SHORTCUT_ID: &str = "show-radial-menu";
+
+/// Human-readable shortcut description shown by the desktop
+const SHORTCUT_DESCRIPTION: &str = "Show JuhRadial MX radial menu";
+
+/// Shortcut list entry for BindShortcuts: (id, {description, preferred_trigger})
+type ShortcutEntry<'a> = (&'a str, PortalOptions<'a>);
+
+/// Activated/Deactivated signal body: (session_handle, shortcut_id, timestamp, options)
+type ShortcutSignal = (OwnedObjectPath, String, u64, HashMap<String, OwnedValue>);
+
+/// GlobalShortcuts portal handler
+pub struct GlobalShortcutsHandler {
+    /// Channel to send gesture events
+    event_tx: mpsc::Sender<GestureEvent>,
+    /// Preferred trigger passed to the portal
+    preferred_trigger: String,
+    /// Time when the shortcut was activated
+    press_time: Option<Instant>,
+}
+
+impl GlobalShortcutsHandler {
+    /// Create a new GlobalShortcuts handler
+    pub fn new(event_tx: mpsc::Sender<GestureEvent>, preferred_trigger: &str) -> Self {
+        Self {
+            event_tx,
+            preferred_trigger: preferred_trigger.to_string(),
+            press_time: None,
+        }
+    }
+
+    /// Register the shortcut and forward activations until the portal goes away
+    pub async fn start(&mut self, connection: &zbus::Connection) -> Result<(), PortalError> {
+        let session = create_session(connection, GLOBAL_SHORTCUTS_INTERFACE).await?;
+
+        // Subscribe before binding so an immediate activation is not lost
+        let proxy = zbus::Proxy::new(connection, PORTAL_NAME, PORTAL_PATH, GLOBAL_SHORTCUTS_INTERFACE).await?;
+        let mut activated = proxy.receive_signal("Activated").await?;
+        let mut deactivated = proxy.receive_signal("Deactivated").await?;
+
+        let token = new_token();
+        let options: PortalOptions = HashMap::from([("handle_token", Value::from(token.as_str()))]);
+        let results = portal_request(
+            connection,
+            GLOBAL_SHORTCUTS_INTERFACE,
+            "BindShortcuts",
+            &(
+                ObjectPath::from(&session),
+                shortcut_entries(&self.preferred_trigger),
+                "",
+                options,
+            ),
+            &token,
+        )
+        .await?;
+
+        tracing::info!(
+            preferred_trigger = %self.preferred_trigger,
+            bound = results.contains_key("shortcuts"),
+            "GlobalShortcuts portal trigger registered"
+        );
+
+        loop {
+            tokio::select! {
+                Some(msg) = activated.next() => {
+                    let (handle, id, _, _): ShortcutSignal = msg.body().deserialize()?;
+                    if handle == session && id == SHORTCUT_ID && self.on_activated() {
+                        self.send_pressed().await;
+                    }
+                }
+                Some(msg) = deactivated.next() => {
+                    let (handle, id, _, _): ShortcutSignal = msg.body().deserialize()?;
+                    if handle == session && id == SHORTCUT_ID {
+                        if let Some(duration_ms) = self.on_deactivated() {
+                            let _ = self.event_tx.send(GestureEvent::Released { duration_ms }).await;
+                        }
+                    }
+                }
+                else => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record an activation; returns false for repeats while already held
+    fn on_activated(&mut self) -> bool {
+        if self.press_time.is_some() {
+            return false;
+        }
+        self.press_time = Some(Instant::now());
+        true
+    }
+
+    /// Record a deactivation; returns the hold duration if a press was active
+    fn on_deactivated(&mut self) -> Option<u64> {
+        self.press_time
+            .take()
+            .map(|t| t.elapsed().as_millis() as u64)
+    }
+
+    /// Emit Pressed at the current cursor position
+    async fn send_pressed(&self) {
+        // Cursor queries shell out to compositor tools; keep them off the runtime
+        let pos = tokio::task::spawn_blocking(crate::pointer_confinement::menu_position)
+            .await
+            .unwrap_or_default();
+        tracing::info!(x = pos.x, y = pos.y, "GlobalShortcuts trigger activated");
+        let _ = self.event_tx.send(GestureEvent::Pressed { x: pos.x, y: pos.y, menu: None }).await;
+    }
+}
+
+/// Build the BindShortcuts shortcut list
+fn shortcut_entries(preferred_trigger: &str) -> Vec<ShortcutEntry<'_>> {
+    let mut options: PortalOptions = HashMap::from([("description", Value::from(SHORTCUT_DESCRIPTION))]);
+    if !preferred_trigger.is_empty() {
+        options.insert("preferred_trigger", Value::from(preferred_trigger));
+    }
+    vec![(SHORTCUT_ID, options)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::zvariant::Type;
+
+    fn handler() -> GlobalShortcutsHandler {
+        let (tx, _rx) = mpsc::channel(4);
+        GlobalShortcutsHandler::new(tx, "F19")
+    }
+
+    #[test]
+    fn test_shortcut_entries() {
+        let entries = shortcut_entries("F19");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, SHORTCUT_ID);
+        assert!(entries[0].1.contains_key("preferred_trigger"));
+        assert_eq!(<Vec<ShortcutEntry>>::SIGNATURE.to_string(), "a(sa{sv})");
+
+        // Empty trigger leaves the choice to the desktop
+        assert!(!shortcut_entries("")[0].1.contains_key("preferred_trigger"));
+    }
+
+    #[test]
+    fn test_activation_cycle() {
+        let mut handler = handler();
+        assert!(handler.on_activated());
+        // Repeated Activated while held is ignored
+        assert!(!handler.on_activated());
+        assert!(handler.on_deactivated().is_some());
+        // Deactivated without a press is ignored
+        assert!(handler.on_deactivated().is_none());
+    }
+}
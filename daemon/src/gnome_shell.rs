@@ -0,0 +1,74 @@
+//! GNOME Shell overview and app grid for `gnome_shell` actions
+//!
+//! ```json
+//! {"type": "gnome_shell", "value": "overview"}
+//! {"type": "gnome_shell", "value": "app_grid"}
+//! ```
+//!
+//! Both toggle: if the overview (or the app grid, which lives in it) is
+//! showing it is closed, otherwise it opens. This goes through
+//! `org.gnome.Shell` (`OverviewActive`, `ShowApplications`). GNOME 41+
+//! only lets allow-listed callers use some of that interface; when the
+//! shell refuses, the action falls back to the default keybinding
+//! ([`GnomeShellView::fallback_shortcut`]).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use serde::{Deserialize, Serialize};
+use zbus::zvariant::Value;
+
+/// GNOME Shell bus name, object and interface
+const SHELL_NAME: &str = "org.gnome.Shell";
+const SHELL_PATH: &str = "/org/gnome/Shell";
+const SHELL_INTERFACE: &str = "org.gnome.Shell";
+
+/// What a `gnome_shell` action toggles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GnomeShellView {
+    /// Activities overview (window picker and workspaces)
+    Overview,
+    /// Application grid
+    AppGrid,
+}
+
+impl GnomeShellView {
+    /// Default GNOME keybinding for the view, used when D-Bus is refused
+    pub fn fallback_shortcut(self) -> &'static str {
+        match self {
+            GnomeShellView::Overview => "super",
+            GnomeShellView::AppGrid => "super+a",
+        }
+    }
+}
+
+/// Toggle `view`; returns whether it is showing afterwards
+pub async fn toggle(view: GnomeShellView) -> zbus::Result<bool> {
+    let connection = zbus::Connection::session().await?;
+    let shell = zbus::Proxy::new(&connection, SHELL_NAME, SHELL_PATH, SHELL_INTERFACE).await?;
+    let showing = shell.get_property::<bool>("OverviewActive").await?;
+
+    let show = !showing;
+    match (view, show) {
+        (GnomeShellView::AppGrid, true) => shell.call_method("ShowApplications", &()).await.map(|_| ())?,
+        _ => shell
+            .set_property("OverviewActive", Value::from(show))
+            .await
+            .map_err(zbus::Error::from)?,
+    }
+    tracing::info!(?view, show, "GNOME Shell view toggled");
+    Ok(show)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_names() {
+        let view: GnomeShellView = serde_json::from_str(r#""app_grid""#).unwrap();
+        assert_eq!(view, GnomeShellView::AppGrid);
+        assert_eq!(serde_json::to_string(&GnomeShellView::Overview).unwrap(), r#""overview""#);
+        assert_eq!(GnomeShellView::AppGrid.fallback_shortcut(), "super+a");
+    }
+}
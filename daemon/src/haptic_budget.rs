@@ -0,0 +1,113 @@
+//! Rate limiting for haptic sends
+//!
+//! A token bucket shared by every send path of the
+//! [`crate::hidpp::HapticManager`], so a flood of slice changes or a
+//! misbehaving D-Bus caller (`TriggerHaptic`, `TestHaptic`) cannot saturate
+//! the HID++ link and delay the feedback that matters. Menu appear, confirm
+//! and invalid are priority sends: they always go out and only spend a
+//! token if one is left, while other sends are dropped when the bucket is
+//! empty.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::time::Instant;
+
+use crate::config::HapticRateLimitConfig;
+
+/// Token bucket limiting haptic sends
+#[derive(Debug, Clone)]
+pub struct HapticBudget {
+    /// Enforce the budget (false = unlimited)
+    enabled: bool,
+    /// Tokens added per second
+    rate: f64,
+    /// Bucket capacity
+    burst: f64,
+    /// Tokens available
+    tokens: f64,
+    /// Last refill
+    last_refill: Instant,
+}
+
+impl HapticBudget {
+    /// Create a full bucket from (validated) configuration
+    pub fn from_config(config: &HapticRateLimitConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            rate: config.events_per_sec as f64,
+            burst: config.burst as f64,
+            tokens: config.burst as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Apply new settings, keeping the tokens already earned
+    pub fn update_from_config(&mut self, config: &HapticRateLimitConfig) {
+        self.enabled = config.enabled;
+        self.rate = config.events_per_sec as f64;
+        self.burst = config.burst as f64;
+        self.tokens = self.tokens.min(self.burst);
+    }
+
+    /// Add the tokens earned since the last refill
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.last_refill = now;
+    }
+
+    /// Whether a send may go out now (spends a token if so)
+    ///
+    /// Priority sends are always allowed.
+    pub fn try_acquire(&mut self, priority: bool, now: Instant) -> bool {
+        if !self.enabled {
+            return true;
+        }
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return true;
+        }
+        priority
+    }
+}
+
+impl Default for HapticBudget {
+    fn default() -> Self {
+        Self::from_config(&HapticRateLimitConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_burst_then_sustained_rate() {
+        let mut budget = HapticBudget::from_config(&HapticRateLimitConfig {
+            enabled: true,
+            events_per_sec: 10,
+            burst: 3,
+        });
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(budget.try_acquire(false, start));
+        }
+        assert!(!budget.try_acquire(false, start));
+        // Priority sends (confirm) still go out with an empty bucket
+        assert!(budget.try_acquire(true, start));
+
+        // One token every 100 ms
+        assert!(budget.try_acquire(false, start + Duration::from_millis(100)));
+        assert!(!budget.try_acquire(false, start + Duration::from_millis(150)));
+
+        // Refill never exceeds the burst
+        let later = start + Duration::from_secs(10);
+        for _ in 0..3 {
+            assert!(budget.try_acquire(false, later));
+        }
+        assert!(!budget.try_acquire(false, later));
+    }
+}
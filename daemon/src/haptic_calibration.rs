@@ -0,0 +1,188 @@
+//! Guided haptic calibration
+//!
+//! Lets non-technical users tune feedback without knowing waveform names.
+//! A calibration walks through the menu events one at a time; for each, the
+//! daemon plays an escalating ladder of waveforms (weakest first) and the
+//! user picks the level they prefer. When every event has a choice the
+//! result is written into `HapticConfig.per_event`.
+//!
+//! Driven over the Settings D-Bus API (`StartHapticCalibration`,
+//! `ReplayHapticCalibration`, `ChooseHapticCalibrationLevel`,
+//! `CancelHapticCalibration`).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::time::Duration;
+
+use crate::config::HapticEventConfig;
+use crate::hidpp::{Mx4HapticPattern, SharedHapticManager};
+
+/// Pause between ladder levels so each can be felt on its own
+pub const LEVEL_GAP: Duration = Duration::from_millis(700);
+
+/// Interaction feedback, weakest to strongest
+const INTENSITY_LADDER: [Mx4HapticPattern; 6] = [
+    Mx4HapticPattern::WhisperCollision,
+    Mx4HapticPattern::SubtleCollision,
+    Mx4HapticPattern::DampCollision,
+    Mx4HapticPattern::DampStateChange,
+    Mx4HapticPattern::SharpCollision,
+    Mx4HapticPattern::SharpStateChange,
+];
+
+/// Error feedback, weakest to strongest
+const ALERT_LADDER: [Mx4HapticPattern; 3] = [
+    Mx4HapticPattern::Knock,
+    Mx4HapticPattern::AngryAlert,
+    Mx4HapticPattern::Mad,
+];
+
+/// Menu event being calibrated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalibrationEvent {
+    MenuAppear,
+    SliceChange,
+    Confirm,
+    Invalid,
+}
+
+impl CalibrationEvent {
+    /// Events in calibration order
+    pub const ALL: [CalibrationEvent; 4] = [
+        CalibrationEvent::MenuAppear,
+        CalibrationEvent::SliceChange,
+        CalibrationEvent::Confirm,
+        CalibrationEvent::Invalid,
+    ];
+
+    /// Name used in `HapticEventConfig` and over D-Bus
+    pub fn name(&self) -> &'static str {
+        match self {
+            CalibrationEvent::MenuAppear => "menu_appear",
+            CalibrationEvent::SliceChange => "slice_change",
+            CalibrationEvent::Confirm => "confirm",
+            CalibrationEvent::Invalid => "invalid",
+        }
+    }
+
+    /// Candidate waveforms, weakest first
+    pub fn ladder(&self) -> &'static [Mx4HapticPattern] {
+        match self {
+            CalibrationEvent::Invalid => &ALERT_LADDER,
+            _ => &INTENSITY_LADDER,
+        }
+    }
+}
+
+/// Calibration error type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalibrationError {
+    /// No calibration is running
+    NotRunning,
+    /// Chosen level is not on the current ladder
+    InvalidLevel(u32),
+}
+
+impl std::fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalibrationError::NotRunning => write!(f, "No haptic calibration is running"),
+            CalibrationError::InvalidLevel(level) => write!(f, "Invalid calibration level: {}", level),
+        }
+    }
+}
+
+impl std::error::Error for CalibrationError {}
+
+/// Progress of one calibration run
+#[derive(Debug, Clone)]
+pub struct HapticCalibration {
+    /// Index into [`CalibrationEvent::ALL`]
+    step: usize,
+    /// Choices so far (unvisited events keep their current pattern)
+    result: HapticEventConfig,
+}
+
+impl HapticCalibration {
+    /// Start a calibration from the current per-event patterns
+    pub fn new(current: &HapticEventConfig) -> Self {
+        Self {
+            step: 0,
+            result: current.clone(),
+        }
+    }
+
+    /// Event waiting for a choice (None once finished)
+    pub fn current_event(&self) -> Option<CalibrationEvent> {
+        CalibrationEvent::ALL.get(self.step).copied()
+    }
+
+    /// Record the preferred level for the current event and move on
+    ///
+    /// Returns the next event, or None when calibration is complete.
+    pub fn choose(&mut self, level: u32) -> Result<Option<CalibrationEvent>, CalibrationError> {
+        let event = self.current_event().ok_or(CalibrationError::NotRunning)?;
+        let pattern = event
+            .ladder()
+            .get(level as usize)
+            .ok_or(CalibrationError::InvalidLevel(level))?;
+
+        let name = pattern.config_name().to_string();
+        match event {
+            CalibrationEvent::MenuAppear => self.result.menu_appear = name,
+            CalibrationEvent::SliceChange => self.result.slice_change = name,
+            CalibrationEvent::Confirm => self.result.confirm = name,
+            CalibrationEvent::Invalid => self.result.invalid = name,
+        }
+        self.step += 1;
+        Ok(self.current_event())
+    }
+
+    /// Per-event patterns chosen so far
+    pub fn result(&self) -> &HapticEventConfig {
+        &self.result
+    }
+}
+
+/// Config names of a ladder's waveforms (what the UI presents as levels)
+pub fn ladder_names(event: CalibrationEvent) -> Vec<String> {
+    event.ladder().iter().map(|p| p.config_name().to_string()).collect()
+}
+
+/// Play a ladder, weakest first, with [`LEVEL_GAP`] between levels
+pub async fn play_ladder(haptic_manager: SharedHapticManager, event: CalibrationEvent) {
+    for (level, &pattern) in event.ladder().iter().enumerate() {
+        if level > 0 {
+            tokio::time::sleep(LEVEL_GAP).await;
+        }
+        let result = haptic_manager.lock().map(|mut m| m.test_pattern(pattern));
+        if let Ok(Err(e)) = result {
+            tracing::warn!(event = event.name(), "Calibration playback failed: {}", e);
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walks_all_events() {
+        let mut calibration = HapticCalibration::new(&HapticEventConfig::default());
+        assert_eq!(calibration.current_event(), Some(CalibrationEvent::MenuAppear));
+
+        assert_eq!(calibration.choose(3), Ok(Some(CalibrationEvent::SliceChange)));
+        assert_eq!(calibration.choose(0), Ok(Some(CalibrationEvent::Confirm)));
+        assert_eq!(calibration.choose(6), Err(CalibrationError::InvalidLevel(6)));
+        assert_eq!(calibration.choose(5), Ok(Some(CalibrationEvent::Invalid)));
+        assert_eq!(calibration.choose(0), Ok(None));
+        assert_eq!(calibration.choose(0), Err(CalibrationError::NotRunning));
+
+        let result = calibration.result();
+        assert_eq!(result.menu_appear, "damp_state_change");
+        assert_eq!(result.slice_change, "whisper_collision");
+        assert_eq!(result.confirm, "sharp_state_change");
+        assert_eq!(result.invalid, "knock");
+    }
+}
@@ -0,0 +1,118 @@
+//! Warm-standby HID++ connection
+//!
+//! Without this the device is only (re)opened lazily, from inside the D-Bus
+//! handler that wants to pulse, so the first haptic after idle or sleep often
+//! misses while the stale handle fails and the device is reopened. The keeper
+//! pings the link every few minutes via [`HapticManager::keep_alive`] and
+//! reconnects proactively, keeping the device open and ready. Pings are
+//! skipped while [Solaar](crate::solaar) is running.
+//!
+//! Waking up can also move the device's feature indices, after which haptic
+//! packets would reach the wrong feature. A wake is noticed either as a
+//! failed ping followed by a working link, or as the receiver reporting the
+//! mouse reachable again (see [`crate::receiver_notifications`]); either way
+//! the feature table is re-read via [`HapticManager::refresh_features`].
+//!
+//! While the mouse is unreachable every other HID++ reader pauses (see
+//! [`crate::host_switch`]), and the native hidraw button reader does not run
+//! under logid. The keeper then drains the HID++ handle every
+//! [`PAUSED_DRAIN_INTERVAL`] so the receiver's "link back" notification is
+//! still read and everything resumes.
+//!
+//! Not started in portal mode (no hidraw access).
+//!
+//! [`HapticManager::keep_alive`]: crate::hidpp::HapticManager::keep_alive
+//! [`HapticManager::refresh_features`]: crate::hidpp::HapticManager::refresh_features
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::time::Duration;
+
+use crate::hidpp::SharedHapticManager;
+
+/// Interval between link checks
+pub const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(180);
+
+/// Interval between reads of pending notifications while the mouse is unreachable
+pub const PAUSED_DRAIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Delay between the receiver reporting the mouse back and re-reading its features
+pub const WAKE_SETTLE: Duration = Duration::from_millis(500);
+
+/// Whether a presence change means the mouse came back
+pub fn is_wake(was_reachable: bool, reachable: bool) -> bool {
+    !was_reachable && reachable
+}
+
+/// Check the HID++ link periodically and reconnect when it goes stale
+pub async fn start_haptic_keeper(haptic_manager: SharedHapticManager) {
+    let mut interval = tokio::time::interval(KEEPALIVE_INTERVAL);
+    // The first tick fires immediately; main already connected at startup
+    interval.tick().await;
+
+    let mut presence = crate::receiver_notifications::subscribe();
+    let mut reachable = *presence.borrow_and_update();
+    let mut drain = tokio::time::interval(PAUSED_DRAIN_INTERVAL);
+
+    loop {
+        let woke = tokio::select! {
+            _ = interval.tick() => false,
+            _ = drain.tick(), if !reachable => {
+                let manager = haptic_manager.clone();
+                let _ = tokio::task::spawn_blocking(move || {
+                    if let Ok(mut m) = manager.lock() {
+                        m.drain_notifications();
+                    }
+                })
+                .await;
+                continue;
+            }
+            Ok(()) = presence.changed() => {
+                let was_reachable = std::mem::replace(&mut reachable, *presence.borrow_and_update());
+                if !is_wake(was_reachable, reachable) {
+                    continue;
+                }
+                true
+            }
+        };
+
+        // Don't add traffic while Solaar shares the device
+        if crate::solaar::is_running() {
+            continue;
+        }
+
+        if woke {
+            tokio::time::sleep(WAKE_SETTLE).await;
+            let manager = haptic_manager.clone();
+            let changed = tokio::task::spawn_blocking(move || {
+                manager.lock().map(|mut m| m.refresh_features()).unwrap_or(false)
+            })
+            .await
+            .unwrap_or(false);
+            tracing::info!(changed, "Mouse reachable again, feature table refreshed");
+            continue;
+        }
+
+        let manager = haptic_manager.clone();
+        let connected = tokio::task::spawn_blocking(move || {
+            manager.lock().map(|mut m| m.keep_alive()).unwrap_or(false)
+        })
+        .await
+        .unwrap_or(false);
+
+        tracing::debug!(connected, "Haptic keep-alive check");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_wake() {
+        assert!(is_wake(false, true));
+        assert!(!is_wake(true, false));
+        assert!(!is_wake(true, true));
+        assert!(!is_wake(false, false));
+    }
+}
@@ -20,7 +20,12 @@ use std::io::{Read, Write};
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::haptic_budget::HapticBudget;
+use crate::hidpp_retry::Failure;
+use crate::link_quality::LinkStats;
+use crate::quiet_hours::{local_minute_of_day, QuietHours};
 
 /// Shared haptic manager for thread-safe access from D-Bus handlers
 pub type SharedHapticManager = Arc<Mutex<HapticManager>>;
@@ -65,6 +70,8 @@ pub mod features {
     pub const I_ROOT: u16 = 0x0000;
     /// IFeatureSet - Enumerate device features (READ-ONLY)
     pub const I_FEATURE_SET: u16 = 0x0001;
+    /// Device information - unit ID, model ID (READ-ONLY)
+    pub const DEVICE_INFO: u16 = 0x0003;
     /// Device name and type (READ-ONLY)
     pub const DEVICE_NAME: u16 = 0x0005;
     /// Battery status (READ-ONLY) - older devices
@@ -165,6 +172,7 @@ pub mod allowed_features {
     pub const SAFELIST: &[u16] = &[
         features::I_ROOT,
         features::I_FEATURE_SET,
+        features::DEVICE_INFO,
         features::DEVICE_NAME,
         features::BATTERY_STATUS,
         features::LED_CONTROL,
@@ -235,6 +243,60 @@ macro_rules! assert_safe_feature {
     }};
 }
 
+/// Runtime-only button divert through REPROG_CONTROLS_V4 (0x1B04)
+///
+/// 0x1B04 stays blocklisted: it is never stored in the feature table and
+/// [`verify_feature_safety`] rejects it. The single exception is
+/// setCidReporting with the temporary divert bit, checked by
+/// [`verify_divert_request`]. That bit is volatile - the device clears it
+/// on power cycle or reconnect - so nothing is written to onboard memory.
+pub mod temporary_divert {
+    /// Function [3] setCidReporting(cid, flags)
+    pub const SET_CID_REPORTING: u8 = 0x03;
+    /// Flag: divert the control to HID++ notifications (volatile)
+    pub const DIVERT: u8 = 0x01;
+    /// Flag: the DIVERT bit is valid
+    pub const DIVERT_VALID: u8 = 0x02;
+    /// Flag: persistent divert (FORBIDDEN)
+    pub const PERSIST: u8 = 0x04;
+    /// Flag: the PERSIST bit is valid (FORBIDDEN)
+    pub const PERSIST_VALID: u8 = 0x08;
+
+    /// Build setCidReporting params that only touch the temporary divert bit
+    pub fn params(cid: u16, divert: bool) -> [u8; 3] {
+        let flags = DIVERT_VALID | if divert { DIVERT } else { 0 };
+        [(cid >> 8) as u8, (cid & 0xFF) as u8, flags]
+    }
+}
+
+/// Verify a REPROG_CONTROLS_V4 request is a temporary divert and nothing else
+///
+/// # CRITICAL SAFETY
+///
+/// Only setCidReporting with a 3-byte payload (cid, flags) is accepted, and
+/// the flags may only contain the temporary divert bit and its valid bit.
+/// Persistent divert, raw XY diversion and remapping are all rejected.
+pub fn verify_divert_request(function: u8, params: &[u8]) -> Result<(), HapticError> {
+    let allowed = temporary_divert::DIVERT | temporary_divert::DIVERT_VALID;
+    let safe = function == temporary_divert::SET_CID_REPORTING
+        && params.len() == 3
+        && params[2] & !allowed == 0;
+
+    if !safe {
+        tracing::error!(
+            function,
+            "SAFETY VIOLATION: Rejected non-temporary REPROG_CONTROLS_V4 request: {:02X?}",
+            params
+        );
+        return Err(HapticError::SafetyViolation {
+            feature_id: blocklisted_features::SPECIAL_KEYS,
+            reason: "Only temporary button divert is permitted",
+        });
+    }
+
+    Ok(())
+}
+
 // ============================================================================
 // HID++ Message Types
 // ============================================================================
@@ -418,6 +480,8 @@ pub struct HidppDevice {
     device_index: u8,
     /// Connection type
     connection_type: ConnectionType,
+    /// Protocol the device answered the ping with
+    protocol: ProtocolVersion,
     /// Cached feature table (feature_id -> feature_index)
     feature_table: std::collections::HashMap<u16, u8>,
     /// Whether haptic feature is available (legacy force feedback 0x8123)
@@ -442,6 +506,14 @@ pub struct HidppDevice {
     battery_feature_index: Option<u8>,
     /// Whether using UNIFIED_BATTERY (true) or BATTERY_STATUS (false)
     is_unified_battery: bool,
+    /// REPROG_CONTROLS_V4 index, looked up only for temporary divert
+    reprog_feature_index: Option<u8>,
+    /// Round-trip and failure statistics of HID++ requests
+    link_stats: LinkStats,
+    /// Wait for the acknowledgement of each haptic waveform
+    verify_haptic_ack: bool,
+    /// Whether the last verified waveform was acknowledged
+    last_haptic_ack_ok: bool,
 }
 
 impl HidppDevice {
@@ -543,6 +615,7 @@ impl HidppDevice {
         }
 
         tracing::debug!(count = candidates.len(), "Trying HID++ device candidates");
+        let mut legacy: Option<(Self, PathBuf)> = None;
 
         for (device_path, connection_type) in candidates {
             // Determine device index based on connection type
@@ -582,6 +655,7 @@ impl HidppDevice {
                 device,
                 device_index,
                 connection_type,
+                protocol: ProtocolVersion::Hidpp20,
                 feature_table: std::collections::HashMap::new(),
                 haptic_supported: false,
                 haptic_feature_index: None,
@@ -594,20 +668,38 @@ impl HidppDevice {
                 battery_supported: false,
                 battery_feature_index: None,
                 is_unified_battery: false,
+                reprog_feature_index: None,
+                link_stats: LinkStats::default(),
+                verify_haptic_ack: false,
+                last_haptic_ack_ok: true,
             };
 
-            // Validate HID++ 2.0 support - if this fails, try next candidate
-            if !hidpp.validate_hidpp20() {
-                tracing::debug!(
-                    path = %device_path.display(),
-                    connection = %connection_type,
-                    "Device does not support HID++ 2.0, trying next candidate"
-                );
-                continue; // Try next candidate
+            // Probe the protocol; keep the first HID++ 1.0 device in case no 2.0 one turns up
+            match hidpp.probe_protocol() {
+                Some(ProtocolVersion::Hidpp20) => {}
+                Some(ProtocolVersion::Hidpp10) => {
+                    tracing::debug!(
+                        path = %device_path.display(),
+                        connection = %connection_type,
+                        "Device speaks HID++ 1.0 only, trying next candidate"
+                    );
+                    hidpp.protocol = ProtocolVersion::Hidpp10;
+                    legacy.get_or_insert((hidpp, device_path));
+                    continue;
+                }
+                None => {
+                    tracing::debug!(
+                        path = %device_path.display(),
+                        connection = %connection_type,
+                        "Device does not support HID++ 2.0, trying next candidate"
+                    );
+                    continue; // Try next candidate
+                }
             }
 
-            // Enumerate features and check for haptic support
-            hidpp.enumerate_features();
+            // Load the feature table (cached per device) and check for haptic support
+            hidpp.load_features();
+            crate::receiver_notifications::set_device_index(device_index);
 
             tracing::info!(
                 path = %device_path.display(),
@@ -620,6 +712,16 @@ impl HidppDevice {
             return Some(hidpp);
         }
 
+        if let Some((hidpp, device_path)) = legacy {
+            tracing::info!(
+                path = %device_path.display(),
+                connection = %hidpp.connection_type,
+                "Connected to a HID++ 1.0 device: battery via registers only, haptics unavailable"
+            );
+            crate::receiver_notifications::set_device_index(hidpp.device_index);
+            return Some(hidpp);
+        }
+
         tracing::debug!("No valid HID++ 2.0 device found among candidates");
         None
     }
@@ -631,7 +733,10 @@ impl HidppDevice {
         let mut drain_buf = [0u8; 64];
         loop {
             match self.device.read(&mut drain_buf) {
-                Ok(_) => continue, // Discard stale data
+                Ok(len) => {
+                    // Discard stale data, but note receiver notifications
+                    crate::receiver_notifications::observe_report(&drain_buf[..len]);
+                }
                 Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
                 Err(_) => break,
             }
@@ -640,10 +745,28 @@ impl HidppDevice {
 
     /// Send a HID++ request and wait for matching response
     ///
-    /// Uses polling with timeout (same approach as battery module).
+    /// Lost or busy requests are retried per [`crate::hidpp_retry`].
     fn hidpp_request(&mut self, feature_index: u8, function: u8, params: &[u8]) -> Option<Vec<u8>> {
+        crate::hidpp_retry::run(|| self.hidpp_request_once(feature_index, function, params), |f| *f).ok()
+    }
+
+    /// Send a HID++ request once, without retries
+    ///
+    /// For writes that must not be repeated (a replayed haptic pulse) or
+    /// whose missing reply is expected (a host switch disconnects the device
+    /// before it answers).
+    fn hidpp_request_single(&mut self, feature_index: u8, function: u8, params: &[u8]) -> Option<Vec<u8>> {
+        self.hidpp_request_once(feature_index, function, params).ok()
+    }
+
+    /// One attempt at a HID++ request
+    ///
+    /// Waits in poll() for the response until the attempt deadline
+    /// (same approach as battery module).
+    fn hidpp_request_once(&mut self, feature_index: u8, function: u8, params: &[u8]) -> Result<Vec<u8>, Failure> {
         // Drain any pending data first
         self.drain_buffer();
+        let started = Instant::now();
 
         // Build HID++ short report (7 bytes)
         let mut request = [0u8; 7];
@@ -664,14 +787,16 @@ impl HidppDevice {
         );
 
         // Send request
+        crate::hidpp_audit::record(&request, self.feature_id_for_index(feature_index));
         if let Err(e) = self.device.write_all(&request) {
             tracing::debug!(error = %e, "Failed to write HID++ message");
-            return None;
+            self.link_stats.record_failure();
+            return Err(Failure::Fatal);
         }
 
         // Read response with timeout (non-blocking, so we poll)
         let mut response = [0u8; 20];
-        let mut attempts = 0;
+        let deadline = started + crate::hidpp_retry::attempt_timeout();
 
         loop {
             match self.device.read(&mut response) {
@@ -696,7 +821,8 @@ impl HidppDevice {
                             && resp_sw_id == SOFTWARE_ID
                         {
                             tracing::debug!("HID++ request matched! Returning response");
-                            return Some(response[..len].to_vec());
+                            self.link_stats.record_success(started.elapsed());
+                            return Ok(response[..len].to_vec());
                         }
                         // Check for error response (0xFF feature_index indicates error)
                         // Format: [report_type, device_idx, 0xFF, orig_feature_idx, orig_fn_sw, error_code, ...]
@@ -721,12 +847,20 @@ impl HidppDevice {
                                 "HID++ error response: {:02X?}",
                                 &response[..len]
                             );
-                            return None;
+                            // The device answered; the link itself is fine
+                            self.link_stats.record_success(started.elapsed());
+                            return Err(if error_code == 0x06 { Failure::Busy } else { Failure::Fatal });
                         }
                         // Legacy error check (0x8F)
                         if response[2] == 0x8F {
                             tracing::debug!("HID++ legacy error response: {:02X?}", &response[..len]);
-                            return None;
+                            // Reported by the receiver: the device is asleep or
+                            // out of range, and asking again right away won't wake it
+                            self.link_stats.record_failure();
+                            return Err(Failure::Fatal);
+                        }
+                        if crate::receiver_notifications::observe_report(&response[..len]) {
+                            continue;
                         }
                         // Log non-matching responses for debugging
                         tracing::debug!(
@@ -746,21 +880,29 @@ impl HidppDevice {
                     // Short read, continue
                 }
                 Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                    // No data yet
+                    // No data yet: sleep until a report arrives or the deadline passes
+                    match crate::hidraw::wait_readable(&self.device, deadline) {
+                        Ok(true) => continue,
+                        Ok(false) => {}
+                        Err(e) => {
+                            tracing::debug!(error = %e, "Error waiting for HID++ response");
+                            self.link_stats.record_failure();
+                            return Err(Failure::Fatal);
+                        }
+                    }
                 }
                 Err(e) => {
                     tracing::debug!(error = %e, "Error reading HID++ response");
-                    return None;
+                    self.link_stats.record_failure();
+                    return Err(Failure::Fatal);
                 }
             }
 
-            attempts += 1;
-            if attempts > 100 {
-                tracing::debug!(feature_index, function, "HID++ request timeout after 100 attempts");
-                return None;
+            if Instant::now() >= deadline {
+                tracing::debug!(feature_index, function, elapsed = ?started.elapsed(), "HID++ request timed out");
+                self.link_stats.record_failure();
+                return Err(Failure::Timeout);
             }
-
-            std::thread::sleep(std::time::Duration::from_millis(10));
         }
     }
 
@@ -788,6 +930,7 @@ impl HidppDevice {
             &request
         );
 
+        crate::hidpp_audit::record(&request, self.feature_id_for_index(feature_index));
         self.device.write_all(&request)
     }
 
@@ -808,29 +951,214 @@ impl HidppDevice {
         false
     }
 
-    /// Enumerate device features and build feature table
-    ///
-    /// # SAFETY
+    /// Check the link is alive (IRoot ping)
+    pub fn ping(&mut self) -> bool {
+        match self.protocol {
+            ProtocolVersion::Hidpp20 => self.validate_hidpp20(),
+            ProtocolVersion::Hidpp10 => self.probe_protocol().is_some(),
+        }
+    }
+
+    /// Protocol the device speaks
+    pub fn protocol(&self) -> ProtocolVersion {
+        self.protocol
+    }
+
+    /// Send the IRoot ping and tell HID++ 1.0 from 2.0 by the answer
     ///
-    /// This method only READS feature information - it does NOT use
-    /// any blocklisted features. Blocklisted features are logged for
-    /// audit purposes but never stored for use.
-    fn enumerate_features(&mut self) {
-        // First, get the feature index for IFeatureSet (0x0001)
-        let feature_set_index = match self.get_feature_index(features::I_FEATURE_SET) {
-            Some(idx) => idx,
-            None => {
-                tracing::debug!("Device does not support IFeatureSet");
-                return;
+    /// Unlike [`Self::validate_hidpp20`], a 1.0 error report is an answer
+    /// here rather than a lost request.
+    fn probe_protocol(&mut self) -> Option<ProtocolVersion> {
+        const PING_DATA: u8 = 0xAA;
+        let request = [report_type::SHORT, self.device_index, 0x00, (0x01 << 4) | SOFTWARE_ID, 0x00, 0x00, PING_DATA];
+        let reply = crate::hidpp_retry::run(|| self.short_exchange_once(&request), |f| *f).ok()?;
+        let protocol = protocol_from_ping(reply, PING_DATA);
+        tracing::debug!(?reply, ?protocol, "HID++ protocol probe");
+        protocol
+    }
+
+    /// Read a HID++ 1.0 register (GET_REGISTER); returns its three bytes
+    fn read_register(&mut self, register: u8) -> Result<[u8; 3], HapticError> {
+        let request = [report_type::SHORT, self.device_index, hidpp10::GET_REGISTER, register, 0x00, 0x00, 0x00];
+        match crate::hidpp_retry::run(|| self.short_exchange_once(&request), |f| *f) {
+            Ok(ShortReply::Ok(params)) => Ok(params),
+            Ok(ShortReply::Error(code)) => Err(HapticError::ProtocolError(format!(
+                "HID++ 1.0 register 0x{:02X} read failed (error 0x{:02X})",
+                register, code
+            ))),
+            Err(_) => Err(HapticError::CommunicationError),
+        }
+    }
+
+    /// One short request, answered by a reply or an error report
+    fn short_exchange_once(&mut self, request: &[u8; 7]) -> Result<ShortReply, Failure> {
+        self.drain_buffer();
+        let started = Instant::now();
+
+        crate::hidpp_audit::record(request, None);
+        if let Err(e) = self.device.write_all(request) {
+            tracing::debug!(error = %e, "Failed to write HID++ message");
+            self.link_stats.record_failure();
+            return Err(Failure::Fatal);
+        }
+
+        let mut response = [0u8; 20];
+        let deadline = started + crate::hidpp_retry::attempt_timeout();
+        loop {
+            match self.device.read(&mut response) {
+                Ok(len) => {
+                    let report = &response[..len];
+                    if let Some(reply) = match_short_reply(report, request) {
+                        self.link_stats.record_success(started.elapsed());
+                        return Ok(reply);
+                    }
+                    crate::receiver_notifications::observe_report(report);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => match crate::hidraw::wait_readable(&self.device, deadline) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        self.link_stats.record_failure();
+                        return Err(Failure::Timeout);
+                    }
+                    Err(e) => {
+                        tracing::debug!(error = %e, "Error waiting for HID++ response");
+                        self.link_stats.record_failure();
+                        return Err(Failure::Fatal);
+                    }
+                },
+                Err(e) => {
+                    tracing::debug!(error = %e, "Error reading HID++ response");
+                    self.link_stats.record_failure();
+                    return Err(Failure::Fatal);
+                }
             }
+        }
+    }
+
+    /// Get the IFeatureSet index and feature count
+    fn feature_set(&mut self) -> Option<(u8, u8)> {
+        let Some(feature_set_index) = self.get_feature_index(features::I_FEATURE_SET) else {
+            tracing::debug!("Device does not support IFeatureSet");
+            return None;
         };
 
         // Get feature count (function 0x00 of IFeatureSet)
-        let feature_count = match self.hidpp_request(feature_set_index, 0x00, &[]) {
-            Some(resp) if resp.len() >= 5 => resp[4],
-            _ => return,
+        match self.hidpp_request(feature_set_index, 0x00, &[]) {
+            Some(resp) if resp.len() >= 5 => Some((feature_set_index, resp[4])),
+            _ => None,
+        }
+    }
+
+    /// Read the device serial (unit ID + model ID from DeviceInformation)
+    ///
+    /// Used as the feature cache key; None if the device has no 0x0003.
+    fn device_serial(&mut self) -> Option<String> {
+        let device_info_index = self.get_feature_index(features::DEVICE_INFO)?;
+
+        // getDeviceInfo: [entityCnt, unitId(4), transport(2), modelId(6), ...]
+        let resp = self.hidpp_request(device_info_index, 0x00, &[])?;
+        if resp.len() < 17 {
+            return None;
+        }
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02X}", b)).collect::<String>();
+        Some(format!("{}-{}", hex(&resp[5..9]), hex(&resp[11..17])))
+    }
+
+    /// Build the feature table, from the cache when possible
+    ///
+    /// A cached table is used only if the device's IFeatureSet count still
+    /// matches; otherwise the table is enumerated and the cache refreshed.
+    fn load_features(&mut self) {
+        let Some((feature_set_index, feature_count)) = self.feature_set() else {
+            return;
+        };
+        let serial = self.device_serial();
+
+        if let Some(cached) = serial.as_deref().and_then(crate::feature_cache::lookup) {
+            if cached.feature_count == feature_count {
+                tracing::debug!(count = feature_count, "Using cached feature table");
+                for (feature_id, feature_index) in cached.features {
+                    self.record_feature(feature_id, feature_index);
+                }
+                self.log_feature_summary();
+                return;
+            }
+            tracing::debug!("Cached feature table is stale, re-enumerating");
+        }
+
+        self.enumerate_and_cache(feature_set_index, feature_count, serial);
+    }
+
+    /// Re-read the feature table from the device, bypassing the cache
+    ///
+    /// Feature indices can change when the device wakes up and reconnects to
+    /// its receiver, and requests sent to the old indices reach the wrong
+    /// feature. The table is kept if the device does not answer. Returns
+    /// true if any index changed.
+    pub fn refresh_features(&mut self) -> bool {
+        if self.protocol == ProtocolVersion::Hidpp10 {
+            return false;
+        }
+        let Some((feature_set_index, feature_count)) = self.feature_set() else {
+            tracing::debug!("Device did not answer IFeatureSet, keeping the feature table");
+            return false;
         };
+        let serial = self.device_serial();
 
+        let previous = std::mem::take(&mut self.feature_table);
+        self.clear_capabilities();
+        self.enumerate_and_cache(feature_set_index, feature_count, serial);
+
+        let changed = previous != self.feature_table;
+        if changed {
+            tracing::info!(
+                count = self.feature_table.len(),
+                "Feature indices changed since the device was opened, table refreshed"
+            );
+        }
+        changed
+    }
+
+    /// Forget every feature index and capability (before re-enumerating)
+    fn clear_capabilities(&mut self) {
+        self.feature_table.clear();
+        self.haptic_supported = false;
+        self.haptic_feature_index = None;
+        self.mx4_haptic_supported = false;
+        self.mx4_haptic_feature_index = None;
+        self.dpi_supported = false;
+        self.dpi_feature_index = None;
+        self.smartshift_supported = false;
+        self.smartshift_feature_index = None;
+        self.battery_supported = false;
+        self.battery_feature_index = None;
+        self.is_unified_battery = false;
+        self.reprog_feature_index = None;
+    }
+
+    /// Enumerate the feature table and store it in the cache under `serial`
+    fn enumerate_and_cache(&mut self, feature_set_index: u8, feature_count: u8, serial: Option<String>) {
+        self.enumerate_features(feature_set_index, feature_count);
+
+        if let Some(serial) = serial {
+            let mut features: Vec<(u16, u8)> =
+                self.feature_table.iter().map(|(&id, &index)| (id, index)).collect();
+            features.sort_unstable_by_key(|&(_, index)| index);
+            crate::feature_cache::store(
+                &serial,
+                Some(crate::feature_cache::CachedFeatures { feature_count, features }),
+            );
+        }
+    }
+
+    /// Enumerate device features and build feature table
+    ///
+    /// # SAFETY
+    ///
+    /// This method only READS feature information - it does NOT use
+    /// any blocklisted features. Blocklisted features are logged for
+    /// audit purposes but never stored for use.
+    fn enumerate_features(&mut self, feature_set_index: u8, feature_count: u8) {
         tracing::debug!(count = feature_count, "Enumerating device features");
 
         // Enumerate each feature (function 0x01 of IFeatureSet)
@@ -842,116 +1170,125 @@ impl HidppDevice {
 
                 let feature_id = ((resp[4] as u16) << 8) | (resp[5] as u16);
                 let feature_index = i; // Feature indices are 0-based (slot = index)
+                self.record_feature(feature_id, feature_index);
+            }
+        }
 
-                // SAFETY CHECK: Log blocklisted features but DO NOT store them
-                if blocklisted_features::is_blocklisted(feature_id) {
-                    let reason = blocklisted_features::blocklist_reason(feature_id)
-                        .unwrap_or("Unknown");
-                    tracing::debug!(
-                        feature_id = format!("0x{:04X}", feature_id),
-                        reason = reason,
-                        "Device has blocklisted feature (will NOT be used)"
-                    );
-                    // Explicitly DO NOT add to feature_table
-                    continue;
-                }
+        self.log_feature_summary();
+    }
 
-                self.feature_table.insert(feature_id, feature_index);
+    /// Add one feature to the table and note the capabilities it provides
+    fn record_feature(&mut self, feature_id: u16, feature_index: u8) {
+        // SAFETY CHECK: Log blocklisted features but DO NOT store them
+        if blocklisted_features::is_blocklisted(feature_id) {
+            let reason = blocklisted_features::blocklist_reason(feature_id)
+                .unwrap_or("Unknown");
+            tracing::debug!(
+                feature_id = format!("0x{:04X}", feature_id),
+                reason = reason,
+                "Device has blocklisted feature (will NOT be used)"
+            );
+            // Explicitly DO NOT add to feature_table
+            return;
+        }
 
-                // Log all features for debugging
-                tracing::debug!(
-                    feature_id = format!("0x{:04X}", feature_id),
-                    feature_index = feature_index,
-                    "Found feature"
-                );
+        self.feature_table.insert(feature_id, feature_index);
 
-                // Check for legacy force feedback feature (0x8123 - for racing wheels)
-                if feature_id == features::FORCE_FEEDBACK {
-                    self.haptic_supported = true;
-                    self.haptic_feature_index = Some(feature_index);
-                    tracing::info!(
-                        index = feature_index,
-                        "Legacy haptic/force feedback feature found (0x8123)"
-                    );
-                }
-
-                // Check for MX Master 4 haptic feature (0x19B0)
-                if feature_id == features::MX_MASTER_4_HAPTIC {
-                    self.mx4_haptic_supported = true;
-                    self.mx4_haptic_feature_index = Some(feature_index);
-                    tracing::info!(
-                        index = feature_index,
-                        "MX Master 4 haptic feature found (0x19B0)"
-                    );
-                }
+        // Log all features for debugging
+        tracing::debug!(
+            feature_id = format!("0x{:04X}", feature_id),
+            feature_index = feature_index,
+            "Found feature"
+        );
 
-                // Check for alternative haptic feature (0x0B4E from mx4notifications)
-                if feature_id == features::MX4_HAPTIC_ALT {
-                    self.mx4_haptic_supported = true;
-                    self.mx4_haptic_feature_index = Some(feature_index);
-                    tracing::info!(
-                        index = feature_index,
-                        "MX Master 4 haptic feature found (0x0B4E - mx4notifications)"
-                    );
-                }
+        // Check for legacy force feedback feature (0x8123 - for racing wheels)
+        if feature_id == features::FORCE_FEEDBACK {
+            self.haptic_supported = true;
+            self.haptic_feature_index = Some(feature_index);
+            tracing::info!(
+                index = feature_index,
+                "Legacy haptic/force feedback feature found (0x8123)"
+            );
+        }
 
-                // Check for adjustable DPI feature (0x2201)
-                if feature_id == features::ADJUSTABLE_DPI {
-                    self.dpi_supported = true;
-                    self.dpi_feature_index = Some(feature_index);
-                    tracing::info!(
-                        index = feature_index,
-                        "Adjustable DPI feature found (0x2201)"
-                    );
-                }
+        // Check for MX Master 4 haptic feature (0x19B0)
+        if feature_id == features::MX_MASTER_4_HAPTIC {
+            self.mx4_haptic_supported = true;
+            self.mx4_haptic_feature_index = Some(feature_index);
+            tracing::info!(
+                index = feature_index,
+                "MX Master 4 haptic feature found (0x19B0)"
+            );
+        }
 
-                // Check for HiResScroll feature (0x2111) - MX Master 3/4 SmartShift control
-                if feature_id == features::HIRES_SCROLL {
-                    self.smartshift_supported = true;
-                    self.smartshift_feature_index = Some(feature_index);
-                    tracing::info!(
-                        index = feature_index,
-                        "HiResScroll feature found (0x2111) - SmartShift control available"
-                    );
-                }
+        // Check for alternative haptic feature (0x0B4E from mx4notifications)
+        if feature_id == features::MX4_HAPTIC_ALT {
+            self.mx4_haptic_supported = true;
+            self.mx4_haptic_feature_index = Some(feature_index);
+            tracing::info!(
+                index = feature_index,
+                "MX Master 4 haptic feature found (0x0B4E - mx4notifications)"
+            );
+        }
 
-                // Also check for legacy SmartShift feature (0x2110) for older mice
-                if feature_id == features::SMARTSHIFT_LEGACY {
-                    // Only set if not already detected via HiResScroll
-                    if !self.smartshift_supported {
-                        self.smartshift_supported = true;
-                        self.smartshift_feature_index = Some(feature_index);
-                        tracing::info!(
-                            index = feature_index,
-                            "Legacy SmartShift feature found (0x2110)"
-                        );
-                    }
-                }
+        // Check for adjustable DPI feature (0x2201)
+        if feature_id == features::ADJUSTABLE_DPI {
+            self.dpi_supported = true;
+            self.dpi_feature_index = Some(feature_index);
+            tracing::info!(
+                index = feature_index,
+                "Adjustable DPI feature found (0x2201)"
+            );
+        }
 
-                // Check for UNIFIED_BATTERY feature (0x1004) - preferred for MX Master 4
-                if feature_id == features::UNIFIED_BATTERY {
-                    self.battery_supported = true;
-                    self.battery_feature_index = Some(feature_index);
-                    self.is_unified_battery = true;
-                    tracing::info!(
-                        index = feature_index,
-                        "Unified Battery feature found (0x1004)"
-                    );
-                }
+        // Check for HiResScroll feature (0x2111) - MX Master 3/4 SmartShift control
+        if feature_id == features::HIRES_SCROLL {
+            self.smartshift_supported = true;
+            self.smartshift_feature_index = Some(feature_index);
+            tracing::info!(
+                index = feature_index,
+                "HiResScroll feature found (0x2111) - SmartShift control available"
+            );
+        }
 
-                // Check for BATTERY_STATUS feature (0x1000) - fallback for older devices
-                if feature_id == features::BATTERY_STATUS && !self.battery_supported {
-                    self.battery_supported = true;
-                    self.battery_feature_index = Some(feature_index);
-                    self.is_unified_battery = false;
-                    tracing::info!(
-                        index = feature_index,
-                        "Battery Status feature found (0x1000)"
-                    );
-                }
+        // Also check for legacy SmartShift feature (0x2110) for older mice
+        if feature_id == features::SMARTSHIFT_LEGACY {
+            // Only set if not already detected via HiResScroll
+            if !self.smartshift_supported {
+                self.smartshift_supported = true;
+                self.smartshift_feature_index = Some(feature_index);
+                tracing::info!(
+                    index = feature_index,
+                    "Legacy SmartShift feature found (0x2110)"
+                );
             }
         }
 
+        // Check for UNIFIED_BATTERY feature (0x1004) - preferred for MX Master 4
+        if feature_id == features::UNIFIED_BATTERY {
+            self.battery_supported = true;
+            self.battery_feature_index = Some(feature_index);
+            self.is_unified_battery = true;
+            tracing::info!(
+                index = feature_index,
+                "Unified Battery feature found (0x1004)"
+            );
+        }
+
+        // Check for BATTERY_STATUS feature (0x1000) - fallback for older devices
+        if feature_id == features::BATTERY_STATUS && !self.battery_supported {
+            self.battery_supported = true;
+            self.battery_feature_index = Some(feature_index);
+            self.is_unified_battery = false;
+            tracing::info!(
+                index = feature_index,
+                "Battery Status feature found (0x1000)"
+            );
+        }
+    }
+
+    /// Log the detected capabilities
+    fn log_feature_summary(&self) {
         tracing::debug!(
             feature_count = self.feature_table.len(),
             legacy_haptic = self.haptic_supported,
@@ -963,6 +1300,41 @@ impl HidppDevice {
         );
     }
 
+    /// List the device's complete feature table as (index, feature ID)
+    ///
+    /// Read-only (IFeatureSet getCount/getFeatureID). Unlike the internal
+    /// table this includes blocklisted features, for diagnostics only.
+    pub fn list_features(&mut self) -> Vec<(u8, u16)> {
+        let Some((feature_set_index, feature_count)) = self.feature_set() else {
+            return Vec::new();
+        };
+
+        // IRoot is always index 0 and not counted by IFeatureSet
+        let mut list = vec![(0x00, features::I_ROOT)];
+        for i in 1..=feature_count {
+            if let Some(resp) = self.hidpp_request(feature_set_index, 0x01, &[i, 0, 0]) {
+                if resp.len() >= 6 {
+                    list.push((i, ((resp[4] as u16) << 8) | (resp[5] as u16)));
+                }
+            }
+        }
+        list
+    }
+
+    /// Resolve a feature index back to its feature ID (for the audit log)
+    fn feature_id_for_index(&self, feature_index: u8) -> Option<u16> {
+        if feature_index == 0x00 {
+            return Some(features::I_ROOT);
+        }
+        if self.reprog_feature_index == Some(feature_index) {
+            return Some(blocklisted_features::SPECIAL_KEYS);
+        }
+        self.feature_table
+            .iter()
+            .find(|(_, &index)| index == feature_index)
+            .map(|(&id, _)| id)
+    }
+
     /// Get the feature index for a given feature ID using IRoot
     fn get_feature_index(&mut self, feature_id: u16) -> Option<u8> {
         // IRoot function 0x00: getFeatureIndex
@@ -1003,6 +1375,11 @@ impl HidppDevice {
         self.connection_type
     }
 
+    /// HID++ request statistics since the device was opened
+    pub fn link_stats(&self) -> LinkStats {
+        self.link_stats
+    }
+
     /// Send an MX Master 4 haptic pattern
     ///
     /// # SAFETY
@@ -1029,20 +1406,23 @@ impl HidppDevice {
         // Packet: [0x10, 0x02, 0x0B, 0x4E, waveform, 0x00, 0x00]
         // - 0x10: SHORT report type
         // - 0x02: device index (Bolt receiver)
-        // - 0x0B: feature index 11 (hardcoded, matches mx4notifications)
+        // - 0x0B: feature index (from the feature table, 11 on current firmware)
         // - 0x4E: (function 0x04 << 4) | sw_id 0x0E
         // - waveform: the haptic pattern ID
 
-        const MX4_HAPTIC_FEATURE_INDEX: u8 = 0x0B;  // Feature index 11
+        const MX4_HAPTIC_FEATURE_INDEX: u8 = 0x0B;  // Fallback, as hardcoded by mx4notifications
         const MX4_HAPTIC_FUNCTION: u8 = 0x04;       // Function ID for haptic play
         const MX4_HAPTIC_SW_ID: u8 = 0x0E;          // Software ID used by mx4notifications
 
+        // The index can move between firmware versions; it is re-read on wake
+        let feature_index = self.mx4_haptic_feature_index.unwrap_or(MX4_HAPTIC_FEATURE_INDEX);
+
         self.drain_buffer();
 
         let mut request = [0u8; 7];
         request[0] = report_type::SHORT;
         request[1] = self.device_index;
-        request[2] = MX4_HAPTIC_FEATURE_INDEX;
+        request[2] = feature_index;
         request[3] = (MX4_HAPTIC_FUNCTION << 4) | MX4_HAPTIC_SW_ID;
         request[4] = pattern.to_id();
         // request[5] and request[6] remain 0
@@ -1052,11 +1432,66 @@ impl HidppDevice {
             &request
         );
 
+        crate::hidpp_audit::record(&request, self.feature_id_for_index(feature_index));
         self.device.write_all(&request).map_err(HapticError::IoError)?;
 
+        if self.verify_haptic_ack {
+            return self.verify_haptic_pattern(pattern, feature_index, request[3]);
+        }
         Ok(())
     }
 
+    /// Wait for the acknowledgement of each haptic waveform (`haptics.verify_ack`)
+    pub fn set_verify_haptic_ack(&mut self, verify: bool) {
+        self.verify_haptic_ack = verify;
+    }
+
+    /// Wait for the answer to a haptic play request and count it in metrics
+    ///
+    /// A waveform is only known to have played once the device echoes the
+    /// request; an error report or silence means it was dropped. Failures
+    /// are logged once per run of failures.
+    fn verify_haptic_pattern(&mut self, pattern: Mx4HapticPattern, feature_index: u8, function_sw: u8) -> Result<(), HapticError> {
+        let ack = self.read_haptic_ack(feature_index, function_sw, Instant::now() + HAPTIC_ACK_TIMEOUT)?;
+        crate::metrics::record_haptic_ack(pattern.config_name(), ack.label());
+
+        let ok = ack == HapticAck::Ok;
+        if !ok && self.last_haptic_ack_ok {
+            tracing::warn!(pattern = %pattern, result = ack.label(), "Haptic waveform not acknowledged by the device");
+        } else if ok && !self.last_haptic_ack_ok {
+            tracing::info!(pattern = %pattern, "Haptic waveforms acknowledged again");
+        }
+        self.last_haptic_ack_ok = ok;
+
+        match ack {
+            HapticAck::Ok => Ok(()),
+            HapticAck::Rejected(code) => Err(HapticError::ProtocolError(format!("haptic waveform rejected (error 0x{:02X})", code))),
+            HapticAck::Timeout => Err(HapticError::CommunicationError),
+        }
+    }
+
+    /// Read reports until the haptic request is answered or `deadline` passes
+    fn read_haptic_ack(&mut self, feature_index: u8, function_sw: u8, deadline: Instant) -> Result<HapticAck, HapticError> {
+        let mut response = [0u8; 20];
+        loop {
+            match self.device.read(&mut response) {
+                Ok(len) => {
+                    let report = &response[..len];
+                    if let Some(ack) = match_haptic_ack(report, self.device_index, feature_index, function_sw) {
+                        return Ok(ack);
+                    }
+                    crate::receiver_notifications::observe_report(report);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    if !crate::hidraw::wait_readable(&self.device, deadline).map_err(HapticError::IoError)? {
+                        return Ok(HapticAck::Timeout);
+                    }
+                }
+                Err(e) => return Err(HapticError::IoError(e)),
+            }
+        }
+    }
+
     /// Send a haptic pulse command (legacy method for force feedback devices)
     ///
     /// # SAFETY
@@ -1080,8 +1515,8 @@ impl HidppDevice {
             (duration_ms & 0xFF) as u8,
         ];
 
-        // Use hidpp_request for short messages (will drain buffer and send)
-        if self.hidpp_request(feature_index, 0x00, &params).is_none() {
+        // Single attempt: a retry would replay the pulse
+        if self.hidpp_request_single(feature_index, 0x00, &params).is_none() {
             tracing::debug!("Legacy haptic pulse - no response (may be expected)");
         }
 
@@ -1223,6 +1658,7 @@ impl HidppDevice {
     /// - wheel_mode: 1 = Freespin, 2 = Ratchet
     /// - auto_disengage: Threshold for automatic ratchet disengagement (1-254 = N/4 turns/sec, 255 = always engaged)
     /// - auto_disengage_default: Default threshold stored in device
+    ///
     /// None if SmartShift is not supported
     pub fn get_smartshift(&mut self) -> Option<(u8, u8, u8)> {
         let feature_index = self.smartshift_feature_index?;
@@ -1313,8 +1749,7 @@ impl HidppDevice {
             }
             None => {
                 tracing::warn!("Failed to set SmartShift config");
-                Err(HapticError::IoError(std::io::Error::new(
-                    std::io::ErrorKind::Other,
+                Err(HapticError::IoError(std::io::Error::other(
                     "Failed to set SmartShift",
                 )))
             }
@@ -1330,6 +1765,7 @@ impl HidppDevice {
     /// - hires: true = high resolution scrolling (more events, feels faster)
     /// - invert: true = natural/inverted scrolling
     /// - target: true = send scroll events directly to focused window
+    ///
     /// None if HiResScroll is not supported
     pub fn get_hiresscroll_mode(&mut self) -> Option<(bool, bool, bool)> {
         let feature_index = self.smartshift_feature_index?;
@@ -1435,8 +1871,7 @@ impl HidppDevice {
             }
             None => {
                 tracing::warn!("Failed to set HiResScroll mode");
-                Err(HapticError::IoError(std::io::Error::new(
-                    std::io::ErrorKind::Other,
+                Err(HapticError::IoError(std::io::Error::other(
                     "Failed to set HiResScroll",
                 )))
             }
@@ -1449,6 +1884,9 @@ impl HidppDevice {
     /// Ok((percentage, charging)) on success, or error if battery query fails.
     /// percentage is 0-100, charging is true if device is charging.
     pub fn query_battery(&mut self) -> Result<(u8, bool), HapticError> {
+        if self.protocol == ProtocolVersion::Hidpp10 {
+            return self.query_battery_hidpp10();
+        }
         let feature_index = match self.battery_feature_index {
             Some(idx) => idx,
             None => {
@@ -1482,7 +1920,7 @@ impl HidppDevice {
                     let charging_status = resp[7];
 
                     // UNIFIED_BATTERY charging_status: 0=discharging, 1=charging, 2=charging_slow, 3=charging_complete, 5=invalid
-                    let charging = charging_status >= 1 && charging_status <= 3;
+                    let charging = (1..=3).contains(&charging_status);
 
                     tracing::debug!(
                         percentage,
@@ -1497,7 +1935,7 @@ impl HidppDevice {
                     let charging_status = resp[6];
 
                     // BATTERY_STATUS status: 0=discharging, 1-4=various charging states
-                    let charging = charging_status >= 1 && charging_status <= 4;
+                    let charging = (1..=4).contains(&charging_status);
 
                     tracing::debug!(
                         percentage,
@@ -1518,9 +1956,33 @@ impl HidppDevice {
         }
     }
 
+    /// Query battery status from HID++ 1.0 registers
+    ///
+    /// Register 0x0D (percentage) is preferred; devices without it usually
+    /// have 0x07 (coarse level).
+    fn query_battery_hidpp10(&mut self) -> Result<(u8, bool), HapticError> {
+        match self.read_register(hidpp10::REG_BATTERY_CHARGE) {
+            Ok(params) => {
+                let (percentage, charging) = parse_battery_charge(params);
+                tracing::debug!(percentage, charging, "Battery query result (HID++ 1.0 register 0x0D)");
+                return Ok((percentage, charging));
+            }
+            Err(e) => tracing::debug!("Battery charge register unavailable: {}", e),
+        }
+
+        let params = self.read_register(hidpp10::REG_BATTERY_STATUS)?;
+        let (percentage, charging) = parse_battery_status(params)
+            .ok_or_else(|| HapticError::ProtocolError(format!("Unknown HID++ 1.0 battery level {}", params[0])))?;
+        tracing::debug!(percentage, charging, "Battery query result (HID++ 1.0 register 0x07)");
+        Ok((percentage, charging))
+    }
+
     /// Check if battery feature is supported
+    ///
+    /// Always true for HID++ 1.0 devices; their battery registers are only
+    /// found out by reading them.
     pub fn battery_supported(&self) -> bool {
-        self.battery_supported
+        self.battery_supported || self.protocol == ProtocolVersion::Hidpp10
     }
 
     /// Get host names for Easy-Switch slots using HID++ 0x1815 (HOSTS_INFO)
@@ -1660,33 +2122,248 @@ impl HidppDevice {
         let change_host_index = self.get_feature_index(features::CHANGE_HOST)
             .ok_or_else(|| "CHANGE_HOST feature (0x1814) not supported".to_string())?;
 
-        // Validate host_index (typically 0, 1, or 2)
-        if host_index > 2 {
-            return Err(format!("Invalid host_index: {}. Must be 0, 1, or 2", host_index));
+        // Validate host_index (typically 0, 1, or 2)
+        if host_index > 2 {
+            return Err(format!("Invalid host_index: {}. Must be 0, 1, or 2", host_index));
+        }
+
+        tracing::info!(host_index, "Switching to Easy-Switch host slot");
+
+        // Function 0x01: setCurrentHost with param = host_index
+        // Note: Some documentation shows function 0x00 for setHost, but logitech-flow-kvm
+        // uses the setCurrentHost function which takes the target host index as parameter
+        // Single attempt: the device drops off instead of answering, don't re-send
+        let resp = self.hidpp_request_single(change_host_index, 0x01, &[host_index]);
+
+        match resp {
+            Some(_) => {
+                tracing::info!(host_index, "Successfully sent host switch command");
+                Ok(())
+            }
+            None => {
+                // Note: The device may disconnect before sending a response
+                // when switching hosts, so a missing response might still mean success
+                tracing::warn!(host_index, "No response from host switch command (device may have disconnected)");
+                Ok(())
+            }
+        }
+    }
+
+    // =========================================================================
+    // Temporary Button Divert (REPROG_CONTROLS_V4, runtime-only)
+    // =========================================================================
+
+    /// Temporarily divert (or release) a control to HID++ notifications
+    ///
+    /// Uses only the volatile divert bit of setCidReporting, validated by
+    /// [`verify_divert_request`]. The device drops the divert on power cycle.
+    pub fn set_temporary_divert(&mut self, cid: u16, divert: bool) -> Result<(), HapticError> {
+        let params = temporary_divert::params(cid, divert);
+        verify_divert_request(temporary_divert::SET_CID_REPORTING, &params)?;
+
+        let feature_index = match self.reprog_feature_index {
+            Some(idx) => idx,
+            None => {
+                let idx = self
+                    .get_feature_index(blocklisted_features::SPECIAL_KEYS)
+                    .ok_or(HapticError::NotSupported)?;
+                self.reprog_feature_index = Some(idx);
+                idx
+            }
+        };
+
+        match self.hidpp_request(feature_index, temporary_divert::SET_CID_REPORTING, &params) {
+            Some(_) => {
+                tracing::info!(cid = format!("0x{:03X}", cid), divert, "Temporary button divert updated");
+                Ok(())
+            }
+            None => Err(HapticError::CommunicationError),
+        }
+    }
+
+    /// Temporarily divert the gesture button (MX Master 4 and 3/3S CIDs)
+    ///
+    /// Succeeds if at least one of the CIDs exists on this device.
+    pub fn set_gesture_divert(&mut self, divert: bool) -> Result<(), HapticError> {
+        let mut result = Err(HapticError::NotSupported);
+        for cid in [crate::hidraw::button_cid::HAPTIC, crate::hidraw::button_cid::GESTURE_BUTTON] {
+            match self.set_temporary_divert(cid, divert) {
+                Ok(()) => result = Ok(()),
+                Err(e @ HapticError::SafetyViolation { .. }) => return Err(e),
+                Err(HapticError::NotSupported) => return Err(HapticError::NotSupported),
+                Err(e) => tracing::debug!(cid, "Control not divertable: {}", e),
+            }
+        }
+        result
+    }
+}
+
+/// Divert (or release) the gesture button and the `button_menus` buttons
+///
+/// Only the gesture button's result counts; a menu button the device lacks
+/// is logged and skipped.
+fn divert_menu_buttons(device: &mut HidppDevice, cids: &[u16], divert: bool) -> Result<(), HapticError> {
+    let result = device.set_gesture_divert(divert);
+    for &cid in cids {
+        if let Err(e) = device.set_temporary_divert(cid, divert) {
+            tracing::warn!(cid, "Menu button divert failed: {}", e);
+        }
+    }
+    result
+}
+
+// ============================================================================
+// Haptic Acknowledgement
+// ============================================================================
+
+/// How long to wait for the device to answer a haptic play request
+pub const HAPTIC_ACK_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// How the device answered a haptic play request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HapticAck {
+    /// The request was echoed back: the waveform played
+    Ok,
+    /// HID++ error report with this error code
+    Rejected(u8),
+    /// No answer within [`HAPTIC_ACK_TIMEOUT`]
+    Timeout,
+}
+
+impl HapticAck {
+    /// Result label used in metrics ("ok", "error", "timeout")
+    pub fn label(&self) -> &'static str {
+        match self {
+            HapticAck::Ok => "ok",
+            HapticAck::Rejected(_) => "error",
+            HapticAck::Timeout => "timeout",
+        }
+    }
+}
+
+/// Whether `report` answers the haptic request sent with `feature_index`
+/// and `function_sw` (function << 4 | software ID); None for unrelated reports
+pub fn match_haptic_ack(report: &[u8], device_index: u8, feature_index: u8, function_sw: u8) -> Option<HapticAck> {
+    if report.len() < 7 || (report[0] != report_type::SHORT && report[0] != report_type::LONG) || report[1] != device_index {
+        return None;
+    }
+    if report[2] == feature_index && report[3] == function_sw {
+        return Some(HapticAck::Ok);
+    }
+    // Error: [type, device, 0xFF, feature_index, function_sw, error_code, ...]
+    if report[2] == 0xFF && report[3] == feature_index && report[4] == function_sw {
+        return Some(HapticAck::Rejected(report[5]));
+    }
+    None
+}
+
+// ============================================================================
+// HID++ 1.0 Fallback
+// ============================================================================
+
+/// HID++ protocol spoken by the device
+///
+/// Older Unifying receivers and some devices paired to them only speak
+/// HID++ 1.0: no feature table, just registers. They answer the IRoot ping
+/// with a 1.0 "invalid SubID" error; such a device is still opened, with
+/// battery read from registers and everything else unavailable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolVersion {
+    /// HID++ 1.0 (registers)
+    Hidpp10,
+    /// HID++ 2.0 or later (features)
+    Hidpp20,
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolVersion::Hidpp10 => write!(f, "HID++ 1.0"),
+            ProtocolVersion::Hidpp20 => write!(f, "HID++ 2.0"),
         }
+    }
+}
 
-        tracing::info!(host_index, "Switching to Easy-Switch host slot");
+/// HID++ 1.0 sub IDs, registers and error codes
+///
+/// Only GET_REGISTER is ever sent; SET_REGISTER (0x80/0x82) would write
+/// device settings and is not used.
+pub mod hidpp10 {
+    /// Short register read
+    pub const GET_REGISTER: u8 = 0x81;
+    /// Error report sub ID
+    pub const ERROR: u8 = 0x8F;
+    /// Error code: unknown sub ID (how a 1.0 device answers the 2.0 ping)
+    pub const ERR_INVALID_SUBID: u8 = 0x01;
+    /// Battery charge: [percentage, reserved, status]
+    pub const REG_BATTERY_CHARGE: u8 = 0x0D;
+    /// Battery status: [level (1-7), charging, reserved]
+    pub const REG_BATTERY_STATUS: u8 = 0x07;
+}
 
-        // Function 0x01: setCurrentHost with param = host_index
-        // Note: Some documentation shows function 0x00 for setHost, but logitech-flow-kvm
-        // uses the setCurrentHost function which takes the target host index as parameter
-        let resp = self.hidpp_request(change_host_index, 0x01, &[host_index]);
+/// Answer to a short request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortReply {
+    /// The request was answered; the three parameter bytes
+    Ok([u8; 3]),
+    /// HID++ 1.0 (0x8F) or 2.0 (0xFF) error report with this error code
+    Error(u8),
+}
 
-        match resp {
-            Some(_) => {
-                tracing::info!(host_index, "Successfully sent host switch command");
-                Ok(())
-            }
-            None => {
-                // Note: The device may disconnect before sending a response
-                // when switching hosts, so a missing response might still mean success
-                tracing::warn!(host_index, "No response from host switch command (device may have disconnected)");
-                Ok(())
-            }
-        }
+/// Whether `report` answers the short `request`; None for unrelated reports
+///
+/// A reply repeats the device index, sub ID (feature index) and address
+/// (function | software ID) of the request. An error report carries them
+/// shifted by one: [type, device, 0x8F/0xFF, sub_id, address, error_code, ...].
+pub fn match_short_reply(report: &[u8], request: &[u8; 7]) -> Option<ShortReply> {
+    if report.len() < 7 || (report[0] != report_type::SHORT && report[0] != report_type::LONG) || report[1] != request[1] {
+        return None;
+    }
+    if report[2] == request[2] && report[3] == request[3] {
+        return Some(ShortReply::Ok([report[4], report[5], report[6]]));
+    }
+    if (report[2] == hidpp10::ERROR || report[2] == 0xFF) && report[3] == request[2] && report[4] == request[3] {
+        return Some(ShortReply::Error(report[5]));
+    }
+    None
+}
+
+/// Protocol version from the answer to the IRoot ping
+///
+/// 2.0 devices echo the ping byte; 1.0 devices reject the unknown sub ID.
+/// Any other error (e.g. no device at this index) says nothing.
+pub fn protocol_from_ping(reply: ShortReply, ping_data: u8) -> Option<ProtocolVersion> {
+    match reply {
+        ShortReply::Ok([_, _, echoed]) if echoed == ping_data => Some(ProtocolVersion::Hidpp20),
+        ShortReply::Error(hidpp10::ERR_INVALID_SUBID) => Some(ProtocolVersion::Hidpp10),
+        _ => None,
     }
 }
 
+/// Battery from register 0x0D: (percentage, charging)
+pub fn parse_battery_charge(params: [u8; 3]) -> (u8, bool) {
+    // Status in the high nibble: 0x30 discharging, 0x50 recharging, 0x90 full
+    let charging = matches!(params[2] & 0xF0, 0x50 | 0x90);
+    (params[0].min(100), charging)
+}
+
+/// Battery from register 0x07: (approximate percentage, charging)
+///
+/// The level is coarse (7 full, 5 good, 3 low, 1 critical); None if the
+/// device reports no level.
+pub fn parse_battery_status(params: [u8; 3]) -> Option<(u8, bool)> {
+    let percentage = match params[0] {
+        7 => 90,
+        5 => 50,
+        3 => 20,
+        1 => 5,
+        _ => return None,
+    };
+    // 0x21 recharging, 0x22 almost full, 0x23 full; 0x00 discharging
+    let charging = params[1] & 0xF0 == 0x20;
+    Some((percentage, charging))
+}
+
 // ============================================================================
 // Haptic Pulse
 // ============================================================================
@@ -1771,7 +2448,8 @@ impl HapticPattern {
 /// MX Master 4 haptic waveforms
 ///
 /// The MX Master 4 uses predefined haptic waveforms. The actual haptic
-/// commands are sent via feature index 0x0B with function 0x04
+/// commands are sent via the haptic feature's index (0x0B on current
+/// firmware) with function 0x04
 /// (based on mx4notifications project implementation).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
@@ -1811,6 +2489,26 @@ pub enum Mx4HapticPattern {
 }
 
 impl Mx4HapticPattern {
+    /// All waveforms, in waveform ID order
+    pub const ALL: [Mx4HapticPattern; 16] = [
+        Self::SharpStateChange,
+        Self::DampStateChange,
+        Self::SharpCollision,
+        Self::DampCollision,
+        Self::SubtleCollision,
+        Self::HappyAlert,
+        Self::AngryAlert,
+        Self::Completed,
+        Self::Square,
+        Self::Wave,
+        Self::Firework,
+        Self::Mad,
+        Self::Knock,
+        Self::Jingle,
+        Self::Ringing,
+        Self::WhisperCollision,
+    ];
+
     /// Convert pattern to raw ID for HID++ command
     pub fn to_id(self) -> u8 {
         self as u8
@@ -1861,31 +2559,40 @@ impl Mx4HapticPattern {
         }
     }
 
+    /// Config name (snake_case), as accepted by [`Mx4HapticPattern::from_name`]
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            Self::SharpStateChange => "sharp_state_change",
+            Self::DampStateChange => "damp_state_change",
+            Self::SharpCollision => "sharp_collision",
+            Self::DampCollision => "damp_collision",
+            Self::SubtleCollision => "subtle_collision",
+            Self::WhisperCollision => "whisper_collision",
+            Self::HappyAlert => "happy_alert",
+            Self::AngryAlert => "angry_alert",
+            Self::Completed => "completed",
+            Self::Square => "square",
+            Self::Wave => "wave",
+            Self::Firework => "firework",
+            Self::Mad => "mad",
+            Self::Knock => "knock",
+            Self::Jingle => "jingle",
+            Self::Ringing => "ringing",
+        }
+    }
+
+    /// Parse a config name string (snake_case); None if not recognized
+    pub fn parse_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.config_name() == name)
+    }
+
     /// Create from config name string (snake_case)
     /// Returns SubtleCollision as default if name is not recognized
     pub fn from_name(name: &str) -> Self {
-        match name {
-            "sharp_state_change" => Self::SharpStateChange,
-            "damp_state_change" => Self::DampStateChange,
-            "sharp_collision" => Self::SharpCollision,
-            "damp_collision" => Self::DampCollision,
-            "subtle_collision" => Self::SubtleCollision,
-            "whisper_collision" => Self::WhisperCollision,
-            "happy_alert" => Self::HappyAlert,
-            "angry_alert" => Self::AngryAlert,
-            "completed" => Self::Completed,
-            "square" => Self::Square,
-            "wave" => Self::Wave,
-            "firework" => Self::Firework,
-            "mad" => Self::Mad,
-            "knock" => Self::Knock,
-            "jingle" => Self::Jingle,
-            "ringing" => Self::Ringing,
-            _ => {
-                tracing::warn!(name, "Unknown haptic pattern name, using default");
-                Self::SubtleCollision
-            }
-        }
+        Self::parse_name(name).unwrap_or_else(|| {
+            tracing::warn!(name, "Unknown haptic pattern name, using default");
+            Self::SubtleCollision
+        })
     }
 }
 
@@ -2010,9 +2717,10 @@ impl PerEventPattern {
 }
 
 /// Connection state for graceful fallback handling
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ConnectionState {
     /// No connection attempted yet
+    #[default]
     NotConnected,
     /// Successfully connected to device
     Connected,
@@ -2022,12 +2730,6 @@ pub enum ConnectionState {
     Cooldown,
 }
 
-impl Default for ConnectionState {
-    fn default() -> Self {
-        ConnectionState::NotConnected
-    }
-}
-
 /// Reconnection cooldown in milliseconds (5 seconds)
 const RECONNECT_COOLDOWN_MS: u64 = 5000;
 
@@ -2037,6 +2739,9 @@ const DEFAULT_SLICE_DEBOUNCE_MS: u64 = 20;
 /// Default re-entry debounce time (milliseconds)
 const DEFAULT_REENTRY_DEBOUNCE_MS: u64 = 50;
 
+/// Legacy pulse intensity while quiet hours reduce haptics to a whisper
+const QUIET_LEGACY_INTENSITY: u8 = 15;
+
 /// HID++ haptic manager
 pub struct HapticManager {
     /// Optional HID++ device connection
@@ -2063,6 +2768,24 @@ pub struct HapticManager {
     last_slice_change_ms: u64,
     /// Last slice index for re-entry detection (None = no previous slice)
     last_slice_index: Option<u8>,
+    /// Battery saver: suppress slice-change haptics (menu/confirm still pulse)
+    power_saving: bool,
+    /// Keep the gesture button temporarily diverted (re-applied on reconnect)
+    gesture_divert: bool,
+    /// Extra buttons diverted along with the gesture button (`button_menus`)
+    menu_button_cids: Vec<u16>,
+    /// A keep-alive ping failed; the next working link is treated as a wake
+    link_down: bool,
+    /// Last DPI read from or written to the device (cleared on disconnect)
+    last_dpi: Option<u16>,
+    /// Quiet hours schedule (None = disabled)
+    quiet_hours: Option<QuietHours>,
+    /// Token bucket shared by all send paths
+    budget: HapticBudget,
+    /// Haptic settings from config.json
+    base_config: crate::config::HapticConfig,
+    /// Overrides of the active profile, applied over `base_config`
+    profile_haptics: Option<crate::profiles::ProfileHaptics>,
     /// Pre-allocated short message buffer for low-latency sends
     _short_msg_buffer: [u8; 7],
 }
@@ -2083,6 +2806,18 @@ impl HapticManager {
             reentry_debounce_ms: DEFAULT_REENTRY_DEBOUNCE_MS,
             last_slice_change_ms: 0,
             last_slice_index: None,
+            power_saving: false,
+            gesture_divert: false,
+            menu_button_cids: Vec::new(),
+            link_down: false,
+            last_dpi: None,
+            quiet_hours: None,
+            budget: HapticBudget::default(),
+            base_config: crate::config::HapticConfig {
+                enabled,
+                ..Default::default()
+            },
+            profile_haptics: None,
             _short_msg_buffer: [0u8; 7],
         }
     }
@@ -2109,12 +2844,50 @@ impl HapticManager {
             reentry_debounce_ms: config.reentry_debounce_ms,
             last_slice_change_ms: 0,
             last_slice_index: None,
+            power_saving: false,
+            gesture_divert: false,
+            menu_button_cids: Vec::new(),
+            link_down: false,
+            last_dpi: None,
+            quiet_hours: QuietHours::from_config(&config.quiet_hours),
+            budget: HapticBudget::from_config(&config.rate_limit),
+            base_config: config.clone(),
+            profile_haptics: None,
             _short_msg_buffer: [0u8; 7],
         }
     }
 
     /// Update settings from configuration (for hot-reload)
+    ///
+    /// The active profile's overrides stay applied on top.
     pub fn update_from_config(&mut self, config: &crate::config::HapticConfig) {
+        self.base_config = config.clone();
+        self.apply_config();
+    }
+
+    /// Apply the active profile's haptic overrides (None = config.json only)
+    ///
+    /// Called on profile switch; a no-op if the overrides did not change.
+    pub fn set_profile_haptics(&mut self, haptics: Option<&crate::profiles::ProfileHaptics>) {
+        if self.profile_haptics.as_ref() == haptics {
+            return;
+        }
+        self.profile_haptics = haptics.cloned();
+        tracing::debug!(overrides = ?self.profile_haptics, "Profile haptic overrides changed");
+        self.apply_config();
+    }
+
+    /// Settings in effect: config.json with the profile's overrides
+    pub fn effective_config(&self) -> crate::config::HapticConfig {
+        match &self.profile_haptics {
+            Some(haptics) => haptics.apply(&self.base_config),
+            None => self.base_config.clone(),
+        }
+    }
+
+    /// Load the effective settings into the manager
+    fn apply_config(&mut self) {
+        let config = self.effective_config();
         self.default_pattern = Mx4HapticPattern::from_name(&config.default_pattern);
         self.per_event = PerEventPattern {
             menu_appear: Mx4HapticPattern::from_name(&config.per_event.menu_appear),
@@ -2126,6 +2899,11 @@ impl HapticManager {
         self.debounce_ms = config.debounce_ms;
         self.slice_debounce_ms = config.slice_debounce_ms;
         self.reentry_debounce_ms = config.reentry_debounce_ms;
+        self.quiet_hours = QuietHours::from_config(&config.quiet_hours);
+        self.budget.update_from_config(&config.rate_limit);
+        if let Some(device) = self.device.as_mut() {
+            device.set_verify_haptic_ack(config.verify_ack);
+        }
 
         tracing::debug!(
             default_pattern = %self.default_pattern,
@@ -2143,9 +2921,15 @@ impl HapticManager {
     /// This is NOT an error - haptics are optional.
     pub fn connect(&mut self) -> Result<bool, HapticError> {
         match HidppDevice::open() {
-            Some(device) => {
+            Some(mut device) => {
                 let haptic_supported = device.haptic_supported();
                 let connection = device.connection_type();
+                device.set_verify_haptic_ack(self.base_config.verify_ack);
+                if self.gesture_divert {
+                    if let Err(e) = divert_menu_buttons(&mut device, &self.menu_button_cids, true) {
+                        tracing::warn!("Native gesture button divert failed: {}", e);
+                    }
+                }
                 self.device = Some(device);
                 self.connection_state = ConnectionState::Connected;
 
@@ -2189,6 +2973,7 @@ impl HapticManager {
         self.device = None;
         self.connection_state = ConnectionState::Disconnected;
         self.last_disconnect_ms = now;
+        self.last_dpi = None;
     }
 
     /// Attempt to reconnect if device was disconnected and cooldown has passed
@@ -2220,6 +3005,7 @@ impl HapticManager {
         match self.connect() {
             Ok(true) => {
                 tracing::info!("Haptic device reconnected successfully");
+                crate::metrics::record_reconnect(crate::metrics::Component::Haptic);
                 true
             }
             Ok(false) => {
@@ -2237,11 +3023,99 @@ impl HapticManager {
         }
     }
 
+    /// Validate the link and reconnect proactively (warm standby)
+    ///
+    /// Pings the open device; a stale handle (e.g. after sleep or a receiver
+    /// replug) is dropped and reopened right away, and a missing device is
+    /// looked for again, so the next haptic never pays the connect cost.
+    /// Returns true if a device is connected afterwards.
+    pub fn keep_alive(&mut self) -> bool {
+        // Pings fail while the mouse is on another host; keep the handle for its return
+        if crate::host_switch::is_paused() {
+            return self.device.is_some();
+        }
+        if let Some(device) = self.device.as_mut() {
+            if device.ping() {
+                if std::mem::take(&mut self.link_down) {
+                    tracing::info!("Haptic link answers again after a failed ping (wake), refreshing features");
+                    self.refresh_features();
+                }
+                return true;
+            }
+            tracing::debug!("Haptic link failed keep-alive ping");
+            self.link_down = true;
+            self.handle_disconnect();
+        }
+
+        let was_disconnected = self.connection_state == ConnectionState::Disconnected;
+        match self.connect() {
+            Ok(true) => {
+                if was_disconnected {
+                    tracing::info!("Haptic device reconnected by keep-alive");
+                    crate::metrics::record_reconnect(crate::metrics::Component::Haptic);
+                }
+                // The cached table may predate the sleep
+                if std::mem::take(&mut self.link_down) {
+                    self.refresh_features();
+                }
+                true
+            }
+            Ok(false) | Err(_) => {
+                if was_disconnected {
+                    self.connection_state = ConnectionState::Disconnected;
+                }
+                false
+            }
+        }
+    }
+
+    /// Read pending reports from the device without sending anything
+    ///
+    /// Receiver notifications among them update the mouse's presence (see
+    /// [`crate::receiver_notifications`]). Used while HID++ traffic is
+    /// paused, so the notification that the mouse is back is seen even when
+    /// no other reader is running.
+    pub fn drain_notifications(&mut self) {
+        if let Some(device) = self.device.as_mut() {
+            device.drain_buffer();
+        }
+    }
+
+    /// Re-read the device's feature table after it woke up
+    ///
+    /// Also re-applies the temporary gesture divert, which the device drops
+    /// while asleep. Returns true if any feature index changed.
+    pub fn refresh_features(&mut self) -> bool {
+        let Some(device) = self.device.as_mut() else {
+            return false;
+        };
+        let changed = device.refresh_features();
+        if changed {
+            self.last_dpi = None;
+        }
+        if self.gesture_divert {
+            if let Err(e) = divert_menu_buttons(device, &self.menu_button_cids, true) {
+                tracing::warn!("Native gesture button divert failed after wake: {}", e);
+            }
+        }
+        changed
+    }
+
     /// Get current connection state
     pub fn connection_state(&self) -> ConnectionState {
         self.connection_state
     }
 
+    /// How the connected device is attached (None if not connected)
+    pub fn connection_type(&self) -> Option<ConnectionType> {
+        self.device.as_ref().map(|d| d.connection_type())
+    }
+
+    /// HID++ link statistics of the connected device (None if not connected)
+    pub fn link_stats(&self) -> Option<LinkStats> {
+        self.device.as_ref().map(|d| d.link_stats())
+    }
+
     /// Check if haptic feedback is available
     pub fn is_available(&self) -> bool {
         self.device
@@ -2250,6 +3124,21 @@ impl HapticManager {
             .unwrap_or(false)
     }
 
+    /// Optional features of the connected device (never connects)
+    pub fn device_capabilities(&self) -> Vec<&'static str> {
+        let Some(device) = self.device.as_ref() else {
+            return Vec::new();
+        };
+        [
+            ("haptics", device.haptic_supported()),
+            ("dpi", device.dpi_supported()),
+            ("smart-shift", device.smartshift_supported()),
+        ]
+        .into_iter()
+        .filter_map(|(name, supported)| supported.then_some(name))
+        .collect()
+    }
+
     /// Send a haptic pulse (runtime only, no memory writes)
     ///
     /// CRITICAL: This method MUST NOT write to onboard mouse memory.
@@ -2260,8 +3149,8 @@ impl HapticManager {
     /// If the device is disconnected or unavailable, this method succeeds
     /// silently. Menu functionality is never blocked by haptic failures.
     pub fn pulse(&mut self, haptic: HapticPulse) -> Result<(), HapticError> {
-        // Check if haptics are enabled
-        if !self.enabled {
+        // Check if haptics are enabled (and the mouse is on this host)
+        if !self.enabled || crate::host_switch::is_paused() {
             return Ok(());
         }
 
@@ -2294,6 +3183,7 @@ impl HapticManager {
         match device.send_haptic_pulse(haptic.intensity, haptic.duration_ms) {
             Ok(()) => {
                 self.last_pulse_ms = now;
+                crate::metrics::record_haptic_send();
                 Ok(())
             }
             Err(HapticError::IoError(_)) => {
@@ -2331,6 +3221,24 @@ impl HapticManager {
             return Ok(());
         }
 
+        // The mouse is connected to another host
+        if crate::host_switch::is_paused() {
+            tracing::debug!("Host switched away - skipping haptic");
+            return Ok(());
+        }
+
+        // Battery saver keeps only the essential (non hover) feedback
+        if self.power_saving && event == HapticEvent::SliceChange {
+            tracing::debug!("Power saving - skipping slice change haptic");
+            return Ok(());
+        }
+
+        // Quiet hours mute haptics or reduce them to a whisper
+        let Some(pattern) = self.scheduled_pattern(self.per_event.get(&event)) else {
+            tracing::debug!("Quiet hours - skipping haptic");
+            return Ok(());
+        };
+
         // Check if device is available (legacy haptic OR MX4 haptic)
         let device = match &mut self.device {
             Some(d) if d.haptic_supported() || d.mx4_haptic_supported() => d,
@@ -2355,10 +3263,14 @@ impl HapticManager {
             return Ok(());
         }
 
+        // Rate limit: slice changes are dropped when over budget, the rest always go out
+        if !self.budget.try_acquire(event != HapticEvent::SliceChange, Instant::now()) {
+            tracing::debug!(event = %event, "Haptic budget exhausted - skipping");
+            return Ok(());
+        }
+
         // Use MX Master 4 haptic patterns (configured per-event)
         if device.mx4_haptic_supported() {
-            // Get the configured pattern for this event
-            let pattern = self.per_event.get(&event);
             tracing::debug!(
                 event = %event,
                 pattern = %pattern,
@@ -2368,6 +3280,7 @@ impl HapticManager {
             match device.send_haptic_pattern(pattern) {
                 Ok(()) => {
                     self.last_pulse_ms = now;
+                    crate::metrics::record_haptic_send();
                     return Ok(());
                 }
                 Err(HapticError::IoError(_)) => {
@@ -2383,10 +3296,14 @@ impl HapticManager {
         }
 
         // Fallback to legacy intensity/duration-based pulses (non-MX4 devices)
-        // Use default intensity of 50 for legacy devices
+        // Use default intensity of 50 for legacy devices (a whisper during quiet hours)
         let base_profile = event.base_profile();
         let pulse_pattern = event.pattern();
-        let legacy_intensity: u8 = 50;
+        let legacy_intensity: u8 = if pattern == Mx4HapticPattern::WhisperCollision {
+            QUIET_LEGACY_INTENSITY
+        } else {
+            50
+        };
 
         tracing::debug!(
             event = %event,
@@ -2427,6 +3344,69 @@ impl HapticManager {
         Ok(())
     }
 
+    /// Play a specific waveform as a one-off cue (e.g. long-hover alternate armed)
+    ///
+    /// Respects the enabled flag and the rate limit but not the pulse
+    /// debounce; devices without MX4 waveforms get the confirm pulse instead.
+    pub fn emit_cue(&mut self, pattern: Mx4HapticPattern) {
+        if !self.enabled || crate::host_switch::is_paused() || !self.budget.try_acquire(false, Instant::now()) {
+            return;
+        }
+        let Some(pattern) = self.scheduled_pattern(pattern) else {
+            return;
+        };
+        let result = match self.device.as_mut() {
+            Some(device) if device.mx4_haptic_supported() => device.send_haptic_pattern(pattern),
+            Some(device) if device.legacy_haptic_supported() => {
+                device.send_haptic_pulse(haptic_profiles::CONFIRM.intensity, haptic_profiles::CONFIRM.duration_ms)
+            }
+            _ => return,
+        };
+        match result {
+            Ok(()) => crate::metrics::record_haptic_send(),
+            Err(HapticError::IoError(_)) => self.handle_disconnect(),
+            Err(e) => tracing::debug!(error = %e, "Haptic cue failed"),
+        }
+    }
+
+    /// Apply quiet hours to a pattern (None = muted right now)
+    fn scheduled_pattern(&self, pattern: Mx4HapticPattern) -> Option<Mx4HapticPattern> {
+        match self.quiet_hours {
+            Some(quiet) => quiet.adjust(pattern, local_minute_of_day()),
+            None => Some(pattern),
+        }
+    }
+
+    /// Play a waveform on demand for previewing patterns
+    ///
+    /// Bypasses debounce and the enabled flag (the user explicitly asked for
+    /// it) but not the rate limit. Fails if no device with MX4 haptics is
+    /// connected.
+    pub fn test_pattern(&mut self, pattern: Mx4HapticPattern) -> Result<(), HapticError> {
+        if !self.budget.try_acquire(false, Instant::now()) {
+            return Err(HapticError::RateLimited);
+        }
+        if crate::host_switch::is_paused() {
+            return Err(HapticError::DeviceNotFound);
+        }
+        if self.device.is_none() {
+            let _ = self.connect();
+        }
+        let device = self.device.as_mut().ok_or(HapticError::DeviceNotFound)?;
+        if !device.mx4_haptic_supported() {
+            return Err(HapticError::UnsupportedDevice);
+        }
+
+        tracing::info!(pattern = %pattern, "Playing haptic pattern preview");
+        match device.send_haptic_pattern(pattern) {
+            Err(HapticError::IoError(e)) => {
+                self.handle_disconnect();
+                Err(HapticError::IoError(e))
+            }
+            result => result,
+        }
+    }
+
     /// Emit a haptic event asynchronously (non-blocking)
     ///
     /// Spawns the haptic pattern execution in a separate thread
@@ -2468,8 +3448,8 @@ impl HapticManager {
     /// * `true` if haptic was emitted
     /// * `false` if debounced/suppressed
     pub fn emit_slice_change(&mut self, slice_index: u8) -> bool {
-        // Check if haptics are enabled
-        if !self.enabled {
+        // Check if haptics are enabled (slice haptics are dropped in power saving)
+        if !self.enabled || self.power_saving {
             return false;
         }
 
@@ -2567,6 +3547,16 @@ impl HapticManager {
         self.enabled
     }
 
+    /// Enable/disable battery-saver haptics (no slice-change pulses)
+    pub fn set_power_saving(&mut self, power_saving: bool) {
+        self.power_saving = power_saving;
+    }
+
+    /// Check if battery-saver haptics are active
+    pub fn is_power_saving(&self) -> bool {
+        self.power_saving
+    }
+
     /// Get the default haptic pattern
     pub fn default_pattern(&self) -> Mx4HapticPattern {
         self.default_pattern
@@ -2591,7 +3581,16 @@ impl HapticManager {
         if self.device.is_none() {
             let _ = self.connect();
         }
-        self.device.as_mut().and_then(|d| d.get_dpi())
+        let dpi = self.device.as_mut().and_then(|d| d.get_dpi());
+        if dpi.is_some() {
+            self.last_dpi = dpi;
+        }
+        dpi
+    }
+
+    /// Last known DPI without querying the device
+    pub fn cached_dpi(&self) -> Option<u16> {
+        self.last_dpi
     }
 
     /// Set DPI value
@@ -2601,7 +3600,11 @@ impl HapticManager {
             let _ = self.connect();
         }
         match self.device.as_mut() {
-            Some(device) => device.set_dpi(dpi),
+            Some(device) => {
+                device.set_dpi(dpi)?;
+                self.last_dpi = Some(dpi);
+                Ok(())
+            }
             None => {
                 tracing::warn!("Cannot set DPI: device not connected");
                 Err(HapticError::DeviceNotFound)
@@ -2618,6 +3621,43 @@ impl HapticManager {
         self.device.as_mut().and_then(|d| d.get_dpi_list())
     }
 
+    // =========================================================================
+    // Native Gesture Divert (delegated to HidppDevice)
+    // =========================================================================
+
+    /// Enable or disable the runtime-only gesture button divert
+    ///
+    /// While enabled the divert is re-applied whenever the device reconnects,
+    /// so the hidraw handler receives gesture presses without Solaar/logid.
+    pub fn set_gesture_divert(&mut self, enabled: bool) -> Result<(), HapticError> {
+        self.gesture_divert = enabled;
+        if self.device.is_none() {
+            // connect() applies the divert itself
+            return match self.connect() {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(HapticError::DeviceNotFound),
+                Err(e) => Err(e),
+            };
+        }
+        match self.device.as_mut() {
+            Some(device) => divert_menu_buttons(device, &self.menu_button_cids, enabled),
+            None => Err(HapticError::DeviceNotFound),
+        }
+    }
+
+    /// Set the extra buttons diverted along with the gesture button
+    ///
+    /// Takes effect with the next [`HapticManager::set_gesture_divert`] or
+    /// reconnect.
+    pub fn set_menu_button_cids(&mut self, cids: Vec<u16>) {
+        self.menu_button_cids = cids;
+    }
+
+    /// Check if the gesture button divert is requested
+    pub fn gesture_divert(&self) -> bool {
+        self.gesture_divert
+    }
+
     // =========================================================================
     // SmartShift Methods (delegated to HidppDevice)
     // =========================================================================
@@ -2640,6 +3680,7 @@ impl HapticManager {
     /// - wheel_mode: 1 = Freespin, 2 = Ratchet
     /// - auto_disengage: Threshold for automatic ratchet disengagement (1-254 = N/4 turns/sec, 255 = always engaged)
     /// - auto_disengage_default: Default threshold stored in device
+    ///
     /// None if SmartShift is not supported or device not connected
     pub fn get_smartshift(&mut self) -> Option<(u8, u8, u8)> {
         // Try to connect if not connected
@@ -2687,6 +3728,7 @@ impl HapticManager {
     /// Some((enabled, threshold)) where:
     /// - enabled: true if in Freespin mode (auto-mode), false if in Ratchet mode
     /// - threshold: auto-disengage threshold (inverted: 255 - raw_threshold for user-friendly 0-255 scale)
+    ///
     /// None if SmartShift is not supported or device not connected
     pub fn get_smart_shift(&mut self) -> Option<(bool, u8)> {
         self.get_smartshift().map(|(wheel_mode, auto_disengage, _default)| {
@@ -2731,6 +3773,7 @@ impl HapticManager {
     /// - hires: true = high resolution scrolling (more events, feels faster)
     /// - invert: true = natural/inverted scrolling
     /// - target: true = send scroll events directly to focused window
+    ///
     /// None if HiResScroll is not supported or device not connected
     pub fn get_hiresscroll_mode(&mut self) -> Option<(bool, bool, bool)> {
         // Try to connect if not connected
@@ -2874,6 +3917,8 @@ pub enum HapticError {
     IoError(std::io::Error),
     /// HID++ protocol error
     ProtocolError(String),
+    /// Haptic send budget exhausted
+    RateLimited,
     /// CRITICAL: Attempted to use blocklisted feature that writes to memory
     ///
     /// This error indicates a programming bug - we should NEVER
@@ -2902,6 +3947,7 @@ impl fmt::Display for HapticError {
             }
             HapticError::IoError(e) => write!(f, "I/O error: {}", e),
             HapticError::ProtocolError(msg) => write!(f, "HID++ protocol error: {}", msg),
+            HapticError::RateLimited => write!(f, "Haptic rate limit exceeded"),
             HapticError::SafetyViolation { feature_id, reason } => {
                 write!(
                     f,
@@ -2954,33 +4000,11 @@ mod tests {
 
     #[test]
     fn test_disabled_haptics() {
-        let mut manager = HapticManager::new(50, false);
+        let mut manager = HapticManager::new(false);
         // Should succeed but do nothing when disabled
         assert!(manager.pulse(haptic_profiles::CONFIRM).is_ok());
     }
 
-    #[test]
-    fn test_zero_intensity() {
-        let mut manager = HapticManager::new(0, true);
-        // Should succeed but do nothing with zero intensity
-        assert!(manager.pulse(haptic_profiles::CONFIRM).is_ok());
-    }
-
-    #[test]
-    fn test_intensity_scaling() {
-        let manager = HapticManager::new(50, true);
-        // 50% of 80 should be 40
-        let scaled = (haptic_profiles::CONFIRM.intensity as u16 * manager.intensity() as u16) / 100;
-        assert_eq!(scaled, 40);
-    }
-
-    #[test]
-    fn test_intensity_clamping() {
-        let manager = HapticManager::new(150, true);
-        // Should be clamped to 100
-        assert_eq!(manager.intensity(), 100);
-    }
-
     #[test]
     fn test_short_message_construction() {
         let msg = HidppShortMessage::new(0xFF, 0x00, 0x01, 0x05)
@@ -3040,7 +4064,7 @@ mod tests {
 
     #[test]
     fn test_graceful_fallback_no_device() {
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
         // Without connect(), device is None
         // Should succeed silently (graceful degradation)
         assert!(manager.pulse(haptic_profiles::CONFIRM).is_ok());
@@ -3050,13 +4074,13 @@ mod tests {
     #[test]
     fn test_default_manager() {
         let manager = HapticManager::default();
-        assert_eq!(manager.intensity(), 50);
+        assert_eq!(manager.default_pattern(), Mx4HapticPattern::SubtleCollision);
         assert!(manager.is_enabled());
     }
 
     #[test]
     fn test_set_debounce() {
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
         manager.set_debounce_ms(30);
         // Debounce is internal but we can verify it doesn't panic
         assert!(manager.pulse(haptic_profiles::CONFIRM).is_ok());
@@ -3068,15 +4092,18 @@ mod tests {
 
         let config = HapticConfig {
             enabled: true,
-            intensity: 75,
+            default_pattern: "sharp_collision".to_string(),
             per_event: Default::default(),
             debounce_ms: 30,
             slice_debounce_ms: 20,
             reentry_debounce_ms: 50,
+            quiet_hours: Default::default(),
+            rate_limit: Default::default(),
+            verify_ack: false,
         };
 
         let manager = HapticManager::from_config(&config);
-        assert_eq!(manager.intensity(), 75);
+        assert_eq!(manager.default_pattern(), Mx4HapticPattern::SharpCollision);
         assert!(manager.is_enabled());
     }
 
@@ -3086,11 +4113,14 @@ mod tests {
 
         let config = HapticConfig {
             enabled: false,
-            intensity: 75,
+            default_pattern: "subtle_collision".to_string(),
             per_event: Default::default(),
             debounce_ms: 20,
             slice_debounce_ms: 20,
             reentry_debounce_ms: 50,
+            quiet_hours: Default::default(),
+            rate_limit: Default::default(),
+            verify_ack: false,
         };
 
         let manager = HapticManager::from_config(&config);
@@ -3101,20 +4131,42 @@ mod tests {
     fn test_update_from_config() {
         use crate::config::HapticConfig;
 
-        let mut manager = HapticManager::new(50, true);
-        assert_eq!(manager.intensity(), 50);
+        let mut manager = HapticManager::new(true);
+        assert_eq!(manager.default_pattern(), Mx4HapticPattern::SubtleCollision);
 
         let new_config = HapticConfig {
             enabled: true,
-            intensity: 80,
+            default_pattern: "happy_alert".to_string(),
             per_event: Default::default(),
             debounce_ms: 25,
             slice_debounce_ms: 20,
             reentry_debounce_ms: 50,
+            quiet_hours: Default::default(),
+            rate_limit: Default::default(),
+            verify_ack: false,
         };
 
         manager.update_from_config(&new_config);
-        assert_eq!(manager.intensity(), 80);
+        assert_eq!(manager.default_pattern(), Mx4HapticPattern::HappyAlert);
+    }
+
+    #[test]
+    fn test_profile_haptics_survive_config_reload() {
+        use crate::config::HapticConfig;
+        use crate::profiles::ProfileHaptics;
+
+        let mut manager = HapticManager::from_config(&HapticConfig::default());
+        let office = ProfileHaptics { enabled: Some(false), ..Default::default() };
+        manager.set_profile_haptics(Some(&office));
+        assert!(!manager.is_enabled());
+
+        // A Settings API change keeps the profile's overrides
+        manager.update_from_config(&HapticConfig { slice_debounce_ms: 35, ..HapticConfig::default() });
+        assert!(!manager.is_enabled());
+        assert_eq!(manager.slice_debounce_ms(), 35);
+
+        manager.set_profile_haptics(None);
+        assert!(manager.is_enabled());
     }
 
     // ========================================================================
@@ -3169,46 +4221,49 @@ mod tests {
     }
 
     #[test]
-    fn test_per_event_intensity_defaults() {
-        let per_event = PerEventIntensity::default();
-        assert_eq!(per_event.menu_appear, 20);
-        assert_eq!(per_event.slice_change, 40);
-        assert_eq!(per_event.confirm, 80);
-        assert_eq!(per_event.invalid, 30);
+    fn test_mx4_pattern_names_round_trip() {
+        for pattern in Mx4HapticPattern::ALL {
+            assert_eq!(Mx4HapticPattern::parse_name(pattern.config_name()), Some(pattern));
+            assert_eq!(Mx4HapticPattern::from_id(pattern.to_id()), Some(pattern));
+        }
+        assert_eq!(Mx4HapticPattern::parse_name("rumble"), None);
+        assert_eq!(Mx4HapticPattern::from_name("rumble"), Mx4HapticPattern::SubtleCollision);
+    }
+
+    #[test]
+    fn test_per_event_pattern_defaults() {
+        let per_event = PerEventPattern::default();
+        assert_eq!(per_event.menu_appear, Mx4HapticPattern::DampStateChange);
+        assert_eq!(per_event.slice_change, Mx4HapticPattern::SubtleCollision);
+        assert_eq!(per_event.confirm, Mx4HapticPattern::SharpStateChange);
+        assert_eq!(per_event.invalid, Mx4HapticPattern::AngryAlert);
     }
 
     #[test]
-    fn test_per_event_intensity_get() {
-        let per_event = PerEventIntensity {
-            menu_appear: 15,
-            slice_change: 35,
-            confirm: 75,
-            invalid: 25,
+    fn test_per_event_pattern_get() {
+        let per_event = PerEventPattern {
+            menu_appear: Mx4HapticPattern::HappyAlert,
+            slice_change: Mx4HapticPattern::WhisperCollision,
+            confirm: Mx4HapticPattern::SharpCollision,
+            invalid: Mx4HapticPattern::AngryAlert,
         };
 
-        assert_eq!(per_event.get(&HapticEvent::MenuAppear), 15);
-        assert_eq!(per_event.get(&HapticEvent::SliceChange), 35);
-        assert_eq!(per_event.get(&HapticEvent::SelectionConfirm), 75);
-        assert_eq!(per_event.get(&HapticEvent::InvalidAction), 25);
+        assert_eq!(per_event.get(&HapticEvent::MenuAppear), Mx4HapticPattern::HappyAlert);
+        assert_eq!(per_event.get(&HapticEvent::SliceChange), Mx4HapticPattern::WhisperCollision);
+        assert_eq!(per_event.get(&HapticEvent::SelectionConfirm), Mx4HapticPattern::SharpCollision);
+        assert_eq!(per_event.get(&HapticEvent::InvalidAction), Mx4HapticPattern::AngryAlert);
     }
 
     #[test]
     fn test_emit_disabled() {
-        let mut manager = HapticManager::new(50, false);
+        let mut manager = HapticManager::new(false);
         // Should succeed but do nothing when disabled
         assert!(manager.emit(HapticEvent::MenuAppear).is_ok());
     }
 
-    #[test]
-    fn test_emit_zero_intensity() {
-        let mut manager = HapticManager::new(0, true);
-        // Should succeed but do nothing with zero intensity
-        assert!(manager.emit(HapticEvent::MenuAppear).is_ok());
-    }
-
     #[test]
     fn test_emit_no_device() {
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
         // Without connect(), device is None - should succeed silently
         assert!(manager.emit(HapticEvent::MenuAppear).is_ok());
         assert!(manager.emit(HapticEvent::SliceChange).is_ok());
@@ -3216,80 +4271,64 @@ mod tests {
         assert!(manager.emit(HapticEvent::InvalidAction).is_ok());
     }
 
-    #[test]
-    fn test_emit_intensity_scaling() {
-        // Test intensity calculation: (global/100) * (per_event/100) * 100
-        // With global=50, per_event=80 (confirm) → 50 * 80 / 100 = 40
-        let global = 50u32;
-        let per_event = 80u32;
-        let scaled = (global * per_event / 100) as u8;
-        assert_eq!(scaled, 40);
-
-        // With global=100, per_event=20 (menu_appear) → 100 * 20 / 100 = 20
-        let global = 100u32;
-        let per_event = 20u32;
-        let scaled = (global * per_event / 100) as u8;
-        assert_eq!(scaled, 20);
-
-        // With global=25, per_event=40 (slice_change) → 25 * 40 / 100 = 10
-        let global = 25u32;
-        let per_event = 40u32;
-        let scaled = (global * per_event / 100) as u8;
-        assert_eq!(scaled, 10);
-    }
-
     #[test]
     fn test_from_config_with_per_event() {
         use crate::config::{HapticConfig, HapticEventConfig};
 
         let config = HapticConfig {
             enabled: true,
-            intensity: 60,
+            default_pattern: "sharp_collision".to_string(),
             per_event: HapticEventConfig {
-                menu_appear: 25,
-                slice_change: 45,
-                confirm: 85,
-                invalid: 35,
+                menu_appear: "happy_alert".to_string(),
+                slice_change: "whisper_collision".to_string(),
+                confirm: "sharp_collision".to_string(),
+                invalid: "angry_alert".to_string(),
             },
             debounce_ms: 25,
             slice_debounce_ms: 20,
             reentry_debounce_ms: 50,
+            quiet_hours: Default::default(),
+            rate_limit: Default::default(),
+            verify_ack: false,
         };
 
         let manager = HapticManager::from_config(&config);
-        assert_eq!(manager.intensity(), 60);
-        assert_eq!(manager.per_event.menu_appear, 25);
-        assert_eq!(manager.per_event.slice_change, 45);
-        assert_eq!(manager.per_event.confirm, 85);
-        assert_eq!(manager.per_event.invalid, 35);
+        assert_eq!(manager.default_pattern(), Mx4HapticPattern::SharpCollision);
+        assert_eq!(manager.per_event.menu_appear, Mx4HapticPattern::HappyAlert);
+        assert_eq!(manager.per_event.slice_change, Mx4HapticPattern::WhisperCollision);
+        assert_eq!(manager.per_event.confirm, Mx4HapticPattern::SharpCollision);
+        assert_eq!(manager.per_event.invalid, Mx4HapticPattern::AngryAlert);
     }
 
     #[test]
     fn test_update_from_config_with_per_event() {
         use crate::config::{HapticConfig, HapticEventConfig};
 
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
 
         let new_config = HapticConfig {
             enabled: true,
-            intensity: 70,
+            default_pattern: "damp_collision".to_string(),
             per_event: HapticEventConfig {
-                menu_appear: 30,
-                slice_change: 50,
-                confirm: 90,
-                invalid: 40,
+                menu_appear: "knock".to_string(),
+                slice_change: "ringing".to_string(),
+                confirm: "completed".to_string(),
+                invalid: "mad".to_string(),
             },
             debounce_ms: 30,
             slice_debounce_ms: 20,
             reentry_debounce_ms: 50,
+            quiet_hours: Default::default(),
+            rate_limit: Default::default(),
+            verify_ack: false,
         };
 
         manager.update_from_config(&new_config);
-        assert_eq!(manager.intensity(), 70);
-        assert_eq!(manager.per_event.menu_appear, 30);
-        assert_eq!(manager.per_event.slice_change, 50);
-        assert_eq!(manager.per_event.confirm, 90);
-        assert_eq!(manager.per_event.invalid, 40);
+        assert_eq!(manager.default_pattern(), Mx4HapticPattern::DampCollision);
+        assert_eq!(manager.per_event.menu_appear, Mx4HapticPattern::Knock);
+        assert_eq!(manager.per_event.slice_change, Mx4HapticPattern::Ringing);
+        assert_eq!(manager.per_event.confirm, Mx4HapticPattern::Completed);
+        assert_eq!(manager.per_event.invalid, Mx4HapticPattern::Mad);
     }
 
     // ========================================================================
@@ -3407,19 +4446,104 @@ mod tests {
         assert!(verify_feature_safety(features::FORCE_FEEDBACK).is_ok());
     }
 
+    #[test]
+    fn test_match_haptic_ack() {
+        let echo = [report_type::SHORT, 0x02, 0x0B, 0x4E, 0x00, 0x00, 0x00];
+        assert_eq!(match_haptic_ack(&echo, 0x02, 0x0B, 0x4E), Some(HapticAck::Ok));
+        let error = [report_type::SHORT, 0x02, 0xFF, 0x0B, 0x4E, 0x02, 0x00];
+        assert_eq!(match_haptic_ack(&error, 0x02, 0x0B, 0x4E), Some(HapticAck::Rejected(0x02)));
+        assert_eq!(HapticAck::Rejected(0x02).label(), "error");
+
+        // Other devices, features or software IDs are not the answer
+        assert_eq!(match_haptic_ack(&echo, 0x01, 0x0B, 0x4E), None);
+        let battery = [report_type::LONG, 0x02, 0x08, 0x00, 0x50, 0x00, 0x00];
+        assert_eq!(match_haptic_ack(&battery, 0x02, 0x0B, 0x4E), None);
+        assert_eq!(match_haptic_ack(&echo[..4], 0x02, 0x0B, 0x4E), None);
+    }
+
+    #[test]
+    fn test_protocol_probe() {
+        let ping = [report_type::SHORT, 0x01, 0x00, 0x11, 0x00, 0x00, 0xAA];
+
+        let v20 = [report_type::SHORT, 0x01, 0x00, 0x11, 0x04, 0x05, 0xAA];
+        let reply = match_short_reply(&v20, &ping).unwrap();
+        assert_eq!(reply, ShortReply::Ok([0x04, 0x05, 0xAA]));
+        assert_eq!(protocol_from_ping(reply, 0xAA), Some(ProtocolVersion::Hidpp20));
+
+        let v10 = [report_type::SHORT, 0x01, hidpp10::ERROR, 0x00, 0x11, 0x01, 0x00];
+        let reply = match_short_reply(&v10, &ping).unwrap();
+        assert_eq!(protocol_from_ping(reply, 0xAA), Some(ProtocolVersion::Hidpp10));
+
+        // Nothing paired at this index: no protocol either way
+        let unknown_device = [report_type::SHORT, 0x01, hidpp10::ERROR, 0x00, 0x11, 0x08, 0x00];
+        let reply = match_short_reply(&unknown_device, &ping).unwrap();
+        assert_eq!(protocol_from_ping(reply, 0xAA), None);
+
+        // Reports for another device or request are not the answer
+        assert_eq!(match_short_reply(&v20, &[report_type::SHORT, 0x02, 0x00, 0x11, 0x00, 0x00, 0xAA]), None);
+        let register = [report_type::SHORT, 0x01, hidpp10::GET_REGISTER, 0x0D, 0x00, 0x00, 0x00];
+        assert_eq!(match_short_reply(&v20, &register), None);
+    }
+
+    #[test]
+    fn test_hidpp10_battery_registers() {
+        let request = [report_type::SHORT, 0x01, hidpp10::GET_REGISTER, hidpp10::REG_BATTERY_CHARGE, 0x00, 0x00, 0x00];
+        let reply = [report_type::SHORT, 0x01, hidpp10::GET_REGISTER, hidpp10::REG_BATTERY_CHARGE, 0x40, 0x00, 0x30];
+        assert_eq!(match_short_reply(&reply, &request), Some(ShortReply::Ok([0x40, 0x00, 0x30])));
+        let unsupported = [report_type::SHORT, 0x01, hidpp10::ERROR, hidpp10::GET_REGISTER, hidpp10::REG_BATTERY_CHARGE, 0x02, 0x00];
+        assert_eq!(match_short_reply(&unsupported, &request), Some(ShortReply::Error(0x02)));
+
+        assert_eq!(parse_battery_charge([0x40, 0x00, 0x30]), (64, false));
+        assert_eq!(parse_battery_charge([0x50, 0x00, 0x50]), (80, true));
+        assert_eq!(parse_battery_charge([0x64, 0x00, 0x90]), (100, true));
+
+        assert_eq!(parse_battery_status([7, 0x00, 0x00]), Some((90, false)));
+        assert_eq!(parse_battery_status([3, 0x21, 0x00]), Some((20, true)));
+        assert_eq!(parse_battery_status([5, 0x23, 0x00]), Some((50, true)));
+        assert_eq!(parse_battery_status([0, 0x00, 0x00]), None);
+    }
+
+    #[test]
+    fn test_temporary_divert_params_are_safe() {
+        let divert = temporary_divert::params(0x1A0, true);
+        assert_eq!(divert, [0x01, 0xA0, 0x03]);
+        assert!(verify_divert_request(temporary_divert::SET_CID_REPORTING, &divert).is_ok());
+
+        let release = temporary_divert::params(0xC3, false);
+        assert_eq!(release, [0x00, 0xC3, 0x02]);
+        assert!(verify_divert_request(temporary_divert::SET_CID_REPORTING, &release).is_ok());
+
+        // 0x1B04 itself stays blocklisted for everything else
+        assert!(verify_feature_safety(blocklisted_features::SPECIAL_KEYS).is_err());
+    }
+
+    #[test]
+    fn test_divert_request_rejects_persistent_writes() {
+        let persist = temporary_divert::PERSIST | temporary_divert::PERSIST_VALID | temporary_divert::DIVERT_VALID;
+        assert!(matches!(
+            verify_divert_request(temporary_divert::SET_CID_REPORTING, &[0x01, 0xA0, persist]),
+            Err(HapticError::SafetyViolation { feature_id: 0x1B04, .. })
+        ));
+        // Raw XY divert (0x10/0x20) is not a plain button divert either
+        assert!(verify_divert_request(temporary_divert::SET_CID_REPORTING, &[0x01, 0xA0, 0x33]).is_err());
+        // Remap payload (long report) and other functions are rejected
+        assert!(verify_divert_request(temporary_divert::SET_CID_REPORTING, &[0x01, 0xA0, 0x03, 0x00, 0x50]).is_err());
+        assert!(verify_divert_request(0x01, &[0x01, 0xA0, 0x03]).is_err());
+    }
+
     // ========================================================================
     // Story 5.5: Graceful Fallback & Error Handling Tests
     // ========================================================================
 
     #[test]
     fn test_connection_state_default() {
-        let manager = HapticManager::new(50, true);
+        let manager = HapticManager::new(true);
         assert_eq!(manager.connection_state(), ConnectionState::NotConnected);
     }
 
     #[test]
     fn test_pulse_succeeds_when_no_device() {
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
         // Without connect(), device is None
         // Should succeed silently (graceful degradation)
         assert!(manager.pulse(haptic_profiles::CONFIRM).is_ok());
@@ -3428,7 +4552,7 @@ mod tests {
 
     #[test]
     fn test_emit_succeeds_when_no_device() {
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
         // All emit calls should succeed silently
         assert!(manager.emit(HapticEvent::MenuAppear).is_ok());
         assert!(manager.emit(HapticEvent::SliceChange).is_ok());
@@ -3438,7 +4562,7 @@ mod tests {
 
     #[test]
     fn test_reconnect_not_needed_when_not_connected() {
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
         // NotConnected state - should return false but not try to reconnect
         assert!(!manager.reconnect_if_needed());
         assert_eq!(manager.connection_state(), ConnectionState::NotConnected);
@@ -3461,20 +4585,12 @@ mod tests {
 
     #[test]
     fn test_graceful_fallback_on_disabled() {
-        let mut manager = HapticManager::new(50, false);
+        let mut manager = HapticManager::new(false);
         // Disabled haptics should always succeed silently
         assert!(manager.pulse(haptic_profiles::CONFIRM).is_ok());
         assert!(manager.emit(HapticEvent::SelectionConfirm).is_ok());
     }
 
-    #[test]
-    fn test_graceful_fallback_on_zero_intensity() {
-        let mut manager = HapticManager::new(0, true);
-        // Zero intensity should always succeed silently
-        assert!(manager.pulse(haptic_profiles::CONFIRM).is_ok());
-        assert!(manager.emit(HapticEvent::SelectionConfirm).is_ok());
-    }
-
     #[test]
     fn test_reconnect_cooldown_constant() {
         // Verify cooldown is reasonable (5 seconds)
@@ -3499,29 +4615,22 @@ mod tests {
 
     #[test]
     fn test_manager_slice_debounce_defaults() {
-        let manager = HapticManager::new(50, true);
+        let manager = HapticManager::new(true);
         assert_eq!(manager.slice_debounce_ms(), 20);
         assert_eq!(manager.reentry_debounce_ms(), 50);
     }
 
     #[test]
     fn test_emit_slice_change_disabled() {
-        let mut manager = HapticManager::new(50, false);
+        let mut manager = HapticManager::new(false);
         // Should return false when disabled
         assert!(!manager.emit_slice_change(0));
         assert!(!manager.emit_slice_change(1));
     }
 
-    #[test]
-    fn test_emit_slice_change_zero_intensity() {
-        let mut manager = HapticManager::new(0, true);
-        // Should return false with zero intensity
-        assert!(!manager.emit_slice_change(0));
-    }
-
     #[test]
     fn test_emit_slice_change_no_device() {
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
         // Without connect(), device is None - should succeed gracefully
         // (returns true because emit succeeds silently without device)
         // First call after debounce window should work
@@ -3531,7 +4640,7 @@ mod tests {
 
     #[test]
     fn test_reset_slice_tracking() {
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
         manager.last_slice_index = Some(3);
         manager.last_slice_change_ms = 12345;
 
@@ -3543,14 +4652,14 @@ mod tests {
 
     #[test]
     fn test_set_slice_debounce_ms() {
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
         manager.set_slice_debounce_ms(30);
         assert_eq!(manager.slice_debounce_ms(), 30);
     }
 
     #[test]
     fn test_set_reentry_debounce_ms() {
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
         manager.set_reentry_debounce_ms(100);
         assert_eq!(manager.reentry_debounce_ms(), 100);
     }
@@ -3561,11 +4670,14 @@ mod tests {
 
         let config = HapticConfig {
             enabled: true,
-            intensity: 50,
+            default_pattern: "subtle_collision".to_string(),
             per_event: HapticEventConfig::default(),
             debounce_ms: 20,
             slice_debounce_ms: 25,
             reentry_debounce_ms: 60,
+            quiet_hours: Default::default(),
+            rate_limit: Default::default(),
+            verify_ack: false,
         };
 
         let manager = HapticManager::from_config(&config);
@@ -3577,17 +4689,20 @@ mod tests {
     fn test_update_from_config_with_slice_debounce() {
         use crate::config::{HapticConfig, HapticEventConfig};
 
-        let mut manager = HapticManager::new(50, true);
+        let mut manager = HapticManager::new(true);
         assert_eq!(manager.slice_debounce_ms(), 20);
         assert_eq!(manager.reentry_debounce_ms(), 50);
 
         let new_config = HapticConfig {
             enabled: true,
-            intensity: 50,
+            default_pattern: "subtle_collision".to_string(),
             per_event: HapticEventConfig::default(),
             debounce_ms: 20,
             slice_debounce_ms: 35,
             reentry_debounce_ms: 75,
+            quiet_hours: Default::default(),
+            rate_limit: Default::default(),
+            verify_ack: false,
         };
 
         manager.update_from_config(&new_config);
@@ -3598,7 +4713,7 @@ mod tests {
     #[test]
     fn test_short_message_buffer_preallocated() {
         // Verify the pre-allocated buffer exists and is correct size
-        let manager = HapticManager::new(50, true);
+        let manager = HapticManager::new(true);
         assert_eq!(manager._short_msg_buffer.len(), 7);
     }
 
@@ -0,0 +1,120 @@
+//! Audit log of outgoing HID++ messages
+//!
+//! Every HID++ report the daemon writes to a Logitech device (haptics, DPI,
+//! SmartShift, battery queries, temporary divert, ...) is appended here with
+//! the resolved feature ID. The log is capped at [`AUDIT_CAPACITY`] entries
+//! and retrievable via the `DumpHidppAudit` D-Bus method, so users can check
+//! that no persistent feature was ever touched.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::hidpp::blocklisted_features;
+
+/// Maximum entries kept (oldest are dropped first)
+pub const AUDIT_CAPACITY: usize = 512;
+
+/// Feature ID recorded when the feature index could not be resolved
+pub const UNKNOWN_FEATURE_ID: u16 = 0xFFFF;
+
+/// One outgoing HID++ message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// Unix time in milliseconds
+    pub timestamp_ms: u64,
+    /// Feature ID ([`UNKNOWN_FEATURE_ID`] if unresolved)
+    pub feature_id: u16,
+    /// Feature index used on the wire
+    pub feature_index: u8,
+    /// HID++ function number
+    pub function: u8,
+    /// Parameter bytes (without the 4-byte header)
+    pub params: Vec<u8>,
+}
+
+impl AuditEntry {
+    /// Build an entry from a raw HID++ report
+    ///
+    /// Returns None for reports shorter than the HID++ header.
+    pub fn from_report(report: &[u8], feature_id: Option<u16>, timestamp_ms: u64) -> Option<Self> {
+        if report.len() < 4 {
+            return None;
+        }
+        Some(Self {
+            timestamp_ms,
+            feature_id: feature_id.unwrap_or(UNKNOWN_FEATURE_ID),
+            feature_index: report[2],
+            function: report[3] >> 4,
+            params: report[4..].to_vec(),
+        })
+    }
+
+    /// Whether the entry targets a blocklisted feature
+    pub fn is_blocklisted(&self) -> bool {
+        blocklisted_features::is_blocklisted(self.feature_id)
+    }
+}
+
+/// Recorded entries, oldest first
+static LOG: Mutex<VecDeque<AuditEntry>> = Mutex::new(VecDeque::new());
+
+/// Append an entry, dropping the oldest once the log is full
+fn push(entry: AuditEntry) {
+    let Ok(mut log) = LOG.lock() else {
+        return;
+    };
+    if log.len() == AUDIT_CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(entry);
+}
+
+/// Record an outgoing HID++ report
+pub fn record(report: &[u8], feature_id: Option<u16>) {
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    if let Some(entry) = AuditEntry::from_report(report, feature_id, timestamp_ms) {
+        push(entry);
+    }
+}
+
+/// All recorded entries, oldest first
+pub fn entries() -> Vec<AuditEntry> {
+    LOG.lock().map(|log| log.iter().cloned().collect()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_from_report() {
+        let report = [0x10, 0x02, 0x0B, 0x41, 0x05, 0x00, 0x00];
+        let entry = AuditEntry::from_report(&report, Some(0x19B0), 42).unwrap();
+        assert_eq!(entry.feature_id, 0x19B0);
+        assert_eq!(entry.feature_index, 0x0B);
+        assert_eq!(entry.function, 0x04);
+        assert_eq!(entry.params, vec![0x05, 0x00, 0x00]);
+        assert!(!entry.is_blocklisted());
+
+        let unresolved = AuditEntry::from_report(&report, None, 42).unwrap();
+        assert_eq!(unresolved.feature_id, UNKNOWN_FEATURE_ID);
+        assert!(AuditEntry::from_report(&report[..3], None, 42).is_none());
+    }
+
+    #[test]
+    fn test_log_is_capped() {
+        for i in 0..AUDIT_CAPACITY + 5 {
+            record(&[0x10, 0xFF, 0x00, 0x01, (i % 256) as u8], Some(0x0000));
+        }
+        let log = entries();
+        assert_eq!(log.len(), AUDIT_CAPACITY);
+        // Newest entry is last
+        assert_eq!(log.last().unwrap().params[0], ((AUDIT_CAPACITY + 4) % 256) as u8);
+    }
+}
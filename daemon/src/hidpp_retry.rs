@@ -0,0 +1,158 @@
+//! Retry policy for HID++ requests
+//!
+//! Over a busy 2.4 GHz band a request or its response is occasionally lost.
+//! Instead of failing the battery query or DPI change outright, requests
+//! that went unanswered (or that the device rejected as busy) are resent
+//! after an exponentially growing, jittered delay. Real errors (the device
+//! is gone, or answered with a definite error) fail immediately, and so does
+//! the receiver's "device unreachable" reply for a sleeping or away mouse.
+//! Writes that must not be repeated (haptic pulses, host switches) are sent
+//! once and never go through this policy.
+//!
+//! `WouldBlock` on the non-blocking hidraw handle only means "no report yet"
+//! and never counts as a failure; an attempt fails when its response does
+//! not arrive within `timeout_ms`.
+//!
+//! The policy comes from `hidpp_retry` in config.json and is applied with
+//! [`configure`] at startup and on reload.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use crate::config::HidppRetryConfig;
+
+/// Active policy
+static POLICY: RwLock<Option<HidppRetryConfig>> = RwLock::new(None);
+
+/// Why a single attempt failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// No matching response within the attempt timeout
+    Timeout,
+    /// The device or receiver answered but could not serve the request now
+    Busy,
+    /// I/O error or a definite error response; retrying will not help
+    Fatal,
+}
+
+impl Failure {
+    /// Whether another attempt may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Failure::Timeout | Failure::Busy)
+    }
+}
+
+/// Apply a new policy
+pub fn configure(config: &HidppRetryConfig) {
+    if let Ok(mut policy) = POLICY.write() {
+        *policy = Some(config.clone());
+    }
+}
+
+/// Active policy (defaults until [`configure`] is called)
+pub fn policy() -> HidppRetryConfig {
+    POLICY.read().ok().and_then(|p| p.clone()).unwrap_or_default()
+}
+
+/// How long one attempt waits for its response
+pub fn attempt_timeout() -> Duration {
+    Duration::from_millis(policy().timeout_ms)
+}
+
+/// Delay before retry number `retry` (1 = first retry)
+///
+/// `random` in 0.0..1.0 picks where within the jitter band the delay lands:
+/// the last `jitter` fraction of the exponential delay is randomized.
+pub fn backoff(config: &HidppRetryConfig, retry: u32, random: f64) -> Duration {
+    let exponential = config.backoff_ms.saturating_mul(1u64 << retry.saturating_sub(1).min(16));
+    let capped = exponential.min(config.max_backoff_ms) as f64;
+    let jitter = capped * config.jitter as f64;
+    Duration::from_millis((capped - jitter + jitter * random.clamp(0.0, 1.0)).round() as u64)
+}
+
+/// Sample in 0.0..1.0 for the jitter
+///
+/// std's hasher keys are seeded from the OS and change with every
+/// `RandomState`, which is plenty for spreading retries apart.
+fn random_unit() -> f64 {
+    let bits = RandomState::new().hash_one(Instant::now());
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Run `attempt` until it succeeds, fails for good or the attempts run out
+///
+/// Blocks the calling thread between attempts, like the request itself.
+pub fn run<T, E>(mut attempt: impl FnMut() -> Result<T, E>, failure: impl Fn(&E) -> Failure) -> Result<T, E> {
+    let config = policy();
+    let mut retry = 0;
+    loop {
+        match attempt() {
+            Ok(value) => {
+                if retry > 0 {
+                    tracing::debug!(retry, "HID++ request succeeded after retrying");
+                }
+                return Ok(value);
+            }
+            Err(e) => {
+                let kind = failure(&e);
+                retry += 1;
+                if !kind.is_retryable() || retry >= config.attempts {
+                    return Err(e);
+                }
+                let delay = backoff(&config, retry, random_unit());
+                tracing::debug!(?kind, retry, delay_ms = delay.as_millis() as u64, "Retrying HID++ request");
+                std::thread::sleep(delay);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let config = HidppRetryConfig { backoff_ms: 50, max_backoff_ms: 500, jitter: 0.0, ..Default::default() };
+        let delays: Vec<_> = (1..=5).map(|r| backoff(&config, r, 0.5).as_millis()).collect();
+        assert_eq!(delays, [50, 100, 200, 400, 500]);
+        assert_eq!(backoff(&config, 60, 0.5), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_backoff_jitter_band() {
+        let config = HidppRetryConfig { backoff_ms: 100, max_backoff_ms: 500, jitter: 0.5, ..Default::default() };
+        assert_eq!(backoff(&config, 1, 0.0), Duration::from_millis(50));
+        assert_eq!(backoff(&config, 1, 1.0), Duration::from_millis(100));
+        assert_eq!(backoff(&config, 2, 0.5), Duration::from_millis(150));
+        assert!((0.0..1.0).contains(&random_unit()));
+    }
+
+    #[test]
+    fn test_run_retries_only_transient_failures() {
+        let mut calls = 0;
+        let result: Result<(), Failure> = run(
+            || {
+                calls += 1;
+                Err(Failure::Fatal)
+            },
+            |f| *f,
+        );
+        assert_eq!(result, Err(Failure::Fatal));
+        assert_eq!(calls, 1);
+
+        let mut calls = 0;
+        let result = run(
+            || {
+                calls += 1;
+                if calls < 2 { Err(Failure::Timeout) } else { Ok(calls) }
+            },
+            |f| *f,
+        );
+        assert_eq!(result, Ok(2));
+    }
+}
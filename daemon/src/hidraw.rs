@@ -4,16 +4,29 @@
 //! protocol), they send HID++ notifications instead of standard evdev events.
 //! This module reads those notifications from the hidraw device.
 //!
+//! The gesture button (and the haptic button) open the main menu. Further
+//! diverted buttons can open other menus of the profile (config.json's
+//! `button_menus`, see [`HidrawHandler::set_menu_buttons`]). A notification
+//! lists every diverted button held down, so the handler remembers which
+//! button opened the menu and releases it once that button is no longer
+//! listed.
+//!
 //! SPDX-License-Identifier: GPL-3.0
 
+use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::{self, Read};
 use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
 use std::path::PathBuf;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
-use crate::evdev::GestureEvent;
+use crate::config::ButtonMenuConfig;
+use crate::evdev::{
+    press_is_stale, EvdevHandler, GestureEvent, KeyState, STALE_PRESS_CHECK_INTERVAL_MS,
+    STALE_PRESS_TIMEOUT_SECS,
+};
 
 /// Logitech vendor ID
 pub const LOGITECH_VENDOR_ID: u16 = 0x046D;
@@ -55,6 +68,14 @@ pub struct HidrawHandler {
     device_path: Option<PathBuf>,
     /// Time when gesture button was pressed
     press_time: Option<Instant>,
+    /// CID of the button that opened the menu
+    pressed_cid: Option<u16>,
+    /// Buttons held down according to the latest diverted buttons notification
+    held_cids: Vec<u16>,
+    /// When the latest diverted buttons notification arrived
+    held_cids_at: Option<Instant>,
+    /// Extra buttons and the profile menu each opens
+    menu_buttons: HashMap<u16, String>,
     /// Device file handle
     device: Option<File>,
     /// Device index (for Bolt receiver, typically 0x02)
@@ -63,6 +84,10 @@ pub struct HidrawHandler {
     /// Feature index for REPROG_CONTROLS_V4 (discovered at runtime)
     /// Reserved for future HID++ feature discovery
     _reprog_feature_index: Option<u8>,
+    /// Press age after which the watchdog cross-checks evdev key state
+    stale_press_timeout: Duration,
+    /// Last time the stale-press watchdog ran
+    last_watchdog_check: Instant,
 }
 
 impl HidrawHandler {
@@ -72,10 +97,35 @@ impl HidrawHandler {
             event_tx,
             device_path: None,
             press_time: None,
+            pressed_cid: None,
+            held_cids: Vec::new(),
+            held_cids_at: None,
+            menu_buttons: HashMap::new(),
             device: None,
             _device_index: 0x02, // Default for Bolt receiver
             _reprog_feature_index: None,
+            stale_press_timeout: Duration::from_secs(STALE_PRESS_TIMEOUT_SECS),
+            last_watchdog_check: Instant::now(),
+        }
+    }
+
+    /// Set the press age after which the stale-press watchdog kicks in
+    pub fn set_stale_press_timeout(&mut self, timeout: Duration) {
+        self.stale_press_timeout = timeout;
+    }
+
+    /// Set the extra buttons that open profile menus (`button_menus`)
+    pub fn set_menu_buttons(&mut self, buttons: &[ButtonMenuConfig]) {
+        self.menu_buttons = buttons.iter().map(|b| (b.cid, b.menu.clone())).collect();
+    }
+
+    /// Which menu a CID opens: `Some(None)` for the main menu, None if the
+    /// button opens no menu
+    fn menu_for(&self, cid: u16) -> Option<Option<String>> {
+        if cid == button_cid::GESTURE_BUTTON || cid == button_cid::HAPTIC {
+            return Some(None);
         }
+        self.menu_buttons.get(&cid).map(|menu| Some(menu.clone()))
     }
 
     /// Find the Logitech hidraw device for HID++ button events
@@ -128,7 +178,7 @@ impl HidrawHandler {
         }
 
         // Sort by priority (highest first)
-        candidates.sort_by(|a, b| b.2.cmp(&a.2));
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.2));
 
         // Prefer interface 2 (input2) which is typically used for HID++ communication
         let max_priority = candidates.first().map(|(_, _, p)| *p).unwrap_or(0);
@@ -210,6 +260,7 @@ impl HidrawHandler {
                 }
                 Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
                     // No data available, sleep briefly and retry
+                    self.check_stale_press().await;
                     tokio::time::sleep(tokio::time::Duration::from_millis(1)).await;
                 }
                 Err(e) => {
@@ -233,6 +284,11 @@ impl HidrawHandler {
             return; // Not a HID++ report
         }
 
+        // Receiver notification that the mouse link was lost (or came back)
+        if crate::receiver_notifications::observe_report(data) {
+            return;
+        }
+
         let _device_index = data[1];
         let feature_index = data[2];
         let function_sw_id = data[3];
@@ -261,53 +317,47 @@ impl HidrawHandler {
             return;
         }
 
-        // HID++ REPROG_CONTROLS_V4 diverted button notification format:
-        // Byte 4-5: CID (Control ID) of the first pressed button (big endian)
-        // Byte 6: Additional info or second button CID high byte
-        // When no buttons are pressed, bytes 4-5 are 0x0000
-
-        // Parse button CID from bytes 4-5 (big endian)
-        let cid = ((data[4] as u16) << 8) | (data[5] as u16);
-
-        // A CID of 0 means all buttons released
-        let pressed = cid != 0;
+        let cids = pressed_cids(data);
+        self.held_cids.clone_from(&cids);
+        self.held_cids_at = Some(Instant::now());
 
         tracing::info!(
-            cid = cid,
-            pressed = pressed,
+            ?cids,
             raw_bytes = format!("{:02X} {:02X} {:02X}", data[4], data[5], data[6]),
             "Diverted button event"
         );
 
-        // Check if this is the gesture button OR haptic button (both can trigger radial menu)
-        if cid == button_cid::GESTURE_BUTTON || cid == button_cid::HAPTIC {
-            self.handle_gesture_button(true).await;
-        } else if cid == 0 {
-            // All buttons released - check if we had a gesture button press
-            if self.press_time.is_some() {
-                self.handle_gesture_button(false).await;
+        match self.pressed_cid {
+            // The button that opened the menu was let go
+            Some(active) if !cids.contains(&active) => self.handle_gesture_button(false, None).await,
+            Some(_) => {}
+            None => {
+                if let Some((cid, menu)) = cids.iter().find_map(|&cid| Some((cid, self.menu_for(cid)?))) {
+                    self.pressed_cid = Some(cid);
+                    self.handle_gesture_button(true, menu).await;
+                }
             }
         }
     }
 
     /// Handle gesture button press/release
-    async fn handle_gesture_button(&mut self, pressed: bool) {
+    ///
+    /// `menu` is the profile menu a `button_menus` button opens.
+    async fn handle_gesture_button(&mut self, pressed: bool, menu: Option<String>) {
         if pressed {
             // Button pressed
             self.press_time = Some(Instant::now());
 
-            // Trigger KWin script to get accurate cursor position and show menu
-            // This works correctly on Plasma 6 Wayland with multiple monitors
-            tracing::info!("Gesture button PRESSED - triggering KWin cursor query");
+            // Let the compositor open the main menu at the true cursor position
+            // (KWin script calls ShowMenuAtCursor), else query the cursor
+            let backend = crate::compositor::backend();
+            tracing::info!(backend = backend.name(), menu = ?menu, "Gesture button PRESSED");
 
-            if !Self::trigger_kwin_cursor_script() {
-                // Fallback to direct cursor query if KWin script fails
+            if menu.is_some() || !backend.show_overlay_hint() {
                 let (x, y) = Self::get_cursor_position();
-                tracing::warn!(x, y, "KWin script failed, using fallback cursor position");
-                let _ = self.event_tx.send(GestureEvent::Pressed { x, y }).await;
+                tracing::info!(x, y, "Cursor position from compositor backend");
+                let _ = self.event_tx.send(GestureEvent::Pressed { x, y, menu }).await;
             }
-            // If KWin script succeeded, it will call ShowMenuAtCursor via D-Bus
-            // which handles showing the menu with correct coordinates
         } else {
             // Button released
             let duration_ms = self
@@ -316,6 +366,7 @@ impl HidrawHandler {
                 .unwrap_or(0);
 
             self.press_time = None;
+            self.pressed_cid = None;
 
             tracing::info!(duration_ms, "Gesture button RELEASED");
 
@@ -323,104 +374,62 @@ impl HidrawHandler {
         }
     }
 
-    /// Get current cursor position (fallback method)
-    fn get_cursor_position() -> (i32, i32) {
-        let pos = crate::cursor::get_cursor_position();
-        (pos.x, pos.y)
-    }
-
-    /// Trigger KWin script to get cursor position and call ShowMenuAtCursor
+    /// Stale-press watchdog for dropped release notifications
     ///
-    /// This method works correctly on Plasma 6 Wayland with multiple monitors,
-    /// unlike xdotool/XWayland which clamps cursor to a single screen.
-    fn trigger_kwin_cursor_script() -> bool {
-        use std::process::Command;
-        use std::io::Write;
-        use tempfile::Builder;
-
-        // Create KWin script that calls ShowMenuAtCursor with true cursor position
-        let script = r#"
-var pos = workspace.cursorPos;
-callDBus("org.kde.juhradialmx", "/org/kde/juhradialmx/Daemon",
-         "org.kde.juhradialmx.Daemon", "ShowMenuAtCursor",
-         pos.x, pos.y);
-"#;
-
-        // Create a temporary file with .js suffix securely
-        let mut temp_file = match Builder::new().suffix(".js").tempfile() {
-            Ok(file) => file,
-            Err(e) => {
-                tracing::warn!("Failed to create temp file for KWin script: {}", e);
-                return false;
-            }
-        };
-
-        // Write script to temp file
-        if let Err(e) = write!(temp_file, "{}", script) {
-            tracing::warn!("Failed to write KWin script: {}", e);
-            return false;
+    /// If the gesture button has been "held" longer than the timeout, check
+    /// whether it really still is (see [`Self::stale_press_state`]) and force
+    /// a release only if it is confirmed up, so the overlay receives HideMenu.
+    async fn check_stale_press(&mut self) {
+        if self.last_watchdog_check.elapsed() < Duration::from_millis(STALE_PRESS_CHECK_INTERVAL_MS) {
+            return;
         }
+        self.last_watchdog_check = Instant::now();
 
-        // Get the path as a string
-        let script_path = temp_file.path().to_string_lossy();
-
-        // Load script via D-Bus
-        let load_result = Command::new("dbus-send")
-            .args([
-                "--session",
-                "--print-reply",
-                "--dest=org.kde.KWin",
-                "/Scripting",
-                "org.kde.kwin.Scripting.loadScript",
-                &format!("string:{}", script_path),
-            ])
-            .output();
-
-        let load_output = match load_result {
-            Ok(output) if output.status.success() => output,
-            _ => {
-                tracing::warn!("Failed to load KWin script");
-                return false;
-            }
-        };
-
-        // Parse script ID from output (looks like "int32 5")
-        let stdout = String::from_utf8_lossy(&load_output.stdout);
-        let script_id: Option<i32> = stdout
-            .lines()
-            .find(|line| line.contains("int32"))
-            .and_then(|line| line.split_whitespace().last())
-            .and_then(|s| s.parse().ok());
+        if !press_is_stale(self.press_time, self.stale_press_timeout) {
+            return;
+        }
+        let state = self.stale_press_state();
+        self.resolve_stale_press(state).await;
+    }
 
-        let script_id = match script_id {
-            Some(id) => id,
-            None => {
-                tracing::warn!("Failed to parse KWin script ID");
-                return false;
+    /// State of the button behind an open press
+    ///
+    /// A diverted button never reaches evdev, so its state comes from HID++
+    /// (see [`diverted_button_state`]). Presses without a CID fall back to
+    /// the evdev key state.
+    fn stale_press_state(&self) -> KeyState {
+        match self.pressed_cid {
+            Some(cid) => {
+                let report_age = self.held_cids_at.map(|t| t.elapsed()).unwrap_or(Duration::MAX);
+                diverted_button_state(
+                    &self.held_cids,
+                    cid,
+                    crate::receiver_notifications::is_reachable(),
+                    report_age,
+                    self.stale_press_timeout,
+                )
             }
-        };
+            None => EvdevHandler::is_gesture_key_held(),
+        }
+    }
 
-        // Run the script
-        let run_result = Command::new("dbus-send")
-            .args([
-                "--session",
-                "--print-reply",
-                "--dest=org.kde.KWin",
-                &format!("/Scripting/Script{}", script_id),
-                "org.kde.kwin.Script.run",
-            ])
-            .output();
-
-        match run_result {
-            Ok(output) if output.status.success() => {
-                tracing::debug!(script_id, "KWin cursor script triggered successfully");
-                true
-            }
-            _ => {
-                tracing::warn!("Failed to run KWin script");
-                false
-            }
+    /// Force-release a stale press if the button is confirmed up
+    async fn resolve_stale_press(&mut self, state: KeyState) {
+        if state != KeyState::Released {
+            tracing::debug!(?state, "Stale press kept, button not confirmed released");
+            return;
         }
+        tracing::warn!(
+            timeout_secs = self.stale_press_timeout.as_secs(),
+            "HID++ release notification lost - resetting stale press"
+        );
+        self.handle_gesture_button(false, None).await;
+    }
+
+    /// Get current cursor position (fallback method)
+    fn get_cursor_position() -> (i32, i32) {
+        let pos = crate::pointer_confinement::menu_position();
+        (pos.x, pos.y)
     }
 
     /// Check if handler is connected
@@ -429,6 +438,41 @@ callDBus("org.kde.juhradialmx", "/org/kde/juhradialmx/Daemon",
     }
 }
 
+/// CIDs of the buttons held down in a diverted buttons notification
+///
+/// REPROG_CONTROLS_V4 lists up to four CIDs (big endian) from byte 4,
+/// padded with zeros; an all-zero list means every button was released.
+/// Short reports only have room for the first.
+pub fn pressed_cids(data: &[u8]) -> Vec<u16> {
+    data.get(4..data.len().min(12))
+        .unwrap_or_default()
+        .chunks_exact(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .filter(|&cid| cid != 0)
+        .collect()
+}
+
+/// State of a diverted button from HID++
+///
+/// `held_cids` is the latest diverted buttons notification, received
+/// `report_age` ago. A lost release leaves the press as the latest
+/// notification, so once that is older than `timeout` the release is taken
+/// as lost; the same goes while the link is down, when no notification can
+/// arrive at all.
+pub fn diverted_button_state(
+    held_cids: &[u16],
+    cid: u16,
+    reachable: bool,
+    report_age: Duration,
+    timeout: Duration,
+) -> KeyState {
+    if !reachable || !held_cids.contains(&cid) || report_age >= timeout {
+        KeyState::Released
+    } else {
+        KeyState::Held
+    }
+}
+
 /// Hidraw error type
 #[derive(Debug)]
 pub enum HidrawError {
@@ -455,6 +499,34 @@ impl std::fmt::Display for HidrawError {
 
 impl std::error::Error for HidrawError {}
 
+/// Wait until `device` has a report to read or `deadline` passes
+///
+/// Returns false on timeout. The thread sleeps in poll(2), so a slow
+/// response costs no CPU; errors and hangups report ready and surface
+/// from the next read.
+pub fn wait_readable(device: &impl AsRawFd, deadline: Instant) -> io::Result<bool> {
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(false);
+        }
+        // Round up so the wait doesn't end just short of the deadline
+        let timeout_ms = remaining.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32;
+        let mut pollfd = libc::pollfd { fd: device.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        // SAFETY: `pollfd` is a single valid entry for the duration of the call
+        match unsafe { libc::poll(&mut pollfd, 1, timeout_ms) } {
+            -1 => {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+            0 => {}
+            _ => return Ok(true),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -472,4 +544,124 @@ mod tests {
         assert_eq!(HIDPP_SHORT, 0x10);
         assert_eq!(HIDPP_LONG, 0x11);
     }
+
+    #[test]
+    fn test_wait_readable() {
+        use std::io::Write;
+
+        let (mut writer, reader) = std::os::unix::net::UnixStream::pair().unwrap();
+        let started = Instant::now();
+        assert!(!wait_readable(&reader, started + Duration::from_millis(20)).unwrap());
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert!(!wait_readable(&reader, started).unwrap());
+
+        writer.write_all(&[HIDPP_SHORT]).unwrap();
+        assert!(wait_readable(&reader, Instant::now() + Duration::from_secs(1)).unwrap());
+    }
+
+    #[test]
+    fn test_pressed_cids() {
+        let report = [HIDPP_LONG, 0x02, 0x08, 0x00, 0x00, 0xC3, 0x00, 0xC4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(pressed_cids(&report), vec![button_cid::GESTURE_BUTTON, button_cid::SMART_SHIFT]);
+        assert!(pressed_cids(&[HIDPP_LONG, 0x02, 0x08, 0x00, 0, 0, 0, 0, 0, 0, 0, 0]).is_empty());
+        assert_eq!(pressed_cids(&[HIDPP_SHORT, 0x02, 0x08, 0x00, 0x00, 0xC4, 0x00]), vec![button_cid::SMART_SHIFT]);
+    }
+
+    #[tokio::test]
+    async fn test_menu_button_press_carries_menu() {
+        let (tx, mut rx) = mpsc::channel::<GestureEvent>(8);
+        let mut handler = HidrawHandler::new(tx);
+        handler.set_menu_buttons(&[ButtonMenuConfig { cid: button_cid::SMART_SHIFT, menu: "media".to_string() }]);
+
+        let report = |cids: [u16; 2]| {
+            let [a, b] = cids.map(u16::to_be_bytes);
+            [HIDPP_LONG, 0x02, 0x08, 0x00, a[0], a[1], b[0], b[1], 0, 0, 0, 0]
+        };
+
+        // Buttons that open no menu are ignored
+        handler.handle_button_event(&report([button_cid::BACK_BUTTON, 0])).await;
+        assert!(rx.try_recv().is_err());
+
+        handler.handle_button_event(&report([button_cid::SMART_SHIFT, 0])).await;
+        assert!(matches!(rx.try_recv(), Ok(GestureEvent::Pressed { menu: Some(m), .. }) if m == "media"));
+
+        // The gesture button joining in neither reopens nor releases
+        handler.handle_button_event(&report([button_cid::SMART_SHIFT, button_cid::GESTURE_BUTTON])).await;
+        assert!(rx.try_recv().is_err());
+
+        // Letting go of the menu button releases even while the other is held
+        handler.handle_button_event(&report([button_cid::GESTURE_BUTTON, 0])).await;
+        assert!(matches!(rx.try_recv(), Ok(GestureEvent::Released { .. })));
+        assert!(handler.pressed_cid.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stale_press_reset_emits_release() {
+        let (tx, mut rx) = mpsc::channel::<GestureEvent>(8);
+        let mut handler = HidrawHandler::new(tx);
+
+        // A press whose release notification was dropped
+        handler.press_time = Some(Instant::now() - Duration::from_secs(11));
+        handler.pressed_cid = Some(button_cid::GESTURE_BUTTON);
+
+        handler.resolve_stale_press(KeyState::Unknown).await;
+        assert!(handler.press_time.is_some());
+        assert!(rx.try_recv().is_err());
+
+        handler.resolve_stale_press(KeyState::Released).await;
+        assert!(handler.press_time.is_none());
+        assert!(matches!(rx.try_recv(), Ok(GestureEvent::Released { .. })));
+    }
+
+    #[test]
+    fn test_diverted_button_state() {
+        let held = [button_cid::GESTURE_BUTTON];
+        let fresh = Duration::from_secs(1);
+        let timeout = Duration::from_secs(10);
+        let state = |held: &[u16], reachable, age| diverted_button_state(held, button_cid::GESTURE_BUTTON, reachable, age, timeout);
+        assert_eq!(state(&held, true, fresh), KeyState::Held);
+        assert_eq!(state(&[], true, fresh), KeyState::Released);
+        // No notification can arrive while the link is down
+        assert_eq!(state(&held, false, fresh), KeyState::Released);
+        // The press is still the latest notification long after the timeout
+        assert_eq!(state(&held, true, timeout), KeyState::Released);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_diverted_release_reset() {
+        let (tx, mut rx) = mpsc::channel::<GestureEvent>(8);
+        let mut handler = HidrawHandler::new(tx);
+        handler.set_stale_press_timeout(Duration::from_millis(10));
+
+        // Press notification received, release notification dropped
+        let report = [HIDPP_LONG, 0x02, 0x08, 0x00, 0x00, 0xC3, 0, 0, 0, 0, 0, 0];
+        handler.handle_button_event(&report).await;
+        assert_eq!(handler.pressed_cid, Some(button_cid::GESTURE_BUTTON));
+        // Pressed is skipped when the compositor opens the menu itself (KWin)
+        let _ = rx.try_recv();
+        handler.press_time = Some(Instant::now() - Duration::from_secs(1));
+        handler.held_cids_at = Some(Instant::now() - Duration::from_secs(1));
+
+        let report_age = handler.held_cids_at.unwrap().elapsed();
+        let state = diverted_button_state(&handler.held_cids, button_cid::GESTURE_BUTTON, true, report_age, handler.stale_press_timeout);
+        assert_eq!(state, KeyState::Released);
+        handler.resolve_stale_press(state).await;
+
+        assert!(handler.press_time.is_none());
+        assert!(matches!(rx.try_recv(), Ok(GestureEvent::Released { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_fresh_press_not_reset() {
+        let (tx, mut rx) = mpsc::channel::<GestureEvent>(8);
+        let mut handler = HidrawHandler::new(tx);
+
+        handler.press_time = Some(Instant::now());
+        handler.last_watchdog_check = Instant::now() - Duration::from_secs(2);
+
+        handler.check_stale_press().await;
+
+        assert!(handler.press_time.is_some());
+        assert!(rx.try_recv().is_err());
+    }
 }
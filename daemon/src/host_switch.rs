@@ -0,0 +1,22 @@
+//! Easy-Switch host-switch detection
+//!
+//! When the mouse Easy-Switches to another computer the receiver stays
+//! plugged in, but every HID++ request to the mouse fails or times out. The
+//! receiver announces the change with a HID++ 1.0 Device Connection
+//! notification (sub ID 0x41) whose link-not-established bit is set; a
+//! second notification with the bit cleared follows when the mouse returns.
+//!
+//! While the link is down, haptics and battery polling are paused instead
+//! of failing repeatedly (no timeouts, no log spam). The HID++ handle stays
+//! open, so everything resumes the moment the return notification arrives.
+//!
+//! The notifications are parsed and tracked by
+//! [`crate::receiver_notifications`]; the same pause applies when the mouse
+//! is switched off, out of range or unpaired.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+/// Whether HID++ traffic to the mouse is paused
+pub fn is_paused() -> bool {
+    !crate::receiver_notifications::is_reachable()
+}
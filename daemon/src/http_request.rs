@@ -0,0 +1,281 @@
+//! HTTP requests for `http_request` actions (Home Assistant webhooks, REST)
+//!
+//! ```json
+//! {"type": "http_request", "value": {
+//!   "method": "POST",
+//!   "url": "http://homeassistant.local:8123/api/services/scene/turn_on",
+//!   "headers": {"Authorization": "Bearer {secret:home_assistant}"},
+//!   "body": "{\"entity_id\": \"scene.movie_night\"}"}}
+//! ```
+//!
+//! `{secret:name}` in the URL, header values and body is replaced by
+//! `name` from `secrets.json` next to `config.json`, a flat
+//! `{"name": "value"}` object. That file must not be readable by group or
+//! others (`chmod 600`); the daemon refuses to use it otherwise, so tokens
+//! stay out of profiles that get shared or synced.
+//!
+//! The request is made by `curl`, which gets its whole configuration on
+//! stdin, so neither the URL nor the headers show up in the process list.
+//! It is aborted after the action's `timeout_secs` (default
+//! [`DEFAULT_TIMEOUT_SECS`]); any 2xx status counts as success.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::BTreeMap;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+/// Secrets file name, in the config directory
+pub const SECRETS_FILE: &str = "secrets.json";
+
+/// Request timeout when the action sets none (seconds)
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+/// `http_request` action payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpRequest {
+    /// HTTP method
+    #[serde(default = "default_method")]
+    pub method: String,
+    /// Request URL (http or https)
+    pub url: String,
+    /// Extra request headers
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Request body, sent verbatim
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Text for `{query}` / `{query_url}` from a text entry, filled into the
+    /// URL and body only after the secrets (see [`crate::text_entry`])
+    #[serde(skip)]
+    pub query: Option<String>,
+}
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+/// HTTP request failure
+#[derive(Debug)]
+pub enum HttpRequestError {
+    /// Bad method, URL scheme or header name
+    Invalid(String),
+    /// `{secret:name}` with no such secret
+    UnknownSecret(String),
+    /// secrets.json is missing, unreadable, malformed or too open
+    Secrets(String),
+    /// curl could not be run or the transfer failed
+    Transfer(String),
+    /// The server answered with a non-2xx status
+    Status(u16),
+}
+
+impl std::fmt::Display for HttpRequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpRequestError::Invalid(msg) => write!(f, "Invalid HTTP request: {}", msg),
+            HttpRequestError::UnknownSecret(name) => write!(f, "Unknown secret: {}", name),
+            HttpRequestError::Secrets(msg) => write!(f, "Secrets file: {}", msg),
+            HttpRequestError::Transfer(msg) => write!(f, "HTTP request failed: {}", msg),
+            HttpRequestError::Status(code) => write!(f, "HTTP request returned status {}", code),
+        }
+    }
+}
+
+impl std::error::Error for HttpRequestError {}
+
+/// Path of the secrets file
+pub fn secrets_path() -> PathBuf {
+    crate::profiles::get_config_dir().join(SECRETS_FILE)
+}
+
+/// Load secrets, refusing files readable by group or others
+pub fn load_secrets(path: &Path) -> Result<BTreeMap<String, String>, HttpRequestError> {
+    let metadata = std::fs::metadata(path).map_err(|e| HttpRequestError::Secrets(format!("{}: {}", path.display(), e)))?;
+    if metadata.permissions().mode() & 0o077 != 0 {
+        return Err(HttpRequestError::Secrets(format!(
+            "{} is readable by other users, run chmod 600 on it",
+            path.display()
+        )));
+    }
+    let contents = std::fs::read_to_string(path).map_err(|e| HttpRequestError::Secrets(e.to_string()))?;
+    serde_json::from_str(&contents).map_err(|e| HttpRequestError::Secrets(e.to_string()))
+}
+
+/// Replace `{secret:name}` placeholders; secrets are only loaded if needed
+fn fill_secrets(
+    template: &str,
+    secrets: &mut Option<BTreeMap<String, String>>,
+    path: &Path,
+) -> Result<String, HttpRequestError> {
+    const OPEN: &str = "{secret:";
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(OPEN) {
+        let Some(len) = rest[start + OPEN.len()..].find('}') else {
+            break;
+        };
+        let name = &rest[start + OPEN.len()..start + OPEN.len() + len];
+        if secrets.is_none() {
+            *secrets = Some(load_secrets(path)?);
+        }
+        let value = secrets
+            .as_ref()
+            .and_then(|s| s.get(name))
+            .ok_or_else(|| HttpRequestError::UnknownSecret(name.to_string()))?;
+        out.push_str(&rest[..start]);
+        out.push_str(value);
+        rest = &rest[start + OPEN.len() + len + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// curl configuration (fed on stdin) for a request with secrets filled in
+pub fn curl_config(request: &HttpRequest, timeout: Duration, secrets_file: &Path) -> Result<String, HttpRequestError> {
+    let method = request.method.to_ascii_uppercase();
+    if method.is_empty() || !method.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(HttpRequestError::Invalid(format!("method {:?}", request.method)));
+    }
+    let mut secrets = None;
+    // Typed text goes in last so it can never name a secret
+    let with_query = |filled: String| match &request.query {
+        Some(query) => crate::text_entry::fill_query(&filled, query),
+        None => filled,
+    };
+    let url = with_query(fill_secrets(&request.url, &mut secrets, secrets_file)?);
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(HttpRequestError::Invalid("URL must start with http:// or https://".to_string()));
+    }
+
+    let mut config = format!(
+        "silent\nshow-error\noutput = \"/dev/null\"\nwrite-out = \"%{{http_code}}\"\nmax-time = {}\nrequest = {}\nurl = {}\n",
+        timeout.as_secs().max(1),
+        curl_quote(&method),
+        curl_quote(&url)
+    );
+    for (name, value) in &request.headers {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(HttpRequestError::Invalid(format!("header name {:?}", name)));
+        }
+        let value = fill_secrets(value, &mut secrets, secrets_file)?;
+        config.push_str(&format!("header = {}\n", curl_quote(&format!("{}: {}", name, value))));
+    }
+    if let Some(body) = &request.body {
+        // data-raw: a leading '@' is not a file name
+        let body = with_query(fill_secrets(body, &mut secrets, secrets_file)?);
+        config.push_str(&format!("data-raw = {}\n", curl_quote(&body)));
+    }
+    Ok(config)
+}
+
+/// Double-quoted curl config value
+fn curl_quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Make the request; returns the HTTP status
+pub async fn send(request: &HttpRequest, timeout_secs: Option<u64>) -> Result<u16, HttpRequestError> {
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+    let config = curl_config(request, timeout, &secrets_path())?;
+    tracing::info!(method = %request.method, url = %request.url, "Sending HTTP request");
+
+    let mut child = tokio::process::Command::new("curl")
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| HttpRequestError::Transfer(format!("could not run curl: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes()).await.map_err(|e| HttpRequestError::Transfer(e.to_string()))?;
+    }
+    // curl enforces max-time itself; this only guards against a hung curl
+    let output = tokio::time::timeout(timeout + Duration::from_secs(2), child.wait_with_output())
+        .await
+        .map_err(|_| HttpRequestError::Transfer("timed out".to_string()))?
+        .map_err(|e| HttpRequestError::Transfer(e.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(HttpRequestError::Transfer(stderr.trim().to_string()));
+    }
+
+    let status: u16 = String::from_utf8_lossy(&output.stdout).trim().parse().unwrap_or(0);
+    if !(200..300).contains(&status) {
+        return Err(HttpRequestError::Status(status));
+    }
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(json: &str) -> HttpRequest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_curl_config_fills_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let secrets = dir.path().join(SECRETS_FILE);
+        std::fs::write(&secrets, r#"{"ha": "tok\"en"}"#).unwrap();
+        std::fs::set_permissions(&secrets, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+        let scene = request(
+            r#"{"url": "https://ha.local/api", "headers": {"Authorization": "Bearer {secret:ha}"}, "body": "@{\"a\": 1}"}"#,
+        );
+        assert_eq!(
+            curl_config(&scene, Duration::from_secs(5), &secrets).unwrap(),
+            "silent\nshow-error\noutput = \"/dev/null\"\nwrite-out = \"%{http_code}\"\nmax-time = 5\n\
+             request = \"POST\"\nurl = \"https://ha.local/api\"\n\
+             header = \"Authorization: Bearer tok\\\"en\"\ndata-raw = \"@{\\\"a\\\": 1}\"\n"
+        );
+
+        let unknown = request(r#"{"url": "http://x/{secret:nope}"}"#);
+        assert!(matches!(
+            curl_config(&unknown, Duration::from_secs(5), &secrets),
+            Err(HttpRequestError::UnknownSecret(name)) if name == "nope"
+        ));
+        assert!(matches!(
+            curl_config(&request(r#"{"url": "file:///etc/passwd"}"#), Duration::from_secs(5), &secrets),
+            Err(HttpRequestError::Invalid(_))
+        ));
+
+        std::fs::set_permissions(&secrets, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(matches!(load_secrets(&secrets), Err(HttpRequestError::Secrets(_))));
+    }
+
+    #[test]
+    fn test_query_cannot_name_a_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let secrets = dir.path().join(SECRETS_FILE);
+        std::fs::write(&secrets, r#"{"ha": "token"}"#).unwrap();
+        std::fs::set_permissions(&secrets, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+        let mut search = request(r#"{"url": "https://x.org/?key={secret:ha}&q={query_url}", "body": "{query}"}"#);
+        search.query = Some("{secret:ha}".to_string());
+        let config = curl_config(&search, Duration::from_secs(5), &secrets).unwrap();
+        assert!(config.contains("url = \"https://x.org/?key=token&q=%7Bsecret%3Aha%7D\"\n"));
+        assert!(config.contains("data-raw = \"{secret:ha}\"\n"));
+    }
+}
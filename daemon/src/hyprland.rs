@@ -0,0 +1,211 @@
+//! Hyprland dispatchers for `hyprland_dispatch` actions
+//!
+//! ```json
+//! {"type": "hyprland_dispatch", "value": "workspace e+1"}
+//! {"type": "hyprland_dispatch", "value": "movetoworkspace special"}
+//! ```
+//!
+//! The command is written to Hyprland's request socket
+//! (`$XDG_RUNTIME_DIR/hypr/$HYPRLAND_INSTANCE_SIGNATURE/.socket.sock`) the
+//! same way `hyprctl dispatch` does, without spawning a process per slice.
+//!
+//! Only window, workspace, group and layout dispatchers are accepted
+//! ([`ALLOWED_DISPATCHERS`]). `exec`, `exit`, `pass` and the like are
+//! refused, as are batch separators, so a profile cannot run programs or
+//! end the session through this action; use a `command` slice for that.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+/// Dispatchers a `hyprland_dispatch` action may use
+pub const ALLOWED_DISPATCHERS: &[&str] = &[
+    // Windows
+    "killactive",
+    "closewindow",
+    "togglefloating",
+    "setfloating",
+    "settiled",
+    "fullscreen",
+    "fakefullscreen",
+    "fullscreenstate",
+    "pin",
+    "centerwindow",
+    "pseudo",
+    "movefocus",
+    "movewindow",
+    "swapwindow",
+    "resizeactive",
+    "moveactive",
+    "cyclenext",
+    "swapnext",
+    "focuswindow",
+    "focusurgentorlast",
+    "focuscurrentorlast",
+    "bringactivetotop",
+    "alterzorder",
+    "tagwindow",
+    "togglesplit",
+    "swapsplit",
+    "splitratio",
+    // Workspaces and monitors
+    "workspace",
+    "movetoworkspace",
+    "movetoworkspacesilent",
+    "togglespecialworkspace",
+    "focusworkspaceoncurrentmonitor",
+    "renameworkspace",
+    "focusmonitor",
+    "movecurrentworkspacetomonitor",
+    "moveworkspacetomonitor",
+    "swapactiveworkspaces",
+    // Groups
+    "togglegroup",
+    "changegroupactive",
+    "lockgroups",
+    "lockactivegroup",
+    "moveintogroup",
+    "moveoutofgroup",
+    "movewindoworgroup",
+    "movegroupwindow",
+    "denywindowfromgroup",
+    // Layouts
+    "layoutmsg",
+];
+
+/// How long to wait for Hyprland's answer
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Hyprland dispatch failure
+#[derive(Debug)]
+pub enum HyprlandError {
+    /// Not running under Hyprland (no instance signature)
+    NotRunning,
+    /// Empty command, unknown or refused dispatcher, or batch syntax
+    Disallowed(String),
+    /// Talking to the socket failed
+    Io(std::io::Error),
+    /// Hyprland answered with an error
+    Rejected(String),
+    /// No answer in time
+    Timeout,
+}
+
+impl std::fmt::Display for HyprlandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HyprlandError::NotRunning => write!(f, "Hyprland is not running (HYPRLAND_INSTANCE_SIGNATURE unset)"),
+            HyprlandError::Disallowed(msg) => write!(f, "Dispatcher not allowed: {}", msg),
+            HyprlandError::Io(e) => write!(f, "Could not reach Hyprland: {}", e),
+            HyprlandError::Rejected(reply) => write!(f, "Hyprland rejected the dispatch: {}", reply),
+            HyprlandError::Timeout => write!(f, "Hyprland did not answer in time"),
+        }
+    }
+}
+
+impl std::error::Error for HyprlandError {}
+
+impl From<std::io::Error> for HyprlandError {
+    fn from(e: std::io::Error) -> Self {
+        HyprlandError::Io(e)
+    }
+}
+
+/// Check a dispatch command; returns it normalized (`dispatcher args`)
+pub fn validate(command: &str) -> Result<String, HyprlandError> {
+    let command = command.trim();
+    if command.contains([';', '\n', '\r']) || command.contains("[[BATCH]]") {
+        return Err(HyprlandError::Disallowed(format!("batch syntax in \"{}\"", command)));
+    }
+    let (dispatcher, args) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+    if dispatcher.is_empty() {
+        return Err(HyprlandError::Disallowed("empty command".to_string()));
+    }
+    if !ALLOWED_DISPATCHERS.contains(&dispatcher) {
+        return Err(HyprlandError::Disallowed(dispatcher.to_string()));
+    }
+    let args = args.trim();
+    Ok(if args.is_empty() { dispatcher.to_string() } else { format!("{} {}", dispatcher, args) })
+}
+
+/// Request socket of the running Hyprland instance
+pub fn socket_path() -> Option<PathBuf> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok().filter(|s| !s.is_empty())?;
+    let runtime = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/run/user/1000".to_string());
+    let path = PathBuf::from(runtime).join("hypr").join(&signature).join(".socket.sock");
+    if path.exists() {
+        return Some(path);
+    }
+    // Hyprland before 0.40 kept its sockets in /tmp
+    Some(PathBuf::from("/tmp/hypr").join(signature).join(".socket.sock"))
+}
+
+/// Run a dispatcher (`dispatch <command>` on the request socket)
+pub async fn dispatch(command: &str) -> Result<(), HyprlandError> {
+    let command = validate(command)?;
+    let path = socket_path().ok_or(HyprlandError::NotRunning)?;
+    dispatch_to(&path, &command).await
+}
+
+/// Send a validated command to the socket at `path`
+async fn dispatch_to(path: &Path, command: &str) -> Result<(), HyprlandError> {
+    let exchange = async {
+        let mut stream = UnixStream::connect(path).await?;
+        stream.write_all(format!("dispatch {}", command).as_bytes()).await?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).await?;
+        Ok::<_, std::io::Error>(reply)
+    };
+    let reply = tokio::time::timeout(REPLY_TIMEOUT, exchange)
+        .await
+        .map_err(|_| HyprlandError::Timeout)??;
+
+    let reply = reply.trim();
+    if reply != "ok" {
+        return Err(HyprlandError::Rejected(reply.to_string()));
+    }
+    tracing::info!(command, "Hyprland dispatch");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_allowed_dispatchers() {
+        assert_eq!(validate("  workspace   e+1 ").unwrap(), "workspace e+1");
+        assert_eq!(validate("killactive").unwrap(), "killactive");
+        assert_eq!(validate("movetoworkspacesilent special:scratch").unwrap(), "movetoworkspacesilent special:scratch");
+    }
+
+    #[test]
+    fn test_validate_refuses_other_dispatchers() {
+        assert!(matches!(validate("exec kitty"), Err(HyprlandError::Disallowed(d)) if d == "exec"));
+        assert!(matches!(validate("exit"), Err(HyprlandError::Disallowed(_))));
+        assert!(matches!(validate(""), Err(HyprlandError::Disallowed(_))));
+        assert!(matches!(validate("workspace 1; dispatch exec kitty"), Err(HyprlandError::Disallowed(_))));
+        assert!(matches!(validate("[[BATCH]]workspace 1"), Err(HyprlandError::Disallowed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_over_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join(".socket.sock");
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 64];
+            let n = stream.read(&mut request).await.unwrap();
+            stream.write_all(b"ok").await.unwrap();
+            String::from_utf8(request[..n].to_vec()).unwrap()
+        });
+
+        dispatch_to(&socket, "workspace 3").await.unwrap();
+        assert_eq!(server.await.unwrap(), "dispatch workspace 3");
+    }
+}
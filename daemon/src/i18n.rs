@@ -0,0 +1,353 @@
+//! Translations for daemon-generated text
+//!
+//! Built-in action labels, dynamic slice labels, notifications and the
+//! `juhradialctl setup` prompts are translated with the same gettext
+//! catalogs as the overlay (`juhradial.mo` under
+//! `/usr/share/juhradial/locales/<lang>/LC_MESSAGES/`). The language comes
+//! from `language` in config.json; "system" follows `LANGUAGE`, `LC_ALL`,
+//! `LC_MESSAGES` and `LANG` like gettext does. Untranslated text stays English.
+//!
+//! Labels stored in profiles.json are English msgids ("Copy"), so the
+//! default slices follow the language without rewriting the file. A
+//! profile's `labels` map overrides individual labels.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// gettext domain shared with the overlay
+pub const DOMAIN: &str = "juhradial";
+
+/// Installed catalog directory
+const INSTALLED_LOCALE_DIR: &str = "/usr/share/juhradial/locales";
+
+/// Environment variable overriding the catalog directory (development)
+const LOCALE_DIR_ENV: &str = "JUHRADIAL_LOCALE_DIR";
+
+/// `language` value that follows the environment
+pub const SYSTEM_LANGUAGE: &str = "system";
+
+/// MO file magic number (native byte order of the writer)
+const MO_MAGIC: u32 = 0x9504_12de;
+
+/// Messages of one language
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catalog {
+    /// Language the messages were loaded for (empty = English)
+    pub language: String,
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Catalog with the given msgid -> translation pairs
+    pub fn from_messages(language: &str, messages: HashMap<String, String>) -> Self {
+        Self { language: language.to_string(), messages }
+    }
+
+    /// Translation of `msgid`, or `msgid` itself
+    pub fn get<'a>(&'a self, msgid: &'a str) -> &'a str {
+        self.messages.get(msgid).map(String::as_str).unwrap_or(msgid)
+    }
+
+    /// Number of translated messages
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Whether nothing is translated
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+/// Active catalog (None = English)
+static CATALOG: RwLock<Option<Arc<Catalog>>> = RwLock::new(None);
+
+/// Switch to `language` ("system" or empty = environment)
+///
+/// Called at startup and whenever config.json is reloaded.
+pub fn set_language(language: &str) {
+    let catalog = load(&locale_dir(), language);
+    tracing::info!(
+        language = %language,
+        resolved = %catalog.language,
+        messages = catalog.len(),
+        "Translations loaded"
+    );
+    if let Ok(mut active) = CATALOG.write() {
+        *active = (!catalog.is_empty()).then(|| Arc::new(catalog));
+    }
+}
+
+fn active() -> Option<Arc<Catalog>> {
+    CATALOG.read().ok().and_then(|c| c.clone())
+}
+
+/// Translate `msgid`
+pub fn tr(msgid: &str) -> String {
+    match active() {
+        Some(catalog) => catalog.get(msgid).to_string(),
+        None => msgid.to_string(),
+    }
+}
+
+/// Translate `msgid` and fill its `{}` placeholders in order
+pub fn tr_fmt(msgid: &str, args: &[&str]) -> String {
+    fill(&tr(msgid), args)
+}
+
+/// Replace each `{}` in `template` with the next argument
+pub fn fill(template: &str, args: &[&str]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(pos) = rest.find("{}") {
+        out.push_str(&rest[..pos]);
+        out.push_str(args.next().copied().unwrap_or("{}"));
+        rest = &rest[pos + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Display text for a slice label
+///
+/// A profile override (keyed by the label as written in profiles.json) wins;
+/// otherwise the label is looked up as a msgid.
+pub fn localize_label(label: &str, overrides: &HashMap<String, String>) -> String {
+    match overrides.get(label) {
+        Some(text) => text.clone(),
+        None => tr(label),
+    }
+}
+
+/// Catalog directory (`$JUHRADIAL_LOCALE_DIR` or the installed one)
+pub fn locale_dir() -> PathBuf {
+    std::env::var_os(LOCALE_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(INSTALLED_LOCALE_DIR))
+}
+
+/// Load the best catalog in `dir` for `language`; empty if none matches
+pub fn load(dir: &Path, language: &str) -> Catalog {
+    let languages = match language {
+        "" | SYSTEM_LANGUAGE => system_languages(|name| std::env::var(name).ok()),
+        language => vec![language.to_string()],
+    };
+    for candidate in languages.iter().flat_map(|l| fallbacks(l)) {
+        let path = dir.join(&candidate).join("LC_MESSAGES").join(format!("{}.mo", DOMAIN));
+        let Ok(data) = fs::read(&path) else { continue };
+        match parse_mo(&data) {
+            Some(messages) => return Catalog::from_messages(&candidate, messages),
+            None => tracing::warn!(path = %path.display(), "Ignoring malformed translation catalog"),
+        }
+    }
+    Catalog::default()
+}
+
+/// Languages requested by the environment, most preferred first
+///
+/// `LANGUAGE` (a colon-separated list) comes first, then the first set of
+/// `LC_ALL`, `LC_MESSAGES` and `LANG`. The C/POSIX locale means English.
+pub fn system_languages(var: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| var(name))
+        .find(|value| !value.is_empty());
+    if locale.as_deref().map(normalize).is_some_and(|l| l == "C" || l == "POSIX") {
+        return Vec::new();
+    }
+
+    let mut languages: Vec<String> = var("LANGUAGE")
+        .unwrap_or_default()
+        .split(':')
+        .map(normalize)
+        .filter(|l| !l.is_empty())
+        .collect();
+    if let Some(locale) = locale {
+        languages.push(normalize(&locale));
+    }
+    languages.retain(|l| l != "C" && l != "POSIX");
+    languages.dedup();
+    languages
+}
+
+/// Strip the encoding and modifier: "de_DE.UTF-8@euro" -> "de_DE"
+fn normalize(locale: &str) -> String {
+    locale.split(['.', '@']).next().unwrap_or_default().to_string()
+}
+
+/// "pt_BR" -> ["pt_BR", "pt"]
+fn fallbacks(language: &str) -> Vec<String> {
+    let mut candidates = vec![language.to_string()];
+    if let Some((base, _)) = language.split_once('_') {
+        candidates.push(base.to_string());
+    }
+    candidates
+}
+
+/// Parse a GNU MO file into msgid -> translation
+///
+/// The header entry and untranslated messages are skipped; for plural
+/// entries the singular msgid maps to the first form.
+pub fn parse_mo(data: &[u8]) -> Option<HashMap<String, String>> {
+    let word = |offset: usize, big_endian: bool| -> Option<u32> {
+        let bytes: [u8; 4] = data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) })
+    };
+    let big_endian = match word(0, false)? {
+        MO_MAGIC => false,
+        magic if magic.swap_bytes() == MO_MAGIC => true,
+        _ => return None,
+    };
+    let count = word(8, big_endian)? as usize;
+    let originals = word(12, big_endian)? as usize;
+    let translations = word(16, big_endian)? as usize;
+
+    // Both tables have to fit before `count` sizes anything
+    let table_len = count.checked_mul(8)?;
+    if originals.checked_add(table_len)? > data.len() || translations.checked_add(table_len)? > data.len() {
+        return None;
+    }
+
+    let string = |table: usize, index: usize| -> Option<&str> {
+        let length = word(table + index * 8, big_endian)? as usize;
+        let offset = word(table + index * 8 + 4, big_endian)? as usize;
+        std::str::from_utf8(data.get(offset..offset.checked_add(length)?)?).ok()
+    };
+
+    let mut messages = HashMap::with_capacity(count);
+    for index in 0..count {
+        let msgid = string(originals, index)?;
+        let msgstr = string(translations, index)?;
+        let msgid = msgid.split('\0').next().unwrap_or_default();
+        let msgstr = msgstr.split('\0').next().unwrap_or_default();
+        if !msgid.is_empty() && !msgstr.is_empty() {
+            messages.insert(msgid.to_string(), msgstr.to_string());
+        }
+    }
+    Some(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little-endian MO file with the given entries (sorted like msgfmt)
+    fn build_mo(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut entries = entries.to_vec();
+        entries.sort();
+        let header = 28;
+        let originals = header;
+        let translations = originals + entries.len() * 8;
+        let mut strings_at = translations + entries.len() * 8;
+        let mut tables = Vec::new();
+        let mut strings = Vec::new();
+        for column in [0, 1] {
+            for entry in &entries {
+                let text = if column == 0 { entry.0 } else { entry.1 };
+                tables.extend((text.len() as u32).to_le_bytes());
+                tables.extend((strings_at as u32).to_le_bytes());
+                strings.extend(text.as_bytes());
+                strings.push(0);
+                strings_at += text.len() + 1;
+            }
+        }
+        let mut data = Vec::new();
+        for word in [MO_MAGIC, 0, entries.len() as u32, originals as u32, translations as u32, 0, 0] {
+            data.extend(word.to_le_bytes());
+        }
+        data.extend(tables);
+        data.extend(strings);
+        data
+    }
+
+    #[test]
+    fn test_parse_mo() {
+        let data = build_mo(&[
+            ("", "Content-Type: text/plain; charset=UTF-8\n"),
+            ("Copy", "Kopieren"),
+            ("Profile: {}", "Profil: {}"),
+            ("Slice\0Slices", "Segment\0Segmente"),
+            ("Untranslated", ""),
+        ]);
+        let messages = parse_mo(&data).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages["Copy"], "Kopieren");
+        assert_eq!(messages["Slice"], "Segment");
+        assert!(!messages.contains_key(""));
+        assert!(!messages.contains_key("Untranslated"));
+    }
+
+    #[test]
+    fn test_parse_mo_rejects_garbage() {
+        assert!(parse_mo(b"not a catalog").is_none());
+        let mut truncated = build_mo(&[("Copy", "Kopieren")]);
+        truncated.truncate(40);
+        assert!(parse_mo(&truncated).is_none());
+
+        // A huge string count in a tiny file is rejected, not allocated for
+        let mut huge = build_mo(&[("Copy", "Kopieren")]);
+        huge[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse_mo(&huge).is_none());
+    }
+
+    #[test]
+    fn test_fill_placeholders() {
+        assert_eq!(fill("Profil: {}", &["Gaming"]), "Profil: Gaming");
+        assert_eq!(fill("{} und {}", &["a", "b"]), "a und b");
+        assert_eq!(fill("{} finished", &[]), "{} finished");
+        assert_eq!(fill("Done.", &["unused"]), "Done.");
+    }
+
+    #[test]
+    fn test_system_languages() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        assert_eq!(system_languages(env(&[("LANG", "de_DE.UTF-8")])), ["de_DE"]);
+        assert_eq!(
+            system_languages(env(&[("LC_ALL", ""), ("LC_MESSAGES", "fr_FR@euro"), ("LANG", "de_DE")])),
+            ["fr_FR"]
+        );
+        assert_eq!(
+            system_languages(env(&[("LANGUAGE", "nb:en"), ("LANG", "nb_NO.UTF-8")])),
+            ["nb", "en", "nb_NO"]
+        );
+        assert!(system_languages(env(&[("LANGUAGE", "de"), ("LC_ALL", "C")])).is_empty());
+        assert!(system_languages(env(&[])).is_empty());
+    }
+
+    #[test]
+    fn test_load_falls_back_to_base_language() {
+        let dir = tempfile::tempdir().unwrap();
+        let messages = dir.path().join("pt").join("LC_MESSAGES");
+        fs::create_dir_all(&messages).unwrap();
+        fs::write(messages.join("juhradial.mo"), build_mo(&[("Copy", "Copiar")])).unwrap();
+
+        let catalog = load(dir.path(), "pt_BR");
+        assert_eq!(catalog.language, "pt");
+        assert_eq!(catalog.get("Copy"), "Copiar");
+        assert_eq!(catalog.get("Paste"), "Paste");
+        assert!(load(dir.path(), "ja").is_empty());
+    }
+
+    #[test]
+    fn test_shipped_catalog_parses() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../overlay/locales");
+        let catalog = load(&dir, "de_DE");
+        assert_eq!(catalog.language, "de");
+        assert_eq!(catalog.get("Copy"), "Kopieren");
+        assert_eq!(fill(catalog.get("Profile: {}"), &["Gaming"]), "Profil: Gaming");
+    }
+
+    #[test]
+    fn test_localize_label_prefers_profile_override() {
+        let overrides = HashMap::from([("Copy".to_string(), "Yoink".to_string())]);
+        assert_eq!(localize_label("Copy", &overrides), "Yoink");
+        assert_eq!(localize_label("My Script", &overrides), "My Script");
+    }
+}
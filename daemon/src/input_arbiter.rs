@@ -0,0 +1,189 @@
+//! Runtime arbitration between gesture button event sources
+//!
+//! The gesture button reaches the daemon either through LogiOps (logid's
+//! virtual input device sends F19/F20) or natively (hidraw for diverted
+//! buttons, evdev for the rest). Only one of them may feed `GestureEvent`s;
+//! with both, every press is reported twice, and with the wrong one, presses
+//! are missed.
+//!
+//! Every source sends through a gate from [`InputArbiter::gate`] that
+//! forwards events only while its source is active. The arbiter task
+//! watches for the LogiOps virtual device and switches the active source
+//! when logid starts or stops, so the choice no longer has to be made once at
+//! startup. A press that is still held when the sources switch gets a
+//! synthetic release, so the menu cannot stay stuck open. The same happens
+//! when the receiver reports that the mouse's link was lost mid-press (see
+//! [`crate::receiver_notifications`]).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, watch};
+
+use crate::evdev::{GestureEvent, LogidHandler};
+
+/// How often the arbiter checks for the LogiOps virtual device (seconds)
+pub const ARBITER_POLL_INTERVAL_SECS: u64 = 2;
+
+/// Buffer of each gate's channel
+const GATE_CHANNEL_SIZE: usize = 32;
+
+/// Gesture event source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputSource {
+    /// Diverted HID++ notifications (hidraw) and evdev
+    Native,
+    /// LogiOps virtual input device (F19/F20)
+    Logid,
+}
+
+impl InputSource {
+    /// Source to use given the devices present right now
+    pub fn detect() -> Self {
+        if LogidHandler::find_logid_device().is_ok() {
+            InputSource::Logid
+        } else {
+            InputSource::Native
+        }
+    }
+
+    /// Name for logs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InputSource::Native => "native",
+            InputSource::Logid => "logid",
+        }
+    }
+}
+
+/// Selects which source's events reach the gesture event processor
+#[derive(Debug)]
+pub struct InputArbiter {
+    /// Currently active source
+    active: watch::Sender<InputSource>,
+    /// A forwarded press has not been released yet
+    held: Arc<AtomicBool>,
+    /// Channel of the gesture event processor
+    events: mpsc::Sender<GestureEvent>,
+}
+
+impl InputArbiter {
+    /// Create an arbiter feeding `events`, starting with `initial` active
+    pub fn new(initial: InputSource, events: mpsc::Sender<GestureEvent>) -> Self {
+        Self {
+            active: watch::Sender::new(initial),
+            held: Arc::new(AtomicBool::new(false)),
+            events,
+        }
+    }
+
+    /// Currently active source
+    pub fn active(&self) -> InputSource {
+        *self.active.borrow()
+    }
+
+    /// Watch for source switches
+    pub fn subscribe(&self) -> watch::Receiver<InputSource> {
+        self.active.subscribe()
+    }
+
+    /// Sender for one source; its events are dropped while it is inactive
+    pub fn gate(&self, source: InputSource) -> mpsc::Sender<GestureEvent> {
+        let (tx, mut rx) = mpsc::channel::<GestureEvent>(GATE_CHANNEL_SIZE);
+        let active = self.active.subscribe();
+        let held = self.held.clone();
+        let events = self.events.clone();
+
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                if *active.borrow() != source {
+                    tracing::trace!(source = source.as_str(), ?event, "Inactive source - event dropped");
+                    continue;
+                }
+                match event {
+                    GestureEvent::Pressed { .. } => held.store(true, Ordering::Relaxed),
+                    GestureEvent::Released { .. } => held.store(false, Ordering::Relaxed),
+                    _ => {}
+                }
+                if events.send(event).await.is_err() {
+                    return;
+                }
+            }
+        });
+        tx
+    }
+
+    /// Make `source` active; returns true if it changed
+    pub async fn switch_to(&self, source: InputSource) -> bool {
+        if !self.active.send_if_modified(|active| std::mem::replace(active, source) != source) {
+            return false;
+        }
+        tracing::info!(source = source.as_str(), "Gesture input source switched");
+
+        // The old source's release will never be forwarded
+        self.release_held().await;
+        true
+    }
+
+    /// Send a synthetic release for a forwarded press that is still held
+    pub async fn release_held(&self) -> bool {
+        if !self.held.swap(false, Ordering::Relaxed) {
+            return false;
+        }
+        let _ = self.events.send(GestureEvent::Released { duration_ms: 0 }).await;
+        true
+    }
+}
+
+/// Follow logid starting and stopping, and the mouse losing its link
+pub async fn start_input_arbiter(arbiter: Arc<InputArbiter>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(ARBITER_POLL_INTERVAL_SECS));
+    let mut presence = crate::receiver_notifications::subscribe();
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            Ok(()) = presence.changed() => {
+                // A mouse that went away can't release the button it held
+                let reachable = *presence.borrow_and_update();
+                if !reachable && arbiter.release_held().await {
+                    tracing::info!("Mouse link lost while the gesture button was held - released");
+                }
+                continue;
+            }
+        }
+        let source = tokio::task::spawn_blocking(InputSource::detect)
+            .await
+            .unwrap_or(InputSource::Native);
+        arbiter.switch_to(source).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_only_active_source_is_forwarded() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let arbiter = InputArbiter::new(InputSource::Native, tx);
+        let native = arbiter.gate(InputSource::Native);
+        let logid = arbiter.gate(InputSource::Logid);
+
+        logid.send(GestureEvent::Pressed { x: 1, y: 1, menu: None }).await.unwrap();
+        native.send(GestureEvent::Pressed { x: 2, y: 2, menu: None }).await.unwrap();
+        assert!(matches!(rx.recv().await, Some(GestureEvent::Pressed { x: 2, y: 2, .. })));
+
+        // Switching while held releases the press
+        assert!(arbiter.switch_to(InputSource::Logid).await);
+        assert!(!arbiter.switch_to(InputSource::Logid).await);
+        assert!(matches!(rx.recv().await, Some(GestureEvent::Released { duration_ms: 0 })));
+
+        native.send(GestureEvent::Released { duration_ms: 30 }).await.unwrap();
+        logid.send(GestureEvent::Scrolled { delta: 1 }).await.unwrap();
+        assert!(matches!(rx.recv().await, Some(GestureEvent::Scrolled { delta: 1 })));
+    }
+}
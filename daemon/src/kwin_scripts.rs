@@ -0,0 +1,273 @@
+//! KWin script lifecycle
+//!
+//! On Plasma the menu is opened at KWin's own `workspace.cursorPos`, the only
+//! cursor position that is correct on Wayland with several monitors. That
+//! used to mean writing a temporary script and loading it into KWin on every
+//! press, leaving one loaded script behind per press.
+//!
+//! Instead the daemon's scripts ([`SCRIPTS`]) are written once under
+//! `$XDG_RUNTIME_DIR/juhradial/kwin/` and loaded at startup under fixed
+//! plugin names, replacing copies a previous run left behind. The cursor
+//! script registers a KWin shortcut ([`SHOW_MENU_SHORTCUT`], unbound) whose
+//! handler calls `ShowMenuAtCursor`, so a press only has to invoke that
+//! shortcut through kglobalaccel.
+//!
+//! KWin forgets its scripts when it restarts; the manager follows
+//! `org.kde.KWin` with `NameOwnerChanged` and loads them again. They are
+//! unloaded when the daemon shuts down.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use tokio_stream::StreamExt;
+use zbus::{proxy, Result as ZbusResult};
+
+/// KWin's bus name
+pub const KWIN_SERVICE: &str = "org.kde.KWin";
+
+/// Shortcut the cursor script registers (KWin's kglobalaccel component)
+pub const SHOW_MENU_SHORTCUT: &str = "juhradial-show-menu";
+
+/// A script the daemon keeps loaded in KWin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KWinScript {
+    /// Plugin name (also the file name)
+    pub name: &'static str,
+    /// JavaScript source
+    pub source: &'static str,
+}
+
+/// Opens the menu at KWin's cursor position when [`SHOW_MENU_SHORTCUT`] fires
+pub const CURSOR_QUERY: KWinScript = KWinScript {
+    name: "juhradial-cursor-query",
+    source: r#"
+registerShortcut("juhradial-show-menu", "JuhRadial MX: open menu at cursor", "", function() {
+    var pos = workspace.cursorPos;
+    callDBus("org.kde.juhradialmx", "/org/kde/juhradialmx/Daemon",
+             "org.kde.juhradialmx.Daemon", "ShowMenuAtCursor",
+             pos.x, pos.y);
+});
+"#,
+};
+
+/// Scripts loaded into KWin
+pub const SCRIPTS: &[KWinScript] = &[CURSOR_QUERY];
+
+/// Loaded scripts (plugin name -> KWin script ID)
+static LOADED: Mutex<Option<HashMap<&'static str, i32>>> = Mutex::new(None);
+
+/// KWin scripting interface
+#[proxy(
+    interface = "org.kde.kwin.Scripting",
+    default_service = "org.kde.KWin",
+    default_path = "/Scripting"
+)]
+trait Scripting {
+    /// Load a script file under a plugin name; returns its ID (-1 on failure)
+    #[zbus(name = "loadScript")]
+    fn load_script(&self, path: &str, plugin_name: &str) -> ZbusResult<i32>;
+
+    /// Unload a script by plugin name
+    #[zbus(name = "unloadScript")]
+    fn unload_script(&self, plugin_name: &str) -> ZbusResult<bool>;
+
+    /// Whether a plugin name is loaded
+    #[zbus(name = "isScriptLoaded")]
+    fn is_script_loaded(&self, plugin_name: &str) -> ZbusResult<bool>;
+}
+
+/// Directory the script files are written to
+pub fn script_dir() -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("juhradial")
+        .join("kwin")
+}
+
+/// Write `script` into `dir`; returns its path
+pub fn write_script(dir: &Path, script: &KWinScript) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.js", script.name));
+    std::fs::write(&path, script.source)?;
+    Ok(path)
+}
+
+/// Whether `script` is loaded into KWin
+pub fn is_loaded(script: &KWinScript) -> bool {
+    LOADED
+        .lock()
+        .is_ok_and(|loaded| loaded.as_ref().is_some_and(|l| l.contains_key(script.name)))
+}
+
+/// Forget all script IDs (KWin went away)
+fn forget_all() {
+    if let Ok(mut loaded) = LOADED.lock() {
+        *loaded = None;
+    }
+}
+
+/// Load and start one script; returns its ID
+async fn load(scripting: &ScriptingProxy<'_>, connection: &zbus::Connection, script: &KWinScript) -> Result<i32, String> {
+    let path = write_script(&script_dir(), script).map_err(|e| format!("could not write script: {}", e))?;
+
+    // A copy from a previous run (or an older version) would keep the name
+    if scripting.is_script_loaded(script.name).await.unwrap_or(false) {
+        let _ = scripting.unload_script(script.name).await;
+    }
+
+    let id = scripting
+        .load_script(&path.to_string_lossy(), script.name)
+        .await
+        .map_err(|e| e.to_string())?;
+    if id < 0 {
+        return Err("KWin refused the script".to_string());
+    }
+
+    let script_path = format!("/Scripting/Script{}", id);
+    let proxy = zbus::Proxy::new(connection, KWIN_SERVICE, script_path.as_str(), "org.kde.kwin.Script")
+        .await
+        .map_err(|e| e.to_string())?;
+    proxy.call_method("run", &()).await.map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// Load every script into KWin; returns how many are running
+pub async fn load_all(connection: &zbus::Connection) -> usize {
+    let scripting = match ScriptingProxy::new(connection).await {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            tracing::debug!("KWin scripting unavailable: {}", e);
+            return 0;
+        }
+    };
+
+    let mut loaded = HashMap::new();
+    for script in SCRIPTS {
+        match load(&scripting, connection, script).await {
+            Ok(id) => {
+                tracing::info!(script = script.name, id, "KWin script loaded");
+                loaded.insert(script.name, id);
+            }
+            Err(e) => tracing::warn!(script = script.name, "Failed to load KWin script: {}", e),
+        }
+    }
+    let count = loaded.len();
+    if let Ok(mut current) = LOADED.lock() {
+        *current = Some(loaded);
+    }
+    count
+}
+
+/// Unload every script (daemon shutdown)
+pub async fn unload_all(connection: &zbus::Connection) {
+    let names: Vec<&'static str> = LOADED
+        .lock()
+        .ok()
+        .and_then(|mut loaded| loaded.take())
+        .map(|loaded| loaded.into_keys().collect())
+        .unwrap_or_default();
+    if names.is_empty() {
+        return;
+    }
+    let Ok(scripting) = ScriptingProxy::new(connection).await else {
+        return;
+    };
+    for name in names {
+        match scripting.unload_script(name).await {
+            Ok(_) => tracing::debug!(script = name, "KWin script unloaded"),
+            Err(e) => tracing::debug!(script = name, "Failed to unload KWin script: {}", e),
+        }
+    }
+}
+
+/// Open the menu at KWin's cursor position
+///
+/// Returns false if the cursor script is not loaded (yet) or the shortcut
+/// could not be invoked; the caller then opens the menu itself.
+pub fn show_menu_at_cursor() -> bool {
+    if !is_loaded(&CURSOR_QUERY) {
+        return false;
+    }
+    let invoked = Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply",
+            "--dest=org.kde.kglobalaccel",
+            "/component/kwin",
+            "org.kde.kglobalaccel.Component.invokeShortcut",
+            &format!("string:{}", SHOW_MENU_SHORTCUT),
+        ])
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if invoked {
+        tracing::debug!("KWin cursor script triggered");
+    } else {
+        tracing::warn!("Failed to invoke the KWin cursor script shortcut");
+    }
+    invoked
+}
+
+/// Keep the scripts loaded for as long as the daemon runs
+///
+/// Loads them now and again whenever KWin (re)appears on the bus.
+pub async fn start_kwin_script_manager(connection: zbus::Connection) {
+    let dbus = match zbus::fdo::DBusProxy::new(&connection).await {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            tracing::warn!("Failed to create org.freedesktop.DBus proxy: {}", e);
+            return;
+        }
+    };
+    let mut owner_changes = match dbus.receive_name_owner_changed_with_args(&[(0, KWIN_SERVICE)]).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            tracing::warn!("Cannot follow KWin restarts: {}", e);
+            load_all(&connection).await;
+            return;
+        }
+    };
+
+    load_all(&connection).await;
+
+    while let Some(change) = owner_changes.next().await {
+        let Ok(args) = change.args() else {
+            continue;
+        };
+        forget_all();
+        if args.new_owner().is_some() {
+            tracing::info!("KWin restarted, reloading scripts");
+            load_all(&connection).await;
+        } else {
+            tracing::info!("KWin left the bus");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dbus::{DBUS_INTERFACE, DBUS_NAME, DBUS_PATH};
+
+    #[test]
+    fn test_cursor_script_targets_daemon() {
+        let source = CURSOR_QUERY.source;
+        assert!(source.contains(&format!("\"{}\", \"{}\"", DBUS_NAME, DBUS_PATH)));
+        assert!(source.contains(&format!("\"{}\", \"ShowMenuAtCursor\"", DBUS_INTERFACE)));
+        assert!(source.contains(&format!("registerShortcut(\"{}\"", SHOW_MENU_SHORTCUT)));
+    }
+
+    #[test]
+    fn test_write_script_is_stable() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = write_script(dir.path(), &CURSOR_QUERY).unwrap();
+        let second = write_script(dir.path(), &CURSOR_QUERY).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.file_name().unwrap(), "juhradial-cursor-query.js");
+        assert_eq!(std::fs::read_to_string(first).unwrap(), CURSOR_QUERY.source);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
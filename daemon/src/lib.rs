@@ -3,20 +3,97 @@
 //! Public API for testing and integration.
 
 pub mod accessibility;
+pub mod action_history;
 pub mod actions;
+pub mod activities;
+pub mod app_dpi;
+pub mod audio_output;
 pub mod battery;
+pub mod battery_saver;
+pub mod blind_mode;
+pub mod bluetooth;
+pub mod bundled_profiles;
 pub mod bundled_themes;
+pub mod command_runner;
+pub mod compositor;
 pub mod config;
 pub mod cursor;
+pub mod cursor_coalesce;
 pub mod dbus;
+pub mod diagnostics;
+pub mod emoji_picker;
 pub mod evdev;
+pub mod fast_path;
+pub mod feature_cache;
+pub mod feature_explorer;
+pub mod game_mode;
+pub mod global_shortcuts;
+pub mod gnome_shell;
+pub mod haptic_budget;
+pub mod haptic_calibration;
+pub mod haptic_keeper;
 pub mod hidpp;
+pub mod hidpp_audit;
+pub mod hidpp_retry;
 pub mod hidraw;
+pub mod host_switch;
+pub mod http_request;
+pub mod hyprland;
+pub mod i18n;
+pub mod input_arbiter;
+pub mod kwin_scripts;
+pub mod link_quality;
+pub mod logid_config;
+pub mod long_hover;
+pub mod menu_grab;
+pub mod menu_pages;
+pub mod metrics;
+pub mod multi_press;
+pub mod network;
+pub mod night_light;
+pub mod notification_haptics;
+pub mod notifications;
+pub mod obs;
+pub mod ocr;
+#[cfg(feature = "overlay")]
+pub mod overlay;
+pub mod overlay_monitor;
 pub mod performance_monitor;
+pub mod pointer_confinement;
+pub mod portal;
+pub mod power_profiles;
+pub mod press_debounce;
+pub mod profile_switch;
 pub mod profiles;
+pub mod quiet_hours;
+pub mod receiver_notifications;
+pub mod runtime_state;
+pub mod screen_watcher;
+pub mod self_test;
+pub mod session_bus;
+pub mod settings_dbus;
+pub mod setup;
+pub mod slice_geometry;
+pub mod slice_state;
+pub mod solaar;
+pub mod state_file;
+pub mod stylus;
+pub mod supervisor;
+pub mod tap_passthrough;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod text_entry;
 pub mod theme;
+pub mod theme_preview;
 pub mod theme_watcher;
+pub mod timer;
+pub mod touchpad;
+pub mod training;
+pub mod usage_stats;
+pub mod widget_dbus;
 pub mod window_tracker;
+#[cfg(feature = "xdg-output")]
+pub mod xdg_output;
 
 /// Re-export commonly used types
 pub use accessibility::{AccessibilitySettings, EffectiveAnimationTimings};
@@ -27,8 +104,10 @@ pub use config::{Config, SharedConfig, new_shared_config, load_shared_config};
 pub use cursor::{get_cursor_position, get_screen_bounds, CursorPosition, ScreenBounds, EDGE_MARGIN, MENU_DIAMETER, MENU_RADIUS};
 pub use dbus::{init_dbus_service, JuhRadialService, DBUS_INTERFACE, DBUS_NAME, DBUS_PATH};
 pub use evdev::{DeviceInfo, EvdevError, EvdevHandler, GestureEvent, LogidHandler, LOGITECH_VENDOR_ID};
+pub use overlay_monitor::{new_shared_overlay_monitor, OverlayMonitor, SharedOverlayMonitor};
 pub use performance_monitor::{BlurMode, PerformanceMonitor};
 pub use profiles::{Profile, ProfileManager};
+pub use settings_dbus::{SettingsService, SETTINGS_INTERFACE, SETTINGS_PATH, SETTINGS_API_VERSION};
 pub use theme::{Theme, ThemeManager};
 pub use theme_watcher::{ThemeEvent, ThemeHotReloader, ThemeWatcher};
 pub use window_tracker::{WindowInfo, WindowTracker};
@@ -0,0 +1,215 @@
+//! Connection type and wireless link quality
+//!
+//! Logitech receivers and Bluetooth expose no signal strength for connected
+//! devices, so link quality is measured from the daemon's own HID++ traffic:
+//! every request records its round-trip time, or a failure when the device
+//! never answers (an error reply still counts as an answer). A link is
+//! degraded when round trips are slow or requests go unanswered often; a
+//! busy 2.4 GHz band or a distant Bluetooth adapter shows up here long
+//! before buttons start lagging.
+//!
+//! Exposed through `GetDeviceInfo` and the `ConnectionChanged` signal on the
+//! daemon interface.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::dbus::{DBUS_INTERFACE, DBUS_PATH};
+use crate::hidpp::{ConnectionType, SharedHapticManager};
+
+/// How often the connection monitor looks for changes (seconds)
+const CONNECTION_POLL_INTERVAL_SECS: u64 = 2;
+
+/// Smoothing factor for the round-trip average (weight of the newest sample)
+const RTT_SMOOTHING: f32 = 0.2;
+
+/// Smoothing factor for the failure rate (a single timeout is not degradation)
+const FAILURE_SMOOTHING: f32 = 0.05;
+
+/// Requests needed before a quality verdict is given
+const MIN_SAMPLES: u64 = 10;
+
+/// Smoothed round trip above which the link is degraded (milliseconds)
+const DEGRADED_RTT_MS: f32 = 40.0;
+
+/// Share of failed requests (smoothed) above which the link is degraded
+const DEGRADED_FAILURE_RATE: f32 = 0.1;
+
+/// Overall link verdict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkQuality {
+    /// Not enough traffic yet
+    Unknown,
+    Good,
+    Degraded,
+}
+
+impl LinkQuality {
+    /// Name used over D-Bus
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkQuality::Unknown => "unknown",
+            LinkQuality::Good => "good",
+            LinkQuality::Degraded => "degraded",
+        }
+    }
+}
+
+/// HID++ request statistics for one connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct LinkStats {
+    /// Requests sent
+    pub requests: u64,
+    /// Requests the device never answered
+    pub failures: u64,
+    /// Last successful round trip (milliseconds)
+    pub last_rtt_ms: f32,
+    /// Smoothed round trip (milliseconds)
+    pub avg_rtt_ms: f32,
+    /// Smoothed failure rate (0.0-1.0)
+    pub failure_rate: f32,
+}
+
+impl LinkStats {
+    /// Record a request that got its response after `rtt`
+    pub fn record_success(&mut self, rtt: Duration) {
+        let rtt_ms = rtt.as_secs_f32() * 1000.0;
+        self.avg_rtt_ms = if self.requests == self.failures {
+            rtt_ms
+        } else {
+            self.avg_rtt_ms + RTT_SMOOTHING * (rtt_ms - self.avg_rtt_ms)
+        };
+        self.last_rtt_ms = rtt_ms;
+        self.requests += 1;
+        self.failure_rate -= FAILURE_SMOOTHING * self.failure_rate;
+    }
+
+    /// Record a request the device never answered
+    pub fn record_failure(&mut self) {
+        self.requests += 1;
+        self.failures += 1;
+        self.failure_rate += FAILURE_SMOOTHING * (1.0 - self.failure_rate);
+    }
+
+    /// Quality verdict from the smoothed values
+    pub fn quality(&self) -> LinkQuality {
+        if self.requests < MIN_SAMPLES {
+            LinkQuality::Unknown
+        } else if self.avg_rtt_ms > DEGRADED_RTT_MS || self.failure_rate > DEGRADED_FAILURE_RATE {
+            LinkQuality::Degraded
+        } else {
+            LinkQuality::Good
+        }
+    }
+}
+
+/// Connection summary returned by `GetDeviceInfo`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConnectionInfo {
+    /// Whether a device is open
+    pub connected: bool,
+    /// "USB", "Bolt", "Bluetooth", "Unifying" or "" when disconnected
+    pub connection_type: String,
+    /// Whether the link is wireless
+    pub wireless: bool,
+    /// The mouse is Easy-Switched to another host
+    pub host_switched_away: bool,
+    /// Link quality verdict
+    pub quality: LinkQuality,
+    /// HID++ request statistics (None when disconnected)
+    pub link: Option<LinkStats>,
+}
+
+impl ConnectionInfo {
+    /// Summarize the haptic manager's connection
+    pub fn collect(haptics: &SharedHapticManager) -> Self {
+        let (connection_type, link) = haptics
+            .lock()
+            .map(|m| (m.connection_type(), m.link_stats()))
+            .unwrap_or((None, None));
+        Self {
+            connected: connection_type.is_some(),
+            connection_type: connection_type.map(|c| c.to_string()).unwrap_or_default(),
+            wireless: connection_type.is_some_and(|c| c != ConnectionType::Usb),
+            host_switched_away: crate::host_switch::is_paused(),
+            quality: link.map(|l| l.quality()).unwrap_or(LinkQuality::Unknown),
+            link,
+        }
+    }
+}
+
+/// Emit `ConnectionChanged` whenever the connection type or link quality changes
+pub async fn start_connection_monitor(connection: zbus::Connection, haptics: SharedHapticManager) {
+    let mut interval = tokio::time::interval(Duration::from_secs(CONNECTION_POLL_INTERVAL_SECS));
+    let mut last: Option<(String, LinkQuality)> = None;
+
+    loop {
+        interval.tick().await;
+
+        let info = ConnectionInfo::collect(&haptics);
+        let current = (info.connection_type, info.quality);
+        if last.as_ref() == Some(&current) {
+            continue;
+        }
+        // The first snapshot is the baseline, not a change
+        let first = last.is_none();
+        last = Some(current.clone());
+        if first {
+            continue;
+        }
+
+        let (connection_type, quality) = current;
+        tracing::info!(connection = %connection_type, quality = quality.as_str(), "Connection changed");
+        if let Err(e) = connection
+            .emit_signal(
+                None::<&str>,
+                DBUS_PATH,
+                DBUS_INTERFACE,
+                "ConnectionChanged",
+                &(connection_type.as_str(), quality.as_str()),
+            )
+            .await
+        {
+            tracing::debug!("Failed to emit ConnectionChanged: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quality_from_stats() {
+        let mut stats = LinkStats::default();
+        for _ in 0..MIN_SAMPLES - 1 {
+            stats.record_success(Duration::from_millis(8));
+        }
+        assert_eq!(stats.quality(), LinkQuality::Unknown);
+        stats.record_success(Duration::from_millis(8));
+        assert_eq!(stats.quality(), LinkQuality::Good);
+        assert!((stats.avg_rtt_ms - 8.0).abs() < 0.01);
+
+        // A single timeout is tolerated, a burst degrades the link
+        stats.record_failure();
+        assert_eq!(stats.quality(), LinkQuality::Good);
+        stats.record_failure();
+        stats.record_failure();
+        assert_eq!(stats.quality(), LinkQuality::Degraded);
+        for _ in 0..10 {
+            stats.record_success(Duration::from_millis(8));
+        }
+        assert_eq!(stats.quality(), LinkQuality::Good);
+
+        // Slow round trips degrade it too
+        for _ in 0..20 {
+            stats.record_success(Duration::from_millis(60));
+        }
+        assert_eq!(stats.quality(), LinkQuality::Degraded);
+        assert_eq!(stats.failures, 3);
+    }
+}
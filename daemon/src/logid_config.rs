@@ -0,0 +1,292 @@
+//! logid.cfg sanity checks
+//!
+//! With LogiOps the daemon relies on logid diverting the gesture button and
+//! sending `KEY_F19`. If `/etc/logid.cfg` does not do that, the daemon would
+//! just never see a press, so the config is checked when logid is detected
+//! and problems are reported through [`crate::diagnostics`].
+//!
+//! This is not a full libconfig parser: it looks at each `cid` entry and the
+//! settings that follow it up to the next `cid`, which is how logid button
+//! lists are written in practice.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::path::Path;
+
+use crate::diagnostics::{self, Diagnostic, Severity};
+
+/// Default logid configuration path
+pub const LOGID_CONFIG_PATH: &str = "/etc/logid.cfg";
+
+/// Gesture button CIDs: MX Master 4, MX Master 3/3S
+pub const GESTURE_CIDS: [u16; 2] = [0x1a0, 0xc3];
+
+/// Key the daemon listens for from logid
+const TRIGGER_KEY: &str = "KEY_F19";
+
+/// Diagnostics source name
+const SOURCE: &str = "logid";
+
+/// A button entry from logid.cfg
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ButtonMapping {
+    /// Device `name` the entry belongs to (if found)
+    pub device: Option<String>,
+    /// Control ID
+    pub cid: u16,
+    /// `divert: true`
+    pub diverted: bool,
+    /// Entry sends KEY_F19
+    pub sends_trigger_key: bool,
+}
+
+/// Strip `//`, `#` and `/* */` comments (string contents are kept)
+fn strip_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '#' => skip_line(&mut chars),
+            '/' if chars.peek() == Some(&'/') => skip_line(&mut chars),
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn skip_line(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) {
+    for c in chars.by_ref() {
+        if c == '\n' {
+            break;
+        }
+    }
+}
+
+/// Find `key` followed by `:` or `=` and return the rest after the separator
+fn find_setting<'a>(text: &'a str, key: &str) -> Vec<(usize, &'a str)> {
+    let mut found = Vec::new();
+    let mut start = 0;
+    while let Some(pos) = text[start..].find(key) {
+        let at = start + pos;
+        start = at + key.len();
+
+        // Whole-word match only
+        let before_ok = text[..at]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !(c.is_ascii_alphanumeric() || c == '_'));
+        let rest = text[start..].trim_start();
+        if before_ok && (rest.starts_with(':') || rest.starts_with('=')) {
+            found.push((at, rest[1..].trim_start()));
+        }
+    }
+    found
+}
+
+fn parse_number(value: &str) -> Option<u16> {
+    let token: String = value
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect();
+    match token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")) {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None => token.parse().ok(),
+    }
+}
+
+/// Extract button entries from logid.cfg contents
+pub fn parse_button_mappings(contents: &str) -> Vec<ButtonMapping> {
+    let text = strip_comments(contents);
+    let names: Vec<(usize, String)> = find_setting(&text, "name")
+        .into_iter()
+        .filter_map(|(at, rest)| {
+            let rest = rest.strip_prefix('"')?;
+            Some((at, rest[..rest.find('"')?].to_string()))
+        })
+        .collect();
+    let cids = find_setting(&text, "cid");
+
+    cids.iter()
+        .enumerate()
+        .filter_map(|(i, &(at, rest))| {
+            let cid = parse_number(rest)?;
+            let end = cids.get(i + 1).map(|&(next, _)| next).unwrap_or(text.len());
+            let entry = &text[at..end];
+            let device = names
+                .iter()
+                .take_while(|(name_at, _)| *name_at < at)
+                .last()
+                .map(|(_, name)| name.clone());
+            let diverted = find_setting(entry, "divert")
+                .first()
+                .is_some_and(|(_, value)| value.starts_with("true"));
+            Some(ButtonMapping {
+                device,
+                cid,
+                diverted,
+                sends_trigger_key: entry.contains(TRIGGER_KEY),
+            })
+        })
+        .collect()
+}
+
+/// Check button mappings for a working gesture button -> F19 setup
+pub fn check_mappings(mappings: &[ButtonMapping]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let describe = |m: &ButtonMapping| match &m.device {
+        Some(device) => format!("CID 0x{:x} ({})", m.cid, device),
+        None => format!("CID 0x{:x}", m.cid),
+    };
+
+    let gesture: Vec<_> = mappings.iter().filter(|m| GESTURE_CIDS.contains(&m.cid)).collect();
+    let working = gesture.iter().any(|m| m.diverted && m.sends_trigger_key);
+
+    if gesture.is_empty() {
+        diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            "logid-gesture-unmapped",
+            format!(
+                "{} has no entry for the gesture button (CID 0x1a0 on MX Master 4, 0xc3 on MX Master 3/3S); \
+                 add one that diverts it to KEY_F19 (see packaging/logid.cfg)",
+                LOGID_CONFIG_PATH
+            ),
+        ));
+    }
+
+    for m in &gesture {
+        if !m.sends_trigger_key {
+            diagnostics.push(Diagnostic::new(
+                if working { Severity::Info } else { Severity::Error },
+                "logid-gesture-conflict",
+                format!(
+                    "Gesture button {} is mapped to another action instead of KEY_F19; the radial menu will not open from it",
+                    describe(m)
+                ),
+            ));
+        } else if !m.diverted {
+            diagnostics.push(Diagnostic::new(
+                Severity::Error,
+                "logid-not-diverted",
+                format!("Gesture button {} sends KEY_F19 but is missing `divert: true`", describe(m)),
+            ));
+        }
+    }
+
+    for m in mappings.iter().filter(|m| m.sends_trigger_key && !GESTURE_CIDS.contains(&m.cid)) {
+        diagnostics.push(Diagnostic::new(
+            Severity::Warning,
+            "logid-f19-other-button",
+            format!("{} also sends KEY_F19 and will open the radial menu", describe(m)),
+        ));
+    }
+
+    diagnostics
+}
+
+/// Check a logid config file and report the findings
+///
+/// Returns the findings (also published via [`crate::diagnostics`]).
+pub fn check_logid_config(path: &Path) -> Vec<Diagnostic> {
+    let findings = match std::fs::read_to_string(path) {
+        Ok(contents) => check_mappings(&parse_button_mappings(&contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![Diagnostic::new(
+            Severity::Warning,
+            "logid-config-missing",
+            format!(
+                "logid is running but {} does not exist; install packaging/logid.cfg to divert the gesture button to F19",
+                path.display()
+            ),
+        )],
+        Err(e) => vec![Diagnostic::new(
+            Severity::Info,
+            "logid-config-unreadable",
+            format!("Cannot read {} to verify the gesture mapping: {}", path.display(), e),
+        )],
+    };
+
+    diagnostics::report(SOURCE, findings.clone());
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUNDLED: &str = include_str!("../../packaging/logid.cfg");
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_bundled_config_is_clean() {
+        let mappings = parse_button_mappings(BUNDLED);
+        assert_eq!(mappings.len(), 3);
+        assert_eq!(mappings[0].device.as_deref(), Some("MX Master 4"));
+        assert_eq!(mappings[0].cid, 0x1a0);
+        assert!(mappings.iter().all(|m| m.diverted && m.sends_trigger_key));
+        assert!(check_mappings(&mappings).is_empty());
+    }
+
+    #[test]
+    fn test_detects_missing_divert_and_conflict() {
+        let config = r#"
+            devices: ({
+                name: "MX Master 4";
+                buttons: ({
+                    cid: 0x1a0;
+                    // divert: true;
+                    action = { type: "Keypress"; keys: ["KEY_F19"]; };
+                },
+                {
+                    cid: 0x53;
+                    divert: true;
+                    action = { type: "Keypress"; keys: ["KEY_F19"]; };
+                });
+            });
+        "#;
+        let found = check_mappings(&parse_button_mappings(config));
+        assert_eq!(codes(&found), vec!["logid-not-diverted", "logid-f19-other-button"]);
+    }
+
+    #[test]
+    fn test_detects_unmapped_and_remapped_gesture() {
+        let unmapped = r#"devices: ({ name: "MX Master 4"; dpi: 1000; });"#;
+        assert_eq!(codes(&check_mappings(&parse_button_mappings(unmapped))), vec!["logid-gesture-unmapped"]);
+
+        let remapped = r#"buttons: ({ cid: 195; divert: true; action = { type: "Gestures"; }; });"#;
+        let found = check_mappings(&parse_button_mappings(remapped));
+        assert_eq!(codes(&found), vec!["logid-gesture-conflict"]);
+        assert_eq!(found[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_missing_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let found = check_logid_config(&dir.path().join("logid.cfg"));
+        assert_eq!(codes(&found), vec!["logid-config-missing"]);
+    }
+}
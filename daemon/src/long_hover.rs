@@ -0,0 +1,171 @@
+//! Long-hover alternate actions
+//!
+//! A slice may define an `alternate` action next to its normal one. While
+//! the menu is open the overlay reports hovered slices (`NotifySliceHover`);
+//! if a slice with an alternate stays hovered for `long_hover_ms` the daemon
+//! arms it, plays [`ALTERNATE_ARMED_PATTERN`] and emits `AlternateArmed` so
+//! the overlay runs the alternate on release. Moving to another slice
+//! disarms it; timing lives in the daemon so every overlay behaves the same.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::{Arc, Mutex};
+
+use crate::hidpp::Mx4HapticPattern;
+use crate::profiles::Profile;
+
+/// Haptic cue when an alternate arms (distinct from the slice-change tick)
+pub const ALTERNATE_ARMED_PATTERN: Mx4HapticPattern = Mx4HapticPattern::Knock;
+
+/// Shared tracker for the D-Bus service and its hover timers
+pub type SharedLongHover = Arc<Mutex<LongHoverTracker>>;
+
+/// Hover state of the open menu
+#[derive(Debug, Default)]
+pub struct LongHoverTracker {
+    /// Slices that have an alternate, per page of the open menu
+    alternates: Vec<[bool; 8]>,
+    /// Page currently shown
+    page: usize,
+    /// Incremented on every hover change; stale timers compare against it
+    generation: u64,
+    /// Currently hovered slice
+    hovered: Option<u8>,
+    /// Slice whose alternate is armed
+    armed: Option<u8>,
+}
+
+impl LongHoverTracker {
+    /// Start tracking a newly opened menu
+    pub fn open_menu(&mut self, layout: &Profile) {
+        self.alternates = (0..layout.page_count())
+            .filter_map(|page| layout.page(page))
+            .map(|slices| std::array::from_fn(|i| slices[i].as_ref().is_some_and(|a| a.alternate.is_some())))
+            .collect();
+        self.show_page(0);
+    }
+
+    /// Follow a page change; hover indices now refer to `page`
+    pub fn show_page(&mut self, page: usize) {
+        self.page = page;
+        self.reset();
+    }
+
+    /// Stop tracking (menu closed)
+    pub fn close_menu(&mut self) {
+        self.alternates.clear();
+        self.show_page(0);
+    }
+
+    /// Forget hover state and invalidate pending timers
+    fn reset(&mut self) {
+        self.generation += 1;
+        self.hovered = None;
+        self.armed = None;
+    }
+
+    /// Register a hovered slice
+    ///
+    /// Returns a timer token if this starts hovering a slice with an
+    /// alternate; pass it to [`LongHoverTracker::arm`] once the threshold
+    /// has elapsed.
+    pub fn on_hover(&mut self, index: u8) -> Option<u64> {
+        if self.hovered == Some(index) {
+            return None;
+        }
+        self.reset();
+        let has_alternate = self
+            .alternates
+            .get(self.page)
+            .and_then(|page| page.get(index as usize))
+            .copied()
+            .unwrap_or(false);
+        if !has_alternate {
+            return None;
+        }
+        self.hovered = Some(index);
+        Some(self.generation)
+    }
+
+    /// Arm the hovered slice if nothing changed since `token` was issued
+    pub fn arm(&mut self, token: u64) -> Option<u8> {
+        if token != self.generation || self.armed.is_some() {
+            return None;
+        }
+        self.armed = self.hovered;
+        self.armed
+    }
+
+    /// Slice whose alternate is armed
+    pub fn armed(&self) -> Option<u8> {
+        self.armed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::{Action, ActionType};
+
+    fn layout_with_alternate_at(index: usize) -> Profile {
+        let mut profile = Profile::default();
+        let action = |value: &str| Action {
+            action_type: ActionType::Shortcut(value.to_string()),
+            label: None,
+            icon: None,
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
+        };
+        profile.slices[0] = Some(action("ctrl+c"));
+        profile.slices[index] = Some(Action {
+            alternate: Some(Box::new(action("ctrl+shift+v"))),
+            ..action("ctrl+v")
+        });
+        profile
+    }
+
+    #[test]
+    fn test_arms_only_if_still_hovered() {
+        let mut tracker = LongHoverTracker::default();
+        tracker.open_menu(&layout_with_alternate_at(1));
+
+        // Slice without alternate: no timer
+        assert_eq!(tracker.on_hover(0), None);
+
+        let token = tracker.on_hover(1).unwrap();
+        // Repeated hover reports keep the running timer
+        assert_eq!(tracker.on_hover(1), None);
+        assert_eq!(tracker.arm(token), Some(1));
+        assert_eq!(tracker.armed(), Some(1));
+
+        // Moving away disarms
+        tracker.on_hover(0);
+        assert_eq!(tracker.armed(), None);
+    }
+
+    #[test]
+    fn test_follows_page_changes() {
+        let mut tracker = LongHoverTracker::default();
+        let mut layout = layout_with_alternate_at(2);
+        layout.pages.push(layout.slices.clone());
+        layout.pages[0][2] = None;
+        tracker.open_menu(&layout);
+
+        let token = tracker.on_hover(2).unwrap();
+        tracker.show_page(1);
+        assert_eq!(tracker.arm(token), None);
+        assert_eq!(tracker.on_hover(2), None);
+    }
+
+    #[test]
+    fn test_stale_timer_after_close() {
+        let mut tracker = LongHoverTracker::default();
+        tracker.open_menu(&layout_with_alternate_at(3));
+        let token = tracker.on_hover(3).unwrap();
+        tracker.close_menu();
+        assert_eq!(tracker.arm(token), None);
+        assert_eq!(tracker.on_hover(3), None);
+    }
+}
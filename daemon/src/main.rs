@@ -3,6 +3,9 @@
 //! A daemon for Linux that provides radial menu functionality for the
 //! Logitech MX Master 4 mouse via evdev input and KWin overlay.
 
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
 use clap::Parser;
 use tokio::sync::mpsc;
 use tokio::time::{sleep, Duration};
@@ -11,13 +14,46 @@ use tracing_subscriber::FmtSubscriber;
 
 use juhradiald::{
     battery::{new_shared_state, start_battery_updater_shared},
-    config::load_shared_config,
-    cursor::{get_screen_bounds, ScreenBounds},
-    dbus::{init_dbus_service, DBUS_PATH, DBUS_NAME},
+    battery_saver::start_battery_saver,
+    blind_mode,
+    action_history::start_action_history_signals,
+    actions::{self, ActionExecutor},
+    activities::start_activity_tracking,
+    app_dpi::start_app_dpi_switcher,
+    config::{load_shared_config, ButtonMenuConfig, EvdevTriggerConfig, MenuGrabConfig, PressBinding, RuntimeMode, SharedConfig, TapPassthroughConfig},
+    cursor_coalesce::MoveCoalescer,
+    dbus::{serve_dbus_service, DbusServiceState, DBUS_PATH, DBUS_NAME},
     evdev::{EvdevHandler, EvdevError, GestureEvent, LogidHandler},
+    fast_path::{self, FastPathUpdate},
+    game_mode::{start_game_mode_monitor, suppresses_trigger},
+    global_shortcuts::GlobalShortcutsHandler,
+    haptic_keeper::start_haptic_keeper,
+    hidpp::SharedHapticManager,
     hidraw::{HidrawHandler, HidrawError},
+    input_arbiter::{start_input_arbiter, InputArbiter, InputSource},
+    kwin_scripts::{self, start_kwin_script_manager},
+    link_quality::start_connection_monitor,
+    logid_config::{check_logid_config, LOGID_CONFIG_PATH},
+    metrics,
+    multi_press::{binding_for, MultiPressDetector},
     new_shared_haptic_manager,
+    notification_haptics::start_notification_haptics,
+    overlay_monitor::{new_shared_overlay_monitor, start_overlay_monitor, SharedOverlayMonitor},
+    portal::{dev_input_accessible, init_remote_desktop, resolve_mode, running_in_flatpak, PortalError},
+    press_debounce::{PressDebouncer, PressOutcome, ReleaseOutcome},
+    profile_switch::start_profile_switcher,
     profiles::ProfileManager,
+    runtime_state,
+    screen_watcher,
+    session_bus::{self, spawn_on_session_bus},
+    solaar::start_solaar_monitor,
+    stylus::start_stylus_trigger,
+    supervisor::spawn_supervised,
+    tap_passthrough::{TapInjector, TapTracker},
+    text_entry::start_text_entry_signals,
+    theme_watcher::start_theme_watcher,
+    touchpad::start_touchpad_trigger,
+    widget_dbus::start_widget_publisher,
     window_tracker::WindowTracker,
 };
 
@@ -40,6 +76,15 @@ struct Args {
     /// List all Logitech devices and exit
     #[arg(long)]
     list_devices: bool,
+
+    /// Write the D-Bus introspection XML of every interface into DIR and exit
+    #[arg(long, value_name = "DIR")]
+    dump_introspection: Option<std::path::PathBuf>,
+
+    /// Render the radial menu with the built-in layer-shell overlay (wlroots compositors)
+    #[cfg(feature = "overlay")]
+    #[arg(long)]
+    builtin_overlay: bool,
 }
 
 #[tokio::main]
@@ -53,6 +98,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .finish();
     tracing::subscriber::set_global_default(subscriber)?;
 
+    // Handle --dump-introspection flag
+    if let Some(dir) = &args.dump_introspection {
+        dump_introspection(dir)?;
+        return Ok(());
+    }
+
     info!("JuhRadial MX Daemon starting...");
 
     // Handle --list-devices flag
@@ -78,47 +129,61 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    // Daemon-generated labels and notifications follow the configured language
+    juhradiald::i18n::set_language(&shared_config.read().unwrap().language);
+    juhradiald::hidpp_retry::configure(&shared_config.read().unwrap().hidpp_retry);
+
+    // Resolve native vs portal (Flatpak) operation
+    let configured_mode = shared_config.read().unwrap().mode;
+    let runtime_mode = resolve_mode(configured_mode, running_in_flatpak());
+    let portal_mode = runtime_mode == RuntimeMode::Portal;
+    info!(?configured_mode, ?runtime_mode, "Runtime mode resolved");
+    if !portal_mode && !dev_input_accessible() {
+        warn!("No /dev/input/event* device is readable yet - check the udev rules and input group membership, or set \"mode\": \"portal\"");
+    }
+
+    // Mouse-less: any pointing device, triggered by the keyboard shortcut or D-Bus
+    let mouseless = shared_config.read().unwrap().mouseless;
+    if mouseless {
+        info!("Mouse-less mode - skipping Logitech device handlers, haptics and battery");
+    }
+    let use_devices = !portal_mode && !mouseless;
+
+    // Restore state from the last run (last profile, DPI, ...)
+    let saved_state = runtime_state::restore();
+
     // Initialize haptic manager for MX4 haptic feedback
     let haptic_config = shared_config.read().unwrap().haptics.clone();
     let haptic_manager = new_shared_haptic_manager(&haptic_config);
 
-    // Try to connect to MX Master 4 for haptic feedback
-    {
+    // Try to connect to MX Master 4 for haptic feedback (needs hidraw, not in portal or mouse-less mode)
+    if use_devices {
         let mut manager = haptic_manager.lock().unwrap();
         match manager.connect() {
-            Ok(true) => info!("Haptic feedback connected to MX Master 4"),
+            Ok(true) => {
+                info!("Haptic feedback connected to MX Master 4");
+                if let Some(dpi) = saved_state.last_dpi {
+                    match manager.set_dpi(dpi) {
+                        Ok(()) => info!(dpi, "Restored DPI from last run"),
+                        Err(e) => warn!(dpi, "Failed to restore DPI: {}", e),
+                    }
+                }
+            }
             Ok(false) => info!("No MX Master 4 found for haptics (optional)"),
             Err(e) => warn!("Haptic connection error (non-fatal): {}", e),
         }
     }
 
     // Clone haptic_manager for battery updater before passing to D-Bus
-    let haptic_manager_for_battery = haptic_manager.clone();
-
-    // Initialize D-Bus service with battery state, config, and haptic manager
-    let dbus_connection = match init_dbus_service(
-        battery_state.clone(),
-        shared_config.clone(),
-        haptic_manager,
-    ).await {
-        Ok(conn) => {
-            info!("D-Bus service initialized successfully");
-            conn
-        }
-        Err(e) => {
-            error!("Failed to initialize D-Bus service: {}", e);
-            return Err(e.into());
-        }
-    };
+    // Actions confirm some results with a haptic cue and read live config (OCR command)
+    actions::set_context(haptic_manager.clone(), shared_config.clone());
 
-    // Spawn battery status updater (shares HidppDevice with haptic via SharedHapticManager)
-    let battery_handle = tokio::spawn(async move {
-        start_battery_updater_shared(battery_state, haptic_manager_for_battery).await
-    });
+    let haptic_manager_for_battery = haptic_manager.clone();
+    let haptic_manager_for_divert = haptic_manager.clone();
 
     // Load profiles (Story 3.1: Task 5)
     // Creates default profiles.json if it doesn't exist
-    let profile_manager = match ProfileManager::load_or_create() {
+    let mut profile_manager = match ProfileManager::load_or_create() {
         Ok(manager) => {
             info!(
                 profile_count = manager.profile_count(),
@@ -133,13 +198,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    // Return to the profile that was active when the daemon last ran
+    if let Some(name) = &saved_state.last_profile {
+        if let Err(e) = profile_manager.set_current(name) {
+            warn!(profile = %name, "Saved profile not restored: {}", e);
+        }
+    }
+
     // Log current profile
     let current = profile_manager.current();
+    if let Ok(mut manager) = haptic_manager.lock() {
+        manager.set_profile_haptics(current.haptics.as_ref());
+    }
     info!(
         profile = current.name,
         "Active profile loaded"
     );
 
+    // Detect the compositor once; cursor, screen and window queries share it
+    juhradiald::compositor::backend();
+
     // Initialize window tracker for per-app profiles (Story 3.2)
     let window_tracker = WindowTracker::new().await;
     if window_tracker.is_available() {
@@ -148,61 +226,261 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         warn!("Window tracking unavailable - using default profile only");
     }
 
-    // Store for later use in Story 3.3 (window-based profile switching)
-    let _window_tracker = window_tracker;
-    let _profile_manager = profile_manager;
+    // Shared with D-Bus for GetMenuLayout (per-app profile, dynamic slices)
+    let window_tracker = Arc::new(window_tracker);
+    let profile_manager = Arc::new(RwLock::new(profile_manager));
+
+    // Overlay liveness tracking (RegisterOverlay/Heartbeat)
+    let overlay_monitor = new_shared_overlay_monitor();
+
+    // Per-app DPI switching (profiles with a preferred dpi; needs hidraw)
+    if use_devices && window_tracker.is_available() {
+        let tracker = window_tracker.clone();
+        let profiles = profile_manager.clone();
+        let haptics = haptic_manager.clone();
+        spawn_supervised("app-dpi", move || {
+            start_app_dpi_switcher(tracker.clone(), profiles.clone(), haptics.clone())
+        });
+    }
+
+    let game_mode_tracker = window_tracker.clone();
+    let widget_state = (profile_manager.clone(), haptic_manager.clone());
+    let profile_switch_state = window_tracker
+        .is_available()
+        .then(|| (window_tracker.clone(), profile_manager.clone(), haptic_manager.clone()));
 
-    // Create channel for gesture events
-    let (event_tx, mut event_rx) = mpsc::channel::<GestureEvent>(32);
+    // Initialize D-Bus service with battery state, config, haptic manager, overlay monitor,
+    // profiles and window tracker
+    let dbus_state = DbusServiceState::new(
+        battery_state.clone(),
+        shared_config.clone(),
+        haptic_manager,
+        overlay_monitor.clone(),
+        profile_manager,
+        window_tracker,
+    );
+    match serve_dbus_service(&dbus_state).await {
+        Ok(conn) => {
+            info!("D-Bus service initialized successfully");
+            session_bus::publish(conn);
+        }
+        Err(e) => {
+            error!("Failed to initialize D-Bus service: {}", e);
+            return Err(e.into());
+        }
+    }
 
-    // Check if logid is available - if so, use it exclusively to avoid duplicate events
-    let logid_available = LogidHandler::find_logid_device().is_ok();
+    // Reconnect and serve the objects again if the session bus restarts
+    tokio::spawn(session_bus::start_session_bus_watcher(move || {
+        let state = dbus_state.clone();
+        async move { serve_dbus_service(&state).await }
+    }));
 
-    if logid_available {
-        info!("LogiOps (logid) detected - using logid handler exclusively");
-    } else {
-        info!("LogiOps not detected - using evdev/hidraw handlers");
+    // Unix-socket fast path for hover tracking (path announced as FastPathSocket)
+    if shared_config.read().unwrap().fast_path.enabled {
+        if let Err(e) = fast_path::start_fast_path() {
+            warn!("Fast-path socket unavailable, overlays will use D-Bus only: {}", e);
+        }
     }
 
-    // Spawn the HID++ hidraw handler (for diverted button events via HID++ protocol)
-    // Only if logid is NOT available
-    let hidraw_handle = if !logid_available {
-        let hidraw_tx = event_tx.clone();
-        Some(tokio::spawn(async move {
-            run_hidraw_loop(hidraw_tx).await
-        }))
-    } else {
-        None
+    // Spawn automatic profile switching (announces switches for profiles that opt in)
+    if let Some((tracker, profiles, haptics)) = profile_switch_state {
+        spawn_on_session_bus("profile-switch", move |connection| {
+            start_profile_switcher(connection, tracker.clone(), profiles.clone(), haptics.clone())
+        });
+    }
+
+    // Spawn widget property publisher (org.kde.juhradialmx.Widget PropertiesChanged)
+    {
+        let battery = battery_state.clone();
+        let (profiles, haptics) = widget_state;
+        spawn_on_session_bus("widget", move |connection| {
+            start_widget_publisher(connection, battery.clone(), haptics.clone(), profiles.clone())
+        });
+    }
+
+    // Spawn connection monitor (ConnectionChanged on connect/disconnect or link degradation)
+    {
+        let haptics = haptic_manager_for_battery.clone();
+        spawn_on_session_bus("connection-monitor", move |connection| {
+            start_connection_monitor(connection, haptics.clone())
+        });
+    }
+
+    // Spawn game-mode monitor (suppresses the trigger or requests a minimal theme while gaming)
+    {
+        let config = shared_config.clone();
+        spawn_on_session_bus("game-mode", move |connection| {
+            start_game_mode_monitor(connection, game_mode_tracker.clone(), config.clone())
+        });
+    }
+
+    // Spawn KDE activity tracking (per-activity profiles)
+    spawn_on_session_bus("activities", start_activity_tracking);
+
+    // Spawn theme watcher (ThemeListChanged when themes are installed or removed)
+    spawn_on_session_bus("theme-watcher", start_theme_watcher);
+
+    // Spawn text entry signals (TextEntryRequested when a text_entry slice runs)
+    spawn_on_session_bus("text-entry", start_text_entry_signals);
+
+    // Spawn action history signals (ActionHistoryChanged for the widget's activity feed)
+    spawn_on_session_bus("action-history", start_action_history_signals);
+
+    // Spawn battery saver policy (reduces haptics/polling/DPI while the battery is low)
+    let battery_saver_handle = {
+        let battery = battery_state.clone();
+        let config = shared_config.clone();
+        let haptics = haptic_manager_for_battery.clone();
+        spawn_supervised("battery-saver", move || {
+            start_battery_saver(battery.clone(), config.clone(), haptics.clone())
+        })
     };
 
-    // Spawn the evdev handler as fallback (for non-diverted button events)
-    // Only if logid is NOT available
-    let evdev_handle = if !logid_available {
-        let evdev_tx = event_tx.clone();
-        Some(tokio::spawn(async move {
-            run_evdev_loop(evdev_tx).await
+    // Keep the HID++ link warm so the first haptic after idle doesn't miss (needs hidraw)
+    if use_devices {
+        let haptics = haptic_manager_for_battery.clone();
+        spawn_supervised("haptic-keeper", move || start_haptic_keeper(haptics.clone()));
+    }
+
+    // Watch for Solaar fighting over the hidraw device (needs hidraw)
+    if use_devices {
+        spawn_on_session_bus("solaar", start_solaar_monitor);
+    }
+
+    // Keep the KWin cursor script loaded across KWin restarts (Plasma only)
+    let on_kwin = juhradiald::compositor::backend().name() == "kwin";
+    if on_kwin {
+        spawn_on_session_bus("kwin-scripts", start_kwin_script_manager);
+    }
+
+    // Mirror desktop notifications as haptics (opt-in)
+    if use_devices && shared_config.read().unwrap().notification_haptics.enabled {
+        let config = shared_config.clone();
+        let haptics = haptic_manager_for_battery.clone();
+        spawn_supervised("notification-haptics", move || {
+            start_notification_haptics(config.clone(), haptics.clone())
+        });
+    }
+
+    // Spawn metrics exporter (Prometheus endpoint / textfile collector)
+    #[cfg(feature = "metrics")]
+    {
+        let metrics_config = shared_config.read().map(|c| c.metrics.clone()).unwrap_or_default();
+        if metrics_config.enabled {
+            tokio::spawn(metrics::start_metrics_exporter(metrics_config, battery_state.clone()));
+        }
+    }
+
+    // Spawn battery status updater (shares HidppDevice with haptic via SharedHapticManager)
+    let battery_handle = (!mouseless).then(|| {
+        spawn_supervised("battery", move || {
+            start_battery_updater_shared(battery_state.clone(), haptic_manager_for_battery.clone())
+        })
+    });
+
+    // Portal mode: inject shortcut keys via the RemoteDesktop portal
+    if portal_mode {
+        spawn_on_session_bus("remote-desktop", |connection| async move {
+            match init_remote_desktop(&connection).await {
+                Ok(()) => info!("RemoteDesktop portal ready for key injection"),
+                Err(e) => warn!("RemoteDesktop portal unavailable, shortcut actions disabled: {}", e),
+            }
+        });
+    }
+
+    // Spawn overlay heartbeat monitor (resets menu state if the overlay dies)
+    let overlay_handle = {
+        let monitor = overlay_monitor.clone();
+        let config = shared_config.clone();
+        spawn_on_session_bus("overlay-monitor", move |connection| {
+            start_overlay_monitor(monitor.clone(), connection, config.clone())
+        })
+    };
+
+    // Built-in layer-shell overlay (replaces the external overlay process)
+    #[cfg(feature = "overlay")]
+    if args.builtin_overlay {
+        start_builtin_overlay(&shared_config);
+    }
+
+    // Create channel for gesture events
+    let (event_tx, event_rx) = mpsc::channel::<GestureEvent>(32);
+
+    // Portal and mouse-less mode: no /dev scanning - the trigger comes from the GlobalShortcuts portal
+    let portal_handle = if portal_mode || mouseless {
+        let portal_tx = event_tx.clone();
+        let trigger = shared_config.read().unwrap().portal.trigger_shortcut.clone();
+        Some(spawn_on_session_bus("portal-trigger", move |connection| {
+            run_portal_trigger_loop(portal_tx.clone(), connection, trigger.clone())
         }))
     } else {
         None
     };
 
-    // Spawn the logid handler (for F19/F20 keypresses from logid)
-    // Only if logid IS available
-    let logid_handle = if logid_available {
-        Some(tokio::spawn(async move {
-            run_logid_loop(event_tx).await
-        }))
-    } else {
+    // Optional touchpad trigger: three-finger hold, alongside the mouse
+    let touchpad = shared_config.read().unwrap().touchpad_trigger.clone();
+    if touchpad.enabled && !portal_mode {
+        let touchpad_tx = event_tx.clone();
+        spawn_supervised("touchpad", move || start_touchpad_trigger(touchpad_tx.clone(), touchpad.clone()));
+    }
+
+    // Optional stylus trigger: pen barrel button as a tool palette
+    let stylus = shared_config.read().unwrap().stylus_trigger.clone();
+    if stylus.enabled && !portal_mode {
+        let stylus_tx = event_tx.clone();
+        spawn_supervised("stylus", move || start_stylus_trigger(stylus_tx.clone(), stylus.clone()));
+    }
+
+    // Device event sources: logid if it is running, otherwise evdev/hidraw.
+    // The arbiter follows logid starting or stopping later, so exactly one
+    // source feeds gesture events at any time.
+    let native_divert = use_devices && shared_config.read().unwrap().native_divert;
+    let arbiter = if !use_devices {
+        info!("Using GlobalShortcuts portal trigger, skipping device handlers");
         None
+    } else {
+        let initial = InputSource::detect();
+        match initial {
+            InputSource::Logid => info!("LogiOps (logid) detected - using logid handler exclusively"),
+            InputSource::Native => info!("LogiOps not detected - using evdev/hidraw handlers"),
+        }
+        let arbiter = Arc::new(InputArbiter::new(initial, event_tx.clone()));
+        {
+            let arbiter = arbiter.clone();
+            spawn_supervised("input-arbiter", move || start_input_arbiter(arbiter.clone()));
+        }
+        {
+            let arbiter = arbiter.clone();
+            let haptics = native_divert.then(|| haptic_manager_for_divert.clone());
+            let menu_grab = shared_config.read().unwrap().menu_grab.clone();
+            let trigger = shared_config.read().unwrap().evdev_trigger.clone();
+            let button_menus = shared_config.read().unwrap().button_menus.clone();
+            if let Some(Ok(mut manager)) = haptics.as_ref().map(|h| h.lock()) {
+                manager.set_menu_button_cids(button_menus.iter().map(|b| b.cid).collect());
+            }
+            tokio::spawn(run_input_sources(arbiter, haptics, menu_grab, trigger, button_menus));
+        }
+        Some(arbiter)
     };
 
-    // Get screen bounds for edge clamping (query once at startup)
-    let screen_bounds = get_screen_bounds();
+    // Get screen bounds for edge clamping (kept current by the screen watcher)
+    let screen_bounds = screen_watcher::refresh();
     info!("Screen bounds: {}x{}", screen_bounds.width, screen_bounds.height);
+    spawn_on_session_bus("screen-watcher", screen_watcher::start_screen_watcher);
 
     // Spawn event processing task with D-Bus connection
-    let event_handle = tokio::spawn(async move {
-        process_gesture_events(&mut event_rx, &dbus_connection, &screen_bounds).await
+    // The receiver outlives restarts of the processing task
+    let event_config = shared_config.clone();
+    let event_rx = Arc::new(tokio::sync::Mutex::new(event_rx));
+    let event_handle = spawn_on_session_bus("gesture-events", move |connection| {
+        let event_rx = event_rx.clone();
+        let monitor = overlay_monitor.clone();
+        let config = event_config.clone();
+        async move {
+            let mut event_rx = event_rx.lock().await;
+            process_gesture_events(&mut event_rx, &connection, &monitor, &config).await
+        }
     });
 
     // TODO: Initialize remaining components
@@ -212,22 +490,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Wait for shutdown signal
     // Use async block to handle Option handles properly
-    let wait_hidraw = async {
-        if let Some(handle) = hidraw_handle {
+    let wait_portal = async {
+        if let Some(handle) = portal_handle {
             handle.await
         } else {
             std::future::pending().await
         }
     };
-    let wait_evdev = async {
-        if let Some(handle) = evdev_handle {
-            handle.await
-        } else {
-            std::future::pending().await
-        }
-    };
-    let wait_logid = async {
-        if let Some(handle) = logid_handle {
+    let wait_battery = async {
+        if let Some(handle) = battery_handle {
             handle.await
         } else {
             std::future::pending().await
@@ -238,29 +509,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         _ = tokio::signal::ctrl_c() => {
             info!("Shutdown signal received, exiting...");
         }
-        result = wait_hidraw => {
+        result = wait_portal => {
             if let Err(e) = result {
-                error!("hidraw task panicked: {:?}", e);
+                error!("GlobalShortcuts portal task panicked: {:?}", e);
             }
         }
-        result = wait_evdev => {
+        result = event_handle => {
             if let Err(e) = result {
-                error!("evdev task panicked: {:?}", e);
+                error!("Event processing task panicked: {:?}", e);
             }
         }
-        result = wait_logid => {
+        result = wait_battery => {
             if let Err(e) = result {
-                error!("logid task panicked: {:?}", e);
+                error!("Battery updater task panicked: {:?}", e);
             }
         }
-        result = event_handle => {
+        result = battery_saver_handle => {
             if let Err(e) = result {
-                error!("Event processing task panicked: {:?}", e);
+                error!("Battery saver task panicked: {:?}", e);
             }
         }
-        result = battery_handle => {
+        result = overlay_handle => {
             if let Err(e) = result {
-                error!("Battery updater task panicked: {:?}", e);
+                error!("Overlay monitor task panicked: {:?}", e);
+            }
+        }
+    }
+
+    // Leave nothing behind in KWin
+    if let Some(connection) = session_bus::current().filter(|_| on_kwin) {
+        kwin_scripts::unload_all(&connection).await;
+    }
+
+    // Hand the gesture button back to the mouse's default behaviour
+    if native_divert && arbiter.is_some_and(|a| a.active() == InputSource::Native) {
+        if let Ok(mut manager) = haptic_manager_for_divert.lock() {
+            if let Err(e) = manager.set_gesture_divert(false) {
+                warn!("Failed to release gesture button divert: {}", e);
             }
         }
     }
@@ -295,18 +580,96 @@ fn list_logitech_devices() {
     }
 }
 
+/// Write `<interface>.xml` for each D-Bus interface (e.g. for /usr/share/dbus-1/interfaces)
+fn dump_introspection(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for (interface, xml) in juhradiald::dbus::introspection_documents() {
+        let path = dir.join(format!("{}.xml", interface));
+        std::fs::write(&path, xml)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+/// Start each input source the first time the arbiter makes it active
+///
+/// Started sources keep running (their gate drops events while inactive),
+/// so switching back is instant. With `native_divert`, the gesture button
+/// (and the `button_menus` buttons) is diverted whenever the native source
+/// becomes active; it is left alone while logid manages the button
+/// (releasing it would undo logid's divert).
+async fn run_input_sources(
+    arbiter: Arc<InputArbiter>,
+    divert: Option<SharedHapticManager>,
+    menu_grab: MenuGrabConfig,
+    trigger: EvdevTriggerConfig,
+    button_menus: Vec<ButtonMenuConfig>,
+) {
+    let mut active = arbiter.subscribe();
+    let mut native_started = false;
+    let mut logid_started = false;
+
+    loop {
+        let source = *active.borrow_and_update();
+        match source {
+            InputSource::Native if !native_started => {
+                native_started = true;
+                // HID++ hidraw handler (diverted button events via HID++ protocol)
+                let hidraw_tx = arbiter.gate(InputSource::Native);
+                let button_menus = button_menus.clone();
+                spawn_supervised("hidraw", move || run_hidraw_loop(hidraw_tx.clone(), button_menus.clone()));
+                // evdev handler as fallback (non-diverted button events)
+                let evdev_tx = arbiter.gate(InputSource::Native);
+                let menu_grab = menu_grab.clone();
+                let trigger = trigger.clone();
+                spawn_supervised("evdev", move || {
+                    run_evdev_loop(evdev_tx.clone(), menu_grab.clone(), trigger.clone())
+                });
+            }
+            InputSource::Logid if !logid_started => {
+                logid_started = true;
+                // logid handler (F19/F20 keypresses)
+                let logid_tx = arbiter.gate(InputSource::Logid);
+                spawn_supervised("logid", move || run_logid_loop(logid_tx.clone()));
+            }
+            _ => {}
+        }
+
+        // Divert the gesture button ourselves (runtime-only) so the hidraw
+        // handler sees presses without Solaar/logid
+        match (&divert, source) {
+            (Some(haptics), InputSource::Native) => match haptics.lock().map(|mut m| m.set_gesture_divert(true)) {
+                Ok(Ok(())) => info!("Gesture button diverted via HID++ (runtime-only)"),
+                Ok(Err(e)) => warn!("Native gesture button divert unavailable: {}", e),
+                Err(_) => {}
+            },
+            (Some(_), InputSource::Logid) => info!("native_divert ignored - logid manages the gesture button"),
+            (None, _) => {}
+        }
+
+        if active.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
 /// Run the HID++ hidraw event loop for diverted buttons
 ///
 /// When buttons are diverted via HID++ configuration, they send HID++ notifications
 /// instead of evdev events. This handler reads from the hidraw device.
-async fn run_hidraw_loop(event_tx: mpsc::Sender<GestureEvent>) {
+async fn run_hidraw_loop(event_tx: mpsc::Sender<GestureEvent>, button_menus: Vec<ButtonMenuConfig>) {
     let mut handler = HidrawHandler::new(event_tx);
+    handler.set_menu_buttons(&button_menus);
+    let mut connected_before = false;
 
     loop {
         // Try to open and start listening
         match handler.open() {
             Ok(()) => {
                 info!("HID++ hidraw handler connected");
+                if std::mem::replace(&mut connected_before, true) {
+                    metrics::record_reconnect(metrics::Component::Hidraw);
+                }
 
                 // Run the event loop until error
                 match handler.start().await {
@@ -348,17 +711,23 @@ async fn run_hidraw_loop(event_tx: mpsc::Sender<GestureEvent>) {
 /// - Initial device detection
 /// - Polling for device when not found (2-second intervals)
 /// - Reconnection after device disconnect
-async fn run_evdev_loop(event_tx: mpsc::Sender<GestureEvent>) {
+async fn run_evdev_loop(event_tx: mpsc::Sender<GestureEvent>, menu_grab: MenuGrabConfig, trigger: EvdevTriggerConfig) {
     let mut handler = EvdevHandler::new(event_tx.clone());
+    handler.set_menu_grab(menu_grab);
+    handler.set_trigger(&trigger);
+    let mut connected_before = false;
 
     loop {
         // Try to find and connect to the device
-        match EvdevHandler::find_device() {
+        match handler.locate() {
             Ok(device_info) => {
                 info!(
-                    "Detected MX Master 4 at {:?} ({})",
+                    "Detected trigger device at {:?} ({})",
                     device_info.path, device_info.name
                 );
+                if std::mem::replace(&mut connected_before, true) {
+                    metrics::record_reconnect(metrics::Component::Evdev);
+                }
 
                 // Run the event loop until device disconnects
                 match handler.start().await {
@@ -380,7 +749,8 @@ async fn run_evdev_loop(event_tx: mpsc::Sender<GestureEvent>) {
             }
             Err(EvdevError::DeviceNotFound) => {
                 // Device not found, this is expected during polling
-                info!("Waiting for MX Master 4... (polling every {}s)", DEVICE_POLL_INTERVAL_SECS);
+                let device = trigger.device().unwrap_or("MX Master 4");
+                info!("Waiting for {}... (polling every {}s)", device, DEVICE_POLL_INTERVAL_SECS);
             }
             Err(EvdevError::PermissionDenied) => {
                 error!("Permission denied accessing input devices.");
@@ -396,6 +766,33 @@ async fn run_evdev_loop(event_tx: mpsc::Sender<GestureEvent>) {
     }
 }
 
+/// Start the built-in layer-shell overlay and its D-Bus bridge
+///
+/// Falls back to the external overlay (logs an error) if the compositor
+/// does not support wlr-layer-shell.
+#[cfg(feature = "overlay")]
+fn start_builtin_overlay(shared_config: &SharedConfig) {
+    use juhradiald::bundled_themes::{get_bundled_theme, get_default_theme};
+    use juhradiald::overlay::{run_dbus_bridge, spawn_layer_shell_overlay};
+
+    let theme_name = shared_config.read().map(|c| c.theme.clone()).unwrap_or_default();
+    let theme = get_bundled_theme(&theme_name).unwrap_or_else(get_default_theme);
+
+    match spawn_layer_shell_overlay(&theme) {
+        Ok(overlay) => {
+            info!("Using built-in layer-shell overlay");
+            tokio::spawn(async move {
+                if let Err(e) = run_dbus_bridge(overlay).await {
+                    error!("Built-in overlay D-Bus bridge failed: {}", e);
+                }
+            });
+        }
+        Err(e) => {
+            error!("Built-in overlay unavailable: {}", e);
+        }
+    }
+}
+
 /// Run the logid event loop for F19/F20 keypresses
 ///
 /// This handler listens to the LogiOps Virtual Input device for:
@@ -403,11 +800,17 @@ async fn run_evdev_loop(event_tx: mpsc::Sender<GestureEvent>) {
 /// - KEY_F20: Gesture button released
 async fn run_logid_loop(event_tx: mpsc::Sender<GestureEvent>) {
     let mut handler = LogidHandler::new(event_tx);
+    let mut connected_before = false;
 
     loop {
         match LogidHandler::find_logid_device() {
             Ok(_) => {
                 info!("LogiOps Virtual Input found, starting logid listener");
+                // Warn if logid.cfg never sends F19 for the gesture button
+                check_logid_config(Path::new(LOGID_CONFIG_PATH));
+                if std::mem::replace(&mut connected_before, true) {
+                    metrics::record_reconnect(metrics::Component::Logid);
+                }
 
                 match handler.start().await {
                     Ok(()) => {
@@ -438,6 +841,35 @@ async fn run_logid_loop(event_tx: mpsc::Sender<GestureEvent>) {
     }
 }
 
+/// Run the GlobalShortcuts portal trigger (portal mode)
+///
+/// Re-registers after portal errors. If the user declines the shortcut the
+/// loop stops retrying; the menu can still be shown via D-Bus (ShowMenu).
+async fn run_portal_trigger_loop(
+    event_tx: mpsc::Sender<GestureEvent>,
+    connection: zbus::Connection,
+    trigger: String,
+) {
+    let mut handler = GlobalShortcutsHandler::new(event_tx, &trigger);
+
+    loop {
+        match handler.start(&connection).await {
+            Ok(()) => {
+                warn!("GlobalShortcuts portal stream ended, re-registering...");
+            }
+            Err(PortalError::Cancelled) => {
+                warn!("GlobalShortcuts trigger declined - menu only available via D-Bus");
+                std::future::pending::<()>().await;
+            }
+            Err(e) => {
+                error!("GlobalShortcuts portal error: {}. Will retry...", e);
+            }
+        }
+
+        sleep(Duration::from_secs(DEVICE_POLL_INTERVAL_SECS)).await;
+    }
+}
+
 /// Process gesture events from the evdev handler
 ///
 /// Press triggers ydotool injection -> cursor_grabber catches -> emits ShowMenu
@@ -445,21 +877,129 @@ async fn run_logid_loop(event_tx: mpsc::Sender<GestureEvent>) {
 async fn process_gesture_events(
     event_rx: &mut mpsc::Receiver<GestureEvent>,
     dbus_connection: &zbus::Connection,
-    _screen_bounds: &ScreenBounds,
+    overlay_monitor: &SharedOverlayMonitor,
+    config: &SharedConfig,
 ) {
-    while let Some(event) = event_rx.recv().await {
-        match event {
-            GestureEvent::Pressed { x, y } => {
-                // HID++ hidraw handler provides cursor coordinates directly
-                info!(x, y, "Gesture button pressed - showing radial menu");
+    let mut multi_press = MultiPressDetector::new(0);
+    let mut cursor_moves = MoveCoalescer::new(0);
+    let mut debounce = PressDebouncer::new(&Default::default());
+    let mut taps = TapTracker::default();
+    let mut tap_injector: Option<TapInjector> = None;
+    // Press ignored because of game mode or chatter (its release/moves are dropped too)
+    let mut suppressed = false;
+    // Menu of the press the debouncer is holding back
+    let mut deferred_menu: Option<String> = None;
+
+    loop {
+        let flush_at = cursor_moves.deadline();
+        let press_due_at = debounce.deadline();
+        let event = tokio::select! {
+            event = event_rx.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            _ = async {
+                match flush_at {
+                    Some(at) => tokio::time::sleep_until(at.into()).await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                // Trailing update after a burst of coalesced moves
+                if let Some((x, y)) = cursor_moves.flush(std::time::Instant::now()) {
+                    if let Err(e) = emit_cursor_moved(dbus_connection, x, y).await {
+                        tracing::trace!("Failed to emit CursorMoved: {}", e);
+                    }
+                }
+                continue;
+            }
+            _ = async {
+                match press_due_at {
+                    Some(at) => tokio::time::sleep_until(at.into()).await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                // Held long enough to not be chatter
+                if let Some((x, y)) = debounce.take_due(std::time::Instant::now()) {
+                    let menu = deferred_menu.take();
+                    if handle_press(x, y, menu, &mut multi_press, dbus_connection, config).await {
+                        taps.on_menu_shown();
+                    }
+                }
+                continue;
+            }
+        };
 
-                // Emit ShowMenu via D-Bus
-                if let Err(e) = emit_menu_requested(dbus_connection, x, y).await {
-                    error!("Failed to emit ShowMenu signal: {}", e);
+        match event {
+            GestureEvent::Pressed { .. } if config.read().is_ok_and(|c| suppresses_trigger(&c.game_mode)) => {
+                info!("Gesture button pressed during game mode - ignored");
+                suppressed = true;
+            }
+            GestureEvent::Released { .. } | GestureEvent::CursorMoved { .. } | GestureEvent::Scrolled { .. } if suppressed => {
+                if matches!(event, GestureEvent::Released { .. }) {
+                    suppressed = false;
+                }
+            }
+            GestureEvent::Pressed { x, y, menu } => {
+                if let Ok(c) = config.read() {
+                    debounce.set_config(&c.press_debounce);
+                    ensure_tap_injector(&mut tap_injector, &c.tap_passthrough);
+                }
+                match debounce.on_press(x, y, std::time::Instant::now()) {
+                    PressOutcome::Accept => {
+                        if handle_press(x, y, menu, &mut multi_press, dbus_connection, config).await {
+                            taps.on_menu_shown();
+                        }
+                    }
+                    PressOutcome::Defer => deferred_menu = menu,
+                    PressOutcome::Ignore => {
+                        tracing::debug!("Gesture button pressed too soon after the last menu - ignored");
+                        suppressed = true;
+                    }
                 }
             }
             GestureEvent::Released { duration_ms } => {
+                if debounce.on_release(std::time::Instant::now()) == ReleaseOutcome::Drop {
+                    tracing::debug!(duration_ms, "Gesture button chatter - press/release dropped");
+                    continue;
+                }
                 info!(duration_ms, "Gesture button released");
+                multi_press.on_release(std::time::Instant::now(), duration_ms);
+                let tap_config = config.read().map(|c| c.tap_passthrough.clone()).unwrap_or_default();
+                let passthrough = taps.on_release(duration_ms, &tap_config);
+
+                // Deliver the final position before the menu resolves the selection
+                if let Some((x, y)) = cursor_moves.take_pending() {
+                    if let Err(e) = emit_cursor_moved(dbus_connection, x, y).await {
+                        tracing::trace!("Failed to emit CursorMoved: {}", e);
+                    }
+                }
+
+                if let Ok(mut monitor) = overlay_monitor.write() {
+                    monitor.set_menu_open(false);
+                }
+                let blind_menu = blind_mode::take();
+
+                if passthrough {
+                    // Quick tap that selected nothing: the button keeps its own function
+                    if let Err(e) = emit_menu_cancelled(dbus_connection).await {
+                        error!("Failed to emit MenuCancelled signal: {}", e);
+                    }
+                    if let Some(injector) = tap_injector.as_mut() {
+                        match injector.click() {
+                            Ok(()) => info!(key_code = injector.key_code(), "Gesture button tap passed through"),
+                            Err(e) => warn!("Tap passthrough failed: {}", e),
+                        }
+                    }
+                    continue;
+                }
+
+                // Blind menus have no overlay to hide; run the selection here
+                if let Some(menu) = blind_menu {
+                    if let Some(action) = menu.into_selection() {
+                        blind_mode::run(action);
+                    }
+                    continue;
+                }
 
                 // Emit HideMenu signal via D-Bus
                 // Overlay tracks duration internally for tap-to-toggle detection
@@ -467,16 +1007,116 @@ async fn process_gesture_events(
                     error!("Failed to emit HideMenu signal: {}", e);
                 }
             }
+            GestureEvent::Scrolled { delta } => {
+                if !overlay_monitor.read().is_ok_and(|m| m.is_menu_open()) {
+                    continue;
+                }
+                // Scrolling down shows the next page
+                if let Err(e) = request_page_change(dbus_connection, delta.signum()).await {
+                    tracing::debug!("Failed to change menu page: {}", e);
+                }
+            }
             GestureEvent::CursorMoved { x, y } => {
-                // Emit CursorMoved signal for overlay hover detection
+                // Nobody hovers while the menu is closed
+                if !overlay_monitor.read().is_ok_and(|m| m.is_menu_open()) {
+                    cursor_moves.take_pending();
+                    continue;
+                }
+
+                if blind_mode::is_open() {
+                    blind_mode::on_cursor_moved(x, y);
+                    continue;
+                }
+
+                // Fast-path clients get every move; D-Bus is rate-limited below
+                fast_path::publish(FastPathUpdate::Cursor { x, y });
+
+                if let Ok(c) = config.read() {
+                    cursor_moves.set_interval(c.cursor_update_interval_ms);
+                    taps.on_cursor_moved(x, y, c.slice_geometry.dead_zone_radius);
+                }
+
+                // Emit CursorMoved signal for overlay hover detection (rate-limited, latest wins)
                 // x, y are relative to button press point (menu center)
-                if let Err(e) = emit_cursor_moved(dbus_connection, x, y).await {
-                    // Don't log errors for every cursor move - too noisy
-                    tracing::trace!("Failed to emit CursorMoved: {}", e);
+                if let Some((x, y)) = cursor_moves.push(x, y, std::time::Instant::now()) {
+                    if let Err(e) = emit_cursor_moved(dbus_connection, x, y).await {
+                        // Don't log errors for every cursor move - too noisy
+                        tracing::trace!("Failed to emit CursorMoved: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Handle a (debounced) gesture button press: multi-press binding or the radial menu
+///
+/// Presses of a `button_menus` button (`menu` set) always open that menu;
+/// multi-press gestures belong to the gesture button.
+///
+/// Returns true if a radial menu was requested.
+async fn handle_press(
+    x: i32,
+    y: i32,
+    menu: Option<String>,
+    multi_press: &mut MultiPressDetector,
+    dbus_connection: &zbus::Connection,
+    config: &SharedConfig,
+) -> bool {
+    if let Some(menu) = menu {
+        info!(x, y, menu = %menu, "Menu button pressed - showing its radial menu");
+        if let Err(e) = emit_menu_requested_by_id(dbus_connection, x, y, &menu).await {
+            error!("Failed to emit ShowMenuById: {}", e);
+        }
+        return true;
+    }
+
+    let multi_press_config = config.read().map(|c| c.multi_press.clone()).unwrap_or_default();
+    let binding = if multi_press_config.enabled {
+        multi_press.set_interval(multi_press_config.interval_ms);
+        let count = multi_press.on_press(std::time::Instant::now());
+        binding_for(&multi_press_config, count).cloned()
+    } else {
+        None
+    };
+
+    match binding {
+        Some(PressBinding::WindowSwitcher) => {
+            info!(x, y, "Gesture button multi-press - showing window switcher");
+            if let Err(e) = emit_menu_requested_with_mode(dbus_connection, x, y, "window_switcher").await {
+                error!("Failed to emit ShowMenuWithMode: {}", e);
+            }
+        }
+        Some(PressBinding::Action(action)) => {
+            info!(x, y, "Gesture button multi-press - running bound action");
+            tokio::spawn(async move {
+                if let Err(e) = ActionExecutor::execute(&action).await {
+                    error!("Multi-press action failed: {}", e);
                 }
+            });
+        }
+        _ => {
+            // HID++ hidraw handler provides cursor coordinates directly
+            info!(x, y, "Gesture button pressed - showing radial menu");
+
+            // Emit ShowMenu via D-Bus
+            if let Err(e) = emit_menu_requested(dbus_connection, x, y).await {
+                error!("Failed to emit ShowMenu signal: {}", e);
             }
+            return true;
         }
     }
+    false
+}
+
+/// Create (or re-create for a new key code) the tap passthrough device
+fn ensure_tap_injector(injector: &mut Option<TapInjector>, config: &TapPassthroughConfig) {
+    if !config.enabled || injector.as_ref().is_some_and(|i| i.key_code() == config.key_code) {
+        return;
+    }
+    *injector = TapInjector::new(config.key_code)
+        .map_err(|e| warn!("Could not create tap passthrough device: {}", e))
+        .ok();
 }
 
 /// Emit MenuRequested signal via D-Bus
@@ -506,6 +1146,70 @@ async fn emit_menu_requested(
     Ok(())
 }
 
+/// Request an alternate menu (e.g. window switcher) via ShowMenuWithMode
+async fn emit_menu_requested_with_mode(
+    connection: &zbus::Connection,
+    x: i32,
+    y: i32,
+    mode: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use zbus::proxy::Proxy;
+
+    let proxy = Proxy::new(
+        connection,
+        DBUS_NAME,
+        DBUS_PATH,
+        "org.kde.juhradialmx.Daemon",
+    )
+    .await?;
+
+    proxy.call_method("ShowMenuWithMode", &(x, y, mode)).await?;
+
+    Ok(())
+}
+
+/// Request one of the profile's menus via ShowMenuById
+async fn emit_menu_requested_by_id(
+    connection: &zbus::Connection,
+    x: i32,
+    y: i32,
+    menu_id: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use zbus::proxy::Proxy;
+
+    let proxy = Proxy::new(
+        connection,
+        DBUS_NAME,
+        DBUS_PATH,
+        "org.kde.juhradialmx.Daemon",
+    )
+    .await?;
+
+    proxy.call_method("ShowMenuById", &(x, y, menu_id)).await?;
+
+    Ok(())
+}
+
+/// Step the open menu's page via ChangePage
+async fn request_page_change(
+    connection: &zbus::Connection,
+    delta: i32,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use zbus::proxy::Proxy;
+
+    let proxy = Proxy::new(
+        connection,
+        DBUS_NAME,
+        DBUS_PATH,
+        "org.kde.juhradialmx.Daemon",
+    )
+    .await?;
+
+    proxy.call_method("ChangePage", &(delta,)).await?;
+
+    Ok(())
+}
+
 /// Emit HideMenu signal via D-Bus (Story 2.7)
 ///
 /// Emits HideMenu signal to dismiss the overlay.
@@ -526,6 +1230,20 @@ async fn emit_hide_menu(
     Ok(())
 }
 
+/// Emit MenuCancelled signal via D-Bus (tap passed through to applications)
+async fn emit_menu_cancelled(
+    connection: &zbus::Connection,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    connection.emit_signal(
+        None::<&str>,
+        DBUS_PATH,
+        "org.kde.juhradialmx.Daemon",
+        "MenuCancelled",
+        &(),
+    ).await?;
+    Ok(())
+}
+
 /// Emit CursorMoved signal via D-Bus
 ///
 /// Broadcasts cursor position updates for overlay hover detection.
@@ -565,6 +1283,7 @@ mod tests {
         assert_eq!(args.config, "~/.config/juhradial/config.json");
         assert!(!args.verbose);
         assert!(!args.list_devices);
+        assert!(args.dump_introspection.is_none());
     }
 
     #[test]
@@ -584,11 +1303,11 @@ mod tests {
         let (tx, mut rx) = mpsc::channel::<GestureEvent>(8);
 
         // Send press event
-        tx.send(GestureEvent::Pressed { x: 100, y: 200 }).await.unwrap();
+        tx.send(GestureEvent::Pressed { x: 100, y: 200, menu: None }).await.unwrap();
 
         // Receive and verify
         let event = rx.recv().await.unwrap();
-        assert!(matches!(event, GestureEvent::Pressed { x: 100, y: 200 }));
+        assert!(matches!(event, GestureEvent::Pressed { x: 100, y: 200, menu: None }));
 
         // Send release event
         tx.send(GestureEvent::Released { duration_ms: 500 }).await.unwrap();
@@ -604,14 +1323,14 @@ mod tests {
 
         // Simulate 5 rapid press/release cycles
         for i in 0..5 {
-            tx.send(GestureEvent::Pressed { x: i * 10, y: i * 10 }).await.unwrap();
+            tx.send(GestureEvent::Pressed { x: i * 10, y: i * 10, menu: None }).await.unwrap();
             tx.send(GestureEvent::Released { duration_ms: 50 + (i as u64 * 10) }).await.unwrap();
         }
 
         // Verify all 10 events are received in order
         for i in 0..5 {
             let press = rx.recv().await.unwrap();
-            assert!(matches!(press, GestureEvent::Pressed { x, y } if x == i * 10 && y == i * 10));
+            assert!(matches!(press, GestureEvent::Pressed { x, y, .. } if x == i * 10 && y == i * 10));
 
             let release = rx.recv().await.unwrap();
             assert!(matches!(release, GestureEvent::Released { duration_ms } if duration_ms == 50 + (i as u64 * 10)));
@@ -652,4 +1371,60 @@ mod tests {
         assert_eq!(clamped.x, 500);
         assert_eq!(clamped.y, 500);
     }
+
+    #[cfg(feature = "test-support")]
+    #[tokio::test]
+    #[ignore] // Needs /dev/uinput and dbus-daemon - run with `cargo test --features test-support -- --ignored`
+    async fn test_virtual_mouse_to_dbus_signals() {
+        use juhradiald::dbus::serve_dbus_service_on;
+        use juhradiald::test_support::{SessionBus, Step, VirtualMouse};
+        use tokio_stream::StreamExt;
+
+        // Private bus, so a daemon running in the desktop session is not disturbed
+        let bus = SessionBus::start().expect("dbus-daemon");
+
+        let config = juhradiald::config::new_shared_config();
+        let overlay_monitor = new_shared_overlay_monitor();
+        let state = DbusServiceState::new(
+            new_shared_state(),
+            config.clone(),
+            new_shared_haptic_manager(&Default::default()),
+            overlay_monitor.clone(),
+            Arc::new(RwLock::new(ProfileManager::new())),
+            Arc::new(WindowTracker::new().await),
+        );
+        let connection = serve_dbus_service_on(&state, bus.builder().unwrap())
+            .await
+            .expect("D-Bus service on the private bus");
+
+        let client = bus.connect().await.unwrap();
+        let proxy = zbus::Proxy::new(&client, DBUS_NAME, DBUS_PATH, "org.kde.juhradialmx.Daemon").await.unwrap();
+        let mut requested = proxy.receive_signal("MenuRequested").await.unwrap();
+        let mut hidden = proxy.receive_signal("HideMenu").await.unwrap();
+
+        let mut mouse = VirtualMouse::new().expect("uinput access");
+        let (tx, mut rx) = mpsc::channel(32);
+        let mut handler = EvdevHandler::new(tx);
+        handler.pin_device(mouse.path().to_path_buf());
+        tokio::spawn(async move { handler.start().await });
+        tokio::spawn(async move { process_gesture_events(&mut rx, &connection, &overlay_monitor, &config).await });
+
+        mouse
+            .play(&[
+                Step::Wait(Duration::from_millis(200)),
+                Step::Press,
+                Step::Move(20, -20),
+                Step::Wait(Duration::from_millis(300)),
+                Step::Release,
+            ])
+            .await
+            .unwrap();
+
+        let timeout = Duration::from_secs(2);
+        // On KWin the compositor script opens the menu on the real session bus
+        if juhradiald::compositor::backend().name() != "kwin" {
+            assert!(tokio::time::timeout(timeout, requested.next()).await.expect("MenuRequested").is_some());
+        }
+        assert!(tokio::time::timeout(timeout, hidden.next()).await.expect("HideMenu").is_some());
+    }
 }
@@ -0,0 +1,173 @@
+//! Exclusive grab of the mouse while the radial menu is open
+//!
+//! The MX Master 4 reports its gesture button as BTN_BACK, so without a
+//! grab every application sees a back press whenever the menu opens and
+//! browsers navigate back underneath it. The same happens when a side
+//! button is pressed while the menu is shown.
+//!
+//! With `menu_grab.enabled`, the evdev handler takes the mouse with
+//! EVIOCGRAB when it opens it and re-injects its events through a uinput
+//! virtual device, so pointer motion, clicks and the wheel keep working.
+//! The [`KeyFilter`] holds back the gesture button always and the side
+//! buttons while the menu is open. A button whose press already reached
+//! applications always gets its release, so nothing stays stuck.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::evdev::GESTURE_BUTTON_CODES;
+
+/// Name of the passthrough device (its vendor is not Logitech, so device
+/// detection never mistakes it for the mouse)
+pub const PASSTHROUGH_DEVICE_NAME: &str = "JuhRadial MX Passthrough";
+
+/// Side buttons held back while the menu is open
+pub const SIDE_BUTTON_CODES: &[u16] = &[
+    0x113, // BTN_SIDE
+    0x114, // BTN_EXTRA
+    0x115, // BTN_FORWARD
+    0x116, // BTN_BACK
+];
+
+/// Whether the overlay currently shows the menu
+static MENU_OPEN: AtomicBool = AtomicBool::new(false);
+
+/// Record whether the menu is shown (mirrored by the overlay monitor)
+///
+/// Covers menus opened through hidraw, logid or D-Bus, where the evdev
+/// handler never sees a gesture press.
+pub fn set_menu_open(open: bool) {
+    MENU_OPEN.store(open, Ordering::Relaxed);
+}
+
+/// Whether the menu is shown
+pub fn is_menu_open() -> bool {
+    MENU_OPEN.load(Ordering::Relaxed)
+}
+
+/// Decides which key events of the grabbed mouse reach applications
+#[derive(Debug, Default)]
+pub struct KeyFilter {
+    /// Hold back side buttons while the menu is open
+    block_side_buttons: bool,
+    /// Side buttons whose press was forwarded and whose release is pending
+    forwarded: HashSet<u16>,
+}
+
+impl KeyFilter {
+    /// Create a filter with nothing forwarded yet
+    pub fn new(block_side_buttons: bool) -> Self {
+        Self {
+            block_side_buttons,
+            forwarded: HashSet::new(),
+        }
+    }
+
+    /// Whether a key event (value 1 = press, 0 = release, 2 = repeat) is forwarded
+    pub fn forward(&mut self, code: u16, value: i32, menu_open: bool) -> bool {
+        // The gesture button opens the menu; it is never a back press
+        if GESTURE_BUTTON_CODES.contains(&code) {
+            return false;
+        }
+        if !self.block_side_buttons || !SIDE_BUTTON_CODES.contains(&code) {
+            return true;
+        }
+        match value {
+            1 if menu_open => false,
+            1 => {
+                self.forwarded.insert(code);
+                true
+            }
+            0 => self.forwarded.remove(&code),
+            _ => self.forwarded.contains(&code),
+        }
+    }
+}
+
+/// Active grab of the mouse with its passthrough device
+#[cfg(target_os = "linux")]
+pub struct MenuGrab {
+    /// Virtual clone of the mouse that applications read instead
+    passthrough: evdev::uinput::VirtualDevice,
+    /// Key filter
+    filter: KeyFilter,
+    /// Events of the current frame, written at SYN_REPORT
+    frame: Vec<evdev::InputEvent>,
+}
+
+#[cfg(target_os = "linux")]
+impl MenuGrab {
+    /// Create the passthrough device and grab `device`
+    ///
+    /// The grab ends when the device is closed.
+    pub fn start(device: &mut evdev::Device, config: &crate::config::MenuGrabConfig) -> std::io::Result<Self> {
+        let mut builder = evdev::uinput::VirtualDevice::builder()?.name(PASSTHROUGH_DEVICE_NAME);
+        if let Some(keys) = device.supported_keys() {
+            builder = builder.with_keys(keys)?;
+        }
+        if let Some(axes) = device.supported_relative_axes() {
+            builder = builder.with_relative_axes(axes)?;
+        }
+        let passthrough = builder.build()?;
+        device.grab()?;
+
+        tracing::info!("Mouse grabbed, events pass through {:?}", PASSTHROUGH_DEVICE_NAME);
+        Ok(Self {
+            passthrough,
+            filter: KeyFilter::new(config.block_side_buttons),
+            frame: Vec::new(),
+        })
+    }
+
+    /// Re-inject one event from the grabbed mouse
+    pub fn pass(&mut self, event: &evdev::InputEvent, menu_open: bool) -> std::io::Result<()> {
+        use evdev::{EventType, SynchronizationCode};
+
+        match event.event_type() {
+            EventType::SYNCHRONIZATION
+                if event.code() == SynchronizationCode::SYN_REPORT.0 && !self.frame.is_empty() =>
+            {
+                // emit() terminates the frame with its own SYN_REPORT
+                self.passthrough.emit(&self.frame)?;
+                self.frame.clear();
+            }
+            EventType::KEY if self.filter.forward(event.code(), event.value(), menu_open) => {
+                self.frame.push(*event);
+            }
+            EventType::RELATIVE => self.frame.push(*event),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_side_buttons_held_back_while_open() {
+        let mut filter = KeyFilter::new(true);
+        const BTN_LEFT: u16 = 0x110;
+        const BTN_FORWARD: u16 = 0x115;
+
+        // Gesture button never reaches applications
+        assert!(!filter.forward(GESTURE_BUTTON_CODES[0], 1, false));
+        // Clicks always do
+        assert!(filter.forward(BTN_LEFT, 1, true));
+
+        // Press while open is held back, and so is its release
+        assert!(!filter.forward(BTN_FORWARD, 1, true));
+        assert!(!filter.forward(BTN_FORWARD, 0, false));
+
+        // A press forwarded before the menu opened gets its release
+        assert!(filter.forward(BTN_FORWARD, 1, false));
+        assert!(filter.forward(BTN_FORWARD, 2, true));
+        assert!(filter.forward(BTN_FORWARD, 0, true));
+
+        let mut unblocked = KeyFilter::new(false);
+        assert!(unblocked.forward(BTN_FORWARD, 1, true));
+    }
+}
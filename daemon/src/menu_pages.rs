@@ -0,0 +1,92 @@
+//! Radial menu paging
+//!
+//! A profile can define more than 8 slices as extra `pages` (see
+//! [`crate::profiles::Profile`]). While the menu is open the daemon tracks
+//! which page is shown: scrolling the wheel or selecting a `page` slice
+//! steps through them (wrapping around) and every change is announced with
+//! the `PageChanged` signal. Each menu opens on its first page.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+/// Page state of the open menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MenuPager {
+    /// Page currently shown
+    page: usize,
+    /// Pages of the open menu (1 while closed)
+    count: usize,
+}
+
+impl Default for MenuPager {
+    fn default() -> Self {
+        Self { page: 0, count: 1 }
+    }
+}
+
+impl MenuPager {
+    /// Start a menu with `count` pages, on the first one
+    pub fn open(&mut self, count: usize) {
+        self.page = 0;
+        self.count = count.max(1);
+    }
+
+    /// Back to a single page (menu closed)
+    pub fn close(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Page currently shown
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// Number of pages of the open menu
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Move by `delta` pages, wrapping around; returns the new page if it changed
+    pub fn step(&mut self, delta: i32) -> Option<usize> {
+        let count = self.count as i64;
+        let page = (self.page as i64 + delta as i64).rem_euclid(count) as usize;
+        self.set(page)
+    }
+
+    /// Show `page`; returns it if it changed (None if unchanged or out of range)
+    pub fn set(&mut self, page: usize) -> Option<usize> {
+        if page >= self.count || page == self.page {
+            return None;
+        }
+        self.page = page;
+        Some(page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_wraps_around() {
+        let mut pager = MenuPager::default();
+        pager.open(3);
+        assert_eq!(pager.step(1), Some(1));
+        assert_eq!(pager.step(2), Some(0));
+        assert_eq!(pager.step(-1), Some(2));
+        assert_eq!(pager.set(2), None);
+        assert_eq!(pager.set(5), None);
+
+        // A reopened menu starts on its first page
+        pager.open(3);
+        assert_eq!(pager.page(), 0);
+    }
+
+    #[test]
+    fn test_single_page_never_changes() {
+        let mut pager = MenuPager::default();
+        assert_eq!(pager.step(1), None);
+        pager.open(0);
+        assert_eq!(pager.count(), 1);
+        assert_eq!(pager.step(-1), None);
+    }
+}
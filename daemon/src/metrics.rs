@@ -0,0 +1,359 @@
+//! Usage metrics for JuhRadial MX
+//!
+//! Counters are plain atomics recorded from the hot paths (menu open, action
+//! execution, haptic sends and their acknowledgements, device reconnects) and
+//! cost next to nothing when nobody reads them. [`render`] formats them in the Prometheus text
+//! exposition format.
+//!
+//! With the `metrics` feature the daemon can publish them, either over HTTP
+//! (`GET /metrics`) or as a node_exporter textfile-collector file, so the data
+//! can be graphed in Grafana.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::battery::BatteryState;
+
+/// Action latency histogram bucket upper bounds (seconds)
+const LATENCY_BUCKETS: [f64; 8] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0];
+
+/// Components that can reconnect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    /// HID++ haptic/battery device
+    Haptic,
+    /// evdev gesture button device
+    Evdev,
+    /// hidraw diverted-button device
+    Hidraw,
+    /// LogiOps virtual input device
+    Logid,
+    /// Session bus connection
+    SessionBus,
+}
+
+impl Component {
+    const ALL: [Component; 5] = [
+        Component::Haptic,
+        Component::Evdev,
+        Component::Hidraw,
+        Component::Logid,
+        Component::SessionBus,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Component::Haptic => "haptic",
+            Component::Evdev => "evdev",
+            Component::Hidraw => "hidraw",
+            Component::Logid => "logid",
+            Component::SessionBus => "session_bus",
+        }
+    }
+}
+
+/// Lock-free latency histogram
+struct Histogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_us: AtomicU64,
+}
+
+impl Histogram {
+    const fn new() -> Self {
+        Self {
+            buckets: [const { AtomicU64::new(0) }; LATENCY_BUCKETS.len()],
+            count: AtomicU64::new(0),
+            sum_us: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        // Buckets are stored non-cumulative and summed at render time
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|&le| secs <= le) {
+            self.buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+static MENU_INVOCATIONS: AtomicU64 = AtomicU64::new(0);
+static HAPTIC_SENDS: AtomicU64 = AtomicU64::new(0);
+static RECONNECTS: [AtomicU64; 5] = [const { AtomicU64::new(0) }; 5];
+static ACTION_LATENCY: Histogram = Histogram::new();
+
+/// Action executions keyed by (action type, result)
+static ACTIONS: Mutex<BTreeMap<(&'static str, &'static str), u64>> = Mutex::new(BTreeMap::new());
+
+/// Verified haptic waveforms keyed by (pattern, result)
+static HAPTIC_ACKS: Mutex<BTreeMap<(&'static str, &'static str), u64>> = Mutex::new(BTreeMap::new());
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+/// Record that the radial menu was shown
+pub fn record_menu_invocation() {
+    MENU_INVOCATIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Record an executed action with its result and latency
+pub fn record_action(kind: &'static str, ok: bool, elapsed: Duration) {
+    let result = if ok { "ok" } else { "error" };
+    if let Ok(mut actions) = ACTIONS.lock() {
+        *actions.entry((kind, result)).or_insert(0) += 1;
+    }
+    ACTION_LATENCY.observe(elapsed);
+}
+
+/// Record a haptic pattern/pulse sent to the device
+pub fn record_haptic_send() {
+    HAPTIC_SENDS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Record how the device answered a haptic waveform (`haptics.verify_ack`)
+///
+/// `result` is "ok", "error" or "timeout".
+pub fn record_haptic_ack(pattern: &'static str, result: &'static str) {
+    if let Ok(mut acks) = HAPTIC_ACKS.lock() {
+        *acks.entry((pattern, result)).or_insert(0) += 1;
+    }
+}
+
+/// Record a device reconnect
+pub fn record_reconnect(component: Component) {
+    RECONNECTS[component as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// Render all metrics in Prometheus text format
+pub fn render(battery: &BatteryState) -> String {
+    let mut out = String::new();
+
+    gauge(&mut out, "juhradial_battery_available", "Whether battery status is available", battery.available as u64 as f64);
+    gauge(&mut out, "juhradial_battery_percent", "Mouse battery level", battery.percentage as f64);
+    gauge(&mut out, "juhradial_battery_charging", "Whether the mouse is charging", battery.charging as u64 as f64);
+
+    counter_header(&mut out, "juhradial_menu_invocations_total", "Radial menu invocations");
+    let _ = writeln!(out, "juhradial_menu_invocations_total {}", MENU_INVOCATIONS.load(Ordering::Relaxed));
+
+    counter_header(&mut out, "juhradial_actions_total", "Executed actions by type and result");
+    if let Ok(actions) = ACTIONS.lock() {
+        for ((kind, result), count) in actions.iter() {
+            let _ = writeln!(out, "juhradial_actions_total{{type=\"{}\",result=\"{}\"}} {}", kind, result, count);
+        }
+    }
+
+    counter_header(&mut out, "juhradial_haptic_sends_total", "Haptic patterns sent to the device");
+    let _ = writeln!(out, "juhradial_haptic_sends_total {}", HAPTIC_SENDS.load(Ordering::Relaxed));
+
+    counter_header(&mut out, "juhradial_haptic_acks_total", "Verified haptic waveforms by pattern and device answer");
+    if let Ok(acks) = HAPTIC_ACKS.lock() {
+        for ((pattern, result), count) in acks.iter() {
+            let _ = writeln!(out, "juhradial_haptic_acks_total{{pattern=\"{}\",result=\"{}\"}} {}", pattern, result, count);
+        }
+    }
+
+    counter_header(&mut out, "juhradial_reconnects_total", "Reconnects by component");
+    for component in Component::ALL {
+        let _ = writeln!(
+            out,
+            "juhradial_reconnects_total{{component=\"{}\"}} {}",
+            component.label(),
+            RECONNECTS[component as usize].load(Ordering::Relaxed)
+        );
+    }
+
+    let _ = writeln!(out, "# HELP juhradial_action_latency_seconds Action execution latency");
+    let _ = writeln!(out, "# TYPE juhradial_action_latency_seconds histogram");
+    let mut cumulative = 0;
+    for (i, le) in LATENCY_BUCKETS.iter().enumerate() {
+        cumulative += ACTION_LATENCY.buckets[i].load(Ordering::Relaxed);
+        let _ = writeln!(out, "juhradial_action_latency_seconds_bucket{{le=\"{}\"}} {}", le, cumulative);
+    }
+    let count = ACTION_LATENCY.count.load(Ordering::Relaxed);
+    let _ = writeln!(out, "juhradial_action_latency_seconds_bucket{{le=\"+Inf\"}} {}", count);
+    let _ = writeln!(
+        out,
+        "juhradial_action_latency_seconds_sum {}",
+        ACTION_LATENCY.sum_us.load(Ordering::Relaxed) as f64 / 1_000_000.0
+    );
+    let _ = writeln!(out, "juhradial_action_latency_seconds_count {}", count);
+
+    out
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: f64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+fn counter_header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+}
+
+// ============================================================================
+// Exporter (feature `metrics`)
+// ============================================================================
+
+#[cfg(feature = "metrics")]
+pub use exporter::start_metrics_exporter;
+
+#[cfg(feature = "metrics")]
+mod exporter {
+    use std::path::Path;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    use crate::battery::SharedBatteryState;
+    use crate::config::MetricsConfig;
+
+    /// Run the configured exporters (HTTP and/or textfile) until the daemon exits
+    pub async fn start_metrics_exporter(config: MetricsConfig, battery: SharedBatteryState) {
+        let http = async {
+            if config.listen.is_empty() {
+                return std::future::pending().await;
+            }
+            if let Err(e) = serve_http(&config.listen, &battery).await {
+                tracing::error!(listen = %config.listen, "Metrics HTTP exporter failed: {}", e);
+            }
+            std::future::pending::<()>().await
+        };
+
+        let textfile = async {
+            if config.textfile_path.is_empty() {
+                return std::future::pending().await;
+            }
+            write_textfile_loop(Path::new(&config.textfile_path), config.textfile_interval_secs, &battery).await
+        };
+
+        tokio::join!(http, textfile);
+    }
+
+    /// Serve `GET /metrics` on the given address
+    async fn serve_http(listen: &str, battery: &SharedBatteryState) -> std::io::Result<()> {
+        let listener = TcpListener::bind(listen).await?;
+        tracing::info!(listen, "Metrics exporter listening on /metrics");
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let battery = battery.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(stream, &battery).await {
+                    tracing::debug!("Metrics request failed: {}", e);
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(mut stream: TcpStream, battery: &SharedBatteryState) -> std::io::Result<()> {
+        let mut buf = [0u8; 1024];
+        let n = stream.read(&mut buf).await?;
+        let request = String::from_utf8_lossy(&buf[..n]);
+
+        let response = if is_metrics_request(&request) {
+            let body = super::render(&*battery.read().await);
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        } else {
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+        };
+
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    /// Check the request line for `GET /metrics`
+    pub(super) fn is_metrics_request(request: &str) -> bool {
+        let mut parts = request.lines().next().unwrap_or("").split_whitespace();
+        matches!((parts.next(), parts.next()), (Some("GET"), Some(path)) if path == "/metrics" || path.starts_with("/metrics?"))
+    }
+
+    /// Periodically write metrics for node_exporter's textfile collector
+    ///
+    /// Writes to a temp file and renames it so the collector never reads a partial file.
+    async fn write_textfile_loop(path: &Path, interval_secs: u64, battery: &SharedBatteryState) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs.max(1)));
+        let tmp = path.with_extension("prom.tmp");
+        tracing::info!(path = %path.display(), "Metrics textfile exporter enabled");
+
+        loop {
+            interval.tick().await;
+            let body = super::render(&*battery.read().await);
+            let result = match tokio::fs::write(&tmp, body).await {
+                Ok(()) => tokio::fs::rename(&tmp, path).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::warn!(path = %path.display(), "Failed to write metrics textfile: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_contains_all_metrics() {
+        record_menu_invocation();
+        record_action("shortcut", true, Duration::from_millis(3));
+        record_haptic_send();
+        record_haptic_ack("happy_alert", "timeout");
+        record_reconnect(Component::Haptic);
+
+        let battery = BatteryState {
+            percentage: 80,
+            available: true,
+            ..BatteryState::default()
+        };
+        let text = render(&battery);
+
+        assert!(text.contains("juhradial_battery_percent 80"));
+        assert!(text.contains("# TYPE juhradial_menu_invocations_total counter"));
+        assert!(text.contains("juhradial_actions_total{type=\"shortcut\",result=\"ok\"}"));
+        assert!(text.contains("juhradial_reconnects_total{component=\"haptic\"}"));
+        assert!(text.contains("juhradial_haptic_acks_total{pattern=\"happy_alert\",result=\"timeout\"}"));
+        assert!(text.contains("juhradial_action_latency_seconds_bucket{le=\"+Inf\"}"));
+    }
+
+    #[test]
+    fn test_histogram_buckets_cumulative() {
+        let histogram = Histogram::new();
+        histogram.observe(Duration::from_micros(500));
+        histogram.observe(Duration::from_millis(20));
+        histogram.observe(Duration::from_secs(5));
+
+        assert_eq!(histogram.buckets[0].load(Ordering::Relaxed), 1);
+        assert_eq!(histogram.buckets[3].load(Ordering::Relaxed), 1);
+        // Above the largest bucket: only counted in +Inf
+        assert_eq!(histogram.buckets.iter().map(|b| b.load(Ordering::Relaxed)).sum::<u64>(), 2);
+        assert_eq!(histogram.count.load(Ordering::Relaxed), 3);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_is_metrics_request() {
+        assert!(exporter::is_metrics_request("GET /metrics HTTP/1.1\r\nHost: x\r\n"));
+        assert!(exporter::is_metrics_request("GET /metrics?x=1 HTTP/1.1\r\n"));
+        assert!(!exporter::is_metrics_request("GET / HTTP/1.1\r\n"));
+        assert!(!exporter::is_metrics_request("POST /metrics HTTP/1.1\r\n"));
+    }
+}
@@ -0,0 +1,120 @@
+//! Double- and triple-press detection for the gesture button
+//!
+//! A press continues a sequence when the previous press was a tap (released
+//! within the interval) and the new press starts within the interval after
+//! that release. The first press of a sequence always shows the normal menu
+//! immediately, so single presses get no added latency.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::time::{Duration, Instant};
+
+use crate::config::{MultiPressConfig, PressBinding};
+
+/// Longest recognised sequence (a 4th press starts over)
+pub const MAX_PRESS_COUNT: u8 = 3;
+
+/// Tracks press sequences
+#[derive(Debug)]
+pub struct MultiPressDetector {
+    /// Max tap length and max gap between taps
+    interval: Duration,
+    /// Presses in the current sequence
+    count: u8,
+    /// Release time of the last press, if it was a tap
+    last_tap_release: Option<Instant>,
+}
+
+impl MultiPressDetector {
+    /// Create a detector with the given interval
+    pub fn new(interval_ms: u64) -> Self {
+        Self {
+            interval: Duration::from_millis(interval_ms),
+            count: 0,
+            last_tap_release: None,
+        }
+    }
+
+    /// Update the interval (config hot-reload)
+    pub fn set_interval(&mut self, interval_ms: u64) {
+        self.interval = Duration::from_millis(interval_ms);
+    }
+
+    /// Register a press; returns its position in the sequence (1 = single)
+    pub fn on_press(&mut self, now: Instant) -> u8 {
+        let continues = self
+            .last_tap_release
+            .is_some_and(|released| now.duration_since(released) <= self.interval);
+        self.count = if continues { self.count % MAX_PRESS_COUNT + 1 } else { 1 };
+        self.last_tap_release = None;
+        self.count
+    }
+
+    /// Register a release after `duration_ms` held
+    pub fn on_release(&mut self, now: Instant, duration_ms: u64) {
+        if Duration::from_millis(duration_ms) <= self.interval {
+            self.last_tap_release = Some(now);
+        } else {
+            // A hold ends the sequence
+            self.last_tap_release = None;
+        }
+    }
+}
+
+/// Binding for the nth press of a sequence (None for single presses)
+pub fn binding_for(config: &MultiPressConfig, count: u8) -> Option<&PressBinding> {
+    let binding = match count {
+        2 => &config.double,
+        3 => &config.triple,
+        _ => return None,
+    };
+    (*binding != PressBinding::None).then_some(binding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_double_and_triple_press() {
+        let mut detector = MultiPressDetector::new(300);
+        let t0 = Instant::now();
+
+        assert_eq!(detector.on_press(t0), 1);
+        detector.on_release(t0 + ms(100), 100);
+        assert_eq!(detector.on_press(t0 + ms(250)), 2);
+        detector.on_release(t0 + ms(350), 100);
+        assert_eq!(detector.on_press(t0 + ms(500)), 3);
+        detector.on_release(t0 + ms(600), 100);
+        // Sequence wraps after the triple press
+        assert_eq!(detector.on_press(t0 + ms(700)), 1);
+    }
+
+    #[test]
+    fn test_hold_or_slow_gap_resets() {
+        let mut detector = MultiPressDetector::new(300);
+        let t0 = Instant::now();
+
+        detector.on_press(t0);
+        // Held too long to count as a tap
+        detector.on_release(t0 + ms(800), 800);
+        assert_eq!(detector.on_press(t0 + ms(900)), 1);
+
+        detector.on_release(t0 + ms(1000), 100);
+        // Gap longer than the interval
+        assert_eq!(detector.on_press(t0 + ms(1500)), 1);
+    }
+
+    #[test]
+    fn test_binding_for() {
+        let config = MultiPressConfig::default();
+        assert!(binding_for(&config, 1).is_none());
+        assert_eq!(binding_for(&config, 2), Some(&PressBinding::WindowSwitcher));
+        // Unbound triple press behaves like a single press
+        assert!(binding_for(&config, 3).is_none());
+    }
+}
@@ -0,0 +1,233 @@
+//! NetworkManager connection and Wi-Fi toggles for `network` / `wifi` actions
+//!
+//! ```json
+//! {"type": "network", "value": {"connection": "Work VPN", "op": "toggle",
+//!   "label_on": "VPN on", "label_off": "VPN off"}}
+//! {"type": "wifi", "value": "toggle"}
+//! ```
+//!
+//! `connection` is a NetworkManager connection name (as listed by
+//! `nmcli connection show`); `op` is `up`, `down` or `toggle` (the
+//! default). `wifi` switches the Wi-Fi radio. Menu layouts mark both slice
+//! types `active` and use `label_on`/`label_off` when given, see
+//! [`crate::slice_state`].
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+
+/// NetworkManager bus name and main object
+const NM_NAME: &str = "org.freedesktop.NetworkManager";
+const NM_PATH: &str = "/org/freedesktop/NetworkManager";
+const NM_SETTINGS_PATH: &str = "/org/freedesktop/NetworkManager/Settings";
+const NM_SETTINGS_INTERFACE: &str = "org.freedesktop.NetworkManager.Settings";
+const NM_SETTINGS_CONNECTION_INTERFACE: &str = "org.freedesktop.NetworkManager.Settings.Connection";
+const NM_ACTIVE_INTERFACE: &str = "org.freedesktop.NetworkManager.Connection.Active";
+
+/// How long menu preparation waits for NetworkManager
+const QUERY_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// What a `network` or `wifi` action does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkOp {
+    Up,
+    Down,
+    /// Down if active, otherwise up
+    #[default]
+    Toggle,
+}
+
+impl NetworkOp {
+    /// Whether the target should end up enabled, given its current state
+    pub fn enable(self, active: bool) -> bool {
+        match self {
+            NetworkOp::Up => true,
+            NetworkOp::Down => false,
+            NetworkOp::Toggle => !active,
+        }
+    }
+}
+
+/// `network` action payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkAction {
+    /// NetworkManager connection name
+    pub connection: String,
+    #[serde(default)]
+    pub op: NetworkOp,
+    /// Slice label while the connection is active
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label_on: Option<String>,
+    /// Slice label while it is not
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label_off: Option<String>,
+}
+
+/// Network action failure
+#[derive(Debug)]
+pub enum NetworkError {
+    /// No saved connection with this name
+    UnknownConnection(String),
+    /// D-Bus error talking to NetworkManager
+    DBus(zbus::Error),
+}
+
+impl std::fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::UnknownConnection(name) => write!(f, "No NetworkManager connection named {}", name),
+            NetworkError::DBus(e) => write!(f, "NetworkManager error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+impl From<zbus::Error> for NetworkError {
+    fn from(e: zbus::Error) -> Self {
+        NetworkError::DBus(e)
+    }
+}
+
+impl From<zbus::fdo::Error> for NetworkError {
+    fn from(e: zbus::fdo::Error) -> Self {
+        NetworkError::DBus(e.into())
+    }
+}
+
+/// Network state shown on menu slices
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkState {
+    /// Names of the active connections
+    pub active_connections: HashSet<String>,
+    /// Whether the Wi-Fi radio is on (None if unknown)
+    pub wifi_enabled: Option<bool>,
+}
+
+/// Current state; empty if NetworkManager does not answer within [`QUERY_TIMEOUT`]
+pub async fn state() -> NetworkState {
+    tokio::time::timeout(QUERY_TIMEOUT, query_state()).await.ok().flatten().unwrap_or_default()
+}
+
+async fn query_state() -> Option<NetworkState> {
+    let connection = crate::dbus::system_bus().await.ok()?;
+    let manager = zbus::Proxy::new(connection, NM_NAME, NM_PATH, NM_NAME).await.ok()?;
+    let mut active_connections = HashSet::new();
+    for (name, _) in active_connections_by_name(connection, &manager).await.ok()? {
+        active_connections.insert(name);
+    }
+    Some(NetworkState {
+        active_connections,
+        wifi_enabled: manager.get_property::<bool>("WirelessEnabled").await.ok(),
+    })
+}
+
+/// Bring a named connection up or down; returns whether it is active afterwards
+pub async fn apply(action: &NetworkAction) -> Result<bool, NetworkError> {
+    let connection = crate::dbus::system_bus().await?;
+    let manager = zbus::Proxy::new(connection, NM_NAME, NM_PATH, NM_NAME).await?;
+    let active = active_connections_by_name(connection, &manager)
+        .await?
+        .into_iter()
+        .find(|(name, _)| *name == action.connection)
+        .map(|(_, path)| path);
+
+    let enable = action.op.enable(active.is_some());
+    match (enable, active) {
+        (true, Some(_)) | (false, None) => {}
+        (true, None) => {
+            let saved = find_saved_connection(connection, &action.connection)
+                .await?
+                .ok_or_else(|| NetworkError::UnknownConnection(action.connection.clone()))?;
+            let root = ObjectPath::from_static_str_unchecked("/");
+            let _: OwnedObjectPath = manager.call("ActivateConnection", &(&saved, &root, &root)).await?;
+        }
+        (false, Some(path)) => {
+            manager.call_method("DeactivateConnection", &(&path,)).await?;
+        }
+    }
+    tracing::info!(connection = %action.connection, enable, "NetworkManager connection toggled");
+    Ok(enable)
+}
+
+/// Switch the Wi-Fi radio; returns whether it is on afterwards
+pub async fn apply_wifi(op: NetworkOp) -> Result<bool, NetworkError> {
+    let connection = crate::dbus::system_bus().await?;
+    let manager = zbus::Proxy::new(connection, NM_NAME, NM_PATH, NM_NAME).await?;
+    let enable = op.enable(manager.get_property::<bool>("WirelessEnabled").await?);
+    manager.set_property("WirelessEnabled", Value::from(enable)).await?;
+    tracing::info!(enable, "Wi-Fi toggled");
+    Ok(enable)
+}
+
+/// (name, active connection path) of every active connection
+async fn active_connections_by_name(
+    connection: &zbus::Connection,
+    manager: &zbus::Proxy<'_>,
+) -> Result<Vec<(String, OwnedObjectPath)>, NetworkError> {
+    let paths: Vec<OwnedObjectPath> = manager.get_property("ActiveConnections").await?;
+    let mut active = Vec::with_capacity(paths.len());
+    for path in paths {
+        let proxy = zbus::Proxy::new(connection, NM_NAME, path.clone(), NM_ACTIVE_INTERFACE).await?;
+        if let Ok(name) = proxy.get_property::<String>("Id").await {
+            active.push((name, path));
+        }
+    }
+    Ok(active)
+}
+
+/// Settings object of the saved connection with this name
+async fn find_saved_connection(
+    connection: &zbus::Connection,
+    name: &str,
+) -> Result<Option<OwnedObjectPath>, NetworkError> {
+    type Settings = HashMap<String, HashMap<String, OwnedValue>>;
+
+    let settings = zbus::Proxy::new(connection, NM_NAME, NM_SETTINGS_PATH, NM_SETTINGS_INTERFACE).await?;
+    let paths: Vec<OwnedObjectPath> = settings.call("ListConnections", &()).await?;
+    for path in paths {
+        let saved = zbus::Proxy::new(connection, NM_NAME, path.clone(), NM_SETTINGS_CONNECTION_INTERFACE).await?;
+        let Ok(values) = saved.call::<_, _, Settings>("GetSettings", &()).await else {
+            continue;
+        };
+        if connection_id(&values).as_deref() == Some(name) {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+/// `connection.id` of a settings dictionary
+fn connection_id(settings: &HashMap<String, HashMap<String, OwnedValue>>) -> Option<String> {
+    let id = settings.get("connection")?.get("id")?;
+    String::try_from(id.try_clone().ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_op_and_payload() {
+        assert!(NetworkOp::Toggle.enable(false));
+        assert!(!NetworkOp::Toggle.enable(true));
+        assert!(NetworkOp::Up.enable(true));
+        assert!(!NetworkOp::Down.enable(false));
+
+        let action: NetworkAction = serde_json::from_str(r#"{"connection": "Work VPN", "label_on": "VPN on"}"#).unwrap();
+        assert_eq!(action.op, NetworkOp::Toggle);
+        assert_eq!(action.label_on.as_deref(), Some("VPN on"));
+        assert_eq!(action.label_off, None);
+
+        let settings = HashMap::from([(
+            "connection".to_string(),
+            HashMap::from([("id".to_string(), OwnedValue::from(zbus::zvariant::Str::from("Work VPN")))]),
+        )]);
+        assert_eq!(connection_id(&settings).as_deref(), Some("Work VPN"));
+    }
+}
@@ -0,0 +1,217 @@
+//! Night light / color temperature control for `night_light` actions
+//!
+//! `{"type": "night_light", "value": "toggle"}` (or `"warmer"`, `"cooler"`)
+//! changes the screen's color temperature without shell snippets:
+//!
+//! - **KDE** (NightLight D-Bus, `ColorCorrect` before Plasma 6): toggle
+//!   inhibits/uninhibits night light through the daemon's own bus
+//!   connection, so KWin lifts the inhibition if the daemon exits. Warmer
+//!   and cooler step `NightTemperature` in kwinrc and ask KWin to reload.
+//! - **Elsewhere**: toggle sends `SIGUSR1` to a running gammastep or
+//!   wlsunset (both switch modes on it). Warmer and cooler run
+//!   `gammastep -P -O <kelvin>` in one-shot mode, which replaces whatever
+//!   gamma a running gammastep daemon has set.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+/// Kelvin changed per warmer/cooler step
+pub const STEP_KELVIN: u32 = 500;
+
+/// Warmest temperature stepped to (KDE's lower limit)
+pub const MIN_KELVIN: u32 = 1000;
+
+/// Neutral daylight temperature, the coolest step
+pub const NEUTRAL_KELVIN: u32 = 6500;
+
+/// KDE's default night temperature, used when kwinrc has none
+const KDE_DEFAULT_KELVIN: u32 = 4500;
+
+/// KWin night light objects: Plasma 6, then Plasma 5
+const KWIN_NIGHT_LIGHT: &[(&str, &str)] = &[
+    ("/org/kde/KWin/NightLight", "org.kde.KWin.NightLight"),
+    ("/ColorCorrect", "org.kde.kwin.ColorCorrect"),
+];
+
+/// Night light change requested by a `night_light` action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NightLightStep {
+    /// Switch night light off (inhibit) or back on
+    Toggle,
+    /// Lower the color temperature by [`STEP_KELVIN`]
+    Warmer,
+    /// Raise the color temperature by [`STEP_KELVIN`]
+    Cooler,
+}
+
+/// Night light failure
+#[derive(Debug)]
+pub enum NightLightError {
+    /// Neither KWin night light nor gammastep/wlsunset (or a helper) is installed
+    Unavailable,
+    /// A D-Bus call to KWin failed
+    DBus(zbus::Error),
+    /// A helper command could not be run or failed
+    Command(String),
+}
+
+impl std::fmt::Display for NightLightError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NightLightError::Unavailable => write!(f, "No night light service found (KWin, gammastep or wlsunset)"),
+            NightLightError::DBus(e) => write!(f, "Night light D-Bus error: {}", e),
+            NightLightError::Command(msg) => write!(f, "Night light command failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for NightLightError {}
+
+impl From<zbus::Error> for NightLightError {
+    fn from(e: zbus::Error) -> Self {
+        NightLightError::DBus(e)
+    }
+}
+
+/// Active KWin inhibition; dropping the connection would lift it
+struct Inhibition {
+    connection: zbus::Connection,
+    path: &'static str,
+    interface: &'static str,
+    cookie: u32,
+}
+
+static INHIBITION: Mutex<Option<Inhibition>> = Mutex::const_new(None);
+
+/// Temperature last set through gammastep one-shot mode
+static ONE_SHOT_KELVIN: Mutex<u32> = Mutex::const_new(NEUTRAL_KELVIN);
+
+/// Temperature after applying a warmer/cooler step
+pub fn next_temperature(current: u32, step: NightLightStep) -> u32 {
+    match step {
+        NightLightStep::Warmer => current.saturating_sub(STEP_KELVIN).max(MIN_KELVIN),
+        NightLightStep::Cooler => (current + STEP_KELVIN).min(NEUTRAL_KELVIN),
+        NightLightStep::Toggle => current,
+    }
+}
+
+/// Apply a night light step on whatever backend is available
+pub async fn apply(step: NightLightStep) -> Result<(), NightLightError> {
+    let connection = zbus::Connection::session().await?;
+    if let Some((path, interface)) = kwin_night_light(&connection).await {
+        return match step {
+            NightLightStep::Toggle => kwin_toggle(connection, path, interface).await,
+            _ => kwin_step(&connection, step).await,
+        };
+    }
+    match step {
+        NightLightStep::Toggle => signal_toggle().await,
+        _ => one_shot_step(step).await,
+    }
+}
+
+/// The first KWin night light object that reports itself available
+async fn kwin_night_light(connection: &zbus::Connection) -> Option<(&'static str, &'static str)> {
+    for &(path, interface) in KWIN_NIGHT_LIGHT {
+        let Ok(proxy) = zbus::Proxy::new(connection, "org.kde.KWin", path, interface).await else {
+            continue;
+        };
+        if proxy.get_property::<bool>("available").await.unwrap_or(false) {
+            return Some((path, interface));
+        }
+    }
+    None
+}
+
+async fn kwin_toggle(
+    connection: zbus::Connection,
+    path: &'static str,
+    interface: &'static str,
+) -> Result<(), NightLightError> {
+    let mut inhibition = INHIBITION.lock().await;
+    if let Some(active) = inhibition.take() {
+        let proxy = zbus::Proxy::new(&active.connection, "org.kde.KWin", active.path, active.interface).await?;
+        proxy.call_method("uninhibit", &(active.cookie,)).await?;
+        tracing::info!("Night light resumed");
+        return Ok(());
+    }
+
+    let proxy = zbus::Proxy::new(&connection, "org.kde.KWin", path, interface).await?;
+    let cookie: u32 = proxy.call("inhibit", &()).await?;
+    tracing::info!(cookie, "Night light inhibited");
+    *inhibition = Some(Inhibition { connection, path, interface, cookie });
+    Ok(())
+}
+
+/// Step kwinrc's night temperature and have KWin reload it
+async fn kwin_step(connection: &zbus::Connection, step: NightLightStep) -> Result<(), NightLightError> {
+    const KEY: [&str; 6] = ["--file", "kwinrc", "--group", "NightColor", "--key", "NightTemperature"];
+    let default = KDE_DEFAULT_KELVIN.to_string();
+    let current = run("kreadconfig6", &[&KEY[..], &["--default", &default]].concat())
+        .await
+        .ok()
+        .and_then(|out| out.trim().parse().ok())
+        .unwrap_or(KDE_DEFAULT_KELVIN);
+    let kelvin = next_temperature(current, step);
+
+    run("kwriteconfig6", &[&KEY[..], &[kelvin.to_string().as_str()]].concat()).await?;
+    connection
+        .call_method(Some("org.kde.KWin"), "/KWin", Some("org.kde.KWin"), "reconfigure", &())
+        .await?;
+    tracing::info!(kelvin, "Night light temperature set");
+    Ok(())
+}
+
+/// Toggle gammastep or wlsunset with `SIGUSR1`
+async fn signal_toggle() -> Result<(), NightLightError> {
+    for program in ["gammastep", "wlsunset"] {
+        if run("pkill", &["-USR1", "-x", program]).await.is_ok() {
+            tracing::info!(program, "Night light toggled");
+            return Ok(());
+        }
+    }
+    Err(NightLightError::Unavailable)
+}
+
+/// Set the next temperature with gammastep's one-shot mode
+async fn one_shot_step(step: NightLightStep) -> Result<(), NightLightError> {
+    let mut current = ONE_SHOT_KELVIN.lock().await;
+    let kelvin = next_temperature(*current, step);
+    run("gammastep", &["-P", "-O", &kelvin.to_string()]).await?;
+    *current = kelvin;
+    tracing::info!(kelvin, "Night light temperature set");
+    Ok(())
+}
+
+/// Run a helper; stdout if it exited successfully, Unavailable if not installed
+async fn run(program: &str, args: &[&str]) -> Result<String, NightLightError> {
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => NightLightError::Unavailable,
+            _ => NightLightError::Command(format!("{}: {}", program, e)),
+        })?;
+    if !output.status.success() {
+        return Err(NightLightError::Command(format!("{} exited with {}", program, output.status)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_temperature() {
+        assert_eq!(next_temperature(4500, NightLightStep::Warmer), 4000);
+        assert_eq!(next_temperature(1200, NightLightStep::Warmer), MIN_KELVIN);
+        assert_eq!(next_temperature(6200, NightLightStep::Cooler), NEUTRAL_KELVIN);
+        assert_eq!(next_temperature(3000, NightLightStep::Toggle), 3000);
+    }
+}
@@ -0,0 +1,124 @@
+//! Haptics for desktop notifications
+//!
+//! Mirrors incoming desktop notifications on the mouse, so users don't need
+//! a separate mx4notifications daemon. A dedicated session bus connection
+//! becomes a monitor for `org.freedesktop.Notifications.Notify` calls; each
+//! notification is matched against `notification_haptics.rules` (app name
+//! and urgency, first match wins) and the mapped waveform plays as a cue, so
+//! the haptic enabled flag and quiet hours apply.
+//!
+//! Opt-in (`notification_haptics.enabled`). Rules are re-read for every
+//! notification; our own notifications are ignored.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+
+use tokio_stream::StreamExt;
+use zbus::zvariant::OwnedValue;
+
+use crate::config::{NotificationHapticRule, NotificationUrgency, SharedConfig};
+use crate::hidpp::{Mx4HapticPattern, SharedHapticManager};
+
+/// Notifications interface whose `Notify` calls are monitored
+const NOTIFICATIONS_INTERFACE: &str = "org.freedesktop.Notifications";
+
+/// `Notify` arguments: app_name, replaces_id, app_icon, summary, body,
+/// actions, hints, expire_timeout
+type NotifyArgs = (String, u32, String, String, String, Vec<String>, HashMap<String, OwnedValue>, i32);
+
+/// Waveform for a notification (None = no rule matches)
+pub fn matching_pattern(
+    rules: &[NotificationHapticRule],
+    app_name: &str,
+    urgency: NotificationUrgency,
+) -> Option<Mx4HapticPattern> {
+    rules
+        .iter()
+        .find(|rule| {
+            (rule.app_name.is_empty() || rule.app_name.eq_ignore_ascii_case(app_name))
+                && rule.urgency.is_none_or(|u| u == urgency)
+        })
+        .and_then(|rule| Mx4HapticPattern::parse_name(&rule.pattern))
+}
+
+/// Monitor notifications and play the mapped haptics
+pub async fn start_notification_haptics(config: SharedConfig, haptic_manager: SharedHapticManager) {
+    let connection = match monitor_connection().await {
+        Ok(connection) => connection,
+        Err(e) => {
+            tracing::warn!("Notification haptics unavailable: {}", e);
+            return;
+        }
+    };
+    tracing::info!("Notification haptics listening");
+
+    let mut stream = zbus::MessageStream::from(connection);
+    while let Some(message) = stream.next().await {
+        let Ok(message) = message else { continue };
+        let header = message.header();
+        if header.member().map(|m| m.as_str()) != Some("Notify") {
+            continue;
+        }
+        let Ok((app_name, _, _, summary, _, _, hints, _)) = message.body().deserialize::<NotifyArgs>() else {
+            continue;
+        };
+        if app_name == crate::notifications::APP_NAME {
+            continue;
+        }
+
+        let urgency = NotificationUrgency::from_hint(hints.get("urgency").and_then(|v| u8::try_from(v).ok()));
+        let rules = match config.read() {
+            Ok(c) => c.notification_haptics.rules.clone(),
+            Err(_) => continue,
+        };
+        let Some(pattern) = matching_pattern(&rules, &app_name, urgency) else {
+            continue;
+        };
+
+        tracing::debug!(app = %app_name, summary = %summary, ?urgency, %pattern, "Notification haptic");
+        let manager = haptic_manager.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Ok(mut manager) = manager.lock() {
+                manager.emit_cue(pattern);
+            }
+        });
+    }
+    tracing::warn!("Notification monitor connection closed");
+}
+
+/// Open a separate session bus connection and turn it into a monitor
+async fn monitor_connection() -> zbus::Result<zbus::Connection> {
+    let connection = zbus::connection::Builder::session()?.build().await?;
+    let rule = zbus::MatchRule::builder()
+        .msg_type(zbus::message::Type::MethodCall)
+        .interface(NOTIFICATIONS_INTERFACE)?
+        .member("Notify")?
+        .build();
+    zbus::fdo::MonitoringProxy::new(&connection)
+        .await?
+        .become_monitor(&[rule], 0)
+        .await?;
+    Ok(connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NotificationHapticsConfig;
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let rules = NotificationHapticsConfig::default().rules;
+        assert_eq!(
+            matching_pattern(&rules, "Slack", NotificationUrgency::Normal),
+            Some(Mx4HapticPattern::Jingle)
+        );
+        // Critical beats the per-app rule
+        assert_eq!(
+            matching_pattern(&rules, "discord", NotificationUrgency::Critical),
+            Some(Mx4HapticPattern::AngryAlert)
+        );
+        assert_eq!(matching_pattern(&rules, "Firefox", NotificationUrgency::Normal), None);
+    }
+}
@@ -0,0 +1,81 @@
+//! Desktop notifications via org.freedesktop.Notifications
+//!
+//! Thin wrapper around the freedesktop notification spec's `Notify` call,
+//! used for short on-screen announcements (e.g. the active profile changed).
+//! Callers pass the id of their previous notification so repeated
+//! announcements replace each other instead of stacking up.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+
+use zbus::zvariant::Value;
+
+/// Notification server well-known name, path and interface
+const NOTIFICATIONS_NAME: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+
+/// Application name shown by the notification server
+pub const APP_NAME: &str = "JuhRadial MX";
+
+/// Default icon for daemon notifications
+pub const APP_ICON: &str = "input-mouse";
+
+/// A notification to show
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    /// Title line
+    pub summary: String,
+    /// Body text (may be empty)
+    pub body: String,
+    /// Icon name or path
+    pub icon: String,
+    /// Expiry in milliseconds (-1 = server default)
+    pub expire_timeout_ms: i32,
+    /// Transient notifications are not kept in the notification history
+    pub transient: bool,
+}
+
+impl Notification {
+    /// Short-lived OSD-style notification that is not kept in the history
+    pub fn transient(summary: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            summary: summary.into(),
+            body: body.into(),
+            icon: APP_ICON.to_string(),
+            expire_timeout_ms: 2000,
+            transient: true,
+        }
+    }
+}
+
+/// Show a notification, replacing `replaces_id` (0 = new); returns the server's id
+pub async fn notify(
+    connection: &zbus::Connection,
+    notification: &Notification,
+    replaces_id: u32,
+) -> zbus::Result<u32> {
+    let proxy = zbus::Proxy::new(connection, NOTIFICATIONS_NAME, NOTIFICATIONS_PATH, NOTIFICATIONS_NAME).await?;
+
+    let mut hints: HashMap<&str, Value<'_>> = HashMap::new();
+    if notification.transient {
+        hints.insert("transient", Value::from(true));
+    }
+    let actions: Vec<&str> = Vec::new();
+
+    proxy
+        .call(
+            "Notify",
+            &(
+                APP_NAME,
+                replaces_id,
+                notification.icon.as_str(),
+                notification.summary.as_str(),
+                notification.body.as_str(),
+                actions,
+                hints,
+                notification.expire_timeout_ms,
+            ),
+        )
+        .await
+}
@@ -0,0 +1,319 @@
+//! OBS Studio control over obs-websocket (protocol v5) for `obs` actions
+//!
+//! ```json
+//! {"type": "obs", "value": "toggle_record"}
+//! {"type": "obs", "value": {"switch_scene": "Be right back"}}
+//! ```
+//!
+//! Commands: `start_record`, `stop_record`, `toggle_record`,
+//! `start_stream`, `stop_stream`, `toggle_stream` and `switch_scene`. Each
+//! action opens a connection to `obs.host`:`obs.port`, identifies
+//! (authenticating with `obs.password` when OBS asks for it), sends one
+//! request, checks its status and closes again; nothing stays connected
+//! between presses.
+//!
+//! The client is built with the `obs` cargo feature (tokio-tungstenite
+//! without TLS, since obs-websocket only listens on plain `ws://`, plus
+//! sha2/base64 for the authentication string). Without it `obs` actions
+//! still parse but fail with [`ObsError::NotBuilt`].
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// What an `obs` action asks OBS to do
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObsCommand {
+    StartRecord,
+    StopRecord,
+    ToggleRecord,
+    StartStream,
+    StopStream,
+    ToggleStream,
+    /// Make the named scene the program scene
+    SwitchScene(String),
+}
+
+impl ObsCommand {
+    /// obs-websocket request type and data
+    pub fn request(&self) -> (&'static str, Value) {
+        match self {
+            ObsCommand::StartRecord => ("StartRecord", json!({})),
+            ObsCommand::StopRecord => ("StopRecord", json!({})),
+            ObsCommand::ToggleRecord => ("ToggleRecord", json!({})),
+            ObsCommand::StartStream => ("StartStream", json!({})),
+            ObsCommand::StopStream => ("StopStream", json!({})),
+            ObsCommand::ToggleStream => ("ToggleStream", json!({})),
+            ObsCommand::SwitchScene(scene) => ("SetCurrentProgramScene", json!({ "sceneName": scene })),
+        }
+    }
+}
+
+/// OBS control failure
+#[derive(Debug)]
+pub enum ObsError {
+    /// Connecting or talking to obs-websocket failed
+    Io(std::io::Error),
+    /// OBS answered with something that is not obs-websocket v5
+    Protocol(String),
+    /// OBS wants a password and `obs.password` is empty
+    PasswordRequired,
+    /// OBS closed the connection (4009 = wrong password)
+    Closed(u16, String),
+    /// OBS rejected the request (status code and comment)
+    Request(u64, String),
+    /// No answer within `obs.timeout_secs`
+    Timeout,
+    /// The daemon was built without the `obs` feature
+    NotBuilt,
+}
+
+impl std::fmt::Display for ObsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObsError::Io(e) => write!(f, "Could not reach OBS: {}", e),
+            ObsError::Protocol(msg) => write!(f, "Unexpected reply from OBS: {}", msg),
+            ObsError::PasswordRequired => write!(f, "OBS requires a password (obs.password)"),
+            ObsError::Closed(code, reason) => write!(f, "OBS closed the connection ({}: {})", code, reason),
+            ObsError::Request(code, comment) => write!(f, "OBS request failed ({}): {}", code, comment),
+            ObsError::Timeout => write!(f, "OBS did not answer in time"),
+            ObsError::NotBuilt => write!(f, "OBS support is not built in (cargo feature `obs`)"),
+        }
+    }
+}
+
+impl std::error::Error for ObsError {}
+
+impl From<std::io::Error> for ObsError {
+    fn from(e: std::io::Error) -> Self {
+        ObsError::Io(e)
+    }
+}
+
+#[cfg(feature = "obs")]
+pub use client::send;
+
+/// Send one command to OBS (always fails: built without the `obs` feature)
+#[cfg(not(feature = "obs"))]
+pub async fn send(_config: &crate::config::ObsConfig, _command: &ObsCommand) -> Result<(), ObsError> {
+    Err(ObsError::NotBuilt)
+}
+
+#[cfg(feature = "obs")]
+mod client {
+    use std::time::Duration;
+
+    use base64::Engine as _;
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use sha2::{Digest, Sha256};
+    use tokio::io::{AsyncRead, AsyncWrite};
+    use tokio::net::TcpStream;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::tungstenite::http::HeaderValue;
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+    use tokio_tungstenite::tungstenite::protocol::{CloseFrame, WebSocketConfig};
+    use tokio_tungstenite::tungstenite::{self, Message};
+    use tokio_tungstenite::WebSocketStream;
+
+    use super::{ObsCommand, ObsError};
+    use crate::config::ObsConfig;
+
+    /// obs-websocket RPC version spoken
+    const RPC_VERSION: u32 = 1;
+
+    /// Requests carry this id; the response is matched against it
+    pub(super) const REQUEST_ID: &str = "juhradial";
+
+    /// Largest message accepted from OBS
+    const MAX_MESSAGE_BYTES: usize = 1 << 20;
+
+    // obs-websocket opcodes
+    const OP_HELLO: u64 = 0;
+    pub(super) const OP_IDENTIFY: u64 = 1;
+    const OP_IDENTIFIED: u64 = 2;
+    const OP_REQUEST: u64 = 6;
+    const OP_REQUEST_RESPONSE: u64 = 7;
+
+    impl From<tungstenite::Error> for ObsError {
+        fn from(e: tungstenite::Error) -> Self {
+            match e {
+                tungstenite::Error::Io(e) => ObsError::Io(e),
+                e => ObsError::Protocol(e.to_string()),
+            }
+        }
+    }
+
+    /// Send one command to OBS
+    pub async fn send(config: &ObsConfig, command: &ObsCommand) -> Result<(), ObsError> {
+        tracing::info!(?command, host = %config.host, port = config.port, "Sending OBS request");
+        let exchange = async {
+            let stream = TcpStream::connect((config.host.as_str(), config.port)).await?;
+            let host = if config.host.contains(':') {
+                format!("[{}]", config.host)
+            } else {
+                config.host.clone()
+            };
+            let mut request = format!("ws://{}:{}/", host, config.port).into_client_request()?;
+            request
+                .headers_mut()
+                .insert("Sec-WebSocket-Protocol", HeaderValue::from_static("obswebsocket.json"));
+            let limits = WebSocketConfig::default().max_message_size(Some(MAX_MESSAGE_BYTES));
+            let (mut socket, _) = tokio_tungstenite::client_async_with_config(request, stream, Some(limits)).await?;
+            session(&mut socket, &config.password, command).await
+        };
+        tokio::time::timeout(Duration::from_secs(config.timeout_secs), exchange)
+            .await
+            .map_err(|_| ObsError::Timeout)?
+    }
+
+    /// Hello, Identify, one request and its response, then close
+    pub(super) async fn session<S>(
+        socket: &mut WebSocketStream<S>,
+        password: &str,
+        command: &ObsCommand,
+    ) -> Result<(), ObsError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let hello = read_json(socket).await?;
+        if hello["op"] != OP_HELLO {
+            return Err(ObsError::Protocol(format!("expected Hello, got {}", hello)));
+        }
+        let identify = identify_message(&hello["d"], password)?;
+        socket.send(Message::text(identify.to_string())).await?;
+        let identified = read_json(socket).await?;
+        if identified["op"] != OP_IDENTIFIED {
+            return Err(ObsError::Protocol(format!("expected Identified, got {}", identified)));
+        }
+
+        let (request_type, request_data) = command.request();
+        let request = json!({
+            "op": OP_REQUEST,
+            "d": {"requestType": request_type, "requestId": REQUEST_ID, "requestData": request_data},
+        });
+        socket.send(Message::text(request.to_string())).await?;
+        let response = loop {
+            let message = read_json(socket).await?;
+            if message["op"] == OP_REQUEST_RESPONSE && message["d"]["requestId"] == REQUEST_ID {
+                break message;
+            }
+        };
+
+        // Normal closure; OBS has already acted, so a failed close does not matter
+        let _ = socket
+            .close(Some(CloseFrame {
+                code: CloseCode::Normal,
+                reason: "".into(),
+            }))
+            .await;
+
+        let status = &response["d"]["requestStatus"];
+        if status["result"] == true {
+            Ok(())
+        } else {
+            Err(ObsError::Request(
+                status["code"].as_u64().unwrap_or(0),
+                status["comment"].as_str().unwrap_or("no comment").to_string(),
+            ))
+        }
+    }
+
+    /// Identify message for a Hello payload (`d`)
+    pub(super) fn identify_message(hello: &Value, password: &str) -> Result<Value, ObsError> {
+        let mut data = json!({"rpcVersion": RPC_VERSION, "eventSubscriptions": 0});
+        if let Some(auth) = hello.get("authentication") {
+            if password.is_empty() {
+                return Err(ObsError::PasswordRequired);
+            }
+            let (Some(salt), Some(challenge)) = (auth["salt"].as_str(), auth["challenge"].as_str()) else {
+                return Err(ObsError::Protocol("authentication without salt/challenge".to_string()));
+            };
+            data["authentication"] = Value::String(auth_response(password, salt, challenge));
+        }
+        Ok(json!({"op": OP_IDENTIFY, "d": data}))
+    }
+
+    /// obs-websocket authentication string:
+    /// `base64(sha256(base64(sha256(password + salt)) + challenge))`
+    pub(super) fn auth_response(password: &str, salt: &str, challenge: &str) -> String {
+        let base64 = base64::engine::general_purpose::STANDARD;
+        let secret = base64.encode(Sha256::digest(format!("{}{}", password, salt)));
+        base64.encode(Sha256::digest(format!("{}{}", secret, challenge)))
+    }
+
+    /// Next text message as JSON; pings are answered by tungstenite
+    pub(super) async fn read_json<S>(socket: &mut WebSocketStream<S>) -> Result<Value, ObsError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        loop {
+            match socket.next().await {
+                Some(Ok(Message::Text(text))) => {
+                    return serde_json::from_str(&text).map_err(|e| ObsError::Protocol(e.to_string()))
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    let (code, reason) = frame.map_or((1005, String::new()), |f| (f.code.into(), f.reason.to_string()));
+                    return Err(ObsError::Closed(code, reason));
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+                None => return Err(ObsError::Closed(1006, "connection lost".to_string())),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "obs"))]
+mod tests {
+    use futures_util::SinkExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use super::client::*;
+    use super::*;
+
+    #[test]
+    fn test_auth_response() {
+        // Example from the obs-websocket protocol documentation
+        assert_eq!(
+            auth_response(
+                "supersecretpassword",
+                "lM1GncleQOaCu9lT1yeUZhFYnqhsLLP1G5lAGo3ixaI=",
+                "+IxH4CnCiqpX1rM9scsNynZzbOe4KhDeYcTNS3PDaeY="
+            ),
+            "1Ct943GAT+6YQUUX47Ia/ncufilbe6+oD6lY+5kaCu4="
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_against_scripted_server() {
+        let (client, server) = tokio::io::duplex(4096);
+        let obs = tokio::spawn(async move {
+            let mut server = tokio_tungstenite::accept_async(server).await.unwrap();
+            let hello = json!({"op": 0, "d": {"rpcVersion": 1}}).to_string();
+            server.send(Message::text(hello)).await.unwrap();
+            let identify = read_json(&mut server).await.unwrap();
+            assert_eq!(identify["op"], OP_IDENTIFY);
+            assert!(identify["d"].get("authentication").is_none());
+            server.send(Message::text(r#"{"op": 2, "d": {"negotiatedRpcVersion": 1}}"#)).await.unwrap();
+
+            let request = read_json(&mut server).await.unwrap();
+            assert_eq!(request["d"]["requestType"], "SetCurrentProgramScene");
+            assert_eq!(request["d"]["requestData"]["sceneName"], "BRB");
+            let response = json!({"op": 7, "d": {"requestType": "SetCurrentProgramScene", "requestId": REQUEST_ID,
+                "requestStatus": {"result": false, "code": 600, "comment": "No source was found"}}});
+            server.send(Message::text(response.to_string())).await.unwrap();
+            assert!(matches!(read_json(&mut server).await, Err(ObsError::Closed(1000, _))));
+        });
+
+        let (mut client, _) = tokio_tungstenite::client_async("ws://localhost/", client).await.unwrap();
+        let result = session(&mut client, "", &ObsCommand::SwitchScene("BRB".to_string())).await;
+        assert!(matches!(result, Err(ObsError::Request(600, _))));
+        obs.await.unwrap();
+
+        let hello = json!({"authentication": {"salt": "s", "challenge": "c"}});
+        assert!(matches!(identify_message(&hello, ""), Err(ObsError::PasswordRequired)));
+    }
+}
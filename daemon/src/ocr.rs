@@ -0,0 +1,97 @@
+//! Screen text recognition for `ocr` actions
+//!
+//! The action captures a region through the Screenshot portal
+//! ([`crate::portal::screenshot`]) and runs `ocr.command` on the image;
+//! whatever the command prints on stdout is put on the clipboard. Any OCR
+//! tool that can write to stdout works, e.g. `tesseract {image} - -l deu`.
+//! `{image}` is replaced by the shell-quoted image path.
+//!
+//! The portal decides where the screenshot is stored; the file is left
+//! where it is.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::OcrConfig;
+
+/// OCR failure
+#[derive(Debug)]
+pub enum OcrError {
+    /// The command could not be started
+    Spawn(std::io::Error),
+    /// The command exited unsuccessfully
+    Failed(std::process::ExitStatus),
+    /// The command ran longer than `ocr.timeout_secs`
+    Timeout,
+    /// No text was recognized
+    NoText,
+}
+
+impl std::fmt::Display for OcrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OcrError::Spawn(e) => write!(f, "Could not run OCR command: {}", e),
+            OcrError::Failed(status) => write!(f, "OCR command failed ({})", status),
+            OcrError::Timeout => write!(f, "OCR command timed out"),
+            OcrError::NoText => write!(f, "No text recognized"),
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// Shell command line for one image
+pub fn command_line(template: &str, image: &Path) -> String {
+    let path = image.to_string_lossy();
+    template.replace("{image}", &format!("'{}'", path.replace('\'', r"'\''")))
+}
+
+/// Run the configured OCR command; returns the recognized text, trimmed
+pub async fn recognize(config: &OcrConfig, image: &Path) -> Result<String, OcrError> {
+    let command = command_line(&config.command, image);
+    tracing::info!(command = %command, "Running OCR");
+
+    let child = tokio::process::Command::new("sh")
+        .args(["-c", &command])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(OcrError::Spawn)?;
+    let output = tokio::time::timeout(Duration::from_secs(config.timeout_secs), child.wait_with_output())
+        .await
+        .map_err(|_| OcrError::Timeout)?
+        .map_err(OcrError::Spawn)?;
+    if !output.status.success() {
+        return Err(OcrError::Failed(output.status));
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if text.is_empty() {
+        return Err(OcrError::NoText);
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_recognize_runs_the_template() {
+        let image = Path::new("/tmp/it's here.png");
+        assert_eq!(command_line("tesseract {image} -", image), r"tesseract '/tmp/it'\''s here.png' -");
+
+        let config = OcrConfig {
+            command: "echo '  recognized '; echo {image} >/dev/null".to_string(),
+            ..OcrConfig::default()
+        };
+        assert_eq!(recognize(&config, image).await.unwrap(), "recognized");
+
+        let silent = OcrConfig { command: "true {image}".to_string(), ..OcrConfig::default() };
+        assert!(matches!(recognize(&silent, image).await, Err(OcrError::NoText)));
+    }
+}
@@ -0,0 +1,1141 @@
+//! Built-in radial menu overlay for wlroots compositors (feature `overlay`)
+//!
+//! Alternative to the external PyQt/KWin overlay: renders the radial menu
+//! directly with wlr-layer-shell and a small software rasterizer (tiny-skia
+//! into a wl_shm buffer), so Sway/Hyprland/river users need no extra process.
+//!
+//! The overlay behaves like any other overlay client: a D-Bus bridge listens
+//! for `MenuReady` / `MenuRequested` / `CursorMoved` / `HideMenu` /
+//! `MenuCancelled`, reports hovers with `NotifySliceHover`, registers with
+//! `RegisterOverlay` and sends heartbeats. Slices come from the daemon's
+//! menu layout, so per-app profiles and profile edits apply as they do for
+//! the external overlay. The Wayland side runs on its own
+//! thread driven by calloop and receives [`OverlayCommand`]s over a channel.
+//!
+//! Slice labels are drawn with fontdue in the theme's custom font or the
+//! fontconfig `sans-serif` default. Icons may be SVG/PNG files, icon-theme
+//! names (looked up in Adwaita, breeze and hicolor; `-symbolic` icons take
+//! the theme's text color) or emoji/symbols the label font has glyphs for.
+//!
+//! Limitations: the menu is positioned with layer-shell margins on the
+//! compositor's default output, and color emoji are not drawn.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc as std_mpsc;
+use std::sync::OnceLock;
+use std::thread::JoinHandle;
+
+use smithay_client_toolkit::{
+    compositor::{CompositorHandler, CompositorState, Region},
+    delegate_compositor, delegate_layer, delegate_output, delegate_registry, delegate_shm,
+    output::{OutputHandler, OutputState},
+    reexports::{
+        calloop::{channel, EventLoop},
+        calloop_wayland_source::WaylandSource,
+        client::{
+            globals::registry_queue_init,
+            protocol::{wl_output, wl_shm, wl_surface},
+            Connection, QueueHandle,
+        },
+    },
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+    shell::{
+        wlr_layer::{
+            Anchor, KeyboardInteractivity, Layer, LayerShell, LayerShellHandler, LayerSurface,
+            LayerSurfaceConfigure,
+        },
+        WaylandSurface,
+    },
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use tiny_skia::{
+    Color, ColorU8, FillRule, FilterQuality, Paint, PathBuilder, Pixmap, PixmapPaint, Stroke, Transform,
+};
+use tokio_stream::StreamExt;
+
+use crate::actions::ActionExecutor;
+use crate::cursor::{MENU_DIAMETER, MENU_RADIUS};
+use crate::dbus::{DBUS_INTERFACE, DBUS_NAME, DBUS_PATH};
+use crate::profiles::Profile;
+use crate::slice_geometry::{SliceGeometry, DEFAULT_DEAD_ZONE_RADIUS};
+use crate::theme::Theme;
+
+/// Radius of the default center dead zone (no slice selected), in pixels
+pub const CENTER_RADIUS: i32 = DEFAULT_DEAD_ZONE_RADIUS as i32;
+
+/// Layer-shell namespace (for compositor window rules)
+const LAYER_NAMESPACE: &str = "juhradial-mx";
+
+/// Label font size, in pixels
+const LABEL_SIZE: f32 = 11.0;
+
+/// Icon edge length, in pixels
+const ICON_SIZE: u32 = 22;
+
+/// Space between a slice's icon and its label, in pixels
+const ICON_LABEL_GAP: f32 = 3.0;
+
+/// Icon themes searched for icon names, in order
+const ICON_THEMES: &[&str] = &["Adwaita", "breeze", "hicolor"];
+
+/// Used when fontconfig is not available
+const FALLBACK_FONTS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu-sans-fonts/DejaVuSans.ttf",
+    "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+];
+
+/// What a slice shows, from the menu layout
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SliceFace {
+    pub label: Option<String>,
+    /// Icon reference (file path, icon-theme name or emoji)
+    pub icon: Option<String>,
+}
+
+impl SliceFace {
+    /// Faces of a profile's slices, in slice order
+    fn from_profile(profile: &Profile) -> Vec<SliceFace> {
+        profile
+            .slices
+            .iter()
+            .map(|slice| SliceFace {
+                label: slice.as_ref().and_then(|action| action.label.clone()),
+                icon: slice.as_ref().and_then(|action| action.icon.clone()),
+            })
+            .collect()
+    }
+}
+
+/// Commands sent from the D-Bus bridge to the Wayland thread
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverlayCommand {
+    /// Show the menu centered at global coordinates
+    Show {
+        x: i32,
+        y: i32,
+        geometry: SliceGeometry,
+        faces: Vec<SliceFace>,
+    },
+    /// Change the highlighted slice (None = center/no slice)
+    Highlight(Option<u8>),
+    /// Dismiss the menu
+    Hide,
+    /// Stop the Wayland thread
+    Shutdown,
+}
+
+/// Handle to the running layer-shell overlay thread
+pub struct OverlayHandle {
+    /// Command channel into the calloop event loop
+    tx: channel::Sender<OverlayCommand>,
+    /// Wayland thread
+    thread: Option<JoinHandle<()>>,
+}
+
+impl OverlayHandle {
+    /// Send a command to the overlay
+    ///
+    /// Returns false if the overlay thread has exited.
+    pub fn send(&self, command: OverlayCommand) -> bool {
+        self.tx.send(command).is_ok()
+    }
+}
+
+impl Drop for OverlayHandle {
+    fn drop(&mut self) {
+        let _ = self.tx.send(OverlayCommand::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Overlay error type
+#[derive(Debug)]
+pub enum OverlayError {
+    /// No Wayland display could be reached
+    NoWaylandDisplay(String),
+    /// A required Wayland global is missing
+    MissingGlobal(&'static str),
+    /// Event loop or thread setup failed
+    Setup(String),
+}
+
+impl std::fmt::Display for OverlayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverlayError::NoWaylandDisplay(msg) => write!(f, "Wayland display unavailable: {}", msg),
+            OverlayError::MissingGlobal(name) => write!(f, "Compositor does not support {}", name),
+            OverlayError::Setup(msg) => write!(f, "Overlay setup failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for OverlayError {}
+
+// ============================================================================
+// Geometry
+// ============================================================================
+
+/// Map a cursor offset from the menu center to a slice index
+///
+/// With the default geometry slices are numbered clockwise starting at
+/// north (N=0, NE=1, ... NW=7), matching the profile slice order. Returns
+/// None inside the center dead zone.
+pub fn slice_at(dx: i32, dy: i32) -> Option<u8> {
+    SliceGeometry::default().slice_at(dx, dy)
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// Colors used by the built-in renderer
+#[derive(Debug, Clone, Copy)]
+struct Palette {
+    base: Color,
+    surface: Color,
+    accent: Color,
+    border: Color,
+    text: Color,
+}
+
+impl Palette {
+    /// Build a palette from a theme, falling back to neutral colors
+    fn from_theme(theme: &Theme) -> Self {
+        let colors = &theme.colors;
+        let opacity = theme.glassmorphism.background_opacity.clamp(0.0, 1.0);
+
+        Self {
+            base: with_alpha(parse_hex_color(&colors.base).unwrap_or(Color::BLACK), opacity.max(0.6)),
+            surface: parse_hex_color(&colors.surface).unwrap_or(Color::BLACK),
+            accent: with_alpha(parse_hex_color(&colors.accent).unwrap_or(Color::WHITE), 0.55),
+            border: with_alpha(parse_hex_color(&colors.border).unwrap_or(Color::WHITE), 0.6),
+            text: parse_hex_color(&colors.text).unwrap_or(Color::WHITE),
+        }
+    }
+}
+
+/// Parse a #RRGGBB or #RGB color string
+fn parse_hex_color(hex: &str) -> Option<Color> {
+    let hex = hex.strip_prefix('#')?;
+    let (r, g, b) = match hex.len() {
+        6 => (
+            u8::from_str_radix(&hex[0..2], 16).ok()?,
+            u8::from_str_radix(&hex[2..4], 16).ok()?,
+            u8::from_str_radix(&hex[4..6], 16).ok()?,
+        ),
+        3 => {
+            let expand = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|v| v * 17);
+            (expand(0)?, expand(1)?, expand(2)?)
+        }
+        _ => return None,
+    };
+    Some(Color::from_rgba8(r, g, b, 255))
+}
+
+fn with_alpha(mut color: Color, alpha: f32) -> Color {
+    color.set_alpha(alpha);
+    color
+}
+
+/// Build an annular sector path for one slice
+fn slice_path(geometry: &SliceGeometry, center: f32, inner: f32, outer: f32, index: u8) -> Option<tiny_skia::Path> {
+    const STEPS: usize = 16;
+    let sector = geometry.sector_deg().to_radians();
+    // Angles measured clockwise from north
+    let start = geometry.slice_center_deg(index).to_radians() - sector / 2.0;
+    let point = |radius: f32, angle: f32| (center + radius * angle.sin(), center - radius * angle.cos());
+
+    let mut pb = PathBuilder::new();
+    let (x, y) = point(outer, start);
+    pb.move_to(x, y);
+    for i in 1..=STEPS {
+        let (x, y) = point(outer, start + sector * i as f32 / STEPS as f32);
+        pb.line_to(x, y);
+    }
+    for i in (0..=STEPS).rev() {
+        let (x, y) = point(inner, start + sector * i as f32 / STEPS as f32);
+        pb.line_to(x, y);
+    }
+    pb.close();
+    pb.finish()
+}
+
+/// Render the radial menu into a square pixmap
+fn render(pixmap: &mut Pixmap, palette: &Palette, geometry: &SliceGeometry, art: &[SliceArt], highlight: Option<u8>) {
+    pixmap.fill(Color::TRANSPARENT);
+
+    let size = pixmap.width() as f32;
+    let center = size / 2.0;
+    let outer = center - 2.0;
+    let inner = geometry.dead_zone_radius;
+
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Paint::default()
+    };
+
+    // Menu body
+    if let Some(circle) = PathBuilder::from_circle(center, center, outer) {
+        paint.set_color(palette.base);
+        pixmap.fill_path(&circle, &paint, FillRule::Winding, Transform::identity(), None);
+    }
+
+    // Highlighted slice
+    if let Some(path) = highlight.and_then(|i| slice_path(geometry, center, inner, outer, i)) {
+        paint.set_color(palette.accent);
+        pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+    }
+
+    // Slice separators
+    let stroke = Stroke {
+        width: 1.5,
+        ..Stroke::default()
+    };
+    paint.set_color(palette.border);
+    let sector = geometry.sector_deg().to_radians();
+    for i in 0..geometry.slice_count {
+        let angle = geometry.slice_center_deg(i).to_radians() - sector / 2.0;
+        let mut pb = PathBuilder::new();
+        pb.move_to(center + inner * angle.sin(), center - inner * angle.cos());
+        pb.line_to(center + outer * angle.sin(), center - outer * angle.cos());
+        if let Some(line) = pb.finish() {
+            pixmap.stroke_path(&line, &paint, &stroke, Transform::identity(), None);
+        }
+    }
+
+    // Outer ring
+    if let Some(circle) = PathBuilder::from_circle(center, center, outer) {
+        pixmap.stroke_path(&circle, &paint, &stroke, Transform::identity(), None);
+    }
+
+    // Icon above label, centered in the ring on each slice's center line
+    let radius = (inner + outer) / 2.0;
+    for (i, art) in art.iter().enumerate().take(geometry.slice_count as usize) {
+        let angle = geometry.slice_center_deg(i as u8).to_radians();
+        let (x, y) = (center + radius * angle.sin(), center - radius * angle.cos());
+        let parts: Vec<&Pixmap> = [&art.icon, &art.label].into_iter().flatten().collect();
+        let height = parts.iter().map(|p| p.height() as f32).sum::<f32>()
+            + ICON_LABEL_GAP * parts.len().saturating_sub(1) as f32;
+        let mut top = y - height / 2.0;
+        for part in parts {
+            let left = x - part.width() as f32 / 2.0;
+            pixmap.draw_pixmap(
+                left.round() as i32,
+                top.round() as i32,
+                part.as_ref(),
+                &PixmapPaint::default(),
+                Transform::identity(),
+                None,
+            );
+            top += part.height() as f32 + ICON_LABEL_GAP;
+        }
+    }
+
+    // Center
+    if let Some(circle) = PathBuilder::from_circle(center, center, inner) {
+        paint.set_color(palette.surface);
+        pixmap.fill_path(&circle, &paint, FillRule::Winding, Transform::identity(), None);
+        paint.set_color(palette.border);
+        pixmap.stroke_path(&circle, &paint, &stroke, Transform::identity(), None);
+    }
+}
+
+// ============================================================================
+// Labels and icons
+// ============================================================================
+
+/// Rasterized label and icon of one slice
+#[derive(Debug, Default)]
+struct SliceArt {
+    label: Option<Pixmap>,
+    icon: Option<Pixmap>,
+}
+
+/// Load the label font: `family` (or `sans-serif`) as resolved by fontconfig
+fn load_font(family: Option<&str>) -> Option<fontdue::Font> {
+    let matched = std::process::Command::new("fc-match")
+        .args(["--format=%{file}", family.unwrap_or("sans-serif")])
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .filter(|path| !path.is_empty());
+    let font = matched
+        .iter()
+        .map(String::as_str)
+        .chain(FALLBACK_FONTS.iter().copied())
+        .find_map(|path| {
+            let bytes = std::fs::read(path).ok()?;
+            fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default()).ok()
+        });
+    if font.is_none() {
+        tracing::warn!("No usable font found, the built-in overlay draws no labels");
+    }
+    font
+}
+
+/// Render one line of `text`, shortened with an ellipsis to fit `max_width`
+fn text_pixmap(font: &fontdue::Font, text: &str, size: f32, color: Color, max_width: f32) -> Option<Pixmap> {
+    let width = |chars: &[char]| chars.iter().map(|&c| font.metrics(c, size).advance_width).sum::<f32>();
+    let mut chars: Vec<char> = text.trim().chars().collect();
+    if width(&chars) > max_width {
+        let ellipsis = font.metrics('…', size).advance_width;
+        while !chars.is_empty() && width(&chars) + ellipsis > max_width {
+            chars.pop();
+        }
+        chars.push('…');
+    }
+
+    let line = font.horizontal_line_metrics(size)?;
+    let mut pixmap = Pixmap::new(width(&chars).ceil() as u32, (line.ascent - line.descent).ceil() as u32)?;
+    let (pixmap_width, pixmap_height) = (pixmap.width() as i32, pixmap.height() as i32);
+    let color = color.to_color_u8();
+    let pixels = pixmap.pixels_mut();
+    let mut pen = 0.0;
+    for c in chars {
+        let (metrics, coverage) = font.rasterize(c, size);
+        let left = (pen + metrics.xmin as f32).round() as i32;
+        let top = (line.ascent - metrics.height as f32 - metrics.ymin as f32).round() as i32;
+        for (i, &value) in coverage.iter().enumerate() {
+            let (x, y) = (left + (i % metrics.width) as i32, top + (i / metrics.width) as i32);
+            if value == 0 || x < 0 || y < 0 || x >= pixmap_width || y >= pixmap_height {
+                continue;
+            }
+            let alpha = (u16::from(value) * u16::from(color.alpha()) / 255) as u8;
+            let pixel = &mut pixels[(y * pixmap_width + x) as usize];
+            // Neighbouring glyphs may touch; keep the stronger coverage
+            if alpha > pixel.alpha() {
+                *pixel = ColorU8::from_rgba(color.red(), color.green(), color.blue(), alpha).premultiply();
+            }
+        }
+        pen += metrics.advance_width;
+    }
+    Some(pixmap)
+}
+
+/// Rasterize an icon reference into a `size` square
+///
+/// File paths are loaded directly, icon names are looked up in the icon
+/// themes and anything else (emoji, symbols) is drawn with the label font.
+fn load_icon(icon: &str, size: u32, font: Option<&fontdue::Font>, color: Color) -> Option<Pixmap> {
+    if icon.contains('/') {
+        return load_icon_file(Path::new(icon), size);
+    }
+    if let Some(path) = find_themed_icon(icon) {
+        let pixmap = load_icon_file(&path, size)?;
+        return Some(if icon.ends_with("-symbolic") { tint(pixmap, color) } else { pixmap });
+    }
+    let font = font?;
+    if icon.chars().all(|c| c.is_whitespace() || font.has_glyph(c)) {
+        text_pixmap(font, icon, size as f32, color, size as f32 * 2.0)
+    } else {
+        None
+    }
+}
+
+/// Render an SVG or PNG file scaled to fit a `size` square
+fn load_icon_file(path: &Path, size: u32) -> Option<Pixmap> {
+    let data = std::fs::read(path).ok()?;
+    let is_svg = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    let mut pixmap = Pixmap::new(size, size)?;
+    if is_svg {
+        let tree = resvg::usvg::Tree::from_data(&data, &resvg::usvg::Options::default()).ok()?;
+        let scale = size as f32 / tree.size().width().max(tree.size().height());
+        resvg::render(&tree, Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    } else {
+        let image = Pixmap::decode_png(&data).ok()?;
+        let scale = size as f32 / image.width().max(image.height()) as f32;
+        let paint = PixmapPaint {
+            quality: FilterQuality::Bicubic,
+            ..PixmapPaint::default()
+        };
+        pixmap.draw_pixmap(0, 0, image.as_ref(), &paint, Transform::from_scale(scale, scale), None);
+    }
+    Some(pixmap)
+}
+
+/// Recolor a symbolic icon, keeping its shape
+fn tint(mut pixmap: Pixmap, color: Color) -> Pixmap {
+    let color = color.to_color_u8();
+    for pixel in pixmap.pixels_mut() {
+        *pixel = ColorU8::from_rgba(color.red(), color.green(), color.blue(), pixel.alpha()).premultiply();
+    }
+    pixmap
+}
+
+/// File of an icon-theme icon, preferring SVG
+fn find_themed_icon(name: &str) -> Option<PathBuf> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)) {
+        return None;
+    }
+    let dirs = icon_dirs();
+    ["svg", "png"].iter().find_map(|ext| {
+        dirs.iter()
+            .map(|dir| dir.join(format!("{}.{}", name, ext)))
+            .find(|path| path.is_file())
+    })
+}
+
+/// Icon directories of [`ICON_THEMES`], scanned once
+///
+/// Themes keep icons two levels down, as size/context (Adwaita, hicolor)
+/// or context/size (breeze). Within a theme scalable directories come
+/// first, then sizes from [`ICON_SIZE`] up, then smaller ones.
+fn icon_dirs() -> &'static [PathBuf] {
+    static DIRS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+    DIRS.get_or_init(|| {
+        let data_dirs = std::env::var_os("XDG_DATA_DIRS")
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+        let bases: Vec<PathBuf> = dirs::home_dir()
+            .map(|home| home.join(".icons"))
+            .into_iter()
+            .chain(dirs::data_dir().map(|data| data.join("icons")))
+            .chain(std::env::split_paths(&data_dirs).map(|data| data.join("icons")))
+            .collect();
+
+        let mut found = Vec::new();
+        for theme in ICON_THEMES {
+            let mut theme_dirs: Vec<PathBuf> = bases
+                .iter()
+                .flat_map(|base| subdirs(&base.join(theme)))
+                .flat_map(|dir| subdirs(&dir))
+                .collect();
+            theme_dirs.sort_by_key(|dir| icon_dir_rank(dir));
+            found.extend(theme_dirs);
+        }
+        found
+    })
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect())
+        .unwrap_or_default()
+}
+
+/// Sort key of an icon directory: scalable, then big enough, then too small
+fn icon_dir_rank(dir: &Path) -> (u8, u32) {
+    let size = dir.components().rev().take(2).find_map(|component| {
+        let name = component.as_os_str().to_str()?;
+        if name == "scalable" || name == "symbolic" {
+            return Some(None);
+        }
+        let digits: String = name.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse::<u32>().ok().map(Some)
+    });
+    match size {
+        Some(None) | None => (0, 0),
+        Some(Some(size)) if size >= ICON_SIZE => (1, size),
+        Some(Some(size)) => (2, u32::MAX - size),
+    }
+}
+
+/// Copy tiny-skia RGBA (premultiplied) pixels into a wl_shm ARGB8888 buffer
+fn copy_to_argb8888(rgba: &[u8], canvas: &mut [u8]) {
+    for (src, dst) in rgba.chunks_exact(4).zip(canvas.chunks_exact_mut(4)) {
+        // ARGB8888 is little-endian: bytes are B, G, R, A
+        dst[0] = src[2];
+        dst[1] = src[1];
+        dst[2] = src[0];
+        dst[3] = src[3];
+    }
+}
+
+// ============================================================================
+// Wayland thread
+// ============================================================================
+
+/// Wayland client state for the layer-shell overlay
+struct OverlayState {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    compositor: CompositorState,
+    layer_shell: LayerShell,
+    shm: Shm,
+    pool: SlotPool,
+    /// Layer surface while the menu is shown
+    layer: Option<LayerSurface>,
+    /// Whether the current layer surface received its first configure
+    configured: bool,
+    /// Currently highlighted slice
+    highlight: Option<u8>,
+    /// Slice layout of the shown menu
+    geometry: SliceGeometry,
+    /// Labels and icons of the shown menu, in slice order
+    art: Vec<SliceArt>,
+    /// Label font (None: no usable font, labels are skipped)
+    font: Option<fontdue::Font>,
+    /// Rasterized icons by reference (None: could not be loaded)
+    icons: HashMap<String, Option<Pixmap>>,
+    palette: Palette,
+    pixmap: Pixmap,
+    exit: bool,
+}
+
+impl OverlayState {
+    fn handle_command(&mut self, qh: &QueueHandle<Self>, command: OverlayCommand) {
+        match command {
+            OverlayCommand::Show { x, y, geometry, faces } => {
+                self.geometry = geometry;
+                self.art = faces.iter().map(|face| self.slice_art(face)).collect();
+                self.show(qh, x, y);
+            }
+            OverlayCommand::Highlight(slice) => {
+                if self.highlight != slice {
+                    self.highlight = slice;
+                    self.draw();
+                }
+            }
+            OverlayCommand::Hide => self.hide(),
+            OverlayCommand::Shutdown => {
+                self.hide();
+                self.exit = true;
+            }
+        }
+    }
+
+    /// Rasterize a slice's label and icon for the current geometry
+    fn slice_art(&mut self, face: &SliceFace) -> SliceArt {
+        let ring = MENU_RADIUS as f32 - self.geometry.dead_zone_radius;
+        let arc = std::f32::consts::TAU * (MENU_RADIUS as f32 + self.geometry.dead_zone_radius) / 2.0
+            / f32::from(self.geometry.slice_count.max(1));
+        let max_width = ring.min(arc) - 8.0;
+
+        let label = face.label.as_deref().zip(self.font.as_ref()).and_then(|(label, font)| {
+            text_pixmap(font, label, LABEL_SIZE, self.palette.text, max_width)
+        });
+        let icon = face.icon.as_ref().and_then(|icon| {
+            self.icons
+                .entry(icon.clone())
+                .or_insert_with(|| load_icon(icon, ICON_SIZE, self.font.as_ref(), self.palette.text))
+                .clone()
+        });
+        SliceArt { label, icon }
+    }
+
+    fn show(&mut self, qh: &QueueHandle<Self>, x: i32, y: i32) {
+        self.hide();
+
+        let surface = self.compositor.create_surface(qh);
+
+        // Click-through: the daemon tracks the pointer itself via evdev
+        if let Ok(region) = Region::new(&self.compositor) {
+            surface.set_input_region(Some(region.wl_region()));
+        }
+
+        let layer = self.layer_shell.create_layer_surface(
+            qh,
+            surface,
+            Layer::Overlay,
+            Some(LAYER_NAMESPACE),
+            None,
+        );
+        layer.set_anchor(Anchor::TOP | Anchor::LEFT);
+        layer.set_size(MENU_DIAMETER as u32, MENU_DIAMETER as u32);
+        layer.set_margin(y - MENU_RADIUS, 0, 0, x - MENU_RADIUS);
+        layer.set_exclusive_zone(-1);
+        layer.set_keyboard_interactivity(KeyboardInteractivity::None);
+        layer.commit();
+
+        self.layer = Some(layer);
+        self.configured = false;
+        self.highlight = None;
+    }
+
+    fn hide(&mut self) {
+        // Dropping the LayerSurface destroys it
+        self.layer = None;
+        self.configured = false;
+        self.highlight = None;
+    }
+
+    fn draw(&mut self) {
+        let Some(layer) = &self.layer else {
+            return;
+        };
+        if !self.configured {
+            return;
+        }
+
+        render(&mut self.pixmap, &self.palette, &self.geometry, &self.art, self.highlight);
+
+        let size = MENU_DIAMETER;
+        let (buffer, canvas) =
+            match self.pool.create_buffer(size, size, size * 4, wl_shm::Format::Argb8888) {
+                Ok(b) => b,
+                Err(e) => {
+                    tracing::warn!("Failed to allocate overlay buffer: {}", e);
+                    return;
+                }
+            };
+        copy_to_argb8888(self.pixmap.data(), canvas);
+
+        let surface = layer.wl_surface();
+        surface.damage_buffer(0, 0, size, size);
+        if let Err(e) = buffer.attach_to(surface) {
+            tracing::warn!("Failed to attach overlay buffer: {}", e);
+            return;
+        }
+        layer.commit();
+    }
+}
+
+impl CompositorHandler for OverlayState {
+    fn scale_factor_changed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &wl_surface::WlSurface, _: i32) {}
+
+    fn transform_changed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &wl_surface::WlSurface, _: wl_output::Transform) {}
+
+    fn frame(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &wl_surface::WlSurface, _: u32) {}
+
+    fn surface_enter(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &wl_surface::WlSurface, _: &wl_output::WlOutput) {}
+
+    fn surface_leave(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &wl_surface::WlSurface, _: &wl_output::WlOutput) {}
+}
+
+impl OutputHandler for OverlayState {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+
+    fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: wl_output::WlOutput) {}
+}
+
+impl LayerShellHandler for OverlayState {
+    fn closed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &LayerSurface) {
+        self.hide();
+    }
+
+    fn configure(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &LayerSurface,
+        _: LayerSurfaceConfigure,
+        _: u32,
+    ) {
+        // Size is fixed; draw on first configure and whenever highlight changes
+        if !self.configured {
+            self.configured = true;
+            self.draw();
+        }
+    }
+}
+
+impl ShmHandler for OverlayState {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+impl ProvidesRegistryState for OverlayState {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
+
+delegate_compositor!(OverlayState);
+delegate_output!(OverlayState);
+delegate_shm!(OverlayState);
+delegate_layer!(OverlayState);
+delegate_registry!(OverlayState);
+
+/// Start the layer-shell overlay on its own thread
+///
+/// Fails early if there is no Wayland display or the compositor lacks
+/// wlr-layer-shell (e.g. GNOME), so callers can fall back to the external overlay.
+pub fn spawn_layer_shell_overlay(theme: &Theme) -> Result<OverlayHandle, OverlayError> {
+    let palette = Palette::from_theme(theme);
+    let font_family = theme.overrides.as_ref().and_then(|overrides| overrides.custom_font.clone());
+    let (tx, rx) = channel::channel::<OverlayCommand>();
+    let (init_tx, init_rx) = std_mpsc::channel::<Result<(), OverlayError>>();
+
+    let thread = std::thread::Builder::new()
+        .name("juhradial-overlay".to_string())
+        .spawn(move || {
+            if let Err(e) = run_wayland_loop(palette, font_family.as_deref(), rx, &init_tx) {
+                let _ = init_tx.send(Err(e));
+            }
+        })
+        .map_err(|e| OverlayError::Setup(e.to_string()))?;
+
+    match init_rx.recv() {
+        Ok(Ok(())) => Ok(OverlayHandle {
+            tx,
+            thread: Some(thread),
+        }),
+        Ok(Err(e)) => {
+            let _ = thread.join();
+            Err(e)
+        }
+        Err(_) => Err(OverlayError::Setup("overlay thread exited during startup".to_string())),
+    }
+}
+
+/// Connect to Wayland, bind globals and run the calloop event loop
+fn run_wayland_loop(
+    palette: Palette,
+    font_family: Option<&str>,
+    rx: channel::Channel<OverlayCommand>,
+    init_tx: &std_mpsc::Sender<Result<(), OverlayError>>,
+) -> Result<(), OverlayError> {
+    let conn = Connection::connect_to_env()
+        .map_err(|e| OverlayError::NoWaylandDisplay(e.to_string()))?;
+    let (globals, event_queue) =
+        registry_queue_init::<OverlayState>(&conn).map_err(|e| OverlayError::Setup(e.to_string()))?;
+    let qh = event_queue.handle();
+
+    let compositor = CompositorState::bind(&globals, &qh)
+        .map_err(|_| OverlayError::MissingGlobal("wl_compositor"))?;
+    let layer_shell = LayerShell::bind(&globals, &qh)
+        .map_err(|_| OverlayError::MissingGlobal("zwlr_layer_shell_v1"))?;
+    let shm = Shm::bind(&globals, &qh).map_err(|_| OverlayError::MissingGlobal("wl_shm"))?;
+
+    let size = MENU_DIAMETER as u32;
+    let pool = SlotPool::new((size * size * 4) as usize, &shm)
+        .map_err(|e| OverlayError::Setup(e.to_string()))?;
+    let pixmap = Pixmap::new(size, size)
+        .ok_or_else(|| OverlayError::Setup("invalid pixmap size".to_string()))?;
+
+    let mut event_loop: EventLoop<OverlayState> =
+        EventLoop::try_new().map_err(|e| OverlayError::Setup(e.to_string()))?;
+
+    WaylandSource::new(conn.clone(), event_queue)
+        .insert(event_loop.handle())
+        .map_err(|e| OverlayError::Setup(e.to_string()))?;
+
+    let command_qh = qh.clone();
+    event_loop
+        .handle()
+        .insert_source(rx, move |event, _, state: &mut OverlayState| match event {
+            channel::Event::Msg(command) => state.handle_command(&command_qh, command),
+            channel::Event::Closed => state.exit = true,
+        })
+        .map_err(|e| OverlayError::Setup(e.to_string()))?;
+
+    let mut state = OverlayState {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+        compositor,
+        layer_shell,
+        shm,
+        pool,
+        layer: None,
+        configured: false,
+        highlight: None,
+        geometry: SliceGeometry::default(),
+        art: Vec::new(),
+        font: load_font(font_family),
+        icons: HashMap::new(),
+        palette,
+        pixmap,
+        exit: false,
+    };
+
+    let _ = init_tx.send(Ok(()));
+    tracing::info!("Built-in layer-shell overlay ready");
+
+    while !state.exit {
+        if let Err(e) = event_loop.dispatch(None, &mut state) {
+            tracing::error!("Overlay event loop error: {}", e);
+            break;
+        }
+    }
+
+    // Make sure no surface outlives the loop
+    state.hide();
+    let _ = conn.flush();
+    Ok(())
+}
+
+// ============================================================================
+// D-Bus bridge
+// ============================================================================
+
+/// Slice geometry and profile of a `GetMenuLayout` / `MenuReady` layout
+fn parse_menu_layout(layout: serde_json::Value) -> Option<(SliceGeometry, Profile)> {
+    let geometry = serde_json::from_value(layout["geometry"].clone()).ok()?;
+    let profile = serde_json::from_value(layout).ok()?;
+    Some((geometry, profile))
+}
+
+/// Layout of the open menu, for a `MenuRequested` without `MenuReady`
+async fn fetch_menu_layout(proxy: &zbus::Proxy<'_>) -> Option<(SliceGeometry, Profile)> {
+    match proxy.call::<_, _, String>("GetMenuLayout", &()).await {
+        Ok(json) => parse_menu_layout(serde_json::from_str(&json).ok()?),
+        Err(e) => {
+            tracing::debug!("GetMenuLayout failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Drive the built-in overlay from daemon D-Bus signals
+///
+/// Registers as the overlay with `RegisterOverlay`, forwards menu signals to
+/// the Wayland thread, reports the hovered slice and executes its action
+/// from the menu layout when the menu is dismissed.
+pub async fn run_dbus_bridge(overlay: OverlayHandle) -> zbus::Result<()> {
+    // Separate connection so the daemon sees us as a regular overlay client
+    let connection = zbus::Connection::session().await?;
+    let proxy = zbus::Proxy::new(&connection, DBUS_NAME, DBUS_PATH, DBUS_INTERFACE).await?;
+
+    let mut ready = proxy.receive_signal("MenuReady").await?;
+    let mut shown = proxy.receive_signal("MenuRequested").await?;
+    let mut hidden = proxy.receive_signal("HideMenu").await?;
+    let mut cancelled = proxy.receive_signal("MenuCancelled").await?;
+    let mut moved = proxy.receive_signal("CursorMoved").await?;
+
+    let interval_ms: u32 = proxy.call("RegisterOverlay", &("",)).await?;
+    let mut heartbeat = tokio::time::interval(tokio::time::Duration::from_millis(interval_ms.max(100) as u64));
+
+    let mut menu_open = false;
+    let mut highlight: Option<u8> = None;
+    let mut geometry = SliceGeometry::default();
+    let mut profile: Option<Profile> = None;
+    // Set by MenuReady, which the daemon emits just before MenuRequested
+    let mut prepared: Option<(SliceGeometry, Profile)> = None;
+
+    loop {
+        tokio::select! {
+            biased;
+            Some(msg) = ready.next() => {
+                let (_x, _y, payload): (i32, i32, String) = match msg.body().deserialize() {
+                    Ok(body) => body,
+                    Err(e) => {
+                        tracing::warn!("Malformed MenuReady signal: {}", e);
+                        continue;
+                    }
+                };
+                prepared = serde_json::from_str::<serde_json::Value>(&payload)
+                    .ok()
+                    .and_then(|mut payload| parse_menu_layout(payload["layout"].take()));
+            }
+            Some(msg) = shown.next() => {
+                let (x, y): (i32, i32) = match msg.body().deserialize() {
+                    Ok(body) => body,
+                    Err(e) => {
+                        tracing::warn!("Malformed MenuRequested signal: {}", e);
+                        continue;
+                    }
+                };
+                let menu = match prepared.take() {
+                    Some(menu) => Some(menu),
+                    None => fetch_menu_layout(&proxy).await,
+                };
+                let Some((menu_geometry, menu_profile)) = menu else {
+                    tracing::warn!("No menu layout for the built-in overlay");
+                    continue;
+                };
+                menu_open = true;
+                highlight = None;
+                geometry = menu_geometry;
+                let faces = SliceFace::from_profile(&menu_profile);
+                profile = Some(menu_profile);
+                overlay.send(OverlayCommand::Show { x, y, geometry, faces });
+            }
+            Some(msg) = moved.next() => {
+                if !menu_open {
+                    continue;
+                }
+                let (dx, dy): (i32, i32) = match msg.body().deserialize() {
+                    Ok(body) => body,
+                    Err(e) => {
+                        tracing::warn!("Malformed CursorMoved signal: {}", e);
+                        continue;
+                    }
+                };
+                let slice = geometry.hit_test(dx, dy, highlight);
+                if slice != highlight {
+                    highlight = slice;
+                    overlay.send(OverlayCommand::Highlight(slice));
+                    if let Some(index) = slice {
+                        let _ = proxy.call_method("NotifySliceHover", &(index,)).await;
+                        let _ = proxy.call_method("TriggerHaptic", &("slice_change",)).await;
+                    }
+                }
+            }
+            Some(_) = hidden.next() => {
+                if !menu_open {
+                    continue;
+                }
+                menu_open = false;
+                overlay.send(OverlayCommand::Hide);
+
+                let action = highlight
+                    .take()
+                    .zip(profile.take())
+                    .and_then(|(i, profile)| profile.slices.get(i as usize).cloned().flatten());
+                if let Some(action) = action {
+                    let _ = proxy.call_method("TriggerHaptic", &("confirm",)).await;
+                    crate::training::record_selection();
+                    if let Err(e) = ActionExecutor::execute(&action).await {
+                        tracing::warn!("Built-in overlay action failed: {}", e);
+                    }
+                }
+            }
+            Some(_) = cancelled.next() => {
+                if std::mem::take(&mut menu_open) {
+                    highlight = None;
+                    overlay.send(OverlayCommand::Hide);
+                }
+            }
+            _ = heartbeat.tick() => {
+                if let Err(e) = proxy.call_method("Heartbeat", &()).await {
+                    tracing::debug!("Overlay heartbeat failed: {}", e);
+                }
+            }
+            else => break,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slice_at_center_dead_zone() {
+        assert_eq!(slice_at(0, 0), None);
+        assert_eq!(slice_at(CENTER_RADIUS - 1, 0), None);
+    }
+
+    #[test]
+    fn test_slice_at_cardinal_directions() {
+        assert_eq!(slice_at(0, -100), Some(0)); // N
+        assert_eq!(slice_at(100, 0), Some(2)); // E
+        assert_eq!(slice_at(0, 100), Some(4)); // S
+        assert_eq!(slice_at(-100, 0), Some(6)); // W
+    }
+
+    #[test]
+    fn test_slice_at_diagonals() {
+        assert_eq!(slice_at(70, -70), Some(1)); // NE
+        assert_eq!(slice_at(70, 70), Some(3)); // SE
+        assert_eq!(slice_at(-70, 70), Some(5)); // SW
+        assert_eq!(slice_at(-70, -70), Some(7)); // NW
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        let c = parse_hex_color("#ff0000").unwrap();
+        assert_eq!(c.red(), 1.0);
+        assert_eq!(c.green(), 0.0);
+        assert!(parse_hex_color("#fff").is_some());
+        assert!(parse_hex_color("ff0000").is_none());
+        assert!(parse_hex_color("#zzzzzz").is_none());
+    }
+
+    #[test]
+    fn test_render_highlight_changes_pixels() {
+        let palette = Palette::from_theme(&crate::bundled_themes::get_default_theme());
+        let mut plain = Pixmap::new(MENU_DIAMETER as u32, MENU_DIAMETER as u32).unwrap();
+        let mut highlighted = plain.clone();
+
+        let geometry = SliceGeometry::default();
+        render(&mut plain, &palette, &geometry, &[], None);
+        render(&mut highlighted, &palette, &geometry, &[], Some(0));
+
+        assert_ne!(plain.data(), highlighted.data());
+        // Corners stay transparent
+        assert_eq!(plain.pixel(0, 0).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn test_render_draws_slice_art() {
+        let palette = Palette::from_theme(&crate::bundled_themes::get_default_theme());
+        let mut plain = Pixmap::new(MENU_DIAMETER as u32, MENU_DIAMETER as u32).unwrap();
+        let mut with_art = plain.clone();
+
+        let geometry = SliceGeometry::default();
+        let mut icon = Pixmap::new(ICON_SIZE, ICON_SIZE).unwrap();
+        icon.fill(Color::from_rgba8(255, 0, 0, 255));
+        let art = [SliceArt { label: None, icon: Some(icon) }];
+        render(&mut plain, &palette, &geometry, &[], None);
+        render(&mut with_art, &palette, &geometry, &art, None);
+
+        // The north slice's icon sits on the ring above the center
+        let radius = (MENU_RADIUS as f32 + geometry.dead_zone_radius) / 2.0;
+        let (x, y) = (MENU_RADIUS as u32, (MENU_RADIUS as f32 - radius) as u32);
+        assert_ne!(plain.pixel(x, y), with_art.pixel(x, y));
+        assert_eq!(with_art.pixel(x, y).unwrap().red(), 255);
+    }
+
+    #[test]
+    fn test_load_icon_file_and_tint() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dot.svg");
+        std::fs::write(
+            &path,
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="16"><rect width="16" height="16" fill="#000"/></svg>"##,
+        )
+        .unwrap();
+
+        let icon = load_icon(path.to_str().unwrap(), ICON_SIZE, None, Color::WHITE).unwrap();
+        assert_eq!((icon.width(), icon.height()), (ICON_SIZE, ICON_SIZE));
+        assert_eq!(icon.pixel(ICON_SIZE / 2, ICON_SIZE / 2).unwrap().alpha(), 255);
+
+        let tinted = tint(icon, Color::WHITE);
+        assert_eq!(tinted.pixel(ICON_SIZE / 2, ICON_SIZE / 2).unwrap().red(), 255);
+        assert!(load_icon("/nonexistent/icon.svg", ICON_SIZE, None, Color::WHITE).is_none());
+        assert!(load_icon("🎉", ICON_SIZE, None, Color::WHITE).is_none());
+    }
+
+    #[test]
+    fn test_text_pixmap_ellipsizes() {
+        // Needs a system font; nothing to check without one
+        let Some(font) = load_font(None) else {
+            return;
+        };
+        let short = text_pixmap(&font, "Copy", LABEL_SIZE, Color::WHITE, 80.0).unwrap();
+        let long = text_pixmap(&font, "A label far too long for any slice", LABEL_SIZE, Color::WHITE, 80.0).unwrap();
+        assert!(short.width() < 80);
+        assert!(long.width() <= 80);
+        assert!(short.pixels().iter().any(|p| p.alpha() > 0));
+    }
+
+    #[test]
+    fn test_icon_dir_rank() {
+        assert_eq!(icon_dir_rank(Path::new("/usr/share/icons/Adwaita/scalable/actions")), (0, 0));
+        assert_eq!(icon_dir_rank(Path::new("/usr/share/icons/Adwaita/symbolic/actions")), (0, 0));
+        assert_eq!(icon_dir_rank(Path::new("/usr/share/icons/hicolor/48x48/apps")), (1, 48));
+        assert_eq!(icon_dir_rank(Path::new("/usr/share/icons/breeze/actions/22")), (1, 22));
+        assert!(icon_dir_rank(Path::new("/usr/share/icons/hicolor/16x16/apps")) > (1, u32::MAX));
+    }
+
+    #[test]
+    fn test_parse_menu_layout() {
+        let mut layout = serde_json::to_value(crate::profiles::create_default_profile()).unwrap();
+        layout["geometry"] = serde_json::to_value(SliceGeometry::default()).unwrap();
+        layout["diameter"] = MENU_DIAMETER.into();
+        layout["slices"][0]["active"] = true.into();
+
+        let (geometry, profile) = parse_menu_layout(layout).unwrap();
+        assert_eq!(geometry, SliceGeometry::default());
+        assert!(profile.slices[0].is_some());
+        assert!(parse_menu_layout(serde_json::json!({"slices": []})).is_none());
+    }
+
+    #[test]
+    fn test_copy_to_argb8888_swaps_channels() {
+        let rgba = [1u8, 2, 3, 4];
+        let mut out = [0u8; 4];
+        copy_to_argb8888(&rgba, &mut out);
+        assert_eq!(out, [3, 2, 1, 4]);
+    }
+}
@@ -0,0 +1,351 @@
+//! Overlay liveness tracking for JuhRadial MX
+//!
+//! The overlay announces itself with `RegisterOverlay` and then calls
+//! `Heartbeat` periodically. If heartbeats stop while the radial menu is open,
+//! the daemon resets its menu state and emits `HideMenu` so nothing is left
+//! stuck on screen. Optionally the overlay is re-activated through the D-Bus
+//! service name it registered with.
+//!
+//! The registered overlay also confirms the menu signals it handled with
+//! `AcknowledgeSignal`, which `juhradialctl selftest` checks (see
+//! [`crate::self_test`]).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::SharedConfig;
+use crate::dbus::{DBUS_INTERFACE, DBUS_PATH};
+
+/// Heartbeat interval requested from the overlay (milliseconds)
+pub const HEARTBEAT_INTERVAL_MS: u32 = 1000;
+
+/// How often the monitor task checks for missed heartbeats (milliseconds)
+const MONITOR_CHECK_INTERVAL_MS: u64 = 500;
+
+/// Registration details reported by the overlay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlayRegistration {
+    /// Unique bus name of the overlay connection (e.g. ":1.42")
+    pub sender: String,
+    /// Well-known service name used to re-activate the overlay (if any)
+    pub service_name: Option<String>,
+}
+
+/// Overlay liveness state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverlayStatus {
+    /// No overlay has registered yet
+    #[default]
+    Unregistered,
+    /// Overlay is sending heartbeats
+    Alive,
+    /// Overlay stopped sending heartbeats
+    Lost,
+}
+
+/// Reported once when a registered overlay stops sending heartbeats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlayLost {
+    /// Whether the menu was open when the overlay went away
+    pub menu_was_open: bool,
+    /// Service name the overlay registered with (for re-activation)
+    pub service_name: Option<String>,
+}
+
+/// Tracks overlay registration, heartbeats and menu visibility
+#[derive(Debug, Default)]
+pub struct OverlayMonitor {
+    /// Current registration (None until RegisterOverlay or first Heartbeat)
+    registration: Option<OverlayRegistration>,
+    /// Timestamp of the last heartbeat (milliseconds since epoch)
+    last_heartbeat_ms: u64,
+    /// Current liveness state
+    status: OverlayStatus,
+    /// Whether the daemon believes the menu is currently shown
+    menu_open: bool,
+    /// Last acknowledgement per signal name (milliseconds since epoch)
+    acks: HashMap<String, u64>,
+}
+
+impl OverlayMonitor {
+    /// Create a new monitor with no overlay registered
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an overlay connection
+    ///
+    /// A new registration replaces any previous one (e.g. after an overlay restart).
+    pub fn register(&mut self, sender: &str, service_name: Option<String>, now_ms: u64) {
+        tracing::info!(sender, service_name = ?service_name, "Overlay registered");
+        self.registration = Some(OverlayRegistration {
+            sender: sender.to_string(),
+            service_name,
+        });
+        self.last_heartbeat_ms = now_ms;
+        self.status = OverlayStatus::Alive;
+        self.acks.clear();
+    }
+
+    /// Record a heartbeat from the overlay
+    ///
+    /// Heartbeats from an unknown sender implicitly register it, so an overlay
+    /// that outlives a daemon restart is picked up again without re-registering.
+    pub fn heartbeat(&mut self, sender: &str, now_ms: u64) {
+        let known = self
+            .registration
+            .as_ref()
+            .is_some_and(|r| r.sender == sender);
+
+        if !known {
+            self.register(sender, None, now_ms);
+            return;
+        }
+
+        if self.status == OverlayStatus::Lost {
+            tracing::info!(sender, "Overlay heartbeat resumed");
+        }
+        self.last_heartbeat_ms = now_ms;
+        self.status = OverlayStatus::Alive;
+    }
+
+    /// Record that the registered overlay handled `signal`
+    ///
+    /// Returns false (and records nothing) for other senders.
+    pub fn acknowledge(&mut self, sender: &str, signal: &str, now_ms: u64) -> bool {
+        if self.registration.as_ref().is_none_or(|r| r.sender != sender) {
+            return false;
+        }
+        self.acks.insert(signal.to_string(), now_ms);
+        true
+    }
+
+    /// Whether `signal` was acknowledged at or after `since_ms`
+    pub fn acknowledged_since(&self, signal: &str, since_ms: u64) -> bool {
+        self.acks.get(signal).is_some_and(|&at| at >= since_ms)
+    }
+
+    /// Update whether the menu is currently shown
+    pub fn set_menu_open(&mut self, open: bool) {
+        self.menu_open = open;
+        crate::menu_grab::set_menu_open(open);
+    }
+
+    /// Check if the menu is currently shown
+    pub fn is_menu_open(&self) -> bool {
+        self.menu_open
+    }
+
+    /// Get current overlay liveness state
+    pub fn status(&self) -> OverlayStatus {
+        self.status
+    }
+
+    /// Get the current overlay registration
+    pub fn registration(&self) -> Option<&OverlayRegistration> {
+        self.registration.as_ref()
+    }
+
+    /// Check for missed heartbeats
+    ///
+    /// Returns `Some` exactly once when a live overlay has gone longer than
+    /// `timeout_ms` without a heartbeat. Menu state is reset at that point.
+    pub fn check(&mut self, now_ms: u64, timeout_ms: u64) -> Option<OverlayLost> {
+        if self.status != OverlayStatus::Alive {
+            return None;
+        }
+
+        if now_ms.saturating_sub(self.last_heartbeat_ms) < timeout_ms {
+            return None;
+        }
+
+        self.status = OverlayStatus::Lost;
+        let menu_was_open = self.menu_open;
+        self.set_menu_open(false);
+
+        Some(OverlayLost {
+            menu_was_open,
+            service_name: self.registration.as_ref().and_then(|r| r.service_name.clone()),
+        })
+    }
+}
+
+/// Thread-safe shared overlay monitor
+pub type SharedOverlayMonitor = Arc<RwLock<OverlayMonitor>>;
+
+/// Create a new shared overlay monitor
+pub fn new_shared_overlay_monitor() -> SharedOverlayMonitor {
+    Arc::new(RwLock::new(OverlayMonitor::new()))
+}
+
+/// Current time in milliseconds since the Unix epoch
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Run the overlay monitor loop
+///
+/// Checks for missed heartbeats and recovers when the overlay dies:
+/// emits `HideMenu` if the menu was open and, when `overlay.restart_on_crash`
+/// is set, asks the bus to re-activate the overlay's service name.
+pub async fn start_overlay_monitor(
+    monitor: SharedOverlayMonitor,
+    connection: zbus::Connection,
+    config: SharedConfig,
+) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(
+        MONITOR_CHECK_INTERVAL_MS,
+    ));
+
+    loop {
+        interval.tick().await;
+
+        let (timeout_ms, restart_on_crash) = match config.read() {
+            Ok(c) => (c.overlay.heartbeat_timeout_ms, c.overlay.restart_on_crash),
+            Err(_) => continue,
+        };
+
+        let lost = match monitor.write() {
+            Ok(mut m) => m.check(now_ms(), timeout_ms),
+            Err(_) => continue,
+        };
+
+        let Some(lost) = lost else {
+            continue;
+        };
+
+        tracing::warn!(
+            timeout_ms,
+            menu_was_open = lost.menu_was_open,
+            "Overlay heartbeat lost - overlay may have crashed"
+        );
+
+        if lost.menu_was_open {
+            if let Err(e) = connection
+                .emit_signal(None::<&str>, DBUS_PATH, DBUS_INTERFACE, "HideMenu", &())
+                .await
+            {
+                tracing::error!("Failed to emit HideMenu after overlay loss: {}", e);
+            }
+        }
+
+        if restart_on_crash {
+            if let Some(service_name) = lost.service_name {
+                reactivate_overlay(&connection, &service_name).await;
+            }
+        }
+    }
+}
+
+/// Ask the bus to (re)start the overlay via D-Bus activation
+async fn reactivate_overlay(connection: &zbus::Connection, service_name: &str) {
+    let proxy = match zbus::fdo::DBusProxy::new(connection).await {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::error!("Failed to create org.freedesktop.DBus proxy: {}", e);
+            return;
+        }
+    };
+
+    let name = match zbus::names::WellKnownName::try_from(service_name) {
+        Ok(n) => n,
+        Err(e) => {
+            tracing::warn!(service_name, "Invalid overlay service name: {}", e);
+            return;
+        }
+    };
+
+    match proxy.start_service_by_name(name, 0).await {
+        Ok(reply) => tracing::info!(service_name, ?reply, "Requested overlay re-activation"),
+        Err(e) => tracing::warn!(service_name, "Failed to re-activate overlay: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_monitor_unregistered() {
+        let monitor = OverlayMonitor::new();
+        assert_eq!(monitor.status(), OverlayStatus::Unregistered);
+        assert!(monitor.registration().is_none());
+        assert!(!monitor.is_menu_open());
+    }
+
+    #[test]
+    fn test_register_and_heartbeat() {
+        let mut monitor = OverlayMonitor::new();
+        monitor.register(":1.42", Some("org.kde.juhradialmx.Overlay".to_string()), 1000);
+        assert_eq!(monitor.status(), OverlayStatus::Alive);
+
+        monitor.heartbeat(":1.42", 4000);
+        // 4000ms since registration but only 2000ms since last heartbeat
+        assert!(monitor.check(6000, 5000).is_none());
+        assert_eq!(monitor.status(), OverlayStatus::Alive);
+    }
+
+    #[test]
+    fn test_acknowledgements() {
+        let mut monitor = OverlayMonitor::new();
+        assert!(!monitor.acknowledge(":1.42", "MenuRequested", 1000));
+
+        monitor.register(":1.42", None, 1000);
+        assert!(!monitor.acknowledge(":1.99", "MenuRequested", 1100));
+        assert!(monitor.acknowledge(":1.42", "MenuRequested", 1100));
+        assert!(monitor.acknowledged_since("MenuRequested", 1100));
+        assert!(!monitor.acknowledged_since("MenuRequested", 1101));
+        assert!(!monitor.acknowledged_since("HideMenu", 0));
+
+        // A new registration starts over
+        monitor.register(":1.43", None, 2000);
+        assert!(!monitor.acknowledged_since("MenuRequested", 0));
+    }
+
+    #[test]
+    fn test_unregistered_never_lost() {
+        let mut monitor = OverlayMonitor::new();
+        assert!(monitor.check(u64::MAX, 5000).is_none());
+    }
+
+    #[test]
+    fn test_lost_resets_menu_state_once() {
+        let mut monitor = OverlayMonitor::new();
+        monitor.register(":1.42", Some("org.kde.juhradialmx.Overlay".to_string()), 1000);
+        monitor.set_menu_open(true);
+
+        let lost = monitor.check(7000, 5000).expect("overlay should be lost");
+        assert!(lost.menu_was_open);
+        assert_eq!(lost.service_name.as_deref(), Some("org.kde.juhradialmx.Overlay"));
+        assert!(!monitor.is_menu_open());
+        assert_eq!(monitor.status(), OverlayStatus::Lost);
+
+        // Only reported once
+        assert!(monitor.check(8000, 5000).is_none());
+    }
+
+    #[test]
+    fn test_heartbeat_from_unknown_sender_registers() {
+        let mut monitor = OverlayMonitor::new();
+        monitor.heartbeat(":1.7", 1000);
+        assert_eq!(monitor.status(), OverlayStatus::Alive);
+        assert_eq!(monitor.registration().unwrap().sender, ":1.7");
+        assert!(monitor.registration().unwrap().service_name.is_none());
+    }
+
+    #[test]
+    fn test_heartbeat_resumes_after_lost() {
+        let mut monitor = OverlayMonitor::new();
+        monitor.register(":1.42", None, 1000);
+        assert!(monitor.check(10_000, 5000).is_some());
+
+        monitor.heartbeat(":1.42", 11_000);
+        assert_eq!(monitor.status(), OverlayStatus::Alive);
+    }
+}
@@ -29,6 +29,7 @@ pub enum BlurMode {
 
 impl BlurMode {
     /// Parse from string (for config files)
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "on" | "forceon" | "force_on" | "enabled" => BlurMode::ForceOn,
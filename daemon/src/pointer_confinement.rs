@@ -0,0 +1,141 @@
+//! Menu placement while a fullscreen app may confine the pointer
+//!
+//! Games and other fullscreen apps often lock or confine the pointer
+//! (`zwp_pointer_constraints_v1`, X11 pointer grabs). The compositor then
+//! keeps reporting wherever the pointer was when the constraint started, or
+//! wherever the app keeps warping it, and the menu opens at a stale spot,
+//! sometimes on another monitor.
+//!
+//! No compositor exposes the constraint state over IPC, so it is inferred:
+//! when the focused window is fullscreen (see
+//! [`crate::compositor::CompositorBackend::focused_window`]) the reported
+//! cursor is distrusted if it
+//!
+//! - could not be read at all,
+//! - lies outside the focused window, or
+//! - has not moved since the previous menu open.
+//!
+//! In those cases the menu opens at the center of the focused window. Outside
+//! fullscreen windows, and on backends that cannot report the focused window,
+//! the cursor position is used as before.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::Mutex;
+
+use crate::compositor::FocusedWindow;
+use crate::cursor::CursorPosition;
+
+/// Cursor position reported at the previous menu open
+static LAST_REPORTED: Mutex<Option<CursorPosition>> = Mutex::new(None);
+
+/// Why the reported cursor position was not used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Correction {
+    /// The backend could not report the cursor
+    Unknown,
+    /// The cursor is outside the fullscreen window
+    OutsideWindow,
+    /// The cursor has not moved since the previous menu open
+    Stale,
+}
+
+impl Correction {
+    /// Short description for logs
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Correction::Unknown => "cursor unknown",
+            Correction::OutsideWindow => "cursor outside fullscreen window",
+            Correction::Stale => "cursor unchanged since last open",
+        }
+    }
+}
+
+/// Where to open the menu
+///
+/// `previous` is the cursor position reported at the previous open. Returns
+/// the position and, if the cursor was not used, why.
+pub fn place(
+    cursor: Option<CursorPosition>,
+    window: Option<FocusedWindow>,
+    previous: Option<CursorPosition>,
+) -> (CursorPosition, Option<Correction>) {
+    let Some(window) = window.filter(|w| w.fullscreen && w.width > 0 && w.height > 0) else {
+        return (cursor.unwrap_or_default(), None);
+    };
+    let correction = match cursor {
+        None => Correction::Unknown,
+        Some(pos) if !window.contains(pos) => Correction::OutsideWindow,
+        Some(pos) if previous == Some(pos) => Correction::Stale,
+        Some(pos) => return (pos, None),
+    };
+    (window.center(), Some(correction))
+}
+
+/// Position to open the menu at, for press sites
+///
+/// Queries the session's [`crate::compositor::CompositorBackend`] for the
+/// cursor and the focused window and applies [`place`]. Returns (0, 0) if
+/// neither can be read.
+pub fn menu_position() -> CursorPosition {
+    let backend = crate::compositor::backend();
+    let cursor = backend.cursor_pos();
+    let window = backend.focused_window();
+    let previous = LAST_REPORTED
+        .lock()
+        .ok()
+        .and_then(|mut last| std::mem::replace(&mut *last, cursor));
+
+    let (pos, correction) = place(cursor, window, previous);
+    match correction {
+        Some(correction) => tracing::info!(
+            backend = backend.name(),
+            x = pos.x,
+            y = pos.y,
+            "Pointer may be confined ({}), centering menu on the focused window",
+            correction.as_str()
+        ),
+        None if cursor.is_none() => {
+            tracing::warn!(backend = backend.name(), "Could not query cursor position, using default (0, 0)")
+        }
+        None => {}
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(fullscreen: bool) -> Option<FocusedWindow> {
+        Some(FocusedWindow { x: 1920, y: 0, width: 2560, height: 1440, fullscreen })
+    }
+
+    #[test]
+    fn test_cursor_used_outside_fullscreen() {
+        let cursor = Some(CursorPosition::new(100, 200));
+        assert_eq!(place(cursor, window(false), cursor), (CursorPosition::new(100, 200), None));
+        assert_eq!(place(cursor, None, cursor), (CursorPosition::new(100, 200), None));
+        assert_eq!(place(None, None, None), (CursorPosition::default(), None));
+    }
+
+    #[test]
+    fn test_cursor_used_when_it_moved_inside_fullscreen() {
+        let cursor = Some(CursorPosition::new(2000, 300));
+        let previous = Some(CursorPosition::new(2100, 300));
+        assert_eq!(place(cursor, window(true), previous), (CursorPosition::new(2000, 300), None));
+        assert_eq!(place(cursor, window(true), None), (CursorPosition::new(2000, 300), None));
+    }
+
+    #[test]
+    fn test_fullscreen_falls_back_to_window_center() {
+        let center = CursorPosition::new(3200, 720);
+        let stale = Some(CursorPosition::new(2000, 300));
+        assert_eq!(place(None, window(true), None), (center, Some(Correction::Unknown)));
+        assert_eq!(
+            place(Some(CursorPosition::new(500, 300)), window(true), None),
+            (center, Some(Correction::OutsideWindow))
+        );
+        assert_eq!(place(stale, window(true), stale), (center, Some(Correction::Stale)));
+    }
+}
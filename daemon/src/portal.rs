@@ -0,0 +1,554 @@
+//! xdg-desktop-portal integration for sandboxed (Flatpak) operation
+//!
+//! In portal mode the daemon does not scan /dev and does not rely on logid,
+//! xdotool or ydotool. Instead it talks to xdg-desktop-portal:
+//!
+//! - **RemoteDesktop** portal for keyboard injection (shortcut actions)
+//! - **GlobalShortcuts** portal for the menu trigger (see `global_shortcuts`)
+//! - **Screenshot** portal's `PickColor` and `Screenshot` for `pick_color`
+//!   and `ocr` actions (any mode)
+//!
+//! Portal methods follow the Request/Response pattern: each call returns a
+//! request object that later emits `org.freedesktop.portal.Request.Response`.
+//! [`portal_request`] subscribes to the predictable request path before
+//! calling, so the response can't be missed.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::OnceLock;
+
+use tokio_stream::StreamExt;
+use zbus::zvariant::{DynamicType, ObjectPath, OwnedObjectPath, OwnedValue, Value};
+
+use crate::config::RuntimeMode;
+
+/// Portal bus name
+pub const PORTAL_NAME: &str = "org.freedesktop.portal.Desktop";
+
+/// Portal object path
+pub const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+
+/// RemoteDesktop portal interface
+const REMOTE_DESKTOP_INTERFACE: &str = "org.freedesktop.portal.RemoteDesktop";
+
+/// Screenshot portal interface (PickColor)
+const SCREENSHOT_INTERFACE: &str = "org.freedesktop.portal.Screenshot";
+
+/// Request interface (Response signal)
+const REQUEST_INTERFACE: &str = "org.freedesktop.portal.Request";
+
+/// RemoteDesktop device type: keyboard
+const DEVICE_KEYBOARD: u32 = 1;
+
+/// Persist the RemoteDesktop permission until explicitly revoked
+const PERSIST_UNTIL_REVOKED: u32 = 2;
+
+/// Key states for NotifyKeyboardKeysym
+const KEY_RELEASED: u32 = 0;
+const KEY_PRESSED: u32 = 1;
+
+/// File (in the data dir) holding the RemoteDesktop restore token
+const RESTORE_TOKEN_FILE: &str = "remote-desktop-token";
+
+/// Counter for unique request/session tokens
+static TOKEN_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Active RemoteDesktop session (set once in portal mode)
+static REMOTE_DESKTOP: OnceLock<RemoteDesktopSession> = OnceLock::new();
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+/// Portal error type
+#[derive(Debug)]
+pub enum PortalError {
+    /// D-Bus communication error
+    DBus(zbus::Error),
+    /// User cancelled the portal dialog
+    Cancelled,
+    /// Portal reported failure (response code)
+    Failed(u32),
+    /// Portal response lacked an expected result
+    MissingResult(&'static str),
+    /// Shortcut string could not be mapped to keysyms
+    UnknownKey(String),
+}
+
+impl std::fmt::Display for PortalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortalError::DBus(e) => write!(f, "D-Bus error: {}", e),
+            PortalError::Cancelled => write!(f, "Portal request cancelled by user"),
+            PortalError::Failed(code) => write!(f, "Portal request failed (response {})", code),
+            PortalError::MissingResult(key) => write!(f, "Portal response missing '{}'", key),
+            PortalError::UnknownKey(key) => write!(f, "Unknown key: {}", key),
+        }
+    }
+}
+
+impl std::error::Error for PortalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PortalError::DBus(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<zbus::Error> for PortalError {
+    fn from(e: zbus::Error) -> Self {
+        PortalError::DBus(e)
+    }
+}
+
+impl From<zbus::zvariant::Error> for PortalError {
+    fn from(e: zbus::zvariant::Error) -> Self {
+        PortalError::DBus(e.into())
+    }
+}
+
+// ============================================================================
+// Mode Detection
+// ============================================================================
+
+/// Check if the daemon runs inside a Flatpak sandbox
+pub fn running_in_flatpak() -> bool {
+    Path::new("/.flatpak-info").exists() || std::env::var_os("FLATPAK_ID").is_some()
+}
+
+/// Check if at least one evdev node can be opened
+pub fn dev_input_accessible() -> bool {
+    let Ok(entries) = std::fs::read_dir("/dev/input") else {
+        return false;
+    };
+
+    entries.flatten().any(|entry| {
+        entry.file_name().to_string_lossy().starts_with("event")
+            && std::fs::File::open(entry.path()).is_ok()
+    })
+}
+
+/// Resolve the configured mode to Native or Portal
+///
+/// The mode is resolved once at startup, so `Auto` only picks Portal inside
+/// Flatpak: an unreadable /dev/input on a host install is usually a missing
+/// udev rule or group membership that may be fixed while the daemon runs, and
+/// native mode picks devices up once they become accessible.
+///
+/// Never returns `RuntimeMode::Auto`.
+pub fn resolve_mode(configured: RuntimeMode, in_flatpak: bool) -> RuntimeMode {
+    match configured {
+        RuntimeMode::Auto if in_flatpak => RuntimeMode::Portal,
+        RuntimeMode::Auto => RuntimeMode::Native,
+        mode => mode,
+    }
+}
+
+// ============================================================================
+// Request/Response Helper
+// ============================================================================
+
+/// Portal options dictionary
+pub type PortalOptions<'a> = HashMap<&'a str, Value<'a>>;
+
+/// Portal results dictionary
+pub type PortalResults = HashMap<String, OwnedValue>;
+
+/// Generate a unique handle token
+pub fn new_token() -> String {
+    format!(
+        "juhradial_{}_{}",
+        std::process::id(),
+        TOKEN_COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Predict the request object path for a handle token
+///
+/// Per the portal spec: `/org/freedesktop/portal/desktop/request/SENDER/TOKEN`
+/// where SENDER is the unique name without the leading ':' and with '.' as '_'.
+fn request_path(unique_name: &str, token: &str) -> String {
+    let sender = unique_name.trim_start_matches(':').replace('.', "_");
+    format!("{}/request/{}/{}", PORTAL_PATH, sender, token)
+}
+
+/// Call a portal method that uses the Request/Response pattern
+///
+/// `body` must include an options dictionary with `handle_token` set to `token`.
+pub async fn portal_request<B>(
+    connection: &zbus::Connection,
+    interface: &str,
+    method: &str,
+    body: &B,
+    token: &str,
+) -> Result<PortalResults, PortalError>
+where
+    B: serde::Serialize + DynamicType,
+{
+    let unique_name = connection
+        .unique_name()
+        .ok_or(PortalError::MissingResult("unique_name"))?
+        .to_string();
+
+    // Subscribe before calling so a fast response is not lost
+    let request = zbus::Proxy::new(
+        connection,
+        PORTAL_NAME,
+        request_path(&unique_name, token),
+        REQUEST_INTERFACE,
+    )
+    .await?;
+    let mut responses = request.receive_signal("Response").await?;
+
+    connection
+        .call_method(Some(PORTAL_NAME), PORTAL_PATH, Some(interface), method, body)
+        .await?;
+
+    let message = responses
+        .next()
+        .await
+        .ok_or(PortalError::MissingResult("Response"))?;
+    let (response, results): (u32, PortalResults) = message.body().deserialize()?;
+
+    match response {
+        0 => Ok(results),
+        1 => Err(PortalError::Cancelled),
+        code => Err(PortalError::Failed(code)),
+    }
+}
+
+/// Create a portal session on a session-based interface
+///
+/// Returns the session object path.
+pub async fn create_session(
+    connection: &zbus::Connection,
+    interface: &str,
+) -> Result<OwnedObjectPath, PortalError> {
+    let token = new_token();
+    let session_token = new_token();
+    let options: PortalOptions = HashMap::from([
+        ("handle_token", Value::from(token.as_str())),
+        ("session_handle_token", Value::from(session_token.as_str())),
+    ]);
+    let results = portal_request(connection, interface, "CreateSession", &(options,), &token).await?;
+
+    let session_handle = results
+        .get("session_handle")
+        .and_then(|v| <&str>::try_from(v).ok())
+        .ok_or(PortalError::MissingResult("session_handle"))?
+        .to_string();
+    Ok(OwnedObjectPath::try_from(session_handle)?)
+}
+
+// ============================================================================
+// Color Picking
+// ============================================================================
+
+/// Let the user pick a color on screen; returns sRGB components in 0.0..=1.0
+pub async fn pick_color(connection: &zbus::Connection) -> Result<(f64, f64, f64), PortalError> {
+    let token = new_token();
+    let options: PortalOptions = HashMap::from([("handle_token", Value::from(token.as_str()))]);
+    let results = portal_request(connection, SCREENSHOT_INTERFACE, "PickColor", &("", options), &token).await?;
+
+    let color = results.get("color").ok_or(PortalError::MissingResult("color"))?;
+    Ok(<(f64, f64, f64)>::try_from(color.try_clone()?)?)
+}
+
+/// Let the user capture the screen or a region; returns the image file
+pub async fn screenshot(connection: &zbus::Connection) -> Result<PathBuf, PortalError> {
+    let token = new_token();
+    let options: PortalOptions = HashMap::from([
+        ("handle_token", Value::from(token.as_str())),
+        ("interactive", Value::from(true)),
+    ]);
+    let results = portal_request(connection, SCREENSHOT_INTERFACE, "Screenshot", &("", options), &token).await?;
+
+    results
+        .get("uri")
+        .and_then(|v| <&str>::try_from(v).ok())
+        .and_then(file_uri_to_path)
+        .ok_or(PortalError::MissingResult("uri"))
+}
+
+/// Path of a `file://` URI (percent-decoded)
+fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let hex = encoded.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (encoded[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                bytes.push(byte);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(OsString::from_vec(bytes)))
+}
+
+/// Format picked components as `#rrggbb`
+pub fn color_to_hex((r, g, b): (f64, f64, f64)) -> String {
+    let channel = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b))
+}
+
+// ============================================================================
+// RemoteDesktop Key Injection
+// ============================================================================
+
+/// RemoteDesktop portal session used for keyboard injection
+pub struct RemoteDesktopSession {
+    connection: zbus::Connection,
+    session: OwnedObjectPath,
+}
+
+impl RemoteDesktopSession {
+    /// Create, configure and start a keyboard-only RemoteDesktop session
+    ///
+    /// Reuses a saved restore token so the permission dialog is only shown once.
+    pub async fn start(connection: &zbus::Connection) -> Result<Self, PortalError> {
+        let session = create_session(connection, REMOTE_DESKTOP_INTERFACE).await?;
+
+        // SelectDevices
+        let token = new_token();
+        let restore_token = load_restore_token();
+        let mut options: PortalOptions = HashMap::from([
+            ("handle_token", Value::from(token.as_str())),
+            ("types", Value::from(DEVICE_KEYBOARD)),
+            ("persist_mode", Value::from(PERSIST_UNTIL_REVOKED)),
+        ]);
+        if let Some(restore) = restore_token.as_deref() {
+            options.insert("restore_token", Value::from(restore));
+        }
+        portal_request(
+            connection,
+            REMOTE_DESKTOP_INTERFACE,
+            "SelectDevices",
+            &(ObjectPath::from(&session), options),
+            &token,
+        )
+        .await?;
+
+        // Start (shows the permission dialog unless restored)
+        let token = new_token();
+        let options: PortalOptions = HashMap::from([("handle_token", Value::from(token.as_str()))]);
+        let results = portal_request(
+            connection,
+            REMOTE_DESKTOP_INTERFACE,
+            "Start",
+            &(ObjectPath::from(&session), "", options),
+            &token,
+        )
+        .await?;
+
+        if let Some(new_token) = results.get("restore_token").and_then(|v| <&str>::try_from(v).ok()) {
+            save_restore_token(new_token);
+        }
+
+        tracing::info!(session = %session.as_str(), "RemoteDesktop portal session started");
+
+        Ok(Self {
+            connection: connection.clone(),
+            session,
+        })
+    }
+
+    /// Send a single keysym press or release
+    async fn notify_keysym(&self, keysym: i32, state: u32) -> Result<(), PortalError> {
+        let options: PortalOptions = HashMap::new();
+        self.connection
+            .call_method(
+                Some(PORTAL_NAME),
+                PORTAL_PATH,
+                Some(REMOTE_DESKTOP_INTERFACE),
+                "NotifyKeyboardKeysym",
+                &(ObjectPath::from(&self.session), options, keysym, state),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Inject a shortcut such as "ctrl+shift+z"
+    ///
+    /// Keys are pressed in order and released in reverse order.
+    pub async fn send_shortcut(&self, keys: &str) -> Result<(), PortalError> {
+        let keysyms = shortcut_to_keysyms(keys)?;
+
+        for &keysym in &keysyms {
+            self.notify_keysym(keysym, KEY_PRESSED).await?;
+        }
+        for &keysym in keysyms.iter().rev() {
+            self.notify_keysym(keysym, KEY_RELEASED).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Start the RemoteDesktop session and make it available to actions
+pub async fn init_remote_desktop(connection: &zbus::Connection) -> Result<(), PortalError> {
+    let session = RemoteDesktopSession::start(connection).await?;
+    if REMOTE_DESKTOP.set(session).is_err() {
+        tracing::debug!("RemoteDesktop session already initialized");
+    }
+    Ok(())
+}
+
+/// Get the active RemoteDesktop session (portal mode only)
+pub fn remote_desktop() -> Option<&'static RemoteDesktopSession> {
+    REMOTE_DESKTOP.get()
+}
+
+/// Path of the saved restore token
+fn restore_token_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|p| p.join("juhradial").join(RESTORE_TOKEN_FILE))
+}
+
+fn load_restore_token() -> Option<String> {
+    let token = std::fs::read_to_string(restore_token_path()?).ok()?;
+    let token = token.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+fn save_restore_token(token: &str) {
+    let Some(path) = restore_token_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(e) = std::fs::write(&path, token) {
+        tracing::warn!(path = %path.display(), "Failed to save RemoteDesktop restore token: {}", e);
+    }
+}
+
+// ============================================================================
+// Keysym Mapping
+// ============================================================================
+
+/// Map a key name (as used in shortcut actions) to an X11 keysym
+fn keysym_for(name: &str) -> Option<i32> {
+    let keysym = match name {
+        "ctrl" | "control" => 0xffe3,
+        "shift" => 0xffe1,
+        "alt" => 0xffe9,
+        "super" | "meta" | "win" => 0xffeb,
+        "return" | "enter" => 0xff0d,
+        "escape" | "esc" => 0xff1b,
+        "tab" => 0xff09,
+        "space" => 0x0020,
+        "backspace" => 0xff08,
+        "delete" | "del" => 0xffff,
+        "insert" => 0xff63,
+        "home" => 0xff50,
+        "end" => 0xff57,
+        "left" => 0xff51,
+        "up" => 0xff52,
+        "right" => 0xff53,
+        "down" => 0xff54,
+        "page_up" | "pageup" | "prior" => 0xff55,
+        "page_down" | "pagedown" | "next" => 0xff56,
+        "print" => 0xff61,
+        "minus" => 0x002d,
+        "plus" | "equal" => 0x003d,
+        _ => {
+            if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<i32>().ok()) {
+                // F1..F24 are contiguous from 0xffbe
+                return (1..=24).contains(&n).then_some(0xffbe + n - 1);
+            }
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                // Latin-1 printable characters map to their code point
+                (Some(c), None) if c.is_ascii_graphic() => c as i32,
+                _ => return None,
+            }
+        }
+    };
+    Some(keysym)
+}
+
+/// Convert a shortcut string ("ctrl+shift+z") to keysyms
+pub fn shortcut_to_keysyms(keys: &str) -> Result<Vec<i32>, PortalError> {
+    let keys = keys.to_lowercase();
+    keys.split('+')
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+        .map(|k| keysym_for(k).ok_or_else(|| PortalError::UnknownKey(k.to_string())))
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|syms| {
+            if syms.is_empty() {
+                Err(PortalError::UnknownKey(keys.clone()))
+            } else {
+                Ok(syms)
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_mode() {
+        assert_eq!(resolve_mode(RuntimeMode::Auto, false), RuntimeMode::Native);
+        assert_eq!(resolve_mode(RuntimeMode::Auto, true), RuntimeMode::Portal);
+        assert_eq!(resolve_mode(RuntimeMode::Native, true), RuntimeMode::Native);
+        assert_eq!(resolve_mode(RuntimeMode::Portal, false), RuntimeMode::Portal);
+    }
+
+    #[test]
+    fn test_request_path() {
+        assert_eq!(
+            request_path(":1.42", "juhradial_1_0"),
+            "/org/freedesktop/portal/desktop/request/1_42/juhradial_1_0"
+        );
+    }
+
+    #[test]
+    fn test_color_to_hex() {
+        assert_eq!(color_to_hex((1.0, 0.5, 0.0)), "#ff8000");
+        assert_eq!(color_to_hex((-0.1, 0.0, 1.2)), "#0000ff");
+    }
+
+    #[test]
+    fn test_file_uri_to_path() {
+        assert_eq!(
+            file_uri_to_path("file:///home/me/Pictures/Screenshot%20from%2012%3A00.png"),
+            Some(PathBuf::from("/home/me/Pictures/Screenshot from 12:00.png"))
+        );
+        assert_eq!(file_uri_to_path("https://example.org/x.png"), None);
+    }
+
+    #[test]
+    fn test_new_token_unique() {
+        assert_ne!(new_token(), new_token());
+    }
+
+    #[test]
+    fn test_shortcut_to_keysyms() {
+        assert_eq!(shortcut_to_keysyms("ctrl+c").unwrap(), vec![0xffe3, 'c' as i32]);
+        assert_eq!(
+            shortcut_to_keysyms("Ctrl+Shift+Z").unwrap(),
+            vec![0xffe3, 0xffe1, 'z' as i32]
+        );
+        assert_eq!(shortcut_to_keysyms("super+f12").unwrap(), vec![0xffeb, 0xffc9]);
+        assert_eq!(shortcut_to_keysyms("alt+tab").unwrap(), vec![0xffe9, 0xff09]);
+    }
+
+    #[test]
+    fn test_shortcut_to_keysyms_invalid() {
+        assert!(matches!(shortcut_to_keysyms("ctrl+bogus"), Err(PortalError::UnknownKey(_))));
+        assert!(shortcut_to_keysyms("").is_err());
+        assert!(shortcut_to_keysyms("f25").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! Power profile switching through power-profiles-daemon
+//!
+//! `{"type": "set_power_profile", "value": "power-saver"}` (or
+//! `"performance"`, `"balanced"`) sets `ActiveProfile` on
+//! `org.freedesktop.UPower.PowerProfiles`, falling back to the older
+//! `net.hadess.PowerProfiles` name. Menu layouts mark the slice of the
+//! current profile `active` (see [`crate::slice_state`]).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use zbus::zvariant::Value;
+
+/// power-profiles-daemon bus names and object paths: current, then pre-0.20
+const SERVICES: &[(&str, &str)] = &[
+    ("org.freedesktop.UPower.PowerProfiles", "/org/freedesktop/UPower/PowerProfiles"),
+    ("net.hadess.PowerProfiles", "/net/hadess/PowerProfiles"),
+];
+
+/// A power-profiles-daemon profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PowerProfile {
+    Performance,
+    Balanced,
+    PowerSaver,
+}
+
+impl PowerProfile {
+    /// Name used by power-profiles-daemon
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PowerProfile::Performance => "performance",
+            PowerProfile::Balanced => "balanced",
+            PowerProfile::PowerSaver => "power-saver",
+        }
+    }
+
+    /// Parse a power-profiles-daemon profile name
+    pub fn parse(name: &str) -> Option<PowerProfile> {
+        match name {
+            "performance" => Some(PowerProfile::Performance),
+            "balanced" => Some(PowerProfile::Balanced),
+            "power-saver" => Some(PowerProfile::PowerSaver),
+            _ => None,
+        }
+    }
+}
+
+/// How long menu preparation waits for the active profile
+const QUERY_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// The currently active profile; None if power-profiles-daemon is not
+/// running or does not answer within [`QUERY_TIMEOUT`]
+pub async fn active() -> Option<PowerProfile> {
+    tokio::time::timeout(QUERY_TIMEOUT, query_active()).await.ok().flatten()
+}
+
+async fn query_active() -> Option<PowerProfile> {
+    let connection = crate::dbus::system_bus().await.ok()?;
+    for &(name, path) in SERVICES {
+        let Ok(proxy) = zbus::Proxy::new(connection, name, path, name).await else {
+            continue;
+        };
+        if let Ok(profile) = proxy.get_property::<String>("ActiveProfile").await {
+            return PowerProfile::parse(&profile);
+        }
+    }
+    None
+}
+
+/// Switch to `profile`
+pub async fn set(profile: PowerProfile) -> zbus::Result<()> {
+    let connection = crate::dbus::system_bus().await?;
+    let mut last_error = None;
+    for &(name, path) in SERVICES {
+        let proxy = zbus::Proxy::new(connection, name, path, name).await?;
+        match proxy.set_property("ActiveProfile", Value::from(profile.as_str())).await {
+            Ok(()) => {
+                tracing::info!(profile = profile.as_str(), "Power profile set");
+                return Ok(());
+            }
+            Err(e) => last_error = Some(e.into()),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| zbus::Error::Failure("power-profiles-daemon not found".to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_names() {
+        for profile in [PowerProfile::Performance, PowerProfile::Balanced, PowerProfile::PowerSaver] {
+            assert_eq!(PowerProfile::parse(profile.as_str()), Some(profile));
+            assert_eq!(serde_json::to_value(profile).unwrap(), profile.as_str());
+        }
+        assert_eq!(PowerProfile::parse("turbo"), None);
+    }
+}
@@ -0,0 +1,149 @@
+//! Debouncing of gesture button chatter
+//!
+//! Some units report spurious press/release pairs a few milliseconds apart,
+//! which makes the menu flicker open and closed. With `min_hold_ms` set, a
+//! press is held back until the button has been down that long; a release
+//! before then drops the pair. With `min_gap_ms` set, a press arriving that
+//! soon after a menu closed is ignored together with its release.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::time::{Duration, Instant};
+
+use crate::config::PressDebounceConfig;
+
+/// What to do with a press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PressOutcome {
+    /// Handle the press now
+    Accept,
+    /// Wait until [`PressDebouncer::deadline`] (or drop it if released first)
+    Defer,
+    /// Ignore the press and its release
+    Ignore,
+}
+
+/// What to do with a release
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReleaseOutcome {
+    /// Handle the release (close the menu)
+    Accept,
+    /// The press was chatter and never shown; drop the release too
+    Drop,
+}
+
+/// Press/release chatter filter
+#[derive(Debug)]
+pub struct PressDebouncer {
+    /// Minimum hold before a press counts
+    min_hold: Duration,
+    /// Minimum time between a release and the next press
+    min_gap: Duration,
+    /// Press waiting for `min_hold` (position, press time)
+    pending: Option<(i32, i32, Instant)>,
+    /// When the last accepted press was released
+    last_release: Option<Instant>,
+}
+
+impl PressDebouncer {
+    /// Create a debouncer from config
+    pub fn new(config: &PressDebounceConfig) -> Self {
+        Self {
+            min_hold: Duration::from_millis(config.min_hold_ms),
+            min_gap: Duration::from_millis(config.min_gap_ms),
+            pending: None,
+            last_release: None,
+        }
+    }
+
+    /// Update the thresholds (config hot-reload)
+    pub fn set_config(&mut self, config: &PressDebounceConfig) {
+        self.min_hold = Duration::from_millis(config.min_hold_ms);
+        self.min_gap = Duration::from_millis(config.min_gap_ms);
+    }
+
+    /// Register a press at (x, y)
+    pub fn on_press(&mut self, x: i32, y: i32, now: Instant) -> PressOutcome {
+        let too_soon = self
+            .last_release
+            .is_some_and(|released| now.duration_since(released) < self.min_gap);
+        if too_soon {
+            return PressOutcome::Ignore;
+        }
+        if self.min_hold.is_zero() {
+            return PressOutcome::Accept;
+        }
+        self.pending = Some((x, y, now));
+        PressOutcome::Defer
+    }
+
+    /// When the pending press becomes a real one (None if nothing is pending)
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.map(|(_, _, pressed)| pressed + self.min_hold)
+    }
+
+    /// Take the pending press once it has been held long enough
+    pub fn take_due(&mut self, now: Instant) -> Option<(i32, i32)> {
+        if self.deadline().is_some_and(|deadline| now >= deadline) {
+            self.pending.take().map(|(x, y, _)| (x, y))
+        } else {
+            None
+        }
+    }
+
+    /// Register a release
+    pub fn on_release(&mut self, now: Instant) -> ReleaseOutcome {
+        if self.pending.take().is_some() {
+            return ReleaseOutcome::Drop;
+        }
+        self.last_release = Some(now);
+        ReleaseOutcome::Accept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    fn debouncer(min_hold_ms: u64, min_gap_ms: u64) -> PressDebouncer {
+        PressDebouncer::new(&PressDebounceConfig { min_hold_ms, min_gap_ms })
+    }
+
+    #[test]
+    fn test_min_hold_drops_short_pairs() {
+        let mut debounce = debouncer(20, 0);
+        let t0 = Instant::now();
+
+        // Chatter: released after 3ms
+        assert_eq!(debounce.on_press(1, 2, t0), PressOutcome::Defer);
+        assert_eq!(debounce.take_due(t0 + ms(3)), None);
+        assert_eq!(debounce.on_release(t0 + ms(3)), ReleaseOutcome::Drop);
+        assert_eq!(debounce.deadline(), None);
+
+        // Real press
+        assert_eq!(debounce.on_press(5, 6, t0 + ms(100)), PressOutcome::Defer);
+        assert_eq!(debounce.deadline(), Some(t0 + ms(120)));
+        assert_eq!(debounce.take_due(t0 + ms(120)), Some((5, 6)));
+        assert_eq!(debounce.on_release(t0 + ms(500)), ReleaseOutcome::Accept);
+    }
+
+    #[test]
+    fn test_min_gap_and_disabled() {
+        let mut debounce = debouncer(0, 50);
+        let t0 = Instant::now();
+
+        assert_eq!(debounce.on_press(0, 0, t0), PressOutcome::Accept);
+        assert_eq!(debounce.on_release(t0 + ms(200)), ReleaseOutcome::Accept);
+        assert_eq!(debounce.on_press(0, 0, t0 + ms(210)), PressOutcome::Ignore);
+        assert_eq!(debounce.on_press(0, 0, t0 + ms(260)), PressOutcome::Accept);
+
+        let mut off = debouncer(0, 0);
+        assert_eq!(off.on_press(0, 0, t0), PressOutcome::Accept);
+        assert_eq!(off.on_release(t0), ReleaseOutcome::Accept);
+        assert_eq!(off.on_press(0, 0, t0), PressOutcome::Accept);
+    }
+}
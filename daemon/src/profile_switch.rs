@@ -0,0 +1,177 @@
+//! Automatic profile switching and switch announcements
+//!
+//! Follows window focus and makes the profile mapped to the focused window
+//! the active one. A focus change only counts once it has been stable for
+//! [`PROFILE_SWITCH_DEBOUNCE_MS`], so alt-tabbing does not flicker through
+//! profiles. Profiles may opt in to announcing the switch with a subtle
+//! haptic and/or a transient desktop notification naming the new profile
+//! (`announce` in profiles.json); both are off by default. A profile's
+//! `haptics` overrides are applied to the haptic manager on the switch.
+//! The active profile is remembered in the runtime state and restored on
+//! startup.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::hidpp::{HapticEvent, SharedHapticManager};
+use crate::i18n;
+use crate::notifications::{self, Notification};
+use crate::profiles::{ProfileAnnounce, SharedProfileManager};
+use crate::runtime_state;
+use crate::window_tracker::WindowTracker;
+
+/// How often the focused window is checked (milliseconds)
+const FOCUS_POLL_INTERVAL_MS: u64 = 500;
+
+/// How long a focus change must be stable before the profile switches (milliseconds)
+pub const PROFILE_SWITCH_DEBOUNCE_MS: u64 = 750;
+
+/// Debounced active-profile state machine
+#[derive(Debug)]
+pub struct ProfileSwitchPolicy {
+    /// Required stable time before switching
+    debounce: Duration,
+    /// Most recently observed profile and when it was first seen
+    candidate: Option<(String, Instant)>,
+    /// Profile currently active (None until the first stable observation)
+    active: Option<String>,
+}
+
+impl ProfileSwitchPolicy {
+    /// Create a policy with the given debounce
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            candidate: None,
+            active: None,
+        }
+    }
+
+    /// Feed the focused window's profile; returns the new profile once a switch is stable
+    ///
+    /// The first stable profile only establishes the baseline and is not
+    /// reported, so the daemon does not announce a switch on startup.
+    pub fn update(&mut self, profile: &str, now: Instant) -> Option<String> {
+        match &self.candidate {
+            Some((candidate, since)) if candidate == profile => {
+                if now.duration_since(*since) < self.debounce || self.active.as_deref() == Some(profile) {
+                    return None;
+                }
+            }
+            _ => {
+                self.candidate = Some((profile.to_string(), now));
+                return None;
+            }
+        }
+
+        let previous = self.active.replace(profile.to_string());
+        previous.map(|_| profile.to_string())
+    }
+}
+
+/// Play the announcement haptic if the profile asks for one
+fn announce_haptic(haptics: &SharedHapticManager, announce: ProfileAnnounce) {
+    if !announce.haptic {
+        return;
+    }
+    if let Ok(mut manager) = haptics.lock() {
+        // The lightest pulse; this is a hint, not a confirmation
+        manager.emit_async(HapticEvent::MenuAppear);
+    }
+}
+
+/// Watch window focus, switch the active profile and announce switches
+pub async fn start_profile_switcher(
+    connection: zbus::Connection,
+    window_tracker: Arc<WindowTracker>,
+    profiles: SharedProfileManager,
+    haptics: SharedHapticManager,
+) {
+    let mut policy = ProfileSwitchPolicy::new(Duration::from_millis(PROFILE_SWITCH_DEBOUNCE_MS));
+    let mut notification_id = 0u32;
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(FOCUS_POLL_INTERVAL_MS));
+
+    loop {
+        interval.tick().await;
+
+        let class = window_tracker.refresh_active_window().await;
+        let Some(name) = profiles
+            .read()
+            .ok()
+            .map(|p| class.as_deref().map_or_else(|| p.current(), |c| p.get_profile_for_window(c)).name.clone())
+        else {
+            continue;
+        };
+
+        let Some(name) = policy.update(&name, Instant::now()) else {
+            continue;
+        };
+
+        let (announce, profile_haptics) = match profiles.write() {
+            Ok(mut p) => {
+                if let Err(e) = p.set_current(&name) {
+                    tracing::warn!(profile = %name, "Profile switch failed: {}", e);
+                    continue;
+                }
+                (p.current().announce, p.current().haptics.clone())
+            }
+            Err(_) => continue,
+        };
+        tracing::info!(profile = %name, window = ?class, "Active profile switched");
+        runtime_state::update(|state| state.last_profile = Some(name.clone()));
+
+        if let Ok(mut manager) = haptics.lock() {
+            manager.set_profile_haptics(profile_haptics.as_ref());
+        }
+
+        announce_haptic(&haptics, announce);
+
+        if announce.notification {
+            let notification = Notification::transient(i18n::tr_fmt("Profile: {}", &[&name]), "");
+            match notifications::notify(&connection, &notification, notification_id).await {
+                Ok(id) => notification_id = id,
+                Err(e) => tracing::debug!("Profile switch notification failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_switch_after_debounce_with_silent_baseline() {
+        let mut policy = ProfileSwitchPolicy::new(ms(750));
+        let t0 = Instant::now();
+
+        // Baseline is established without a switch
+        assert_eq!(policy.update("default", t0), None);
+        assert_eq!(policy.update("default", t0 + ms(800)), None);
+
+        assert_eq!(policy.update("blender", t0 + ms(1000)), None);
+        assert_eq!(policy.update("blender", t0 + ms(1500)), None);
+        assert_eq!(policy.update("blender", t0 + ms(1800)), Some("blender".to_string()));
+        // Already active
+        assert_eq!(policy.update("blender", t0 + ms(3000)), None);
+    }
+
+    #[test]
+    fn test_brief_focus_does_not_switch() {
+        let mut policy = ProfileSwitchPolicy::new(ms(750));
+        let t0 = Instant::now();
+        policy.update("default", t0);
+        policy.update("default", t0 + ms(800));
+
+        // Alt-tab through another app and back
+        assert_eq!(policy.update("firefox", t0 + ms(1000)), None);
+        assert_eq!(policy.update("default", t0 + ms(1500)), None);
+        assert_eq!(policy.update("default", t0 + ms(3000)), None);
+    }
+}
@@ -9,8 +9,11 @@ use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 
-use crate::actions::{Action, get_default_actions};
+use crate::actions::{resolve_action, Action, ActionType, ProviderContext, get_default_actions};
+use crate::activities::Activity;
+use crate::config::{HapticConfig, MenuContent};
 
 /// Current schema version for profiles.json
 pub const SCHEMA_VERSION: u32 = 1;
@@ -56,6 +59,28 @@ impl ProfilesConfig {
     }
 }
 
+/// What a profile's slices show
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MenuMode {
+    /// Configured slice actions
+    #[default]
+    Actions,
+    /// Open windows; selecting a slice focuses that window
+    WindowSwitcher,
+}
+
+impl MenuMode {
+    /// Parse a mode name as used in profiles.json
+    pub fn parse(name: &str) -> Option<MenuMode> {
+        match name {
+            "actions" => Some(MenuMode::Actions),
+            "window_switcher" => Some(MenuMode::WindowSwitcher),
+            _ => None,
+        }
+    }
+}
+
 /// A radial menu profile (Story 3.1: Task 1.2)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Profile {
@@ -66,9 +91,17 @@ pub struct Profile {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub window_class: Option<String>,
 
+    /// KDE activity (name or id) this profile is bound to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<String>,
+
     /// 8 slice actions (N, NE, E, SE, S, SW, W, NW)
     pub slices: [Option<Action>; 8],
 
+    /// Further pages of 8 slices, shown after `slices` when paging
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<[Option<Action>; 8]>,
+
     /// Center tap action
     #[serde(skip_serializing_if = "Option::is_none")]
     pub center: Option<Action>,
@@ -80,6 +113,302 @@ pub struct Profile {
     /// Profile description
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+
+    /// Slice content mode
+    #[serde(default)]
+    pub mode: MenuMode,
+
+    /// Never show the overlay; slices are told apart by haptic pulse counts
+    /// (see [`crate::blind_mode`])
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub blind: bool,
+
+    /// Preferred DPI while a matching window is focused
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dpi: Option<u16>,
+
+    /// How switching to this profile is announced
+    #[serde(default, skip_serializing_if = "ProfileAnnounce::is_quiet")]
+    pub announce: ProfileAnnounce,
+
+    /// Haptic settings while this profile is active (over config.json's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub haptics: Option<ProfileHaptics>,
+
+    /// Training mode while the layout is being learned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub training: Option<ProfileTraining>,
+
+    /// Display text for slice labels, keyed by the label as written
+    /// (e.g. `{"Copy": "Yoink"}`); wins over the translation
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, String>,
+
+    /// Further menus by ID, opened by the buttons mapped in config.json's
+    /// `button_menus` (e.g. `{"media": {"slices": [...]}}`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub menus: HashMap<String, ProfileMenu>,
+}
+
+/// A profile menu opened by its own button
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileMenu {
+    /// 8 slice actions (N, NE, E, SE, S, SW, W, NW)
+    pub slices: [Option<Action>; 8],
+
+    /// Further pages of 8 slices
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<[Option<Action>; 8]>,
+
+    /// Center tap action
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub center: Option<Action>,
+}
+
+/// Announcement when focus switches to a profile
+///
+/// Both default to off so profiles that are switched to often stay quiet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileAnnounce {
+    /// Play a subtle haptic pulse
+    #[serde(default)]
+    pub haptic: bool,
+
+    /// Show a desktop notification naming the profile
+    #[serde(default)]
+    pub notification: bool,
+}
+
+impl ProfileAnnounce {
+    /// Whether the switch is not announced at all
+    pub fn is_quiet(&self) -> bool {
+        !self.haptic && !self.notification
+    }
+}
+
+/// Training mode for learning a profile's layout
+///
+/// Until `selections` slices have been selected in the profile, its menu
+/// animates slower, slices carry a `description` and resting on a slice
+/// plays a guidance pulse (see [`crate::training`]):
+///
+/// ```json
+/// "training": {"selections": 30}
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileTraining {
+    /// Selections after which training ends
+    #[serde(default = "default_training_selections")]
+    pub selections: u64,
+}
+
+/// Upper bound for `training.selections`
+pub const MAX_TRAINING_SELECTIONS: u64 = 10_000;
+
+fn default_training_selections() -> u64 { 50 }
+
+impl Default for ProfileTraining {
+    fn default() -> Self {
+        Self { selections: default_training_selections() }
+    }
+}
+
+/// Haptic settings a profile overrides while it is active
+///
+/// Unset fields keep the value from `haptics` in config.json, so a game
+/// can get strong feedback while office apps stay silent:
+///
+/// ```json
+/// "haptics": {"per_event": {"slice_change": "sharp_collision"}, "slice_debounce_ms": 0}
+/// "haptics": {"enabled": false}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileHaptics {
+    /// Enable haptic feedback
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+
+    /// Default haptic pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_pattern: Option<String>,
+
+    /// Per-event patterns
+    #[serde(default, skip_serializing_if = "ProfileHapticEvents::is_empty")]
+    pub per_event: ProfileHapticEvents,
+
+    /// Minimum time between pulses in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debounce_ms: Option<u64>,
+
+    /// Minimum time between slice change haptics in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slice_debounce_ms: Option<u64>,
+
+    /// Time window for re-entry detection in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reentry_debounce_ms: Option<u64>,
+}
+
+/// Per-event patterns a profile overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileHapticEvents {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub menu_appear: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slice_change: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invalid: Option<String>,
+}
+
+impl ProfileHapticEvents {
+    /// Whether no event pattern is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl ProfileHaptics {
+    /// Clamp the debounce overrides like config.json's
+    pub fn validate(&mut self) {
+        let max = crate::config::MAX_HAPTIC_DEBOUNCE_MS;
+        for (name, value) in [
+            ("profile haptics.debounce_ms", &mut self.debounce_ms),
+            ("profile haptics.slice_debounce_ms", &mut self.slice_debounce_ms),
+            ("profile haptics.reentry_debounce_ms", &mut self.reentry_debounce_ms),
+        ] {
+            if let Some(value) = value {
+                crate::config::clamp_setting(name, value, 0, max);
+            }
+        }
+    }
+
+    /// `base` with this profile's overrides applied
+    pub fn apply(&self, base: &HapticConfig) -> HapticConfig {
+        let mut config = base.clone();
+        let pick = |value: &Option<String>, base: &mut String| {
+            if let Some(value) = value {
+                base.clone_from(value);
+            }
+        };
+        config.enabled = self.enabled.unwrap_or(base.enabled);
+        pick(&self.default_pattern, &mut config.default_pattern);
+        pick(&self.per_event.menu_appear, &mut config.per_event.menu_appear);
+        pick(&self.per_event.slice_change, &mut config.per_event.slice_change);
+        pick(&self.per_event.confirm, &mut config.per_event.confirm);
+        pick(&self.per_event.invalid, &mut config.per_event.invalid);
+        config.debounce_ms = self.debounce_ms.unwrap_or(base.debounce_ms);
+        config.slice_debounce_ms = self.slice_debounce_ms.unwrap_or(base.slice_debounce_ms);
+        config.reentry_debounce_ms = self.reentry_debounce_ms.unwrap_or(base.reentry_debounce_ms);
+        config
+    }
+}
+
+impl Profile {
+    /// Copy of this profile with dynamic slices resolved for the current desktop state
+    ///
+    /// Window switcher profiles get one slice per open window (first 8, in
+    /// the order KWin reports them) and a single page; the center action is kept.
+    /// Action labels are translated (see [`crate::i18n`]); window titles are not.
+    pub fn resolved(&self, ctx: &ProviderContext) -> Profile {
+        let resolve = |slot: &Option<Action>| slot.as_ref().map(|a| self.localized(resolve_action(a, ctx)));
+        let resolve_page = |page: &[Option<Action>; 8]| std::array::from_fn(|i| resolve(&page[i]));
+        let (slices, pages) = match self.mode {
+            MenuMode::Actions => (resolve_page(&self.slices), self.pages.iter().map(resolve_page).collect()),
+            MenuMode::WindowSwitcher => (
+                std::array::from_fn(|i| {
+                    ctx.open_windows.get(i).map(|window| Action {
+                        action_type: ActionType::FocusWindow(window.id.clone()),
+                        label: Some(window.title.clone()),
+                        icon: Some(window.icon.clone()),
+                        notify: None,
+                        alternate: None,
+                        timeout_secs: None,
+                        inverse: None,
+                    })
+                }),
+                Vec::new(),
+            ),
+        };
+        Profile {
+            slices,
+            pages,
+            center: resolve(&self.center),
+            ..self.clone()
+        }
+    }
+
+    /// `action` with its label (and its alternate's) in the display language
+    fn localized(&self, mut action: Action) -> Action {
+        if let Some(label) = &action.label {
+            action.label = Some(crate::i18n::localize_label(label, &self.labels));
+        }
+        action.alternate = action.alternate.map(|alternate| Box::new(self.localized(*alternate)));
+        action
+    }
+
+    /// Copy showing menu `id` in place of the main slices, None if there is no such menu
+    ///
+    /// Everything else (labels, haptics, blind, training) stays the
+    /// profile's; the other menus are dropped.
+    pub fn with_menu(&self, id: &str) -> Option<Profile> {
+        let menu = self.menus.get(id)?;
+        Some(Profile {
+            slices: menu.slices.clone(),
+            pages: menu.pages.clone(),
+            center: menu.center.clone(),
+            mode: MenuMode::Actions,
+            menus: HashMap::new(),
+            ..self.clone()
+        })
+    }
+
+    /// Number of menu pages (at least 1)
+    pub fn page_count(&self) -> usize {
+        1 + self.pages.len()
+    }
+
+    /// Slices of a menu page (0 = `slices`)
+    pub fn page(&self, index: usize) -> Option<&[Option<Action>; 8]> {
+        match index {
+            0 => Some(&self.slices),
+            _ => self.pages.get(index - 1),
+        }
+    }
+
+    /// Copy with relative icon file paths made absolute against `base_dir`
+    pub fn with_icon_paths(mut self, base_dir: &Path) -> Profile {
+        let pages = std::iter::once(&mut self.slices).chain(self.pages.iter_mut()).flatten();
+        for action in pages.chain(std::iter::once(&mut self.center)).flatten() {
+            if let Some(icon) = &action.icon {
+                action.icon = Some(resolve_icon_path(icon, base_dir));
+            }
+        }
+        self
+    }
+
+    /// Copy with labels or icons dropped for the configured menu density
+    ///
+    /// A slice missing the kept field (e.g. no icon in an icon-only menu)
+    /// keeps the other, so no slice ends up blank.
+    pub fn with_content(mut self, content: MenuContent) -> Profile {
+        fn strip(action: &mut Action, content: MenuContent) {
+            match content {
+                MenuContent::IconsOnly if action.icon.is_some() => action.label = None,
+                MenuContent::LabelsOnly if action.label.is_some() => action.icon = None,
+                _ => {}
+            }
+            if let Some(alternate) = &mut action.alternate {
+                strip(alternate, content);
+            }
+        }
+        let pages = std::iter::once(&mut self.slices).chain(self.pages.iter_mut()).flatten();
+        for action in pages.chain(std::iter::once(&mut self.center)).flatten() {
+            strip(action, content);
+        }
+        self
+    }
 }
 
 impl Default for Profile {
@@ -87,10 +416,20 @@ impl Default for Profile {
         Self {
             name: "default".to_string(),
             window_class: None,
+            activity: None,
             slices: [None, None, None, None, None, None, None, None],
+            pages: Vec::new(),
             center: None,
             icon: None,
             description: Some("Default profile".to_string()),
+            mode: MenuMode::Actions,
+            blind: false,
+            dpi: None,
+            announce: ProfileAnnounce::default(),
+            haptics: None,
+            training: None,
+            labels: HashMap::new(),
+            menus: HashMap::new(),
         }
     }
 }
@@ -102,6 +441,7 @@ pub fn create_default_profile() -> Profile {
     Profile {
         name: "default".to_string(),
         window_class: None,
+        activity: None,
         slices: [
             Some(default_actions[0].clone()), // N: Copy
             Some(default_actions[1].clone()), // NE: Paste
@@ -112,9 +452,18 @@ pub fn create_default_profile() -> Profile {
             Some(default_actions[6].clone()), // W: Save
             Some(default_actions[7].clone()), // NW: Close
         ],
+        pages: Vec::new(),
         center: None,
         icon: Some("🎯".to_string()),
         description: Some("Default profile with common shortcuts".to_string()),
+        mode: MenuMode::Actions,
+        blind: false,
+        dpi: None,
+        announce: ProfileAnnounce::default(),
+        haptics: None,
+        training: None,
+        labels: HashMap::new(),
+        menus: HashMap::new(),
     }
 }
 
@@ -154,6 +503,20 @@ pub fn validate_icon_reference(icon: &str) -> bool {
     false
 }
 
+/// Resolve a relative icon file path against `base_dir`
+///
+/// Emoji, system icon names and absolute paths are returned unchanged, so
+/// overlays can load file icons without knowing where profiles.json lives.
+pub fn resolve_icon_path(icon: &str, base_dir: &Path) -> String {
+    let lower = icon.to_lowercase();
+    let is_file = lower.ends_with(".png") || lower.ends_with(".svg") || lower.ends_with(".ico");
+    if is_file && Path::new(icon).is_relative() {
+        base_dir.join(icon).to_string_lossy().into_owned()
+    } else {
+        icon.to_string()
+    }
+}
+
 /// Direction indices for slices
 pub mod direction {
     pub const NORTH: usize = 0;
@@ -201,6 +564,13 @@ pub fn ensure_config_dir() -> Result<PathBuf, ProfileError> {
     Ok(config_dir)
 }
 
+/// Write a profiles file
+fn write_profiles(path: &Path, config: &ProfilesConfig) -> Result<(), ProfileError> {
+    let json = serde_json::to_string_pretty(config).map_err(ProfileError::ParseError)?;
+    let mut file = fs::File::create(path).map_err(ProfileError::IoError)?;
+    file.write_all(json.as_bytes()).map_err(ProfileError::IoError)
+}
+
 /// Profile manager for loading and switching profiles
 #[derive(Debug)]
 pub struct ProfileManager {
@@ -213,6 +583,9 @@ pub struct ProfileManager {
     /// Window class to profile mapping (Story 3.1: Task 3.4)
     window_mappings: HashMap<String, String>,
 
+    /// (window class, activity, profile) for profiles bound to an activity
+    activity_profiles: Vec<(Option<String>, String, String)>,
+
     /// Config file path (used for future save functionality)
     #[allow(dead_code)]
     config_path: PathBuf,
@@ -229,6 +602,7 @@ impl ProfileManager {
             profiles,
             current_profile: "default".to_string(),
             window_mappings: HashMap::new(),
+            activity_profiles: Vec::new(),
             config_path: get_profiles_path(),
         }
     }
@@ -272,6 +646,7 @@ impl ProfileManager {
         // Task 3.3, 3.4: Build profile map and window mappings
         let mut profiles = HashMap::new();
         let mut window_mappings = HashMap::new();
+        let mut activity_profiles = Vec::new();
 
         for mut profile in config.profiles {
             // Story 3.6: Validate and fix slice count
@@ -308,8 +683,25 @@ impl ProfileManager {
                 }
             }
 
+            if let Some(dpi) = profile.dpi.as_mut() {
+                crate::config::clamp_setting("profile dpi", dpi, crate::config::MIN_DPI, crate::config::MAX_DPI);
+            }
+            if let Some(haptics) = profile.haptics.as_mut() {
+                haptics.validate();
+            }
+            if let Some(training) = profile.training.as_mut() {
+                crate::config::clamp_setting(
+                    "profile training.selections",
+                    &mut training.selections,
+                    1,
+                    MAX_TRAINING_SELECTIONS,
+                );
+            }
+
             // Story 3.3: Build window class mapping for profile matching
-            if let Some(ref window_class) = profile.window_class {
+            if let Some(ref activity) = profile.activity {
+                activity_profiles.push((profile.window_class.clone(), activity.clone(), profile.name.clone()));
+            } else if let Some(ref window_class) = profile.window_class {
                 window_mappings.insert(window_class.clone(), profile.name.clone());
             }
 
@@ -332,42 +724,79 @@ impl ProfileManager {
             profiles,
             current_profile: "default".to_string(),
             window_mappings,
+            activity_profiles,
             config_path: path.to_path_buf(),
         })
     }
 
     /// Create default profiles.json file (Story 3.1: Task 4.3, 4.4)
+    ///
+    /// Uses the bundled starter profiles for the detected desktop.
     fn create_default_file() -> Result<Self, ProfileError> {
         // Ensure directory exists (Task 2.4)
         ensure_config_dir()?;
 
         let config_path = get_profiles_path();
-        let config = ProfilesConfig::with_default_actions();
+        let (set, config) = crate::bundled_profiles::profiles_for_session();
 
         // Write JSON file (Task 4.3)
-        let json = serde_json::to_string_pretty(&config).map_err(ProfileError::ParseError)?;
-
-        let mut file = fs::File::create(&config_path).map_err(ProfileError::IoError)?;
-        file.write_all(json.as_bytes())
-            .map_err(ProfileError::IoError)?;
+        write_profiles(&config_path, &config)?;
 
         // Log creation (Task 4.4)
-        tracing::info!("Created default profiles.json at {:?}", config_path);
+        tracing::info!(set, "Created default profiles.json at {:?}", config_path);
 
         // Load the newly created config
         Self::load_from_path(&config_path)
     }
 
-    /// Get profile for a window class (falls back to default)
-    pub fn get_profile_for_window(&self, window_class: &str) -> &Profile {
-        if let Some(profile_name) = self.window_mappings.get(window_class) {
-            if let Some(profile) = self.profiles.get(profile_name) {
-                return profile;
-            }
+    /// Replace profiles.json with `config`, keeping the old file as `profiles.json.bak`
+    pub fn install(config: &ProfilesConfig) -> Result<Self, ProfileError> {
+        ensure_config_dir()?;
+        Self::install_at(&get_profiles_path(), config)
+    }
+
+    /// Replace the profiles file at `path`, keeping the old one next to it with `.bak` appended
+    pub fn install_at(path: &Path, config: &ProfilesConfig) -> Result<Self, ProfileError> {
+        if path.exists() {
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(".bak");
+            fs::rename(path, &backup).map_err(ProfileError::IoError)?;
+            tracing::info!("Previous profiles kept at {:?}", backup);
         }
-        self.profiles
-            .get("default")
-            .expect("Default profile must exist")
+        write_profiles(path, config)?;
+        Self::load_from_path(path)
+    }
+
+    /// Get profile for a window class in the current activity (falls back to default)
+    pub fn get_profile_for_window(&self, window_class: &str) -> &Profile {
+        self.profile_for(window_class, crate::activities::current().as_ref())
+    }
+
+    /// Get profile for a window class in an activity
+    ///
+    /// App-in-activity profiles win over app profiles, which win over
+    /// activity profiles; the default profile is the fallback.
+    pub fn profile_for(&self, window_class: &str, activity: Option<&Activity>) -> &Profile {
+        let in_activity = |class: Option<&str>| {
+            let activity = activity?;
+            self.activity_profiles
+                .iter()
+                .find(|(c, reference, _)| c.as_deref() == class && activity.matches(reference))
+                .map(|(_, _, name)| name)
+        };
+        in_activity(Some(window_class))
+            .or_else(|| self.window_mappings.get(window_class))
+            .or_else(|| in_activity(None))
+            .and_then(|name| self.profiles.get(name))
+            .unwrap_or_else(|| self.profiles.get("default").expect("Default profile must exist"))
+    }
+
+    /// `profile` showing menu `id`, taken from the default profile if
+    /// `profile` does not define it
+    pub fn profile_with_menu(&self, profile: &Profile, id: &str) -> Option<Profile> {
+        profile
+            .with_menu(id)
+            .or_else(|| self.profiles.get("default")?.with_menu(id))
     }
 
     /// Get current active profile
@@ -392,12 +821,30 @@ impl ProfileManager {
         self.profiles.len()
     }
 
+    /// Directory containing profiles.json (base for relative icon paths)
+    pub fn base_dir(&self) -> Option<&Path> {
+        self.config_path.parent()
+    }
+
+    /// Check if any profile declares a preferred DPI
+    pub fn has_dpi_profiles(&self) -> bool {
+        self.profiles.values().any(|p| p.dpi.is_some())
+    }
+
+    /// Check if any profile announces switches to it
+    pub fn has_announcing_profiles(&self) -> bool {
+        self.profiles.values().any(|p| !p.announce.is_quiet())
+    }
+
     /// Get list of profile names
     pub fn profile_names(&self) -> Vec<&String> {
         self.profiles.keys().collect()
     }
 }
 
+/// Thread-safe profile manager handle
+pub type SharedProfileManager = Arc<RwLock<ProfileManager>>;
+
 impl Default for ProfileManager {
     fn default() -> Self {
         Self::new()
@@ -468,6 +915,59 @@ mod tests {
         assert_eq!(parsed.slices.len(), 8);
     }
 
+    #[test]
+    fn test_profile_pages() {
+        let json = r#"{
+            "name": "editing",
+            "slices": [{"type": "page", "value": 1}, null, null, null, null, null, null, null],
+            "pages": [[{"type": "dynamic", "value": "previous-window"}, null, null, null, null, null, null, null]]
+        }"#;
+        let profile: Profile = serde_json::from_str(json).unwrap();
+        assert_eq!(profile.page_count(), 2);
+        assert!(matches!(profile.page(0).unwrap()[0].as_ref().unwrap().action_type, ActionType::Page(1)));
+        assert!(profile.page(2).is_none());
+
+        // Extra pages are resolved like the first one
+        let resolved = profile.resolved(&ProviderContext::default());
+        let first = resolved.page(1).unwrap()[0].as_ref().unwrap();
+        assert!(!matches!(first.action_type, ActionType::Dynamic(_)));
+
+        // Single-page profiles keep their old JSON shape
+        let json = serde_json::to_string(&create_default_profile()).unwrap();
+        assert!(!json.contains("\"pages\""));
+    }
+
+    #[test]
+    fn test_profile_menus() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+        let json = r#"{"version": 1, "profiles": [
+            {"name": "default", "slices": [null, null, null, null, null, null, null, null],
+             "menus": {"media": {"slices": [{"type": "shortcut", "value": "XF86AudioPlay"}, null, null, null, null, null, null, null]}}},
+            {"name": "gimp", "window_class": "gimp", "mode": "window_switcher",
+             "slices": [null, null, null, null, null, null, null, null],
+             "menus": {"tools": {"slices": [null, null, null, null, null, null, null, null],
+                                 "center": {"type": "shortcut", "value": "ctrl+z"}}}}
+        ]}"#;
+        fs::write(&config_path, json).unwrap();
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+        let gimp = manager.get_profile_for_window("gimp");
+
+        let tools = manager.profile_with_menu(gimp, "tools").unwrap();
+        assert_eq!(tools.name, "gimp");
+        assert_eq!(tools.mode, MenuMode::Actions);
+        assert!(tools.center.is_some());
+        assert!(tools.menus.is_empty());
+
+        // Menus the app profile lacks come from the default profile
+        let media = manager.profile_with_menu(gimp, "media").unwrap();
+        assert_eq!(media.name, "default");
+        assert!(media.slices[0].is_some());
+
+        assert!(manager.profile_with_menu(gimp, "missing").is_none());
+        assert!(!serde_json::to_string(&create_default_profile()).unwrap().contains("\"menus\""));
+    }
+
     // Task 6.2: Test default profile creation
     #[test]
     fn test_create_default_profile() {
@@ -534,6 +1034,66 @@ mod tests {
         assert_eq!(unknown.name, "default");
     }
 
+    #[test]
+    fn test_profile_dpi() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+
+        let mut config = ProfilesConfig::with_default_actions();
+        let mut blender = create_default_profile();
+        blender.name = "blender".to_string();
+        blender.window_class = Some("blender".to_string());
+        blender.dpi = Some(800);
+        config.profiles.push(blender);
+        fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap()).unwrap();
+
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+        assert!(manager.has_dpi_profiles());
+        assert_eq!(manager.get_profile_for_window("blender").dpi, Some(800));
+        assert_eq!(manager.get_profile_for_window("konsole").dpi, None);
+        assert!(!ProfileManager::new().has_dpi_profiles());
+    }
+
+    #[test]
+    fn test_profile_haptics_override() {
+        let json = r#"{"name":"game","slices":[null,null,null,null,null,null,null,null],
+            "haptics":{"per_event":{"slice_change":"sharp_collision"},"slice_debounce_ms":0}}"#;
+        let game: Profile = serde_json::from_str(json).unwrap();
+        let overrides = game.haptics.as_ref().unwrap();
+
+        let base = HapticConfig::default();
+        let effective = overrides.apply(&base);
+        assert_eq!(effective.per_event.slice_change, "sharp_collision");
+        assert_eq!(effective.per_event.confirm, base.per_event.confirm);
+        assert_eq!(effective.slice_debounce_ms, 0);
+        assert_eq!(effective.debounce_ms, base.debounce_ms);
+
+        let saved = serde_json::to_string(overrides).unwrap();
+        assert_eq!(saved, r#"{"per_event":{"slice_change":"sharp_collision"},"slice_debounce_ms":0}"#);
+        assert!(!serde_json::to_string(&create_default_profile()).unwrap().contains("haptics"));
+    }
+
+    #[test]
+    fn test_profile_announce_default_quiet() {
+        let profile = create_default_profile();
+        let json = serde_json::to_string(&profile).unwrap();
+        assert!(!json.contains("announce"));
+
+        let mut gaming = create_default_profile();
+        gaming.name = "gaming".to_string();
+        gaming.announce.notification = true;
+        let parsed: Profile = serde_json::from_str(&serde_json::to_string(&gaming).unwrap()).unwrap();
+        assert_eq!(parsed.announce, ProfileAnnounce { haptic: false, notification: true });
+
+        assert!(!ProfileManager::new().has_announcing_profiles());
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+        let mut config = ProfilesConfig::with_default_actions();
+        config.profiles.push(gaming);
+        fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap()).unwrap();
+        assert!(ProfileManager::load_from_path(&config_path).unwrap().has_announcing_profiles());
+    }
+
     // Task 6.4: Test load failure on malformed JSON
     #[test]
     fn test_load_malformed_json() {
@@ -549,6 +1109,21 @@ mod tests {
         assert!(matches!(result.unwrap_err(), ProfileError::ParseError(_)));
     }
 
+    #[test]
+    fn test_install_keeps_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+        fs::write(&config_path, "{ edited by hand }").unwrap();
+
+        let gnome = crate::bundled_profiles::get_bundled_profiles("gnome").unwrap();
+        let manager = ProfileManager::install_at(&config_path, &gnome).unwrap();
+        assert!(matches!(
+            manager.current().slices[0].as_ref().unwrap().action_type,
+            ActionType::GnomeShell(_)
+        ));
+        assert_eq!(fs::read_to_string(temp_dir.path().join("profiles.json.bak")).unwrap(), "{ edited by hand }");
+    }
+
     // Story 3.6: Test that wrong slice count is padded, not rejected
     #[test]
     fn test_load_wrong_slice_count_pads() {
@@ -568,6 +1143,35 @@ mod tests {
         assert_eq!(manager.current().slices.len(), 8);
     }
 
+    #[test]
+    fn test_with_icon_paths() {
+        let mut profile = Profile::default();
+        profile.slices[0] = Some(Action {
+            action_type: ActionType::None,
+            label: None,
+            icon: Some("icons/copy.svg".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
+        });
+        profile.slices[1] = Some(Action {
+            action_type: ActionType::None,
+            label: None,
+            icon: Some("edit-copy".to_string()),
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
+        });
+
+        let profile = profile.with_icon_paths(Path::new("/home/u/.config/juhradial"));
+        let icon = |i: usize| profile.slices[i].as_ref().unwrap().icon.clone().unwrap();
+        assert_eq!(icon(0), "/home/u/.config/juhradial/icons/copy.svg");
+        assert_eq!(icon(1), "edit-copy");
+        assert_eq!(resolve_icon_path("/usr/share/a.png", Path::new("/x")), "/usr/share/a.png");
+    }
+
     // Story 3.5: Test icon validation
     #[test]
     fn test_validate_icon_reference() {
@@ -617,6 +1221,35 @@ mod tests {
         assert_eq!(profile.name, "default");
     }
 
+    #[test]
+    fn test_activity_profiles() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+
+        let mut config = ProfilesConfig::with_default_actions();
+        for (name, class, activity) in [
+            ("code", Some("code"), None),
+            ("work", None, Some("Work")),
+            ("code-at-work", Some("code"), Some("Work")),
+        ] {
+            let mut profile = create_default_profile();
+            profile.name = name.to_string();
+            profile.window_class = class.map(str::to_string);
+            profile.activity = activity.map(str::to_string);
+            config.profiles.push(profile);
+        }
+        fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+
+        let work = Activity { id: "5e1a".to_string(), name: "Work".to_string() };
+        let home = Activity { id: "0c2f".to_string(), name: "Home".to_string() };
+        assert_eq!(manager.profile_for("code", Some(&work)).name, "code-at-work");
+        assert_eq!(manager.profile_for("konsole", Some(&work)).name, "work");
+        assert_eq!(manager.profile_for("code", Some(&home)).name, "code");
+        assert_eq!(manager.profile_for("code", None).name, "code");
+        assert_eq!(manager.profile_for("konsole", Some(&home)).name, "default");
+    }
+
     // Story 3.4: Test default profile fallback
     #[test]
     fn test_default_profile_fallback() {
@@ -702,6 +1335,92 @@ mod tests {
         assert_eq!(direction::NORTH_WEST, 7);
     }
 
+    #[test]
+    fn test_profile_resolved_dynamic_slices() {
+        let mut profile = create_default_profile();
+        profile.slices[direction::WEST] = Some(Action {
+            action_type: ActionType::Dynamic("previous-window".to_string()),
+            label: None,
+            icon: None,
+            notify: None,
+            alternate: None,
+            timeout_secs: None,
+            inverse: None,
+        });
+
+        let resolved = profile.resolved(&ProviderContext::default());
+        let west = resolved.slices[direction::WEST].as_ref().unwrap();
+        assert!(matches!(west.action_type, ActionType::Shortcut(_)));
+        assert_eq!(resolved.name, profile.name);
+        // Static slices unchanged
+        assert_eq!(resolved.slices[0].as_ref().unwrap().label, profile.slices[0].as_ref().unwrap().label);
+    }
+
+    #[test]
+    fn test_window_switcher_layout() {
+        use crate::window_tracker::OpenWindow;
+
+        let profile = Profile {
+            mode: MenuMode::WindowSwitcher,
+            ..Profile::default()
+        };
+        let ctx = ProviderContext {
+            open_windows: (0..10)
+                .map(|i| OpenWindow {
+                    id: format!("0_{{{}}}", i),
+                    title: format!("Window {}", i),
+                    icon: "konsole".to_string(),
+                })
+                .collect(),
+            ..ProviderContext::default()
+        };
+
+        let resolved = profile.resolved(&ctx);
+        assert!(resolved.slices.iter().all(Option::is_some));
+        let first = resolved.slices[0].as_ref().unwrap();
+        assert!(matches!(first.action_type, ActionType::FocusWindow(ref id) if id == "0_{0}"));
+        assert_eq!(first.label.as_deref(), Some("Window 0"));
+
+        // Fewer windows than slices leaves the rest empty
+        let ctx = ProviderContext { open_windows: ctx.open_windows[..2].to_vec(), ..ProviderContext::default() };
+        assert_eq!(profile.resolved(&ctx).slices.iter().flatten().count(), 2);
+
+        let json = r#"{"name":"alt-tab","slices":[null,null,null,null,null,null,null,null],"mode":"window_switcher"}"#;
+        let parsed: Profile = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.mode, MenuMode::WindowSwitcher);
+    }
+
+    #[test]
+    fn test_profile_label_overrides() {
+        let mut profile = create_default_profile();
+        profile.labels.insert("Copy".to_string(), "Yoink".to_string());
+
+        let resolved = profile.resolved(&ProviderContext::default());
+        assert_eq!(resolved.slices[0].as_ref().unwrap().label.as_deref(), Some("Yoink"));
+        assert_eq!(resolved.slices[1].as_ref().unwrap().label.as_deref(), Some("Paste"));
+
+        let json = serde_json::to_string(&profile).unwrap();
+        assert!(json.contains(r#""labels":{"Copy":"Yoink"}"#));
+        assert!(!serde_json::to_string(&create_default_profile()).unwrap().contains("labels"));
+    }
+
+    #[test]
+    fn test_profile_menu_content() {
+        let mut profile = create_default_profile();
+        profile.slices[1].as_mut().unwrap().icon = None;
+
+        let icons = profile.clone().with_content(MenuContent::IconsOnly);
+        assert_eq!(icons.slices[0].as_ref().unwrap().label, None);
+        assert!(icons.slices[0].as_ref().unwrap().icon.is_some());
+        assert_eq!(icons.slices[1].as_ref().unwrap().label.as_deref(), Some("Paste"));
+
+        let labels = profile.clone().with_content(MenuContent::LabelsOnly);
+        assert!(labels.slices.iter().flatten().all(|a| a.icon.is_none() && a.label.is_some()));
+
+        let both = profile.clone().with_content(MenuContent::Both);
+        assert_eq!(both.slices[0].as_ref().unwrap().icon, profile.slices[0].as_ref().unwrap().icon);
+    }
+
     #[test]
     fn test_profile_error_display() {
         let err = ProfileError::NotFound("test".to_string());
@@ -0,0 +1,115 @@
+//! Quiet hours for haptic feedback
+//!
+//! A daily window (e.g. 22:00-07:00, local time) during which the
+//! [`crate::hidpp::HapticManager`] mutes haptics or reduces every event to
+//! `whisper_collision`. Windows may wrap past midnight. Explicit previews
+//! (`TestHaptic`, calibration) are never affected.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use crate::config::QuietHoursConfig;
+use crate::hidpp::Mx4HapticPattern;
+
+/// Minutes in a day
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Parse "HH:MM" (24-hour) into minutes since midnight
+pub fn parse_clock(clock: &str) -> Option<u16> {
+    let (hours, minutes) = clock.trim().split_once(':')?;
+    let hours: u16 = hours.parse().ok()?;
+    let minutes: u16 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Current local time in minutes since midnight
+pub fn local_minute_of_day() -> u16 {
+    // SAFETY: time(2) with a null pointer only returns the time
+    let now = unsafe { libc::time(std::ptr::null_mut()) };
+    // SAFETY: tm is plain data; all-zero is a valid value
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    // SAFETY: `now` and `tm` are valid for the duration of the call
+    if unsafe { libc::localtime_r(&now, &mut tm) }.is_null() {
+        return 0;
+    }
+    (tm.tm_hour * 60 + tm.tm_min) as u16
+}
+
+/// Parsed quiet hours schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    /// Start, minutes since midnight (inclusive)
+    start: u16,
+    /// End, minutes since midnight (exclusive)
+    end: u16,
+    /// Whisper instead of muting
+    reduce_to_whisper: bool,
+}
+
+impl QuietHours {
+    /// Parse a schedule (None if disabled or the times are invalid)
+    pub fn from_config(config: &QuietHoursConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        Some(Self {
+            start: parse_clock(&config.start)?,
+            end: parse_clock(&config.end)?,
+            reduce_to_whisper: config.reduce_to_whisper,
+        })
+    }
+
+    /// Whether `minute` (since midnight) falls inside the window
+    pub fn contains(&self, minute: u16) -> bool {
+        let minute = minute % MINUTES_PER_DAY;
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+
+    /// Pattern to play instead of `pattern` at `minute` (None = mute)
+    pub fn adjust(&self, pattern: Mx4HapticPattern, minute: u16) -> Option<Mx4HapticPattern> {
+        if !self.contains(minute) {
+            return Some(pattern);
+        }
+        self.reduce_to_whisper.then_some(Mx4HapticPattern::WhisperCollision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_clock() {
+        assert_eq!(parse_clock("22:00"), Some(1320));
+        assert_eq!(parse_clock("7:05"), Some(425));
+        assert_eq!(parse_clock("24:00"), None);
+        assert_eq!(parse_clock("12:60"), None);
+        assert_eq!(parse_clock("noon"), None);
+    }
+
+    #[test]
+    fn test_window_wraps_midnight() {
+        let config = QuietHoursConfig {
+            enabled: true,
+            ..QuietHoursConfig::default()
+        };
+        let quiet = QuietHours::from_config(&config).unwrap();
+        assert!(quiet.contains(parse_clock("23:30").unwrap()));
+        assert!(quiet.contains(parse_clock("06:59").unwrap()));
+        assert!(!quiet.contains(parse_clock("07:00").unwrap()));
+        assert!(!quiet.contains(parse_clock("12:00").unwrap()));
+
+        let noon = parse_clock("12:00").unwrap();
+        let night = parse_clock("01:00").unwrap();
+        assert_eq!(quiet.adjust(Mx4HapticPattern::SharpCollision, noon), Some(Mx4HapticPattern::SharpCollision));
+        assert_eq!(quiet.adjust(Mx4HapticPattern::SharpCollision, night), Some(Mx4HapticPattern::WhisperCollision));
+
+        let muted = QuietHours::from_config(&QuietHoursConfig { reduce_to_whisper: false, ..config }).unwrap();
+        assert_eq!(muted.adjust(Mx4HapticPattern::SharpCollision, night), None);
+
+        assert!(QuietHours::from_config(&QuietHoursConfig::default()).is_none());
+    }
+}
@@ -0,0 +1,207 @@
+//! Receiver-level wireless notifications
+//!
+//! Unifying and Bolt receivers report changes of the wireless link with
+//! HID++ 1.0 notifications addressed to the paired device's slot:
+//!
+//! - `0x41` Device Connection: the link was established or lost (Easy-Switch
+//!   to another host, powered off, out of range)
+//! - `0x40` Device Disconnection: the device was unpaired
+//! - `0x4A` power notification (logged; does not change presence)
+//!
+//! Every HID++ reader hands its reports to [`observe_report`], which keeps a
+//! single presence flag for the mouse. Subsystems check [`is_reachable`] or
+//! [`subscribe`] to it instead of each discovering a disconnect through
+//! failed writes and timeouts: haptics and battery polling pause (see
+//! [`crate::host_switch`]), the battery is re-read as soon as the mouse is
+//! back, and the input arbiter releases a press the mouse can no longer end.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+use tokio::sync::watch;
+
+/// HID++ 1.0 Device Disconnection (unpaired) notification sub ID
+pub const DEVICE_DISCONNECTION: u8 = 0x40;
+
+/// HID++ 1.0 Device Connection notification sub ID
+pub const DEVICE_CONNECTION: u8 = 0x41;
+
+/// HID++ 1.0 power notification sub ID
+pub const POWER: u8 = 0x4A;
+
+/// Link-not-established flag in the connection notification's first parameter byte
+const LINK_NOT_ESTABLISHED: u8 = 0x40;
+
+/// A parsed receiver notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiverNotification {
+    /// Link to the device established or lost
+    Connection {
+        /// Receiver slot
+        device_index: u8,
+        /// Link is up
+        reachable: bool,
+        /// Wireless product ID of the device
+        wireless_pid: u16,
+    },
+    /// Device unpaired from the receiver
+    Disconnection {
+        /// Receiver slot
+        device_index: u8,
+    },
+    /// Power notification
+    Power {
+        /// Receiver slot
+        device_index: u8,
+        /// Raw first parameter byte
+        state: u8,
+    },
+}
+
+impl ReceiverNotification {
+    /// Receiver slot the notification is about
+    pub fn device_index(&self) -> u8 {
+        match *self {
+            ReceiverNotification::Connection { device_index, .. }
+            | ReceiverNotification::Disconnection { device_index }
+            | ReceiverNotification::Power { device_index, .. } => device_index,
+        }
+    }
+
+    /// Whether the device can be reached afterwards (None: no change)
+    pub fn reachable(&self) -> Option<bool> {
+        match *self {
+            ReceiverNotification::Connection { reachable, .. } => Some(reachable),
+            ReceiverNotification::Disconnection { .. } => Some(false),
+            ReceiverNotification::Power { .. } => None,
+        }
+    }
+}
+
+/// Parse a receiver notification
+///
+/// Layout: `[0x10, device index, sub ID, p0, p1, p2, p3]`. Connection
+/// notifications carry flags in p1 and the wireless PID in p2/p3.
+pub fn parse(report: &[u8]) -> Option<ReceiverNotification> {
+    if report.len() < 7 || report[0] != crate::hidpp::report_type::SHORT {
+        return None;
+    }
+    let device_index = report[1];
+    match report[2] {
+        DEVICE_CONNECTION => Some(ReceiverNotification::Connection {
+            device_index,
+            reachable: report[4] & LINK_NOT_ESTABLISHED == 0,
+            wireless_pid: u16::from_le_bytes([report[5], report[6]]),
+        }),
+        DEVICE_DISCONNECTION if report[3] == 0x02 => Some(ReceiverNotification::Disconnection { device_index }),
+        POWER => Some(ReceiverNotification::Power { device_index, state: report[3] }),
+        _ => None,
+    }
+}
+
+/// Device index of the mouse on its receiver (0 = not known yet)
+static DEVICE_INDEX: AtomicU8 = AtomicU8::new(0);
+
+/// Presence of the mouse, true until a notification says otherwise
+fn presence() -> &'static watch::Sender<bool> {
+    static PRESENCE: OnceLock<watch::Sender<bool>> = OnceLock::new();
+    PRESENCE.get_or_init(|| watch::Sender::new(true))
+}
+
+/// Remember which receiver slot the mouse uses
+///
+/// Notifications for other devices on the same receiver (e.g. a keyboard)
+/// are ignored once this is set.
+pub fn set_device_index(device_index: u8) {
+    DEVICE_INDEX.store(device_index, Ordering::Relaxed);
+}
+
+/// Whether the receiver reports the mouse as reachable
+pub fn is_reachable() -> bool {
+    *presence().borrow()
+}
+
+/// Watch the mouse's presence
+pub fn subscribe() -> watch::Receiver<bool> {
+    presence().subscribe()
+}
+
+/// Inspect an incoming report; returns true if it was a receiver notification
+pub fn observe_report(report: &[u8]) -> bool {
+    let Some(notification) = parse(report) else {
+        return false;
+    };
+    let device_index = notification.device_index();
+    let ours = DEVICE_INDEX.load(Ordering::Relaxed);
+    if ours != 0 && device_index != ours {
+        return true;
+    }
+
+    if !apply(presence(), &notification) {
+        return true;
+    }
+    match notification {
+        ReceiverNotification::Disconnection { .. } => {
+            tracing::warn!(device_index, "Mouse was unpaired from its receiver")
+        }
+        _ if is_reachable() => tracing::info!(device_index, "Mouse is back, resuming haptics and battery polling"),
+        _ => tracing::info!(device_index, "Mouse link lost (other host, off or out of range), pausing HID++ traffic"),
+    }
+    true
+}
+
+/// Update `presence` from a notification; returns true if it changed
+fn apply(presence: &watch::Sender<bool>, notification: &ReceiverNotification) -> bool {
+    let Some(reachable) = notification.reachable() else {
+        tracing::debug!(?notification, "Receiver notification");
+        return false;
+    };
+    presence.send_if_modified(|present| std::mem::replace(present, reachable) != reachable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_connection_notification() {
+        // Unifying/Bolt: [0x10, index, 0x41, protocol, flags|type, wpid lo, wpid hi]
+        assert_eq!(
+            parse(&[0x10, 0x02, 0x41, 0x10, 0x42, 0x34, 0x40]),
+            Some(ReceiverNotification::Connection { device_index: 2, reachable: false, wireless_pid: 0x4034 })
+        );
+        let back = parse(&[0x10, 0x02, 0x41, 0x10, 0x02, 0x34, 0x40]).unwrap();
+        assert_eq!(back.reachable(), Some(true));
+        // Diverted button event is not a receiver notification
+        assert_eq!(parse(&[0x11, 0x02, 0x0A, 0x00, 0x00, 0xC3, 0x00]), None);
+    }
+
+    #[test]
+    fn test_parse_unpair_and_power() {
+        let unpaired = parse(&[0x10, 0x03, 0x40, 0x02, 0x00, 0x00, 0x00]).unwrap();
+        assert_eq!(unpaired, ReceiverNotification::Disconnection { device_index: 3 });
+        assert_eq!(unpaired.reachable(), Some(false));
+
+        let power = parse(&[0x10, 0x03, 0x4A, 0x01, 0x00, 0x00, 0x00]).unwrap();
+        assert_eq!(power, ReceiverNotification::Power { device_index: 3, state: 1 });
+        assert_eq!(power.reachable(), None);
+    }
+
+    #[test]
+    fn test_apply_tracks_presence() {
+        let presence = watch::Sender::new(true);
+        let lost = parse(&[0x10, 0x05, 0x41, 0x10, 0x42, 0x34, 0x40]).unwrap();
+        assert!(apply(&presence, &lost));
+        assert!(!*presence.borrow());
+        assert!(!apply(&presence, &lost));
+
+        let power = parse(&[0x10, 0x05, 0x4A, 0x01, 0x00, 0x00, 0x00]).unwrap();
+        assert!(!apply(&presence, &power));
+
+        let back = parse(&[0x10, 0x05, 0x41, 0x10, 0x02, 0x34, 0x40]).unwrap();
+        assert!(apply(&presence, &back));
+        assert!(*presence.borrow());
+    }
+}
@@ -0,0 +1,172 @@
+//! Persistent runtime state for JuhRadial MX
+//!
+//! Volatile state that is not configuration but should survive a restart
+//! (or crash): the last active profile, the last DPI chosen with `SetDpi`,
+//! onboarding progress and the overlay's automatic blur decision. Stored as
+//! JSON in `$XDG_STATE_HOME/juhradial/state.json`.
+//!
+//! Every change is written immediately and atomically (temporary file,
+//! fsync, rename), so a crash leaves either the old or the new file, never a
+//! truncated one. A missing or corrupt file starts from defaults.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+/// State subdirectory
+const STATE_DIR: &str = "juhradial";
+
+/// State file name
+const STATE_FILE: &str = "state.json";
+
+/// How far the user got through first-run onboarding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnboardingProgress {
+    /// Last completed onboarding step (0 = not started)
+    #[serde(default)]
+    pub step: u32,
+
+    /// Onboarding finished or dismissed
+    #[serde(default)]
+    pub complete: bool,
+}
+
+/// Runtime state persisted across restarts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeState {
+    /// Profile active when the daemon last ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_profile: Option<String>,
+
+    /// DPI last set with SetDpi (re-applied when the mouse connects)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_dpi: Option<u16>,
+
+    /// First-run onboarding progress
+    #[serde(default)]
+    pub onboarding: OnboardingProgress,
+
+    /// Whether the overlay auto-disabled blur on this machine (None = undecided)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blur_auto_disabled: Option<bool>,
+
+    /// Backing file (not serialized, None = in-memory only)
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+/// Process-wide state, loaded by [`restore`]
+static STATE: Mutex<RuntimeState> = Mutex::new(RuntimeState::EMPTY);
+
+impl RuntimeState {
+    /// Defaults, usable in a `static`
+    const EMPTY: RuntimeState = RuntimeState {
+        last_profile: None,
+        last_dpi: None,
+        onboarding: OnboardingProgress { step: 0, complete: false },
+        blur_auto_disabled: None,
+        path: None,
+    };
+
+    /// Get the default state file path
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::state_dir().map(|p| p.join(STATE_DIR).join(STATE_FILE))
+    }
+
+    /// Load state from a file (missing or corrupt files start from defaults)
+    pub fn load(path: &Path) -> Self {
+        let mut state: Self = fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| match serde_json::from_str(&contents) {
+                Ok(state) => Some(state),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), "Ignoring corrupt runtime state: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        state.path = Some(path.to_path_buf());
+        state
+    }
+
+    /// Write state to the backing file (see [`crate::state_file`])
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        crate::state_file::write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+}
+
+/// Load the state file from the default location and make it current
+///
+/// Returns the restored state.
+pub fn restore() -> RuntimeState {
+    let state = match RuntimeState::default_path() {
+        Some(path) => RuntimeState::load(&path),
+        None => RuntimeState::default(),
+    };
+    if let Ok(mut current) = STATE.lock() {
+        *current = state.clone();
+    }
+    state
+}
+
+/// Copy of the current state
+pub fn snapshot() -> RuntimeState {
+    STATE.lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Change the state and persist it if anything changed
+pub fn update(change: impl FnOnce(&mut RuntimeState)) {
+    let Ok(mut state) = STATE.lock() else {
+        return;
+    };
+    let before = state.clone();
+    change(&mut state);
+    if *state == before {
+        return;
+    }
+    if let Err(e) = state.save() {
+        tracing::warn!("Failed to save runtime state: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("juhradial").join(STATE_FILE);
+
+        let mut state = RuntimeState::load(&path);
+        assert_eq!(state.last_profile, None);
+        state.last_profile = Some("blender".to_string());
+        state.last_dpi = Some(1600);
+        state.onboarding = OnboardingProgress { step: 3, complete: false };
+        state.blur_auto_disabled = Some(true);
+        state.save().unwrap();
+
+        assert_eq!(RuntimeState::load(&path), state);
+        // No temporary file left behind
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+
+    #[test]
+    fn test_corrupt_file_starts_from_defaults() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(STATE_FILE);
+        fs::write(&path, "{\"last_dpi\": ").unwrap();
+
+        let state = RuntimeState::load(&path);
+        assert_eq!(state.last_dpi, None);
+        assert_eq!(state.onboarding, OnboardingProgress::default());
+    }
+}
@@ -0,0 +1,96 @@
+//! Live screen bounds for JuhRadial MX
+//!
+//! Screen bounds used for edge clamping change when a laptop is docked or a
+//! monitor is plugged in. The watcher waits for output change events from
+//! the [`crate::compositor::CompositorBackend`] (or polls where the backend
+//! has none), re-queries the bounds and announces changes with the
+//! `ScreenConfigurationChanged(width, height)` signal. The latest bounds
+//! are available process-wide via [`current`].
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::RwLock;
+
+use crate::compositor;
+use crate::cursor::{get_screen_bounds, ScreenBounds};
+use crate::dbus::{DBUS_INTERFACE, DBUS_PATH};
+
+/// Poll interval for backends without output change events (seconds)
+const SCREEN_POLL_INTERVAL_SECS: u64 = 5;
+
+/// Delay after a change event before re-querying (outputs settle in stages)
+const SCREEN_SETTLE_MS: u64 = 500;
+
+/// Latest known screen bounds
+static BOUNDS: RwLock<ScreenBounds> = RwLock::new(ScreenBounds { width: 1920, height: 1080 });
+
+/// Latest known screen bounds
+pub fn current() -> ScreenBounds {
+    BOUNDS.read().map(|b| *b).unwrap_or_default()
+}
+
+/// Store new bounds; returns true if they changed
+fn store(bounds: ScreenBounds) -> bool {
+    match BOUNDS.write() {
+        Ok(mut current) if *current != bounds => {
+            *current = bounds;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Query the screen bounds once and make them current (blocking)
+pub fn refresh() -> ScreenBounds {
+    let bounds = get_screen_bounds();
+    store(bounds);
+    bounds
+}
+
+/// Follow output changes and announce them on D-Bus
+pub async fn start_screen_watcher(connection: zbus::Connection) {
+    loop {
+        let notified = tokio::task::spawn_blocking(|| compositor::backend().wait_screen_change())
+            .await
+            .unwrap_or(false);
+        let wait = if notified { SCREEN_SETTLE_MS } else { SCREEN_POLL_INTERVAL_SECS * 1000 };
+        tokio::time::sleep(tokio::time::Duration::from_millis(wait)).await;
+
+        let Ok(bounds) = tokio::task::spawn_blocking(get_screen_bounds).await else {
+            continue;
+        };
+        if !store(bounds) {
+            continue;
+        }
+
+        tracing::info!(width = bounds.width, height = bounds.height, "Screen configuration changed");
+        if let Err(e) = connection
+            .emit_signal(
+                None::<&str>,
+                DBUS_PATH,
+                DBUS_INTERFACE,
+                "ScreenConfigurationChanged",
+                &(bounds.width, bounds.height),
+            )
+            .await
+        {
+            tracing::warn!("Failed to emit ScreenConfigurationChanged: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_reports_changes() {
+        let docked = ScreenBounds { width: 4480, height: 1440 };
+        assert!(store(docked));
+        assert!(!store(docked));
+        assert_eq!(current(), docked);
+
+        assert!(store(ScreenBounds::default()));
+        assert_eq!(current(), ScreenBounds::default());
+    }
+}
@@ -0,0 +1,162 @@
+//! End-to-end self-test (`juhradialctl selftest`, `RunSelfTest`)
+//!
+//! Walks the path a real press takes and reports each hop:
+//!
+//! 1. an overlay is registered and sending heartbeats
+//! 2. `MenuRequested` at the screen center is acknowledged by the overlay
+//! 3. `CursorMoved(0, 0)` (center, nothing highlighted) is acknowledged
+//! 4. `HideMenu` after [`HOLD`] is acknowledged; the menu closes without
+//!    running anything since no slice is highlighted and the hold is longer
+//!    than the overlay's tap-to-toggle threshold
+//! 5. a haptic waveform plays on the mouse
+//! 6. a battery reading is available
+//!
+//! The overlay acknowledges signals with `AcknowledgeSignal` (see
+//! [`crate::overlay_monitor`]); an overlay that predates it fails steps 2-4
+//! even when it works.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::time::Duration;
+
+use crate::battery::SharedBatteryState;
+use crate::dbus::{DBUS_INTERFACE, DBUS_PATH};
+use crate::hidpp::{Mx4HapticPattern, SharedHapticManager};
+use crate::overlay_monitor::{now_ms, OverlayStatus, SharedOverlayMonitor};
+
+/// How long the overlay has to acknowledge a signal
+pub const ACK_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// How long the synthetic menu stays open (past the overlay's 250 ms tap threshold)
+pub const HOLD: Duration = Duration::from_millis(400);
+
+/// Waveform played by the haptic check
+pub const HAPTIC_PATTERN: Mx4HapticPattern = Mx4HapticPattern::HappyAlert;
+
+/// Interval between acknowledgement checks
+const ACK_POLL: Duration = Duration::from_millis(20);
+
+/// Outcome of one step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// Step name (e.g. "overlay", "MenuRequested")
+    pub name: String,
+    /// Whether the step passed
+    pub passed: bool,
+    /// What was observed
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &str, passed: bool, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), passed, detail: detail.into() }
+    }
+}
+
+/// (passed, total)
+pub fn summary(checks: &[Check]) -> (usize, usize) {
+    (checks.iter().filter(|c| c.passed).count(), checks.len())
+}
+
+/// Wait until the overlay acknowledges `signal` (sent at `since_ms`)
+pub async fn wait_for_ack(monitor: &SharedOverlayMonitor, signal: &str, since_ms: u64, timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if monitor.read().is_ok_and(|m| m.acknowledged_since(signal, since_ms)) {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(ACK_POLL).await;
+    }
+}
+
+/// Emit `signal` and check that the overlay acknowledges it
+async fn signal_check<B>(connection: &zbus::Connection, monitor: &SharedOverlayMonitor, signal: &str, body: &B) -> Check
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    let sent = now_ms();
+    if let Err(e) = connection.emit_signal(None::<&str>, DBUS_PATH, DBUS_INTERFACE, signal, body).await {
+        return Check::new(signal, false, format!("could not emit: {}", e));
+    }
+    if wait_for_ack(monitor, signal, sent, ACK_TIMEOUT).await {
+        let elapsed = now_ms().saturating_sub(sent);
+        Check::new(signal, true, format!("acknowledged after {} ms", elapsed))
+    } else {
+        Check::new(signal, false, format!("not acknowledged within {} ms", ACK_TIMEOUT.as_millis()))
+    }
+}
+
+/// Run every step; later steps run even if earlier ones fail
+pub async fn run(
+    connection: &zbus::Connection,
+    monitor: &SharedOverlayMonitor,
+    haptics: &SharedHapticManager,
+    battery: &SharedBatteryState,
+) -> Vec<Check> {
+    tracing::info!("Running self-test");
+    let mut checks = Vec::new();
+
+    let status = monitor.read().map(|m| m.status()).unwrap_or_default();
+    checks.push(match status {
+        OverlayStatus::Alive => Check::new("overlay", true, "registered, heartbeats arriving"),
+        OverlayStatus::Lost => Check::new("overlay", false, "registered but heartbeats stopped"),
+        OverlayStatus::Unregistered => Check::new("overlay", false, "no overlay registered (is it running?)"),
+    });
+
+    let screen = crate::screen_watcher::current();
+    let opened = tokio::time::Instant::now();
+    checks.push(signal_check(connection, monitor, "MenuRequested", &(screen.width / 2, screen.height / 2)).await);
+    checks.push(signal_check(connection, monitor, "CursorMoved", &(0i32, 0i32)).await);
+    tokio::time::sleep_until(opened + HOLD).await;
+    checks.push(signal_check(connection, monitor, "HideMenu", &()).await);
+
+    let played = match haptics.lock() {
+        Ok(mut manager) => manager.test_pattern(HAPTIC_PATTERN).map_err(|e| e.to_string()),
+        Err(e) => Err(format!("lock error: {}", e)),
+    };
+    checks.push(match played {
+        Ok(()) => Check::new("haptic", true, format!("played {}", HAPTIC_PATTERN.config_name())),
+        Err(e) => Check::new("haptic", false, e),
+    });
+
+    let state = battery.read().await;
+    checks.push(if state.available {
+        let charging = if state.charging { ", charging" } else { "" };
+        Check::new("battery", true, format!("{}%{}", state.percentage, charging))
+    } else {
+        Check::new("battery", false, state.error.clone().unwrap_or_else(|| "no reading yet".to_string()))
+    });
+
+    let (passed, total) = summary(&checks);
+    tracing::info!(passed, total, "Self-test finished");
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overlay_monitor::new_shared_overlay_monitor;
+
+    #[tokio::test]
+    async fn test_wait_for_ack() {
+        let monitor = new_shared_overlay_monitor();
+        monitor.write().unwrap().register(":1.42", None, 1000);
+        assert!(!wait_for_ack(&monitor, "MenuRequested", 1000, Duration::from_millis(30)).await);
+
+        let acker = monitor.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(40)).await;
+            acker.write().unwrap().acknowledge(":1.42", "MenuRequested", 2000);
+        });
+        assert!(wait_for_ack(&monitor, "MenuRequested", 1500, Duration::from_secs(2)).await);
+    }
+
+    #[test]
+    fn test_summary() {
+        let checks = [Check::new("overlay", true, ""), Check::new("haptic", false, "no device")];
+        assert_eq!(summary(&checks), (1, 2));
+    }
+}
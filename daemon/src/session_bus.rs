@@ -0,0 +1,203 @@
+//! Session bus connection that survives bus restarts
+//!
+//! When the session bus goes away (dbus-daemon or dbus-broker crashing or
+//! being restarted with the desktop session) every clone of the daemon's
+//! connection dies with it, and nothing used to notice: signals were simply
+//! no longer delivered until the daemon was restarted by hand.
+//!
+//! The current connection is published here. [`start_session_bus_watcher`]
+//! checks it with a periodic `GetId` call; after [`HEARTBEAT_MISSES`] failed
+//! checks in a row it is given up and a new one is opened with backoff,
+//! which claims the name and serves the objects again. Tasks spawned with
+//! [`spawn_on_session_bus`] are handed each new connection: the run on the
+//! old connection is dropped and a fresh one started.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::supervisor::{spawn_supervised, RestartBackoff};
+
+/// Interval between liveness checks
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a liveness check may take
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Failed checks in a row before the connection is given up
+pub const HEARTBEAT_MISSES: u32 = 2;
+
+/// First reconnect delay
+pub const RECONNECT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Upper bound for the reconnect delay
+pub const RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Current connection, None while reconnecting
+fn bus() -> &'static watch::Sender<Option<zbus::Connection>> {
+    static BUS: OnceLock<watch::Sender<Option<zbus::Connection>>> = OnceLock::new();
+    BUS.get_or_init(|| watch::Sender::new(None))
+}
+
+/// Publish a new connection (the first one, or one after a reconnect)
+pub fn publish(connection: zbus::Connection) {
+    bus().send_replace(Some(connection));
+}
+
+/// Current connection, None before the first one or while reconnecting
+pub fn current() -> Option<zbus::Connection> {
+    bus().borrow().clone()
+}
+
+/// Run `factory` once per published value
+///
+/// A run still going when the value changes is dropped; a run that ends
+/// waits for the next value. Nothing runs while the value is None.
+async fn run_per_connection<T, F, Fut>(mut values: watch::Receiver<Option<T>>, factory: F)
+where
+    T: Clone,
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    loop {
+        let value = values.borrow_and_update().clone();
+        if let Some(value) = value {
+            tokio::select! {
+                () = factory(value) => {}
+                changed = values.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                    continue;
+                }
+            }
+        }
+        if values.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Spawn a supervised task that needs the session bus
+///
+/// Like [`spawn_supervised`], but `factory` is called with the current
+/// connection and called again whenever the bus is reconnected.
+pub fn spawn_on_session_bus<F, Fut>(name: &'static str, factory: F) -> JoinHandle<()>
+where
+    F: Fn(zbus::Connection) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    spawn_supervised(name, move || run_per_connection(bus().subscribe(), factory.clone()))
+}
+
+/// Return once `connection` fails [`HEARTBEAT_MISSES`] checks in a row
+async fn wait_for_loss(connection: &zbus::Connection) {
+    let dbus = match zbus::fdo::DBusProxy::new(connection).await {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            tracing::debug!("Failed to create org.freedesktop.DBus proxy: {}", e);
+            return;
+        }
+    };
+    let mut misses = 0;
+    loop {
+        tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+        match tokio::time::timeout(HEARTBEAT_TIMEOUT, dbus.get_id()).await {
+            Ok(Ok(_)) => misses = 0,
+            Ok(Err(e)) => {
+                misses += 1;
+                tracing::debug!(misses, "Session bus check failed: {}", e);
+            }
+            Err(_) => {
+                misses += 1;
+                tracing::debug!(misses, "Session bus check timed out");
+            }
+        }
+        if misses >= HEARTBEAT_MISSES {
+            return;
+        }
+    }
+}
+
+/// Keep a session bus connection published for as long as the daemon runs
+///
+/// `connect` opens a new connection and serves the daemon's objects on it
+/// (see [`crate::dbus::serve_dbus_service`]). The first connection must
+/// already be published.
+pub async fn start_session_bus_watcher<F, Fut>(connect: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = zbus::Result<zbus::Connection>>,
+{
+    loop {
+        if let Some(connection) = current() {
+            wait_for_loss(&connection).await;
+            tracing::warn!("Lost the session bus connection, reconnecting");
+            bus().send_replace(None);
+        }
+
+        let mut backoff = RestartBackoff::new(RECONNECT_INITIAL_BACKOFF, RECONNECT_MAX_BACKOFF, Duration::MAX);
+        loop {
+            let delay = backoff.next_delay(Duration::ZERO);
+            tokio::time::sleep(delay).await;
+            match connect().await {
+                Ok(connection) => {
+                    tracing::info!("Reconnected to the session bus");
+                    crate::metrics::record_reconnect(crate::metrics::Component::SessionBus);
+                    publish(connection);
+                    break;
+                }
+                Err(e) => tracing::debug!(retry_in_ms = delay.as_millis() as u64, "Session bus unavailable: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn test_run_per_connection_restarts_on_change() {
+        let (tx, rx) = watch::channel(Some(1u32));
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let log = started.clone();
+        let task = tokio::spawn(run_per_connection(rx, move |value| {
+            log.lock().unwrap().push(value);
+            std::future::pending::<()>()
+        }));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        tx.send_replace(None);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        tx.send_replace(Some(2));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(*started.lock().unwrap(), vec![1, 2]);
+
+        drop(tx);
+        tokio::time::timeout(Duration::from_secs(1), task).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_finished_run_waits_for_next_connection() {
+        let (tx, rx) = watch::channel(Some(1u32));
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let log = started.clone();
+        tokio::spawn(run_per_connection(rx, move |value| {
+            log.lock().unwrap().push(value);
+            async {}
+        }));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(*started.lock().unwrap(), vec![1]);
+        tx.send_replace(Some(2));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(*started.lock().unwrap(), vec![1, 2]);
+    }
+}
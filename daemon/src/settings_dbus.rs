@@ -0,0 +1,612 @@
+//! Settings D-Bus API for JuhRadial MX
+//!
+//! Implements the org.kde.juhradialmx.Settings interface: a stable, versioned
+//! backend for GUI settings applications (GTK/libadwaita, Qt, ...). Every
+//! config section has a typed getter/setter; setters validate, persist to
+//! config.json, apply the change live and emit a change signal.
+//!
+//! Clients should check the `Version` property before use. Additive changes
+//! (new methods/signals) keep the version; breaking changes bump it.
+//! Version 2 added `quiet_hours` to `HapticConfig`, version 3 `rate_limit`,
+//! version 4 `verify_ack`.
+//!
+//! ## Interface: org.kde.juhradialmx.Settings
+//!
+//! ### Methods:
+//! - `GetHaptics() -> HapticConfig` / `SetHaptics(HapticConfig)`
+//! - `StartHapticCalibration() -> (s, as)` - Begin guided calibration; plays the first event's ladder
+//! - `ReplayHapticCalibration()` - Play the current event's ladder again
+//! - `ChooseHapticCalibrationLevel(level: u32) -> (s, as)` - Pick a level, get the next event (empty when done)
+//! - `CancelHapticCalibration()` - Abort without changing settings
+//! - `GetTheme() -> String` / `SetTheme(name: String)`
+//! - `GetThemePreview(name: String, size: u32) -> String` - Path of a cached SVG preview of any theme (size 0 = default)
+//! - `GetBlurEnabled() -> bool` / `SetBlurEnabled(enabled: bool)`
+//! - `GetOverlay() -> OverlayConfig` / `SetOverlay(OverlayConfig)`
+//! - `GetMode() -> String` / `SetMode(mode: String)` - "auto", "native" or "portal"
+//! - `GetPortal() -> PortalConfig` / `SetPortal(PortalConfig)`
+//! - `GetBatterySaver() -> BatterySaverConfig` / `SetBatterySaver(BatterySaverConfig)`
+//! - `Reload()` - Re-read config.json and emit all change signals
+//! - `ValidateConfig(json: String) -> a(sssuu)` - Dry-run check of config.json contents:
+//!   (severity "error"/"warning", key path, message, line, column) per issue; nothing is applied
+//!
+//! ### Signals:
+//! - `HapticsChanged(HapticConfig)`
+//! - `ThemeChanged(name: String)`
+//! - `ThemeListChanged(names: as)` - Themes were added to or removed from the theme directories
+//! - `BlurEnabledChanged(enabled: bool)`
+//! - `OverlayChanged(OverlayConfig)`
+//! - `ModeChanged(mode: String)`
+//! - `PortalChanged(PortalConfig)`
+//! - `BatterySaverChanged(BatterySaverConfig)`
+//!
+//! ### Properties:
+//! - `Version: u32` - Settings API version
+
+use std::sync::Mutex;
+
+use zbus::{interface, object_server::SignalEmitter, fdo};
+use crate::config::{BatterySaverConfig, Config, ConfigError, ConfigIssue, HapticConfig, OverlayConfig, PortalConfig, RuntimeMode, SharedConfig};
+use crate::haptic_calibration::{ladder_names, play_ladder, CalibrationEvent, HapticCalibration};
+use crate::hidpp::SharedHapticManager;
+use crate::theme::ThemeManager;
+
+/// Settings D-Bus interface name
+pub const SETTINGS_INTERFACE: &str = "org.kde.juhradialmx.Settings";
+
+/// Settings D-Bus object path
+pub const SETTINGS_PATH: &str = "/org/kde/juhradialmx/Settings";
+
+/// Settings API version (bumped on incompatible changes)
+pub const SETTINGS_API_VERSION: u32 = 4;
+
+/// Settings D-Bus service
+///
+/// Shares configuration and haptic manager with the daemon interface.
+pub struct SettingsService {
+    /// Shared configuration
+    config: SharedConfig,
+    /// Shared haptic manager (updated when haptic settings change)
+    haptic_manager: SharedHapticManager,
+    /// Running haptic calibration
+    calibration: Mutex<Option<HapticCalibration>>,
+    /// Ladder currently playing (aborted when the next one starts)
+    calibration_playback: Mutex<Option<tokio::task::JoinHandle<()>>>,
+}
+
+impl SettingsService {
+    /// Create a new settings service
+    pub fn new(config: SharedConfig, haptic_manager: SharedHapticManager) -> Self {
+        Self {
+            config,
+            haptic_manager,
+            calibration: Mutex::new(None),
+            calibration_playback: Mutex::new(None),
+        }
+    }
+
+    /// Read a value from the current config
+    fn read<T>(&self, f: impl FnOnce(&Config) -> T) -> fdo::Result<T> {
+        self.config
+            .read()
+            .map(|c| f(&c))
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))
+    }
+
+    /// Apply a change, persist it and publish it to the shared config
+    ///
+    /// The change is made on a copy so a failed save leaves the live config untouched.
+    fn update(&self, f: impl FnOnce(&mut Config)) -> Result<Config, ConfigError> {
+        let mut updated = self
+            .config
+            .read()
+            .map_err(|e| ConfigError::ValidationError(format!("Lock error: {}", e)))?
+            .clone();
+        f(&mut updated);
+        updated.save()?;
+
+        let mut config = self
+            .config
+            .write()
+            .map_err(|e| ConfigError::ValidationError(format!("Lock error: {}", e)))?;
+        *config = updated.clone();
+        Ok(updated)
+    }
+
+    /// Push haptic settings to the haptic manager
+    fn apply_haptics(&self, haptics: &HapticConfig) {
+        match self.haptic_manager.lock() {
+            Ok(mut manager) => manager.update_from_config(haptics),
+            Err(e) => tracing::error!(error = %e, "Failed to lock haptic manager for update"),
+        }
+    }
+
+    /// Play a calibration ladder, replacing any ladder still playing
+    fn play_calibration(&self, event: Option<CalibrationEvent>) {
+        let Ok(mut playback) = self.calibration_playback.lock() else {
+            return;
+        };
+        if let Some(previous) = playback.take() {
+            previous.abort();
+        }
+        *playback = event.map(|event| tokio::spawn(play_ladder(self.haptic_manager.clone(), event)));
+    }
+
+    /// Current calibration event, or NotRunning
+    fn calibration_event(&self) -> fdo::Result<CalibrationEvent> {
+        self.calibration
+            .lock()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?
+            .as_ref()
+            .and_then(HapticCalibration::current_event)
+            .ok_or_else(|| fdo::Error::Failed("No haptic calibration is running".to_string()))
+    }
+}
+
+/// D-Bus reply for a calibration step: event name and ladder (empty when finished)
+fn calibration_step(event: Option<CalibrationEvent>) -> (String, Vec<String>) {
+    match event {
+        Some(event) => (event.name().to_string(), ladder_names(event)),
+        None => (String::new(), Vec::new()),
+    }
+}
+
+/// Map a config error to a D-Bus error
+fn to_fdo_error(e: ConfigError) -> fdo::Error {
+    match e {
+        ConfigError::ValidationError(msg) => fdo::Error::InvalidArgs(msg),
+        e => fdo::Error::Failed(format!("Failed to save settings: {}", e)),
+    }
+}
+
+/// Validate overlay settings
+fn validate_overlay(overlay: &OverlayConfig) -> Result<(), ConfigError> {
+    if overlay.heartbeat_timeout_ms == 0 {
+        return Err(ConfigError::ValidationError(
+            "heartbeat_timeout_ms must be greater than 0".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Runtime mode as its config-file name
+fn mode_name(mode: RuntimeMode) -> String {
+    serde_json::to_value(mode)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Parse a runtime mode from its config-file name
+fn parse_mode(name: &str) -> Result<RuntimeMode, ConfigError> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .map_err(|_| ConfigError::ValidationError(format!("Unknown mode: {}", name)))
+}
+
+#[interface(name = "org.kde.juhradialmx.Settings")]
+impl SettingsService {
+    // =========================================================================
+    // HAPTICS
+    // =========================================================================
+
+    /// Get haptic feedback settings
+    async fn get_haptics(&self) -> fdo::Result<HapticConfig> {
+        self.read(|c| c.haptics.clone())
+    }
+
+    /// Replace haptic feedback settings
+    async fn set_haptics(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        mut haptics: HapticConfig,
+    ) -> fdo::Result<()> {
+        haptics.validate();
+        let config = self
+            .update(|c| c.haptics = haptics)
+            .map_err(to_fdo_error)?;
+        self.apply_haptics(&config.haptics);
+
+        tracing::info!(enabled = config.haptics.enabled, "Haptic settings changed via Settings API");
+        Self::haptics_changed(&emitter, config.haptics).await?;
+        Ok(())
+    }
+
+    // =========================================================================
+    // HAPTIC CALIBRATION
+    // =========================================================================
+
+    /// Start a guided haptic calibration
+    ///
+    /// Plays the first event's ladder, weakest level first. Restarts any
+    /// calibration already running.
+    ///
+    /// # Returns
+    /// `(event, levels)` - Event being calibrated (e.g. "menu_appear") and the
+    /// waveform names of its levels, in playback order
+    async fn start_haptic_calibration(&self) -> fdo::Result<(String, Vec<String>)> {
+        let calibration = HapticCalibration::new(&self.read(|c| c.haptics.per_event.clone())?);
+        let event = calibration.current_event();
+        *self
+            .calibration
+            .lock()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))? = Some(calibration);
+
+        tracing::info!("Haptic calibration started");
+        self.play_calibration(event);
+        Ok(calibration_step(event))
+    }
+
+    /// Play the current calibration event's ladder again
+    async fn replay_haptic_calibration(&self) -> fdo::Result<()> {
+        let event = self.calibration_event()?;
+        self.play_calibration(Some(event));
+        Ok(())
+    }
+
+    /// Choose the preferred level for the current event
+    ///
+    /// Plays the next event's ladder. After the last event the choices are
+    /// written into the haptic settings, applied and announced with
+    /// `HapticsChanged`.
+    ///
+    /// # Arguments
+    /// * `level` - Index into the levels returned for the current event
+    ///
+    /// # Returns
+    /// `(event, levels)` for the next event; both empty when calibration is complete
+    async fn choose_haptic_calibration_level(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        level: u32,
+    ) -> fdo::Result<(String, Vec<String>)> {
+        let (next, result) = {
+            let mut calibration = self
+                .calibration
+                .lock()
+                .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+            let running = calibration
+                .as_mut()
+                .ok_or_else(|| fdo::Error::Failed("No haptic calibration is running".to_string()))?;
+            let next = running
+                .choose(level)
+                .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+            let result = running.result().clone();
+            if next.is_none() {
+                *calibration = None;
+            }
+            (next, result)
+        };
+
+        self.play_calibration(next);
+        if next.is_some() {
+            return Ok(calibration_step(next));
+        }
+
+        let config = self
+            .update(|c| c.haptics.per_event = result)
+            .map_err(to_fdo_error)?;
+        self.apply_haptics(&config.haptics);
+
+        tracing::info!(per_event = ?config.haptics.per_event, "Haptic calibration complete");
+        Self::haptics_changed(&emitter, config.haptics).await?;
+        Ok(calibration_step(None))
+    }
+
+    /// Abort the running calibration without changing settings
+    async fn cancel_haptic_calibration(&self) -> fdo::Result<()> {
+        self.play_calibration(None);
+        if let Ok(mut calibration) = self.calibration.lock() {
+            if calibration.take().is_some() {
+                tracing::info!("Haptic calibration cancelled");
+            }
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // APPEARANCE
+    // =========================================================================
+
+    /// Get current theme name
+    async fn get_theme(&self) -> fdo::Result<String> {
+        self.read(|c| c.theme.clone())
+    }
+
+    /// Set the theme by name
+    async fn set_theme(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        name: String,
+    ) -> fdo::Result<()> {
+        if name.trim().is_empty() {
+            return Err(fdo::Error::InvalidArgs("Theme name must not be empty".to_string()));
+        }
+
+        let config = self
+            .update(|c| c.theme = name)
+            .map_err(to_fdo_error)?;
+
+        tracing::info!(theme = %config.theme, "Theme changed via Settings API");
+        Self::theme_changed(&emitter, config.theme).await?;
+        Ok(())
+    }
+
+    /// Render (or reuse) a preview of a theme and return the SVG's path
+    async fn get_theme_preview(&self, name: String, size: u32) -> fdo::Result<String> {
+        let themes = ThemeManager::load_all().map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let theme = themes
+            .get(&name)
+            .cloned()
+            .or_else(|| crate::bundled_themes::get_bundled_theme(&name))
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("Theme not found: {}", name)))?;
+        let path = crate::theme_preview::cached_preview(&theme, size)
+            .map_err(|e| fdo::Error::Failed(format!("Theme preview failed: {}", e)))?;
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    /// Get whether blur effects are enabled
+    async fn get_blur_enabled(&self) -> fdo::Result<bool> {
+        self.read(|c| c.blur_enabled)
+    }
+
+    /// Enable or disable blur effects
+    async fn set_blur_enabled(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        enabled: bool,
+    ) -> fdo::Result<()> {
+        self.update(|c| c.blur_enabled = enabled)
+            .map_err(to_fdo_error)?;
+
+        Self::blur_enabled_changed(&emitter, enabled).await?;
+        Ok(())
+    }
+
+    // =========================================================================
+    // OVERLAY
+    // =========================================================================
+
+    /// Get overlay liveness settings
+    async fn get_overlay(&self) -> fdo::Result<OverlayConfig> {
+        self.read(|c| c.overlay.clone())
+    }
+
+    /// Replace overlay liveness settings
+    async fn set_overlay(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        mut overlay: OverlayConfig,
+    ) -> fdo::Result<()> {
+        validate_overlay(&overlay).map_err(to_fdo_error)?;
+        overlay.validate();
+        let config = self
+            .update(|c| c.overlay = overlay)
+            .map_err(to_fdo_error)?;
+
+        Self::overlay_changed(&emitter, config.overlay).await?;
+        Ok(())
+    }
+
+    // =========================================================================
+    // BATTERY SAVER
+    // =========================================================================
+
+    /// Get low-battery power-saving policy
+    async fn get_battery_saver(&self) -> fdo::Result<BatterySaverConfig> {
+        self.read(|c| c.battery_saver.clone())
+    }
+
+    /// Replace low-battery power-saving policy (values are clamped)
+    async fn set_battery_saver(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        mut battery_saver: BatterySaverConfig,
+    ) -> fdo::Result<()> {
+        battery_saver.validate();
+        let config = self
+            .update(|c| c.battery_saver = battery_saver)
+            .map_err(to_fdo_error)?;
+
+        Self::battery_saver_changed(&emitter, config.battery_saver).await?;
+        Ok(())
+    }
+
+    // =========================================================================
+    // RUNTIME MODE
+    // =========================================================================
+
+    /// Get the configured runtime mode
+    async fn get_mode(&self) -> fdo::Result<String> {
+        self.read(|c| mode_name(c.mode))
+    }
+
+    /// Set the runtime mode (takes effect after a daemon restart)
+    async fn set_mode(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        mode: String,
+    ) -> fdo::Result<()> {
+        let mode = parse_mode(&mode).map_err(to_fdo_error)?;
+        self.update(|c| c.mode = mode).map_err(to_fdo_error)?;
+
+        Self::mode_changed(&emitter, mode_name(mode)).await?;
+        Ok(())
+    }
+
+    /// Get portal mode settings
+    async fn get_portal(&self) -> fdo::Result<PortalConfig> {
+        self.read(|c| c.portal.clone())
+    }
+
+    /// Replace portal mode settings (takes effect after a daemon restart)
+    async fn set_portal(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        portal: PortalConfig,
+    ) -> fdo::Result<()> {
+        let config = self
+            .update(|c| c.portal = portal)
+            .map_err(to_fdo_error)?;
+
+        Self::portal_changed(&emitter, config.portal).await?;
+        Self::battery_saver_changed(&emitter, config.battery_saver).await?;
+        Ok(())
+    }
+
+    // =========================================================================
+    // RELOAD / VALIDATE
+    // =========================================================================
+
+    /// Re-read config.json and emit change signals for every section
+    ///
+    /// Lets settings apps resync after the file was edited by hand.
+    async fn reload(&self, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) -> fdo::Result<()> {
+        let config = Config::load_default()
+            .map_err(|e| fdo::Error::Failed(format!("Config reload failed: {}", e)))?;
+
+        self.config
+            .write()
+            .map(|mut c| *c = config.clone())
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+        self.apply_haptics(&config.haptics);
+        crate::i18n::set_language(&config.language);
+        crate::hidpp_retry::configure(&config.hidpp_retry);
+
+        Self::haptics_changed(&emitter, config.haptics).await?;
+        Self::theme_changed(&emitter, config.theme).await?;
+        Self::blur_enabled_changed(&emitter, config.blur_enabled).await?;
+        Self::overlay_changed(&emitter, config.overlay).await?;
+        Self::mode_changed(&emitter, mode_name(config.mode)).await?;
+        Self::portal_changed(&emitter, config.portal).await?;
+        Ok(())
+    }
+
+    /// Check config.json contents without saving or applying them
+    ///
+    /// Lets settings apps verify an edit before writing it. An empty list
+    /// means the contents load exactly as written.
+    async fn validate_config(&self, json: &str) -> Vec<ConfigIssue> {
+        Config::check(json)
+    }
+
+    // =========================================================================
+    // SIGNALS
+    // =========================================================================
+
+    /// Emitted when haptic settings change
+    #[zbus(signal)]
+    async fn haptics_changed(emitter: &SignalEmitter<'_>, haptics: HapticConfig) -> zbus::Result<()>;
+
+    /// Emitted when the theme changes
+    #[zbus(signal)]
+    async fn theme_changed(emitter: &SignalEmitter<'_>, name: String) -> zbus::Result<()>;
+
+    /// Emitted when themes are added or removed (see [`crate::theme_watcher`])
+    #[zbus(signal)]
+    async fn theme_list_changed(emitter: &SignalEmitter<'_>, names: Vec<String>) -> zbus::Result<()>;
+
+    /// Emitted when blur is toggled
+    #[zbus(signal)]
+    async fn blur_enabled_changed(emitter: &SignalEmitter<'_>, enabled: bool) -> zbus::Result<()>;
+
+    /// Emitted when overlay settings change
+    #[zbus(signal)]
+    async fn overlay_changed(emitter: &SignalEmitter<'_>, overlay: OverlayConfig) -> zbus::Result<()>;
+
+    /// Emitted when the runtime mode changes
+    #[zbus(signal)]
+    async fn mode_changed(emitter: &SignalEmitter<'_>, mode: String) -> zbus::Result<()>;
+
+    /// Emitted when portal settings change
+    #[zbus(signal)]
+    async fn portal_changed(emitter: &SignalEmitter<'_>, portal: PortalConfig) -> zbus::Result<()>;
+
+    /// Emitted when the battery saver policy changes
+    #[zbus(signal)]
+    async fn battery_saver_changed(emitter: &SignalEmitter<'_>, battery_saver: BatterySaverConfig) -> zbus::Result<()>;
+
+    // =========================================================================
+    // PROPERTIES
+    // =========================================================================
+
+    /// Settings API version
+    #[zbus(property)]
+    async fn version(&self) -> u32 {
+        SETTINGS_API_VERSION
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hidpp::new_shared_haptic_manager;
+    use std::sync::{Arc, RwLock};
+
+    fn service_with_temp_config(dir: &tempfile::TempDir) -> SettingsService {
+        let config = Config {
+            config_path: Some(dir.path().join("config.json")),
+            ..Config::default()
+        };
+        let haptic_manager = new_shared_haptic_manager(&config.haptics);
+        SettingsService::new(Arc::new(RwLock::new(config)), haptic_manager)
+    }
+
+    #[test]
+    fn test_settings_constants() {
+        assert_eq!(SETTINGS_INTERFACE, "org.kde.juhradialmx.Settings");
+        assert_eq!(SETTINGS_PATH, "/org/kde/juhradialmx/Settings");
+        assert_eq!(SETTINGS_API_VERSION, 4);
+    }
+
+    #[test]
+    fn test_update_persists_and_publishes() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = service_with_temp_config(&dir);
+
+        service.update(|c| c.theme = "nord".to_string()).unwrap();
+
+        assert_eq!(service.read(|c| c.theme.clone()).unwrap(), "nord");
+        let saved = Config::load(dir.path().join("config.json")).unwrap();
+        assert_eq!(saved.theme, "nord");
+    }
+
+    #[test]
+    fn test_failed_save_keeps_live_config() {
+        let dir = tempfile::tempdir().unwrap();
+        // A directory where config.json should be makes the write fail
+        std::fs::create_dir(dir.path().join("config.json")).unwrap();
+        let service = service_with_temp_config(&dir);
+
+        assert!(service.update(|c| c.blur_enabled = false).is_err());
+        assert!(service.read(|c| c.blur_enabled).unwrap());
+    }
+
+    #[test]
+    fn test_validate_overlay() {
+        let mut overlay = OverlayConfig::default();
+        assert!(validate_overlay(&overlay).is_ok());
+
+        overlay.heartbeat_timeout_ms = 0;
+        assert!(matches!(
+            to_fdo_error(validate_overlay(&overlay).unwrap_err()),
+            fdo::Error::InvalidArgs(_)
+        ));
+    }
+
+    #[test]
+    fn test_mode_names_round_trip() {
+        for mode in [RuntimeMode::Auto, RuntimeMode::Native, RuntimeMode::Portal] {
+            assert_eq!(parse_mode(&mode_name(mode)).unwrap(), mode);
+        }
+        assert_eq!(mode_name(RuntimeMode::Portal), "portal");
+        assert!(parse_mode("sandbox").is_err());
+    }
+
+    #[test]
+    fn test_haptic_config_signature() {
+        use zbus::zvariant::Type;
+        // (b s (ssss) t t t (bssb) (buu) b) - enabled, default_pattern, per_event, debounces,
+        // quiet_hours, rate_limit, verify_ack
+        assert_eq!(HapticConfig::SIGNATURE.to_string(), "(bs(ssss)ttt(bssb)(buu)b)");
+        assert_eq!(OverlayConfig::SIGNATURE.to_string(), "(tb)");
+    }
+}
@@ -0,0 +1,448 @@
+//! Permissions self-setup for JuhRadial MX
+//!
+//! The daemon needs read access to Logitech evdev/hidraw nodes, which is
+//! granted by our udev rules plus membership in the `input` group. This
+//! module checks that state and installs the missing pieces through a
+//! privileged helper flow:
+//!
+//! 1. `juhradialctl setup` (or the daemon's `InstallUdevRules` D-Bus method)
+//!    builds a `pkexec juhradialctl install-rules-helper --user <name>` call.
+//! 2. polkit authorizes it via the `org.kde.juhradialmx.install-udev-rules`
+//!    action (see `packaging/polkit/`), prompting for admin credentials.
+//! 3. The helper, running as root, writes the embedded rules to
+//!    `/etc/udev/rules.d`, reloads udev and adds the user to `input`.
+//!
+//! No setuid binaries are involved; the helper refuses to run without root.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// polkit action ID for the install helper
+pub const POLKIT_ACTION_ID: &str = "org.kde.juhradialmx.install-udev-rules";
+
+/// Hidden juhradialctl subcommand that performs the privileged install
+pub const HELPER_SUBCOMMAND: &str = "install-rules-helper";
+
+/// Group granting access to input devices
+pub const INPUT_GROUP: &str = "input";
+
+/// Directory for locally installed udev rules
+const UDEV_RULES_DIR: &str = "/etc/udev/rules.d";
+
+/// Directory for distro-packaged udev rules
+const UDEV_VENDOR_RULES_DIR: &str = "/usr/lib/udev/rules.d";
+
+/// Fallback helper location when juhradialctl is not next to the running binary
+const DEFAULT_HELPER_PATH: &str = "/usr/bin/juhradialctl";
+
+/// pkexec exit status when the user dismissed the authentication dialog
+const PKEXEC_DISMISSED: i32 = 126;
+
+/// pkexec exit status when authorization failed
+const PKEXEC_NOT_AUTHORIZED: i32 = 127;
+
+/// udev rules shipped with the daemon: (file name, contents)
+pub const UDEV_RULES: &[(&str, &str)] = &[
+    (
+        "99-juhradialmx.rules",
+        include_str!("../../packaging/udev/99-juhradialmx.rules"),
+    ),
+    (
+        "60-ydotool-uinput.rules",
+        include_str!("../../packaging/udev/60-ydotool-uinput.rules"),
+    ),
+];
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+/// Setup error type
+#[derive(Debug)]
+pub enum SetupError {
+    /// Helper was run without root privileges
+    NotRoot,
+    /// Username is malformed or unknown
+    InvalidUser(String),
+    /// pkexec is not installed
+    PkexecUnavailable,
+    /// User dismissed or failed the polkit authentication
+    NotAuthorized,
+    /// An external command failed
+    CommandFailed(String),
+    /// I/O error writing rules
+    IoError(io::Error),
+}
+
+impl std::fmt::Display for SetupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetupError::NotRoot => write!(f, "The install helper must run as root (via pkexec)"),
+            SetupError::InvalidUser(user) => write!(f, "Invalid user: {}", user),
+            SetupError::PkexecUnavailable => write!(f, "pkexec not found - install polkit"),
+            SetupError::NotAuthorized => write!(f, "Authorization was cancelled or denied"),
+            SetupError::CommandFailed(msg) => write!(f, "Command failed: {}", msg),
+            SetupError::IoError(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SetupError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SetupError::IoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SetupError {
+    fn from(e: io::Error) -> Self {
+        SetupError::IoError(e)
+    }
+}
+
+// ============================================================================
+// Permission Status
+// ============================================================================
+
+/// Current permission setup state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PermissionStatus {
+    /// Our udev rules exist (local or vendor directory)
+    pub rules_installed: bool,
+    /// Installed rules match the rules shipped with this build
+    pub rules_current: bool,
+    /// User is listed as a member of the input group
+    pub in_input_group: bool,
+    /// input group is active in the current session (requires re-login after usermod)
+    pub group_active: bool,
+}
+
+impl PermissionStatus {
+    /// Check whether the install helper has anything to do
+    pub fn needs_install(&self) -> bool {
+        !self.rules_current || !self.in_input_group
+    }
+
+    /// Check whether the user must log out and back in
+    pub fn needs_relogin(&self) -> bool {
+        self.in_input_group && !self.group_active
+    }
+}
+
+/// Check permission setup for a user
+pub fn check_permissions(user: &str) -> PermissionStatus {
+    let (rules_installed, rules_current) = check_rules(&[
+        Path::new(UDEV_RULES_DIR),
+        Path::new(UDEV_VENDOR_RULES_DIR),
+    ]);
+
+    let group_file = fs::read_to_string("/etc/group").unwrap_or_default();
+    let passwd_file = fs::read_to_string("/etc/passwd").unwrap_or_default();
+    let group_gid = group_gid(&group_file, INPUT_GROUP);
+
+    PermissionStatus {
+        rules_installed,
+        rules_current,
+        in_input_group: user_in_group(&group_file, &passwd_file, user, INPUT_GROUP),
+        group_active: group_gid.is_some_and(process_has_gid),
+    }
+}
+
+/// Check installed rules across the given directories
+///
+/// Returns (installed, current). A rule counts as current if any directory
+/// holds an identical copy.
+fn check_rules(dirs: &[&Path]) -> (bool, bool) {
+    let mut installed = false;
+    let mut current = true;
+
+    for (name, contents) in UDEV_RULES {
+        let copies: Vec<String> = dirs
+            .iter()
+            .filter_map(|dir| fs::read_to_string(dir.join(name)).ok())
+            .collect();
+
+        // The main rules file decides "installed"; ydotool rules are optional extras
+        if *name == UDEV_RULES[0].0 {
+            installed = !copies.is_empty();
+        }
+        if !copies.iter().any(|c| c == contents) {
+            current = false;
+        }
+    }
+
+    (installed, current)
+}
+
+/// Look up a group's GID in /etc/group contents
+fn group_gid(group_file: &str, group: &str) -> Option<u32> {
+    group_file
+        .lines()
+        .map(|l| l.split(':').collect::<Vec<_>>())
+        .find(|f| f.len() >= 3 && f[0] == group)
+        .and_then(|f| f[2].parse().ok())
+}
+
+/// Check group membership (supplementary list or primary GID)
+fn user_in_group(group_file: &str, passwd_file: &str, user: &str, group: &str) -> bool {
+    let entry = group_file
+        .lines()
+        .map(|l| l.split(':').collect::<Vec<_>>())
+        .find(|f| f.len() >= 4 && f[0] == group);
+
+    let Some(fields) = entry else {
+        return false;
+    };
+
+    if fields[3].split(',').any(|m| m.trim() == user) {
+        return true;
+    }
+
+    // Primary group from /etc/passwd
+    let gid = fields[2];
+    passwd_file
+        .lines()
+        .map(|l| l.split(':').collect::<Vec<_>>())
+        .any(|f| f.len() >= 4 && f[0] == user && f[3] == gid)
+}
+
+/// Check if a user exists in /etc/passwd contents
+fn user_exists(passwd_file: &str, user: &str) -> bool {
+    passwd_file
+        .lines()
+        .any(|l| l.split(':').next() == Some(user))
+}
+
+/// Check whether the current process has a GID (primary or supplementary)
+fn process_has_gid(gid: u32) -> bool {
+    // SAFETY: getgroups with size 0 returns the count; the second call fills
+    // a buffer of exactly that size.
+    unsafe {
+        if libc::getegid() == gid {
+            return true;
+        }
+        let count = libc::getgroups(0, std::ptr::null_mut());
+        if count <= 0 {
+            return false;
+        }
+        let mut groups = vec![0 as libc::gid_t; count as usize];
+        let count = libc::getgroups(count, groups.as_mut_ptr());
+        count > 0 && groups[..count as usize].contains(&gid)
+    }
+}
+
+// ============================================================================
+// User Helpers
+// ============================================================================
+
+/// Validate a username before passing it to usermod
+///
+/// Accepts the portable POSIX set plus `-` and a trailing `$`, never a leading `-`.
+pub fn validate_username(user: &str) -> Result<(), SetupError> {
+    let valid = !user.is_empty()
+        && user.len() <= 32
+        && !user.starts_with('-')
+        && user
+            .char_indices()
+            .all(|(i, c)| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || (c == '$' && i == user.len() - 1));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(SetupError::InvalidUser(user.to_string()))
+    }
+}
+
+/// Name of the user running this process
+pub fn current_username() -> Option<String> {
+    // SAFETY: getuid has no preconditions
+    let uid = unsafe { libc::getuid() }.to_string();
+    fs::read_to_string("/etc/passwd")
+        .ok()
+        .and_then(|passwd| {
+            passwd
+                .lines()
+                .map(|l| l.split(':').collect::<Vec<_>>())
+                .find(|f| f.len() >= 3 && f[2] == uid)
+                .map(|f| f[0].to_string())
+        })
+        .or_else(|| std::env::var("USER").ok())
+}
+
+// ============================================================================
+// Privileged Helper Flow
+// ============================================================================
+
+/// Locate the juhradialctl helper binary
+///
+/// Prefers juhradialctl next to the running executable so that local builds
+/// and /usr/local installs work; pkexec requires an absolute path.
+pub fn helper_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("juhradialctl")))
+        .filter(|p| p.is_file())
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_HELPER_PATH))
+}
+
+/// Build the pkexec invocation for the install helper
+pub fn pkexec_command(helper: &Path, user: &str) -> Command {
+    let mut command = Command::new("pkexec");
+    command
+        .arg(helper)
+        .arg(HELPER_SUBCOMMAND)
+        .arg("--user")
+        .arg(user);
+    command
+}
+
+/// Map a pkexec exit status to a result
+pub fn pkexec_result(status: std::process::ExitStatus) -> Result<(), SetupError> {
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(PKEXEC_DISMISSED) | Some(PKEXEC_NOT_AUTHORIZED) => Err(SetupError::NotAuthorized),
+        Some(code) => Err(SetupError::CommandFailed(format!("install helper exited with {}", code))),
+        None => Err(SetupError::CommandFailed("install helper was terminated".to_string())),
+    }
+}
+
+/// Request installation through pkexec (blocks while the polkit dialog is shown)
+pub fn request_install(user: &str) -> Result<(), SetupError> {
+    validate_username(user)?;
+
+    let status = pkexec_command(&helper_path(), user)
+        .status()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => SetupError::PkexecUnavailable,
+            _ => SetupError::IoError(e),
+        })?;
+
+    pkexec_result(status)
+}
+
+/// Privileged part: install rules and add the user to the input group
+///
+/// Must run as root; invoked by pkexec through `juhradialctl install-rules-helper`.
+pub fn run_install_helper(user: &str) -> Result<(), SetupError> {
+    // SAFETY: geteuid has no preconditions
+    if unsafe { libc::geteuid() } != 0 {
+        return Err(SetupError::NotRoot);
+    }
+
+    validate_username(user)?;
+    let passwd_file = fs::read_to_string("/etc/passwd")?;
+    if !user_exists(&passwd_file, user) {
+        return Err(SetupError::InvalidUser(user.to_string()));
+    }
+
+    install_rules(Path::new(UDEV_RULES_DIR))?;
+    run("udevadm", &["control", "--reload-rules"])?;
+    run("udevadm", &["trigger"])?;
+
+    let group_file = fs::read_to_string("/etc/group")?;
+    if !user_in_group(&group_file, &passwd_file, user, INPUT_GROUP) {
+        run("usermod", &["-aG", INPUT_GROUP, user])?;
+    }
+
+    Ok(())
+}
+
+/// Write the embedded rules into a directory
+fn install_rules(dir: &Path) -> Result<(), SetupError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::create_dir_all(dir)?;
+    for (name, contents) in UDEV_RULES {
+        let path = dir.join(name);
+        fs::write(&path, contents)?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644))?;
+    }
+    Ok(())
+}
+
+/// Run a command, failing on non-zero exit
+fn run(program: &str, args: &[&str]) -> Result<(), SetupError> {
+    let status = Command::new(program).args(args).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(SetupError::CommandFailed(format!("{} {} ({})", program, args.join(" "), status)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GROUP: &str = "root:x:0:\ninput:x:104:alice,bob\nwheel:x:10:carol\nusers:x:100:\n";
+    const PASSWD: &str = "root:x:0:0::/root:/bin/sh\nalice:x:1000:1000::/home/alice:/bin/sh\ndave:x:1001:104::/home/dave:/bin/sh\n";
+
+    #[test]
+    fn test_user_in_group() {
+        assert!(user_in_group(GROUP, PASSWD, "alice", "input"));
+        assert!(user_in_group(GROUP, PASSWD, "bob", "input"));
+        // Primary GID match
+        assert!(user_in_group(GROUP, PASSWD, "dave", "input"));
+        assert!(!user_in_group(GROUP, PASSWD, "carol", "input"));
+        assert!(!user_in_group(GROUP, PASSWD, "alice", "missing"));
+    }
+
+    #[test]
+    fn test_group_gid_and_user_exists() {
+        assert_eq!(group_gid(GROUP, "input"), Some(104));
+        assert_eq!(group_gid(GROUP, "plugdev"), None);
+        assert!(user_exists(PASSWD, "alice"));
+        assert!(!user_exists(PASSWD, "mallory"));
+    }
+
+    #[test]
+    fn test_validate_username() {
+        assert!(validate_username("alice").is_ok());
+        assert!(validate_username("j.doe-2").is_ok());
+        assert!(validate_username("machine$").is_ok());
+        assert!(validate_username("").is_err());
+        assert!(validate_username("-aG").is_err());
+        assert!(validate_username("a b").is_err());
+        assert!(validate_username("a$b").is_err());
+        assert!(validate_username("root;rm").is_err());
+    }
+
+    #[test]
+    fn test_check_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(check_rules(&[dir.path()]), (false, false));
+
+        install_rules(dir.path()).unwrap();
+        assert_eq!(check_rules(&[dir.path()]), (true, true));
+
+        fs::write(dir.path().join(UDEV_RULES[0].0), "# outdated").unwrap();
+        assert_eq!(check_rules(&[dir.path()]), (true, false));
+    }
+
+    #[test]
+    fn test_pkexec_command_args() {
+        let command = pkexec_command(Path::new("/usr/bin/juhradialctl"), "alice");
+        assert_eq!(command.get_program(), "pkexec");
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["/usr/bin/juhradialctl", HELPER_SUBCOMMAND, "--user", "alice"]);
+    }
+
+    #[test]
+    fn test_permission_status_flags() {
+        let status = PermissionStatus {
+            rules_installed: true,
+            rules_current: true,
+            in_input_group: true,
+            group_active: false,
+        };
+        assert!(!status.needs_install());
+        assert!(status.needs_relogin());
+        assert!(PermissionStatus::default().needs_install());
+    }
+}
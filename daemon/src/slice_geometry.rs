@@ -0,0 +1,165 @@
+//! Radial menu slice geometry
+//!
+//! Maps a cursor offset from the menu center to a slice. Angles are in
+//! degrees, measured clockwise from north (screen Y grows downward), and
+//! slice 0 is centered on `start_angle_deg`. Slices follow clockwise, or
+//! counter-clockwise when `mirrored` (left-handed). Offsets closer than
+//! `dead_zone_radius` select nothing. Once a slice is selected it stays
+//! selected until the cursor moves `hysteresis_deg` past its boundary, so
+//! the selection does not flicker between neighbours when the cursor rests
+//! on the line between them.
+//!
+//! The daemon hit-tests with [`SliceGeometry::hit_test`] and exports the
+//! same geometry to overlays (`GetSliceGeometry`, and `geometry` in the
+//! MenuReady payload and the `GetMenuLayout` JSON).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::SliceGeometryConfig;
+
+/// Number of slices in the radial menu
+pub const SLICE_COUNT: u8 = 8;
+
+/// Default center dead-zone radius, in pixels
+pub const DEFAULT_DEAD_ZONE_RADIUS: u32 = 45;
+
+/// Default hysteresis at slice boundaries, in degrees
+pub const DEFAULT_HYSTERESIS_DEG: f32 = 4.0;
+
+/// Slice layout and hit-testing parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SliceGeometry {
+    /// Number of slices
+    pub slice_count: u8,
+    /// Radius of the center dead zone (no slice selected), in pixels
+    pub dead_zone_radius: f32,
+    /// Center of slice 0, degrees clockwise from north (rotation included)
+    pub start_angle_deg: f32,
+    /// Extra angle a selected slice keeps past its boundaries
+    pub hysteresis_deg: f32,
+    /// Slices run counter-clockwise from slice 0
+    #[serde(default)]
+    pub mirrored: bool,
+}
+
+impl Default for SliceGeometry {
+    fn default() -> Self {
+        Self::from_config(&SliceGeometryConfig::default())
+    }
+}
+
+impl SliceGeometry {
+    /// Build the geometry from (validated) configuration
+    pub fn from_config(config: &SliceGeometryConfig) -> Self {
+        Self {
+            slice_count: SLICE_COUNT,
+            dead_zone_radius: config.dead_zone_radius as f32,
+            start_angle_deg: (config.start_angle_deg + config.rotation.degrees()).rem_euclid(360.0),
+            hysteresis_deg: config.hysteresis_deg,
+            mirrored: config.left_handed,
+        }
+    }
+
+    /// Angular width of one slice, in degrees
+    pub fn sector_deg(&self) -> f32 {
+        360.0 / self.slice_count.max(1) as f32
+    }
+
+    /// Center angle of a slice, degrees clockwise from north
+    pub fn slice_center_deg(&self, index: u8) -> f32 {
+        let step = index as f32 * self.sector_deg();
+        let angle = if self.mirrored { self.start_angle_deg - step } else { self.start_angle_deg + step };
+        angle.rem_euclid(360.0)
+    }
+
+    /// Cursor angle, or None inside the dead zone
+    fn angle_of(&self, dx: i32, dy: i32) -> Option<f32> {
+        let (dx, dy) = (dx as f32, dy as f32);
+        if dx * dx + dy * dy < self.dead_zone_radius * self.dead_zone_radius {
+            return None;
+        }
+        // atan2(dx, -dy): 0 at north, increasing clockwise
+        Some(dx.atan2(-dy).to_degrees())
+    }
+
+    /// Slice under a cursor offset, without hysteresis
+    pub fn slice_at(&self, dx: i32, dy: i32) -> Option<u8> {
+        let angle = self.angle_of(dx, dy)?;
+        let sector = self.sector_deg();
+        let offset = if self.mirrored { self.start_angle_deg - angle } else { angle - self.start_angle_deg };
+        let relative = (offset + sector / 2.0).rem_euclid(360.0);
+        Some((relative / sector) as u8 % self.slice_count.max(1))
+    }
+
+    /// Slice under a cursor offset, keeping `current` near its boundaries
+    pub fn hit_test(&self, dx: i32, dy: i32, current: Option<u8>) -> Option<u8> {
+        let angle = self.angle_of(dx, dy)?;
+        if let Some(index) = current.filter(|&i| i < self.slice_count) {
+            let offset = (angle - self.slice_center_deg(index) + 180.0).rem_euclid(360.0) - 180.0;
+            if offset.abs() <= self.sector_deg() / 2.0 + self.hysteresis_deg {
+                return Some(index);
+            }
+        }
+        self.slice_at(dx, dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MenuRotation;
+
+    #[test]
+    fn test_start_angle_and_dead_zone() {
+        let geometry = SliceGeometry::default();
+        assert_eq!(geometry.slice_at(0, 0), None);
+        assert_eq!(geometry.slice_at(0, -100), Some(0)); // N
+        assert_eq!(geometry.slice_at(100, 0), Some(2)); // E
+
+        // Slice 0 centered on east
+        let rotated = SliceGeometry::from_config(&SliceGeometryConfig {
+            start_angle_deg: 90.0,
+            ..SliceGeometryConfig::default()
+        });
+        assert_eq!(rotated.slice_at(100, 0), Some(0));
+        assert_eq!(rotated.slice_at(0, -100), Some(6));
+    }
+
+    #[test]
+    fn test_rotation_and_mirroring() {
+        let left_handed = SliceGeometry::from_config(&SliceGeometryConfig {
+            left_handed: true,
+            ..SliceGeometryConfig::default()
+        });
+        assert_eq!(left_handed.slice_at(0, -100), Some(0)); // N
+        assert_eq!(left_handed.slice_at(-100, 0), Some(2)); // W
+        assert_eq!(left_handed.slice_at(70, -70), Some(7)); // NE
+        assert_eq!(left_handed.slice_center_deg(2), 270.0);
+
+        // Quarter turn adds to the start angle
+        let portrait = SliceGeometry::from_config(&SliceGeometryConfig {
+            start_angle_deg: 45.0,
+            rotation: MenuRotation::CounterClockwise,
+            ..SliceGeometryConfig::default()
+        });
+        assert_eq!(portrait.start_angle_deg, 315.0);
+        assert_eq!(portrait.slice_at(-70, -70), Some(0)); // NW
+        assert_eq!(portrait.slice_at(70, -70), Some(2)); // NE
+    }
+
+    #[test]
+    fn test_hysteresis_at_boundary() {
+        let geometry = SliceGeometry::default();
+        // Just past the N/NE boundary (22.5 degrees)
+        let (dx, dy) = (41, -94);
+        assert_eq!(geometry.slice_at(dx, dy), Some(1));
+        assert_eq!(geometry.hit_test(dx, dy, Some(0)), Some(0));
+        assert_eq!(geometry.hit_test(dx, dy, None), Some(1));
+        // Well into NE: switch
+        assert_eq!(geometry.hit_test(70, -70, Some(0)), Some(1));
+        // Dead zone clears the selection
+        assert_eq!(geometry.hit_test(5, 5, Some(0)), None);
+    }
+}
@@ -0,0 +1,130 @@
+//! System state shown on menu slices
+//!
+//! Some slices switch something outside the daemon on and off. When the
+//! menu layout is built, those slices get `"active": true|false` in the
+//! `GetMenuLayout` / `MenuReady` JSON, and `network` slices with
+//! `label_on`/`label_off` get the matching `label`:
+//!
+//! | Slice type          | Active when                             |
+//! |---------------------|-----------------------------------------|
+//! | `set_power_profile` | its profile is the current one          |
+//! | `network`           | its NetworkManager connection is active |
+//! | `wifi`              | the Wi-Fi radio is on                   |
+//!
+//! Services are only queried when the layout has such slices, so other
+//! menus open as fast as before.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use serde_json::Value;
+
+use crate::actions::{Action, ActionType};
+use crate::network::{NetworkAction, NetworkState};
+use crate::power_profiles::PowerProfile;
+use crate::profiles::Profile;
+
+/// Mark the stateful slices of `json`, the serialized form of `profile`
+pub async fn annotate(profile: &Profile, json: &mut Value) {
+    let actions: Vec<&Action> = profile_actions(profile).collect();
+    let power = if actions.iter().any(|a| matches!(a.action_type, ActionType::SetPowerProfile(_))) {
+        crate::power_profiles::active().await
+    } else {
+        None
+    };
+    let network = if actions.iter().any(|a| matches!(a.action_type, ActionType::Network(_) | ActionType::Wifi(_))) {
+        crate::network::state().await
+    } else {
+        NetworkState::default()
+    };
+    mark(json, power, &network);
+}
+
+/// Apply already-queried state to the layout JSON
+pub fn mark(json: &mut Value, power: Option<PowerProfile>, network: &NetworkState) {
+    for slice in layout_slices_mut(json) {
+        let active = match slice["type"].as_str() {
+            Some("set_power_profile") => power.is_some_and(|p| slice["value"] == p.as_str()),
+            Some("network") => {
+                let Ok(action) = serde_json::from_value::<NetworkAction>(slice["value"].clone()) else {
+                    continue;
+                };
+                let active = network.active_connections.contains(&action.connection);
+                if let Some(label) = if active { action.label_on } else { action.label_off } {
+                    slice["label"] = Value::String(label);
+                }
+                active
+            }
+            Some("wifi") => network.wifi_enabled == Some(true),
+            _ => continue,
+        };
+        slice["active"] = Value::Bool(active);
+    }
+}
+
+/// Slice and center actions of every page
+fn profile_actions(profile: &Profile) -> impl Iterator<Item = &Action> {
+    profile
+        .slices
+        .iter()
+        .chain(profile.pages.iter().flatten())
+        .chain(std::iter::once(&profile.center))
+        .flatten()
+}
+
+/// Slice objects on every page of a layout JSON, plus the center action
+pub(crate) fn layout_slices_mut(layout: &mut Value) -> Vec<&mut Value> {
+    let Some(object) = layout.as_object_mut() else {
+        return Vec::new();
+    };
+    let mut slices = Vec::new();
+    for (key, value) in object.iter_mut() {
+        match (key.as_str(), value) {
+            ("slices", Value::Array(page)) => slices.extend(page.iter_mut()),
+            ("pages", Value::Array(pages)) => {
+                for page in pages.iter_mut().filter_map(|p| p.as_array_mut()) {
+                    slices.extend(page.iter_mut());
+                }
+            }
+            ("center", center) => slices.push(center),
+            _ => {}
+        }
+    }
+    slices.retain(|slice| slice.is_object());
+    slices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mark() {
+        let mut layout = serde_json::json!({
+            "slices": [
+                {"type": "set_power_profile", "value": "power-saver"},
+                null,
+                {"type": "set_power_profile", "value": "performance"},
+                {"type": "command", "value": "true"},
+                {"type": "network", "value": {"connection": "Work VPN", "label_on": "VPN on", "label_off": "VPN off"},
+                 "label": "VPN"},
+                {"type": "network", "value": {"connection": "Home"}, "label": "Home"},
+            ],
+            "pages": [[{"type": "set_power_profile", "value": "balanced"}, {"type": "wifi", "value": "toggle"}]],
+        });
+        let network = NetworkState {
+            active_connections: ["Home".to_string()].into(),
+            wifi_enabled: Some(true),
+        };
+        mark(&mut layout, Some(PowerProfile::PowerSaver), &network);
+
+        assert_eq!(layout["slices"][0]["active"], true);
+        assert_eq!(layout["slices"][2]["active"], false);
+        assert!(layout["slices"][3].get("active").is_none());
+        assert_eq!(layout["slices"][4]["active"], false);
+        assert_eq!(layout["slices"][4]["label"], "VPN off");
+        assert_eq!(layout["slices"][5]["active"], true);
+        assert_eq!(layout["slices"][5]["label"], "Home");
+        assert_eq!(layout["pages"][0][0]["active"], false);
+        assert_eq!(layout["pages"][0][1]["active"], true);
+    }
+}
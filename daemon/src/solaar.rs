@@ -0,0 +1,214 @@
+//! Solaar conflict detection
+//!
+//! Solaar opens the same hidraw node as the daemon. Both then read each
+//! other's HID++ responses, so requests interleave and time out (battery
+//! queries, haptics, divert setup). The monitor looks for a Solaar process
+//! (and its `io.github.pwr_solaar.solaar` bus name) every
+//! [`POLL_INTERVAL`] and, while it runs:
+//!
+//! - reports the conflict through [`crate::diagnostics`] with what to do
+//! - sets [`is_running`], which makes the daemon fall back to listening:
+//!   periodic requests (battery polling, keep-alive pings) are skipped and
+//!   only user-triggered traffic such as haptics is sent
+//!
+//! Not started in portal mode (no hidraw access).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::diagnostics::{self, Diagnostic, Severity};
+
+/// Well-known bus name Solaar owns on the session bus
+pub const DBUS_NAME: &str = "io.github.pwr_solaar.solaar";
+
+/// Interval between checks
+pub const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Diagnostics source name
+const SOURCE: &str = "solaar";
+
+/// Set while Solaar is detected
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Whether Solaar is running; periodic HID++ requests should be skipped
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::Relaxed)
+}
+
+/// A running Solaar instance
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SolaarProcess {
+    /// Process ID (None if only its bus name was seen)
+    pub pid: Option<u32>,
+    /// hidraw nodes it has open (empty if unknown, e.g. another user's process)
+    pub hidraw: Vec<PathBuf>,
+}
+
+/// Whether a NUL-separated `/proc/<pid>/cmdline` is Solaar
+///
+/// Matches both the `solaar` launcher and `python3 /usr/bin/solaar ...`.
+pub fn is_solaar_cmdline(cmdline: &[u8]) -> bool {
+    let mut args = cmdline.split(|&b| b == 0).filter(|a| !a.is_empty()).map(basename);
+    let Some(program) = args.next() else {
+        return false;
+    };
+    if program == b"solaar" {
+        return true;
+    }
+    if !program.starts_with(b"python") {
+        return false;
+    }
+    // Interpreter options come before the script
+    args.find(|a| !a.starts_with(b"-")).is_some_and(|script| script == b"solaar")
+}
+
+fn basename(arg: &[u8]) -> &[u8] {
+    arg.rsplit(|&b| b == b'/').next().unwrap_or(arg)
+}
+
+/// Find a Solaar process under `proc_dir` (normally `/proc`)
+pub fn find_process(proc_dir: &Path) -> Option<SolaarProcess> {
+    let entries = std::fs::read_dir(proc_dir).ok()?;
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(cmdline) = std::fs::read(entry.path().join("cmdline")) else {
+            continue;
+        };
+        if is_solaar_cmdline(&cmdline) {
+            return Some(SolaarProcess { pid: Some(pid), hidraw: open_hidraw(&entry.path()) });
+        }
+    }
+    None
+}
+
+/// hidraw nodes a process has open
+fn open_hidraw(process_dir: &Path) -> Vec<PathBuf> {
+    let Ok(fds) = std::fs::read_dir(process_dir.join("fd")) else {
+        return Vec::new();
+    };
+    let mut hidraw: Vec<PathBuf> = fds
+        .flatten()
+        .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+        .filter(|target| target.starts_with("/dev") && target.to_string_lossy().contains("hidraw"))
+        .collect();
+    hidraw.sort();
+    hidraw.dedup();
+    hidraw
+}
+
+/// Diagnostics for a detected Solaar instance (empty if none)
+pub fn diagnostics(solaar: Option<&SolaarProcess>) -> Vec<Diagnostic> {
+    let Some(solaar) = solaar else {
+        return Vec::new();
+    };
+    let which = match solaar.pid {
+        Some(pid) => format!("Solaar (pid {})", pid),
+        None => "Solaar".to_string(),
+    };
+    let devices = if solaar.hidraw.is_empty() {
+        String::new()
+    } else {
+        let paths: Vec<_> = solaar.hidraw.iter().map(|p| p.display().to_string()).collect();
+        format!(" and holds {}", paths.join(", "))
+    };
+    vec![Diagnostic::new(
+        Severity::Warning,
+        "solaar-running",
+        format!(
+            "{}{} is running{}; its HID++ traffic interleaves with the daemon's and requests time out. \
+             Battery polling and keep-alive pings are paused while it runs, haptics may still miss. \
+             Quit Solaar (`solaar` tray menu → Quit) and remove ~/.config/autostart/solaar.desktop, \
+             or at least disable its rules for the gesture button so it does not divert it.",
+            which,
+            if solaar.pid.is_none() { " (on D-Bus)" } else { "" },
+            devices,
+        ),
+    )]
+}
+
+/// Whether Solaar owns its bus name
+async fn owns_bus_name(connection: &zbus::Connection) -> bool {
+    let Ok(proxy) = zbus::fdo::DBusProxy::new(connection).await else {
+        return false;
+    };
+    let Ok(name) = zbus::names::BusName::try_from(DBUS_NAME) else {
+        return false;
+    };
+    proxy.name_has_owner(name).await.unwrap_or(false)
+}
+
+/// Periodically check for Solaar and report/coordinate
+pub async fn start_solaar_monitor(connection: zbus::Connection) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let process = tokio::task::spawn_blocking(|| find_process(Path::new("/proc")))
+            .await
+            .unwrap_or(None);
+        let solaar = match process {
+            Some(process) => Some(process),
+            None if owns_bus_name(&connection).await => Some(SolaarProcess::default()),
+            None => None,
+        };
+
+        let running = solaar.is_some();
+        if RUNNING.swap(running, Ordering::Relaxed) != running {
+            if running {
+                tracing::info!("Solaar detected - pausing periodic HID++ requests");
+            } else {
+                tracing::info!("Solaar gone - resuming periodic HID++ requests");
+            }
+        }
+        diagnostics::report(SOURCE, diagnostics(solaar.as_ref()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solaar_cmdline() {
+        assert!(is_solaar_cmdline(b"solaar\0--window=hide\0"));
+        assert!(is_solaar_cmdline(b"/usr/bin/solaar\0"));
+        assert!(is_solaar_cmdline(b"/usr/bin/python3\0/usr/bin/solaar\0-w\0hide\0"));
+        assert!(is_solaar_cmdline(b"python3.12\0-s\0/usr/local/bin/solaar\0"));
+
+        assert!(!is_solaar_cmdline(b""));
+        assert!(!is_solaar_cmdline(b"python3\0/usr/bin/juhradial-settings\0"));
+        assert!(!is_solaar_cmdline(b"vim\0solaar\0"));
+        assert!(!is_solaar_cmdline(b"/usr/bin/solaar-cli\0"));
+    }
+
+    #[test]
+    fn test_find_process() {
+        let dir = tempfile::tempdir().unwrap();
+        for (pid, cmdline) in [("1", &b"/sbin/init\0"[..]), ("4242", b"python3\0/usr/bin/solaar\0"), ("self", b"solaar\0")] {
+            std::fs::create_dir(dir.path().join(pid)).unwrap();
+            std::fs::write(dir.path().join(pid).join("cmdline"), cmdline).unwrap();
+        }
+
+        let found = find_process(dir.path()).unwrap();
+        assert_eq!(found.pid, Some(4242));
+        assert!(found.hidraw.is_empty());
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        assert!(diagnostics(None).is_empty());
+
+        let solaar = SolaarProcess { pid: Some(4242), hidraw: vec![PathBuf::from("/dev/hidraw3")] };
+        let findings = diagnostics(Some(&solaar));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, "solaar-running");
+        assert!(findings[0].message.contains("pid 4242"));
+        assert!(findings[0].message.contains("/dev/hidraw3"));
+    }
+}
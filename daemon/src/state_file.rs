@@ -0,0 +1,59 @@
+//! Atomic writes for the daemon's state files
+//!
+//! Runtime state, usage statistics, the action history and the feature
+//! cache are written to a temporary file next to the target, synced and
+//! renamed over it, so a crash or power loss leaves either the old or the
+//! new contents, never a truncated file. The directory is synced as well so
+//! the rename itself is durable.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Temporary file `path` is written through (`<name>.tmp` in the same directory)
+fn tmp_path(path: &Path) -> io::Result<PathBuf> {
+    let mut name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "state file path has no file name"))?
+        .to_os_string();
+    name.push(".tmp");
+    Ok(path.with_file_name(name))
+}
+
+/// Replace the file at `path` with `contents`, creating its directory if needed
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+
+    let tmp = tmp_path(path)?;
+    let mut file = File::create(&tmp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)?;
+
+    // Make the rename itself durable
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("runtime.json");
+
+        write_atomic(&path, b"{\"a\": 1}").unwrap();
+        write_atomic(&path, b"{}").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"{}");
+        assert!(!tmp_path(&path).unwrap().exists());
+        assert!(write_atomic(Path::new("/"), b"").is_err());
+    }
+}
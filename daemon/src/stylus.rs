@@ -0,0 +1,323 @@
+//! Pen/stylus barrel-button trigger
+//!
+//! Opt-in (`stylus_trigger.enabled`). Pressing the upper barrel button
+//! (`BTN_STYLUS2`) while the pen hovers over or touches a tablet opens the
+//! radial menu at the cursor, turning it into a tool palette. Moving the pen
+//! selects a slice and releasing the button selects, like the gesture button.
+//!
+//! With `pressure_confirm` the menu stays open after the button is released
+//! and the selection is made by pressing the tip harder than
+//! `pressure_threshold`, so the pen never has to lift mid-stroke. Taking the
+//! pen out of range cancels: the offset returns to the center (nothing is
+//! selected) before the release is sent.
+//!
+//! The tablet's evdev node is read without grabbing it; the compositor keeps
+//! moving the pointer. Pen coordinates are scaled to screen pixels on the
+//! assumption that the tablet is mapped to the whole screen.
+//!
+//! Not available in portal mode (no /dev/input access).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+
+use crate::config::StylusTriggerConfig;
+use crate::evdev::{EvdevError, GestureEvent};
+
+/// How long to wait before looking for a tablet again
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Pen state after a complete frame (SYN_REPORT)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PenFrame {
+    /// Pen is in proximity of the tablet
+    pub in_range: bool,
+    /// Upper barrel button is held
+    pub barrel: bool,
+    /// Position in tablet units
+    pub position: (i32, i32),
+    /// Tip pressure (0.0 - 1.0)
+    pub pressure: f32,
+}
+
+/// What a pen frame means for the menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteEvent {
+    /// Barrel button pressed: open the menu
+    Open,
+    /// Pen moved while the menu is open (offset from where it opened, pixels)
+    Move { dx: i32, dy: i32 },
+    /// Select the hovered slice
+    Select { duration_ms: u64 },
+    /// Pen left the tablet: close without selecting
+    Cancel { duration_ms: u64 },
+}
+
+/// Turns pen frames into menu open/move/select
+#[derive(Debug)]
+pub struct PaletteDetector {
+    pressure_confirm: bool,
+    pressure_threshold: f32,
+    /// Screen pixels per tablet unit (x, y)
+    scale: (f32, f32),
+    /// Menu open since, and the pen position at that time
+    open: Option<(Instant, (i32, i32))>,
+    /// Previous frame
+    last: PenFrame,
+    /// Last offset reported
+    last_offset: (i32, i32),
+}
+
+impl PaletteDetector {
+    /// Detector for a tablet whose units map to `scale` screen pixels
+    pub fn new(config: &StylusTriggerConfig, scale: (f32, f32)) -> Self {
+        Self {
+            pressure_confirm: config.pressure_confirm,
+            pressure_threshold: config.pressure_threshold,
+            scale,
+            open: None,
+            last: PenFrame::default(),
+            last_offset: (0, 0),
+        }
+    }
+
+    /// Whether the menu is open
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Feed a complete frame
+    pub fn frame(&mut self, pen: PenFrame, now: Instant) -> Vec<PaletteEvent> {
+        let last = std::mem::replace(&mut self.last, pen);
+        let mut events = Vec::new();
+
+        let Some((since, origin)) = self.open else {
+            if pen.in_range && pen.barrel && !last.barrel {
+                self.open = Some((now, pen.position));
+                self.last_offset = (0, 0);
+                events.push(PaletteEvent::Open);
+            }
+            return events;
+        };
+        let duration_ms = now.duration_since(since).as_millis() as u64;
+
+        if !pen.in_range {
+            self.open = None;
+            events.push(PaletteEvent::Cancel { duration_ms });
+            return events;
+        }
+
+        let offset = (
+            ((pen.position.0 - origin.0) as f32 * self.scale.0).round() as i32,
+            ((pen.position.1 - origin.1) as f32 * self.scale.1).round() as i32,
+        );
+        if offset != std::mem::replace(&mut self.last_offset, offset) {
+            events.push(PaletteEvent::Move { dx: offset.0, dy: offset.1 });
+        }
+
+        let select = if self.pressure_confirm {
+            pen.pressure >= self.pressure_threshold && last.pressure < self.pressure_threshold
+        } else {
+            !pen.barrel
+        };
+        if select {
+            self.open = None;
+            events.push(PaletteEvent::Select { duration_ms });
+        }
+        events
+    }
+}
+
+/// Screen pixels per tablet unit for an axis spanning `min..=max`
+pub fn axis_scale(min: i32, max: i32, screen: i32) -> f32 {
+    let span = max.saturating_sub(min);
+    if span <= 0 {
+        return 1.0;
+    }
+    screen as f32 / span as f32
+}
+
+/// Find a tablet whose pen has an upper barrel button
+#[cfg(target_os = "linux")]
+pub fn find_stylus() -> Result<PathBuf, EvdevError> {
+    use evdev::{AbsoluteAxisCode, KeyCode};
+
+    let entries = std::fs::read_dir("/dev/input").map_err(EvdevError::IoError)?;
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("event")))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .find(|path| {
+            let Ok(device) = evdev::Device::open(path) else {
+                return false;
+            };
+            let pen = device
+                .supported_keys()
+                .is_some_and(|k| k.contains(KeyCode::BTN_TOOL_PEN) && k.contains(KeyCode::BTN_STYLUS2));
+            let absolute = device
+                .supported_absolute_axes()
+                .is_some_and(|a| a.contains(AbsoluteAxisCode::ABS_X) && a.contains(AbsoluteAxisCode::ABS_Y));
+            pen && absolute
+        })
+        .ok_or(EvdevError::DeviceNotFound)
+}
+
+/// Forward barrel-button palettes as gesture events
+pub async fn start_stylus_trigger(event_tx: mpsc::Sender<GestureEvent>, config: StylusTriggerConfig) {
+    loop {
+        match run_stylus(&event_tx, &config).await {
+            Ok(()) => return,
+            Err(EvdevError::DeviceNotFound) => tracing::debug!("No pen tablet with a barrel button found"),
+            Err(e) => tracing::warn!("Stylus trigger error: {}", e),
+        }
+        tokio::time::sleep(RETRY_INTERVAL).await;
+    }
+}
+
+#[cfg(target_os = "linux")]
+async fn run_stylus(event_tx: &mpsc::Sender<GestureEvent>, config: &StylusTriggerConfig) -> Result<(), EvdevError> {
+    use evdev::{AbsoluteAxisCode, EventType, KeyCode, SynchronizationCode};
+
+    let path = find_stylus()?;
+    let device = evdev::Device::open(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::PermissionDenied => EvdevError::PermissionDenied,
+        _ => EvdevError::IoError(e),
+    })?;
+
+    let screen = crate::screen_watcher::current();
+    let mut scale = (1.0, 1.0);
+    let mut max_pressure = 0;
+    if let Ok(axes) = device.get_absinfo() {
+        for (axis, info) in axes {
+            match axis {
+                AbsoluteAxisCode::ABS_X => scale.0 = axis_scale(info.minimum(), info.maximum(), screen.width),
+                AbsoluteAxisCode::ABS_Y => scale.1 = axis_scale(info.minimum(), info.maximum(), screen.height),
+                AbsoluteAxisCode::ABS_PRESSURE => max_pressure = info.maximum(),
+                _ => {}
+            }
+        }
+    }
+    if config.pressure_confirm && max_pressure <= 0 {
+        tracing::warn!("Pen reports no pressure - select by releasing the barrel button instead");
+    }
+    tracing::info!(path = %path.display(), name = device.name().unwrap_or("Unknown"), "Stylus trigger listening");
+
+    let mut config = config.clone();
+    config.pressure_confirm &= max_pressure > 0;
+    let mut detector = PaletteDetector::new(&config, scale);
+    let mut events = device.into_event_stream().map_err(EvdevError::IoError)?;
+    let mut pen = PenFrame::default();
+
+    loop {
+        let event = events.next_event().await.map_err(EvdevError::IoError)?;
+        match event.event_type() {
+            EventType::KEY if event.code() == KeyCode::BTN_TOOL_PEN.code() => pen.in_range = event.value() != 0,
+            EventType::KEY if event.code() == KeyCode::BTN_STYLUS2.code() => pen.barrel = event.value() != 0,
+            EventType::ABSOLUTE if event.code() == AbsoluteAxisCode::ABS_X.0 => pen.position.0 = event.value(),
+            EventType::ABSOLUTE if event.code() == AbsoluteAxisCode::ABS_Y.0 => pen.position.1 = event.value(),
+            EventType::ABSOLUTE if event.code() == AbsoluteAxisCode::ABS_PRESSURE.0 && max_pressure > 0 => {
+                pen.pressure = event.value() as f32 / max_pressure as f32;
+            }
+            EventType::SYNCHRONIZATION if event.code() == SynchronizationCode::SYN_REPORT.0 => {
+                for palette in detector.frame(pen, Instant::now()) {
+                    for gesture in gesture_events(palette) {
+                        if event_tx.send(gesture).await.is_err() {
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn run_stylus(_event_tx: &mpsc::Sender<GestureEvent>, _config: &StylusTriggerConfig) -> Result<(), EvdevError> {
+    Err(EvdevError::DeviceNotFound)
+}
+
+/// Gesture events for a palette event
+fn gesture_events(palette: PaletteEvent) -> Vec<GestureEvent> {
+    match palette {
+        PaletteEvent::Open => {
+            tracing::info!("Stylus barrel button pressed");
+            // Like the gesture button: the compositor may place the menu itself
+            if crate::compositor::backend().show_overlay_hint() {
+                return Vec::new();
+            }
+            let pos = crate::pointer_confinement::menu_position();
+            vec![GestureEvent::Pressed { x: pos.x, y: pos.y, menu: None }]
+        }
+        PaletteEvent::Move { dx, dy } => vec![GestureEvent::CursorMoved { x: dx, y: dy }],
+        PaletteEvent::Select { duration_ms } => vec![GestureEvent::Released { duration_ms }],
+        PaletteEvent::Cancel { duration_ms } => {
+            vec![GestureEvent::CursorMoved { x: 0, y: 0 }, GestureEvent::Released { duration_ms }]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pen(barrel: bool, position: (i32, i32), pressure: f32) -> PenFrame {
+        PenFrame { in_range: true, barrel, position, pressure }
+    }
+
+    #[test]
+    fn test_barrel_press_move_release() {
+        let mut palette = PaletteDetector::new(&StylusTriggerConfig::default(), (0.5, 0.5));
+        let t0 = Instant::now();
+        assert!(palette.frame(pen(false, (100, 100), 0.0), t0).is_empty());
+        assert_eq!(palette.frame(pen(true, (100, 100), 0.0), t0), vec![PaletteEvent::Open]);
+        assert_eq!(palette.frame(pen(true, (140, 80), 0.0), t0), vec![PaletteEvent::Move { dx: 20, dy: -10 }]);
+        assert!(palette.frame(pen(true, (140, 80), 0.0), t0).is_empty());
+        assert_eq!(
+            palette.frame(pen(false, (140, 80), 0.0), t0 + Duration::from_millis(250)),
+            vec![PaletteEvent::Select { duration_ms: 250 }]
+        );
+        assert!(!palette.is_open());
+    }
+
+    #[test]
+    fn test_pressure_confirm() {
+        let config = StylusTriggerConfig { pressure_confirm: true, ..Default::default() };
+        let mut palette = PaletteDetector::new(&config, (1.0, 1.0));
+        let t0 = Instant::now();
+        // Opened while the tip already touches: that press does not select
+        assert_eq!(palette.frame(pen(true, (0, 0), 0.5), t0), vec![PaletteEvent::Open]);
+        assert!(palette.frame(pen(false, (0, 0), 0.5), t0).is_empty());
+        assert!(palette.frame(pen(false, (0, 0), 0.1), t0).is_empty());
+        assert_eq!(
+            palette.frame(pen(false, (0, 30), 0.4), t0 + Duration::from_millis(900)),
+            vec![PaletteEvent::Move { dx: 0, dy: 30 }, PaletteEvent::Select { duration_ms: 900 }]
+        );
+    }
+
+    #[test]
+    fn test_leaving_range_cancels() {
+        let mut palette = PaletteDetector::new(&StylusTriggerConfig::default(), (1.0, 1.0));
+        let t0 = Instant::now();
+        palette.frame(pen(true, (0, 0), 0.0), t0);
+        let away = PenFrame { in_range: false, ..pen(true, (0, 0), 0.0) };
+        assert_eq!(palette.frame(away, t0), vec![PaletteEvent::Cancel { duration_ms: 0 }]);
+        assert!(!palette.is_open());
+        // Out of range the barrel button does nothing
+        assert!(palette.frame(PenFrame { barrel: true, ..away }, t0).is_empty());
+    }
+
+    #[test]
+    fn test_axis_scale() {
+        assert_eq!(axis_scale(0, 1000, 2000), 2.0);
+        assert_eq!(axis_scale(0, 0, 1920), 1.0);
+    }
+}
@@ -0,0 +1,168 @@
+//! Panic isolation and restart for daemon subsystem tasks
+//!
+//! Each long-running subsystem (hidraw, evdev, battery, event processing...)
+//! runs inside a supervisor. If the task panics the supervisor logs the
+//! panic, waits with exponential backoff and starts a fresh instance, so a
+//! bug in one subsystem does not take the whole daemon (and the menu) down.
+//! A task that returns normally is not restarted.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinHandle;
+
+/// First restart delay (milliseconds)
+pub const RESTART_INITIAL_BACKOFF_MS: u64 = 500;
+
+/// Upper bound for the restart delay (seconds)
+pub const RESTART_MAX_BACKOFF_SECS: u64 = 30;
+
+/// A task running this long is considered healthy; the backoff starts over (seconds)
+pub const RESTART_STABLE_AFTER_SECS: u64 = 60;
+
+/// Exponential restart backoff
+#[derive(Debug)]
+pub struct RestartBackoff {
+    /// First delay
+    initial: Duration,
+    /// Maximum delay
+    max: Duration,
+    /// Run time after which the delay resets
+    stable_after: Duration,
+    /// Delay for the next restart
+    next: Duration,
+}
+
+impl RestartBackoff {
+    /// Create a backoff with explicit bounds
+    pub fn new(initial: Duration, max: Duration, stable_after: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            stable_after,
+            next: initial,
+        }
+    }
+
+    /// Delay before restarting a task that crashed after running for `ran_for`
+    pub fn next_delay(&mut self, ran_for: Duration) -> Duration {
+        if ran_for >= self.stable_after {
+            self.next = self.initial;
+        }
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+}
+
+impl Default for RestartBackoff {
+    fn default() -> Self {
+        Self::new(
+            Duration::from_millis(RESTART_INITIAL_BACKOFF_MS),
+            Duration::from_secs(RESTART_MAX_BACKOFF_SECS),
+            Duration::from_secs(RESTART_STABLE_AFTER_SECS),
+        )
+    }
+}
+
+/// Best-effort panic message from a panic payload
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "non-string panic payload"
+    }
+}
+
+/// Spawn a task that is restarted whenever it panics
+///
+/// `factory` builds a fresh future for every (re)start. The returned handle
+/// completes only when the task returns normally or is cancelled.
+pub fn spawn_supervised<F, Fut>(name: &'static str, factory: F) -> JoinHandle<()>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    spawn_with_backoff(name, RestartBackoff::default(), factory)
+}
+
+fn spawn_with_backoff<F, Fut>(name: &'static str, mut backoff: RestartBackoff, mut factory: F) -> JoinHandle<()>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            let started = Instant::now();
+            match tokio::spawn(factory()).await {
+                Ok(()) => {
+                    tracing::debug!(task = name, "Supervised task finished");
+                    return;
+                }
+                Err(e) if e.is_panic() => {
+                    let payload = e.into_panic();
+                    let delay = backoff.next_delay(started.elapsed());
+                    tracing::error!(
+                        task = name,
+                        restart_in_ms = delay.as_millis() as u64,
+                        "Task panicked: {}",
+                        panic_message(payload.as_ref())
+                    );
+                    tokio::time::sleep(delay).await;
+                    tracing::info!(task = name, "Restarting task");
+                }
+                Err(_) => {
+                    tracing::debug!(task = name, "Supervised task cancelled");
+                    return;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_backoff_grows_and_resets() {
+        let mut backoff = RestartBackoff::new(
+            Duration::from_millis(100),
+            Duration::from_millis(350),
+            Duration::from_secs(10),
+        );
+        let quick = Duration::from_millis(5);
+
+        assert_eq!(backoff.next_delay(quick), Duration::from_millis(100));
+        assert_eq!(backoff.next_delay(quick), Duration::from_millis(200));
+        assert_eq!(backoff.next_delay(quick), Duration::from_millis(350));
+        assert_eq!(backoff.next_delay(quick), Duration::from_millis(350));
+        // Ran long enough to count as healthy
+        assert_eq!(backoff.next_delay(Duration::from_secs(11)), Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_panicking_task_is_restarted() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        let backoff = RestartBackoff::new(Duration::from_millis(1), Duration::from_millis(2), Duration::from_secs(10));
+
+        let handle = spawn_with_backoff("test", backoff, move || {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                    panic!("boom");
+                }
+            }
+        });
+
+        handle.await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,119 @@
+//! Keep the gesture button's own function for quick taps
+//!
+//! Once the gesture button is diverted (HID++, logid) or grabbed
+//! ([`crate::menu_grab`]), applications no longer see it, so the back
+//! navigation it used to do is lost. With `tap_passthrough.enabled`, a press
+//! that opened the radial menu, was released within `max_tap_ms` and never
+//! left the dead zone cancels the menu (`MenuCancelled` instead of
+//! `HideMenu`, so the overlay does not enter tap-to-toggle) and clicks the
+//! configured key on a small uinput device instead.
+//!
+//! With multi-press gestures enabled, the first tap of a double press is
+//! passed through as well; the second press then opens its binding.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use crate::config::TapPassthroughConfig;
+
+/// Name of the virtual device the taps come from
+pub const TAP_DEVICE_NAME: &str = "JuhRadial MX Tap Passthrough";
+
+/// Highest key code accepted in `key_code` (KEY_MAX)
+pub const MAX_KEY_CODE: u16 = 0x2FF;
+
+/// Follows one gesture button press to decide whether it was a plain tap
+#[derive(Debug, Default)]
+pub struct TapTracker {
+    /// The press opened the regular radial menu
+    armed: bool,
+    /// The cursor left the dead zone (a slice may have been selected)
+    moved: bool,
+}
+
+impl TapTracker {
+    /// The press showed the radial menu
+    pub fn on_menu_shown(&mut self) {
+        self.armed = true;
+        self.moved = false;
+    }
+
+    /// Cursor offset from the menu center while the menu is open
+    pub fn on_cursor_moved(&mut self, x: i32, y: i32, dead_zone_radius: u32) {
+        let distance_sq = (x as i64).pow(2) + (y as i64).pow(2);
+        if distance_sq > (dead_zone_radius as i64).pow(2) {
+            self.moved = true;
+        }
+    }
+
+    /// Whether the release ends a tap that should be passed through
+    pub fn on_release(&mut self, duration_ms: u64, config: &TapPassthroughConfig) -> bool {
+        // 0 ms releases are synthetic (input source switch), not taps
+        let tap = std::mem::take(&mut self.armed) && !self.moved && (1..=config.max_tap_ms).contains(&duration_ms);
+        config.enabled && tap
+    }
+}
+
+/// Virtual device clicking the passthrough key
+pub struct TapInjector {
+    device: evdev::uinput::VirtualDevice,
+    key_code: u16,
+}
+
+impl TapInjector {
+    /// Create the virtual device for `key_code`
+    ///
+    /// The compositor needs a moment to pick up a new device, so create it
+    /// when the button is pressed rather than on release.
+    pub fn new(key_code: u16) -> std::io::Result<Self> {
+        let mut keys = evdev::AttributeSet::<evdev::KeyCode>::new();
+        keys.insert(evdev::KeyCode(key_code));
+        let device = evdev::uinput::VirtualDevice::builder()?
+            .name(TAP_DEVICE_NAME)
+            .with_keys(&keys)?
+            .build()?;
+        tracing::debug!(key_code, "Tap passthrough device created");
+        Ok(Self { device, key_code })
+    }
+
+    /// Key code this device clicks
+    pub fn key_code(&self) -> u16 {
+        self.key_code
+    }
+
+    /// Press and release the key
+    pub fn click(&mut self) -> std::io::Result<()> {
+        let key = evdev::KeyCode(self.key_code);
+        self.device.emit(&[*evdev::KeyEvent::new(key, 1)])?;
+        self.device.emit(&[*evdev::KeyEvent::new(key, 0)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_still_quick_taps_pass_through() {
+        let config = TapPassthroughConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let mut tracker = TapTracker::default();
+
+        tracker.on_menu_shown();
+        tracker.on_cursor_moved(10, 10, 40);
+        assert!(tracker.on_release(120, &config));
+
+        // Too long, moved out of the dead zone, or no menu shown
+        tracker.on_menu_shown();
+        assert!(!tracker.on_release(config.max_tap_ms + 1, &config));
+        tracker.on_menu_shown();
+        tracker.on_cursor_moved(60, 0, 40);
+        assert!(!tracker.on_release(120, &config));
+        assert!(!tracker.on_release(120, &config));
+
+        // Disabled
+        tracker.on_menu_shown();
+        assert!(!tracker.on_release(120, &TapPassthroughConfig::default()));
+    }
+}
@@ -0,0 +1,219 @@
+//! End-to-end test support: a scripted virtual mouse and a private bus
+//!
+//! [`VirtualMouse`] creates a uinput device that looks like a Logitech
+//! mouse and plays scripted [`Step`]s (gesture button, motion, wheel), so
+//! the real evdev path can be exercised without hardware. Pin the
+//! [`crate::evdev::EvdevHandler`] to [`VirtualMouse::path`]; the product ID
+//! is not an MX Master 4, so a running daemon never picks the device up.
+//!
+//! [`SessionBus`] starts a throwaway `dbus-daemon`, so D-Bus signals can be
+//! checked without touching the desktop session (or a daemon running there).
+//!
+//! Only built for the crate's own tests and with the `test-support`
+//! feature. Tests using this need write access to /dev/uinput and are
+//! `#[ignore]`d; run them on a developer machine with
+//! `cargo test --features test-support -- --ignored`.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use evdev::uinput::VirtualDevice;
+use evdev::{AttributeSet, BusType, InputEvent, InputId, KeyCode, KeyEvent, RelativeAxisCode, RelativeAxisEvent};
+
+use crate::evdev::{GESTURE_BUTTON_CODES, LOGITECH_VENDOR_ID};
+
+/// Name of the scripted virtual mouse
+pub const VIRTUAL_MOUSE_NAME: &str = "JuhRadial MX Test Mouse";
+
+/// How long to wait for the device node to appear
+const DEVICE_NODE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// One step of a scripted input sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Press the gesture button (BTN_BACK)
+    Press,
+    /// Release the gesture button
+    Release,
+    /// Relative pointer motion
+    Move(i32, i32),
+    /// Wheel notches (positive = up)
+    Scroll(i32),
+    /// Pause between steps
+    Wait(Duration),
+}
+
+/// uinput mouse with the MX Master 4's buttons and axes
+pub struct VirtualMouse {
+    device: VirtualDevice,
+    path: PathBuf,
+}
+
+impl VirtualMouse {
+    /// Create the device and wait for its /dev/input node
+    pub fn new() -> io::Result<Self> {
+        let mut keys = AttributeSet::<KeyCode>::new();
+        for code in [KeyCode::BTN_LEFT, KeyCode::BTN_RIGHT, KeyCode::BTN_MIDDLE, KeyCode::BTN_SIDE, KeyCode::BTN_EXTRA] {
+            keys.insert(code);
+        }
+        keys.insert(KeyCode(GESTURE_BUTTON_CODES[0]));
+
+        let mut axes = AttributeSet::<RelativeAxisCode>::new();
+        for axis in [RelativeAxisCode::REL_X, RelativeAxisCode::REL_Y, RelativeAxisCode::REL_WHEEL] {
+            axes.insert(axis);
+        }
+
+        let mut device = VirtualDevice::builder()?
+            .name(VIRTUAL_MOUSE_NAME)
+            .input_id(InputId::new(BusType::BUS_USB, LOGITECH_VENDOR_ID, 0, 1))
+            .with_keys(&keys)?
+            .with_relative_axes(&axes)?
+            .build()?;
+        let path = wait_for_node(&mut device)?;
+        Ok(Self { device, path })
+    }
+
+    /// Device node to open
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Emit one frame of events
+    pub fn emit(&mut self, events: &[InputEvent]) -> io::Result<()> {
+        self.device.emit(events)
+    }
+
+    /// Play a script, sleeping for its `Wait` steps
+    pub async fn play(&mut self, script: &[Step]) -> io::Result<()> {
+        let gesture = KeyCode(GESTURE_BUTTON_CODES[0]);
+        for step in script {
+            match *step {
+                Step::Press => self.emit(&[*KeyEvent::new(gesture, 1)])?,
+                Step::Release => self.emit(&[*KeyEvent::new(gesture, 0)])?,
+                Step::Move(dx, dy) => self.emit(&[
+                    *RelativeAxisEvent::new(RelativeAxisCode::REL_X, dx),
+                    *RelativeAxisEvent::new(RelativeAxisCode::REL_Y, dy),
+                ])?,
+                Step::Scroll(notches) => self.emit(&[*RelativeAxisEvent::new(RelativeAxisCode::REL_WHEEL, notches)])?,
+                Step::Wait(duration) => tokio::time::sleep(duration).await,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Find the event node udev creates for a new virtual device
+fn wait_for_node(device: &mut VirtualDevice) -> io::Result<PathBuf> {
+    let deadline = std::time::Instant::now() + DEVICE_NODE_TIMEOUT;
+    loop {
+        let node = device
+            .enumerate_dev_nodes_blocking()?
+            .filter_map(Result::ok)
+            .find(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("event")));
+        match node {
+            Some(path) if evdev::Device::open(&path).is_ok() => return Ok(path),
+            _ if std::time::Instant::now() >= deadline => {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "virtual mouse node did not appear"));
+            }
+            _ => std::thread::sleep(Duration::from_millis(20)),
+        }
+    }
+}
+
+/// Private `dbus-daemon` for the duration of a test
+pub struct SessionBus {
+    child: Child,
+    address: String,
+}
+
+impl SessionBus {
+    /// Start a bus with the session configuration
+    pub fn start() -> io::Result<Self> {
+        let mut child = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address=1"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let mut address = String::new();
+        let stdout = child.stdout.take().ok_or_else(|| io::Error::other("dbus-daemon stdout missing"))?;
+        BufReader::new(stdout).read_line(&mut address)?;
+        let address = address.trim().to_string();
+        if address.is_empty() {
+            let _ = child.kill();
+            return Err(io::Error::other("dbus-daemon printed no address"));
+        }
+        Ok(Self { child, address })
+    }
+
+    /// Bus address (`unix:path=...`)
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Connection builder for the bus, e.g. for [`crate::dbus::serve_dbus_service_on`]
+    pub fn builder(&self) -> zbus::Result<zbus::connection::Builder<'static>> {
+        zbus::connection::Builder::address(self.address.as_str())
+    }
+
+    /// Open a client connection to the bus
+    pub async fn connect(&self) -> zbus::Result<zbus::Connection> {
+        self.builder()?.build().await
+    }
+}
+
+impl Drop for SessionBus {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evdev::{EvdevHandler, GestureEvent};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    #[ignore] // Needs /dev/uinput - run explicitly with `cargo test -- --ignored`
+    async fn test_scripted_gesture_reaches_handler() {
+        let mut mouse = VirtualMouse::new().expect("uinput access");
+        let (tx, mut rx) = mpsc::channel(32);
+        let mut handler = EvdevHandler::new(tx);
+        handler.pin_device(mouse.path().to_path_buf());
+        let listener = tokio::spawn(async move { handler.start().await });
+
+        mouse
+            .play(&[
+                Step::Wait(Duration::from_millis(200)),
+                Step::Press,
+                Step::Move(30, 0),
+                Step::Move(0, 40),
+                Step::Scroll(-1),
+                Step::Wait(Duration::from_millis(50)),
+                Step::Release,
+            ])
+            .await
+            .unwrap();
+
+        let mut events = Vec::new();
+        while let Ok(Some(event)) = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await {
+            let released = matches!(event, GestureEvent::Released { .. });
+            events.push(event);
+            if released {
+                break;
+            }
+        }
+        listener.abort();
+
+        // Pressed is skipped when the compositor opens the menu itself (KWin)
+        assert!(events.contains(&GestureEvent::CursorMoved { x: 30, y: 40 }), "{:?}", events);
+        assert!(events.contains(&GestureEvent::Scrolled { delta: 1 }), "{:?}", events);
+        assert!(matches!(events.last(), Some(GestureEvent::Released { duration_ms }) if *duration_ms >= 50));
+    }
+}
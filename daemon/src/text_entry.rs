@@ -0,0 +1,196 @@
+//! Text entry slices: ask for a query, then run an action with it
+//!
+//! A `text_entry` slice puts the daemon into capture mode: it remembers the
+//! slice's templated action and emits `TextEntryRequested(prompt)`. The
+//! overlay, which has keyboard focus while it asks, hands the typed text
+//! back with `SubmitTextEntry` (or gives up with `CancelTextEntry`). The
+//! template's `{query}` is replaced by the text (shell-quoted in `command`
+//! actions) and `{query_url}` by its percent-encoded form. `http_request`
+//! templates get the text only after their `{secret:name}` placeholders are
+//! expanded, so typing `{secret:...}` cannot pull a secret into the request.
+//! For example
+//!
+//! ```json
+//! {"type": "text_entry", "value": {"prompt": "Search the web",
+//!   "action": {"type": "command", "value": "xdg-open https://duckduckgo.com/?q={query_url}"}}}
+//! ```
+//!
+//! Only one entry is pending at a time; a new request replaces it and an
+//! unanswered one expires after [`TEXT_ENTRY_TIMEOUT`].
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Notify;
+
+use crate::actions::{Action, ActionType, TextEntry};
+use crate::dbus::{DBUS_INTERFACE, DBUS_PATH};
+
+/// How long a requested entry waits for the overlay
+pub const TEXT_ENTRY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Longest query passed to the action (characters)
+pub const MAX_QUERY_CHARS: usize = 1024;
+
+/// Entry waiting for its text
+struct Pending {
+    prompt: String,
+    template: Action,
+    since: Instant,
+}
+
+static PENDING: Mutex<Option<Pending>> = Mutex::new(None);
+
+/// Wakes the signal task when an entry is requested
+fn requested() -> &'static Notify {
+    static REQUESTED: OnceLock<Notify> = OnceLock::new();
+    REQUESTED.get_or_init(Notify::new)
+}
+
+/// Enter capture mode for a `text_entry` slice
+pub fn begin(entry: &TextEntry) {
+    tracing::info!(prompt = %entry.prompt, "Text entry requested");
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = Some(Pending {
+            prompt: entry.prompt.clone(),
+            template: (*entry.action).clone(),
+            since: Instant::now(),
+        });
+    }
+    requested().notify_one();
+}
+
+/// Leave capture mode; returns the action to run with the query applied
+///
+/// None if no entry is pending (never requested, cancelled or expired).
+pub fn submit(text: &str) -> Option<Action> {
+    let pending = take_pending()?;
+    let query: String = text.chars().filter(|c| !c.is_control()).take(MAX_QUERY_CHARS).collect();
+    Some(expand(&pending.template, &query))
+}
+
+/// Leave capture mode without running anything; false if nothing was pending
+pub fn cancel() -> bool {
+    take_pending().is_some()
+}
+
+fn take_pending() -> Option<Pending> {
+    PENDING.lock().ok()?.take().filter(|p| p.since.elapsed() < TEXT_ENTRY_TIMEOUT)
+}
+
+/// Emit `TextEntryRequested` whenever a slice asks for text
+pub async fn start_text_entry_signals(connection: zbus::Connection) {
+    loop {
+        requested().notified().await;
+        let prompt = match PENDING.lock() {
+            Ok(pending) => pending.as_ref().map(|p| p.prompt.clone()),
+            Err(_) => None,
+        };
+        let Some(prompt) = prompt else {
+            continue;
+        };
+        if let Err(e) = connection
+            .emit_signal(None::<&str>, DBUS_PATH, DBUS_INTERFACE, "TextEntryRequested", &(prompt.as_str(),))
+            .await
+        {
+            tracing::warn!("Failed to emit TextEntryRequested: {}", e);
+        }
+    }
+}
+
+/// Replace `{query_url}` and `{query}` (unquoted) in `s`
+pub fn fill_query(s: &str, query: &str) -> String {
+    s.replace("{query_url}", &percent_encode(query)).replace("{query}", query)
+}
+
+/// Fill `{query}` and `{query_url}` into the template action
+pub fn expand(template: &Action, query: &str) -> Action {
+    let url = percent_encode(query);
+    let fill = |s: &str, quoted: &str| s.replace("{query_url}", &url).replace("{query}", quoted);
+
+    let action_type = match &template.action_type {
+        ActionType::Command(cmd) => ActionType::Command(fill(cmd, &shell_quote(query))),
+        ActionType::KWin(script) => ActionType::KWin(fill(script, query)),
+        ActionType::HyprlandDispatch(command) => ActionType::HyprlandDispatch(fill(command, query)),
+        ActionType::DBus(call) => {
+            let mut call = call.clone();
+            for arg in &mut call.args {
+                if let serde_json::Value::String(s) = arg {
+                    *s = fill(s, query);
+                }
+            }
+            ActionType::DBus(call)
+        }
+        // Filled in by `http_request::curl_config`, after the secrets
+        ActionType::HttpRequest(request) => ActionType::HttpRequest(crate::http_request::HttpRequest {
+            query: Some(query.to_string()),
+            ..request.clone()
+        }),
+        other => other.clone(),
+    };
+    Action {
+        action_type,
+        label: template.label.as_deref().map(|l| fill(l, query)),
+        ..template.clone()
+    }
+}
+
+/// Quote for `sh -c` so the query is always a single literal word
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// RFC 3986 percent-encoding (unreserved characters kept)
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submit_fills_the_template() {
+        let entry: TextEntry = serde_json::from_str(
+            r#"{"prompt": "Search", "action": {"type": "command", "value": "open {query} https://x.org/?q={query_url}"}}"#,
+        )
+        .unwrap();
+
+        assert!(submit("nothing pending").is_none());
+        begin(&entry);
+        let action = submit("it's a \"test\"; rm -rf ~").unwrap();
+        assert_eq!(
+            action.action_type,
+            ActionType::Command(
+                r#"open 'it'\''s a "test"; rm -rf ~' https://x.org/?q=it%27s%20a%20%22test%22%3B%20rm%20-rf%20~"#
+                    .to_string()
+            )
+        );
+
+        // Requests get the query after their secrets are filled in
+        let search: Action = serde_json::from_str(
+            r#"{"type": "http_request", "value": {"url": "https://x.org/?q={query_url}"}}"#,
+        )
+        .unwrap();
+        let ActionType::HttpRequest(request) = expand(&search, "{secret:ha}").action_type else {
+            panic!("not an http_request");
+        };
+        assert_eq!(request.url, "https://x.org/?q={query_url}");
+        assert_eq!(request.query.as_deref(), Some("{secret:ha}"));
+
+        // One submit per request
+        assert!(submit("again").is_none());
+        begin(&entry);
+        assert!(cancel());
+        assert!(!cancel());
+    }
+}
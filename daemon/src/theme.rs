@@ -5,22 +5,38 @@
 //! Supports JSON themes with validation and directory scanning.
 //! Themes are loaded from:
 //! - System: `/usr/share/juhradial/themes/`
+//! - User data: `~/.local/share/juhradial/themes/` (downloaded/installed themes)
 //! - User: `~/.config/juhradial/themes/` (XDG compliant)
+//!
+//! ## Palette
+//! A theme may name its colors once in `palette` and refer to them as
+//! `$name` from `colors` and `overrides.sliceColors` (palette entries may
+//! refer to each other too). References are resolved when the theme is
+//! parsed; one that names no palette entry fails validation.
+//!
+//! ```json
+//! "palette": {"green": "#00ff00", "dim": "#009900"},
+//! "colors": {"text": "$green", "accent": "$green", "textSecondary": "$dim", ...}
+//! ```
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 /// System themes directory
 const SYSTEM_THEMES_DIR: &str = "/usr/share/juhradial/themes";
 
-/// User themes directory name (under XDG_CONFIG_HOME or ~/.config/)
+/// User themes directory name (under XDG_CONFIG_HOME or ~/.config/, and
+/// under XDG_DATA_HOME or ~/.local/share/)
 const USER_THEMES_DIR_NAME: &str = "juhradial/themes";
 
 /// Theme configuration filename
 const THEME_FILENAME: &str = "theme.json";
 
+/// Longest chain of palette entries referring to each other
+const MAX_PALETTE_DEPTH: usize = 8;
+
 /// Theme configuration (Story 4.1: Task 2.3 - matches UX Spec Section 4.2)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Theme {
@@ -40,6 +56,10 @@ pub struct Theme {
     #[serde(default)]
     pub author: String,
 
+    /// Named colors referenced as `$name` from `colors` and overrides
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub palette: BTreeMap<String, String>,
+
     /// Color palette (11 colors from UX spec)
     pub colors: ThemeColors,
 
@@ -103,6 +123,25 @@ pub struct ThemeColors {
     pub error: String,
 }
 
+impl ThemeColors {
+    /// All colors by name
+    fn fields_mut(&mut self) -> [(&'static str, &mut String); 11] {
+        [
+            ("base", &mut self.base),
+            ("surface", &mut self.surface),
+            ("text", &mut self.text),
+            ("text_secondary", &mut self.text_secondary),
+            ("accent", &mut self.accent),
+            ("accent_secondary", &mut self.accent_secondary),
+            ("border", &mut self.border),
+            ("shadow", &mut self.shadow),
+            ("success", &mut self.success),
+            ("warning", &mut self.warning),
+            ("error", &mut self.error),
+        ]
+    }
+}
+
 fn default_text_secondary() -> String {
     "#bac2de".to_string()
 }
@@ -178,6 +217,22 @@ pub struct AnimationSettings {
     /// Idle effect type: "none", "matrix-rain", "particles"
     #[serde(default = "default_idle_effect")]
     pub idle_effect: String,
+
+    /// Menu appear duration (ms, default 30)
+    #[serde(default = "default_appear_ms")]
+    pub appear_ms: u16,
+
+    /// Menu dismiss duration (ms, default 50)
+    #[serde(default = "default_dismiss_ms")]
+    pub dismiss_ms: u16,
+
+    /// Slice highlight in duration (ms, default 80)
+    #[serde(default = "default_highlight_in_ms")]
+    pub highlight_in_ms: u16,
+
+    /// Slice highlight out duration (ms, default 60)
+    #[serde(default = "default_highlight_out_ms")]
+    pub highlight_out_ms: u16,
 }
 
 fn default_glow_intensity() -> f32 {
@@ -186,6 +241,18 @@ fn default_glow_intensity() -> f32 {
 fn default_idle_effect() -> String {
     "none".to_string()
 }
+fn default_appear_ms() -> u16 {
+    30
+}
+fn default_dismiss_ms() -> u16 {
+    50
+}
+fn default_highlight_in_ms() -> u16 {
+    80
+}
+fn default_highlight_out_ms() -> u16 {
+    60
+}
 
 /// Theme overrides for custom configurations
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -271,6 +338,7 @@ impl Theme {
             display_name: "Catppuccin Mocha".to_string(),
             version: "1.0".to_string(),
             author: "JuhRadial Team".to_string(),
+            palette: BTreeMap::new(),
             colors: ThemeColors {
                 base: "#1e1e2e".to_string(),
                 surface: "#313244".to_string(),
@@ -295,6 +363,10 @@ impl Theme {
                 glow_intensity: 1.0,
                 enable_particles: false,
                 idle_effect: "none".to_string(),
+                appear_ms: default_appear_ms(),
+                dismiss_ms: default_dismiss_ms(),
+                highlight_in_ms: default_highlight_in_ms(),
+                highlight_out_ms: default_highlight_out_ms(),
             },
             overrides: None,
         }
@@ -303,29 +375,18 @@ impl Theme {
     /// Get effective animation timings based on accessibility settings (Story 4.6: Task 3.1)
     ///
     /// Returns 0ms for all timings when reduced motion is active,
-    /// otherwise returns the theme's configured timings.
+    /// otherwise returns the theme's configured timings. See
+    /// [`crate::accessibility::EffectiveAnimationTimings::compute`] for the
+    /// user's speed multiplier.
     pub fn get_effective_animation_timings(
         &self,
         reduce_motion: bool,
     ) -> crate::accessibility::EffectiveAnimationTimings {
-        use crate::accessibility::EffectiveAnimationTimings;
+        use crate::accessibility::{AccessibilitySettings, EffectiveAnimationTimings};
 
-        if reduce_motion {
-            // Story 4.6: Task 3.2 - Return 0ms for all timings
-            EffectiveAnimationTimings::reduced_motion()
-        } else {
-            // Use theme's animation settings
-            EffectiveAnimationTimings {
-                appear_ms: 30, // UX spec default
-                dismiss_ms: 50,
-                highlight_in_ms: 80,
-                highlight_out_ms: 60,
-                icon_scale_enabled: true,
-                // Task 3.3: Idle effects from theme
-                idle_effects_enabled: self.animation.idle_effect != "none"
-                    || self.animation.enable_particles,
-            }
-        }
+        let mut settings = AccessibilitySettings::default();
+        settings.reduced_motion_override = Some(reduce_motion);
+        EffectiveAnimationTimings::compute(&self.animation, &settings)
     }
 
     /// Get effective colors with high contrast adjustments (Story 4.5: Task 1.2, 1.3)
@@ -403,6 +464,7 @@ impl Theme {
     pub fn from_json(json: &str) -> Result<Self, ThemeError> {
         let mut theme: Theme =
             serde_json::from_str(json).map_err(ThemeError::ParseError)?;
+        theme.resolve_palette();
 
         // Set display_name from name if not provided
         if theme.display_name.is_empty() {
@@ -412,6 +474,20 @@ impl Theme {
         Ok(theme)
     }
 
+    /// Replace `$name` references with palette colors
+    ///
+    /// Unresolvable references are left in place for
+    /// [`Theme::validate_and_clamp`] to report.
+    pub fn resolve_palette(&mut self) {
+        let palette = &self.palette;
+        let slice_colors = self.overrides.iter_mut().flat_map(|o| o.slice_colors.iter_mut().flatten());
+        for value in self.colors.fields_mut().into_iter().map(|(_, v)| v).chain(slice_colors) {
+            if let Some(color) = resolve_reference(palette, value) {
+                *value = color;
+            }
+        }
+    }
+
     /// Load theme from a JSON file (Story 4.1: Task 2.1, 2.2)
     pub fn load_from_path(path: &Path) -> Result<Self, ThemeError> {
         // Read file content
@@ -547,7 +623,9 @@ impl Theme {
         ];
 
         for (name, value) in color_fields {
-            if !is_valid_hex_color(value) {
+            if value.starts_with('$') {
+                result.add_error(format!("Unresolved palette reference for {}: '{}'", name, value));
+            } else if !is_valid_hex_color(value) {
                 result.add_error(format!(
                     "Invalid hex color for {}: '{}' (expected #RRGGBB)",
                     name, value
@@ -555,12 +633,51 @@ impl Theme {
             }
         }
 
+        let slice_colors = self.overrides.iter().flat_map(|o| o.slice_colors.iter().flatten());
+        for (i, value) in slice_colors.enumerate() {
+            if value.starts_with('$') {
+                result.add_error(format!("Unresolved palette reference for slice color {}: '{}'", i, value));
+            }
+        }
+
         result
     }
 }
 
+/// Follow a `$name` reference through the palette (None if not a reference or unresolvable)
+fn resolve_reference(palette: &BTreeMap<String, String>, value: &str) -> Option<String> {
+    let mut name = value.strip_prefix('$')?;
+    for _ in 0..MAX_PALETTE_DEPTH {
+        let color = palette.get(name)?;
+        match color.strip_prefix('$') {
+            Some(next) => name = next,
+            None => return Some(color.clone()),
+        }
+    }
+    None
+}
+
+/// Load one theme by directory name: user, user data, then system themes,
+/// then bundled
+///
+/// Cheaper than [`ThemeManager::load_all`] when only the configured theme
+/// is needed. Invalid theme files are skipped.
+pub fn find_theme(name: &str) -> Option<Theme> {
+    if !name.is_empty() && !name.contains('/') && name != ".." {
+        for dir in [get_user_themes_dir(), get_user_data_themes_dir(), get_system_themes_dir()] {
+            let Ok(mut theme) = Theme::load_from_path(&dir.join(name).join(THEME_FILENAME)) else {
+                continue;
+            };
+            if theme.validate_and_clamp().is_valid() {
+                return Some(theme);
+            }
+        }
+    }
+    crate::bundled_themes::get_bundled_theme(name)
+}
+
 /// Check if a string is a valid hex color (#RRGGBB or #RGB)
-fn is_valid_hex_color(color: &str) -> bool {
+pub(crate) fn is_valid_hex_color(color: &str) -> bool {
     if !color.starts_with('#') {
         return false;
     }
@@ -638,7 +755,8 @@ impl ThemeManager {
     /// Loading order (later overrides earlier):
     /// 1. Bundled themes (always available)
     /// 2. System themes (/usr/share/juhradial/themes/)
-    /// 3. User themes (~/.config/juhradial/themes/)
+    /// 3. User data themes (~/.local/share/juhradial/themes/)
+    /// 4. User themes (~/.config/juhradial/themes/)
     pub fn load_all() -> Result<Self, ThemeError> {
         let mut themes = HashMap::new();
 
@@ -706,9 +824,11 @@ impl ThemeManager {
             }
         }
 
-        // Step 3: Load user themes (override system and bundled)
-        let user_dir = get_user_themes_dir();
-        if user_dir.exists() {
+        // Step 3: Load user data, then user themes (override system and bundled)
+        for user_dir in [get_user_data_themes_dir(), get_user_themes_dir()] {
+            if !user_dir.exists() {
+                continue;
+            }
             for theme_path in scan_themes_directory(&user_dir) {
                 match Theme::load_from_path(&theme_path) {
                     Ok(mut theme) => {
@@ -891,6 +1011,21 @@ pub fn get_user_themes_dir() -> PathBuf {
     PathBuf::from(".config").join(USER_THEMES_DIR_NAME)
 }
 
+/// Get user data themes directory path, where installed themes go (XDG compliant)
+pub fn get_user_data_themes_dir() -> PathBuf {
+    if let Ok(xdg_data) = std::env::var("XDG_DATA_HOME") {
+        return PathBuf::from(xdg_data).join(USER_THEMES_DIR_NAME);
+    }
+
+    if let Some(home) = std::env::var_os("HOME") {
+        return PathBuf::from(home)
+            .join(".local/share")
+            .join(USER_THEMES_DIR_NAME);
+    }
+
+    PathBuf::from(".local/share").join(USER_THEMES_DIR_NAME)
+}
+
 /// Scan a directory for theme.json files (Story 4.1: Task 1.4)
 ///
 /// Returns paths to all theme.json files found in subdirectories.
@@ -1212,6 +1347,31 @@ mod tests {
         assert!(!result.is_valid());
     }
 
+    #[test]
+    fn test_palette_references() {
+        let mut theme: Theme = serde_json::from_value(serde_json::json!({
+            "name": "palette",
+            "palette": {"pink": "#ff0080", "accent": "$pink", "loop": "$loop"},
+            "colors": {
+                "base": "#000000", "surface": "#111111", "text": "$pink",
+                "accent": "$accent", "border": "$missing", "error": "$loop"
+            },
+            "glassmorphism": {},
+            "animation": {},
+            "overrides": {"sliceColors": ["$pink", "#00ff00"]}
+        }))
+        .unwrap();
+        theme.resolve_palette();
+
+        assert_eq!(theme.colors.text, "#ff0080");
+        assert_eq!(theme.colors.accent, "#ff0080");
+        assert_eq!(theme.overrides.as_ref().unwrap().slice_colors.as_ref().unwrap()[0], "#ff0080");
+        let errors = theme.validate_and_clamp().errors;
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("border") && errors[0].contains("$missing"));
+        assert!(errors[1].contains("error") && errors[1].contains("$loop"));
+    }
+
     #[test]
     fn test_theme_error_display() {
         let err = ThemeError::NotFound("test".to_string());
@@ -0,0 +1,169 @@
+//! Theme preview thumbnails
+//!
+//! Applies a theme's palette to a small radial-menu mock and renders it as
+//! SVG, for theme pickers: the Settings API's `GetThemePreview` and
+//! `juhradialctl themes --preview`. The mock shows the menu body (`base`
+//! at the theme's background opacity), slices (`surface`, `border`), the
+//! highlighted north slice (`accent`), slice icons (`text`) and the center
+//! (`accentSecondary`).
+//!
+//! Previews are rendered on demand and cached under
+//! `$XDG_CACHE_HOME/juhradial/theme-previews/`. The file name includes a
+//! hash of the theme, so an edited theme gets a fresh preview.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write as _;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+use crate::slice_geometry::SliceGeometry;
+use crate::theme::{is_valid_hex_color, Theme, ThemeError};
+
+/// Cache directory name (under XDG_CACHE_HOME)
+const CACHE_DIR: &str = "juhradial/theme-previews";
+
+/// Preview edge length when the caller asks for none (pixels)
+pub const DEFAULT_PREVIEW_SIZE: u32 = 96;
+
+/// Smallest and largest preview edge length (pixels)
+const MIN_PREVIEW_SIZE: u32 = 16;
+const MAX_PREVIEW_SIZE: u32 = 512;
+
+/// Center radius relative to the outer radius
+const INNER_RATIO: f32 = 0.36;
+
+/// Clamp a requested preview size; 0 means [`DEFAULT_PREVIEW_SIZE`]
+pub fn preview_size(size: u32) -> u32 {
+    match size {
+        0 => DEFAULT_PREVIEW_SIZE,
+        size => size.clamp(MIN_PREVIEW_SIZE, MAX_PREVIEW_SIZE),
+    }
+}
+
+/// A theme color usable in SVG (#rgb or #rrggbb), else `fallback`
+fn svg_color<'a>(color: &'a str, fallback: &'a str) -> &'a str {
+    if is_valid_hex_color(color) {
+        color
+    } else {
+        fallback
+    }
+}
+
+/// Render the preview of `theme` as an SVG document
+pub fn render_svg(theme: &Theme, size: u32) -> String {
+    let size = preview_size(size);
+    let colors = &theme.colors;
+    let base = svg_color(&colors.base, "#1e1e2e");
+    let surface = svg_color(&colors.surface, "#313244");
+    let text = svg_color(&colors.text, "#cdd6f4");
+    let accent = svg_color(&colors.accent, "#b4befe");
+    let accent_secondary = svg_color(&colors.accent_secondary, "#89b4fa");
+    let border = svg_color(&colors.border, "#45475a");
+    let opacity = theme.glassmorphism.background_opacity.clamp(0.5, 1.0);
+
+    let geometry = SliceGeometry::default();
+    let center = size as f32 / 2.0;
+    let outer = center - 1.5;
+    let inner = outer * INNER_RATIO;
+    let stroke = (size as f32 / 96.0).max(0.75);
+    let point = |radius: f32, deg: f32| {
+        let angle = deg.to_radians();
+        (center + radius * angle.sin(), center - radius * angle.cos())
+    };
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}">"#
+    );
+    let _ = write!(
+        svg,
+        r#"<circle cx="{center}" cy="{center}" r="{outer:.2}" fill="{base}" fill-opacity="{opacity:.2}"/>"#
+    );
+    let sector = geometry.sector_deg();
+    for index in 0..geometry.slice_count {
+        let mid = geometry.slice_center_deg(index);
+        let (start, end) = (mid - sector / 2.0, mid + sector / 2.0);
+        let (ox1, oy1) = point(outer, start);
+        let (ox2, oy2) = point(outer, end);
+        let (ix2, iy2) = point(inner, end);
+        let (ix1, iy1) = point(inner, start);
+        let fill = if index == 0 { accent } else { surface };
+        let _ = write!(
+            svg,
+            r#"<path d="M{ox1:.2} {oy1:.2}A{outer:.2} {outer:.2} 0 0 1 {ox2:.2} {oy2:.2}L{ix2:.2} {iy2:.2}A{inner:.2} {inner:.2} 0 0 0 {ix1:.2} {iy1:.2}Z" fill="{fill}" stroke="{border}" stroke-width="{stroke:.2}"/>"#
+        );
+        let (dx, dy) = point((inner + outer) / 2.0, mid);
+        let _ = write!(
+            svg,
+            r#"<circle cx="{dx:.2}" cy="{dy:.2}" r="{:.2}" fill="{text}"/>"#,
+            outer * 0.07
+        );
+    }
+    let _ = write!(
+        svg,
+        r#"<circle cx="{center}" cy="{center}" r="{inner:.2}" fill="{surface}" stroke="{accent_secondary}" stroke-width="{stroke:.2}"/></svg>"#
+    );
+    svg
+}
+
+/// Cache file for a theme preview (None without a cache directory)
+pub fn preview_path(theme: &Theme, size: u32) -> Option<PathBuf> {
+    let size = preview_size(size);
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(theme).unwrap_or_default().hash(&mut hasher);
+    size.hash(&mut hasher);
+
+    let name: String = theme
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let file = format!("{}-{}-{:016x}.svg", name, size, hasher.finish());
+    dirs::cache_dir().map(|dir| dir.join(CACHE_DIR).join(file))
+}
+
+/// Path of the theme's preview, rendering it into the cache if needed
+pub fn cached_preview(theme: &Theme, size: u32) -> Result<PathBuf, ThemeError> {
+    let path = preview_path(theme, size).ok_or_else(|| {
+        ThemeError::IoError(std::io::Error::new(std::io::ErrorKind::NotFound, "no cache directory"))
+    })?;
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(ThemeError::IoError)?;
+        }
+        std::fs::write(&path, render_svg(theme, size)).map_err(ThemeError::IoError)?;
+        tracing::debug!(theme = %theme.name, path = %path.display(), "Rendered theme preview");
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_svg_uses_palette() {
+        let mut theme = Theme::catppuccin_mocha();
+        theme.colors.accent = "#ff0080".to_string();
+        theme.colors.surface = "\"/><script>".to_string();
+
+        let svg = render_svg(&theme, 0);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="96" height="96""#));
+        assert!(svg.ends_with("</svg>"));
+        assert_eq!(svg.matches("<path").count(), 8);
+        assert_eq!(svg.matches(r##"fill="#ff0080""##).count(), 1);
+        assert!(!svg.contains("script"));
+        assert!(svg.contains(r##"fill="#313244""##));
+    }
+
+    #[test]
+    fn test_preview_path_tracks_theme_content() {
+        let mut theme = Theme::catppuccin_mocha();
+        let first = preview_path(&theme, 64);
+        assert_eq!(first, preview_path(&theme, 64));
+        theme.colors.accent = "#000000".to_string();
+        assert_ne!(first, preview_path(&theme, 64));
+        assert_eq!(preview_size(4096), MAX_PREVIEW_SIZE);
+    }
+}
@@ -2,19 +2,41 @@
 //!
 //! Watches theme directories for changes using inotify and triggers hot-reload.
 //! Changes are detected within 100ms and debounced to avoid rapid reloads.
+//!
+//! Besides edits, themes appearing or disappearing are tracked: a theme
+//! directory moved into or out of a themes directory, or its `theme.json`
+//! created or deleted. When that changes the set of available themes the
+//! daemon emits `ThemeListChanged` on the Settings API so pickers refresh.
+//!
+//! Editors often save by writing a temporary file and renaming it over
+//! `theme.json` (or renaming the old file away first). Renames count as a
+//! delete of the old name and a create of the new one, and events for the
+//! same file within the debounce window collapse into the last one, so such
+//! a save reads as a single reload rather than a removal.
 
+use notify::event::{ModifyKind, RenameMode};
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use crate::theme::{get_system_themes_dir, get_user_themes_dir, Theme, ThemeManager};
+use crate::settings_dbus::{SETTINGS_INTERFACE, SETTINGS_PATH};
+use crate::theme::{
+    get_system_themes_dir, get_user_data_themes_dir, get_user_themes_dir, scan_themes_directory, Theme,
+    ThemeManager,
+};
 
 /// Debounce window to avoid multiple reloads on rapid saves
 const DEBOUNCE_MS: u64 = 50;
 
+/// How often the daemon task polls for debounced events
+const POLL_INTERVAL_MS: u64 = 100;
+
+/// Theme file name inside a theme directory
+const THEME_FILENAME: &str = "theme.json";
+
 /// Theme change event
 #[derive(Debug, Clone)]
 pub enum ThemeEvent {
@@ -28,14 +50,26 @@ pub enum ThemeEvent {
     Error(String),
 }
 
+impl ThemeEvent {
+    /// The theme.json file the event is about
+    fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Modified(path) | Self::Created(path) | Self::Deleted(path) => Some(path),
+            Self::Error(_) => None,
+        }
+    }
+}
+
 /// Theme file watcher using inotify
 pub struct ThemeWatcher {
     /// The underlying notify watcher
     _watcher: RecommendedWatcher,
     /// Channel receiver for events
     event_rx: Receiver<Result<Event, notify::Error>>,
-    /// Debounce state: paths that were recently modified
-    pending_changes: Arc<Mutex<HashSet<PathBuf>>>,
+    /// Watched themes directories, lowest precedence first
+    roots: Vec<PathBuf>,
+    /// Debounce state: events held back until the files settle
+    pending_events: Arc<Mutex<Vec<ThemeEvent>>>,
     /// Last event time for debouncing
     last_event_time: Arc<Mutex<Instant>>,
 }
@@ -43,6 +77,9 @@ pub struct ThemeWatcher {
 impl ThemeWatcher {
     /// Create a new theme watcher that monitors system and user theme directories.
     ///
+    /// The user directories are created if missing, so themes dropped into
+    /// them later are seen.
+    ///
     /// # Returns
     /// * `Ok(ThemeWatcher)` - Watcher is running
     /// * `Err` - Failed to initialize watcher
@@ -54,6 +91,7 @@ impl ThemeWatcher {
 
         let mut watcher = RecommendedWatcher::new(tx, config)
             .map_err(|e| ThemeWatcherError::InitError(e.to_string()))?;
+        let mut roots = Vec::new();
 
         // Watch system themes directory
         let system_dir = get_system_themes_dir();
@@ -62,39 +100,55 @@ impl ThemeWatcher {
                 .watch(&system_dir, RecursiveMode::Recursive)
                 .map_err(|e| ThemeWatcherError::WatchError(system_dir.clone(), e.to_string()))?;
             tracing::info!(path = %system_dir.display(), "Watching system themes directory");
+            roots.push(system_dir);
         }
 
-        // Watch user themes directory
-        let user_dir = get_user_themes_dir();
-        if user_dir.exists() {
+        // Watch user data and user themes directories
+        for user_dir in [get_user_data_themes_dir(), get_user_themes_dir()] {
+            if let Err(e) = std::fs::create_dir_all(&user_dir) {
+                tracing::debug!(path = %user_dir.display(), error = %e, "User themes directory does not exist yet");
+                continue;
+            }
             watcher
                 .watch(&user_dir, RecursiveMode::Recursive)
                 .map_err(|e| ThemeWatcherError::WatchError(user_dir.clone(), e.to_string()))?;
             tracing::info!(path = %user_dir.display(), "Watching user themes directory");
-        } else {
-            tracing::debug!(path = %user_dir.display(), "User themes directory does not exist yet");
+            roots.push(user_dir);
         }
 
         Ok(Self {
             _watcher: watcher,
             event_rx: rx,
-            pending_changes: Arc::new(Mutex::new(HashSet::new())),
+            roots,
+            pending_events: Arc::new(Mutex::new(Vec::new())),
             last_event_time: Arc::new(Mutex::new(Instant::now())),
         })
     }
 
+    /// Watched themes directories, lowest precedence first
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
     /// Check for pending theme events (non-blocking).
     ///
     /// Returns events that have been debounced and are ready to process.
+    /// Events arriving within the debounce window are held back, not
+    /// dropped, and returned by a later call.
     pub fn poll_events(&self) -> Vec<ThemeEvent> {
         let mut events = Vec::new();
+        let mut pending = self.pending_events.lock().unwrap();
 
         // Collect all pending notify events
         while let Ok(result) = self.event_rx.try_recv() {
             match result {
                 Ok(event) => {
-                    if let Some(theme_event) = self.process_notify_event(event) {
-                        events.push(theme_event);
+                    let theme_events = classify(&event, &self.roots);
+                    if !theme_events.is_empty() {
+                        *self.last_event_time.lock().unwrap() = Instant::now();
+                    }
+                    for theme_event in theme_events {
+                        coalesce(&mut pending, theme_event);
                     }
                 }
                 Err(e) => {
@@ -104,68 +158,92 @@ impl ThemeWatcher {
         }
 
         // Apply debouncing
-        self.debounce_events(&mut events);
-
-        events
-    }
-
-    /// Process a raw notify event into a theme event.
-    fn process_notify_event(&self, event: Event) -> Option<ThemeEvent> {
-        // Only process events for theme.json files
-        let theme_json_paths: Vec<PathBuf> = event
-            .paths
-            .into_iter()
-            .filter(|p| p.file_name().map(|n| n == "theme.json").unwrap_or(false))
-            .collect();
-
-        if theme_json_paths.is_empty() {
-            return None;
+        if self.last_event_time.lock().unwrap().elapsed() >= Duration::from_millis(DEBOUNCE_MS) {
+            events.append(&mut pending);
         }
 
-        let path = theme_json_paths.into_iter().next()?;
-
-        // Update debounce tracking
-        {
-            let mut pending = self.pending_changes.lock().unwrap();
-            pending.insert(path.clone());
-            *self.last_event_time.lock().unwrap() = Instant::now();
-        }
-
-        match event.kind {
-            EventKind::Create(_) => Some(ThemeEvent::Created(path)),
-            EventKind::Modify(_) => Some(ThemeEvent::Modified(path)),
-            EventKind::Remove(_) => Some(ThemeEvent::Deleted(path)),
-            _ => None,
-        }
-    }
-
-    /// Apply debouncing to events.
-    fn debounce_events(&self, events: &mut Vec<ThemeEvent>) {
-        let last_event = *self.last_event_time.lock().unwrap();
-        let elapsed = last_event.elapsed();
-
-        // If we're within the debounce window, clear events
-        if elapsed < Duration::from_millis(DEBOUNCE_MS) {
-            events.clear();
-        } else {
-            // Clear the pending set since we're processing
-            let mut pending = self.pending_changes.lock().unwrap();
-            pending.clear();
-        }
+        events
     }
 
     /// Blocking wait for the next theme event.
     ///
-    /// Waits up to the specified timeout for an event.
+    /// Waits up to the specified timeout for an event. If one notification
+    /// stands for several theme events the rest are left for
+    /// [`poll_events`](Self::poll_events).
     pub fn wait_for_event(&self, timeout: Duration) -> Option<ThemeEvent> {
         match self.event_rx.recv_timeout(timeout) {
-            Ok(Ok(event)) => self.process_notify_event(event),
+            Ok(Ok(event)) => {
+                let mut theme_events = classify(&event, &self.roots).into_iter();
+                let first = theme_events.next();
+                let mut pending = self.pending_events.lock().unwrap();
+                for theme_event in theme_events {
+                    coalesce(&mut pending, theme_event);
+                }
+                first
+            }
             Ok(Err(e)) => Some(ThemeEvent::Error(e.to_string())),
             Err(_) => None, // Timeout
         }
     }
 }
 
+/// theme.json a changed path stands for, if any
+///
+/// `whole_dir` events (create, delete, rename) on a directory directly in a
+/// themes root stand for that theme's theme.json: the directory was moved
+/// in or out, or created before its theme.json was written.
+fn theme_file(path: &Path, roots: &[PathBuf], whole_dir: bool) -> Option<PathBuf> {
+    let name = path.file_name()?;
+    if name == THEME_FILENAME {
+        return Some(path.to_path_buf());
+    }
+    let in_root = path.parent().is_some_and(|parent| roots.iter().any(|root| root == parent));
+    if whole_dir && in_root && !name.to_string_lossy().starts_with('.') {
+        return Some(path.join(THEME_FILENAME));
+    }
+    None
+}
+
+/// Turn a raw notify event into theme events
+///
+/// Renames are split into a delete of the old path and a create of the new
+/// one, so a temporary file renamed over theme.json reads as a create.
+fn classify(event: &Event, roots: &[PathBuf]) -> Vec<ThemeEvent> {
+    let whole_dir = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+    );
+    event
+        .paths
+        .iter()
+        .enumerate()
+        .filter_map(|(index, path)| {
+            let file = theme_file(path, roots, whole_dir)?;
+            Some(match event.kind {
+                EventKind::Create(_) => ThemeEvent::Created(file),
+                EventKind::Remove(_) => ThemeEvent::Deleted(file),
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)) => ThemeEvent::Deleted(file),
+                EventKind::Modify(ModifyKind::Name(RenameMode::To)) => ThemeEvent::Created(file),
+                // Paths are [from, to]
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if index == 0 => ThemeEvent::Deleted(file),
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => ThemeEvent::Created(file),
+                EventKind::Modify(ModifyKind::Name(_)) if file.exists() => ThemeEvent::Created(file),
+                EventKind::Modify(ModifyKind::Name(_)) => ThemeEvent::Deleted(file),
+                EventKind::Modify(_) => ThemeEvent::Modified(file),
+                _ => return None,
+            })
+        })
+        .collect()
+}
+
+/// Queue an event, replacing any earlier one for the same file
+fn coalesce(pending: &mut Vec<ThemeEvent>, event: ThemeEvent) {
+    if let Some(path) = event.path() {
+        pending.retain(|queued| queued.path() != Some(path));
+    }
+    pending.push(event);
+}
+
 /// Error types for theme watcher
 #[derive(Debug)]
 pub enum ThemeWatcherError {
@@ -188,28 +266,57 @@ impl std::fmt::Display for ThemeWatcherError {
 
 impl std::error::Error for ThemeWatcherError {}
 
+/// Result of processing theme events
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThemeChanges {
+    /// Themes that were (re)loaded
+    pub reloaded: Vec<String>,
+    /// Whether the set of available theme names changed
+    pub list_changed: bool,
+}
+
 /// Hot-reload handler for theme manager
 pub struct ThemeHotReloader {
     /// Theme manager to reload into
     manager: Arc<Mutex<ThemeManager>>,
     /// Theme watcher
     watcher: ThemeWatcher,
+    /// Theme name loaded from each watched theme.json
+    sources: Mutex<HashMap<PathBuf, String>>,
 }
 
 impl ThemeHotReloader {
     /// Create a new hot-reloader for the given theme manager.
     pub fn new(manager: Arc<Mutex<ThemeManager>>) -> Result<Self, ThemeWatcherError> {
         let watcher = ThemeWatcher::new()?;
-        Ok(Self { manager, watcher })
+        let mut sources = HashMap::new();
+        for root in watcher.roots() {
+            for path in scan_themes_directory(root) {
+                if let Ok(theme) = Theme::load_from_path(&path) {
+                    sources.insert(path, theme.name);
+                }
+            }
+        }
+        Ok(Self {
+            manager,
+            watcher,
+            sources: Mutex::new(sources),
+        })
     }
 
     /// Process pending theme events and apply changes.
     ///
-    /// Returns the list of themes that were reloaded.
-    pub fn process_events(&self) -> Vec<String> {
-        let mut reloaded = Vec::new();
+    /// Returns the themes that were reloaded and whether themes were added
+    /// or removed.
+    pub fn process_events(&self) -> ThemeChanges {
+        let events = self.watcher.poll_events();
+        if events.is_empty() {
+            return ThemeChanges::default();
+        }
 
-        for event in self.watcher.poll_events() {
+        let before = self.theme_names();
+        let mut reloaded = Vec::new();
+        for event in events {
             match event {
                 ThemeEvent::Modified(path) | ThemeEvent::Created(path) => {
                     if let Some(theme_name) = self.reload_theme(&path) {
@@ -218,8 +325,10 @@ impl ThemeHotReloader {
                 }
                 ThemeEvent::Deleted(path) => {
                     tracing::info!(path = %path.display(), "Theme file deleted");
-                    // Optionally remove the theme from manager
-                    // For now, we keep it since it might be bundled
+                    let name = self.sources.lock().unwrap().remove(&path);
+                    if let Some(name) = name {
+                        self.release_theme(&name);
+                    }
                 }
                 ThemeEvent::Error(msg) => {
                     tracing::error!(error = %msg, "Theme watcher error");
@@ -227,13 +336,57 @@ impl ThemeHotReloader {
             }
         }
 
-        reloaded
+        let list_changed = self.theme_names() != before;
+        ThemeChanges { reloaded, list_changed }
+    }
+
+    /// Sorted names of all themes in the manager
+    fn theme_names(&self) -> Vec<String> {
+        let manager = self.manager.lock().unwrap();
+        let mut names: Vec<String> = manager.theme_names().into_iter().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// A theme file named `name` went away: fall back to another watched
+    /// file with that name, then the bundled theme, else drop it
+    fn release_theme(&self, name: &str) {
+        let other = {
+            let sources = self.sources.lock().unwrap();
+            self.watcher
+                .roots()
+                .iter()
+                .rev()
+                .flat_map(|root| sources.iter().filter(move |(path, _)| path.starts_with(root)))
+                .find(|(_, other)| other.as_str() == name)
+                .map(|(path, _)| path.clone())
+        };
+        if let Some(path) = other {
+            if self.reload_theme(&path).is_some() {
+                return;
+            }
+        }
+
+        let mut manager = self.manager.lock().unwrap();
+        match crate::bundled_themes::get_bundled_theme(name) {
+            Some(theme) => manager.add_or_update_theme(theme),
+            None => {
+                if manager.remove_theme(name).is_some() {
+                    tracing::info!(theme = %name, "Theme removed");
+                }
+            }
+        }
     }
 
     /// Reload a single theme from file.
     ///
     /// Returns the theme name if successful.
     fn reload_theme(&self, path: &Path) -> Option<String> {
+        // A theme directory shows up before its theme.json is written
+        if !path.is_file() {
+            tracing::debug!(path = %path.display(), "No theme file yet");
+            return None;
+        }
         tracing::debug!(path = %path.display(), "Attempting to reload theme");
 
         match Theme::load_from_path(path) {
@@ -263,8 +416,13 @@ impl ThemeHotReloader {
                 let theme_name = theme.name.clone();
 
                 // Update the manager
-                let mut manager = self.manager.lock().unwrap();
-                manager.add_or_update_theme(theme);
+                self.manager.lock().unwrap().add_or_update_theme(theme);
+
+                // The file may have been renamed to a different theme
+                let previous = self.sources.lock().unwrap().insert(path.to_path_buf(), theme_name.clone());
+                if let Some(previous) = previous.filter(|previous| *previous != theme_name) {
+                    self.release_theme(&previous);
+                }
 
                 tracing::info!(
                     theme = %theme_name,
@@ -286,6 +444,35 @@ impl ThemeHotReloader {
     }
 }
 
+/// Watch the theme directories and emit `ThemeListChanged` (with the new
+/// list of theme names) on the Settings API when themes come or go
+pub async fn start_theme_watcher(connection: zbus::Connection) {
+    let manager = Arc::new(Mutex::new(ThemeManager::load_all().unwrap_or_default()));
+    let reloader = match ThemeHotReloader::new(manager) {
+        Ok(reloader) => reloader,
+        Err(e) => {
+            tracing::warn!("Theme watcher unavailable: {}", e);
+            return;
+        }
+    };
+
+    let mut interval = tokio::time::interval(Duration::from_millis(POLL_INTERVAL_MS));
+    loop {
+        interval.tick().await;
+        if !reloader.process_events().list_changed {
+            continue;
+        }
+        let names = reloader.theme_names();
+        tracing::info!(themes = names.len(), "Theme list changed");
+        if let Err(e) = connection
+            .emit_signal(None::<&str>, SETTINGS_PATH, SETTINGS_INTERFACE, "ThemeListChanged", &(names,))
+            .await
+        {
+            tracing::warn!("Failed to emit ThemeListChanged: {}", e);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,6 +509,58 @@ mod tests {
         assert_eq!(DEBOUNCE_MS, 50);
     }
 
+    #[test]
+    fn test_rename_replace_save_reads_as_one_create() {
+        let root = PathBuf::from("/themes");
+        let file = root.join("nord/theme.json");
+        let event = |kind, paths: &[&str]| Event {
+            kind,
+            paths: paths.iter().map(PathBuf::from).collect(),
+            attrs: Default::default(),
+        };
+
+        // vim: old file renamed away, new one written; gedit: temp renamed over
+        let mut pending = Vec::new();
+        for raw in [
+            event(EventKind::Modify(ModifyKind::Name(RenameMode::Both)), &["/themes/nord/theme.json", "/themes/nord/theme.json~"]),
+            event(EventKind::Create(notify::event::CreateKind::File), &["/themes/nord/theme.json"]),
+            event(EventKind::Modify(ModifyKind::Name(RenameMode::Both)), &["/themes/nord/.goutputstream-X", "/themes/nord/theme.json"]),
+        ] {
+            for theme_event in classify(&raw, std::slice::from_ref(&root)) {
+                coalesce(&mut pending, theme_event);
+            }
+        }
+        assert_eq!(pending.len(), 1);
+        assert!(matches!(&pending[0], ThemeEvent::Created(path) if *path == file));
+    }
+
+    #[test]
+    fn test_theme_directory_moves() {
+        let roots = [PathBuf::from("/themes")];
+        let moved_in = Event {
+            kind: EventKind::Modify(ModifyKind::Name(RenameMode::To)),
+            paths: vec![PathBuf::from("/themes/nord")],
+            attrs: Default::default(),
+        };
+        let events = classify(&moved_in, &roots);
+        assert!(matches!(&events[..], [ThemeEvent::Created(path)] if path.ends_with("nord/theme.json")));
+
+        let removed = Event {
+            kind: EventKind::Remove(notify::event::RemoveKind::Folder),
+            paths: vec![PathBuf::from("/themes/nord")],
+            attrs: Default::default(),
+        };
+        assert!(matches!(&classify(&removed, &roots)[..], [ThemeEvent::Deleted(_)]));
+
+        // Editor swap files and nested directories are not themes
+        let swap = Event {
+            kind: EventKind::Create(notify::event::CreateKind::File),
+            paths: vec![PathBuf::from("/themes/nord/.theme.json.swp"), PathBuf::from("/themes/nord/icons")],
+            attrs: Default::default(),
+        };
+        assert!(classify(&swap, &roots).is_empty());
+    }
+
     // Integration test for file watching (requires actual filesystem)
     #[test]
     #[ignore] // This test requires actual inotify which may not work in all environments
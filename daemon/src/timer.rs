@@ -0,0 +1,131 @@
+//! Countdown timer for `start_timer` slices (e.g. a pomodoro)
+//!
+//! `{"type": "start_timer", "value": 1500}` starts a countdown of that many
+//! seconds. When it elapses the mouse plays [`COMPLETION_CUES`] and a
+//! desktop notification is shown. There is one timer; starting another
+//! replaces it. The remaining time is published as the widget property
+//! `TimerRemaining` and returned by `GetTimer`; `CancelTimer` stops it.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::hidpp::Mx4HapticPattern;
+use crate::i18n;
+use crate::notifications::{self, Notification};
+
+/// Longest timer accepted (seconds)
+pub const MAX_TIMER_SECS: u64 = 24 * 60 * 60;
+
+/// Haptic sequence played when the timer elapses
+pub const COMPLETION_CUES: &[Mx4HapticPattern] =
+    &[Mx4HapticPattern::Ringing, Mx4HapticPattern::Ringing, Mx4HapticPattern::Jingle];
+
+/// Gap between the completion cues
+const CUE_GAP: Duration = Duration::from_millis(600);
+
+/// The running timer
+struct Running {
+    /// Distinguishes this timer from the ones it replaced
+    id: u64,
+    label: String,
+    deadline: Instant,
+}
+
+static TIMER: Mutex<Option<Running>> = Mutex::new(None);
+
+/// Start (or restart) the timer; returns the clamped duration
+pub fn start(duration: Duration, label: Option<&str>) -> Duration {
+    let duration = duration.clamp(Duration::from_secs(1), Duration::from_secs(MAX_TIMER_SECS));
+    let label = label.map(str::to_string).unwrap_or_else(|| i18n::tr("Timer"));
+    let id = {
+        let Ok(mut timer) = TIMER.lock() else {
+            return duration;
+        };
+        let id = timer.as_ref().map_or(0, |t| t.id + 1);
+        *timer = Some(Running { id, label: label.clone(), deadline: Instant::now() + duration });
+        id
+    };
+    tracing::info!(secs = duration.as_secs(), label = %label, "Timer started");
+
+    tokio::spawn(async move {
+        tokio::time::sleep(duration).await;
+        let finished = TIMER
+            .lock()
+            .ok()
+            .and_then(|mut timer| timer.take_if(|t| t.id == id))
+            .is_some();
+        if finished {
+            complete(&label).await;
+        }
+    });
+    duration
+}
+
+/// Stop the timer without alerting; false if none was running
+pub fn cancel() -> bool {
+    let cancelled = TIMER.lock().ok().and_then(|mut t| t.take()).is_some();
+    if cancelled {
+        tracing::info!("Timer cancelled");
+    }
+    cancelled
+}
+
+/// Remaining time and label of the running timer
+pub fn remaining() -> Option<(Duration, String)> {
+    let timer = TIMER.lock().ok()?;
+    let running = timer.as_ref()?;
+    Some((running.deadline.saturating_duration_since(Instant::now()), running.label.clone()))
+}
+
+/// Whole seconds left, rounded up (0 = no timer)
+pub fn remaining_secs() -> u32 {
+    remaining().map_or(0, |(left, _)| left.as_millis().div_ceil(1000) as u32)
+}
+
+/// Alert that the timer elapsed
+async fn complete(label: &str) {
+    tracing::info!(label, "Timer elapsed");
+    for (i, cue) in COMPLETION_CUES.iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(CUE_GAP).await;
+        }
+        crate::actions::haptic_cue(*cue);
+    }
+
+    let connection = match zbus::Connection::session().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::debug!("Session bus unavailable for timer notification: {}", e);
+            return;
+        }
+    };
+    let notification = Notification {
+        expire_timeout_ms: -1,
+        transient: false,
+        ..Notification::transient(i18n::tr_fmt("{} finished", &[label]), "")
+    };
+    if let Err(e) = notifications::notify(&connection, &notification, 0).await {
+        tracing::debug!("Timer notification failed: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_restart_and_cancel() {
+        assert_eq!(start(Duration::ZERO, None), Duration::from_secs(1));
+        start(Duration::from_secs(90), Some("Tea"));
+        let (left, label) = remaining().unwrap();
+        assert_eq!(label, "Tea");
+        assert!(left > Duration::from_secs(89));
+        assert_eq!(remaining_secs(), 90);
+
+        assert!(cancel());
+        assert!(!cancel());
+        assert_eq!(remaining_secs(), 0);
+    }
+}
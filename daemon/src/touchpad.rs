@@ -0,0 +1,307 @@
+//! Touchpad three-finger-hold trigger
+//!
+//! Opt-in (`touchpad_trigger.enabled`). Resting three fingers on the
+//! touchpad for `hold_ms` opens the radial menu; moving them selects a slice
+//! and lifting them selects, exactly like holding the gesture button. The
+//! touchpad's evdev node is read without grabbing it (libinput keeps
+//! handling the pointer), and a touch that travels more than
+//! `max_travel_mm` before the hold completes is left alone as a swipe.
+//!
+//! Events go into the same [`GestureEvent`] pipeline as the mouse:
+//! `Pressed` at the cursor, `CursorMoved` offsets from where the fingers
+//! rested (scaled by `pixels_per_mm`), `Released` when they lift.
+//!
+//! Not available in portal mode (no /dev/input access).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+
+use crate::config::TouchpadTriggerConfig;
+use crate::evdev::{EvdevError, GestureEvent};
+
+/// Resolution assumed when the touchpad reports none (units per millimeter)
+pub const DEFAULT_UNITS_PER_MM: i32 = 12;
+
+/// How long to wait before looking for a touchpad again
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What a touch frame means for the menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldEvent {
+    /// Hold completed: open the menu
+    Start,
+    /// Fingers moved while the menu is open (offset from the hold position, pixels)
+    Move { dx: i32, dy: i32 },
+    /// Fingers lifted: select
+    End { duration_ms: u64 },
+}
+
+/// Turns touch frames into hold start/move/end
+#[derive(Debug)]
+pub struct HoldDetector {
+    hold: Duration,
+    max_travel: f32,
+    pixels_per_unit: f32,
+    /// Three fingers down since
+    since: Option<Instant>,
+    /// Position when the fingers went down (then when the hold completed)
+    origin: Option<(i32, i32)>,
+    /// Latest position
+    position: Option<(i32, i32)>,
+    /// Moved too far before the hold completed
+    swiped: bool,
+    /// Menu open since
+    active: Option<Instant>,
+    /// Last offset reported
+    last_offset: (i32, i32),
+}
+
+impl HoldDetector {
+    /// Detector for a touchpad with `units_per_mm` resolution
+    pub fn new(config: &TouchpadTriggerConfig, units_per_mm: i32) -> Self {
+        let units_per_mm = if units_per_mm > 0 { units_per_mm } else { DEFAULT_UNITS_PER_MM } as f32;
+        Self {
+            hold: Duration::from_millis(config.hold_ms),
+            max_travel: config.max_travel_mm * units_per_mm,
+            pixels_per_unit: config.pixels_per_mm / units_per_mm,
+            since: None,
+            origin: None,
+            position: None,
+            swiped: false,
+            active: None,
+            last_offset: (0, 0),
+        }
+    }
+
+    /// Feed a complete frame (after SYN_REPORT)
+    pub fn frame(&mut self, three_fingers: bool, position: (i32, i32), now: Instant) -> Option<HoldEvent> {
+        self.position = Some(position);
+        if !three_fingers {
+            self.since = None;
+            self.origin = None;
+            self.swiped = false;
+            let pressed = self.active.take()?;
+            return Some(HoldEvent::End { duration_ms: now.duration_since(pressed).as_millis() as u64 });
+        }
+
+        let Some(origin) = self.origin else {
+            self.since = Some(now);
+            self.origin = Some(position);
+            return None;
+        };
+
+        if self.active.is_some() {
+            let offset = (
+                ((position.0 - origin.0) as f32 * self.pixels_per_unit).round() as i32,
+                ((position.1 - origin.1) as f32 * self.pixels_per_unit).round() as i32,
+            );
+            if offset == std::mem::replace(&mut self.last_offset, offset) {
+                return None;
+            }
+            return Some(HoldEvent::Move { dx: offset.0, dy: offset.1 });
+        }
+
+        let travel = ((position.0 - origin.0) as f32).hypot((position.1 - origin.1) as f32);
+        if travel > self.max_travel {
+            self.swiped = true;
+        }
+        self.tick(now)
+    }
+
+    /// When the pending hold completes, if one is pending
+    pub fn deadline(&self) -> Option<Instant> {
+        match (self.since, self.swiped, self.active) {
+            (Some(since), false, None) => Some(since + self.hold),
+            _ => None,
+        }
+    }
+
+    /// Complete the hold once its time is up
+    pub fn tick(&mut self, now: Instant) -> Option<HoldEvent> {
+        if self.deadline()? > now {
+            return None;
+        }
+        self.active = Some(now);
+        self.origin = self.position;
+        self.last_offset = (0, 0);
+        Some(HoldEvent::Start)
+    }
+}
+
+/// Find a touchpad that reports three-finger touches
+#[cfg(target_os = "linux")]
+pub fn find_touchpad() -> Result<PathBuf, EvdevError> {
+    use evdev::{AbsoluteAxisCode, KeyCode, PropType};
+
+    let entries = std::fs::read_dir("/dev/input").map_err(EvdevError::IoError)?;
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("event")))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .find(|path| {
+            let Ok(device) = evdev::Device::open(path) else {
+                return false;
+            };
+            let triple = device.supported_keys().is_some_and(|k| k.contains(KeyCode::BTN_TOOL_TRIPLETAP));
+            let absolute = device
+                .supported_absolute_axes()
+                .is_some_and(|a| a.contains(AbsoluteAxisCode::ABS_X) && a.contains(AbsoluteAxisCode::ABS_Y));
+            let direct = device.properties().contains(PropType::DIRECT);
+            triple && absolute && !direct
+        })
+        .ok_or(EvdevError::DeviceNotFound)
+}
+
+/// Forward three-finger holds as gesture events
+pub async fn start_touchpad_trigger(event_tx: mpsc::Sender<GestureEvent>, config: TouchpadTriggerConfig) {
+    loop {
+        match run_touchpad(&event_tx, &config).await {
+            Ok(()) => return,
+            Err(EvdevError::DeviceNotFound) => tracing::debug!("No touchpad with three-finger support found"),
+            Err(e) => tracing::warn!("Touchpad trigger error: {}", e),
+        }
+        tokio::time::sleep(RETRY_INTERVAL).await;
+    }
+}
+
+#[cfg(target_os = "linux")]
+async fn run_touchpad(event_tx: &mpsc::Sender<GestureEvent>, config: &TouchpadTriggerConfig) -> Result<(), EvdevError> {
+    use evdev::{AbsoluteAxisCode, EventType, KeyCode, SynchronizationCode};
+
+    let path = find_touchpad()?;
+    let device = evdev::Device::open(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::PermissionDenied => EvdevError::PermissionDenied,
+        _ => EvdevError::IoError(e),
+    })?;
+    let units_per_mm = device
+        .get_absinfo()
+        .ok()
+        .and_then(|mut axes| axes.find(|(axis, _)| *axis == AbsoluteAxisCode::ABS_X))
+        .map(|(_, info)| info.resolution())
+        .unwrap_or(0);
+    tracing::info!(path = %path.display(), name = device.name().unwrap_or("Unknown"), units_per_mm, "Touchpad trigger listening");
+
+    let mut detector = HoldDetector::new(config, units_per_mm);
+    let mut events = device.into_event_stream().map_err(EvdevError::IoError)?;
+    let mut three_fingers = false;
+    let mut position = (0, 0);
+
+    loop {
+        let deadline = detector.deadline();
+        let hold = tokio::select! {
+            result = events.next_event() => {
+                let event = result.map_err(EvdevError::IoError)?;
+                match event.event_type() {
+                    EventType::KEY if event.code() == KeyCode::BTN_TOOL_TRIPLETAP.code() => {
+                        three_fingers = event.value() != 0;
+                        None
+                    }
+                    EventType::ABSOLUTE if event.code() == AbsoluteAxisCode::ABS_X.0 => {
+                        position.0 = event.value();
+                        None
+                    }
+                    EventType::ABSOLUTE if event.code() == AbsoluteAxisCode::ABS_Y.0 => {
+                        position.1 = event.value();
+                        None
+                    }
+                    EventType::SYNCHRONIZATION if event.code() == SynchronizationCode::SYN_REPORT.0 => {
+                        detector.frame(three_fingers, position, Instant::now())
+                    }
+                    _ => None,
+                }
+            }
+            _ = sleep_until(deadline) => detector.tick(Instant::now()),
+        };
+
+        let gesture = match hold {
+            Some(HoldEvent::Start) => {
+                tracing::info!("Three-finger hold on touchpad");
+                // Like the gesture button: the compositor may place the menu itself
+                if crate::compositor::backend().show_overlay_hint() {
+                    continue;
+                }
+                let pos = crate::pointer_confinement::menu_position();
+                GestureEvent::Pressed { x: pos.x, y: pos.y, menu: None }
+            }
+            Some(HoldEvent::Move { dx, dy }) => GestureEvent::CursorMoved { x: dx, y: dy },
+            Some(HoldEvent::End { duration_ms }) => GestureEvent::Released { duration_ms },
+            None => continue,
+        };
+        if event_tx.send(gesture).await.is_err() {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn run_touchpad(_event_tx: &mpsc::Sender<GestureEvent>, _config: &TouchpadTriggerConfig) -> Result<(), EvdevError> {
+    Err(EvdevError::DeviceNotFound)
+}
+
+/// Sleep until `deadline`, or forever without one
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> HoldDetector {
+        // 10 units/mm: 3 mm travel = 30 units, 10 px/mm = 1 px/unit
+        HoldDetector::new(&TouchpadTriggerConfig::default(), 10)
+    }
+
+    #[test]
+    fn test_hold_move_release() {
+        let mut hold = detector();
+        let t0 = Instant::now();
+        assert_eq!(hold.frame(true, (500, 500), t0), None);
+        assert_eq!(hold.frame(true, (505, 500), t0 + Duration::from_millis(100)), None);
+        assert_eq!(hold.deadline(), Some(t0 + Duration::from_millis(300)));
+        assert_eq!(hold.tick(t0 + Duration::from_millis(299)), None);
+        assert_eq!(hold.tick(t0 + Duration::from_millis(300)), Some(HoldEvent::Start));
+        assert_eq!(hold.deadline(), None);
+
+        // Offsets are relative to where the hold completed
+        assert_eq!(hold.frame(true, (525, 490), t0 + Duration::from_millis(400)), Some(HoldEvent::Move { dx: 20, dy: -10 }));
+        assert_eq!(hold.frame(true, (525, 490), t0 + Duration::from_millis(410)), None);
+        assert_eq!(
+            hold.frame(false, (525, 490), t0 + Duration::from_millis(500)),
+            Some(HoldEvent::End { duration_ms: 200 })
+        );
+    }
+
+    #[test]
+    fn test_swipe_is_not_a_hold() {
+        let mut hold = detector();
+        let t0 = Instant::now();
+        hold.frame(true, (500, 500), t0);
+        hold.frame(true, (540, 500), t0 + Duration::from_millis(50));
+        assert_eq!(hold.deadline(), None);
+        assert_eq!(hold.frame(true, (500, 500), t0 + Duration::from_millis(400)), None);
+        assert_eq!(hold.frame(false, (500, 500), t0 + Duration::from_millis(450)), None);
+
+        // Lifting re-arms
+        hold.frame(true, (500, 500), t0 + Duration::from_millis(500));
+        assert_eq!(hold.frame(true, (500, 500), t0 + Duration::from_millis(800)), Some(HoldEvent::Start));
+    }
+
+    #[test]
+    fn test_missing_resolution_uses_default() {
+        let hold = HoldDetector::new(&TouchpadTriggerConfig::default(), 0);
+        assert_eq!(hold.max_travel, 3.0 * DEFAULT_UNITS_PER_MM as f32);
+    }
+}
@@ -0,0 +1,172 @@
+//! Training mode for learning a profile's layout
+//!
+//! While a profile with `training` has had fewer selections than its
+//! target (counted in [`crate::usage_stats`]) its menu helps the user learn:
+//!
+//! - animations run [`ANIMATION_SLOWDOWN`] times slower
+//! - the `GetMenuLayout` / `MenuReady` layout carries
+//!   `"training": {"remaining": n}` and every slice a `description`
+//!   (what it does and, if set, its long-hover alternate)
+//! - resting on a slice for [`GUIDANCE_DELAY`] plays [`GUIDANCE_PATTERN`],
+//!   confirming the slice that releasing would select
+//!
+//! Once the target is reached the profile behaves normally.
+//!
+//! The first selection run from a menu opened in training counts toward the
+//! target: [`record_selection`] is called where the daemon runs it (the
+//! built-in overlay and blind mode) and for selections the overlay reports
+//! through `ExecuteAction`.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::config::AccessibilityConfig;
+use crate::hidpp::Mx4HapticPattern;
+use crate::profiles::Profile;
+use crate::usage_stats::UsageStats;
+
+/// Factor applied to animation durations
+pub const ANIMATION_SLOWDOWN: f32 = 2.0;
+
+/// Pulse played after resting on a slice
+pub const GUIDANCE_PATTERN: Mx4HapticPattern = Mx4HapticPattern::Knock;
+
+/// How long the cursor has to rest on a slice before the guidance pulse
+pub const GUIDANCE_DELAY: Duration = Duration::from_millis(400);
+
+/// Latest hover; a pending guidance pulse for an older one is dropped
+static HOVER: AtomicU64 = AtomicU64::new(0);
+
+/// Profile of the open menu if it is in training, until its selection is counted
+static OPEN_PROFILE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Selections left before `profile` leaves training, None if not training
+pub fn remaining(profile: &Profile, stats: &UsageStats) -> Option<u64> {
+    let target = profile.training?.selections;
+    target.checked_sub(stats.training_selections(&profile.name)).filter(|&n| n > 0)
+}
+
+/// Accessibility settings with training's slower animations
+pub fn slower(accessibility: &AccessibilityConfig) -> AccessibilityConfig {
+    let mut slower = accessibility.clone();
+    slower.animation_scale *= ANIMATION_SLOWDOWN;
+    slower.validate();
+    slower
+}
+
+/// Add `training` and slice descriptions to a layout JSON
+pub fn annotate(json: &mut Value, remaining: u64) {
+    json["training"] = serde_json::json!({ "remaining": remaining });
+    for slice in crate::slice_state::layout_slices_mut(json) {
+        let description = describe(slice);
+        slice["description"] = Value::String(description);
+    }
+}
+
+/// What a serialized slice does, e.g. "shortcut ctrl+c"
+pub fn describe(slice: &Value) -> String {
+    let kind = slice["type"].as_str().unwrap_or("none").replace('_', " ");
+    let mut description = match &slice["value"] {
+        Value::String(value) => format!("{} {}", kind, value),
+        Value::Number(value) => format!("{} {}", kind, value),
+        _ => kind,
+    };
+    if let Some(alternate) = slice["alternate"]["label"].as_str() {
+        description.push_str(" · ");
+        description.push_str(&crate::i18n::tr_fmt("Hold: {}", &[alternate]));
+    }
+    description
+}
+
+/// Note the menu being opened, with its profile if it is in training
+pub fn open(profile: Option<String>) {
+    if let Ok(mut open) = OPEN_PROFILE.lock() {
+        *open = profile;
+    }
+}
+
+/// Whether the open menu is in training
+pub fn is_open() -> bool {
+    OPEN_PROFILE.lock().is_ok_and(|p| p.is_some())
+}
+
+/// Count a selection toward the open menu's training target
+///
+/// Only the first selection after [`open`] counts.
+pub fn record_selection() {
+    let Some(profile) = OPEN_PROFILE.lock().ok().and_then(|mut p| p.take()) else {
+        return;
+    };
+    crate::usage_stats::record_training_selection(&profile);
+}
+
+/// Play the guidance pulse if the cursor is still on this slice after [`GUIDANCE_DELAY`]
+pub fn on_hover() {
+    let hover = HOVER.fetch_add(1, Ordering::Relaxed) + 1;
+    tokio::spawn(async move {
+        tokio::time::sleep(GUIDANCE_DELAY).await;
+        if HOVER.load(Ordering::Relaxed) == hover {
+            crate::actions::haptic_cue(GUIDANCE_PATTERN);
+        }
+    });
+}
+
+/// Drop a pending guidance pulse (menu closed)
+pub fn cancel() {
+    HOVER.fetch_add(1, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::{create_default_profile, ProfileTraining};
+
+    #[test]
+    fn test_remaining_selections() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut stats = UsageStats::load(&dir.path().join("usage-stats.json"));
+        let mut profile = create_default_profile();
+        assert_eq!(remaining(&profile, &stats), None);
+
+        profile.training = Some(ProfileTraining { selections: 2 });
+        assert_eq!(remaining(&profile, &stats), Some(2));
+        stats.record_training_selection("default");
+        assert_eq!(remaining(&profile, &stats), Some(1));
+        stats.record_training_selection("default");
+        assert_eq!(remaining(&profile, &stats), None);
+    }
+
+    #[test]
+    fn test_slower_animations_stay_in_range() {
+        let config = AccessibilityConfig::default();
+        assert_eq!(slower(&config).animation_scale, 2.0);
+
+        let slow = AccessibilityConfig { animation_scale: 3.0, ..config };
+        assert_eq!(slower(&slow).animation_scale, 4.0);
+    }
+
+    #[test]
+    fn test_annotate_descriptions() {
+        let mut layout = serde_json::json!({
+            "slices": [
+                {"type": "shortcut", "value": "ctrl+c", "label": "Copy",
+                 "alternate": {"type": "shortcut", "value": "ctrl+v", "label": "Paste"}},
+                null,
+                {"type": "start_timer", "value": 300},
+                {"type": "pick_color"},
+            ],
+        });
+        annotate(&mut layout, 5);
+
+        assert_eq!(layout["training"]["remaining"], 5);
+        assert_eq!(layout["slices"][0]["description"], "shortcut ctrl+c · Hold: Paste");
+        assert!(layout["slices"][1].is_null());
+        assert_eq!(layout["slices"][2]["description"], "start timer 300");
+        assert_eq!(layout["slices"][3]["description"], "pick color");
+    }
+}
@@ -0,0 +1,272 @@
+//! Per-action usage statistics for JuhRadial MX
+//!
+//! Opt-in (`usage_stats.enabled` in config). Records how often each action
+//! is executed and when it was last used, persisted as JSON under
+//! `$XDG_STATE_HOME/juhradial/usage-stats.json`. Nothing leaves the machine;
+//! the data backs `GetActionStats` and most-used action suggestions.
+//!
+//! Executions are counted by [`ActionExecutor::execute`], keyed by the
+//! action's label (or its kind when it has none), and for the actions the
+//! overlay reports through `ExecuteAction`. Until [`init`] is called (i.e.
+//! outside the daemon) nothing is counted.
+//!
+//! Selections made in profiles with `training` are counted per profile
+//! whether or not statistics are enabled; training ends once the count
+//! reaches the profile's target (see [`crate::training`]).
+//!
+//! [`ActionExecutor::execute`]: crate::actions::ActionExecutor::execute
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::SharedConfig;
+
+/// State subdirectory
+const STATE_DIR: &str = "juhradial";
+
+/// Statistics file name
+const STATS_FILE: &str = "usage-stats.json";
+
+/// Usage of a single action
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionStat {
+    /// Number of executions
+    pub count: u64,
+    /// Last execution (Unix seconds)
+    pub last_used: u64,
+}
+
+/// Usage statistics for all actions
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// Statistics keyed by action ID
+    #[serde(default)]
+    actions: BTreeMap<String, ActionStat>,
+
+    /// Selections made in training mode, keyed by profile name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    training: BTreeMap<String, u64>,
+
+    /// Backing file (not serialized, None = in-memory only)
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+/// Thread-safe usage statistics handle
+pub type SharedUsageStats = Arc<Mutex<UsageStats>>;
+
+impl UsageStats {
+    /// Get the default statistics file path
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::state_dir().map(|p| p.join(STATE_DIR).join(STATS_FILE))
+    }
+
+    /// Load statistics from a file (missing or corrupt files start empty)
+    pub fn load(path: &Path) -> Self {
+        let mut stats: Self = fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| match serde_json::from_str(&contents) {
+                Ok(stats) => Some(stats),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), "Ignoring corrupt usage statistics: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        stats.path = Some(path.to_path_buf());
+        stats
+    }
+
+    /// Load statistics from the default location
+    pub fn load_default() -> Self {
+        match Self::default_path() {
+            Some(path) => Self::load(&path),
+            None => Self::default(),
+        }
+    }
+
+    /// Record an execution of `action_id` and persist
+    pub fn record(&mut self, action_id: &str) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.record_at(action_id, now);
+
+        if let Err(e) = self.save() {
+            tracing::warn!("Failed to save usage statistics: {}", e);
+        }
+    }
+
+    fn record_at(&mut self, action_id: &str, timestamp: u64) {
+        let stat = self.actions.entry(action_id.to_string()).or_default();
+        stat.count += 1;
+        stat.last_used = timestamp;
+    }
+
+    /// Count a selection made in `profile` while in training and persist;
+    /// returns the new count
+    pub fn record_training_selection(&mut self, profile: &str) -> u64 {
+        let count = self.training.entry(profile.to_string()).or_default();
+        *count += 1;
+        let count = *count;
+
+        if let Err(e) = self.save() {
+            tracing::warn!("Failed to save usage statistics: {}", e);
+        }
+        count
+    }
+
+    /// Selections made in `profile` while in training
+    pub fn training_selections(&self, profile: &str) -> u64 {
+        self.training.get(profile).copied().unwrap_or(0)
+    }
+
+    /// Get statistics for one action
+    pub fn get(&self, action_id: &str) -> Option<&ActionStat> {
+        self.actions.get(action_id)
+    }
+
+    /// All statistics, most used first (ties: most recent first)
+    pub fn ranked(&self) -> Vec<(&str, &ActionStat)> {
+        let mut ranked: Vec<_> = self.actions.iter().map(|(id, stat)| (id.as_str(), stat)).collect();
+        ranked.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(b.1.last_used.cmp(&a.1.last_used)));
+        ranked
+    }
+
+    /// The most used action, if any
+    pub fn most_used(&self) -> Option<&str> {
+        self.ranked().first().map(|(id, _)| *id)
+    }
+
+    /// Write statistics to the backing file (see [`crate::state_file`])
+    fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        crate::state_file::write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+}
+
+/// Create a new shared statistics handle from the default location
+pub fn new_shared_usage_stats() -> SharedUsageStats {
+    Arc::new(Mutex::new(UsageStats::load_default()))
+}
+
+/// The daemon's statistics and the config that enables them
+static RECORDER: Mutex<Option<(SharedUsageStats, SharedConfig)>> = Mutex::new(None);
+
+/// Count executions into `stats` from now on, while `config` enables it
+pub fn init(stats: SharedUsageStats, config: SharedConfig) {
+    if let Ok(mut recorder) = RECORDER.lock() {
+        *recorder = Some((stats, config));
+    }
+}
+
+/// Count an execution of `action_id` if statistics are enabled
+pub fn record_execution(action_id: &str) {
+    let Some((stats, config)) = RECORDER.lock().ok().and_then(|r| r.clone()) else {
+        return;
+    };
+    record_if_enabled(&stats, &config, action_id);
+}
+
+/// Count a selection made in `profile` while in training
+pub fn record_training_selection(profile: &str) {
+    let Some((stats, _)) = RECORDER.lock().ok().and_then(|r| r.clone()) else {
+        return;
+    };
+    let Ok(mut stats) = stats.lock() else {
+        return;
+    };
+    let count = stats.record_training_selection(profile);
+    tracing::debug!(profile = %profile, count, "Training selection recorded");
+}
+
+fn record_if_enabled(stats: &SharedUsageStats, config: &SharedConfig, action_id: &str) {
+    let enabled = config.read().map(|c| c.usage_stats.enabled).unwrap_or(false);
+    if enabled {
+        if let Ok(mut stats) = stats.lock() {
+            stats.record(action_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_rank() {
+        let mut stats = UsageStats::default();
+        stats.record_at("copy", 100);
+        stats.record_at("paste", 200);
+        stats.record_at("copy", 300);
+        stats.record_at("undo", 400);
+
+        assert_eq!(stats.get("copy"), Some(&ActionStat { count: 2, last_used: 300 }));
+        let ranked: Vec<_> = stats.ranked().into_iter().map(|(id, _)| id).collect();
+        // paste and undo tie on count; undo is more recent
+        assert_eq!(ranked, vec!["copy", "undo", "paste"]);
+        assert_eq!(stats.most_used(), Some("copy"));
+    }
+
+    #[test]
+    fn test_recorded_only_when_enabled() {
+        let stats = SharedUsageStats::default();
+        let config = crate::config::new_shared_config();
+
+        record_if_enabled(&stats, &config, "Copy");
+        assert!(stats.lock().unwrap().get("Copy").is_none());
+
+        config.write().unwrap().usage_stats.enabled = true;
+        record_if_enabled(&stats, &config, "Copy");
+        assert_eq!(stats.lock().unwrap().get("Copy").map(|s| s.count), Some(1));
+    }
+
+    #[test]
+    fn test_persistence_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join(STATS_FILE);
+
+        let mut stats = UsageStats::load(&path);
+        assert!(stats.most_used().is_none());
+        stats.record("screenshot");
+        stats.record("screenshot");
+
+        let reloaded = UsageStats::load(&path);
+        assert_eq!(reloaded.get("screenshot").map(|s| s.count), Some(2));
+    }
+
+    #[test]
+    fn test_training_selections() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STATS_FILE);
+
+        let mut stats = UsageStats::load(&path);
+        assert_eq!(stats.record_training_selection("blender"), 1);
+        assert_eq!(stats.record_training_selection("blender"), 2);
+
+        let reloaded = UsageStats::load(&path);
+        assert_eq!(reloaded.training_selections("blender"), 2);
+        assert_eq!(reloaded.training_selections("default"), 0);
+        assert!(reloaded.ranked().is_empty());
+    }
+
+    #[test]
+    fn test_corrupt_file_starts_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STATS_FILE);
+        fs::write(&path, "not json").unwrap();
+
+        let stats = UsageStats::load(&path);
+        assert!(stats.ranked().is_empty());
+    }
+}
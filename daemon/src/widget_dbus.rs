@@ -0,0 +1,277 @@
+//! Widget D-Bus API for JuhRadial MX
+//!
+//! Implements the org.kde.juhradialmx.Widget interface: a read-only set of
+//! properties for the Plasma applet (and other panel widgets). Every
+//! property emits `org.freedesktop.DBus.Properties.PropertiesChanged` when
+//! its value changes, so widgets can bind to them directly instead of
+//! polling methods on the daemon interface.
+//!
+//! Values come from a snapshot the daemon refreshes every
+//! [`WIDGET_REFRESH_INTERVAL_SECS`]; reading a property never touches the
+//! device.
+//!
+//! ## Interface: org.kde.juhradialmx.Widget
+//!
+//! ### Properties:
+//! - `BatteryLevel: y` - Battery percentage (0-100)
+//! - `BatteryCharging: b` - Whether the mouse is charging
+//! - `BatteryAvailable: b` - Whether battery information is available
+//! - `Dpi: q` - Current DPI (0 if unknown)
+//! - `ActiveProfile: s` - Name of the active radial menu profile
+//! - `ConnectionType: s` - "USB", "Bolt", "Bluetooth", "Unifying" or "" when disconnected
+//! - `TimerRemaining: u` - Seconds left on the `start_timer` countdown (0 = none)
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use zbus::interface;
+
+use crate::battery::SharedBatteryState;
+use crate::hidpp::SharedHapticManager;
+use crate::profiles::SharedProfileManager;
+
+/// Widget D-Bus interface name
+pub const WIDGET_INTERFACE: &str = "org.kde.juhradialmx.Widget";
+
+/// Widget D-Bus object path
+pub const WIDGET_PATH: &str = "/org/kde/juhradialmx/Widget";
+
+/// How often the widget snapshot is refreshed (seconds)
+pub const WIDGET_REFRESH_INTERVAL_SECS: u64 = 1;
+
+/// A widget property, as named on D-Bus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetProperty {
+    BatteryLevel,
+    BatteryCharging,
+    BatteryAvailable,
+    Dpi,
+    ActiveProfile,
+    ConnectionType,
+    TimerRemaining,
+}
+
+/// Snapshot of everything the widget shows
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WidgetState {
+    /// Battery percentage (0-100)
+    pub battery_level: u8,
+    /// Whether the mouse is charging
+    pub battery_charging: bool,
+    /// Whether battery information is available
+    pub battery_available: bool,
+    /// Current DPI (0 if unknown)
+    pub dpi: u16,
+    /// Active profile name
+    pub active_profile: String,
+    /// Connection type ("" when disconnected)
+    pub connection_type: String,
+    /// Seconds left on the countdown timer (0 = none)
+    pub timer_remaining: u32,
+}
+
+impl WidgetState {
+    /// Properties whose value differs between `self` and `other`
+    pub fn changes(&self, other: &WidgetState) -> Vec<WidgetProperty> {
+        let mut changed = Vec::new();
+        if self.battery_level != other.battery_level {
+            changed.push(WidgetProperty::BatteryLevel);
+        }
+        if self.battery_charging != other.battery_charging {
+            changed.push(WidgetProperty::BatteryCharging);
+        }
+        if self.battery_available != other.battery_available {
+            changed.push(WidgetProperty::BatteryAvailable);
+        }
+        if self.dpi != other.dpi {
+            changed.push(WidgetProperty::Dpi);
+        }
+        if self.active_profile != other.active_profile {
+            changed.push(WidgetProperty::ActiveProfile);
+        }
+        if self.connection_type != other.connection_type {
+            changed.push(WidgetProperty::ConnectionType);
+        }
+        if self.timer_remaining != other.timer_remaining {
+            changed.push(WidgetProperty::TimerRemaining);
+        }
+        changed
+    }
+}
+
+/// Widget D-Bus service
+#[derive(Debug, Default)]
+pub struct WidgetService {
+    /// Last published snapshot
+    state: WidgetState,
+}
+
+impl WidgetService {
+    /// Create a widget service with an empty snapshot
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[interface(name = "org.kde.juhradialmx.Widget")]
+impl WidgetService {
+    /// Battery percentage (0-100)
+    #[zbus(property)]
+    async fn battery_level(&self) -> u8 {
+        self.state.battery_level
+    }
+
+    /// Whether the mouse is charging
+    #[zbus(property)]
+    async fn battery_charging(&self) -> bool {
+        self.state.battery_charging
+    }
+
+    /// Whether battery information is available
+    #[zbus(property)]
+    async fn battery_available(&self) -> bool {
+        self.state.battery_available
+    }
+
+    /// Current DPI (0 if unknown)
+    #[zbus(property)]
+    async fn dpi(&self) -> u16 {
+        self.state.dpi
+    }
+
+    /// Active radial menu profile
+    #[zbus(property)]
+    async fn active_profile(&self) -> &str {
+        &self.state.active_profile
+    }
+
+    /// How the mouse is connected ("" when disconnected)
+    #[zbus(property)]
+    async fn connection_type(&self) -> &str {
+        &self.state.connection_type
+    }
+
+    /// Seconds left on the countdown timer (0 = none)
+    #[zbus(property)]
+    async fn timer_remaining(&self) -> u32 {
+        self.state.timer_remaining
+    }
+}
+
+/// Build a snapshot from the daemon's shared state
+///
+/// The DPI is taken from the haptic manager's cache; it is only read from
+/// the device when a (new) connection shows up.
+async fn collect_state(
+    battery: &SharedBatteryState,
+    haptics: &SharedHapticManager,
+    profiles: &SharedProfileManager,
+    previous: &WidgetState,
+) -> WidgetState {
+    let mut state = WidgetState::default();
+
+    {
+        let battery = battery.read().await;
+        state.battery_level = battery.percentage;
+        state.battery_charging = battery.charging;
+        state.battery_available = battery.available;
+    }
+
+    if let Ok(mut manager) = haptics.lock() {
+        state.connection_type = manager.connection_type().map(|c| c.to_string()).unwrap_or_default();
+        let reconnected = !state.connection_type.is_empty() && state.connection_type != previous.connection_type;
+        let dpi = if reconnected { manager.get_dpi() } else { manager.cached_dpi() };
+        state.dpi = dpi.unwrap_or(0);
+    }
+
+    if let Ok(profiles) = profiles.read() {
+        state.active_profile = profiles.current().name.clone();
+    }
+
+    state.timer_remaining = crate::timer::remaining_secs();
+
+    state
+}
+
+/// Keep the widget snapshot current and emit PropertiesChanged for changed values
+pub async fn start_widget_publisher(
+    connection: zbus::Connection,
+    battery: SharedBatteryState,
+    haptics: SharedHapticManager,
+    profiles: SharedProfileManager,
+) {
+    let iface_ref = match connection
+        .object_server()
+        .interface::<_, WidgetService>(WIDGET_PATH)
+        .await
+    {
+        Ok(iface) => iface,
+        Err(e) => {
+            tracing::warn!("Widget interface not registered: {}", e);
+            return;
+        }
+    };
+
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(WIDGET_REFRESH_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        let previous = iface_ref.get().await.state.clone();
+        let state = collect_state(&battery, &haptics, &profiles, &previous).await;
+
+        let mut iface = iface_ref.get_mut().await;
+        let changed = iface.state.changes(&state);
+        if changed.is_empty() {
+            continue;
+        }
+        iface.state = state;
+
+        let emitter = iface_ref.signal_emitter();
+        for property in changed {
+            let result = match property {
+                WidgetProperty::BatteryLevel => iface.battery_level_changed(emitter).await,
+                WidgetProperty::BatteryCharging => iface.battery_charging_changed(emitter).await,
+                WidgetProperty::BatteryAvailable => iface.battery_available_changed(emitter).await,
+                WidgetProperty::Dpi => iface.dpi_changed(emitter).await,
+                WidgetProperty::ActiveProfile => iface.active_profile_changed(emitter).await,
+                WidgetProperty::ConnectionType => iface.connection_type_changed(emitter).await,
+                WidgetProperty::TimerRemaining => iface.timer_remaining_changed(emitter).await,
+            };
+            if let Err(e) = result {
+                tracing::debug!(?property, "Failed to emit widget property change: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_widget_constants() {
+        assert_eq!(WIDGET_INTERFACE, "org.kde.juhradialmx.Widget");
+        assert_eq!(WIDGET_PATH, "/org/kde/juhradialmx/Widget");
+    }
+
+    #[test]
+    fn test_changes_lists_only_differing_properties() {
+        let before = WidgetState {
+            battery_level: 80,
+            active_profile: "default".to_string(),
+            ..WidgetState::default()
+        };
+        assert!(before.changes(&before.clone()).is_empty());
+
+        let after = WidgetState {
+            battery_level: 79,
+            active_profile: "blender".to_string(),
+            connection_type: "Bolt".to_string(),
+            ..before.clone()
+        };
+        assert_eq!(
+            before.changes(&after),
+            vec![WidgetProperty::BatteryLevel, WidgetProperty::ActiveProfile, WidgetProperty::ConnectionType]
+        );
+    }
+}
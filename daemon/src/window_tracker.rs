@@ -3,11 +3,15 @@
 //! Story 3.2: Detect Focused Window via KWin/Plasma APIs
 //!
 //! Monitors active window changes on KDE Plasma to enable
-//! per-application profile switching.
+//! per-application profile switching. Without KWin, the focused window
+//! comes from the session's [`CompositorBackend`] (Hyprland, Sway, X11).
 
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use zbus::{proxy, Connection, Result as ZbusResult};
+use zbus::{proxy, zvariant::OwnedValue, Connection, Result as ZbusResult};
+
+use crate::compositor::CompositorBackend;
 
 /// KWin D-Bus service name (for future KWin integration)
 #[allow(dead_code)]
@@ -21,8 +25,14 @@ const KWIN_PATH: &str = "/KWin";
 #[allow(dead_code)]
 const KWIN_SCRIPTING_PATH: &str = "/Scripting";
 
+/// KRunner keyword that makes KWin's windows runner list every open window
+const LIST_ALL_WINDOWS_QUERY: &str = "window";
+
+/// Number of window classes kept in the focus history
+const MAX_RECENT_WINDOWS: usize = 10;
+
 /// Window tracker state
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct WindowInfo {
     /// Window resource class (e.g., "firefox", "konsole")
     pub resource_class: String,
@@ -30,13 +40,15 @@ pub struct WindowInfo {
     pub caption: Option<String>,
 }
 
-impl Default for WindowInfo {
-    fn default() -> Self {
-        Self {
-            resource_class: String::new(),
-            caption: None,
-        }
-    }
+/// An open window as reported by KWin's windows runner
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenWindow {
+    /// Runner match ID (used to activate the window)
+    pub id: String,
+    /// Window title
+    pub title: String,
+    /// Icon name
+    pub icon: String,
 }
 
 /// Tracks the currently focused window via KWin D-Bus
@@ -49,6 +61,10 @@ pub struct WindowTracker {
     active_window: Arc<RwLock<WindowInfo>>,
     /// Whether KWin is available
     kwin_available: bool,
+    /// Focus history (window classes, most recent first, no duplicates)
+    recent_windows: Arc<RwLock<VecDeque<String>>>,
+    /// Compositor backend that reports the focused window when KWin is not available
+    fallback: Option<&'static dyn CompositorBackend>,
 }
 
 impl WindowTracker {
@@ -71,8 +87,13 @@ impl WindowTracker {
             false
         };
 
+        let backend = crate::compositor::backend();
+        let fallback = (!kwin_available && backend.tracks_active_window()).then_some(backend);
+
         if kwin_available {
             tracing::info!("WindowTracker connected to KWin D-Bus");
+        } else if fallback.is_some() {
+            tracing::info!(backend = backend.name(), "WindowTracker using compositor backend");
         } else {
             tracing::warn!("KWin not available - window tracking disabled");
         }
@@ -81,6 +102,8 @@ impl WindowTracker {
             connection,
             active_window: Arc::new(RwLock::new(WindowInfo::default())),
             kwin_available,
+            recent_windows: Arc::default(),
+            fallback,
         }
     }
 
@@ -101,7 +124,7 @@ impl WindowTracker {
     /// Returns the cached value or queries KWin if cache is stale.
     /// Performance: This should complete in <5ms (NFR-004).
     pub async fn get_active_window_class(&self) -> Option<String> {
-        if !self.kwin_available {
+        if !self.is_available() {
             return None;
         }
 
@@ -117,8 +140,12 @@ impl WindowTracker {
 
     /// Refresh the active window info from KWin
     ///
-    /// Queries KWin D-Bus for the currently focused window's resource class.
+    /// Queries KWin D-Bus (or the compositor backend) for the currently
+    /// focused window's resource class.
     pub async fn refresh_active_window(&self) -> Option<String> {
+        if let Some(backend) = self.fallback {
+            return self.refresh_from_backend(backend).await;
+        }
         let connection = self.connection.as_ref()?;
 
         let start = std::time::Instant::now();
@@ -175,10 +202,42 @@ impl WindowTracker {
         // Update cache
         let mut info = self.active_window.write().await;
         info.resource_class = resource_class.clone();
+        drop(info);
+        self.push_recent(&resource_class).await;
 
         Some(resource_class)
     }
 
+    /// Refresh the active window from a compositor backend (blocking IPC)
+    async fn refresh_from_backend(&self, backend: &'static dyn CompositorBackend) -> Option<String> {
+        let class = tokio::task::spawn_blocking(move || backend.active_window())
+            .await
+            .ok()
+            .flatten();
+
+        let mut info = self.active_window.write().await;
+        info.resource_class = class.clone().unwrap_or_default();
+        info.caption = None;
+        drop(info);
+        if let Some(class) = &class {
+            self.push_recent(class).await;
+        }
+        class
+    }
+
+    /// Move a window class to the front of the focus history
+    async fn push_recent(&self, resource_class: &str) {
+        let mut recent = self.recent_windows.write().await;
+        recent.retain(|class| class != resource_class);
+        recent.push_front(resource_class.to_string());
+        recent.truncate(MAX_RECENT_WINDOWS);
+    }
+
+    /// Get recently focused window classes, most recent first
+    pub async fn recent_windows(&self) -> Vec<String> {
+        self.recent_windows.read().await.iter().cloned().collect()
+    }
+
     /// Query a window's resource class by client ID
     async fn query_window_resource_class(
         &self,
@@ -224,7 +283,32 @@ impl WindowTracker {
 
     /// Check if window tracking is available
     pub fn is_available(&self) -> bool {
-        self.kwin_available
+        self.kwin_available || self.fallback.is_some()
+    }
+
+    /// List open windows via KWin's windows runner
+    ///
+    /// Returns an empty list when KWin is not available.
+    pub async fn list_windows(&self) -> Vec<OpenWindow> {
+        let Some(connection) = self.connection.as_ref().filter(|_| self.kwin_available) else {
+            return Vec::new();
+        };
+
+        let proxy = match WindowsRunnerProxy::new(connection).await {
+            Ok(p) => p,
+            Err(e) => {
+                tracing::debug!("Failed to create KWin windows runner proxy: {}", e);
+                return Vec::new();
+            }
+        };
+
+        match proxy.match_query(LIST_ALL_WINDOWS_QUERY).await {
+            Ok(matches) => matches.into_iter().map(open_window_from_match).collect(),
+            Err(e) => {
+                tracing::debug!("KWin windows runner query failed: {}", e);
+                Vec::new()
+            }
+        }
     }
 
     /// Clear the cached window info
@@ -242,10 +326,24 @@ impl Default for WindowTracker {
             connection: None,
             active_window: Arc::new(RwLock::new(WindowInfo::default())),
             kwin_available: false,
+            recent_windows: Arc::default(),
+            fallback: None,
         }
     }
 }
 
+/// Activate a window listed by [`WindowTracker::list_windows`]
+pub async fn focus_window(connection: &Connection, window_id: &str) -> ZbusResult<()> {
+    WindowsRunnerProxy::new(connection).await?.run(window_id, "").await
+}
+
+/// KRunner match: (id, text, icon, type, relevance, properties)
+type RemoteMatch = (String, String, String, i32, f64, HashMap<String, OwnedValue>);
+
+fn open_window_from_match((id, title, icon, ..): RemoteMatch) -> OpenWindow {
+    OpenWindow { id, title, icon }
+}
+
 /// KWin D-Bus proxy for window management
 #[proxy(
     interface = "org.kde.KWin",
@@ -258,6 +356,21 @@ trait KWin {
     fn active_client_id(&self) -> ZbusResult<String>;
 }
 
+/// KWin windows runner (KRunner D-Bus API)
+#[proxy(
+    interface = "org.kde.krunner1",
+    default_service = "org.kde.KWin",
+    default_path = "/WindowsRunner"
+)]
+trait WindowsRunner {
+    /// Query matching windows
+    #[zbus(name = "Match")]
+    fn match_query(&self, query: &str) -> ZbusResult<Vec<RemoteMatch>>;
+
+    /// Run a match (activates the window)
+    fn run(&self, match_id: &str, action_id: &str) -> ZbusResult<()>;
+}
+
 /// KWin Scripting D-Bus proxy
 #[proxy(
     interface = "org.kde.KWin.Scripting",
@@ -330,6 +443,41 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[tokio::test]
+    async fn test_list_windows_without_kwin() {
+        let tracker = WindowTracker::default();
+        assert!(tracker.list_windows().await.is_empty());
+    }
+
+    #[test]
+    fn test_open_window_from_match() {
+        let window = open_window_from_match((
+            "0_{abc}".to_string(),
+            "Konsole".to_string(),
+            "utilities-terminal".to_string(),
+            100,
+            1.0,
+            HashMap::new(),
+        ));
+        assert_eq!(window.id, "0_{abc}");
+        assert_eq!(window.title, "Konsole");
+        assert_eq!(window.icon, "utilities-terminal");
+    }
+
+    #[tokio::test]
+    async fn test_recent_windows_history() {
+        let tracker = WindowTracker::default();
+        for class in ["konsole", "firefox", "konsole"] {
+            tracker.push_recent(class).await;
+        }
+        assert_eq!(tracker.recent_windows().await, vec!["konsole", "firefox"]);
+
+        for i in 0..20 {
+            tracker.push_recent(&format!("app{}", i)).await;
+        }
+        assert_eq!(tracker.recent_windows().await.len(), MAX_RECENT_WINDOWS);
+    }
+
     #[tokio::test]
     async fn test_clear_cache() {
         let tracker = WindowTracker::default();
@@ -0,0 +1,245 @@
+//! Monitor topology via xdg-output (feature `xdg-output`)
+//!
+//! Binds `zxdg_output_manager_v1` on a short-lived Wayland connection to
+//! learn every monitor's connector name, logical position, logical size and
+//! scale straight from the compositor, without shelling out to xrandr or
+//! hyprctl. Logical coordinates are the ones the cursor and the overlay use,
+//! so they feed edge clamping directly ([`screen_bounds`]) and let callers
+//! find the monitor under the cursor ([`monitor_at`]).
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use wayland_client::{
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::{wl_output, wl_registry},
+    Connection, Dispatch, QueueHandle,
+};
+use wayland_protocols::xdg::xdg_output::zv1::client::{
+    zxdg_output_manager_v1::ZxdgOutputManagerV1,
+    zxdg_output_v1::{self, ZxdgOutputV1},
+};
+
+use crate::compositor::bounding_box;
+use crate::cursor::ScreenBounds;
+
+/// A monitor in the compositor's logical coordinate space
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Monitor {
+    /// Connector name (e.g. "DP-1")
+    pub name: String,
+    /// Human-readable description (make, model, connector)
+    pub description: String,
+    /// Logical X position
+    pub x: i32,
+    /// Logical Y position
+    pub y: i32,
+    /// Logical width (mode width / scale, after rotation)
+    pub width: i32,
+    /// Logical height
+    pub height: i32,
+    /// Integer buffer scale reported by wl_output
+    pub scale: i32,
+}
+
+impl Monitor {
+    /// Whether a logical point lies on this monitor
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// Bounding box of all monitors (for edge clamping)
+pub fn screen_bounds(monitors: &[Monitor]) -> Option<ScreenBounds> {
+    bounding_box(monitors.iter().map(|m| (m.x, m.y, m.width, m.height)))
+}
+
+/// The monitor under a logical point
+pub fn monitor_at(monitors: &[Monitor], x: i32, y: i32) -> Option<&Monitor> {
+    monitors.iter().find(|m| m.contains(x, y))
+}
+
+/// xdg-output query error
+#[derive(Debug)]
+pub enum XdgOutputError {
+    /// No Wayland display to connect to
+    Connect(String),
+    /// The compositor does not offer zxdg_output_manager_v1
+    Unsupported,
+    /// Protocol error while querying
+    Protocol(String),
+}
+
+impl std::fmt::Display for XdgOutputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XdgOutputError::Connect(e) => write!(f, "Wayland connection failed: {}", e),
+            XdgOutputError::Unsupported => write!(f, "Compositor does not support xdg-output"),
+            XdgOutputError::Protocol(e) => write!(f, "Wayland protocol error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for XdgOutputError {}
+
+/// Query all monitors from the compositor
+///
+/// Blocking (two Wayland roundtrips); call from a blocking context.
+pub fn query_monitors() -> Result<Vec<Monitor>, XdgOutputError> {
+    let connection =
+        Connection::connect_to_env().map_err(|e| XdgOutputError::Connect(e.to_string()))?;
+    let (globals, mut queue) = registry_queue_init::<QueryState>(&connection)
+        .map_err(|e| XdgOutputError::Protocol(e.to_string()))?;
+    let qh = queue.handle();
+
+    let manager: ZxdgOutputManagerV1 = globals
+        .bind(&qh, 1..=3, ())
+        .map_err(|_| XdgOutputError::Unsupported)?;
+
+    let mut state = QueryState::default();
+    let outputs: Vec<_> = globals.contents().with_list(|list| {
+        list.iter()
+            .filter(|global| global.interface == "wl_output")
+            .map(|global| (global.name, global.version.min(4)))
+            .collect()
+    });
+    for (index, (name, version)) in outputs.into_iter().enumerate() {
+        let output: wl_output::WlOutput = globals.registry().bind(name, version, &qh, index);
+        manager.get_xdg_output(&output, &qh, index);
+        state.monitors.push(Monitor {
+            scale: 1,
+            ..Monitor::default()
+        });
+    }
+
+    // First roundtrip delivers the bind events, the second the xdg-output state
+    for _ in 0..2 {
+        queue
+            .roundtrip(&mut state)
+            .map_err(|e| XdgOutputError::Protocol(e.to_string()))?;
+    }
+
+    Ok(state
+        .monitors
+        .into_iter()
+        .filter(|m| m.width > 0 && m.height > 0)
+        .collect())
+}
+
+/// Monitors collected while dispatching (indexed by bind order)
+#[derive(Default)]
+struct QueryState {
+    monitors: Vec<Monitor>,
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for QueryState {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // Hotplug during a one-shot query is picked up by the next query
+    }
+}
+
+impl Dispatch<ZxdgOutputManagerV1, ()> for QueryState {
+    fn event(
+        _: &mut Self,
+        _: &ZxdgOutputManagerV1,
+        _: <ZxdgOutputManagerV1 as wayland_client::Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, usize> for QueryState {
+    fn event(
+        state: &mut Self,
+        _: &wl_output::WlOutput,
+        event: wl_output::Event,
+        index: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(monitor) = state.monitors.get_mut(*index) else {
+            return;
+        };
+        match event {
+            wl_output::Event::Scale { factor } => monitor.scale = factor,
+            // wl_output v4 names; xdg-output's name takes precedence
+            wl_output::Event::Name { name } if monitor.name.is_empty() => monitor.name = name,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZxdgOutputV1, usize> for QueryState {
+    fn event(
+        state: &mut Self,
+        _: &ZxdgOutputV1,
+        event: zxdg_output_v1::Event,
+        index: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(monitor) = state.monitors.get_mut(*index) else {
+            return;
+        };
+        match event {
+            zxdg_output_v1::Event::LogicalPosition { x, y } => {
+                monitor.x = x;
+                monitor.y = y;
+            }
+            zxdg_output_v1::Event::LogicalSize { width, height } => {
+                monitor.width = width;
+                monitor.height = height;
+            }
+            zxdg_output_v1::Event::Name { name } => monitor.name = name,
+            zxdg_output_v1::Event::Description { description } => monitor.description = description,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, x: i32, y: i32, width: i32, height: i32) -> Monitor {
+        Monitor {
+            name: name.to_string(),
+            x,
+            y,
+            width,
+            height,
+            scale: 1,
+            ..Monitor::default()
+        }
+    }
+
+    #[test]
+    fn test_bounds_and_monitor_at() {
+        let monitors = vec![
+            monitor("eDP-1", 0, 360, 1280, 800),
+            monitor("DP-1", 1280, 0, 2560, 1440),
+        ];
+
+        let bounds = screen_bounds(&monitors).unwrap();
+        assert_eq!((bounds.width, bounds.height), (3840, 1440));
+
+        assert_eq!(
+            monitor_at(&monitors, 100, 500).map(|m| m.name.as_str()),
+            Some("eDP-1")
+        );
+        assert_eq!(
+            monitor_at(&monitors, 1280, 0).map(|m| m.name.as_str()),
+            Some("DP-1")
+        );
+        // Above the laptop panel: no monitor
+        assert!(monitor_at(&monitors, 100, 100).is_none());
+    }
+}
@@ -478,13 +478,30 @@ install_files() {
 
     # Install daemon binary
     sudo install -Dm755 daemon/target/release/juhradiald "$BIN_DIR/juhradiald"
+    sudo install -Dm755 daemon/target/release/juhradialctl "$BIN_DIR/juhradialctl"
     log_success "Daemon binary"
 
+    # Install polkit action for `juhradialctl setup` (exec.path must match BIN_DIR)
+    if [ -f packaging/polkit/org.kde.juhradialmx.policy ]; then
+        sed "s|/usr/bin/juhradialctl|$BIN_DIR/juhradialctl|" packaging/polkit/org.kde.juhradialmx.policy \
+            | sudo install -Dm644 /dev/stdin /usr/share/polkit-1/actions/org.kde.juhradialmx.policy
+    fi
+
+    # Install D-Bus introspection XML (generated from the daemon's interfaces)
+    INTROSPECTION_DIR=$(mktemp -d)
+    if daemon/target/release/juhradiald --dump-introspection "$INTROSPECTION_DIR" >/dev/null; then
+        sudo install -Dm644 -t /usr/share/dbus-1/interfaces "$INTROSPECTION_DIR"/*.xml
+    fi
+    rm -rf "$INTROSPECTION_DIR"
+
     # Install overlay scripts
     sudo mkdir -p /usr/share/juhradial
     sudo cp -r overlay/*.py /usr/share/juhradial/
     log_success "Overlay scripts"
 
+    # D-Bus activation of the overlay (overlay.restart_on_crash)
+    sudo install -Dm644 -t /usr/share/dbus-1/services packaging/dbus/org.kde.juhradialmx.Overlay.service
+
     # Install locale files
     if [ -d overlay/locales ]; then
         sudo mkdir -p /usr/share/juhradial/locales
@@ -19,7 +19,8 @@ os.environ["QT_QPA_PLATFORM"] = "xcb"
 import math
 import shlex
 import subprocess
-from PyQt6.QtWidgets import QApplication, QWidget, QSystemTrayIcon, QMenu
+import time
+from PyQt6.QtWidgets import QApplication, QWidget, QSystemTrayIcon, QMenu, QInputDialog
 from PyQt6.QtCore import (
     Qt,
     pyqtSlot,
@@ -28,6 +29,7 @@ from PyQt6.QtCore import (
     QPointF,
     QRectF,
     QTimer,
+    QMetaType,
 )
 from PyQt6.QtGui import QCursor
 from PyQt6.QtGui import (
@@ -42,7 +44,11 @@ from PyQt6.QtGui import (
     QPixmap,
 )
 from PyQt6.QtSvg import QSvgRenderer
-from PyQt6.QtDBus import QDBusConnection, QDBusInterface
+from PyQt6.QtDBus import QDBusArgument, QDBusConnection, QDBusInterface
+
+# Bus name the daemon re-activates us by when overlay.restart_on_crash is set
+# (packaging/dbus/org.kde.juhradialmx.Overlay.service)
+OVERLAY_SERVICE_NAME = "org.kde.juhradialmx.Overlay"
 
 # =============================================================================
 # GEOMETRY
@@ -519,6 +525,24 @@ class RadialMenu(QWidget):
             "",
             self.on_hide,
         )
+        # Tap passed through to applications - close without tap-to-toggle
+        bus.connect(
+            "org.kde.juhradialmx",
+            "/org/kde/juhradialmx/Daemon",
+            "org.kde.juhradialmx.Daemon",
+            "MenuCancelled",
+            "",
+            self.on_cancelled,
+        )
+        # Text entry slice - ask for the query and hand it back to the daemon
+        bus.connect(
+            "org.kde.juhradialmx",
+            "/org/kde/juhradialmx/Daemon",
+            "org.kde.juhradialmx.Daemon",
+            "TextEntryRequested",
+            "s",
+            self.on_text_entry_requested,
+        )
         bus.connect(
             "org.kde.juhradialmx",
             "/org/kde/juhradialmx/Daemon",
@@ -539,6 +563,14 @@ class RadialMenu(QWidget):
             f"[DBUS] D-Bus interface created - isValid: {self.daemon_iface.isValid()}"
         )
 
+        # Overlay heartbeat: lets the daemon detect a crashed overlay and
+        # reset menu state (RegisterOverlay/Heartbeat)
+        self.heartbeat_timer = QTimer(self)
+        self.heartbeat_timer.timeout.connect(self._send_heartbeat)
+        self._register_overlay()
+        # Last CursorMoved acknowledgement (throttled, they arrive at ~60 Hz)
+        self.last_cursor_ack = 0.0
+
         # Fade animation
         self.anim = QPropertyAnimation(self, b"windowOpacity")
         self.anim.setDuration(180)
@@ -588,6 +620,7 @@ class RadialMenu(QWidget):
         # Reload actions, theme, and translations from config each time menu is shown
         # This ensures changes from settings are picked up immediately
         ACTIONS = load_actions_from_config()
+        self._fill_emoji_submenus()
 
         global COLORS, RADIAL_IMAGE, RADIAL_PARAMS
         COLORS = load_theme()
@@ -667,11 +700,38 @@ class RadialMenu(QWidget):
 
         # Trigger haptic feedback for menu appearance
         self._trigger_haptic("menu_appear")
+        self._acknowledge("MenuRequested")
 
     def _get_center_radius(self):
         params = RADIAL_PARAMS or {}
         return params.get("center_radius", params.get("ring_inner", CENTER_ZONE_RADIUS))
 
+    def _fill_emoji_submenus(self):
+        """Turn emoji slices into submenus of the daemon's configured characters.
+
+        Without the daemon the slice keeps opening plasma-emojier.
+        """
+        import json
+
+        if not self.daemon_iface.isValid():
+            return
+        for i, action in enumerate(ACTIONS):
+            if action[1] != "emoji":
+                continue
+            reply = self.daemon_iface.call("GetSubmenu", "emoji")
+            if reply.type() == reply.MessageType.ErrorMessage:
+                return
+            try:
+                items = json.loads(reply.arguments()[0])
+            except (IndexError, TypeError, ValueError):
+                return
+            submenu = [
+                (item.get("label", ""), "insert_text", str(index), "emoji_char")
+                for index, item in enumerate(items)
+            ]
+            if submenu:
+                ACTIONS[i] = (action[0], "submenu", "", action[3], action[4], submenu)
+
     def _trigger_haptic(self, event):
         """Trigger haptic feedback via D-Bus call to daemon.
 
@@ -694,6 +754,32 @@ class RadialMenu(QWidget):
                 f"[HAPTIC] ERROR: daemon_iface is INVALID - cannot send haptic signal"
             )
 
+    def _register_overlay(self):
+        """Register with the daemon and start sending heartbeats."""
+        interval_ms = 1000
+        if self.daemon_iface.isValid():
+            # Only claim re-activation if we own the name (not a second instance)
+            owned = QDBusConnection.sessionBus().registerService(OVERLAY_SERVICE_NAME)
+            service_name = OVERLAY_SERVICE_NAME if owned else ""
+            reply = self.daemon_iface.call("RegisterOverlay", service_name)
+            if reply.type() == reply.MessageType.ErrorMessage:
+                print(
+                    f"[DBUS] RegisterOverlay failed: {reply.errorName()} - {reply.errorMessage()}"
+                )
+            elif reply.arguments():
+                interval_ms = int(reply.arguments()[0])
+        self.heartbeat_timer.start(interval_ms)
+
+    def _send_heartbeat(self):
+        """Keep-alive for the daemon's overlay monitor."""
+        if self.daemon_iface.isValid():
+            self.daemon_iface.asyncCall("Heartbeat")
+
+    def _acknowledge(self, signal):
+        """Tell the daemon a menu signal was handled (checked by juhradialctl selftest)."""
+        if self.daemon_iface.isValid():
+            self.daemon_iface.asyncCall("AcknowledgeSignal", signal)
+
     @pyqtSlot()
     def on_hide(self):
         """Handle HideMenu signal - determine tap vs hold based on time elapsed."""
@@ -706,6 +792,7 @@ class RadialMenu(QWidget):
             duration_ms = 1000  # Default to hold mode if no time recorded
 
         print(f"OVERLAY: HideMenu received (duration={duration_ms:.0f}ms)")
+        self._acknowledge("HideMenu")
 
         if duration_ms < self.TAP_THRESHOLD_MS:
             # Quick tap - enter toggle mode
@@ -718,10 +805,33 @@ class RadialMenu(QWidget):
             # Normal hold-and-release - close and execute
             self._close_menu(execute=True)
 
+    @pyqtSlot()
+    def on_cancelled(self):
+        """Handle MenuCancelled signal - close without executing anything."""
+        print("OVERLAY: MenuCancelled received - tap passed through")
+        if self.isVisible():
+            self._close_menu(execute=False)
+
+    @pyqtSlot(str)
+    def on_text_entry_requested(self, prompt):
+        """Handle TextEntryRequested signal - prompt for text and submit it."""
+        text, ok = QInputDialog.getText(None, "JuhRadial MX", prompt)
+        if not self.daemon_iface.isValid():
+            return
+        if ok and text:
+            self.daemon_iface.call("SubmitTextEntry", text)
+        else:
+            self.daemon_iface.call("CancelTextEntry")
+
     @pyqtSlot(int, int)
     def on_cursor_moved(self, dx, dy):
         """Handle cursor movement from daemon (relative to menu center)."""
         # dx, dy are relative offsets from menu center (button press point)
+        now = time.monotonic()
+        if now - self.last_cursor_ack >= 0.25:
+            self.last_cursor_ack = now
+            self._acknowledge("CursorMoved")
+
         distance = math.hypot(dx, dy)
         center_radius = self._get_center_radius()
 
@@ -779,6 +889,11 @@ class RadialMenu(QWidget):
         self.highlighted_subitem = -1
         self.hide()
 
+    def _report_action(self, label):
+        """Tell the daemon a slice action ran, for its usage statistics."""
+        if self.daemon_iface.isValid():
+            self.daemon_iface.call("ExecuteAction", label)
+
     def _execute_action(self, action):
         label, cmd_type, cmd = action[0], action[1], action[2]
         print(f"Executing: {label}")
@@ -793,10 +908,12 @@ class RadialMenu(QWidget):
                     )
                 except ValueError as e:
                     print(f"Invalid command syntax: {cmd} - {e}")
+                    return
             elif cmd_type == "url":
                 # Ensure cmd doesn't start with - to prevent option injection
                 if cmd.startswith("-"):
                     print(f"Invalid URL (starts with -): {cmd}")
+                    return
                 else:
                     subprocess.Popen(
                         ["xdg-open", cmd],
@@ -812,6 +929,10 @@ class RadialMenu(QWidget):
             elif cmd_type == "settings":
                 # Launch settings (uses singleton check defined at module level)
                 open_settings()
+            elif cmd_type == "undo":
+                # The daemon keeps the history of reversible actions
+                if self.daemon_iface.isValid():
+                    self.daemon_iface.call("UndoLastAction")
             elif cmd_type == "submenu":
                 # Submenu - activate it instead of executing
                 self.submenu_active = True
@@ -819,6 +940,9 @@ class RadialMenu(QWidget):
                 self.highlighted_subitem = -1
                 self.update()
                 return  # Don't close menu
+            else:
+                return
+            self._report_action(label)
         except Exception as e:
             print(f"Error executing action: {e}")
 
@@ -847,6 +971,11 @@ class RadialMenu(QWidget):
                         stdout=subprocess.DEVNULL,
                         stderr=subprocess.DEVNULL,
                     )
+            elif cmd_type == "insert_text":
+                # Emoji submenu item - the daemon copies/pastes it
+                if self.daemon_iface.isValid():
+                    index = QDBusArgument(int(cmd), QMetaType.Type.UInt.value)
+                    self.daemon_iface.call("RunSubmenuItem", "emoji", index)
             elif cmd_type == "easy_switch":
                 # Switch to host via D-Bus call to daemon
                 # Validate host_index (Easy-Switch supports 0-2 for 3 hosts)
@@ -1689,7 +1818,18 @@ class RadialMenu(QWidget):
 
             # Icon - use SVG if available, fallback to drawn icon
             icon_name = item[3]  # e.g., "claude", "chatgpt", etc.
-            if icon_name in AI_ICONS:
+            if item[1] == "insert_text":
+                # Emoji / character - draw the text itself
+                font = p.font()
+                font.setPointSizeF(SUBITEM_RADIUS * 0.7)
+                p.setFont(font)
+                p.setPen(COLORS["text"])
+                p.drawText(
+                    QRectF(item_x - SUBITEM_RADIUS, item_y - SUBITEM_RADIUS, SUBITEM_RADIUS * 2, SUBITEM_RADIUS * 2),
+                    Qt.AlignmentFlag.AlignCenter,
+                    item[0],
+                )
+            elif icon_name in AI_ICONS:
                 # Render SVG icon
                 icon_size = SUBITEM_RADIUS * 1.4  # Size of icon
                 icon_rect = QRectF(
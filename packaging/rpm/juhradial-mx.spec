@@ -71,6 +71,9 @@ install -Dm755 juhradial-mx.sh %{buildroot}%{_bindir}/juhradial-mx
 install -dm755 %{buildroot}%{_datadir}/juhradial
 install -Dm644 overlay/*.py %{buildroot}%{_datadir}/juhradial/
 
+# Install overlay D-Bus activation file
+install -Dm644 packaging/dbus/org.kde.juhradialmx.Overlay.service %{buildroot}%{_datadir}/dbus-1/services/org.kde.juhradialmx.Overlay.service
+
 # Install locales
 if [ -d overlay/locales ]; then
     cp -r overlay/locales %{buildroot}%{_datadir}/juhradial/
@@ -113,6 +116,7 @@ install -Dm644 packaging/logid.cfg %{buildroot}%{_sysconfdir}/logid.cfg.juhradia
 %{_bindir}/juhradiald
 %{_bindir}/juhradial-mx
 %{_datadir}/juhradial/
+%{_datadir}/dbus-1/services/org.kde.juhradialmx.Overlay.service
 %{_datadir}/applications/juhradial-mx.desktop
 %{_datadir}/icons/hicolor/scalable/apps/juhradial-mx.svg
 %{_userunitdir}/juhradialmx-daemon.service